@@ -0,0 +1,26 @@
+// Performance baseline: `cargo bench`, or `cargo bench -- 1000/` for the smallest graphs only.
+// `trust-flow --bench` prints the same benchmarks as a plain throughput table.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use trust_flow::bench::{BenchFrame, BENCH_SIZES};
+use trust_flow::random::DEFAULT_SEED;
+
+fn frame_benchmarks(c: &mut Criterion) {
+    for size in BENCH_SIZES {
+        let frame = BenchFrame::new(size, DEFAULT_SEED);
+        let mut group = c.benchmark_group(size.to_string());
+        group.throughput(Throughput::Elements(frame.num_of_edges() as u64));
+        if size >= 100_000 {
+            group.sample_size(10);
+        }
+        group.bench_function(BenchmarkId::from_parameter("pagerank_variant"), |b| b.iter(|| frame.rank()));
+        group.bench_function(BenchmarkId::from_parameter("compile_graph"), |b| b.iter(|| frame.compile()));
+        group.bench_function(BenchmarkId::from_parameter("edge_weights"), |b| b.iter(|| frame.decay_weights()));
+        let mut dot = Vec::new();
+        group.bench_function(BenchmarkId::from_parameter("write_dot"), |b| b.iter(|| frame.write_dot(&mut dot)));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, frame_benchmarks);
+criterion_main!(benches);
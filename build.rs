@@ -0,0 +1,14 @@
+// With the grpc feature, compiles proto/trust_flow.proto into the service and client of
+// src/grpc.rs. protoc comes with the protoc-bin-vendored crate, so none has to be installed.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("no bundled protoc for this platform");
+        let mut config = tonic_prost_build::Config::new();
+        config.protoc_executable(protoc);
+        tonic_prost_build::configure()
+            .compile_with_config(config, &["proto/trust_flow.proto"], &["proto"])
+            .expect("cannot compile proto/trust_flow.proto");
+    }
+}
@@ -0,0 +1,66 @@
+/* C interface of trust-flow; src/ffi.rs describes every function and the contract of its pointers. */
+
+#ifndef TRUST_FLOW_H
+#define TRUST_FLOW_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stddef.h>
+
+typedef enum TfStatus {
+  TF_STATUS_OK = 0,
+  TF_STATUS_NULL_POINTER,
+  // Out-of-range node, bad weight, unknown algorithm, ...
+  TF_STATUS_INVALID_ARGUMENT,
+  // Scores asked for before the first tf_graph_rank
+  TF_STATUS_NOT_RANKED,
+  TF_STATUS_PANIC,
+} TfStatus;
+
+// A temporal trust graph with the ranks of its last tf_graph_rank call; opaque to C
+typedef struct TfGraph TfGraph;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// A graph of the given number of nodes without edges or experts, with the default decay constant;
+// free it with tf_graph_free
+struct TfGraph *tf_graph_new(size_t num_of_nodes);
+
+void tf_graph_free(struct TfGraph *graph);
+
+size_t tf_graph_num_of_nodes(const struct TfGraph *graph);
+
+// Makes the node an expert, where teleported rank goes
+enum TfStatus tf_graph_add_expert(struct TfGraph *graph, size_t node);
+
+// Adds trust from source to target, created at the given time with the given weight (1 for a
+// single confirmation)
+enum TfStatus tf_graph_add_edge(struct TfGraph *graph,
+                                size_t source,
+                                size_t target,
+                                double time,
+                                double weight);
+
+// Exponential decay constant of edge weights; 0 disables decay
+enum TfStatus tf_graph_set_decay(struct TfGraph *graph, double decay_constant);
+
+// Ranks the graph at the given time with the algorithm of the given id (see `trust-flow
+// algorithms list`), or with the custom variant when it is null. The scores are kept for
+// tf_graph_scores until the next call.
+enum TfStatus tf_graph_rank(struct TfGraph *graph, double time, const char *algorithm);
+
+// Copies the scores of the last tf_graph_rank call, one per node, into `scores`, which has room
+// for `len` of them
+enum TfStatus tf_graph_scores(const struct TfGraph *graph, double *scores, size_t len);
+
+// Description of the last failure on the calling thread, empty when nothing failed yet; valid
+// until the next failing call on the thread
+const char *tf_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TRUST_FLOW_H */
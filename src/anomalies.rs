@@ -0,0 +1,77 @@
+use std::io::{self, Write};
+
+use crate::history::RankHistory;
+use crate::json::Json;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// Smallest standard deviation a change is measured against, so that nodes whose rank never moved
+// before are not flagged for rounding noise
+const MIN_DEVIATION: f64 = 1e-6;
+
+// Flags rank changes that lie more than `threshold` standard deviations from the mean of the node's
+// earlier changes, once the node has `min_history` (at least 2) earlier changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyDetector {
+    pub threshold: f64,
+    pub min_history: usize,
+}
+
+// A rank change between a frame and the one before it that the node's history does not explain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankAnomaly {
+    pub frame: FrameIdx,
+    pub time: Time,
+    pub node: NodeId,
+    pub change: f64,
+    pub mean: f64,      // of the node's earlier changes
+    pub deviation: f64, // standard deviation of the node's earlier changes
+    pub score: f64,     // (change - mean) / deviation
+}
+
+impl AnomalyDetector {
+    // Anomalies in frame order, nodes in index order within a frame
+    pub fn detect(&self, history: &RankHistory) -> Vec<RankAnomaly> {
+        let frames = history.frames();
+        // Running count, mean and sum of squared deviations (Welford) of every node's changes
+        let mut stats = vec![(0usize, 0.0, 0.0); history.num_of_nodes()];
+        let mut anomalies = Vec::new();
+        for frame in 1..frames.len() {
+            for (node, (count, mean, squares)) in stats.iter_mut().enumerate() {
+                let change = frames[frame][node] - frames[frame - 1][node];
+                if *count >= self.min_history {
+                    let deviation = (*squares / (*count - 1) as f64).sqrt().max(MIN_DEVIATION);
+                    let score = (change - *mean) / deviation;
+                    if score.abs() > self.threshold {
+                        anomalies.push(RankAnomaly { frame: FrameIdx(frame), time: history.times()[frame], node: NodeId(node), change, mean: *mean, deviation, score });
+                    }
+                }
+                *count += 1;
+                let delta = change - *mean;
+                *mean += delta / *count as f64;
+                *squares += delta * (change - *mean);
+            }
+        }
+        anomalies
+    }
+}
+
+// The detector's settings and every anomaly
+pub fn write_anomalies_json(out: &mut dyn Write, anomalies: &[RankAnomaly], labels: &[String], detector: &AnomalyDetector) -> io::Result<()> {
+    let anomalies = anomalies
+        .iter()
+        .map(|a| {
+            Json::object(vec![
+                ("frame", a.frame.number().into()),
+                ("time", a.time.value().into()),
+                ("node", a.node.index().into()),
+                ("label", labels[a.node.index()].as_str().into()),
+                ("change", a.change.into()),
+                ("mean", a.mean.into()),
+                ("deviation", a.deviation.into()),
+                ("score", a.score.into()),
+            ])
+        })
+        .collect();
+    let json = Json::object(vec![("threshold", detector.threshold.into()), ("min_history", detector.min_history.into()), ("anomalies", Json::Array(anomalies))]);
+    writeln!(out, "{}", json)
+}
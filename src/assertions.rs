@@ -0,0 +1,246 @@
+use std::fmt;
+
+use crate::batch::{run_scenario, RunConfig};
+use crate::scenario::{parse_number, Scenario, ScenarioError};
+use crate::types::{FrameIdx, NodeId, Time};
+
+// Expected outcomes declared in scenario files and checked after a run:
+//
+//   assert rank 4 > rank 2                 # at the final frame
+//   assert rank 4 >= 0.15 at frame 10      # at a given frame (1-based, as rendered)
+//   assert rank 7 > rank 3 by frame 15     # at some frame up to and including frame 15
+//   assert trusted 7 by frame 15           # rank above the scenario's trusted threshold
+//   assert order 0 > 1 > 3 at time 6       # ranked in this order, at the frame of time 6
+//   assert rank 0 = 0.47 within 0.01       # approximately; within 1e-6 when not given
+//   assert rank 2 > rank 3 by time 4       # at some frame up to and including time 4
+
+// Tolerance of `assert rank <node> = <value>` without `within`
+pub const DEFAULT_APPROX_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    Rank(NodeId),
+    Value(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum When {
+    Final,
+    AtFrame(FrameIdx),
+    ByFrame(FrameIdx),
+    AtTime(Time), // the frame sampled at this time
+    ByTime(Time), // frames sampled up to and including this time
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare(Operand, Comparison, Operand),
+    Trusted(NodeId),
+    Order(Vec<NodeId>), // from the highest rank to the lowest, each strictly above the next
+    Approx { node: NodeId, value: f64, tolerance: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    pub condition: Condition,
+    pub when: When,
+    pub text: String, // as written in the scenario file
+    pub line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssertionOutcome {
+    pub assertion: Assertion,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Rank(node) => write!(f, "rank {}", node),
+            Operand::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+        }
+    }
+}
+
+fn parse_operand<'a>(tokens: &mut impl Iterator<Item = &'a str>, line: usize) -> Result<Operand, ScenarioError> {
+    match tokens.next() {
+        Some("rank") => Ok(Operand::Rank(NodeId(parse_number(tokens.next(), "node", line)?))),
+        token => Ok(Operand::Value(parse_number(token, "operand", line)?)),
+    }
+}
+
+impl Assertion {
+    pub fn parse(text: &str, line: usize) -> Result<Assertion, ScenarioError> {
+        let error = |message: String| ScenarioError { line, message };
+        let mut tokens = text.split_whitespace().peekable();
+        let node = |token: Option<&str>| parse_number(token, "node", line).map(NodeId);
+
+        let condition = match tokens.peek() {
+            Some(&"trusted") => {
+                tokens.next();
+                Condition::Trusted(node(tokens.next())?)
+            }
+            Some(&"order") => {
+                tokens.next();
+                let mut nodes = vec![node(tokens.next())?];
+                while tokens.next_if_eq(&">").is_some() {
+                    nodes.push(node(tokens.next())?);
+                }
+                if nodes.len() < 2 {
+                    return Err(error("expected 'order <node> > <node> [> <node> ...]'".to_string()));
+                }
+                Condition::Order(nodes)
+            }
+            _ => {
+                let left = parse_operand(&mut tokens, line)?;
+                let comparison = match tokens.next() {
+                    Some("=") => None,
+                    Some(">") => Some(Comparison::Greater),
+                    Some(">=") => Some(Comparison::GreaterOrEqual),
+                    Some("<") => Some(Comparison::Less),
+                    Some("<=") => Some(Comparison::LessOrEqual),
+                    other => return Err(error(format!("expected comparison operator, found {:?}", other))),
+                };
+                match (left, comparison) {
+                    (left, Some(comparison)) => Condition::Compare(left, comparison, parse_operand(&mut tokens, line)?),
+                    (Operand::Rank(node), None) => {
+                        let value = parse_number(tokens.next(), "expected rank", line)?;
+                        let tolerance = match tokens.next_if_eq(&"within") {
+                            Some(_) => parse_number(tokens.next(), "tolerance", line)?,
+                            None => DEFAULT_APPROX_TOLERANCE,
+                        };
+                        Condition::Approx { node, value, tolerance }
+                    }
+                    (Operand::Value(_), None) => return Err(error("expected 'rank <node> = <value>'".to_string())),
+                }
+            }
+        };
+
+        let when = match tokens.next() {
+            None => When::Final,
+            Some(qualifier @ ("at" | "by")) => match tokens.next() {
+                Some("frame") => {
+                    let number: usize = parse_number(tokens.next(), "frame number", line)?;
+                    if number == 0 {
+                        return Err(error("frame numbers start at 1".to_string()));
+                    }
+                    let frame = FrameIdx(number - 1);
+                    if qualifier == "at" { When::AtFrame(frame) } else { When::ByFrame(frame) }
+                }
+                Some("time") => {
+                    let time = Time(parse_number(tokens.next(), "time", line)?);
+                    if qualifier == "at" { When::AtTime(time) } else { When::ByTime(time) }
+                }
+                _ => return Err(error(format!("expected 'frame' or 'time' after '{}'", qualifier))),
+            },
+            Some(other) => return Err(error(format!("unexpected token: {}", other))),
+        };
+        if let Some(extra) = tokens.next() {
+            return Err(error(format!("unexpected token: {}", extra)));
+        }
+
+        Ok(Assertion { condition, when, text: text.to_string(), line })
+    }
+
+    // Nodes referenced by the assertion
+    pub fn nodes(&self) -> Vec<NodeId> {
+        match &self.condition {
+            Condition::Trusted(node) | Condition::Approx { node, .. } => vec![*node],
+            Condition::Order(nodes) => nodes.clone(),
+            Condition::Compare(left, _, right) => [left, right]
+                .iter()
+                .filter_map(|operand| match operand {
+                    Operand::Rank(node) => Some(*node),
+                    Operand::Value(_) => None,
+                })
+                .collect(),
+        }
+    }
+
+    fn holds_at(&self, ranks: &[f64], trusted_threshold: f64) -> bool {
+        let value = |operand: &Operand| match operand {
+            Operand::Rank(node) => ranks[node.index()],
+            Operand::Value(value) => *value,
+        };
+        match &self.condition {
+            Condition::Compare(left, comparison, right) => comparison.holds(value(left), value(right)),
+            Condition::Trusted(node) => ranks[node.index()] > trusted_threshold,
+            Condition::Order(nodes) => nodes.windows(2).all(|pair| ranks[pair[0].index()] > ranks[pair[1].index()]),
+            Condition::Approx { node, value, tolerance } => (ranks[node.index()] - value).abs() <= *tolerance,
+        }
+    }
+
+    // The ranks of the nodes the assertion is about, e.g. "rank 4 = 0.1523", to tell how far off
+    // a failed assertion is
+    fn actual(&self, ranks: &[f64]) -> String {
+        self.nodes().iter().map(|node| format!("rank {} = {:.4}", node, ranks[node.index()])).collect::<Vec<_>>().join(", ")
+    }
+
+    // `frames` holds the rank vector of every frame of the run and `times` their times
+    pub fn check(&self, frames: &[Vec<f64>], times: &[Time], trusted_threshold: f64) -> AssertionOutcome {
+        let at = |frame: usize, what: String| {
+            let passed = self.holds_at(&frames[frame], trusted_threshold);
+            let detail = if passed { what } else { format!("{}: {}", what, self.actual(&frames[frame])) };
+            (passed, detail)
+        };
+        // Frames up to and including the given one
+        let by = |frames_by: usize, what: String| match frames[..frames_by].iter().position(|ranks| self.holds_at(ranks, trusted_threshold)) {
+            Some(i) => (true, format!("first holds at frame {}", FrameIdx(i).number())),
+            None => (false, format!("does not hold up to {}", what)),
+        };
+        let (passed, detail) = match self.when {
+            When::Final => match frames.len() {
+                0 => (false, "no frames".to_string()),
+                n => at(n - 1, format!("final frame {}", n)),
+            },
+            When::AtFrame(frame) if frame.index() < frames.len() => at(frame.index(), format!("frame {}", frame.number())),
+            When::AtFrame(frame) => (false, format!("frame {} not rendered ({} frames)", frame.number(), frames.len())),
+            When::ByFrame(frame) => by(frame.number().min(frames.len()), format!("frame {}", frame.number().min(frames.len()))),
+            When::AtTime(time) => match times.iter().position(|&t| t == time) {
+                Some(frame) => at(frame, format!("frame {} at time {}", FrameIdx(frame).number(), time)),
+                None => (false, format!("no frame at time {}", time)),
+            },
+            When::ByTime(time) => by(times.partition_point(|&t| t <= time).min(frames.len()), format!("time {}", time)),
+        };
+        AssertionOutcome { assertion: self.clone(), passed, detail }
+    }
+}
+
+// Ranks a scenario with an algorithm of the registry at its own frame times and checks its
+// assertions, so scenario files can serve as regression tests of the algorithms
+pub fn check_scenario(scenario: &Scenario, algorithm: &str) -> Result<Vec<AssertionOutcome>, String> {
+    let result = run_scenario(&RunConfig::new(scenario.clone()).algorithm(algorithm))?;
+    let (frames, times) = (result.output.history.frames(), result.output.history.times());
+    Ok(scenario.assertions.iter().map(|assertion| assertion.check(frames, times, scenario.trusted_threshold())).collect())
+}
+
+// For #[test] functions: loads a scenario file, ranks it with an algorithm of the registry and
+// panics with every failed assertion, or when the file has none
+pub fn assert_scenario_file(path: &str, algorithm: &str) {
+    let scenario = Scenario::from_file(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    assert!(!scenario.assertions.is_empty(), "{}: no assertions", path);
+    let outcomes = check_scenario(&scenario, algorithm).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    let failed: Vec<String> = outcomes.iter().filter(|o| !o.passed).map(|o| format!("  line {}: {} ({})", o.assertion.line, o.assertion.text, o.detail)).collect();
+    assert!(failed.is_empty(), "{}: {} of {} assertions failed with {}:\n{}", path, failed.len(), outcomes.len(), algorithm, failed.join("\n"));
+}
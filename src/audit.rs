@@ -0,0 +1,97 @@
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::rank::{edge_fraction, outflow_normalizers, RankConfig, RankIteration};
+use crate::types::{FrameIdx, Time};
+
+// Deviations up to this size count as rounding, unless set otherwise
+pub const DEFAULT_AUDIT_TOLERANCE: f64 = 1e-9;
+
+// Numerical bookkeeping of one frame of the custom variant. Every quantity that should be
+// conserved is recorded as its largest deviation, so the audit tells how close it came.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameAudit {
+    pub time: Time,
+    pub teleport_total: f64,    // sum of the teleportation targets, 1
+    pub max_outflow_share: f64, // largest share of a node's rank that its out-edges carry, at most 1
+    pub max_step_error: f64,    // largest change of the total rank (mass) in one step, 0
+    pub max_flow_error: f64,    // largest gap between rank leaving and arriving (edge, dangling, teleported), 0
+    pub total_mass: f64,        // sum of the frame's final ranks, 1
+    pub min_rank: f64,          // at least 0
+}
+
+impl FrameAudit {
+    // Replays the power iteration of the frame step by step; `ranks` are the frame's final ranks
+    // as the run produced them, whatever solver or warm start produced them
+    pub fn new(time: Time, graph: &CompiledGraph, teleportation_targets: &[f64], config: &RankConfig, ranks: &[f64]) -> Self {
+        let normalizers = outflow_normalizers(graph, config.outflow_normalization);
+        let mut outflow_shares = vec![0.0; graph.num_of_nodes];
+        for edge in &graph.edges {
+            outflow_shares[edge.source.index()] += edge_fraction(edge.weight, normalizers[edge.source.index()]);
+        }
+
+        let mut max_step_error: f64 = 0.0;
+        let mut max_flow_error: f64 = 0.0;
+        let mut iteration = RankIteration::new(graph, teleportation_targets, config);
+        let mut previous_mass: f64 = iteration.ranks().iter().sum();
+        for step in iteration.by_ref() {
+            let trace = &step.trace;
+            let total = |values: &[f64]| values.iter().fold(0.0, |sum, v| sum + v);
+            let mass = total(&step.ranks);
+            max_step_error = max_step_error.max((mass - previous_mass).abs());
+            let edge_gap = total(&trace.edge_outflow) - total(&trace.edge_inflow);
+            let dangling_gap = total(&trace.dangling_outflow) - total(&trace.dangling_inflow);
+            let teleport_gap = (1.0 - config.damping_factor) * previous_mass - total(&trace.teleport_inflow);
+            max_flow_error = max_flow_error.max(edge_gap.abs()).max(dangling_gap.abs()).max(teleport_gap.abs());
+            previous_mass = mass;
+        }
+
+        FrameAudit {
+            time,
+            teleport_total: teleportation_targets.iter().sum(),
+            max_outflow_share: outflow_shares.iter().copied().fold(0.0, f64::max),
+            max_step_error,
+            max_flow_error,
+            total_mass: ranks.iter().sum(),
+            min_rank: ranks.iter().copied().fold(f64::INFINITY, f64::min),
+        }
+    }
+
+    // Descriptions of the invariants the frame breaks by more than `tolerance`
+    pub fn violations(&self, tolerance: f64) -> Vec<String> {
+        let mut violations = Vec::new();
+        if (self.teleport_total - 1.0).abs() > tolerance {
+            violations.push(format!("teleportation targets sum to {:.12}", self.teleport_total));
+        }
+        if self.max_outflow_share > 1.0 + tolerance {
+            violations.push(format!("a node's out-edges carry {:.12} of its rank (column normalization)", self.max_outflow_share));
+        }
+        if self.max_step_error > tolerance {
+            violations.push(format!("total rank changed by {:.3e} in one step", self.max_step_error));
+        }
+        if self.max_flow_error > tolerance {
+            violations.push(format!("rank leaving and arriving differ by {:.3e}", self.max_flow_error));
+        }
+        if (self.total_mass - 1.0).abs() > tolerance {
+            violations.push(format!("final ranks sum to {:.12}", self.total_mass));
+        }
+        if self.min_rank < -tolerance {
+            violations.push(format!("negative rank {:.3e}", self.min_rank));
+        }
+        violations
+    }
+}
+
+// frame,time,teleport_total,max_outflow_share,max_step_error,max_flow_error,total_mass,min_rank,violations rows
+pub fn write_audit_csv(out: &mut dyn Write, audits: &[FrameAudit], tolerance: f64) -> io::Result<()> {
+    writeln!(out, "frame,time,teleport_total,max_outflow_share,max_step_error,max_flow_error,total_mass,min_rank,violations")?;
+    for (frame, audit) in audits.iter().enumerate() {
+        writeln!(
+            out,
+            "{},{},{:.12},{:.12},{:.3e},{:.3e},{:.12},{:.3e},{}",
+            FrameIdx(frame).number(), audit.time, audit.teleport_total, audit.max_outflow_share, audit.max_step_error, audit.max_flow_error, audit.total_mass, audit.min_rank,
+            audit.violations(tolerance).len()
+        )?;
+    }
+    Ok(())
+}
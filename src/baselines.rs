@@ -0,0 +1,211 @@
+use crate::graph::CompiledGraph;
+use crate::rank::RankingAlgorithm;
+use crate::registry::{HITS, KATZ, STANDARD_PAGERANK, TRUSTRANK, WEIGHTED_IN_DEGREE};
+
+// Cheap baselines to compare the custom PageRank variant against.
+// Both ignore teleportation targets and are normalized to a total of 1 like the variant.
+
+pub struct Katz {
+    pub attenuation: f64, // must be below 1 / spectral radius of the weight matrix to converge
+    pub num_of_iterations: usize,
+}
+
+impl Default for Katz {
+    fn default() -> Self {
+        Katz { attenuation: 0.1, num_of_iterations: 50 }
+    }
+}
+
+impl RankingAlgorithm for Katz {
+    fn name(&self) -> &str {
+        KATZ.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, _teleportation_targets: &[f64]) -> Vec<f64> {
+        // x = attenuation * W^T x + 1
+        let mut centrality = vec![1.0; graph.num_of_nodes];
+        for _ in 0..self.num_of_iterations {
+            let mut new_centrality = vec![1.0; graph.num_of_nodes];
+            for edge in &graph.edges {
+                new_centrality[edge.target.index()] += self.attenuation * edge.weight * centrality[edge.source.index()];
+            }
+            centrality = new_centrality;
+        }
+        normalize(centrality)
+    }
+}
+
+pub struct WeightedInDegree;
+
+impl RankingAlgorithm for WeightedInDegree {
+    fn name(&self) -> &str {
+        WEIGHTED_IN_DEGREE.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, _teleportation_targets: &[f64]) -> Vec<f64> {
+        let mut in_degree = vec![0.0; graph.num_of_nodes];
+        for edge in &graph.edges {
+            in_degree[edge.target.index()] += edge.weight;
+        }
+        normalize(in_degree)
+    }
+}
+
+fn normalize(mut values: Vec<f64>) -> Vec<f64> {
+    let total: f64 = values.iter().sum();
+    if total > 0.0 {
+        for v in values.iter_mut() {
+            *v /= total;
+        }
+    } else if !values.is_empty() {
+        let uniform = 1.0 / values.len() as f64;
+        values.iter_mut().for_each(|v| *v = uniform);
+    }
+    values
+}
+
+// Classic personalized PageRank: out-weights normalized to 1 at every node,
+// dangling rank (mass) sent back along the teleportation targets.
+pub struct TrustRank {
+    pub damping_factor: f64,
+    pub num_of_iterations: usize,
+}
+
+impl Default for TrustRank {
+    fn default() -> Self {
+        TrustRank { damping_factor: 0.85, num_of_iterations: 50 }
+    }
+}
+
+impl RankingAlgorithm for TrustRank {
+    fn name(&self) -> &str {
+        TRUSTRANK.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Vec<f64> {
+        let num_of_nodes = graph.num_of_nodes;
+        let seeds = normalize(teleportation_targets.to_vec());
+
+        let mut outflow_values = vec![0.0; num_of_nodes];
+        for edge in &graph.edges {
+            outflow_values[edge.source.index()] += edge.weight;
+        }
+
+        let mut rank_values = vec![1.0 / num_of_nodes as f64; num_of_nodes];
+        for _ in 0..self.num_of_iterations {
+            let mut new_rank_values = vec![0.0; num_of_nodes];
+            for edge in graph.edges.iter().filter(|e| e.weight > 0.0) {
+                let source = edge.source.index();
+                new_rank_values[edge.target.index()] += rank_values[source] * edge.weight / outflow_values[source];
+            }
+            let dangling_rank: f64 = (0..num_of_nodes)
+                .filter(|&i| outflow_values[i] == 0.0)
+                .map(|i| rank_values[i])
+                .sum();
+            for (i, new_rank) in new_rank_values.iter_mut().enumerate() {
+                *new_rank = self.damping_factor * (*new_rank + dangling_rank * seeds[i])
+                    + (1.0 - self.damping_factor) * seeds[i];
+            }
+            rank_values = new_rank_values;
+        }
+        rank_values
+    }
+
+    fn with_damping(&self, damping_factor: f64) -> Option<Box<dyn RankingAlgorithm>> {
+        Some(Box::new(TrustRank { damping_factor, num_of_iterations: self.num_of_iterations }))
+    }
+}
+
+// Textbook PageRank with the semantics of networkx.pagerank: decayed out-weights normalized to 1
+// at every node, rank of dangling nodes and teleportation spread uniformly over all nodes, and
+// power iteration from the uniform vector until the L1 change drops below num_of_nodes * tolerance.
+pub struct StandardPageRank {
+    pub damping_factor: f64,
+    pub max_iterations: usize,
+    pub tolerance: f64,
+}
+
+impl Default for StandardPageRank {
+    fn default() -> Self {
+        StandardPageRank { damping_factor: 0.85, max_iterations: 100, tolerance: 1e-6 }
+    }
+}
+
+impl RankingAlgorithm for StandardPageRank {
+    fn name(&self) -> &str {
+        STANDARD_PAGERANK.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, _teleportation_targets: &[f64]) -> Vec<f64> {
+        let num_of_nodes = graph.num_of_nodes;
+        if num_of_nodes == 0 {
+            return Vec::new();
+        }
+        let uniform = 1.0 / num_of_nodes as f64;
+
+        let mut outflow_values = vec![0.0; num_of_nodes];
+        for edge in graph.edges.iter().filter(|e| e.weight > 0.0) {
+            outflow_values[edge.source.index()] += edge.weight;
+        }
+
+        let mut rank_values = vec![uniform; num_of_nodes];
+        for _ in 0..self.max_iterations {
+            let mut new_rank_values = vec![0.0; num_of_nodes];
+            for edge in graph.edges.iter().filter(|e| e.weight > 0.0) {
+                let source = edge.source.index();
+                new_rank_values[edge.target.index()] += self.damping_factor * rank_values[source] * edge.weight / outflow_values[source];
+            }
+            let dangling_rank: f64 = (0..num_of_nodes).filter(|&i| outflow_values[i] == 0.0).map(|i| rank_values[i]).sum();
+            for new_rank in new_rank_values.iter_mut() {
+                *new_rank += self.damping_factor * dangling_rank * uniform + (1.0 - self.damping_factor) * uniform;
+            }
+            let change: f64 = new_rank_values.iter().zip(&rank_values).map(|(new, old)| (new - old).abs()).sum();
+            rank_values = new_rank_values;
+            if change < num_of_nodes as f64 * self.tolerance {
+                break;
+            }
+        }
+        rank_values
+    }
+
+    fn with_damping(&self, damping_factor: f64) -> Option<Box<dyn RankingAlgorithm>> {
+        Some(Box::new(StandardPageRank { damping_factor, ..*self }))
+    }
+}
+
+// HITS authority scores
+pub struct Hits {
+    pub num_of_iterations: usize,
+}
+
+impl Default for Hits {
+    fn default() -> Self {
+        Hits { num_of_iterations: 50 }
+    }
+}
+
+impl RankingAlgorithm for Hits {
+    fn name(&self) -> &str {
+        HITS.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, _teleportation_targets: &[f64]) -> Vec<f64> {
+        let num_of_nodes = graph.num_of_nodes;
+        let mut hubs = vec![1.0; num_of_nodes];
+        let mut authorities = vec![1.0; num_of_nodes];
+        for _ in 0..self.num_of_iterations {
+            authorities = vec![0.0; num_of_nodes];
+            for edge in &graph.edges {
+                authorities[edge.target.index()] += edge.weight * hubs[edge.source.index()];
+            }
+            authorities = normalize(authorities);
+
+            hubs = vec![0.0; num_of_nodes];
+            for edge in &graph.edges {
+                hubs[edge.source.index()] += edge.weight * authorities[edge.target.index()];
+            }
+            hubs = normalize(hubs);
+        }
+        authorities
+    }
+}
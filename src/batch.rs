@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use crate::rank::{Convergence, RankConfig};
+use crate::registry::{AlgorithmRegistry, PAGERANK_VARIANT};
+use crate::run::{continue_scenario_parallel, RunOutput};
+use crate::scenario::Scenario;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// A batch run for services that embed the ranking: which scenario to rank with which algorithm
+// at which times, and the results as plain values instead of files
+#[derive(Debug, Clone)]
+pub struct RunConfig {
+    pub scenario: Scenario,
+    pub algorithm: String, // id in the AlgorithmRegistry
+    pub rank_config: RankConfig,
+    pub frame_times: Option<Vec<Time>>, // the scenario's own frames when not set
+    pub jobs: usize,                    // frames ranked at the same time
+}
+
+impl RunConfig {
+    pub fn new(scenario: Scenario) -> Self {
+        RunConfig { scenario, algorithm: PAGERANK_VARIANT.id.to_string(), rank_config: RankConfig::default(), frame_times: None, jobs: 1 }
+    }
+
+    pub fn algorithm(mut self, id: &str) -> Self {
+        self.algorithm = id.to_string();
+        self
+    }
+
+    pub fn rank_config(mut self, rank_config: RankConfig) -> Self {
+        self.rank_config = rank_config;
+        self
+    }
+
+    pub fn frame_times(mut self, frame_times: Vec<Time>) -> Self {
+        self.frame_times = Some(frame_times);
+        self
+    }
+
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+}
+
+// Everything a run found out, frame by frame
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub scenario: String,
+    pub algorithm: String,         // name, as shown in frame labels
+    pub labels: Vec<String>,       // indexed by node: its name, or its index without one
+    pub frames: Vec<FrameResult>,
+    pub warnings: Vec<String>,     // about the scenario and the frames; the ranks are still valid
+    pub output: RunOutput,         // the same results for the exporters
+}
+
+#[derive(Debug, Clone)]
+pub struct FrameResult {
+    pub frame: FrameIdx,
+    pub time: Time,
+    pub ranks: BTreeMap<NodeId, f64>,
+    pub convergence: Option<Convergence>, // None for algorithms without power iteration and warm-started frames
+    pub active_edges: usize,              // edges that weigh something in the frame
+}
+
+impl FrameResult {
+    // Nodes from the highest rank to the lowest; ties in node order
+    pub fn ranking(&self) -> Vec<(NodeId, f64)> {
+        let mut ranking: Vec<(NodeId, f64)> = self.ranks.iter().map(|(&node, &rank)| (node, rank)).collect();
+        ranking.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranking
+    }
+}
+
+impl RunResult {
+    // Rank of a node, by name or index, in the last frame
+    pub fn final_rank(&self, node: &str) -> Option<f64> {
+        let node = self.labels.iter().position(|label| label == node).or_else(|| node.parse().ok())?;
+        self.frames.last()?.ranks.get(&NodeId(node)).copied()
+    }
+}
+
+// Validates the scenario and ranks every frame; errors are an invalid scenario, an unknown
+// algorithm or frame times out of order
+pub fn run_scenario(config: &RunConfig) -> Result<RunResult, String> {
+    let scenario = &config.scenario;
+    scenario.validate().map_err(|e| format!("{}: {}", scenario.name, e))?;
+    let algorithm = AlgorithmRegistry::create(&config.algorithm, &config.rank_config)?;
+    let frame_times = config.frame_times.clone().unwrap_or_else(|| scenario.frame_times());
+    if frame_times.windows(2).any(|pair| pair[1] < pair[0]) {
+        return Err(format!("{}: frame times must not decrease", scenario.name));
+    }
+
+    let mut warnings = Vec::new();
+    if frame_times.is_empty() {
+        warnings.push("no frames to rank".to_string());
+    }
+    if scenario.looks_reversed() {
+        warnings.push("the experts endorse nobody but are endorsed; the edges may point from endorsee to endorser".to_string());
+    }
+    let mut output = RunOutput::new();
+    continue_scenario_parallel(scenario, algorithm.as_ref(), &frame_times, &scenario.graph_options, config.jobs, &mut output, &mut |_| Ok(()))
+        .map_err(|e| format!("{}: {}", scenario.name, e))?;
+
+    let tolerance = config.rank_config.tolerance;
+    let frames: Vec<FrameResult> = (0..output.history.num_of_frames())
+        .map(|frame| {
+            let time = output.history.times()[frame];
+            let convergence = output.convergence[frame];
+            if let Some(convergence) = convergence.filter(|c| tolerance > 0.0 && c.residual > tolerance) {
+                warnings.push(format!("frame {} (time {}) did not converge: residual {:.2e} after {} iterations", FrameIdx(frame).number(), time, convergence.residual, convergence.iterations));
+            }
+            let active_edges = output.edge_weights[frame].iter().filter(|&&w| w > 0.0).count();
+            if active_edges == 0 && !scenario.edges.is_empty() {
+                warnings.push(format!("frame {} (time {}) has no edges yet", FrameIdx(frame).number(), time));
+            }
+            let ranks = output.history.ranks_at(FrameIdx(frame)).iter().enumerate().map(|(node, &rank)| (NodeId(node), rank)).collect();
+            FrameResult { frame: FrameIdx(frame), time, ranks, convergence, active_edges }
+        })
+        .collect();
+    Ok(RunResult { scenario: scenario.name.clone(), algorithm: algorithm.name().to_string(), labels: scenario.node_labels(), frames, warnings, output })
+}
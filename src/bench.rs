@@ -0,0 +1,153 @@
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crate::dot::{write_dot, FrameRender};
+use crate::graph::{compile_graph, CompiledGraph, Edge, GraphOptions};
+use crate::random::Rng;
+use crate::rank::{pagerank_variant, RankConfig};
+use crate::scenario::Scenario;
+use crate::style::ColorScale;
+use crate::types::{NodeId, Time};
+
+// Graph sizes (number of edges) of the standard suite
+pub const BENCH_SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+
+// Every benchmark is repeated until it has run for at least this long
+const MIN_DURATION: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub name: String,
+    pub edges: usize,
+    pub iterations: usize,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    pub fn time_per_iteration(&self) -> Duration {
+        self.elapsed / self.iterations as u32
+    }
+
+    pub fn edges_per_second(&self) -> f64 {
+        (self.edges * self.iterations) as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+// Runs `routine` once to warm up, then repeatedly until MIN_DURATION has passed
+pub fn measure<T>(name: &str, edges: usize, mut routine: impl FnMut() -> T) -> BenchResult {
+    black_box(routine());
+    let start = Instant::now();
+    let mut iterations = 0;
+    while iterations == 0 || start.elapsed() < MIN_DURATION {
+        black_box(routine());
+        iterations += 1;
+    }
+    BenchResult { name: name.to_string(), edges, iterations, elapsed: start.elapsed() }
+}
+
+// Random graph with the given number of edges among edges / 10 nodes (at least 10), created over
+// 100 time units, with the first node as the only expert. The same seed gives the same graph.
+pub fn bench_scenario(num_of_edges: usize, seed: u64) -> Scenario {
+    let num_of_nodes = (num_of_edges / 10).max(10);
+    let mut rng = Rng::new(seed);
+    let edges = (0..num_of_edges)
+        .map(|i| Edge {
+            source: NodeId(rng.below(num_of_nodes)),
+            target: NodeId(rng.below(num_of_nodes)),
+            time_of_creation: Time(100.0 * i as f64 / num_of_edges as f64),
+            weight: 1.0,
+        })
+        .collect();
+    Scenario::new(&format!("bench-{}", num_of_edges), num_of_nodes, vec![NodeId(0)], edges)
+}
+
+// One frame at the end of a bench scenario with everything its routines need, so that each
+// benchmark times only ranking, graph compilation, decay weights or DOT serialization
+pub struct BenchFrame {
+    pub scenario: Scenario,
+    time: Time,
+    options: GraphOptions,
+    config: RankConfig,
+    weights: Vec<f64>,
+    graph: CompiledGraph,
+    targets: Vec<f64>,
+    ranks: Vec<f64>,
+    names: Vec<String>,
+    positions: Vec<(f64, f64)>,
+    decay_desc: String,
+    scale: ColorScale,
+}
+
+impl BenchFrame {
+    pub fn new(num_of_edges: usize, seed: u64) -> Self {
+        let scenario = bench_scenario(num_of_edges, seed);
+        let time = Time(100.0);
+        let options = GraphOptions::default();
+        let config = RankConfig::new();
+        let weights = scenario.edge_weights(time);
+        let graph = compile_graph(&scenario.edges, &weights, scenario.num_of_nodes, &options).unwrap();
+        let targets = scenario.teleportation_targets(time);
+        let ranks = pagerank_variant(&graph, &targets, &config);
+        let scale = ColorScale::new(&scenario.style, &ranks, std::slice::from_ref(&ranks));
+        BenchFrame {
+            names: scenario.node_labels(),
+            positions: scenario.layout(),
+            decay_desc: scenario.decay_description(),
+            scenario,
+            time,
+            options,
+            config,
+            weights,
+            graph,
+            targets,
+            ranks,
+            scale,
+        }
+    }
+
+    pub fn num_of_edges(&self) -> usize {
+        self.scenario.edges.len()
+    }
+
+    pub fn rank(&self) -> Vec<f64> {
+        pagerank_variant(&self.graph, &self.targets, &self.config)
+    }
+
+    pub fn compile(&self) -> CompiledGraph {
+        compile_graph(&self.scenario.edges, &self.weights, self.scenario.num_of_nodes, &self.options).unwrap()
+    }
+
+    pub fn decay_weights(&self) -> Vec<f64> {
+        self.scenario.edge_weights(self.time)
+    }
+
+    // Serializes into `dot`, which is cleared first so that the buffer can be reused
+    pub fn write_dot(&self, dot: &mut Vec<u8>) {
+        dot.clear();
+        let scenario = &self.scenario;
+        let render = FrameRender::new(&self.ranks, &scenario.edges, &self.weights, &self.names, &self.positions).experts(&scenario.experts).title("bench", &self.decay_desc);
+        write_dot(dot, &render, &scenario.style, &self.scale).unwrap();
+    }
+}
+
+// Every benchmark of one frame, timed with `measure`
+pub fn bench_suite(num_of_edges: usize, seed: u64) -> Vec<BenchResult> {
+    let frame = BenchFrame::new(num_of_edges, seed);
+    let mut dot = Vec::new();
+    vec![
+        measure("pagerank_variant", num_of_edges, || frame.rank()),
+        measure("compile_graph", num_of_edges, || frame.compile()),
+        measure("edge_weights", num_of_edges, || frame.decay_weights()),
+        measure("write_dot", num_of_edges, || frame.write_dot(&mut dot)),
+    ]
+}
+
+pub fn write_bench_table(out: &mut dyn Write, results: &[BenchResult]) -> io::Result<()> {
+    writeln!(out, "{:<18} {:>10} {:>8} {:>14} {:>16}", "benchmark", "edges", "runs", "time/run", "edges/s")?;
+    for result in results {
+        writeln!(out, "{:<18} {:>10} {:>8} {:>14} {:>16.0}",
+            result.name, result.edges, result.iterations, format!("{:.3?}", result.time_per_iteration()), result.edges_per_second())?;
+    }
+    Ok(())
+}
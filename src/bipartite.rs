@@ -0,0 +1,126 @@
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::rank::RankingAlgorithm;
+use crate::registry::COHITS;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// Co-HITS (Deng, Lyu and King) on a bipartite rater → item graph: scores alternate between the
+// two sides. An item collects the scores of its raters in proportion to the share of their
+// rating weight it gets, a rater collects the scores of the items it rated in proportion to its
+// share of their incoming weight, and each side mixes in its prior with probability 1 - jump:
+//   x_u = (1 - λ) x0_u + λ Σ_i x_i w_ui / Σ_u' w_u'i      (raters)
+//   y_i = (1 - λ) y0_i + λ Σ_u x_u w_ui / Σ_i' w_ui'      (items)
+// The rater prior is the teleportation targets among the raters (the experts as trusted raters),
+// uniform when none is a rater; the item prior is uniform. Each side is normalized to 1 after
+// every step and holds half of the total, or all of it when the other side is empty.
+pub struct CoHits {
+    pub jump: f64, // probability of following a rating instead of returning to the prior, on both sides
+    pub num_of_iterations: usize,
+    pub items: Option<Vec<bool>>, // indexed by node; when not given, items are the nodes with weighted incoming edges
+}
+
+impl Default for CoHits {
+    fn default() -> Self {
+        CoHits { jump: 0.8, num_of_iterations: 50, items: None }
+    }
+}
+
+impl CoHits {
+    pub fn with_items(items: Vec<bool>) -> Self {
+        CoHits { items: Some(items), ..CoHits::default() }
+    }
+
+    // Which nodes are items in the graph
+    pub fn sides(&self, graph: &CompiledGraph) -> Vec<bool> {
+        match &self.items {
+            Some(items) => (0..graph.num_of_nodes).map(|i| items.get(i).copied().unwrap_or(false)).collect(),
+            None => {
+                let mut items = vec![false; graph.num_of_nodes];
+                for edge in graph.edges.iter().filter(|e| e.weight > 0.0) {
+                    items[edge.target.index()] = true;
+                }
+                items
+            }
+        }
+    }
+}
+
+impl RankingAlgorithm for CoHits {
+    fn name(&self) -> &str {
+        COHITS.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Vec<f64> {
+        let num_of_nodes = graph.num_of_nodes;
+        let items = self.sides(graph);
+        // Only ratings from a rater to an item propagate
+        let ratings: Vec<(usize, usize, f64)> = graph.edges.iter()
+            .map(|e| (e.source.index(), e.target.index(), e.weight))
+            .filter(|&(rater, item, weight)| weight > 0.0 && !items[rater] && items[item])
+            .collect();
+        let mut given = vec![0.0; num_of_nodes];
+        let mut received = vec![0.0; num_of_nodes];
+        for &(rater, item, weight) in &ratings {
+            given[rater] += weight;
+            received[item] += weight;
+        }
+
+        let side_prior = |side: bool, weights: &dyn Fn(usize) -> f64| -> Vec<f64> {
+            let total: f64 = (0..num_of_nodes).filter(|&i| items[i] == side).map(weights).sum();
+            let members = items.iter().filter(|&&item| item == side).count();
+            (0..num_of_nodes)
+                .map(|i| if items[i] != side { 0.0 } else if total > 0.0 { weights(i) / total } else { 1.0 / members as f64 })
+                .collect()
+        };
+        let rater_prior = side_prior(false, &|i| teleportation_targets[i]);
+        let item_prior = side_prior(true, &|_| 1.0);
+
+        let mut raters = rater_prior.clone();
+        let mut item_scores = item_prior.clone();
+        for _ in 0..self.num_of_iterations {
+            let mut new_items: Vec<f64> = item_prior.iter().map(|p| (1.0 - self.jump) * p).collect();
+            for &(rater, item, weight) in &ratings {
+                new_items[item] += self.jump * raters[rater] * weight / given[rater];
+            }
+            let mut new_raters: Vec<f64> = rater_prior.iter().map(|p| (1.0 - self.jump) * p).collect();
+            for &(rater, item, weight) in &ratings {
+                new_raters[rater] += self.jump * new_items[item] * weight / received[item];
+            }
+            raters = normalized(new_raters);
+            item_scores = normalized(new_items);
+        }
+
+        let sides = usize::from(raters.iter().any(|&x| x > 0.0)) + usize::from(item_scores.iter().any(|&y| y > 0.0));
+        let share = if sides > 0 { 1.0 / sides as f64 } else { 0.0 };
+        raters.iter().zip(&item_scores).map(|(x, y)| share * (x + y)).collect()
+    }
+
+    fn with_damping(&self, damping_factor: f64) -> Option<Box<dyn RankingAlgorithm>> {
+        Some(Box::new(CoHits { jump: damping_factor, num_of_iterations: self.num_of_iterations, items: self.items.clone() }))
+    }
+}
+
+fn normalized(mut values: Vec<f64>) -> Vec<f64> {
+    let total: f64 = values.iter().sum();
+    if total > 0.0 {
+        values.iter_mut().for_each(|v| *v /= total);
+    }
+    values
+}
+
+// frame,time,node,side,score rows, side being rater or item; scores are normalized to 1 within
+// each side, so raters and items can be ranked among themselves
+pub fn write_bipartite_csv(out: &mut dyn Write, times: &[Time], frames: &[Vec<f64>], items: &[NodeId], labels: &[String]) -> io::Result<()> {
+    writeln!(out, "frame,time,node,side,score")?;
+    for (frame, (time, scores)) in times.iter().zip(frames).enumerate() {
+        let is_item = |node: usize| items.contains(&NodeId(node));
+        let side_total = |side: bool| scores.iter().enumerate().filter(|&(node, _)| is_item(node) == side).map(|(_, s)| s).sum::<f64>();
+        let (rater_total, item_total) = (side_total(false), side_total(true));
+        for (node, score) in scores.iter().enumerate() {
+            let (side, total) = if is_item(node) { ("item", item_total) } else { ("rater", rater_total) };
+            writeln!(out, "{},{},{},{},{:.6}", FrameIdx(frame).number(), time, labels[node], side, if total > 0.0 { score / total } else { 0.0 })?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use std::io::{self, Write};
+
+use crate::json::Json;
+use crate::random::Rng;
+use crate::rank::RankingAlgorithm;
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::types::{FrameIdx, Time};
+
+// How a bootstrap sample is drawn from the edges of a frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resampling {
+    Edges,        // as many edges as weigh something, drawn with replacement; an edge drawn k times weighs k times as much
+    Weights(f64), // every weight multiplied by a factor drawn uniformly from [1 - spread, 1 + spread]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BootstrapOptions {
+    pub samples: usize,
+    pub confidence: f64, // share of the samples inside the interval, e.g. 0.95
+    pub resampling: Resampling,
+}
+
+// Mean rank of a node over the samples of a frame, and the percentile interval around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankInterval {
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+// Ranks every frame of a run again on `options.samples` resampled graphs. Each frame draws from
+// its own generator (the seed plus the frame index), so its intervals do not depend on the
+// frames before it. Frames are ranked from scratch with the scenario's teleportation targets,
+// without warm starts or dynamic authority.
+pub fn bootstrap_run(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, output: &RunOutput, options: &BootstrapOptions, seed: u64) -> Vec<Vec<RankInterval>> {
+    let num_of_frames = output.history.num_of_frames();
+    output
+        .history
+        .times()
+        .iter()
+        .zip(&output.edge_weights)
+        .enumerate()
+        .map(|(frame, (&time, edge_weights))| {
+            let damped = scenario.damping_factor(frame, num_of_frames).and_then(|d| algorithm.with_damping(d));
+            let mut rng = Rng::new(seed.wrapping_add(frame as u64));
+            bootstrap_frame(scenario, damped.as_deref().unwrap_or(algorithm), time, edge_weights, options, &mut rng)
+        })
+        .collect()
+}
+
+pub fn bootstrap_frame(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, time: Time, edge_weights: &[f64], options: &BootstrapOptions, rng: &mut Rng) -> Vec<RankInterval> {
+    let teleportation_targets = scenario.teleportation_targets(time);
+    let present: Vec<usize> = (0..edge_weights.len()).filter(|&edge| edge_weights[edge] > 0.0).collect();
+    let mut samples: Vec<Vec<f64>> = vec![Vec::with_capacity(options.samples); scenario.num_of_nodes];
+    for _ in 0..options.samples {
+        let weights: Vec<f64> = match options.resampling {
+            Resampling::Edges => {
+                let mut weights = vec![0.0; edge_weights.len()];
+                for _ in 0..present.len() {
+                    let edge = present[rng.below(present.len())];
+                    weights[edge] += edge_weights[edge];
+                }
+                weights
+            }
+            Resampling::Weights(spread) => edge_weights.iter().map(|&w| w * (1.0 + spread * (2.0 * rng.next_f64() - 1.0))).collect(),
+        };
+        let graph = scenario.frame_graph(time, &weights).unwrap();
+        for (node, rank) in algorithm.rank(&graph, &teleportation_targets).into_iter().enumerate() {
+            samples[node].push(rank);
+        }
+    }
+    let tail = (1.0 - options.confidence) / 2.0;
+    samples
+        .into_iter()
+        .map(|mut ranks| {
+            ranks.sort_by(f64::total_cmp);
+            RankInterval {
+                mean: ranks.iter().sum::<f64>() / ranks.len() as f64,
+                lower: percentile(&ranks, tail),
+                upper: percentile(&ranks, 1.0 - tail),
+            }
+        })
+        .collect()
+}
+
+// Linear interpolation between the closest ranks of the sorted values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let position = p * (sorted.len() - 1) as f64;
+    let (below, above) = (position.floor() as usize, position.ceil() as usize);
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+// The settings, then per frame the mean and interval of every node
+pub fn write_bootstrap_json(out: &mut dyn Write, times: &[Time], intervals: &[Vec<RankInterval>], labels: &[String], options: &BootstrapOptions) -> io::Result<()> {
+    let (resampling, spread) = match options.resampling {
+        Resampling::Edges => ("edges", Json::Null),
+        Resampling::Weights(spread) => ("weights", spread.into()),
+    };
+    let frames = times
+        .iter()
+        .zip(intervals)
+        .enumerate()
+        .map(|(frame, (time, intervals))| {
+            let nodes = intervals
+                .iter()
+                .enumerate()
+                .map(|(node, interval)| {
+                    Json::object(vec![
+                        ("node", node.into()),
+                        ("label", labels[node].as_str().into()),
+                        ("mean", interval.mean.into()),
+                        ("lower", interval.lower.into()),
+                        ("upper", interval.upper.into()),
+                    ])
+                })
+                .collect();
+            Json::object(vec![("frame", FrameIdx(frame).number().into()), ("time", time.value().into()), ("nodes", Json::Array(nodes))])
+        })
+        .collect();
+    let json = Json::object(vec![
+        ("samples", options.samples.into()),
+        ("confidence", options.confidence.into()),
+        ("resampling", resampling.into()),
+        ("spread", spread),
+        ("frames", Json::Array(frames)),
+    ]);
+    writeln!(out, "{}", json)
+}
@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+use std::io;
+use std::sync::Arc;
+
+use object_store::path::Path;
+use object_store::{ObjectStore, WriteMultipart};
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+use crate::sink::Sink;
+
+// Writes straight to an S3 or GCS bucket, for batch jobs running in containers without persistent
+// disks. Credentials and regions come from the environment like for the providers' own tools
+// (AWS_ACCESS_KEY_ID, AWS_REGION, GOOGLE_APPLICATION_CREDENTIALS, ...). Uploads run in parallel,
+// up to `max_parallel` at a time, and files above PART_SIZE are uploaded in parts.
+
+// Size of the parts of a multipart upload; smaller files are uploaded in one request
+pub const PART_SIZE: usize = 8 << 20;
+
+pub struct ObjectStoreSink {
+    store: Arc<dyn ObjectStore>,
+    url_prefix: String, // without trailing '/'
+    prefix: String,     // the part of url_prefix within the bucket
+    max_parallel: usize,
+    runtime: Runtime,
+    in_flight: VecDeque<(String, JoinHandle<object_store::Result<()>>)>,
+}
+
+impl ObjectStoreSink {
+    // `url` is the bucket URL with an optional prefix, e.g. "s3://my-bucket/runs/42"
+    pub fn new(url: &str, max_parallel: usize) -> io::Result<Self> {
+        let (scheme, location) = url.split_once("://").unwrap_or(("", url));
+        let (bucket, _) = location.split_once('/').unwrap_or((location, ""));
+        let store = match scheme {
+            "s3" => s3_store(bucket)?,
+            "gs" => gcs_store(bucket)?,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("not an s3:// or gs:// URL: {}", url))),
+        };
+        Self::with_store(store, url, max_parallel)
+    }
+
+    // Writes to any object store; `url` is where its root is, e.g. "memory://bucket/prefix", and
+    // the part after the bucket is the prefix of every written path
+    pub fn with_store(store: Arc<dyn ObjectStore>, url: &str, max_parallel: usize) -> io::Result<Self> {
+        let url_prefix = url.trim_end_matches('/').to_string();
+        let location = url_prefix.split_once("://").map_or(url_prefix.as_str(), |(_, location)| location);
+        let prefix = location.split_once('/').map_or("", |(_, prefix)| prefix).to_string();
+        let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+        Ok(ObjectStoreSink { store, url_prefix, prefix, max_parallel: max_parallel.max(1), runtime, in_flight: VecDeque::new() })
+    }
+
+    fn wait_oldest(&mut self) -> io::Result<()> {
+        if let Some((url, upload)) = self.in_flight.pop_front() {
+            let result = self.runtime.block_on(upload).map_err(io::Error::other)?;
+            result.map_err(|e| io::Error::other(format!("upload to {} failed: {}", url, e)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "s3")]
+fn s3_store(bucket: &str) -> io::Result<Arc<dyn ObjectStore>> {
+    Ok(Arc::new(object_store::aws::AmazonS3Builder::from_env().with_bucket_name(bucket).build().map_err(io::Error::other)?))
+}
+
+#[cfg(not(feature = "s3"))]
+fn s3_store(_bucket: &str) -> io::Result<Arc<dyn ObjectStore>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "s3:// buckets need trust-flow built with the s3 feature"))
+}
+
+#[cfg(feature = "gcs")]
+fn gcs_store(bucket: &str) -> io::Result<Arc<dyn ObjectStore>> {
+    Ok(Arc::new(object_store::gcp::GoogleCloudStorageBuilder::from_env().with_bucket_name(bucket).build().map_err(io::Error::other)?))
+}
+
+#[cfg(not(feature = "gcs"))]
+fn gcs_store(_bucket: &str) -> io::Result<Arc<dyn ObjectStore>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "gs:// buckets need trust-flow built with the gcs feature"))
+}
+
+// Uploads in one request, or in parts that are cleaned up again if one of them fails
+async fn upload(store: Arc<dyn ObjectStore>, path: Path, contents: Vec<u8>) -> object_store::Result<()> {
+    if contents.len() <= PART_SIZE {
+        store.put(&path, contents.into()).await?;
+        return Ok(());
+    }
+    let mut upload = WriteMultipart::new_with_chunk_size(store.put_multipart(&path).await?, PART_SIZE);
+    upload.write(&contents);
+    if let Err(e) = upload.wait_for_capacity(0).await {
+        let _ = upload.abort().await;
+        return Err(e);
+    }
+    upload.finish().await?;
+    Ok(())
+}
+
+impl Sink for ObjectStoreSink {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        while self.in_flight.len() >= self.max_parallel {
+            self.wait_oldest()?;
+        }
+        let object = Path::from_iter(self.prefix.split('/').chain(path.split('/')).filter(|part| !part.is_empty()));
+        let task = self.runtime.spawn(upload(Arc::clone(&self.store), object, contents.to_vec()));
+        self.in_flight.push_back((self.location(path), task));
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.in_flight.is_empty() {
+            self.wait_oldest()?;
+        }
+        Ok(())
+    }
+
+    fn location(&self, path: &str) -> String {
+        format!("{}/{}", self.url_prefix, path)
+    }
+}
+
+impl Drop for ObjectStoreSink {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::graph::Edge;
+use crate::nodes::NodeInfo;
+use crate::run::InitialRanks;
+use crate::scenario::{FrameSchedule, Scenario, ScenarioError};
+use crate::types::{NodeId, Time};
+
+// Builds a scenario in code with named nodes instead of indices, e.g.
+//
+//   Scenario::builder("demo").node("a").edge("a", "b").at(3.0).expert("a").frames(20.0).build()
+//
+// Nodes are numbered in the order they are first named, by `node`, `edge` or `expert`. `at` and
+// `weight` apply to the edge added last. A misuse is reported by `build`, as the first error.
+#[derive(Debug, Clone)]
+pub struct ScenarioBuilder {
+    scenario: Scenario,
+    nodes: HashMap<String, NodeId>,
+    error: Option<String>,
+}
+
+impl ScenarioBuilder {
+    pub fn new(name: &str) -> Self {
+        ScenarioBuilder { scenario: Scenario::new(name, 0, Vec::new(), Vec::new()), nodes: HashMap::new(), error: None }
+    }
+
+    // Declares a node; a node that exists already is left as it is
+    pub fn node(mut self, name: &str) -> Self {
+        self.node_id(name);
+        self
+    }
+
+    // Adds an edge created at time 0 with weight 1
+    pub fn edge(mut self, source: &str, target: &str) -> Self {
+        let (source, target) = (self.node_id(source), self.node_id(target));
+        self.scenario.edges.push(Edge { source, target, time_of_creation: Time(0.0), weight: 1.0 });
+        self
+    }
+
+    // Time of creation of the last edge
+    pub fn at(mut self, time: f64) -> Self {
+        match self.scenario.edges.last_mut() {
+            Some(edge) => edge.time_of_creation = Time(time),
+            None => self.fail("at() must follow edge()"),
+        }
+        self
+    }
+
+    // Weight at creation of the last edge
+    pub fn weight(mut self, weight: f64) -> Self {
+        match self.scenario.edges.last_mut() {
+            Some(edge) => edge.weight = weight,
+            None => self.fail("weight() must follow edge()"),
+        }
+        self
+    }
+
+    pub fn expert(mut self, name: &str) -> Self {
+        let node = self.node_id(name);
+        if !self.scenario.experts.contains(&node) {
+            self.scenario.experts.push(node);
+        }
+        self
+    }
+
+    pub fn decay(mut self, decay_constant: f64) -> Self {
+        self.scenario.decay_constant = decay_constant;
+        self
+    }
+
+    // Where frames ranked from scratch start their power iteration
+    pub fn initial_ranks(mut self, initial_ranks: InitialRanks) -> Self {
+        self.scenario.initial_ranks = Some(initial_ranks);
+        self
+    }
+
+    // Frames from time 0 to `end`, one time unit apart
+    pub fn frames(mut self, end: f64) -> Self {
+        self.scenario.frame_schedule = Some(FrameSchedule::Range { start: None, end: Some(Time(end)), step: None });
+        self
+    }
+
+    // Frames at exactly these times
+    pub fn frames_at(mut self, times: &[f64]) -> Self {
+        self.scenario.frame_schedule = Some(FrameSchedule::Times(times.iter().copied().map(Time).collect()));
+        self
+    }
+
+    // The scenario, validated like one read from a file
+    pub fn build(self) -> Result<Scenario, ScenarioError> {
+        if let Some(message) = self.error {
+            return Err(ScenarioError { line: 0, message });
+        }
+        self.scenario.validate()?;
+        Ok(self.scenario)
+    }
+
+    fn node_id(&mut self, name: &str) -> NodeId {
+        if let Some(&node) = self.nodes.get(name) {
+            return node;
+        }
+        let node = NodeId(self.scenario.num_of_nodes);
+        self.scenario.num_of_nodes += 1;
+        self.scenario.node_info.push(NodeInfo { name: Some(name.to_string()), ..NodeInfo::default() });
+        self.nodes.insert(name.to_string(), node);
+        node
+    }
+
+    fn fail(&mut self, message: &str) {
+        self.error.get_or_insert_with(|| message.to_string());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::graph::CompiledGraph;
+use crate::rank::{edge_fraction, outflow_normalizers, rankable_inputs, redistribute_dangling, RankConfig, RankingAlgorithm};
+use crate::registry::CAPACITY_LIMITED;
+
+// PageRank variant with limited attention and credibility: per step, no node emits more than
+// `capacity` rank (mass) along its edges, and no node receives more than `capacity` along its
+// edges. Emission over the limit is scaled down across the node's out-edges, reception over the
+// limit across its in-edges. The excess cut off by the limits is teleported, while teleportation
+// and dangling rank work as in the custom variant, so the total stays 1 and a capacity of 1 or
+// more gives the custom variant's ranks (reward and penalty events aside).
+pub struct CapacityLimited {
+    pub config: RankConfig, // damping factor, iterations, tolerance and outflow normalization
+    pub capacity: f64,
+}
+
+impl RankingAlgorithm for CapacityLimited {
+    fn name(&self) -> &str {
+        CAPACITY_LIMITED.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Vec<f64> {
+        let (graph, teleportation_targets) = rankable_inputs(graph, teleportation_targets);
+        let (graph, teleportation_targets) = (graph.as_ref(), teleportation_targets.as_ref());
+        let num_of_nodes = graph.num_of_nodes;
+        let normalizers = outflow_normalizers(graph, self.config.outflow_normalization);
+        let total_teleport: f64 = teleportation_targets.iter().sum();
+        let teleport_share = |i: usize| if total_teleport > 0.0 { teleportation_targets[i] / total_teleport } else { 1.0 / num_of_nodes as f64 };
+
+        let mut ranks = match &self.config.initial_ranks {
+            Some(initial_ranks) => initial_ranks.clone(),
+            None => graph.uniform_ranks(),
+        };
+        for _ in 0..self.config.num_of_iterations {
+            let flows: Vec<f64> = graph
+                .edges
+                .iter()
+                .map(|e| self.config.damping_factor * ranks[e.source.index()] * edge_fraction(e.weight, normalizers[e.source.index()]))
+                .collect();
+            let mut emitted = vec![0.0; num_of_nodes];
+            let mut dangling: Vec<f64> = ranks.iter().map(|&r| self.config.damping_factor * r).collect();
+            for (e, &flow) in graph.edges.iter().zip(&flows) {
+                emitted[e.source.index()] += flow;
+                dangling[e.source.index()] -= flow;
+            }
+            let dangling_inflow = redistribute_dangling(graph, &dangling, teleportation_targets, self.config.dangling_policy);
+            let emit_scale: Vec<f64> = emitted.iter().map(|&emitted| limit(emitted, self.capacity)).collect();
+
+            let mut received = vec![0.0; num_of_nodes];
+            for (e, &flow) in graph.edges.iter().zip(&flows) {
+                received[e.target.index()] += flow * emit_scale[e.source.index()];
+            }
+            let delivered: Vec<f64> = received.iter().map(|&received| received * limit(received, self.capacity)).collect();
+
+            let excess = emitted.iter().sum::<f64>() - delivered.iter().sum::<f64>();
+            let new_ranks: Vec<f64> = (0..num_of_nodes)
+                .map(|i| (1.0 - self.config.damping_factor) * teleportation_targets[i] + dangling_inflow[i] + delivered[i] + excess * teleport_share(i))
+                .collect();
+            let residual: f64 = new_ranks.iter().zip(&ranks).map(|(new, old)| (new - old).abs()).sum();
+            ranks = new_ranks;
+            if residual < self.config.tolerance {
+                break;
+            }
+        }
+        ranks
+    }
+
+    fn with_damping(&self, damping_factor: f64) -> Option<Box<dyn RankingAlgorithm>> {
+        Some(Box::new(CapacityLimited { config: self.config.clone().damping_factor(damping_factor), capacity: self.capacity }))
+    }
+}
+
+// Factor that brings a flow down to the capacity
+fn limit(flow: f64, capacity: f64) -> f64 {
+    if flow > capacity { capacity / flow } else { 1.0 }
+}
@@ -0,0 +1,57 @@
+use crate::types::EdgeId;
+
+// Weight below which an edge counts as decayed, unless set otherwise
+pub const DEFAULT_FADED_WEIGHT: f64 = 0.1;
+
+// Border of nodes whose rank moved by more than the threshold since the previous frame
+pub const MOVED_BORDER: &str = "darkorange";
+
+// What happened to an edge since the previous frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeChange {
+    Added, // inactive before, active now
+    Faded, // at or above the faded weight before, below it now
+}
+
+impl EdgeChange {
+    // Color of the edge in rendered frames; faded edges are half transparent
+    pub fn color(&self) -> &'static str {
+        match self {
+            EdgeChange::Added => "green3",
+            EdgeChange::Faded => "#CD000080",
+        }
+    }
+}
+
+// Differences between two consecutive frames, for rendering what changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameChanges {
+    pub edges: Vec<(EdgeId, EdgeChange)>,
+    pub moved: Vec<bool>, // indexed by node
+}
+
+impl FrameChanges {
+    // `rank_threshold` is the rank change beyond which a node counts as moved
+    pub fn between(previous_weights: &[f64], weights: &[f64], previous_ranks: &[f64], ranks: &[f64], rank_threshold: f64, faded_weight: f64) -> Self {
+        let edges = previous_weights
+            .iter()
+            .zip(weights)
+            .enumerate()
+            .filter_map(|(id, (&before, &now))| {
+                if before == 0.0 && now > 0.0 {
+                    Some((EdgeId(id), EdgeChange::Added))
+                } else if before >= faded_weight && now < faded_weight {
+                    Some((EdgeId(id), EdgeChange::Faded))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let moved = previous_ranks.iter().zip(ranks).map(|(before, now)| (now - before).abs() > rank_threshold).collect();
+        FrameChanges { edges, moved }
+    }
+
+    pub fn borders(&self) -> Vec<Option<&'static str>> {
+        self.moved.iter().map(|&moved| moved.then_some(MOVED_BORDER)).collect()
+    }
+}
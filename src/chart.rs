@@ -0,0 +1,93 @@
+use std::io::{self, Write};
+
+use crate::history::RankHistory;
+use crate::leaderboard::rank_order;
+use crate::types::NodeId;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 500.0;
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 140.0; // room for the legend
+const MARGIN_TOP: f64 = 50.0;
+const MARGIN_BOTTOM: f64 = 60.0;
+const NUM_OF_TICKS: usize = 5;
+
+// Categorical palette (Tableau 10)
+const PALETTE: [&str; 10] = [
+    "#4E79A7", "#F28E2B", "#E15759", "#76B7B2", "#59A14F",
+    "#EDC948", "#B07AA1", "#FF9DA7", "#9C755F", "#BAB0AC",
+];
+
+// Nodes with the highest rank in the last frame, ties broken by `tie_keys` (see rank_order)
+pub fn top_nodes(history: &RankHistory, count: usize, tie_keys: &[f64]) -> Vec<NodeId> {
+    let Some(last) = history.frames().last() else { return Vec::new() };
+    let mut nodes = rank_order(last, tie_keys);
+    nodes.truncate(count);
+    nodes
+}
+
+// Line chart of rank versus time for the given nodes, as a standalone SVG document
+pub fn write_rank_chart(out: &mut dyn Write, history: &RankHistory, nodes: &[NodeId], title: &str) -> io::Result<()> {
+    let times = history.times();
+    let (t_min, t_max) = match (times.first(), times.last()) {
+        (Some(first), Some(last)) => (first.value(), last.value()),
+        _ => (0.0, 1.0),
+    };
+    let t_span = if t_max > t_min { t_max - t_min } else { 1.0 };
+    let max_rank = nodes
+        .iter()
+        .flat_map(|&node| history.trajectory(node))
+        .map(|(_, rank)| rank)
+        .fold(0.0, f64::max);
+    // Round the rank axis up to the next tenth
+    let r_max = ((max_rank * 10.0).ceil() / 10.0).max(0.1);
+
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let x = |t: f64| MARGIN_LEFT + (t - t_min) / t_span * plot_width;
+    let y = |r: f64| MARGIN_TOP + (1.0 - r / r_max) * plot_height;
+
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"sans-serif\">", WIDTH, HEIGHT, WIDTH, HEIGHT)?;
+    writeln!(out, "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+    writeln!(out, "  <text x=\"{}\" y=\"30\" font-size=\"18\">{}</text>", MARGIN_LEFT, escape(title))?;
+
+    // Axes, grid lines and tick labels
+    let bottom = MARGIN_TOP + plot_height;
+    let right = MARGIN_LEFT + plot_width;
+    writeln!(out, "  <line x1=\"{0:.1}\" y1=\"{1:.1}\" x2=\"{0:.1}\" y2=\"{2:.1}\" stroke=\"black\"/>", MARGIN_LEFT, MARGIN_TOP, bottom)?;
+    writeln!(out, "  <line x1=\"{0:.1}\" y1=\"{1:.1}\" x2=\"{2:.1}\" y2=\"{1:.1}\" stroke=\"black\"/>", MARGIN_LEFT, bottom, right)?;
+    for i in 0..=NUM_OF_TICKS {
+        let fraction = i as f64 / NUM_OF_TICKS as f64;
+        let r = r_max * fraction;
+        writeln!(out, "  <line x1=\"{0:.1}\" y1=\"{1:.1}\" x2=\"{2:.1}\" y2=\"{1:.1}\" stroke=\"#DDDDDD\"/>", MARGIN_LEFT, y(r), right)?;
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" text-anchor=\"end\">{:.2}</text>", MARGIN_LEFT - 8.0, y(r) + 4.0, r)?;
+        let t = t_min + t_span * fraction;
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" text-anchor=\"middle\">{}</text>", x(t), bottom + 20.0, format_tick(t))?;
+    }
+    writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"14\" text-anchor=\"middle\">Time</text>", MARGIN_LEFT + plot_width / 2.0, HEIGHT - 15.0)?;
+    writeln!(out, "  <text x=\"20\" y=\"{0:.1}\" font-size=\"14\" text-anchor=\"middle\" transform=\"rotate(-90 20 {0:.1})\">Rank</text>", MARGIN_TOP + plot_height / 2.0)?;
+
+    // One polyline per node, with a legend entry
+    for (i, &node) in nodes.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let points: Vec<String> = history
+            .trajectory(node)
+            .iter()
+            .map(|&(t, r)| format!("{:.1},{:.1}", x(t.value()), y(r)))
+            .collect();
+        writeln!(out, "  <polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>", color, points.join(" "))?;
+        let legend_y = MARGIN_TOP + 20.0 * i as f64;
+        writeln!(out, "  <line x1=\"{0:.1}\" y1=\"{1:.1}\" x2=\"{2:.1}\" y2=\"{1:.1}\" stroke=\"{3}\" stroke-width=\"3\"/>", right + 15.0, legend_y, right + 35.0, color)?;
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\">node {}</text>", right + 42.0, legend_y + 4.0, node)?;
+    }
+
+    writeln!(out, "</svg>")
+}
+
+fn format_tick(value: f64) -> String {
+    if value.fract() == 0.0 { format!("{}", value) } else { format!("{:.2}", value) }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,137 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::decay::DecayKernel;
+use crate::graph::Edge;
+use crate::hashing::bytes_hash;
+use crate::json::Json;
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::style::RenderStyle;
+use crate::types::{NodeId, Time};
+
+// Saved state of a scenario run, so that an interrupted run can continue where it stopped.
+// The file is JSON lines: a header with the run's fingerprint, frame times and full edge list
+// (including simulated edges, so the random process is not replayed), then one line per completed
+// frame, appended and flushed as soon as the frame is ranked.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub fingerprint: u64,
+    pub frame_times: Vec<Time>,
+    pub edges: Vec<Edge>,
+    pub output: RunOutput, // completed frames
+}
+
+// Identifies the inputs of a run: the scenario as loaded (before simulation) apart from its render
+// style and decay threads, the ranking settings and the frame times. A checkpoint with another fingerprint is not
+// resumed, and cached ranks are filed under it.
+pub fn run_fingerprint(scenario: &Scenario, settings: &str, frame_times: &[Time]) -> u64 {
+    let scenario = Scenario { style: RenderStyle::default(), decay_kernel: DecayKernel { threads: 0, ..scenario.decay_kernel }, ..scenario.clone() };
+    bytes_hash(format!("{:?}|{}|{:?}", scenario, settings, frame_times).as_bytes())
+}
+
+impl Checkpoint {
+    pub fn path(folder: &Path, scenario_name: &str) -> PathBuf {
+        folder.join(format!("{}.checkpoint.jsonl", scenario_name))
+    }
+
+    // Cached ranks of a complete run, named after its fingerprint
+    pub fn cache_path(folder: &Path, fingerprint: u64) -> PathBuf {
+        folder.join(format!("{:016x}.jsonl", fingerprint))
+    }
+
+    // Reads a checkpoint; a last line cut off by the interruption is ignored
+    pub fn read(text: &str) -> Result<Checkpoint, String> {
+        let mut lines = text.split_inclusive('\n').filter(|line| line.ends_with('\n'));
+        let header = Json::parse(lines.next().ok_or("checkpoint is empty")?.trim())?;
+        let fingerprint = header.get("fingerprint").and_then(Json::as_str).and_then(|f| u64::from_str_radix(f, 16).ok()).ok_or("checkpoint has no fingerprint")?;
+        let frame_times = numbers(header.get("frame_times"))?.into_iter().map(Time).collect();
+        let edges = header
+            .get("edges")
+            .and_then(Json::as_array)
+            .ok_or("checkpoint has no edges")?
+            .iter()
+            .map(|edge| match numbers(Some(edge))?.as_slice() {
+                &[source, target, time, weight] => Ok(Edge { source: NodeId(source as usize), target: NodeId(target as usize), time_of_creation: Time(time), weight }),
+                _ => Err("invalid checkpoint edge".to_string()),
+            })
+            .collect::<Result<_, String>>()?;
+
+        let mut output = RunOutput::new();
+        for line in lines {
+            let frame = Json::parse(line.trim())?;
+            let time = frame.get("time").and_then(Json::as_f64).ok_or("checkpoint frame has no time")?;
+            let hash = frame.get("hash").and_then(Json::as_str).and_then(|h| u64::from_str_radix(h, 16).ok()).ok_or("checkpoint frame has no hash")?;
+            output.history.push(Time(time), numbers(frame.get("ranks"))?);
+            output.edge_weights.push(numbers(frame.get("weights"))?);
+            output.frame_hashes.push(hash);
+            output.convergence.push(None);
+            output.pruned_edges.push(frame.get("pruned").and_then(Json::as_f64).ok_or("checkpoint frame has no pruned edge count")? as usize);
+        }
+        Ok(Checkpoint { fingerprint, frame_times, edges, output })
+    }
+}
+
+fn numbers(value: Option<&Json>) -> Result<Vec<f64>, String> {
+    let invalid = || "invalid number list in checkpoint".to_string();
+    value.and_then(Json::as_array).ok_or_else(invalid)?.iter().map(|v| v.as_f64().ok_or_else(invalid)).collect()
+}
+
+// Writes a whole checkpoint, e.g. the stored results of `trust-flow run`
+pub fn write_checkpoint(out: &mut dyn Write, checkpoint: &Checkpoint) -> io::Result<()> {
+    write_header(out, checkpoint)?;
+    for frame in 0..checkpoint.output.history.num_of_frames() {
+        write_frame(out, &checkpoint.output, frame)?;
+    }
+    Ok(())
+}
+
+fn write_header(out: &mut dyn Write, checkpoint: &Checkpoint) -> io::Result<()> {
+    let edges = checkpoint
+        .edges
+        .iter()
+        .map(|e| Json::numbers(&[e.source.index() as f64, e.target.index() as f64, e.time_of_creation.value(), e.weight]))
+        .collect();
+    let header = Json::object(vec![
+        ("fingerprint", format!("{:016x}", checkpoint.fingerprint).into()),
+        ("frame_times", Json::numbers(&checkpoint.frame_times.iter().map(|t| t.value()).collect::<Vec<_>>())),
+        ("edges", Json::Array(edges)),
+    ]);
+    writeln!(out, "{}", header)
+}
+
+fn write_frame(out: &mut dyn Write, output: &RunOutput, frame: usize) -> io::Result<()> {
+    let line = Json::object(vec![
+        ("time", output.history.times()[frame].value().into()),
+        ("hash", format!("{:016x}", output.frame_hashes[frame]).into()),
+        ("ranks", Json::numbers(&output.history.frames()[frame])),
+        ("weights", Json::numbers(&output.edge_weights[frame])),
+        ("pruned", output.pruned_edges[frame].into()),
+    ]);
+    writeln!(out, "{}", line)
+}
+
+// Appends completed frames to a checkpoint file
+pub struct CheckpointWriter {
+    file: File,
+}
+
+impl CheckpointWriter {
+    // Starts the file over with the given state, which may already contain completed frames
+    pub fn create(path: &Path, checkpoint: &Checkpoint) -> io::Result<Self> {
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+        let mut file = File::create(path)?;
+        write_checkpoint(&mut file, checkpoint)?;
+        file.sync_data()?;
+        Ok(CheckpointWriter { file })
+    }
+
+    // Appends the newest frame of the run
+    pub fn push(&mut self, output: &RunOutput) -> io::Result<()> {
+        write_frame(&mut self.file, output, output.history.num_of_frames() - 1)?;
+        self.file.flush()
+    }
+}
@@ -0,0 +1,73 @@
+use crate::graph::CompiledGraph;
+use crate::rank::{edge_fraction, outflow_normalizers, rankable_inputs, redistribute_dangling, RankConfig, RankingAlgorithm};
+use crate::registry::SOURCE_CLIPPED;
+
+// PageRank variant that blunts single-source domination: per step, no source brings a node more
+// than `max_share` of the rank (mass) the node receives along its edges. A whale endorser's flow
+// into a node is cut down to that share of the node's inflow before clipping, and the excess is
+// teleported, while teleportation and dangling rank work as in the custom variant. The total stays
+// 1, and a share of 1 gives the custom variant's ranks (reward and penalty events aside). A node
+// endorsed by a single source keeps only `max_share` of what it receives from it.
+pub struct SourceClipped {
+    pub config: RankConfig, // damping factor, iterations, tolerance and outflow normalization
+    pub max_share: f64,
+}
+
+impl RankingAlgorithm for SourceClipped {
+    fn name(&self) -> &str {
+        SOURCE_CLIPPED.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Vec<f64> {
+        let (graph, teleportation_targets) = rankable_inputs(graph, teleportation_targets);
+        let (graph, teleportation_targets) = (graph.as_ref(), teleportation_targets.as_ref());
+        let num_of_nodes = graph.num_of_nodes;
+        let normalizers = outflow_normalizers(graph, self.config.outflow_normalization);
+        let total_teleport: f64 = teleportation_targets.iter().sum();
+        let teleport_share = |i: usize| if total_teleport > 0.0 { teleportation_targets[i] / total_teleport } else { 1.0 / num_of_nodes as f64 };
+        // Edges by target and source, so that parallel edges of a pair are clipped together
+        let mut order: Vec<usize> = (0..graph.edges.len()).collect();
+        order.sort_by_key(|&i| (graph.edges[i].target, graph.edges[i].source));
+
+        let mut ranks = match &self.config.initial_ranks {
+            Some(initial_ranks) => initial_ranks.clone(),
+            None => graph.uniform_ranks(),
+        };
+        for _ in 0..self.config.num_of_iterations {
+            let flows: Vec<f64> = graph
+                .edges
+                .iter()
+                .map(|e| self.config.damping_factor * ranks[e.source.index()] * edge_fraction(e.weight, normalizers[e.source.index()]))
+                .collect();
+            let mut received = vec![0.0; num_of_nodes];
+            let mut dangling: Vec<f64> = ranks.iter().map(|&r| self.config.damping_factor * r).collect();
+            for (e, &flow) in graph.edges.iter().zip(&flows) {
+                received[e.target.index()] += flow;
+                dangling[e.source.index()] -= flow;
+            }
+            let dangling_inflow = redistribute_dangling(graph, &dangling, teleportation_targets, self.config.dangling_policy);
+
+            let mut delivered = vec![0.0; num_of_nodes];
+            for pair in order.chunk_by(|&a, &b| (graph.edges[a].target, graph.edges[a].source) == (graph.edges[b].target, graph.edges[b].source)) {
+                let target = graph.edges[pair[0]].target.index();
+                let flow: f64 = pair.iter().map(|&i| flows[i]).sum();
+                delivered[target] += flow.min(self.max_share * received[target]);
+            }
+
+            let excess = received.iter().sum::<f64>() - delivered.iter().sum::<f64>();
+            let new_ranks: Vec<f64> = (0..num_of_nodes)
+                .map(|i| (1.0 - self.config.damping_factor) * teleportation_targets[i] + dangling_inflow[i] + delivered[i] + excess * teleport_share(i))
+                .collect();
+            let residual: f64 = new_ranks.iter().zip(&ranks).map(|(new, old)| (new - old).abs()).sum();
+            ranks = new_ranks;
+            if residual < self.config.tolerance {
+                break;
+            }
+        }
+        ranks
+    }
+
+    fn with_damping(&self, damping_factor: f64) -> Option<Box<dyn RankingAlgorithm>> {
+        Some(Box::new(SourceClipped { config: self.config.clone().damping_factor(damping_factor), max_share: self.max_share }))
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::nodes::NodeInfo;
+use crate::types::NodeId;
+
+// What groups nodes into the Graphviz clusters of a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterBy {
+    Community, // detected in every frame, clusters change over time
+    Category,  // node metadata
+    Org,       // node metadata
+}
+
+impl ClusterBy {
+    pub fn parse(text: &str) -> Result<ClusterBy, String> {
+        match text {
+            "community" => Ok(ClusterBy::Community),
+            "category" => Ok(ClusterBy::Category),
+            "org" => Ok(ClusterBy::Org),
+            _ => Err(format!("unknown clustering: {} (expected community, category or org)", text)),
+        }
+    }
+}
+
+// Light background colors of clusters, in the order of the community border palette
+const BACKGROUNDS: [&str; 10] = ["#deebf7", "#fee6ce", "#e5f5e0", "#fde0dd", "#efedf5", "#f3e5dc", "#fde0ef", "#f0f0f0", "#f7fcb9", "#e0f3f8"];
+
+// A labeled box with a background color around a group of nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub label: String,
+    pub background: &'static str,
+    pub nodes: Vec<NodeId>,
+}
+
+// One cluster per category or org, in name order; nodes without one stay outside every cluster
+pub fn metadata_clusters(node_info: &[NodeInfo], by: ClusterBy) -> Vec<Cluster> {
+    let mut groups: BTreeMap<&str, Vec<NodeId>> = BTreeMap::new();
+    for (i, info) in node_info.iter().enumerate() {
+        let group = match by {
+            ClusterBy::Category => info.category.as_deref(),
+            ClusterBy::Org => info.org.as_deref(),
+            ClusterBy::Community => None,
+        };
+        if let Some(group) = group {
+            groups.entry(group).or_default().push(NodeId(i));
+        }
+    }
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(i, (label, nodes))| Cluster { label: label.to_string(), background: BACKGROUNDS[i % BACKGROUNDS.len()], nodes })
+        .collect()
+}
+
+// One cluster per community of more than one node, colored by community id so that a community
+// tracked across frames keeps its color (the same as its border color)
+pub fn community_clusters(communities: &[usize]) -> Vec<Cluster> {
+    let mut members: HashMap<usize, Vec<NodeId>> = HashMap::new();
+    for (node, &community) in communities.iter().enumerate() {
+        members.entry(community).or_default().push(NodeId(node));
+    }
+    let mut clusters: Vec<(usize, Vec<NodeId>)> = members.into_iter().filter(|(_, nodes)| nodes.len() > 1).collect();
+    clusters.sort_by_key(|&(community, _)| community);
+    clusters
+        .into_iter()
+        .map(|(community, nodes)| Cluster { label: format!("community {}", community), background: BACKGROUNDS[community % BACKGROUNDS.len()], nodes })
+        .collect()
+}
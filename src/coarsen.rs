@@ -0,0 +1,94 @@
+use crate::communities::label_propagation;
+use crate::graph::{compile_graph, Edge, GraphOptions, ParallelEdgePolicy, SelfLoopPolicy};
+use crate::nodes::NodeInfo;
+use crate::random::Rng;
+use crate::scenario::{EdgeCorrection, ExpertAging, ExpertTerm, RankEvent, Scenario};
+use crate::script::ScenarioScripts;
+use crate::types::NodeId;
+
+// How a scenario is reduced for a fast, low-fidelity preview of its rank dynamics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coarsening {
+    SampleEdges { fraction: f64 }, // every edge is kept with this probability and weighs 1 / fraction times as much
+    Communities,                   // every community of the graph of all edges becomes a single node
+}
+
+impl Coarsening {
+    // `sample:0.1` or `communities`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.split_once(':') {
+            None if text == "communities" => Ok(Coarsening::Communities),
+            Some(("sample", fraction)) => match fraction.parse::<f64>() {
+                Ok(fraction) if fraction > 0.0 && fraction <= 1.0 => Ok(Coarsening::SampleEdges { fraction }),
+                _ => Err(format!("the sampled fraction of edges must be in (0, 1]: {}", fraction)),
+            },
+            _ => Err(format!("unknown coarsening: {} (expected sample:FRACTION or communities)", text)),
+        }
+    }
+}
+
+// The preview scenario, named after the original with a -preview suffix. Its assertions are
+// dropped, as they state ranks of the full graph.
+pub fn coarsen(scenario: &Scenario, coarsening: Coarsening) -> Scenario {
+    let name = format!("{}-preview", scenario.name);
+    match coarsening {
+        Coarsening::SampleEdges { fraction } => {
+            // Seeded by the scenario, so a preview is reproducible
+            let mut rng = Rng::new(scenario.seed);
+            let kept: Vec<usize> = (0..scenario.edges.len()).filter(|_| rng.chance(fraction)).collect();
+            let select = |values: &[Option<usize>]| -> Vec<Option<usize>> { kept.iter().map(|&id| values.get(id).copied().flatten()).collect() };
+            Scenario {
+                name,
+                edges: kept.iter().map(|&id| Edge { weight: scenario.edges[id].weight / fraction, ..scenario.edges[id] }).collect(),
+                edge_sources: kept.iter().filter_map(|&id| scenario.edge_sources.get(id).cloned()).collect(),
+                edge_category: select(&scenario.edge_category),
+                edge_layer: select(&scenario.edge_layer),
+                assertions: Vec::new(),
+                ..scenario.clone()
+            }
+        }
+        Coarsening::Communities => collapse_communities(scenario, name),
+    }
+}
+
+// Communities come from label propagation on the graph of every edge at its weight at creation.
+// Edges keep their times, weights and categories and join the communities of their ends; edges
+// within a community become self-loops, so the trust they carry stays inside. Experts, expert terms
+// and reward and penalty events move to their nodes' communities, and each community is labeled
+// by its first node and its size. Node metadata, blacklists, scripts and simulations are dropped.
+fn collapse_communities(scenario: &Scenario, name: String) -> Scenario {
+    let weights: Vec<f64> = scenario.edges.iter().map(|e| e.weight).collect();
+    let graph = compile_graph(&scenario.edges, &weights, scenario.num_of_nodes, &GraphOptions::default()).unwrap();
+    let communities = label_propagation(&graph);
+    let num_of_communities = communities.iter().max().map_or(0, |&c| c + 1);
+    let community = |node: NodeId| NodeId(communities[node.index()]);
+
+    let mut experts: Vec<NodeId> = scenario.experts.iter().map(|&node| community(node)).collect();
+    experts.sort();
+    experts.dedup();
+    let node_info = (0..num_of_communities)
+        .map(|c| {
+            let members: Vec<usize> = (0..scenario.num_of_nodes).filter(|&node| communities[node] == c).collect();
+            let first = scenario.node_label(NodeId(members[0]));
+            let name = if members.len() > 1 { format!("{} +{}", first, members.len() - 1) } else { first };
+            NodeInfo { name: Some(name), ..NodeInfo::default() }
+        })
+        .collect();
+    Scenario {
+        name,
+        num_of_nodes: num_of_communities,
+        experts,
+        expert_terms: scenario.expert_terms.iter().map(|term| ExpertTerm { node: community(term.node), ..*term }).collect(),
+        expert_aging: scenario.expert_aging.iter().map(|aging| ExpertAging { node: community(aging.node), ..*aging }).collect(),
+        node_info,
+        edges: scenario.edges.iter().map(|e| Edge { source: community(e.source), target: community(e.target), ..*e }).collect(),
+        rank_events: scenario.rank_events.iter().map(|event| RankEvent { node: community(event.node), ..*event }).collect(),
+        corrections: scenario.corrections.iter().map(|c| EdgeCorrection { source: community(c.source), target: community(c.target), ..*c }).collect(),
+        graph_options: GraphOptions { self_loops: SelfLoopPolicy::Keep, parallel_edges: ParallelEdgePolicy::SumWeights, ..scenario.graph_options },
+        blacklist: Vec::new(),
+        assertions: Vec::new(),
+        scripts: ScenarioScripts::default(),
+        simulation: None,
+        ..scenario.clone()
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::json::Json;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// What makes a community of a frame a suspected collusion ring
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RingCriteria {
+    pub max_size: usize, // largest number of nodes; rings have at least 2
+    pub min_density: f64, // share of the ordered pairs of members joined by a weighted edge
+    pub max_external: f64, // largest share of the members' incoming weight that comes from outside
+}
+
+impl Default for RingCriteria {
+    fn default() -> Self {
+        RingCriteria { max_size: 6, min_density: 0.5, max_external: 0.2 }
+    }
+}
+
+// A small group that endorses itself densely and is hardly endorsed by anyone else
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollusionRing {
+    pub nodes: Vec<NodeId>,
+    pub density: f64,
+    pub external_share: f64,
+}
+
+// Rings among the communities of a frame's graph (e.g. by label propagation), in order of their
+// first node. Edges count with their current weight; self-loops are ignored.
+pub fn find_rings(graph: &CompiledGraph, communities: &[usize], criteria: &RingCriteria) -> Vec<CollusionRing> {
+    let num_of_communities = communities.iter().max().map_or(0, |&c| c + 1);
+    let mut members: Vec<Vec<NodeId>> = vec![Vec::new(); num_of_communities];
+    for (node, &community) in communities.iter().enumerate() {
+        members[community].push(NodeId(node));
+    }
+    let mut linked_pairs: Vec<HashSet<(NodeId, NodeId)>> = vec![HashSet::new(); num_of_communities];
+    let (mut internal, mut external) = (vec![0.0; num_of_communities], vec![0.0; num_of_communities]);
+    for edge in graph.edges.iter().filter(|e| e.weight > 0.0 && e.source != e.target) {
+        let community = communities[edge.target.index()];
+        if communities[edge.source.index()] == community {
+            internal[community] += edge.weight;
+            linked_pairs[community].insert((edge.source, edge.target));
+        } else {
+            external[community] += edge.weight;
+        }
+    }
+    (0..num_of_communities)
+        .filter(|&c| (2..=criteria.max_size).contains(&members[c].len()))
+        .map(|c| {
+            let size = members[c].len() as f64;
+            let density = linked_pairs[c].len() as f64 / (size * (size - 1.0));
+            let incoming = internal[c] + external[c];
+            let external_share = if incoming > 0.0 { external[c] / incoming } else { 0.0 };
+            CollusionRing { nodes: members[c].clone(), density, external_share }
+        })
+        .filter(|ring| ring.density >= criteria.min_density && ring.external_share <= criteria.max_external)
+        .collect()
+}
+
+// One line per frame with its rings and the rank they hold together
+pub fn write_rings_jsonl(out: &mut dyn Write, times: &[Time], rings: &[Vec<CollusionRing>], ranks: &[Vec<f64>], labels: &[String]) -> io::Result<()> {
+    for (frame, ((time, rings), ranks)) in times.iter().zip(rings).zip(ranks).enumerate() {
+        let rings = rings
+            .iter()
+            .map(|ring| {
+                Json::object(vec![
+                    ("nodes", Json::Array(ring.nodes.iter().map(|n| labels[n.index()].as_str().into()).collect())),
+                    ("density", ring.density.into()),
+                    ("external_share", ring.external_share.into()),
+                    ("rank", ring.nodes.iter().map(|n| ranks[n.index()]).sum::<f64>().into()),
+                ])
+            })
+            .collect();
+        writeln!(out, "{}", Json::object(vec![("frame", FrameIdx(frame).number().into()), ("time", time.value().into()), ("rings", Json::Array(rings))]))?;
+    }
+    Ok(())
+}
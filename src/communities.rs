@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::types::{FrameIdx, Time};
+
+// Upper bound on label propagation sweeps; it usually settles within a handful
+const MAX_SWEEPS: usize = 100;
+
+// Communities of one frame's graph by weighted label propagation (Raghavan, Albert and Kumara,
+// 2007) on the undirected graph of current edge weights. Nodes are visited in index order and
+// adopt the label with the largest total weight among their neighbors, keeping their own label
+// or else taking the smallest on ties, so the result is deterministic. Communities are numbered
+// from 0 in order of their first node; nodes without weighted edges form their own community.
+pub fn label_propagation(graph: &CompiledGraph) -> Vec<usize> {
+    let n = graph.num_of_nodes;
+    let mut neighbors: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+    for edge in graph.edges.iter().filter(|e| e.weight > 0.0 && e.source != e.target) {
+        neighbors[edge.source.index()].push((edge.target.index(), edge.weight));
+        neighbors[edge.target.index()].push((edge.source.index(), edge.weight));
+    }
+
+    let mut labels: Vec<usize> = (0..n).collect();
+    for _ in 0..MAX_SWEEPS {
+        let mut changed = false;
+        for node in 0..n {
+            let mut weights: HashMap<usize, f64> = HashMap::new();
+            for &(neighbor, weight) in &neighbors[node] {
+                *weights.entry(labels[neighbor]).or_insert(0.0) += weight;
+            }
+            let Some(best) = weights.values().copied().reduce(f64::max) else { continue };
+            if weights.get(&labels[node]) == Some(&best) {
+                continue;
+            }
+            let label = weights.iter().filter(|&(_, &w)| w == best).map(|(&l, _)| l).min().unwrap();
+            labels[node] = label;
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    renumber(&labels)
+}
+
+fn renumber(labels: &[usize]) -> Vec<usize> {
+    let mut ids: HashMap<usize, usize> = HashMap::new();
+    labels
+        .iter()
+        .map(|&label| {
+            let next = ids.len();
+            *ids.entry(label).or_insert(next)
+        })
+        .collect()
+}
+
+// Keeps community ids stable across frames: each community of a new frame takes the id of the
+// previous frame's community it shares the most nodes with, unless a larger community claimed
+// it first; communities without a match get fresh ids
+#[derive(Debug, Clone, Default)]
+pub struct CommunityTracker {
+    previous: Vec<usize>,
+    next_id: usize,
+}
+
+impl CommunityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(&mut self, communities: &[usize]) -> Vec<usize> {
+        let num_of_communities = communities.iter().map(|&c| c + 1).max().unwrap_or(0);
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); num_of_communities];
+        for (node, &community) in communities.iter().enumerate() {
+            members[community].push(node);
+        }
+        let mut order: Vec<usize> = (0..num_of_communities).collect();
+        order.sort_by_key(|&c| std::cmp::Reverse(members[c].len()));
+
+        let mut ids = vec![usize::MAX; num_of_communities];
+        let mut taken: Vec<usize> = Vec::new();
+        for community in order {
+            let mut overlaps: HashMap<usize, usize> = HashMap::new();
+            for &node in members[community].iter().filter(|&&node| node < self.previous.len()) {
+                *overlaps.entry(self.previous[node]).or_insert(0) += 1;
+            }
+            let best = overlaps
+                .into_iter()
+                .filter(|(id, _)| !taken.contains(id))
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+                .map(|(id, _)| id);
+            ids[community] = best.unwrap_or_else(|| {
+                self.next_id += 1;
+                self.next_id - 1
+            });
+            taken.push(ids[community]);
+        }
+        self.next_id = self.next_id.max(taken.iter().map(|&id| id + 1).max().unwrap_or(0));
+        self.previous = communities.iter().map(|&c| ids[c]).collect();
+        self.previous.clone()
+    }
+}
+
+// Graphviz colors of community borders, repeating for more communities
+const PALETTE: [&str; 10] = ["#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf"];
+
+// Border color of every node; nodes alone in their community get none
+pub fn community_borders(communities: &[usize]) -> Vec<Option<&'static str>> {
+    let mut sizes: HashMap<usize, usize> = HashMap::new();
+    for &community in communities {
+        *sizes.entry(community).or_insert(0) += 1;
+    }
+    communities.iter().map(|c| (sizes[c] > 1).then_some(PALETTE[c % PALETTE.len()])).collect()
+}
+
+// frame,time,node,community rows; community ids are tracked across frames
+pub fn write_communities_csv(out: &mut dyn Write, times: &[Time], communities: &[Vec<usize>], labels: &[String]) -> io::Result<()> {
+    writeln!(out, "frame,time,node,community")?;
+    for (frame, (time, assignment)) in times.iter().zip(communities).enumerate() {
+        for (node, community) in assignment.iter().enumerate() {
+            writeln!(out, "{},{},{},{}", FrameIdx(frame).number(), time, labels[node], community)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+// Rank correlation measures for comparing the output of different ranking algorithms
+
+// Rank values that differ by less than this are treated as ties,
+// otherwise floating point noise decides the order of equally trusted nodes
+const TIE_TOLERANCE: f64 = 1e-12;
+
+fn tied(a: f64, b: f64) -> bool {
+    (a - b).abs() < TIE_TOLERANCE
+}
+
+// Ranks of the values (1 = smallest), ties get the average of the ranks they span
+fn fractional_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && tied(values[order[end]], values[order[start]]) {
+            end += 1;
+        }
+        let average_rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = average_rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_b = 0.0;
+    for (&x, &y) in a.iter().zip(b.iter()) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return f64::NAN;
+    }
+    covariance / (variance_a * variance_b).sqrt()
+}
+
+// Spearman rank correlation; NaN when either vector is constant
+pub fn spearman(a: &[f64], b: &[f64]) -> f64 {
+    pearson(&fractional_ranks(a), &fractional_ranks(b))
+}
+
+// Kendall tau-b (accounts for ties); NaN when either vector is constant
+pub fn kendall_tau(a: &[f64], b: &[f64]) -> f64 {
+    let mut concordant: f64 = 0.0;
+    let mut discordant = 0.0;
+    let mut ties_a = 0.0;
+    let mut ties_b = 0.0;
+    for i in 0..a.len() {
+        for j in (i + 1)..a.len() {
+            let da = if tied(a[i], a[j]) { 0.0 } else { (a[i] - a[j]).signum() };
+            let db = if tied(b[i], b[j]) { 0.0 } else { (b[i] - b[j]).signum() };
+            if da == 0.0 && db == 0.0 {
+                continue;
+            } else if da == 0.0 {
+                ties_a += 1.0;
+            } else if db == 0.0 {
+                ties_b += 1.0;
+            } else if da == db {
+                concordant += 1.0;
+            } else {
+                discordant += 1.0;
+            }
+        }
+    }
+    let denominator = ((concordant + discordant + ties_a) * (concordant + discordant + ties_b)).sqrt();
+    if denominator == 0.0 {
+        return f64::NAN;
+    }
+    (concordant - discordant) / denominator
+}
@@ -0,0 +1,68 @@
+use std::io::{self, Write};
+
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::types::NodeId;
+
+// Cypher statements that load a ranked graph into Neo4j, e.g. with
+// `cypher-shell -f scenario.cypher`. Nodes become (:TrustNode {scenario, id}) and edges
+// [:TRUSTS {id}] relationships; per-frame values are list properties aligned with the
+// graph's `frame_times` (`ranks` on nodes, `weights` on edges). Statements MERGE on the
+// scenario name and ids, so loading an export again updates the graph instead of duplicating it.
+pub fn write_cypher(out: &mut dyn Write, scenario: &Scenario, output: &RunOutput) -> io::Result<()> {
+    let name = string(&scenario.name);
+    let times: Vec<f64> = output.history.times().iter().map(|t| t.value()).collect();
+    writeln!(out, "// trust-flow export of {}", scenario.name.replace('\n', " "))?;
+    writeln!(out, "CREATE INDEX trust_node IF NOT EXISTS FOR (n:TrustNode) ON (n.scenario, n.id);")?;
+    writeln!(out, "MERGE (g:TrustGraph {{scenario: {}}}) SET g.decay = {}, g.seed = {}, g.frame_times = {};",
+        name, string(&scenario.decay_description()), string(&scenario.seed.to_string()), list(&times))?;
+
+    for node in (0..scenario.num_of_nodes).map(NodeId) {
+        let ranks: Vec<f64> = output.history.frames().iter().map(|ranks| ranks[node.index()]).collect();
+        let category = scenario.node_info(node).and_then(|info| info.category.as_deref()).map_or("null".to_string(), string);
+        writeln!(out, "MERGE (n:TrustNode {{scenario: {}, id: {}}}) SET n.label = {}, n.category = {}, n.expert = {}, n.ranks = {}, n.rank = {};",
+            name, node, string(&scenario.node_label(node)), category, scenario.experts.contains(&node), list(&ranks), ranks.last().copied().map_or("null".to_string(), number))?;
+    }
+
+    for (id, edge) in scenario.edges.iter().enumerate() {
+        let weights: Vec<f64> = output.edge_weights.iter().map(|weights| weights[id]).collect();
+        let end = scenario.edge_end_time(edge).map_or("null".to_string(), |end| number(end.value()));
+        writeln!(out, "MATCH (a:TrustNode {{scenario: {}, id: {}}}), (b:TrustNode {{scenario: {}, id: {}}}) MERGE (a)-[r:TRUSTS {{id: {}}}]->(b) SET r.created = {}, r.end = {}, r.weight = {}, r.weights = {};",
+            name, edge.source, name, edge.target, id, number(edge.time_of_creation.value()), end, number(edge.weight), list(&weights))?;
+    }
+    Ok(())
+}
+
+// Single-quoted Cypher string literal
+fn string(text: &str) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push('\'');
+    for c in text.chars() {
+        match c {
+            '\'' => literal.push_str("\\'"),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            _ => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+// Cypher has no literals for non-finite floats; those become null
+fn number(value: f64) -> String {
+    if !value.is_finite() {
+        "null".to_string()
+    } else if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{:.1}", value) // keeps the property a float
+    } else {
+        value.to_string()
+    }
+}
+
+fn list(values: &[f64]) -> String {
+    let items: Vec<String> = values.iter().map(|&v| number(v)).collect();
+    format!("[{}]", items.join(", "))
+}
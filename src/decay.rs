@@ -0,0 +1,190 @@
+use std::f64::consts::{LN_2, LOG2_E};
+use std::fmt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+use crate::graph::Edge;
+use crate::types::Time;
+
+// Edges decayed per batch: their creation times and initial weights are gathered into two arrays
+// (struct of arrays), so the exponentials run as one branch-free loop the compiler can vectorize
+const BATCH: usize = 256;
+
+// Below this many edges, rayon's threads cost more than they save
+pub const PARALLEL_DECAY_MIN_EDGES: usize = 1 << 16;
+
+// Ages a decay table covers at most (8 MB of factors); older edges are decayed directly
+pub const MAX_DECAY_TABLE_AGES: usize = 1 << 20;
+
+// Decay constant under which weights halve every `half_life` time units, e^(-k half_life) = 1/2.
+// Both exponentials of the kernel take the same constant; fast_exp halves within its 2e-7 error.
+pub fn decay_constant_for_half_life(half_life: f64) -> f64 {
+    LN_2 / half_life
+}
+
+// Time units in which weights halve under the decay constant; None when nothing decays
+pub fn half_life(decay_constant: f64) -> Option<f64> {
+    (decay_constant > 0.0).then(|| LN_2 / decay_constant)
+}
+
+// Half-life for labels and summaries, to 3 decimals: "halves every 3.466"
+pub fn describe_half_life(decay_constant: f64) -> Option<String> {
+    half_life(decay_constant).map(|h| format!("halves every {}", (h * 1000.0).round() / 1000.0))
+}
+
+// How edge weights are decayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecayKernel {
+    pub fast_exp: bool, // fast_exp instead of f64::exp, for very large graphs
+    pub threads: usize, // 0 for rayon's global pool (one thread per core) once there are PARALLEL_DECAY_MIN_EDGES edges
+}
+
+impl DecayKernel {
+    // Weights of the edges at the given time under exponential decay, indexed by EdgeId; edges
+    // that do not exist yet weigh 0
+    pub fn decayed_weights(&self, edges: &[Edge], time: Time, decay_constant: f64) -> Vec<f64> {
+        self.decayed_weights_with(edges, time, decay_constant, None)
+    }
+
+    // As decayed_weights, taking the decay factors of whole ages from the table where it has them
+    pub fn decayed_weights_with(&self, edges: &[Edge], time: Time, decay_constant: f64, table: Option<&DecayTable>) -> Vec<f64> {
+        let table = table.filter(|t| t.fits(self, decay_constant));
+        let mut weights = vec![0.0; edges.len()];
+        if edges.is_empty() || self.threads == 1 || (self.threads == 0 && edges.len() < PARALLEL_DECAY_MIN_EDGES) {
+            decay_into(self, edges, &mut weights, time, decay_constant, table);
+            return weights;
+        }
+        // Whole batches per task, so only the last batch is partial
+        let decay = |weights: &mut [f64]| edges.par_chunks(BATCH).zip(weights.par_chunks_mut(BATCH)).for_each(|(edges, weights)| decay_into(self, edges, weights, time, decay_constant, table));
+        match self.threads {
+            0 => decay(&mut weights),
+            threads => pool(threads).install(|| decay(&mut weights)),
+        }
+        weights
+    }
+
+    // e^x with the kernel's exponential
+    pub fn exp(&self, x: f64) -> f64 {
+        if self.fast_exp { fast_exp(x) } else { x.exp() }
+    }
+}
+
+// Decay factors e^(-k age) of the whole ages 0, 1, ..., max_age. When timestamps are discrete
+// ticks, edges and frames only ever see these ages, so every factor is computed once and then
+// looked up. A factor is the same number the kernel computes for that age.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecayTable {
+    decay_constant: f64,
+    fast_exp: bool,
+    factors: Vec<f64>,
+}
+
+impl DecayTable {
+    pub fn new(kernel: &DecayKernel, decay_constant: f64, max_age: usize) -> Self {
+        let factors = (0..=max_age.min(MAX_DECAY_TABLE_AGES - 1)).map(|age| kernel.exp(-(age as f64) * decay_constant)).collect();
+        DecayTable { decay_constant, fast_exp: kernel.fast_exp, factors }
+    }
+
+    // A table for the ages edges created at the given times reach by the given end time; none when
+    // some edge is created between ticks, or nothing decays
+    pub fn for_creation_times(kernel: &DecayKernel, decay_constant: f64, times: impl IntoIterator<Item = Time>, end: Time) -> Option<Self> {
+        if decay_constant == 0.0 {
+            return None;
+        }
+        let (mut first, mut last) = (f64::INFINITY, end.value());
+        for time in times {
+            if time.value().fract() != 0.0 {
+                return None;
+            }
+            first = first.min(time.value());
+            last = last.max(time.value());
+        }
+        if first == f64::INFINITY {
+            return None;
+        }
+        Some(DecayTable::new(kernel, decay_constant, (last.ceil() - first) as usize))
+    }
+
+    // The factor of the age, if it is a whole age the table covers
+    pub fn factor(&self, age: f64) -> Option<f64> {
+        if age >= 0.0 && age.fract() == 0.0 { self.factors.get(age as usize).copied() } else { None }
+    }
+
+    pub fn max_age(&self) -> usize {
+        self.factors.len() - 1
+    }
+
+    // Whether the table was computed for this decay constant and exponential
+    pub fn fits(&self, kernel: &DecayKernel, decay_constant: f64) -> bool {
+        self.decay_constant == decay_constant && self.fast_exp == kernel.fast_exp
+    }
+}
+
+// A decay table built on first use and shared by clones made after that. Its Debug output leaves
+// the table out, so it does not change a scenario's fingerprint.
+#[derive(Clone, Default)]
+pub struct DecayTableCache(OnceLock<Option<DecayTable>>);
+
+impl DecayTableCache {
+    pub fn get_or_build(&self, build: impl FnOnce() -> Option<DecayTable>) -> Option<&DecayTable> {
+        self.0.get_or_init(build).as_ref()
+    }
+}
+
+impl fmt::Debug for DecayTableCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("DecayTableCache")
+    }
+}
+
+// Pool of the given number of threads, built on first use and kept for the later frames
+fn pool(threads: usize) -> Arc<ThreadPool> {
+    static POOLS: OnceLock<Mutex<HashMap<usize, Arc<ThreadPool>>>> = OnceLock::new();
+    let mut pools = POOLS.get_or_init(Default::default).lock().unwrap();
+    let pool = pools.entry(threads).or_insert_with(|| Arc::new(rayon::ThreadPoolBuilder::new().num_threads(threads).build().expect("failed to start the decay threads")));
+    Arc::clone(pool)
+}
+
+fn decay_into(kernel: &DecayKernel, edges: &[Edge], weights: &mut [f64], time: Time, decay_constant: f64, table: Option<&DecayTable>) {
+    let mut ages = [0.0; BATCH];
+    let mut initial = [0.0; BATCH];
+    for (edges, weights) in edges.chunks(BATCH).zip(weights.chunks_mut(BATCH)) {
+        for ((age, w0), e) in ages.iter_mut().zip(&mut initial).zip(edges) {
+            *age = time.value() - e.time_of_creation.value();
+            *w0 = e.weight;
+        }
+        let n = edges.len();
+        let batch = weights.iter_mut().zip(&ages[..n]).zip(&initial[..n]);
+        // Negative ages are edges created later, which weigh 0
+        if let Some(table) = table {
+            batch.for_each(|((w, &age), &w0)| *w = if age >= 0.0 { w0 * table.factor(age).unwrap_or_else(|| kernel.exp(-age * decay_constant)) } else { 0.0 });
+        } else if kernel.fast_exp {
+            batch.for_each(|((w, &age), &w0)| *w = if age >= 0.0 { w0 * fast_exp_negative(-age * decay_constant) } else { 0.0 });
+        } else {
+            batch.for_each(|((w, &age), &w0)| *w = if age >= 0.0 { w0 * (-age * decay_constant).exp() } else { 0.0 });
+        }
+    }
+}
+
+// e^x for x <= 0 with a relative error below 2e-7, several times faster than f64::exp: x = n ln 2 + r
+// with |r| <= ln 2 / 2, e^r from its Taylor polynomial of degree 6, and 2^n from the exponent bits.
+// Results too small for a normal f64 are 0.
+pub fn fast_exp(x: f64) -> f64 {
+    if x > 0.0 {
+        return 1.0 / fast_exp_negative(-x);
+    }
+    fast_exp_negative(x)
+}
+
+fn fast_exp_negative(x: f64) -> f64 {
+    if x < -708.0 || x.is_nan() {
+        return 0.0;
+    }
+    let n = (x * LOG2_E).round();
+    let r = x - n * LN_2;
+    let p = 1.0 + r * (1.0 + r * (1.0 / 2.0 + r * (1.0 / 6.0 + r * (1.0 / 24.0 + r * (1.0 / 120.0 + r * (1.0 / 720.0))))));
+    p * f64::from_bits(((n as i64 + 1023) as u64) << 52)
+}
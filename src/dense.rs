@@ -0,0 +1,147 @@
+use crate::graph::CompiledGraph;
+use crate::rank::{edge_fraction, outflow_normalizers, OutflowNormalization};
+
+// Square matrix for the dense backend, the exact solver and the diagnostics. With the nalgebra
+// feature it is a nalgebra DMatrix, whose products and LU decomposition are vectorized; without it,
+// plain row-major loops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseMatrix {
+    #[cfg(feature = "nalgebra")]
+    matrix: nalgebra::DMatrix<f64>,
+    #[cfg(not(feature = "nalgebra"))]
+    size: usize,
+    #[cfg(not(feature = "nalgebra"))]
+    values: Vec<f64>, // row-major
+}
+
+#[cfg(feature = "nalgebra")]
+impl DenseMatrix {
+    pub fn zeros(size: usize) -> Self {
+        DenseMatrix { matrix: nalgebra::DMatrix::zeros(size, size) }
+    }
+
+    pub fn size(&self) -> usize {
+        self.matrix.nrows()
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        self.matrix[(row, column)]
+    }
+
+    pub fn add(&mut self, row: usize, column: usize, value: f64) {
+        self.matrix[(row, column)] += value;
+    }
+
+    pub fn mul_vec(&self, vector: &[f64]) -> Vec<f64> {
+        (&self.matrix * nalgebra::DVector::from_column_slice(vector)).data.into()
+    }
+
+    // Solution of self · x = rhs by LU decomposition with partial pivoting; None when the matrix
+    // is singular
+    pub fn solve(&self, rhs: &[f64]) -> Option<Vec<f64>> {
+        if self.size() == 0 {
+            return Some(Vec::new()); // nalgebra's triangular solvers do not take empty systems
+        }
+        let lu = self.matrix.clone().lu();
+        if (0..self.size()).any(|i| lu.u()[(i, i)].abs() < f64::EPSILON) {
+            return None;
+        }
+        Some(lu.solve(&nalgebra::DVector::from_column_slice(rhs))?.data.into())
+    }
+}
+
+#[cfg(not(feature = "nalgebra"))]
+impl DenseMatrix {
+    pub fn zeros(size: usize) -> Self {
+        DenseMatrix { size, values: vec![0.0; size * size] }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        self.values[row * self.size + column]
+    }
+
+    pub fn add(&mut self, row: usize, column: usize, value: f64) {
+        self.values[row * self.size + column] += value;
+    }
+
+    pub fn mul_vec(&self, vector: &[f64]) -> Vec<f64> {
+        self.values
+            .chunks_exact(self.size.max(1))
+            .map(|row| row.iter().zip(vector).map(|(a, b)| a * b).sum())
+            .collect()
+    }
+
+    // Solution of self · x = rhs by LU decomposition with partial pivoting; None when the matrix
+    // is singular
+    pub fn solve(&self, rhs: &[f64]) -> Option<Vec<f64>> {
+        let n = self.size;
+        let mut lu = self.values.clone();
+        let mut x = rhs.to_vec();
+        for column in 0..n {
+            let pivot = (column..n).max_by(|&a, &b| lu[a * n + column].abs().total_cmp(&lu[b * n + column].abs()))?;
+            if lu[pivot * n + column].abs() < f64::EPSILON {
+                return None;
+            }
+            if pivot != column {
+                for k in 0..n {
+                    lu.swap(pivot * n + k, column * n + k);
+                }
+                x.swap(pivot, column);
+            }
+            for row in column + 1..n {
+                let factor = lu[row * n + column] / lu[column * n + column];
+                if factor != 0.0 {
+                    for k in column..n {
+                        lu[row * n + k] -= factor * lu[column * n + k];
+                    }
+                    x[row] -= factor * x[column];
+                }
+            }
+        }
+        for row in (0..n).rev() {
+            let sum: f64 = (row + 1..n).map(|k| lu[row * n + k] * x[k]).sum();
+            x[row] = (x[row] - sum) / lu[row * n + row];
+        }
+        Some(x)
+    }
+}
+
+impl DenseMatrix {
+    // Edge flow operator of a frame: entry (target, source) is the fraction of the source's
+    // rank (mass) that flows to the target per step before damping, i.e. weight / normalizer
+    pub fn edge_flow(graph: &CompiledGraph, normalization: OutflowNormalization) -> Self {
+        let normalizers = outflow_normalizers(graph, normalization);
+        let mut matrix = DenseMatrix::zeros(graph.num_of_nodes);
+        for edge in &graph.edges {
+            matrix.add(edge.target.index(), edge.source.index(), edge_fraction(edge.weight, normalizers[edge.source.index()]));
+        }
+        matrix
+    }
+
+    // Largest absolute eigenvalue, estimated by power iteration. For the edge flow operator times
+    // the damping factor this is the factor by which the error of the rank iteration shrinks per
+    // step once transients have died out.
+    pub fn spectral_radius(&self, num_of_iterations: usize) -> f64 {
+        if self.size() == 0 {
+            return 0.0;
+        }
+        // Not uniform, so that the start vector is unlikely to be orthogonal to the dominant eigenvector
+        let mut vector: Vec<f64> = (0..self.size()).map(|i| 1.0 + i as f64 / self.size() as f64).collect();
+        let mut radius = 0.0;
+        for _ in 0..num_of_iterations {
+            let next = self.mul_vec(&vector);
+            let norm = next.iter().map(|v| v.abs()).sum::<f64>();
+            let previous_norm = vector.iter().map(|v| v.abs()).sum::<f64>();
+            if norm == 0.0 {
+                return 0.0; // nilpotent, e.g. a directed acyclic graph
+            }
+            radius = norm / previous_norm;
+            vector = next.iter().map(|v| v / norm).collect();
+        }
+        radius
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::{self, Write};
+
+use crate::history::RankHistory;
+use crate::json::Json;
+use crate::types::{FrameIdx, NodeId};
+
+// A node whose rank moved by more than the threshold since it was last reported
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankDelta {
+    pub node: NodeId,
+    pub rank: f64,
+    pub change: f64, // relative to the last reported rank
+}
+
+// Turns a sequence of full rank vectors into a stream of deltas for downstream sync.
+// Changes are measured against the last *reported* rank of each node rather than the previous
+// snapshot, so slow drifts are still reported once they add up and a consumer applying the
+// deltas never ends up more than the threshold away from the actual ranks.
+#[derive(Debug, Clone)]
+pub struct RankDiffer {
+    threshold: f64,
+    reported: Vec<f64>,
+}
+
+impl RankDiffer {
+    pub fn new(threshold: f64) -> Self {
+        RankDiffer { threshold, reported: Vec::new() }
+    }
+
+    // Nodes seen for the first time are reported relative to 0, so the first snapshot
+    // contains every node with a rank above the threshold
+    pub fn diff(&mut self, ranks: &[f64]) -> Vec<RankDelta> {
+        if self.reported.len() < ranks.len() {
+            self.reported.resize(ranks.len(), 0.0);
+        }
+        let mut deltas = Vec::new();
+        for (i, &rank) in ranks.iter().enumerate() {
+            let change = rank - self.reported[i];
+            if change.abs() > self.threshold {
+                self.reported[i] = rank;
+                deltas.push(RankDelta { node: NodeId(i), rank, change });
+            }
+        }
+        deltas
+    }
+}
+
+// One JSON line per snapshot: {"frame":2,"time":1,"changes":[{"node":3,"rank":0.21,"delta":0.05}]}.
+// Snapshots without changes are skipped entirely.
+pub fn write_diff_line(out: &mut dyn Write, frame: FrameIdx, time: f64, deltas: &[RankDelta]) -> io::Result<()> {
+    if deltas.is_empty() {
+        return Ok(());
+    }
+    let changes = deltas
+        .iter()
+        .map(|d| Json::object(vec![("node", d.node.index().into()), ("rank", d.rank.into()), ("delta", d.change.into())]))
+        .collect();
+    let line = Json::object(vec![("frame", frame.number().into()), ("time", time.into()), ("changes", Json::Array(changes))]);
+    writeln!(out, "{}", line)
+}
+
+pub fn write_diff_stream(out: &mut dyn Write, history: &RankHistory, threshold: f64) -> io::Result<()> {
+    let mut differ = RankDiffer::new(threshold);
+    for (frame, (time, ranks)) in history.times().iter().zip(history.frames()).enumerate() {
+        write_diff_line(out, FrameIdx(frame), time.value(), &differ.diff(ranks))?;
+    }
+    Ok(())
+}
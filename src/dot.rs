@@ -0,0 +1,429 @@
+use std::io::{self, Write};
+
+use crate::changes::EdgeChange;
+use crate::clusters::Cluster;
+use crate::graph::Edge;
+use crate::style::{ColorScale, RenderStyle};
+use crate::types::{EdgeId, FrameIdx, NodeId};
+
+// A frame to draw: the graph with its ranks, and what goes on top of it. Only the ranks, edges,
+// weights, names and positions are required; everything else starts out empty, which leaves it
+// out, and is set with the builder methods.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameRender<'a> {
+    pub node_ranks: &'a [f64],
+    pub edges: &'a [Edge],
+    pub weights: &'a [f64],
+    pub names: &'a [String],
+    pub positions: &'a [(f64, f64)],
+    pub experts: &'a [NodeId],
+    pub frame: FrameIdx,
+    pub total_frames: usize,
+    pub algorithm: &'a str, // the title's second line
+    pub decay_desc: &'a str,
+    pub annotations: &'a [&'a str],
+    pub active: &'a [bool],                  // per node whether it is in the graph at the frame's time; the others are invisible but keep their place
+    pub pinned: &'a [bool],                  // per node whether it stays at its position; the others start there and are moved by the layout engine
+    pub borders: &'a [Option<&'a str>],      // border color per node (trust tier, community or rank change), drawn on non-experts
+    pub deltas: &'a [f64],                   // rank change per node, shown in the labels
+    pub clusters: &'a [Cluster],
+    pub highlighted: &'a [EdgeId],           // drawn in HIGHLIGHT_COLOR, e.g. the paths of an explanation
+    pub changed: &'a [(EdgeId, EdgeChange)], // drawn in the color of their change since the previous frame, even once inactive
+    pub upcoming: &'a [bool],                // per edge, drawn as dashed ghosts as wide as their initial weight
+    pub flux: &'a [f64],                     // rank each edge carries, shown by the edge's color and label
+    pub tooltips: &'a [String],              // per edge
+    pub alarms: &'a [String],                // shown in a red banner above the graph
+}
+
+impl Default for FrameRender<'_> {
+    fn default() -> Self {
+        FrameRender {
+            node_ranks: &[],
+            edges: &[],
+            weights: &[],
+            names: &[],
+            positions: &[],
+            experts: &[],
+            frame: FrameIdx(0),
+            total_frames: 1,
+            algorithm: "",
+            decay_desc: "",
+            annotations: &[],
+            active: &[],
+            pinned: &[],
+            borders: &[],
+            deltas: &[],
+            clusters: &[],
+            highlighted: &[],
+            changed: &[],
+            upcoming: &[],
+            flux: &[],
+            tooltips: &[],
+            alarms: &[],
+        }
+    }
+}
+
+impl<'a> FrameRender<'a> {
+    pub fn new(node_ranks: &'a [f64], edges: &'a [Edge], weights: &'a [f64], names: &'a [String], positions: &'a [(f64, f64)]) -> Self {
+        FrameRender { node_ranks, edges, weights, names, positions, ..Self::default() }
+    }
+
+    pub fn experts(mut self, experts: &'a [NodeId]) -> Self {
+        self.experts = experts;
+        self
+    }
+
+    pub fn frame(mut self, frame: FrameIdx, total_frames: usize) -> Self {
+        self.frame = frame;
+        self.total_frames = total_frames;
+        self
+    }
+
+    pub fn title(mut self, algorithm: &'a str, decay_desc: &'a str) -> Self {
+        self.algorithm = algorithm;
+        self.decay_desc = decay_desc;
+        self
+    }
+
+    pub fn annotations(mut self, annotations: &'a [&'a str]) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    pub fn active(mut self, active: &'a [bool]) -> Self {
+        self.active = active;
+        self
+    }
+
+    pub fn pinned(mut self, pinned: &'a [bool]) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    pub fn borders(mut self, borders: &'a [Option<&'a str>]) -> Self {
+        self.borders = borders;
+        self
+    }
+
+    pub fn deltas(mut self, deltas: &'a [f64]) -> Self {
+        self.deltas = deltas;
+        self
+    }
+
+    pub fn clusters(mut self, clusters: &'a [Cluster]) -> Self {
+        self.clusters = clusters;
+        self
+    }
+
+    pub fn highlighted(mut self, highlighted: &'a [EdgeId]) -> Self {
+        self.highlighted = highlighted;
+        self
+    }
+
+    pub fn changed(mut self, changed: &'a [(EdgeId, EdgeChange)]) -> Self {
+        self.changed = changed;
+        self
+    }
+
+    pub fn upcoming(mut self, upcoming: &'a [bool]) -> Self {
+        self.upcoming = upcoming;
+        self
+    }
+
+    pub fn flux(mut self, flux: &'a [f64]) -> Self {
+        self.flux = flux;
+        self
+    }
+
+    pub fn tooltips(mut self, tooltips: &'a [String]) -> Self {
+        self.tooltips = tooltips;
+        self
+    }
+
+    pub fn alarms(mut self, alarms: &'a [String]) -> Self {
+        self.alarms = alarms;
+        self
+    }
+}
+
+pub fn write_dot(file: &mut dyn Write, frame: &FrameRender, style: &RenderStyle, scale: &ColorScale) -> io::Result<()> {
+    let FrameRender { node_ranks, edges, weights, names, positions, experts, frame: current_frame, total_frames, algorithm, decay_desc, annotations, active, pinned, borders, deltas, clusters, highlighted, changed, upcoming, flux, tooltips, alarms } = *frame;
+    writeln!(file, "digraph G {{")?;
+    writeln!(file, "  nodesep=0.8;")?;
+    writeln!(file, "  graph [{}];", style.graph_attributes())?;
+    writeln!(file, "  labelloc=\"t\";")?;
+    writeln!(file, "  labeljust=\"l\";")?;
+    writeln!(file, "  labelfontsize={};", style.title_font_size)?;
+    writeln!(file, "  label=\"Trust flow over time\nAlgorithm: {}\nEdge decay: {}\nFrame: {}/{}{}\";", algorithm, decay_desc, current_frame.number(), total_frames, annotation_lines(annotations))?;
+    for (i, &rank) in node_ranks.iter().enumerate() {
+        let node = NodeId(i);
+        let fill_color = scale.color(rank);
+        let label_text = style.node_label(&names[i], rank);
+        let position = node_position(positions[i], pinned.get(i) != Some(&false));
+        let size = format!("{}{}{}", style.node_size(rank), scale.font_color(rank), deltas.get(i).map_or(String::new(), |&delta| style.rank_delta(delta)));
+        if active.get(i) == Some(&false) {
+            writeln!(file, "  {} [style=invis, shape=circle, {}];", node, position)?;
+        } else if experts.contains(&node) {
+            writeln!(file,
+                "  {} [label=\"{}\", shape=circle, style=filled, fillcolor=\"{}\", color=\"{}\", penwidth={}, fontsize={}{}, {}];",
+                node, label_text, fill_color, style.expert_color, style.expert_pen_width, style.font_size, size, position
+            )?;
+        } else {
+            let border = match borders.get(i).copied().flatten() {
+                Some(color) => format!(", color=\"{}\", penwidth={}", color, BORDER_PEN_WIDTH),
+                None => String::new(),
+            };
+            writeln!(file,
+                "  {} [label=\"{}\", shape=circle, style=filled, fillcolor=\"{}\"{}, fontsize={}{}, {}];",
+                node, label_text, fill_color, border, style.font_size, size, position
+            )?;
+        }
+    }
+    write_clusters(file, clusters, style)?;
+    write_alarm_banner(file, alarms, style.font_size)?;
+
+    // Edges left out by the style's edge sampling are drawn like edges without weight, unless they
+    // are highlighted or changed
+    let kept = style.edge_sampling.map_or(Vec::new(), |sampling| sampling.kept(node_ranks.len(), edges, weights));
+    let max_flux = flux.iter().copied().fold(0.0, f64::max);
+    for (id, (e, &w)) in edges.iter().zip(weights.iter()).enumerate() {
+        let sampled_out = kept.get(id) == Some(&false) && !highlighted.contains(&EdgeId(id)) && !changed.iter().any(|(changed, _)| *changed == EdgeId(id));
+        let w = if sampled_out { 0.0 } else { w };
+        let tooltip = tooltips.get(id).map_or(String::new(), |text| style.edge_tooltip(text));
+        if let Some(&(_, change)) = changed.iter().find(|(changed, _)| *changed == EdgeId(id)) {
+            let edgewidth = style.edge_width.width(w, style.edge_width_scale).max(CHANGE_MIN_WIDTH);
+            let dashes = if change == EdgeChange::Faded { ", style=dashed" } else { "" };
+            writeln!(file,"  {} -> {} [penwidth={}, color=\"{}\"{}{}];", e.source, e.target, edgewidth, change.color(), dashes, tooltip)?;
+        } else if w == 0.0 && upcoming.get(id) == Some(&true) {
+            let edgewidth = style.edge_width.width(e.weight, style.edge_width_scale);
+            writeln!(file,"  {} -> {} [penwidth={}, color=\"{}\", style=dashed{}];", e.source, e.target, edgewidth, GHOST_COLOR, tooltip)?;
+        } else if w == 0.0 {
+            if style.invisible_edges {
+                writeln!(file,"  {} -> {} [style=invis];", e.source, e.target)?;
+            }
+        } else if highlighted.contains(&EdgeId(id)) {
+            let edgewidth = style.edge_width.width(w, style.edge_width_scale).max(HIGHLIGHT_MIN_WIDTH);
+            writeln!(file,"  {} -> {} [penwidth={}, color=\"{}\"{}];", e.source, e.target, edgewidth, HIGHLIGHT_COLOR, tooltip)?;
+        } else {
+            let edgewidth = style.edge_width.width(w, style.edge_width_scale);
+            let flux = flux.get(id).map_or(String::new(), |&flux| style.edge_flux(flux, max_flux));
+            writeln!(file,"  {} -> {} [penwidth={}{}{}];", e.source, e.target, edgewidth, flux, tooltip)?;
+        }
+    }
+
+    if style.legend {
+        let active_edges = weights.iter().filter(|&&w| w > 0.0).count();
+        let mut parameters = vec![
+            format!("Nodes: {} ({} experts)", node_ranks.len() - active.iter().filter(|&&a| !a).count(), experts.len()),
+            format!("Active edges: {}/{}", active_edges, edges.len()),
+            format!("Colors: {}, {}", scale.colormap.name(), style.normalization.name()),
+        ];
+        if let Some(sampling) = style.edge_sampling {
+            let drawn = weights.iter().zip(&kept).filter(|&(&w, &kept)| w > 0.0 && kept).count();
+            parameters.insert(2, format!("Drawn edges: {} ({})", drawn, sampling.describe()));
+        }
+        write_legend(file, scale, &parameters, LEGEND_X, style.font_size)?;
+    }
+
+    writeln!(file, "}}")
+}
+
+// Small multiples: one copy of the graph per algorithm, laid out side by side. The frame's own
+// ranks and overlays are not drawn; its title line is the subtitle.
+pub fn write_dot_small_multiples(file: &mut dyn Write, panels: &[(&str, Vec<f64>)], frame: &FrameRender, style: &RenderStyle, scale: &ColorScale) -> io::Result<()> {
+    let FrameRender { edges, weights, experts, names, positions, frame: current_frame, total_frames, algorithm: subtitle, decay_desc, annotations, .. } = *frame;
+    let panel_offset = 3.0;
+    let kept = style.edge_sampling.map_or(Vec::new(), |sampling| sampling.kept(positions.len(), edges, weights));
+    writeln!(file, "digraph G {{")?;
+    writeln!(file, "  nodesep=0.8;")?;
+    writeln!(file, "  graph [{}];", style.graph_attributes())?;
+    writeln!(file, "  labelloc=\"t\";")?;
+    writeln!(file, "  labeljust=\"l\";")?;
+    writeln!(file, "  labelfontsize={};", style.title_font_size)?;
+    writeln!(file, "  label=\"Trust flow over time\n{}\nEdge decay: {}\nFrame: {}/{}{}\";", subtitle, decay_desc, current_frame.number(), total_frames, annotation_lines(annotations))?;
+    for (p, (algorithm, node_ranks)) in panels.iter().enumerate() {
+        let x_offset = panel_offset * p as f64;
+        writeln!(file,
+            "  panel{} [label=\"{}\", shape=plaintext, fontsize=24, pos=\"{:.2},{:.2}!\", pin=true];",
+            p, algorithm, x_offset, 1.6
+        )?;
+        for (i, &rank) in node_ranks.iter().enumerate() {
+            let node = NodeId(i);
+            let fill_color = scale.color(rank);
+            let label_text = style.node_label(&names[i], rank);
+            let (x, y) = positions[i];
+            let expert_style = if experts.contains(&node) {
+                format!(", color=\"{}\", penwidth={}", style.expert_color, style.expert_pen_width)
+            } else {
+                String::new()
+            };
+            writeln!(file,
+                "  p{}_{} [label=\"{}\", shape=circle, style=filled, fillcolor=\"{}\"{}, fontsize={}{}{}, pos=\"{:.2},{:.2}!\", pin=true];",
+                p, node, label_text, fill_color, expert_style, style.font_size, style.node_size(rank), scale.font_color(rank), x + x_offset, y
+            )?;
+        }
+        for ((e, &w), kept) in edges.iter().zip(weights.iter()).zip(kept.iter().chain(std::iter::repeat(&true))) {
+            if w == 0.0 || !kept {
+                if style.invisible_edges {
+                    writeln!(file,"  p{}_{} -> p{}_{} [style=invis];", p, e.source, p, e.target)?;
+                }
+            } else {
+                let edgewidth = style.edge_width.width(w, style.edge_width_scale);
+                writeln!(file,"  p{}_{} -> p{}_{} [penwidth={}];", p, e.source, p, e.target, edgewidth)?;
+            }
+        }
+    }
+
+    if style.legend {
+        let active_edges = weights.iter().filter(|&&w| w > 0.0).count();
+        let parameters = [
+            format!("Nodes: {} ({} experts)", positions.len(), experts.len()),
+            format!("Active edges: {}/{}", active_edges, edges.len()),
+            format!("Colors: {}, {}", scale.colormap.name(), style.normalization.name()),
+        ];
+        let x = panel_offset * (panels.len() - 1) as f64 + LEGEND_X;
+        write_legend(file, scale, &parameters, x, style.font_size)?;
+    }
+
+    writeln!(file, "}}")
+}
+
+// Categorical colors of expert groups (Tableau 10)
+pub const GROUP_COLORS: [&str; 10] = ["#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf"];
+
+// One graph whose nodes are pies of the expert groups' shares of their rank, each group in its
+// GROUP_COLORS color, so that the reach of several expert sets can be compared in one picture.
+// `shares` holds every node's split over the groups; the frame's ranks are the nodes' mean ranks
+// over them, which size and label them. Members of any group get the expert border.
+pub fn write_dot_group_shares(file: &mut dyn Write, groups: &[String], shares: &[Vec<f64>], frame: &FrameRender, style: &RenderStyle) -> io::Result<()> {
+    let FrameRender { node_ranks: ranks, edges, weights, experts, names, positions, frame: current_frame, total_frames, decay_desc, annotations, .. } = *frame;
+    let kept = style.edge_sampling.map_or(Vec::new(), |sampling| sampling.kept(positions.len(), edges, weights));
+    writeln!(file, "digraph G {{")?;
+    writeln!(file, "  graph [{}];", style.graph_attributes())?;
+    writeln!(file, "  labelloc=\"t\";")?;
+    writeln!(file, "  labeljust=\"l\";")?;
+    writeln!(file, "  labelfontsize={};", style.title_font_size)?;
+    writeln!(file, "  label=\"Trust flow over time\nExpert group shares\nEdge decay: {}\nFrame: {}/{}{}\";", decay_desc, current_frame.number(), total_frames, annotation_lines(annotations))?;
+    for (i, &rank) in ranks.iter().enumerate() {
+        let node = NodeId(i);
+        // The last group takes what the rounded shares leave, as Graphviz refuses fractions above 1
+        let last = shares[i].len().saturating_sub(1);
+        let wedges: Vec<String> = shares[i]
+            .iter()
+            .enumerate()
+            .map(|(g, share)| if g < last { format!("{};{:.3}", GROUP_COLORS[g % GROUP_COLORS.len()], share) } else { GROUP_COLORS[g % GROUP_COLORS.len()].to_string() })
+            .collect();
+        let expert_style = if experts.contains(&node) {
+            format!(", color=\"{}\", penwidth={}", style.expert_color, style.expert_pen_width)
+        } else {
+            String::new()
+        };
+        writeln!(file,
+            "  {} [label=\"{}\", shape=circle, style=wedged, fillcolor=\"{}\"{}, fontsize={}{}, pos=\"{:.2},{:.2}!\", pin=true];",
+            node, style.node_label(&names[i], rank), wedges.join(":"), expert_style, style.font_size, style.node_size(rank), positions[i].0, positions[i].1
+        )?;
+    }
+    for ((e, &w), kept) in edges.iter().zip(weights.iter()).zip(kept.iter().chain(std::iter::repeat(&true))) {
+        if w == 0.0 || !kept {
+            if style.invisible_edges {
+                writeln!(file, "  {} -> {} [style=invis];", e.source, e.target)?;
+            }
+        } else {
+            writeln!(file, "  {} -> {} [penwidth={}];", e.source, e.target, style.edge_width.width(w, style.edge_width_scale))?;
+        }
+    }
+    if style.legend {
+        let rows: String = groups
+            .iter()
+            .enumerate()
+            .map(|(g, group)| format!("<TR><TD BGCOLOR=\"{}\" WIDTH=\"18\" HEIGHT=\"18\"></TD><TD ALIGN=\"LEFT\">{}</TD></TR>", GROUP_COLORS[g % GROUP_COLORS.len()], group))
+            .collect();
+        writeln!(file,
+            "  legend [shape=plaintext, fontsize={}, pos=\"{:.2},{:.2}!\", pin=true, label=<<TABLE BORDER=\"0\" CELLSPACING=\"2\" CELLPADDING=\"2\"><TR><TD COLSPAN=\"2\">Expert groups</TD></TR>{}</TABLE>>];",
+            style.font_size * 0.6, LEGEND_X, 0.6, rows
+        )?;
+    }
+    writeln!(file, "}}")
+}
+
+// Pen width of tier and community borders
+const BORDER_PEN_WIDTH: f64 = 4.0;
+
+const HIGHLIGHT_COLOR: &str = "orangered";
+// Highlighted edges are drawn at least this wide, so that weak edges on a path stay visible
+const HIGHLIGHT_MIN_WIDTH: f64 = 3.0;
+// Changed edges are drawn at least this wide, so that a faded edge is still seen going away
+const CHANGE_MIN_WIDTH: f64 = 2.0;
+// Edges that are yet to be created
+const GHOST_COLOR: &str = "#c8c8c8";
+// Outline of cluster boxes, which are told apart by their background
+const CLUSTER_BORDER: &str = "#9e9e9e";
+
+// Position attributes of a node: pinned nodes keep it ("!"), free nodes only start from it
+fn node_position((x, y): (f64, f64), pinned: bool) -> String {
+    if pinned { format!("pos=\"{:.2},{:.2}!\", pin=true", x, y) } else { format!("pos=\"{:.2},{:.2}\"", x, y) }
+}
+
+const ALARM_COLOR: &str = "#C62828";
+// Vertical position of the alarm banner, above a graph laid out on the unit circle
+const ALARM_Y: f64 = 1.8;
+
+// Horizontal position of the legend, right of a graph laid out on the unit circle
+const LEGEND_X: f64 = 2.6;
+const LEGEND_STEPS: usize = 5;
+
+// Color legend with rank ticks, and below it a box of run parameters, pinned right of the graph
+fn write_legend(file: &mut dyn Write, scale: &ColorScale, parameters: &[String], x: f64, font_size: f64) -> io::Result<()> {
+    let positions: Vec<f64> = (0..LEGEND_STEPS).map(|i| i as f64 / (LEGEND_STEPS - 1) as f64).collect();
+    let cells: String = positions
+        .iter()
+        .map(|&p| format!("<TD BGCOLOR=\"{}\" WIDTH=\"30\" HEIGHT=\"18\"></TD>", scale.colormap.color(p)))
+        .collect();
+    let ticks: String = positions
+        .iter()
+        .map(|&p| {
+            let value = scale.value_at(p);
+            let tick = if value < 0.01 && value > 0.0 { format!("{:.0e}", value) } else { format!("{:.2}", value) };
+            format!("<TD>{}</TD>", tick)
+        })
+        .collect();
+    writeln!(file,
+        "  legend [shape=plaintext, fontsize={}, pos=\"{:.2},{:.2}!\", pin=true, label=<<TABLE BORDER=\"0\" CELLSPACING=\"0\" CELLPADDING=\"2\"><TR><TD COLSPAN=\"{}\">Rank</TD></TR><TR>{}</TR><TR>{}</TR></TABLE>>];",
+        font_size * 0.6, x, 0.6, LEGEND_STEPS, cells, ticks
+    )?;
+    let rows: String = parameters.iter().map(|p| format!("<TR><TD ALIGN=\"LEFT\">{}</TD></TR>", p)).collect();
+    writeln!(file,
+        "  parameters [shape=plaintext, fontsize={}, pos=\"{:.2},{:.2}!\", pin=true, label=<<TABLE BORDER=\"1\" CELLBORDER=\"0\" CELLSPACING=\"0\" CELLPADDING=\"3\">{}</TABLE>>];",
+        font_size * 0.6, x, -0.6, rows
+    )
+}
+
+// Scenario annotations as extra lines of a graph label, quotes escaped
+// Subgraphs naming the members of every cluster; the nodes themselves are declared before
+fn write_clusters(file: &mut dyn Write, clusters: &[Cluster], style: &RenderStyle) -> io::Result<()> {
+    for (i, cluster) in clusters.iter().enumerate() {
+        let members: String = cluster.nodes.iter().map(|node| format!(" {};", node)).collect();
+        writeln!(file,
+            "  subgraph cluster_{} {{ label=\"{}\"; style=\"filled,rounded\"; fillcolor=\"{}\"; color=\"{}\"; fontsize={};{} }}",
+            i, cluster.label.replace('\\', "\\\\").replace('"', "\\\""), cluster.background, CLUSTER_BORDER, style.font_size, members
+        )?;
+    }
+    Ok(())
+}
+
+// Messages of alarms raised in the frame, one per line, pinned above the graph
+fn write_alarm_banner(file: &mut dyn Write, alarms: &[String], font_size: f64) -> io::Result<()> {
+    if alarms.is_empty() {
+        return Ok(());
+    }
+    let text: Vec<String> = alarms.iter().map(|alarm| alarm.replace('\\', "\\\\").replace('"', "\\\"")).collect();
+    writeln!(file,
+        "  alarms [label=\"{}\", shape=box, style=filled, fillcolor=\"{}\", fontcolor=\"white\", fontsize={}, pos=\"{:.2},{:.2}!\", pin=true];",
+        text.join("\\n"), ALARM_COLOR, font_size, 0.0, ALARM_Y
+    )
+}
+
+fn annotation_lines(annotations: &[&str]) -> String {
+    annotations.iter().map(|text| format!("\n{}", text.replace('\\', "\\\\").replace('"', "\\\""))).collect()
+}
@@ -0,0 +1,207 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Seek, SeekFrom, Write};
+
+use memmap2::Mmap;
+
+use crate::decay::{DecayKernel, DecayTable};
+use crate::graph::{exponential_decay, Edge};
+use crate::input::EventParser;
+use crate::rank::{edge_fraction, DanglingPolicy, OutflowNormalization, RankConfig};
+use crate::types::{NodeId, Time};
+
+// Binary edge file for graphs too large for memory. Little-endian throughout:
+//
+//   header  "TFEDGES1", number of nodes (u64), flags (u64; bit 0: edges sorted by creation time)
+//   edges   source (u32), target (u32), time of creation (f64), weight at creation (f64)
+//
+// The file is memory-mapped, so the operating system pages edges in and out as they are
+// scanned and only per-node vectors are held in memory.
+const MAGIC: &[u8; 8] = b"TFEDGES1";
+const HEADER_LENGTH: usize = 24;
+const RECORD_LENGTH: usize = 24;
+const SORTED_BY_TIME: u64 = 1;
+
+// Writes edges one at a time, then the header once the number of nodes is known
+pub struct EdgeFileWriter {
+    out: BufWriter<File>,
+    num_of_nodes: usize,
+    num_of_edges: usize,
+    last_time: Time,
+    sorted: bool,
+}
+
+impl EdgeFileWriter {
+    pub fn create(path: &str) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(&[0; HEADER_LENGTH])?;
+        Ok(EdgeFileWriter { out, num_of_nodes: 0, num_of_edges: 0, last_time: Time(f64::NEG_INFINITY), sorted: true })
+    }
+
+    pub fn push(&mut self, edge: &Edge) -> io::Result<()> {
+        let node = |node: NodeId| u32::try_from(node.index()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("node {} does not fit 32 bits", node)));
+        self.out.write_all(&node(edge.source)?.to_le_bytes())?;
+        self.out.write_all(&node(edge.target)?.to_le_bytes())?;
+        self.out.write_all(&edge.time_of_creation.value().to_le_bytes())?;
+        self.out.write_all(&edge.weight.to_le_bytes())?;
+        self.num_of_nodes = self.num_of_nodes.max(edge.source.index().max(edge.target.index()) + 1);
+        self.sorted &= edge.time_of_creation >= self.last_time;
+        self.last_time = edge.time_of_creation;
+        self.num_of_edges += 1;
+        Ok(())
+    }
+
+    // Number of edges written
+    pub fn finish(self, min_num_of_nodes: usize) -> io::Result<usize> {
+        let mut file = self.out.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(MAGIC)?;
+        file.write_all(&(self.num_of_nodes.max(min_num_of_nodes) as u64).to_le_bytes())?;
+        file.write_all(&(if self.sorted { SORTED_BY_TIME } else { 0 }).to_le_bytes())?;
+        file.sync_all()?;
+        Ok(self.num_of_edges)
+    }
+}
+
+// Converts JSONL or CSV edge events to an edge file line by line, without holding the edges in memory.
+// Returns the number of edges.
+pub fn convert_events(input: &mut dyn BufRead, mut parser: EventParser, path: &str, min_num_of_nodes: usize) -> Result<usize, String> {
+    let mut writer = EdgeFileWriter::create(path).map_err(|e| e.to_string())?;
+    for (i, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if let Some(event) = parser.parse_line(&line).map_err(|e| format!("line {}: {}", i + 1, e))? {
+            writer.push(&event.edge).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+    }
+    writer.finish(min_num_of_nodes).map_err(|e| e.to_string())
+}
+
+pub struct EdgeFile {
+    map: Mmap,
+    pub num_of_nodes: usize,
+    pub sorted_by_time: bool,
+    pub reversed: bool, // every edge is read turned around, for files that record edges from endorsee to endorser
+}
+
+impl EdgeFile {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the map is read-only; like every reader of the file, it relies on nobody
+        // truncating or rewriting the file while it is open
+        let map = unsafe { Mmap::map(&file)? };
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message));
+        if map.len() < HEADER_LENGTH || &map[..8] != MAGIC {
+            return Err(invalid("not a trust-flow edge file"));
+        }
+        if !(map.len() - HEADER_LENGTH).is_multiple_of(RECORD_LENGTH) {
+            return Err(invalid("truncated edge record"));
+        }
+        let num_of_nodes = u64::from_le_bytes(map[8..16].try_into().unwrap()) as usize;
+        let flags = u64::from_le_bytes(map[16..24].try_into().unwrap());
+        Ok(EdgeFile { map, num_of_nodes, sorted_by_time: flags & SORTED_BY_TIME != 0, reversed: false })
+    }
+
+    pub fn len(&self) -> usize {
+        (self.map.len() - HEADER_LENGTH) / RECORD_LENGTH
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Edge> + '_ {
+        self.map[HEADER_LENGTH..].chunks_exact(RECORD_LENGTH).map(|record| {
+            let edge = Edge {
+                source: NodeId(u32::from_le_bytes(record[0..4].try_into().unwrap()) as usize),
+                target: NodeId(u32::from_le_bytes(record[4..8].try_into().unwrap()) as usize),
+                time_of_creation: Time(f64::from_le_bytes(record[8..16].try_into().unwrap())),
+                weight: f64::from_le_bytes(record[16..24].try_into().unwrap()),
+            };
+            if self.reversed { edge.reversed() } else { edge }
+        })
+    }
+
+    // Edges created up to the given time; stops reading at the first later edge when the file is sorted
+    fn edges_until(&self, time: Time) -> impl Iterator<Item = Edge> + '_ {
+        let sorted = self.sorted_by_time;
+        self.edges().take_while(move |e| !sorted || e.time_of_creation <= time).filter(move |e| e.time_of_creation <= time)
+    }
+}
+
+// Ranks the graph of an edge file out of core, one frame at a time, with the PageRank variant's
+// Jacobi steps (edge list, parallel edges and self-loops kept as they are, exponential decay).
+// Every step scans the mapped edges once and recomputes their decayed weights, looking up their
+// decay factors when the edges are created at whole times and a decay table has been built.
+pub struct OutOfCoreRanker<'a> {
+    file: &'a EdgeFile,
+    config: &'a RankConfig,
+    decay_constant: f64,
+    lifetime_normalizers: Vec<f64>, // sums of creation weights, of every edge in the file
+    decay_table: Option<DecayTable>,
+}
+
+impl<'a> OutOfCoreRanker<'a> {
+    pub fn new(file: &'a EdgeFile, config: &'a RankConfig, decay_constant: f64) -> Self {
+        let mut lifetime_normalizers = vec![0.0; file.num_of_nodes];
+        if config.outflow_normalization == OutflowNormalization::Lifetime {
+            for edge in file.edges() {
+                lifetime_normalizers[edge.source.index()] += edge.weight;
+            }
+        }
+        OutOfCoreRanker { file, config, decay_constant, lifetime_normalizers, decay_table: None }
+    }
+
+    // Precomputes the decay factors of every whole age frames up to the given time can see, with
+    // one more scan of the file; nothing is precomputed when some edge is created between ticks
+    pub fn decay_table_until(mut self, end: Time) -> Self {
+        self.decay_table = DecayTable::for_creation_times(&DecayKernel::default(), self.decay_constant, self.file.edges().map(|e| e.time_of_creation), end);
+        self
+    }
+
+    pub fn rank(&self, time: Time, teleportation_targets: &[f64]) -> Vec<f64> {
+        let num_of_nodes = self.file.num_of_nodes;
+        let damping_factor = self.config.damping_factor;
+        let weight = |edge: &Edge| match self.decay_table.as_ref().and_then(|t| t.factor(time.elapsed_since(edge.time_of_creation))) {
+            Some(factor) => edge.weight * factor,
+            None => exponential_decay(time, edge.time_of_creation, edge.weight, self.decay_constant),
+        };
+
+        let mut outflow = vec![0.0; num_of_nodes];
+        for edge in self.file.edges_until(time) {
+            outflow[edge.source.index()] += weight(&edge);
+        }
+        let normalizers: Vec<f64> = match self.config.outflow_normalization {
+            OutflowNormalization::Lifetime => self.lifetime_normalizers.clone(),
+            OutflowNormalization::Current => outflow.clone(),
+            OutflowNormalization::None => outflow.iter().map(|&o| o.max(1.0)).collect(),
+        };
+        let dangling_fraction: Vec<f64> = (0..num_of_nodes).map(|i| 1.0 - edge_fraction(outflow[i], normalizers[i])).collect();
+        let total_teleport: f64 = teleportation_targets.iter().sum();
+
+        let mut ranks = match &self.config.initial_ranks {
+            Some(initial_ranks) => initial_ranks.clone(),
+            None => vec![1.0 / num_of_nodes as f64; num_of_nodes],
+        };
+        for _ in 0..self.config.num_of_iterations {
+            let mut new_ranks: Vec<f64> = teleportation_targets.iter().map(|&t| (1.0 - damping_factor) * t).collect();
+            for edge in self.file.edges_until(time) {
+                let source = edge.source.index();
+                new_ranks[edge.target.index()] += damping_factor * ranks[source] * edge_fraction(weight(&edge), normalizers[source]);
+            }
+            let dangling: Vec<f64> = ranks.iter().zip(&dangling_fraction).map(|(r, d)| damping_factor * r * d).collect();
+            let dangling_total: f64 = dangling.iter().sum();
+            for (i, new_rank) in new_ranks.iter_mut().enumerate() {
+                *new_rank += match self.config.dangling_policy {
+                    DanglingPolicy::Uniform => dangling_total / num_of_nodes as f64,
+                    DanglingPolicy::Teleport => dangling_total * teleportation_targets[i] / total_teleport,
+                    DanglingPolicy::Retain => dangling[i],
+                };
+            }
+            let residual: f64 = new_ranks.iter().zip(&ranks).map(|(new, old)| (new - old).abs()).sum();
+            ranks = new_ranks;
+            if residual < self.config.tolerance {
+                break;
+            }
+        }
+        ranks
+    }
+}
@@ -0,0 +1,156 @@
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::rank::{edge_fraction, outflow_normalizers, RankConfig, RankIteration, Solver};
+use crate::scenario::Scenario;
+use crate::types::{EdgeId, NodeId, Time};
+
+// Rank (mass) that reaches a node along one of its incoming edges
+#[derive(Debug, Clone)]
+pub struct EdgeContribution {
+    pub source: NodeId,
+    pub edges: Vec<EdgeId>, // scenario edges merged into the compiled edge
+    pub amount: f64,
+}
+
+// "Why is this node ranked high?": the node's rank split into what one step of the PageRank
+// variant brings in from teleportation, from each incoming edge, from dangling rank and from
+// reward and penalty events. The step starts from the frame's ranks, so at the fixed point the
+// parts add up to the rank; otherwise they differ by the remaining iteration residual.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub node: NodeId,
+    pub time: Time,
+    pub rank: f64,
+    pub teleport: f64,
+    pub dangling: f64,
+    pub adjustment: f64,
+    pub edges: Vec<EdgeContribution>, // largest first
+}
+
+impl Explanation {
+    pub fn total(&self) -> f64 {
+        self.teleport + self.dangling + self.adjustment + self.edges.iter().map(|e| e.amount).sum::<f64>()
+    }
+}
+
+// Chain of edges along which rank reaches the explained node: the first edge's contribution,
+// scaled at every further edge by the share of the intermediate node's inflow that came that way
+#[derive(Debug, Clone)]
+pub struct ContributionPath {
+    pub nodes: Vec<NodeId>, // from the origin to the explained node
+    pub edges: Vec<EdgeId>, // scenario edges along the path
+    pub amount: f64,
+}
+
+// Decomposition of the whole frame, from which single nodes and paths are explained
+pub struct FrameExplainer<'a> {
+    scenario: &'a Scenario,
+    time: Time,
+    graph: CompiledGraph,
+    ranks: Vec<f64>,
+    inflow: Vec<f64>,       // per node, the sum of its parts
+    contributions: Vec<f64>, // per compiled edge
+    teleport: Vec<f64>,
+    dangling: Vec<f64>,
+    adjustment: Vec<f64>,
+}
+
+impl<'a> FrameExplainer<'a> {
+    // `ranks` are the frame's ranks as computed by the run
+    pub fn new(scenario: &'a Scenario, config: &RankConfig, time: Time, ranks: &[f64]) -> Self {
+        let edge_weights = scenario.edge_weights(time);
+        let graph = scenario.frame_graph(time, &edge_weights).unwrap();
+        let teleportation_targets = scenario.teleportation_targets(time);
+        let step_config = config.clone().solver(Solver::Jacobi).iterations(1).tolerance(0.0).initial_ranks(ranks.to_vec());
+        let step = RankIteration::new(&graph, &teleportation_targets, &step_config).next().unwrap();
+
+        let normalizers = outflow_normalizers(&graph, config.outflow_normalization);
+        let contributions = graph
+            .edges
+            .iter()
+            .map(|e| config.damping_factor * ranks[e.source.index()] * edge_fraction(e.weight, normalizers[e.source.index()]))
+            .collect();
+        FrameExplainer {
+            scenario,
+            time,
+            ranks: ranks.to_vec(),
+            inflow: step.ranks,
+            contributions,
+            teleport: step.trace.teleport_inflow,
+            dangling: step.trace.dangling_inflow,
+            adjustment: step.trace.event_adjustment,
+            graph,
+        }
+    }
+
+    pub fn explain(&self, node: NodeId) -> Explanation {
+        let i = node.index();
+        let mut edges: Vec<EdgeContribution> = self
+            .graph
+            .edges
+            .iter()
+            .zip(&self.contributions)
+            .filter(|&(e, &amount)| e.target == node && amount > 0.0)
+            .map(|(e, &amount)| EdgeContribution { source: e.source, edges: e.sources.clone(), amount })
+            .collect();
+        edges.sort_by(|a, b| b.amount.total_cmp(&a.amount));
+        Explanation { node, time: self.time, rank: self.ranks[i], teleport: self.teleport[i], dangling: self.dangling[i], adjustment: self.adjustment[i], edges }
+    }
+
+    // The `count` largest contributions along paths of up to `max_hops` edges without repeated
+    // nodes, found by a beam search backwards from the node that keeps `count` paths per length
+    pub fn top_paths(&self, node: NodeId, count: usize, max_hops: usize) -> Vec<ContributionPath> {
+        let mut found: Vec<ContributionPath> = Vec::new();
+        let mut frontier = vec![ContributionPath { nodes: vec![node], edges: Vec::new(), amount: 1.0 }];
+        for _ in 0..max_hops {
+            let mut extended = Vec::new();
+            for path in &frontier {
+                let origin = path.nodes[0];
+                // Share of the origin's inflow per incoming edge; the explained node takes the amounts as they are
+                let scale = if path.edges.is_empty() { 1.0 } else if self.inflow[origin.index()] > 0.0 { path.amount / self.inflow[origin.index()] } else { 0.0 };
+                for (e, &contribution) in self.graph.edges.iter().zip(&self.contributions) {
+                    if e.target != origin || contribution <= 0.0 || path.nodes.contains(&e.source) {
+                        continue;
+                    }
+                    let mut nodes = vec![e.source];
+                    nodes.extend(&path.nodes);
+                    let mut edges = e.sources.clone();
+                    edges.extend(&path.edges);
+                    extended.push(ContributionPath { nodes, edges, amount: scale * contribution });
+                }
+            }
+            extended.sort_by(|a, b| b.amount.total_cmp(&a.amount));
+            extended.truncate(count);
+            found.extend(extended.iter().cloned());
+            frontier = extended;
+        }
+        found.sort_by(|a, b| b.amount.total_cmp(&a.amount));
+        found.truncate(count);
+        found
+    }
+
+    pub fn write_explanation(&self, out: &mut dyn Write, node: NodeId, paths: &[ContributionPath]) -> io::Result<()> {
+        let explanation = self.explain(node);
+        let label = |node: NodeId| self.scenario.node_label(node);
+        let share = |amount: f64| if explanation.total() > 0.0 { 100.0 * amount / explanation.total() } else { 0.0 };
+        writeln!(out, "Rank of node {} at time {}: {:.6}", label(node), explanation.time, explanation.rank)?;
+        writeln!(out, "  teleportation       {:.6}  ({:5.1}%)", explanation.teleport, share(explanation.teleport))?;
+        for edge in &explanation.edges {
+            writeln!(out, "  from {:<14} {:.6}  ({:5.1}%)", label(edge.source), edge.amount, share(edge.amount))?;
+        }
+        writeln!(out, "  dangling rank       {:.6}  ({:5.1}%)", explanation.dangling, share(explanation.dangling))?;
+        if explanation.adjustment != 0.0 {
+            writeln!(out, "  reward and penalty  {:+.6}", explanation.adjustment)?;
+        }
+        writeln!(out, "  sum of parts        {:.6}", explanation.total())?;
+        if !paths.is_empty() {
+            writeln!(out, "Top contributing paths:")?;
+            for path in paths {
+                let nodes: Vec<String> = path.nodes.iter().map(|&n| label(n)).collect();
+                writeln!(out, "  {:.6}  {}", path.amount, nodes.join(" -> "))?;
+            }
+        }
+        Ok(())
+    }
+}
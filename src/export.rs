@@ -0,0 +1,143 @@
+use std::io::{self, Write};
+
+use crate::dot::{write_dot, FrameRender};
+use crate::html::viewer_data;
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::sink::Sink;
+use crate::stats::{alarms_at, FrameStats, RaisedAlarm};
+use crate::style::ColorScale;
+use crate::tikz::TikzExporter;
+use crate::types::FrameIdx;
+
+// A finished run as exporters see it: the scenario, its per-frame results and the name of the
+// algorithm that ranked it
+pub struct ExportedRun<'a> {
+    pub scenario: &'a Scenario,
+    pub output: &'a RunOutput,
+    pub algorithm: &'a str,
+}
+
+// Writes the artifacts of a run in one format through a sink, below the scenario's name.
+// Library users implement it for their own formats and register it next to the built-in ones.
+pub trait Exporter {
+    // Name the exporter is chosen by, e.g. `--export json`
+    fn format(&self) -> &str;
+
+    fn export(&self, sink: &mut dyn Sink, run: &ExportedRun) -> io::Result<()>;
+}
+
+// Every frame as the scenario's style renders it, without the command line's extras:
+// <scenario>/frame_NNN.dot, or as the scenario's frame names have it
+pub struct DotExporter;
+
+impl Exporter for DotExporter {
+    fn format(&self) -> &str {
+        "dot"
+    }
+
+    fn export(&self, sink: &mut dyn Sink, run: &ExportedRun) -> io::Result<()> {
+        let num_of_frames = run.output.history.num_of_frames();
+        for (frame, &time) in run.output.history.times().iter().enumerate() {
+            let mut dot = Vec::new();
+            write_frame_dot(&mut dot, run, FrameIdx(frame))?;
+            sink.write(&format!("{}/{}", run.scenario.name, run.scenario.frame_file(FrameIdx(frame), time, num_of_frames, "dot")), &dot)?;
+        }
+        Ok(())
+    }
+}
+
+// The data of the HTML viewer: <scenario>.json
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn format(&self) -> &str {
+        "json"
+    }
+
+    fn export(&self, sink: &mut dyn Sink, run: &ExportedRun) -> io::Result<()> {
+        let data = viewer_data(run.scenario, run.output, &run.scenario.layout());
+        sink.write(&format!("{}.json", run.scenario.name), format!("{}\n", data).as_bytes())
+    }
+}
+
+// The rank timeline: <scenario>/ranks.csv
+pub struct CsvExporter;
+
+impl Exporter for CsvExporter {
+    fn format(&self) -> &str {
+        "csv"
+    }
+
+    fn export(&self, sink: &mut dyn Sink, run: &ExportedRun) -> io::Result<()> {
+        let mut csv = Vec::new();
+        run.output.history.write_timeline_csv(&mut csv, &run.scenario.node_labels())?;
+        sink.write(&format!("{}/ranks.csv", run.scenario.name), &csv)
+    }
+}
+
+// Exporters by format, the built-in ones first
+pub struct ExporterRegistry {
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl Default for ExporterRegistry {
+    fn default() -> Self {
+        ExporterRegistry { exporters: vec![Box::new(DotExporter), Box::new(JsonExporter), Box::new(CsvExporter), Box::new(TikzExporter::default())] }
+    }
+}
+
+impl ExporterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds an exporter; one with the format of an earlier one replaces it
+    pub fn register(&mut self, exporter: Box<dyn Exporter>) {
+        self.exporters.retain(|e| e.format() != exporter.format());
+        self.exporters.push(exporter);
+    }
+
+    pub fn get(&self, format: &str) -> Option<&dyn Exporter> {
+        self.exporters.iter().find(|e| e.format() == format).map(|e| e.as_ref())
+    }
+
+    pub fn formats(&self) -> Vec<&str> {
+        self.exporters.iter().map(|e| e.format()).collect()
+    }
+
+    // Runs the exporters of the formats in order
+    pub fn export(&self, sink: &mut dyn Sink, run: &ExportedRun, formats: &[&str]) -> Result<(), String> {
+        for format in formats {
+            let exporter = self.get(format).ok_or_else(|| format!("unknown export format: {} (expected {})", format, self.formats().join(", ")))?;
+            exporter.export(sink, run).map_err(|e| format!("{} export: {}", format, e))?;
+        }
+        Ok(())
+    }
+}
+
+// One frame with the scenario's experts, layout, annotations, statistics, alarms and style, as the server serves it
+pub fn write_frame_dot(out: &mut dyn Write, run: &ExportedRun, frame: FrameIdx) -> io::Result<()> {
+    let (scenario, history) = (run.scenario, &run.output.history);
+    let ranks = history.ranks_at(frame);
+    let time = history.times()[frame.index()];
+    let scale = ColorScale::new(&scenario.style, ranks, history.frames());
+    let weights = &run.output.edge_weights[frame.index()];
+    let stats_lines = if scenario.style.frame_stats { FrameStats::new(ranks, weights, &scenario.active_nodes(time)).label_lines() } else { Vec::new() };
+    let mut annotations = scenario.annotations_at(time);
+    annotations.extend(stats_lines.iter().map(String::as_str));
+    let alarms: Vec<String> = if scenario.style.alarm_banner { alarms_at(scenario, frame, ranks, weights, time).iter().map(RaisedAlarm::message).collect() } else { Vec::new() };
+    let (labels, layout, experts, active, pinned, clusters, upcoming) = (scenario.node_labels(), scenario.layout(), scenario.experts_at(time), scenario.active_nodes(time), scenario.pinned_nodes(), scenario.clusters(), scenario.upcoming_edges(time));
+    let decay_description = scenario.decay_description();
+    let render = FrameRender::new(ranks, &scenario.edges, weights, &labels, &layout)
+        .experts(&experts)
+        .frame(frame, history.num_of_frames())
+        .title(run.algorithm, &decay_description)
+        .annotations(&annotations)
+        .active(&active)
+        .pinned(&pinned)
+        .clusters(&clusters)
+        .upcoming(&upcoming)
+        .alarms(&alarms);
+    write_dot(out, &render, &scenario.style, &scale)
+}
@@ -0,0 +1,197 @@
+// C interface for embedding the ranking in services written in other languages, part of the
+// cdylib. The declarations are in include/trust_flow.h, generated with their /// comments from
+// this file with
+//
+//   cbindgen --config cbindgen.toml --output include/trust_flow.h src/ffi.rs
+//
+// As in the browser bindings, only the in-memory part of the library is used.
+//
+//   TfGraph *graph = tf_graph_new(3);
+//   tf_graph_add_expert(graph, 0);
+//   tf_graph_add_edge(graph, 0, 1, 0.0, 1.0);   // source, target, time, weight
+//   tf_graph_rank(graph, 0.0, NULL);            // at time 0, with the custom variant
+//   double scores[3];
+//   tf_graph_scores(graph, scores, 3);
+//   tf_graph_free(graph);
+//
+// Functions that can fail return a TfStatus, and tf_last_error() describes the last failure on
+// the calling thread. Graph pointers must be null or come from tf_graph_new and not be freed yet,
+// and a graph must not be used from two threads at once. Panics do not cross the interface; they
+// are reported as TF_STATUS_PANIC.
+#![allow(clippy::missing_safety_doc)] // the contract above holds for every function
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::graph::Edge;
+use crate::rank::RankConfig;
+use crate::registry::AlgorithmRegistry;
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+
+/// A temporal trust graph with the ranks of its last tf_graph_rank call; opaque to C
+pub struct TfGraph {
+    scenario: Scenario,
+    ranks: Option<Vec<f64>>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TfStatus {
+    Ok = 0,
+    NullPointer,
+    /// Out-of-range node, bad weight, unknown algorithm, ...
+    InvalidArgument,
+    /// Scores asked for before the first tf_graph_rank
+    NotRanked,
+    Panic,
+}
+
+type Failure = (TfStatus, String);
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn invalid(message: impl ToString) -> Failure {
+    (TfStatus::InvalidArgument, message.to_string())
+}
+
+// Runs the body with panics caught, and keeps the message of a failure for tf_last_error
+fn call(body: impl FnOnce() -> Result<(), Failure>) -> TfStatus {
+    let (status, message) = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => return TfStatus::Ok,
+        Ok(Err(failure)) => failure,
+        Err(payload) => {
+            let message = payload.downcast_ref::<&str>().map(|m| m.to_string()).or_else(|| payload.downcast_ref::<String>().cloned());
+            (TfStatus::Panic, message.unwrap_or_else(|| "panic".to_string()))
+        }
+    };
+    // Interior NULs would cut the message short anyway
+    let message = CString::new(message.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+unsafe fn graph_ref<'a>(graph: *const TfGraph) -> Result<&'a TfGraph, Failure> {
+    unsafe { graph.as_ref() }.ok_or((TfStatus::NullPointer, "graph is null".to_string()))
+}
+
+unsafe fn graph_mut<'a>(graph: *mut TfGraph) -> Result<&'a mut TfGraph, Failure> {
+    unsafe { graph.as_mut() }.ok_or((TfStatus::NullPointer, "graph is null".to_string()))
+}
+
+/// A graph of the given number of nodes without edges or experts, with the default decay constant;
+/// free it with tf_graph_free
+#[unsafe(no_mangle)]
+pub extern "C" fn tf_graph_new(num_of_nodes: usize) -> *mut TfGraph {
+    Box::into_raw(Box::new(TfGraph { scenario: Scenario::new("ffi", num_of_nodes, Vec::new(), Vec::new()), ranks: None }))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tf_graph_free(graph: *mut TfGraph) {
+    if !graph.is_null() {
+        drop(unsafe { Box::from_raw(graph) });
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tf_graph_num_of_nodes(graph: *const TfGraph) -> usize {
+    unsafe { graph.as_ref() }.map_or(0, |graph| graph.scenario.num_of_nodes)
+}
+
+/// Makes the node an expert, where teleported rank goes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tf_graph_add_expert(graph: *mut TfGraph, node: usize) -> TfStatus {
+    call(|| {
+        let graph = unsafe { graph_mut(graph) }?;
+        if node >= graph.scenario.num_of_nodes {
+            return Err(invalid(format!("expert {} is outside 0..{}", node, graph.scenario.num_of_nodes)));
+        }
+        if !graph.scenario.experts.contains(&NodeId(node)) {
+            graph.scenario.experts.push(NodeId(node));
+        }
+        Ok(())
+    })
+}
+
+/// Adds trust from source to target, created at the given time with the given weight (1 for a
+/// single confirmation)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tf_graph_add_edge(graph: *mut TfGraph, source: usize, target: usize, time: f64, weight: f64) -> TfStatus {
+    call(|| {
+        let graph = unsafe { graph_mut(graph) }?;
+        if source >= graph.scenario.num_of_nodes || target >= graph.scenario.num_of_nodes {
+            return Err(invalid(format!("edge {} -> {} refers to a node outside 0..{}", source, target, graph.scenario.num_of_nodes)));
+        }
+        if !time.is_finite() {
+            return Err(invalid(format!("edge time must be finite: {}", time)));
+        }
+        if weight.is_nan() || weight <= 0.0 {
+            return Err(invalid(format!("edge weight must be positive: {}", weight)));
+        }
+        graph.scenario.edges.push(Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight });
+        Ok(())
+    })
+}
+
+/// Exponential decay constant of edge weights; 0 disables decay
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tf_graph_set_decay(graph: *mut TfGraph, decay_constant: f64) -> TfStatus {
+    call(|| {
+        let graph = unsafe { graph_mut(graph) }?;
+        if !(decay_constant >= 0.0 && decay_constant.is_finite()) {
+            return Err(invalid(format!("decay constant must be non-negative: {}", decay_constant)));
+        }
+        graph.scenario.decay_constant = decay_constant;
+        Ok(())
+    })
+}
+
+/// Ranks the graph at the given time with the algorithm of the given id (see `trust-flow
+/// algorithms list`), or with the custom variant when it is null. The scores are kept for
+/// tf_graph_scores until the next call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tf_graph_rank(graph: *mut TfGraph, time: f64, algorithm: *const c_char) -> TfStatus {
+    call(|| {
+        let graph = unsafe { graph_mut(graph) }?;
+        let id = match unsafe { algorithm.as_ref() } {
+            Some(_) => unsafe { CStr::from_ptr(algorithm) }.to_str().map_err(|_| invalid("algorithm id is not UTF-8"))?,
+            None => "pagerank",
+        };
+        let algorithm = AlgorithmRegistry::create(id, &RankConfig::new()).map_err(invalid)?;
+        let scenario = &graph.scenario;
+        scenario.validate().map_err(invalid)?;
+        let time = Time(time);
+        let frame = scenario.frame_graph(time, &scenario.edge_weights(time)).map_err(invalid)?;
+        graph.ranks = Some(algorithm.rank(&frame, &scenario.teleportation_targets(time)));
+        Ok(())
+    })
+}
+
+/// Copies the scores of the last tf_graph_rank call, one per node, into `scores`, which has room
+/// for `len` of them
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tf_graph_scores(graph: *const TfGraph, scores: *mut f64, len: usize) -> TfStatus {
+    call(|| {
+        let graph = unsafe { graph_ref(graph) }?;
+        let ranks = graph.ranks.as_ref().ok_or((TfStatus::NotRanked, "the graph has not been ranked yet".to_string()))?;
+        if scores.is_null() {
+            return Err((TfStatus::NullPointer, "scores is null".to_string()));
+        }
+        if len < ranks.len() {
+            return Err(invalid(format!("room for {} scores, but the graph has {} nodes", len, ranks.len())));
+        }
+        unsafe { ptr::copy_nonoverlapping(ranks.as_ptr(), scores, ranks.len()) };
+        Ok(())
+    })
+}
+
+/// Description of the last failure on the calling thread, empty when nothing failed yet; valid
+/// until the next failing call on the thread
+#[unsafe(no_mangle)]
+pub extern "C" fn tf_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
@@ -0,0 +1,58 @@
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::rank::RankingAlgorithm;
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::types::{EdgeId, FrameIdx};
+
+// Rank (mass) every scenario edge carries in one step from the frame's ranks, indexed by EdgeId.
+// Edge width only shows the decayed weight; the flux also depends on the source's rank and on its
+// other edges. A compiled edge that merges parallel edges splits its flux over them by their
+// weights, and an undirected edge carries the flux of both directions.
+pub fn scenario_edge_flux(graph: &CompiledGraph, compiled_flux: &[f64], weights: &[f64]) -> Vec<f64> {
+    let mut flux = vec![0.0; weights.len()];
+    for (edge, &amount) in graph.edges.iter().zip(compiled_flux) {
+        let total: f64 = edge.sources.iter().map(|id| weights[id.index()]).sum();
+        if total > 0.0 {
+            for id in &edge.sources {
+                flux[id.index()] += amount * weights[id.index()] / total;
+            }
+        }
+    }
+    flux
+}
+
+// Flux of every edge in every frame of the run, from the frame's final ranks and damping factor;
+// None for algorithms whose scores do not flow along edges
+pub fn flux_history(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, output: &RunOutput) -> Option<Vec<Vec<f64>>> {
+    let num_of_frames = output.history.num_of_frames();
+    output
+        .history
+        .times()
+        .iter()
+        .zip(&output.edge_weights)
+        .enumerate()
+        .map(|(frame, (&time, weights))| {
+            let damped = scenario.damping_factor(frame, num_of_frames).and_then(|d| algorithm.with_damping(d));
+            let graph = scenario.frame_graph(time, weights).unwrap();
+            let flux = damped.as_deref().unwrap_or(algorithm).edge_flux(&graph, output.history.ranks_at(FrameIdx(frame)))?;
+            Some(scenario_edge_flux(&graph, &flux, weights))
+        })
+        .collect()
+}
+
+// frame,time,edge,source,target,weight,flux rows of the edges that weigh something in the frame
+pub fn write_flux_csv(out: &mut dyn Write, scenario: &Scenario, output: &RunOutput, flux: &[Vec<f64>]) -> io::Result<()> {
+    writeln!(out, "frame,time,edge,source,target,weight,flux")?;
+    for (frame, ((time, weights), flux)) in output.history.times().iter().zip(&output.edge_weights).zip(flux).enumerate() {
+        for (id, edge) in scenario.edges.iter().enumerate().filter(|&(id, _)| weights[id] > 0.0) {
+            writeln!(
+                out,
+                "{},{},{},{},{},{:.6},{:.6}",
+                FrameIdx(frame).number(), time, EdgeId(id), scenario.node_label(edge.source), scenario.node_label(edge.target), weights[id], flux[id]
+            )?;
+        }
+    }
+    Ok(())
+}
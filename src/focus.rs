@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use crate::changes::EdgeChange;
+use crate::clusters::Cluster;
+use crate::graph::Edge;
+use crate::types::{EdgeId, NodeId};
+
+// Hops drawn around the focus node when not given
+pub const DEFAULT_FOCUS_HOPS: usize = 2;
+
+// The part of a frame drawn with --focus: the nodes within `hops` hops of the focus node over the
+// frame's weighted edges, in either direction, and every edge among them. Nodes and edges are
+// renumbered in their original order, so the arrays of a frame can be cut down to the view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusView {
+    pub focus: NodeId,
+    pub hops: usize,
+    pub nodes: Vec<NodeId>,
+    pub distances: Vec<usize>, // hops from the focus node, by view node
+    pub edges: Vec<EdgeId>,
+    index: Vec<Option<usize>>, // view node by original node
+}
+
+impl FocusView {
+    pub fn new(num_of_nodes: usize, edges: &[Edge], weights: &[f64], focus: NodeId, hops: usize) -> Self {
+        let mut neighbors = vec![Vec::new(); num_of_nodes];
+        for (edge, &weight) in edges.iter().zip(weights) {
+            if weight > 0.0 {
+                neighbors[edge.source.index()].push(edge.target.index());
+                neighbors[edge.target.index()].push(edge.source.index());
+            }
+        }
+        let mut distance = vec![None; num_of_nodes];
+        distance[focus.index()] = Some(0);
+        let mut queue = VecDeque::from([focus.index()]);
+        while let Some(node) = queue.pop_front() {
+            let hop = distance[node].unwrap();
+            if hop == hops {
+                continue;
+            }
+            for &neighbor in &neighbors[node] {
+                if distance[neighbor].is_none() {
+                    distance[neighbor] = Some(hop + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        let nodes: Vec<NodeId> = (0..num_of_nodes).filter(|&i| distance[i].is_some()).map(NodeId).collect();
+        let mut index = vec![None; num_of_nodes];
+        for (i, node) in nodes.iter().enumerate() {
+            index[node.index()] = Some(i);
+        }
+        let distances = nodes.iter().map(|node| distance[node.index()].unwrap()).collect();
+        let edges = edges.iter().enumerate().filter(|(_, e)| index[e.source.index()].is_some() && index[e.target.index()].is_some()).map(|(id, _)| EdgeId(id)).collect();
+        FocusView { focus, hops, nodes, distances, edges, index }
+    }
+
+    pub fn node(&self, node: NodeId) -> Option<NodeId> {
+        self.index.get(node.index()).copied().flatten().map(NodeId)
+    }
+
+    pub fn edge(&self, edge: EdgeId) -> Option<EdgeId> {
+        self.edges.binary_search(&edge).ok().map(EdgeId)
+    }
+
+    // Values by node cut down to the view; empty stays empty
+    pub fn node_values<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        if values.is_empty() { Vec::new() } else { self.nodes.iter().map(|node| values[node.index()].clone()).collect() }
+    }
+
+    // Values by edge cut down to the view; empty stays empty
+    pub fn edge_values<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        if values.is_empty() { Vec::new() } else { self.edges.iter().map(|edge| values[edge.index()].clone()).collect() }
+    }
+
+    pub fn edge_list(&self, edges: &[Edge]) -> Vec<Edge> {
+        self.edges
+            .iter()
+            .map(|id| {
+                let edge = &edges[id.index()];
+                Edge { source: self.node(edge.source).unwrap(), target: self.node(edge.target).unwrap(), ..edge.clone() }
+            })
+            .collect()
+    }
+
+    pub fn node_list(&self, nodes: &[NodeId]) -> Vec<NodeId> {
+        nodes.iter().filter_map(|&node| self.node(node)).collect()
+    }
+
+    pub fn changes(&self, changes: &[(EdgeId, EdgeChange)]) -> Vec<(EdgeId, EdgeChange)> {
+        changes.iter().filter_map(|&(edge, change)| self.edge(edge).map(|edge| (edge, change))).collect()
+    }
+
+    // Clusters with their members in the view; empty clusters are left out
+    pub fn clusters(&self, clusters: &[Cluster]) -> Vec<Cluster> {
+        clusters
+            .iter()
+            .map(|cluster| Cluster { nodes: self.node_list(&cluster.nodes), ..cluster.clone() })
+            .filter(|cluster| !cluster.nodes.is_empty())
+            .collect()
+    }
+
+    // The focus node in the center and the nodes k hops away evenly spaced on a circle of radius
+    // k / hops, so the view fills the unit circle like the full layout
+    pub fn layout(&self) -> Vec<(f64, f64)> {
+        let mut ring_sizes = vec![0; self.hops + 1];
+        let mut places = Vec::with_capacity(self.nodes.len());
+        for &distance in &self.distances {
+            places.push(ring_sizes[distance]);
+            ring_sizes[distance] += 1;
+        }
+        self.distances
+            .iter()
+            .zip(places)
+            .map(|(&distance, place)| {
+                if distance == 0 {
+                    return (0.0, 0.0);
+                }
+                let radius = distance as f64 / self.hops as f64;
+                // Consecutive rings are turned against each other, so their nodes do not line up
+                let angle = 2.0 * PI * (place as f64 + 0.5 * (distance % 2) as f64) / ring_sizes[distance] as f64;
+                (radius * angle.cos(), radius * angle.sin())
+            })
+            .collect()
+    }
+}
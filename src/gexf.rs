@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::graph::Edge;
+use crate::nodes::{NodeInfo, NodeRow};
+use crate::provenance::EdgeSource;
+use crate::run::RunOutput;
+use crate::scenario::{Scenario, ScenarioError};
+use crate::types::{NodeId, Time};
+use crate::xml::{self, escape};
+
+// Dynamic GEXF 1.3 for Gephi: edges appear at their creation time (and disappear when they
+// leave a time window), node rank and edge weight are dynamic attributes with one value per frame,
+// valid until the next frame.
+pub fn write_gexf(out: &mut dyn Write, scenario: &Scenario, output: &RunOutput) -> io::Result<()> {
+    let times = output.history.times();
+    // Interval of each frame: [time, next frame time), open-ended for the last frame
+    let interval = |frame: usize| match times.get(frame + 1) {
+        Some(next) => format!(" start=\"{}\" end=\"{}\"", times[frame], next),
+        None => format!(" start=\"{}\"", times[frame]),
+    };
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">")?;
+    writeln!(out, "  <meta>")?;
+    writeln!(out, "    <creator>trust-flow</creator>")?;
+    writeln!(out, "    <description>{}</description>", escape(&scenario.name))?;
+    writeln!(out, "    <keywords>seed {}</keywords>", scenario.seed)?;
+    writeln!(out, "  </meta>")?;
+    writeln!(out, "  <graph mode=\"dynamic\" defaultedgetype=\"directed\" timeformat=\"double\" timerepresentation=\"interval\">")?;
+    writeln!(out, "    <attributes class=\"node\">")?;
+    writeln!(out, "      <attribute id=\"expert\" title=\"expert\" type=\"boolean\"><default>false</default></attribute>")?;
+    writeln!(out, "      <attribute id=\"category\" title=\"category\" type=\"string\"/>")?;
+    writeln!(out, "      <attribute id=\"rank\" title=\"rank\" type=\"double\"/>")?;
+    writeln!(out, "    </attributes>")?;
+    writeln!(out, "    <attributes class=\"edge\">")?;
+    writeln!(out, "      <attribute id=\"weight\" title=\"weight\" type=\"double\"/>")?;
+    writeln!(out, "    </attributes>")?;
+
+    writeln!(out, "    <nodes>")?;
+    for node in 0..scenario.num_of_nodes {
+        writeln!(out, "      <node id=\"{}\" label=\"{}\">", node, escape(&scenario.node_label(NodeId(node))))?;
+        writeln!(out, "        <attvalues>")?;
+        if let Some(category) = scenario.node_info(NodeId(node)).and_then(|info| info.category.as_ref()) {
+            writeln!(out, "          <attvalue for=\"category\" value=\"{}\"/>", escape(category))?;
+        }
+        let terms: Vec<_> = scenario.expert_terms.iter().filter(|term| term.node == NodeId(node)).collect();
+        if scenario.experts.contains(&NodeId(node)) && terms.is_empty() {
+            writeln!(out, "          <attvalue for=\"expert\" value=\"true\"/>")?;
+        }
+        for term in terms {
+            let start = term.from.map(|from| format!(" start=\"{}\"", from)).unwrap_or_default();
+            let end = term.until.map(|until| format!(" end=\"{}\"", until)).unwrap_or_default();
+            writeln!(out, "          <attvalue for=\"expert\" value=\"true\"{}{}/>", start, end)?;
+        }
+        for (frame, ranks) in output.history.frames().iter().enumerate() {
+            writeln!(out, "          <attvalue for=\"rank\" value=\"{}\"{}/>", ranks[node], interval(frame))?;
+        }
+        writeln!(out, "        </attvalues>")?;
+        writeln!(out, "      </node>")?;
+    }
+    writeln!(out, "    </nodes>")?;
+
+    writeln!(out, "    <edges>")?;
+    for (id, edge) in scenario.edges.iter().enumerate() {
+        let end = match scenario.edge_end_time(edge) {
+            Some(end) => format!(" end=\"{}\"", end),
+            None => String::new(),
+        };
+        writeln!(out, "      <edge id=\"{}\" source=\"{}\" target=\"{}\" start=\"{}\"{}>", id, edge.source, edge.target, edge.time_of_creation, end)?;
+        writeln!(out, "        <attvalues>")?;
+        for (frame, weights) in output.edge_weights.iter().enumerate() {
+            if weights[id] > 0.0 {
+                writeln!(out, "          <attvalue for=\"weight\" value=\"{}\"{}/>", weights[id], interval(frame))?;
+            }
+        }
+        writeln!(out, "        </attvalues>")?;
+        writeln!(out, "      </edge>")?;
+    }
+    writeln!(out, "    </edges>")?;
+
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</gexf>")
+}
+
+// Nodes are numbered in document order. Experts are nodes with a true "expert" attribute value,
+// edge creation times come from the edge's "start" attribute or its first spell (0 when missing),
+// the seed from the "seed N" keywords written by write_gexf.
+pub fn read_gexf(text: &str, name: &str) -> Result<Scenario, ScenarioError> {
+    let error = |message: String| ScenarioError { line: 0, message };
+    let root = xml::parse(text).map_err(|e| error(format!("invalid GEXF: {}", e)))?;
+    let graph = root.child("graph").ok_or_else(|| error("GEXF file has no <graph>".to_string()))?;
+
+    let mut attribute_titles: HashMap<&str, &str> = HashMap::new();
+    for attributes in graph.children_named("attributes") {
+        for attribute in attributes.children_named("attribute") {
+            if let (Some(id), Some(title)) = (attribute.attribute("id"), attribute.attribute("title")) {
+                attribute_titles.insert(id, title);
+            }
+        }
+    }
+
+    let mut node_indices: HashMap<String, NodeId> = HashMap::new();
+    let mut experts = Vec::new();
+    let mut node_rows = Vec::new();
+    let mut nodes = Vec::new();
+    if let Some(node_list) = graph.child("nodes") {
+        node_list.descendants_named("node", &mut nodes);
+    }
+    for node in nodes {
+        let id = node.attribute("id").ok_or_else(|| error("node without id".to_string()))?;
+        let index = NodeId(node_indices.len());
+        node_indices.insert(id.to_string(), index);
+        let value = |title: &str| {
+            node.child("attvalues")
+                .into_iter()
+                .flat_map(|values| values.children_named("attvalue"))
+                .find(|v| v.attribute("for").and_then(|f| attribute_titles.get(f)) == Some(&title))
+                .and_then(|v| v.attribute("value"))
+        };
+        if matches!(value("expert"), Some("true" | "1")) {
+            experts.push(index);
+        }
+        let info = NodeInfo {
+            name: node.attribute("label").filter(|&label| label != index.to_string()).map(str::to_string),
+            category: value("category").map(str::to_string),
+            ..NodeInfo::default()
+        };
+        node_rows.push(NodeRow { node: index, info, expert: false });
+    }
+
+    let mut edges = Vec::new();
+    let mut edge_sources = Vec::new();
+    for edge in graph.child("edges").into_iter().flat_map(|e| e.children_named("edge")) {
+        let endpoint = |attribute: &str| {
+            edge.attribute(attribute)
+                .and_then(|id| node_indices.get(id).copied())
+                .ok_or_else(|| error(format!("edge with unknown {}", attribute)))
+        };
+        let start = edge
+            .attribute("start")
+            .or_else(|| edge.child("spells").and_then(|s| s.child("spell")).and_then(|s| s.attribute("start")));
+        let time = match start {
+            Some(value) => value.parse().map_err(|_| error(format!("invalid edge start: {}", value)))?,
+            None => 0.0,
+        };
+        edges.push(Edge { source: endpoint("source")?, target: endpoint("target")?, time_of_creation: Time(time), weight: 1.0 });
+        edge_sources.push(EdgeSource { line: 0, event_id: edge.attribute("id").map(str::to_string), metadata: Vec::new() });
+    }
+
+    let mut scenario = Scenario::new(name, node_indices.len(), experts, edges);
+    scenario.apply_node_rows(node_rows);
+    scenario.edge_sources = edge_sources;
+    let keywords = root.child("meta").and_then(|meta| meta.child("keywords")).map(|k| k.text.trim());
+    if let Some(seed) = keywords.and_then(|k| k.strip_prefix("seed ")) {
+        scenario.seed = seed.parse().map_err(|_| error(format!("invalid seed: {}", seed)))?;
+    }
+    Ok(scenario)
+}
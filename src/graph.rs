@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::decay::DecayKernel;
+use crate::types::{EdgeId, NodeId, Time};
+
+pub const DEFAULT_DECAY_CONSTANT: f64 = 0.1;
+
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub time_of_creation: Time,
+    pub weight: f64, // weight at creation, 1 unless the input says otherwise
+}
+
+impl Edge {
+    // The same edge pointing the other way
+    pub fn reversed(&self) -> Edge {
+        Edge { source: self.target, target: self.source, ..*self }
+    }
+}
+
+// Weight does not grow when t1 precedes t0
+pub fn exponential_decay(t1: Time, t0: Time, weight_at_t0: f64, decay_constant: f64) -> f64 {
+    weight_at_t0 * (- t1.elapsed_since(t0) * decay_constant).exp()
+}
+
+// Edge weights at the given time; edges that do not exist yet have zero weight.
+// The returned vector is indexed by EdgeId. Large graphs are decayed in parallel with rayon.
+pub fn decayed_weights(edges: &[Edge], time: Time, decay_constant: f64) -> Vec<f64> {
+    DecayKernel::default().decayed_weights(edges, time, decay_constant)
+}
+
+// One frame per distinct edge creation time, for graphs without explicit frame times
+pub fn event_times(edges: &[Edge]) -> Vec<Time> {
+    let mut times: Vec<Time> = edges.iter().map(|e| e.time_of_creation).collect();
+    times.sort_by(|a, b| a.value().total_cmp(&b.value()));
+    times.dedup();
+    times
+}
+
+pub fn edge_ids(edges: &[Edge]) -> impl Iterator<Item = (EdgeId, &Edge)> {
+    edges.iter().enumerate().map(|(i, e)| (EdgeId(i), e))
+}
+
+// Multiplex combination of parallel edge sets (layers, e.g. reviews and payments): the factor
+// every edge's weight and capacity are scaled by. Within each layer, the capacities of a node's
+// out-edges that weigh something are scaled to sum to the layer's weight, so a node's outflow is
+// split between its layers by their weights, not by how many edges each has. `edge_layer` is
+// indexed by EdgeId; edges without a layer (or beyond its end) form one more layer of weight 1.
+pub fn layer_factors(edges: &[Edge], weights: &[f64], edge_layer: &[Option<usize>], layer_weights: &[f64]) -> Vec<f64> {
+    let layer = |i: usize| edge_layer.get(i).copied().flatten();
+    let mut capacities: HashMap<(NodeId, Option<usize>), f64> = HashMap::new();
+    for (i, (e, &w)) in edges.iter().zip(weights).enumerate() {
+        if w > 0.0 {
+            *capacities.entry((e.source, layer(i))).or_insert(0.0) += e.weight;
+        }
+    }
+    edges
+        .iter()
+        .zip(weights)
+        .enumerate()
+        .map(|(i, (e, &w))| {
+            let capacity = capacities.get(&(e.source, layer(i))).copied().unwrap_or(0.0);
+            if w > 0.0 && capacity > 0.0 { layer(i).map_or(1.0, |l| layer_weights[l]) / capacity } else { 0.0 }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfLoopPolicy {
+    Ignore, // drop self-loops
+    Keep,   // a self-loop keeps part of the node's rank (mass) on the node
+    Error,  // refuse to compile a graph with self-loops
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParallelEdgePolicy {
+    SumWeights,         // merge parallel edges into one edge carrying the sum of their weights
+    KeepMax,            // merge parallel edges into one edge carrying the largest weight
+    SaturatingSum(f64), // like SumWeights, but the merged weight never exceeds the cap
+    KeepAll,            // every confirmation event is its own edge
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphOptions {
+    pub self_loops: SelfLoopPolicy,
+    pub parallel_edges: ParallelEdgePolicy,
+    pub undirected: bool, // every edge records mutual trust and also counts from target to source
+    pub prune_below: f64, // edges weighing less are left out of the frame's graph; 0.0 keeps every edge
+}
+
+impl GraphOptions {
+    pub fn prunes(&self, weight: f64) -> bool {
+        weight < self.prune_below
+    }
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        GraphOptions {
+            self_loops: SelfLoopPolicy::Keep,
+            parallel_edges: ParallelEdgePolicy::KeepAll,
+            undirected: false,
+            prune_below: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    SelfLoop(EdgeId),
+    UnknownEdge(EdgeId),
+    NodeOutOfRange(EdgeId), // an endpoint is not below the node count
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::SelfLoop(id) => write!(f, "edge {} is a self-loop", id),
+            GraphError::UnknownEdge(id) => write!(f, "edge {} does not exist", id),
+            GraphError::NodeOutOfRange(id) => write!(f, "edge {} refers to a node that is not in the graph", id),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+#[derive(Debug, Clone)]
+pub struct CompiledEdge {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub weight: f64,   // current (decayed) weight
+    pub capacity: f64, // weight at creation; by default outflow along the edge is proportional to weight / capacity
+    pub sources: Vec<EdgeId>, // scenario edges merged into this one
+}
+
+// Graph as seen by the ranking algorithms in a single frame
+#[derive(Debug, Clone)]
+pub struct CompiledGraph {
+    pub num_of_nodes: usize,
+    pub edges: Vec<CompiledEdge>,
+    pub rank_adjustments: Vec<f64>, // rank (mass) added to each node per iteration by reward and penalty events; empty when none
+    pub active_nodes: Vec<bool>, // indexed by node; false for nodes that have not joined yet or have left; empty when all are active
+    pub conductances: Vec<f64>, // indexed by node; share of its damped rank (mass) a node passes on, it retains the rest; empty when all pass on everything
+    pub pruned_edges: usize, // scenario edges that exist but weigh less than the pruning threshold
+}
+
+impl CompiledGraph {
+    pub fn is_active(&self, node: usize) -> bool {
+        self.active_nodes.get(node) != Some(&false)
+    }
+
+    pub fn conductance(&self, node: usize) -> f64 {
+        self.conductances.get(node).copied().unwrap_or(1.0)
+    }
+
+    pub fn num_of_active_nodes(&self) -> usize {
+        (0..self.num_of_nodes).filter(|&node| self.is_active(node)).count()
+    }
+
+    // Uniform distribution over the active nodes, the initial ranks unless configured otherwise
+    pub fn uniform_ranks(&self) -> Vec<f64> {
+        let num_of_active = self.num_of_active_nodes();
+        (0..self.num_of_nodes).map(|node| if self.is_active(node) { 1.0 / num_of_active as f64 } else { 0.0 }).collect()
+    }
+}
+
+pub fn compile_graph(edges: &[Edge], weights: &[f64], num_of_nodes: usize, options: &GraphOptions) -> Result<CompiledGraph, GraphError> {
+    let mut compiled: Vec<CompiledEdge> = Vec::with_capacity(edges.len());
+    let mut merged: HashMap<(NodeId, NodeId), usize> = HashMap::new();
+    let mut pruned_edges = 0;
+
+    for ((id, edge), &w) in edge_ids(edges).zip(weights.iter()) {
+        if edge.source.index() >= num_of_nodes || edge.target.index() >= num_of_nodes {
+            return Err(GraphError::NodeOutOfRange(id));
+        }
+        // Edges that have not been created yet weigh 0 and do not count as pruned
+        if options.prunes(w) {
+            pruned_edges += usize::from(w > 0.0);
+            continue;
+        }
+        if edge.source == edge.target {
+            match options.self_loops {
+                SelfLoopPolicy::Ignore => continue,
+                SelfLoopPolicy::Keep => {}
+                SelfLoopPolicy::Error => return Err(GraphError::SelfLoop(id)),
+            }
+        }
+
+        // An undirected edge becomes one edge in each direction, both with the full weight, so
+        // each endpoint's outflow (and lifetime normalizer) accounts for it
+        let directions = if options.undirected && edge.source != edge.target { 2 } else { 1 };
+        for (source, target) in [(edge.source, edge.target), (edge.target, edge.source)].into_iter().take(directions) {
+            match (options.parallel_edges, merged.get(&(source, target))) {
+                (ParallelEdgePolicy::SumWeights | ParallelEdgePolicy::SaturatingSum(_), Some(&i)) => {
+                    compiled[i].weight += w;
+                    compiled[i].capacity += edge.weight;
+                    compiled[i].sources.push(id);
+                }
+                (ParallelEdgePolicy::KeepMax, Some(&i)) => {
+                    compiled[i].weight = compiled[i].weight.max(w);
+                    compiled[i].capacity = compiled[i].capacity.max(edge.weight);
+                    compiled[i].sources.push(id);
+                }
+                _ => {
+                    merged.insert((source, target), compiled.len());
+                    compiled.push(CompiledEdge { source, target, weight: w, capacity: edge.weight, sources: vec![id] });
+                }
+            }
+        }
+    }
+
+    // Repeated interactions reinforce trust only up to the cap, single edges included
+    if let ParallelEdgePolicy::SaturatingSum(cap) = options.parallel_edges {
+        for edge in &mut compiled {
+            edge.weight = edge.weight.min(cap);
+            edge.capacity = edge.capacity.min(cap);
+        }
+    }
+
+    Ok(CompiledGraph { num_of_nodes, edges: compiled, rank_adjustments: Vec::new(), active_nodes: Vec::new(), conductances: Vec::new(), pruned_edges })
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::graph::Edge;
+use crate::nodes::{NodeInfo, NodeRow};
+use crate::provenance::{metadata_keys, EdgeSource};
+use crate::run::RunOutput;
+use crate::scenario::{Scenario, ScenarioError};
+use crate::types::{EdgeId, NodeId, Time};
+use crate::xml::{self, escape};
+
+// GraphML has no notion of dynamic graphs, so ranks and weights are written as one
+// attribute per frame ("rank_t<time>", "weight_t<time>") and edges carry their
+// creation time in "start" (and "end" when a time window makes them disappear).
+// Edge metadata from the input becomes string attributes named "meta.<key>".
+pub fn write_graphml(out: &mut dyn Write, scenario: &Scenario, output: &RunOutput) -> io::Result<()> {
+    let times = output.history.times();
+    let metadata_keys = metadata_keys(scenario);
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+    writeln!(out, "  <key id=\"seed\" for=\"graph\" attr.name=\"seed\" attr.type=\"long\"/>")?;
+    writeln!(out, "  <key id=\"expert\" for=\"node\" attr.name=\"expert\" attr.type=\"boolean\"><default>false</default></key>")?;
+    writeln!(out, "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>")?;
+    writeln!(out, "  <key id=\"category\" for=\"node\" attr.name=\"category\" attr.type=\"string\"/>")?;
+    writeln!(out, "  <key id=\"start\" for=\"edge\" attr.name=\"start\" attr.type=\"double\"/>")?;
+    writeln!(out, "  <key id=\"end\" for=\"edge\" attr.name=\"end\" attr.type=\"double\"/>")?;
+    for (i, key) in metadata_keys.iter().enumerate() {
+        writeln!(out, "  <key id=\"meta{}\" for=\"edge\" attr.name=\"meta.{}\" attr.type=\"string\"/>", i, escape(key))?;
+    }
+    for (frame, time) in times.iter().enumerate() {
+        writeln!(out, "  <key id=\"rank{}\" for=\"node\" attr.name=\"rank_t{}\" attr.type=\"double\"/>", frame, time)?;
+        writeln!(out, "  <key id=\"weight{}\" for=\"edge\" attr.name=\"weight_t{}\" attr.type=\"double\"/>", frame, time)?;
+    }
+    writeln!(out, "  <graph id=\"{}\" edgedefault=\"directed\">", escape(&scenario.name))?;
+    writeln!(out, "    <data key=\"seed\">{}</data>", scenario.seed)?;
+
+    for node in 0..scenario.num_of_nodes {
+        writeln!(out, "    <node id=\"{}\">", node)?;
+        writeln!(out, "      <data key=\"label\">{}</data>", escape(&scenario.node_label(NodeId(node))))?;
+        if let Some(category) = scenario.node_info(NodeId(node)).and_then(|info| info.category.as_ref()) {
+            writeln!(out, "      <data key=\"category\">{}</data>", escape(category))?;
+        }
+        if scenario.experts.contains(&NodeId(node)) {
+            writeln!(out, "      <data key=\"expert\">true</data>")?;
+        }
+        for (frame, ranks) in output.history.frames().iter().enumerate() {
+            writeln!(out, "      <data key=\"rank{}\">{}</data>", frame, ranks[node])?;
+        }
+        writeln!(out, "    </node>")?;
+    }
+
+    for (id, edge) in scenario.edges.iter().enumerate() {
+        writeln!(out, "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">", id, edge.source, edge.target)?;
+        writeln!(out, "      <data key=\"start\">{}</data>", edge.time_of_creation)?;
+        if let Some(end) = scenario.edge_end_time(edge) {
+            writeln!(out, "      <data key=\"end\">{}</data>", end)?;
+        }
+        for (key, value) in scenario.edge_source(EdgeId(id)).into_iter().flat_map(|source| &source.metadata) {
+            let i = metadata_keys.iter().position(|k| k == key).unwrap();
+            writeln!(out, "      <data key=\"meta{}\">{}</data>", i, escape(value))?;
+        }
+        for (frame, weights) in output.edge_weights.iter().enumerate() {
+            writeln!(out, "      <data key=\"weight{}\">{}</data>", frame, weights[id])?;
+        }
+        writeln!(out, "    </edge>")?;
+    }
+
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")
+}
+
+// Nodes are numbered in document order. Experts are nodes with a true "expert" attribute,
+// edge creation times come from a "start" or "time" attribute (0 when missing), the seed from the graph's "seed".
+// Edge attributes named "meta.<key>" are read back as edge metadata.
+pub fn read_graphml(text: &str, name: &str) -> Result<Scenario, ScenarioError> {
+    let error = |message: String| ScenarioError { line: 0, message };
+    let root = xml::parse(text).map_err(|e| error(format!("invalid GraphML: {}", e)))?;
+    let graph = root.child("graph").ok_or_else(|| error("GraphML file has no <graph>".to_string()))?;
+
+    let mut key_names: HashMap<&str, &str> = HashMap::new();
+    for key in root.children_named("key") {
+        if let (Some(id), Some(attr_name)) = (key.attribute("id"), key.attribute("attr.name")) {
+            key_names.insert(id, attr_name);
+        }
+    }
+    let data = |element: &xml::Element, wanted: &[&str]| -> Option<String> {
+        element
+            .children_named("data")
+            .find(|d| d.attribute("key").and_then(|k| key_names.get(k)).is_some_and(|n| wanted.contains(n)))
+            .map(|d| d.text.trim().to_string())
+    };
+
+    let mut node_indices: HashMap<String, NodeId> = HashMap::new();
+    let mut experts = Vec::new();
+    let mut node_rows = Vec::new();
+    for node in graph.children_named("node") {
+        let id = node.attribute("id").ok_or_else(|| error("node without id".to_string()))?;
+        let index = NodeId(node_indices.len());
+        node_indices.insert(id.to_string(), index);
+        if data(node, &["expert"]).is_some_and(|v| v == "true" || v == "1") {
+            experts.push(index);
+        }
+        let info = NodeInfo {
+            name: data(node, &["label", "name"]).or_else(|| (id != index.to_string()).then(|| id.to_string())),
+            category: data(node, &["category"]),
+            ..NodeInfo::default()
+        };
+        node_rows.push(NodeRow { node: index, info, expert: false });
+    }
+
+    let mut edges = Vec::new();
+    let mut edge_sources = Vec::new();
+    for edge in graph.children_named("edge") {
+        let endpoint = |attribute: &str| {
+            edge.attribute(attribute)
+                .and_then(|id| node_indices.get(id).copied())
+                .ok_or_else(|| error(format!("edge with unknown {}", attribute)))
+        };
+        let time = match data(edge, &["start", "time"]) {
+            Some(value) => value.parse().map_err(|_| error(format!("invalid edge time: {}", value)))?,
+            None => 0.0,
+        };
+        edges.push(Edge { source: endpoint("source")?, target: endpoint("target")?, time_of_creation: Time(time), weight: 1.0 });
+        let metadata = edge
+            .children_named("data")
+            .filter_map(|d| d.attribute("key").and_then(|k| key_names.get(k)).and_then(|n| n.strip_prefix("meta.")).map(|key| (key.to_string(), d.text.trim().to_string())))
+            .collect();
+        edge_sources.push(EdgeSource { line: 0, event_id: edge.attribute("id").map(str::to_string), metadata });
+    }
+
+    let mut scenario = Scenario::new(name, node_indices.len(), experts, edges);
+    scenario.apply_node_rows(node_rows);
+    scenario.edge_sources = edge_sources;
+    if let Some(seed) = data(graph, &["seed"]) {
+        scenario.seed = seed.parse().map_err(|_| error(format!("invalid seed: {}", seed)))?;
+    }
+    Ok(scenario)
+}
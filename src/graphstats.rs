@@ -0,0 +1,315 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::collusion::{find_rings, CollusionRing, RingCriteria};
+use crate::communities::label_propagation;
+use crate::graph::{compile_graph, Edge, GraphOptions};
+use crate::json::Json;
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+
+// Share of all edge events a single node may take part in before it is reported
+const DOMINANT_SHARE: f64 = 0.5;
+
+// Edge events below which no node is reported as dominant, since small samples are lopsided anyway
+const MIN_EVENTS_FOR_DOMINANCE: usize = 10;
+
+// Sizes, degrees, time span and structure of a scenario's edge events, with what looks wrong in
+// them, to check data before ranking it (`trust-flow stats`). Every event counts, without decay;
+// degrees count distinct neighbors, so events that repeat an edge only add to its weight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub num_of_nodes: usize,
+    pub num_of_events: usize,
+    pub num_of_edges: usize, // distinct source and target pairs
+    pub total_weight: f64,
+    pub time_span: Option<(Time, Time)>, // earliest and latest event
+    pub out_degree: DegreeDistribution,
+    pub in_degree: DegreeDistribution,
+    pub dangling_nodes: usize, // without edges of their own, so their rank goes where the dangling policy puts it
+    pub components: usize,     // strongly connected
+    pub largest_component: usize,
+    pub single_node_components: usize,
+    pub anomalies: Vec<DataAnomaly>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DegreeDistribution {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub histogram: Vec<usize>, // nodes of degree 0, 1, 2-3, 4-7, 8-15, ...
+}
+
+// Something in the edge events that often means the data is not what the ranking expects
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataAnomaly {
+    SelfLoops(usize),
+    DuplicateEvents(usize), // repeating an earlier event's source, target and time
+    OutOfOrder(usize),      // earlier than the event before them
+    IsolatedNodes(usize),   // within the node range, but in no event
+    DominantNode { node: NodeId, share: f64 }, // of the events it takes part in
+    LooksReversed,
+    CollusionRing(CollusionRing),
+}
+
+impl fmt::Display for DataAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataAnomaly::SelfLoops(count) => write!(f, "self-loops: {} (nodes trusting themselves)", count),
+            DataAnomaly::DuplicateEvents(count) => write!(f, "duplicate events: {} (the source, target and time of an earlier event)", count),
+            DataAnomaly::OutOfOrder(count) => write!(f, "events out of time order: {}", count),
+            DataAnomaly::IsolatedNodes(count) => write!(f, "nodes in no event: {} (gaps in the node numbering?)", count),
+            DataAnomaly::DominantNode { node, share } => write!(f, "node {} takes part in {:.0}% of the events", node, share * 100.0),
+            DataAnomaly::LooksReversed => write!(f, "the experts endorse nobody but are endorsed; the edges may point the wrong way (--reverse-edges)"),
+            DataAnomaly::CollusionRing(ring) => write!(
+                f,
+                "nodes {} endorse each other densely ({:.0}% of pairs) and get {:.0}% of their weight from outside",
+                ring.nodes.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", "),
+                ring.density * 100.0,
+                ring.external_share * 100.0
+            ),
+        }
+    }
+}
+
+impl DegreeDistribution {
+    pub fn new(degrees: &[usize]) -> Self {
+        let mut sorted = degrees.to_vec();
+        sorted.sort_unstable();
+        let n = sorted.len();
+        let median = match n {
+            0 => 0.0,
+            _ if n % 2 == 1 => sorted[n / 2] as f64,
+            _ => (sorted[n / 2 - 1] + sorted[n / 2]) as f64 / 2.0,
+        };
+        let mut histogram = Vec::new();
+        for &degree in &sorted {
+            let bucket = (usize::BITS - degree.leading_zeros()) as usize;
+            if histogram.len() <= bucket {
+                histogram.resize(bucket + 1, 0);
+            }
+            histogram[bucket] += 1;
+        }
+        DegreeDistribution {
+            min: sorted.first().copied().unwrap_or(0),
+            max: sorted.last().copied().unwrap_or(0),
+            mean: if n > 0 { sorted.iter().sum::<usize>() as f64 / n as f64 } else { 0.0 },
+            median,
+            histogram,
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("min", self.min.into()),
+            ("max", self.max.into()),
+            ("mean", self.mean.into()),
+            ("median", self.median.into()),
+            ("histogram", Json::Array(self.histogram.iter().enumerate().map(|(bucket, &count)| Json::object(vec![("degrees", bucket_label(bucket).into()), ("nodes", count.into())])).collect())),
+        ])
+    }
+}
+
+// Degrees of a histogram bucket: 0, 1, 2-3, 4-7, ...
+fn bucket_label(bucket: usize) -> String {
+    match bucket {
+        0 => "0".to_string(),
+        1 => "1".to_string(),
+        _ => format!("{}-{}", 1usize << (bucket - 1), (1usize << bucket) - 1),
+    }
+}
+
+impl GraphStats {
+    pub fn new(scenario: &Scenario) -> Self {
+        let n = scenario.num_of_nodes;
+        let edges = &scenario.edges;
+        let pairs: HashSet<(NodeId, NodeId)> = edges.iter().map(|e| (e.source, e.target)).collect();
+        let (mut out_degrees, mut in_degrees) = (vec![0; n], vec![0; n]);
+        for &(source, target) in &pairs {
+            out_degrees[source.index()] += 1;
+            in_degrees[target.index()] += 1;
+        }
+        let components = strongly_connected_components(n, edges);
+        let mut component_sizes = vec![0; components.iter().max().map_or(0, |&c| c + 1)];
+        for &component in &components {
+            component_sizes[component] += 1;
+        }
+        let earliest = edges.iter().map(|e| e.time_of_creation).reduce(|a, b| if b < a { b } else { a });
+        let latest = edges.iter().map(|e| e.time_of_creation).reduce(|a, b| if b > a { b } else { a });
+        GraphStats {
+            num_of_nodes: n,
+            num_of_events: edges.len(),
+            num_of_edges: pairs.len(),
+            total_weight: edges.iter().map(|e| e.weight).sum(),
+            time_span: earliest.zip(latest),
+            out_degree: DegreeDistribution::new(&out_degrees),
+            in_degree: DegreeDistribution::new(&in_degrees),
+            dangling_nodes: out_degrees.iter().filter(|&&d| d == 0).count(),
+            components: component_sizes.len(),
+            largest_component: component_sizes.iter().copied().max().unwrap_or(0),
+            single_node_components: component_sizes.iter().filter(|&&size| size == 1).count(),
+            anomalies: data_anomalies(scenario),
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("nodes", self.num_of_nodes.into()),
+            ("events", self.num_of_events.into()),
+            ("edges", self.num_of_edges.into()),
+            ("total_weight", self.total_weight.into()),
+            ("first_time", self.time_span.map_or(Json::Null, |(first, _)| first.value().into())),
+            ("last_time", self.time_span.map_or(Json::Null, |(_, last)| last.value().into())),
+            ("out_degree", self.out_degree.to_json()),
+            ("in_degree", self.in_degree.to_json()),
+            ("dangling_nodes", self.dangling_nodes.into()),
+            ("components", self.components.into()),
+            ("largest_component", self.largest_component.into()),
+            ("single_node_components", self.single_node_components.into()),
+            ("anomalies", Json::Array(self.anomalies.iter().map(|anomaly| anomaly.to_string().into()).collect())),
+        ])
+    }
+
+    // The statistics as text for the terminal, headed by where the events come from
+    pub fn write_report(&self, out: &mut dyn Write, source: &str) -> io::Result<()> {
+        let percent = |count: usize| if self.num_of_nodes > 0 { count as f64 * 100.0 / self.num_of_nodes as f64 } else { 0.0 };
+        writeln!(out, "{}: {} edge events, {} nodes", source, self.num_of_events, self.num_of_nodes)?;
+        writeln!(out, "  distinct edges     {} (total weight {})", self.num_of_edges, self.total_weight)?;
+        match self.time_span {
+            Some((first, last)) => writeln!(out, "  time               {} to {} (span {})", first, last, last.value() - first.value())?,
+            None => writeln!(out, "  time               no events")?,
+        }
+        for (name, degrees) in [("out-degree", &self.out_degree), ("in-degree", &self.in_degree)] {
+            writeln!(out, "  {:<18} min {}, median {}, mean {:.2}, max {}", name, degrees.min, degrees.median, degrees.mean, degrees.max)?;
+        }
+        writeln!(out, "  degree histogram   {:>9} {:>9} {:>9}", "degrees", "out", "in")?;
+        for bucket in 0..self.out_degree.histogram.len().max(self.in_degree.histogram.len()) {
+            let count = |degrees: &DegreeDistribution| degrees.histogram.get(bucket).copied().unwrap_or(0);
+            writeln!(out, "                     {:>9} {:>9} {:>9}", bucket_label(bucket), count(&self.out_degree), count(&self.in_degree))?;
+        }
+        writeln!(out, "  dangling nodes     {} ({:.1}%)", self.dangling_nodes, percent(self.dangling_nodes))?;
+        writeln!(
+            out,
+            "  components         {} strongly connected, the largest with {} nodes ({:.1}%), {} of a single node",
+            self.components,
+            self.largest_component,
+            percent(self.largest_component),
+            self.single_node_components
+        )?;
+        if self.anomalies.is_empty() {
+            return writeln!(out, "no anomalies found");
+        }
+        writeln!(out, "anomalies:")?;
+        for anomaly in &self.anomalies {
+            writeln!(out, "  {}", anomaly)?;
+        }
+        Ok(())
+    }
+}
+
+fn data_anomalies(scenario: &Scenario) -> Vec<DataAnomaly> {
+    let edges = &scenario.edges;
+    let mut anomalies = Vec::new();
+    let self_loops = edges.iter().filter(|e| e.source == e.target).count();
+    if self_loops > 0 {
+        anomalies.push(DataAnomaly::SelfLoops(self_loops));
+    }
+    let mut seen = HashSet::new();
+    let duplicates = edges.iter().filter(|e| !seen.insert((e.source, e.target, e.time_of_creation.value().to_bits()))).count();
+    if duplicates > 0 {
+        anomalies.push(DataAnomaly::DuplicateEvents(duplicates));
+    }
+    let out_of_order = edges.windows(2).filter(|pair| pair[1].time_of_creation < pair[0].time_of_creation).count();
+    if out_of_order > 0 {
+        anomalies.push(DataAnomaly::OutOfOrder(out_of_order));
+    }
+    let mut events_of = vec![0; scenario.num_of_nodes];
+    for edge in edges {
+        events_of[edge.source.index()] += 1;
+        if edge.target != edge.source {
+            events_of[edge.target.index()] += 1;
+        }
+    }
+    let isolated = events_of.iter().filter(|&&count| count == 0).count();
+    if isolated > 0 && !edges.is_empty() {
+        anomalies.push(DataAnomaly::IsolatedNodes(isolated));
+    }
+    if edges.len() >= MIN_EVENTS_FOR_DOMINANCE {
+        for (node, &count) in events_of.iter().enumerate() {
+            let share = count as f64 / edges.len() as f64;
+            if share > DOMINANT_SHARE {
+                anomalies.push(DataAnomaly::DominantNode { node: NodeId(node), share });
+            }
+        }
+    }
+    if !scenario.experts.is_empty() && scenario.looks_reversed() {
+        anomalies.push(DataAnomaly::LooksReversed);
+    }
+    let weights: Vec<f64> = edges.iter().map(|e| e.weight).collect();
+    if let Ok(graph) = compile_graph(edges, &weights, scenario.num_of_nodes, &GraphOptions::default()) {
+        anomalies.extend(find_rings(&graph, &label_propagation(&graph), &RingCriteria::default()).into_iter().map(DataAnomaly::CollusionRing));
+    }
+    anomalies
+}
+
+// Strongly connected component of every node, numbered from 0 in the order they are completed
+// (Tarjan, with an explicit stack so that long paths cannot overflow the call stack)
+pub fn strongly_connected_components(num_of_nodes: usize, edges: &[Edge]) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+    let mut successors = vec![Vec::new(); num_of_nodes];
+    for edge in edges {
+        successors[edge.source.index()].push(edge.target.index());
+    }
+    let mut index = vec![UNVISITED; num_of_nodes];
+    let mut lowlink = vec![0; num_of_nodes];
+    let mut on_stack = vec![false; num_of_nodes];
+    let mut stack = Vec::new();
+    let mut component = vec![UNVISITED; num_of_nodes];
+    let (mut next_index, mut num_of_components) = (0, 0);
+    for root in 0..num_of_nodes {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        let mut visit = |node: usize, index: &mut [usize], lowlink: &mut [usize], on_stack: &mut [bool], stack: &mut Vec<usize>| {
+            index[node] = next_index;
+            lowlink[node] = next_index;
+            next_index += 1;
+            on_stack[node] = true;
+            stack.push(node);
+        };
+        visit(root, &mut index, &mut lowlink, &mut on_stack, &mut stack);
+        // Nodes being visited, with the position of the next successor to look at
+        let mut calls = vec![(root, 0)];
+        while let Some(call) = calls.last_mut() {
+            let node = call.0;
+            if let Some(&successor) = successors[node].get(call.1) {
+                call.1 += 1;
+                if index[successor] == UNVISITED {
+                    visit(successor, &mut index, &mut lowlink, &mut on_stack, &mut stack);
+                    calls.push((successor, 0));
+                } else if on_stack[successor] {
+                    lowlink[node] = lowlink[node].min(index[successor]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+            if lowlink[node] == index[node] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component[member] = num_of_components;
+                    if member == node {
+                        break;
+                    }
+                }
+                num_of_components += 1;
+            }
+        }
+    }
+    component
+}
@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How often running Graphviz processes are checked for completion and timeouts
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// Renders DOT files with Graphviz's `dot` command (which honors the layout engine a file names),
+// running at most `max_parallel` processes at a time
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphvizPool {
+    pub format: String, // output format, e.g. png or svg
+    pub max_parallel: usize,
+    pub timeout: Duration, // per attempt; a process running longer is killed
+    pub retries: usize, // further attempts after a failed or timed out one
+}
+
+// A DOT file and the image it is rendered to
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphvizJob {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphvizError {
+    NotInstalled,
+    Failed(Vec<(PathBuf, String)>), // DOT files that failed every attempt, with the last error
+}
+
+impl fmt::Display for GraphvizError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphvizError::NotInstalled => write!(f, "Graphviz is not installed: the dot command was not found (see https://graphviz.org/download/)"),
+            GraphvizError::Failed(failures) => {
+                write!(f, "Graphviz failed to render {} files:", failures.len())?;
+                failures.iter().try_for_each(|(input, message)| write!(f, "\n  {}: {}", input.display(), message))
+            }
+        }
+    }
+}
+
+impl GraphvizPool {
+    pub fn render(&self, jobs: &[GraphvizJob]) -> Result<(), GraphvizError> {
+        if Command::new("dot").arg("-V").stdout(Stdio::null()).stderr(Stdio::null()).status().is_err() {
+            return Err(GraphvizError::NotInstalled);
+        }
+        let mut queue: VecDeque<(usize, usize)> = (0..jobs.len()).map(|job| (job, 0)).collect(); // job, attempt
+        let mut running: Vec<(usize, usize, Child, Instant)> = Vec::new();
+        let mut failures = Vec::new();
+        while !queue.is_empty() || !running.is_empty() {
+            while running.len() < self.max_parallel.max(1) {
+                let Some((job, attempt)) = queue.pop_front() else { break };
+                match self.spawn(&jobs[job]) {
+                    Ok(child) => running.push((job, attempt, child, Instant::now())),
+                    Err(e) => self.retry(job, attempt, e.to_string(), &mut queue, &mut failures, jobs),
+                }
+            }
+            let mut i = 0;
+            while i < running.len() {
+                let (_, _, child, started) = &mut running[i];
+                let outcome = match child.try_wait() {
+                    Ok(Some(status)) if status.success() => Some(Ok(())),
+                    Ok(Some(status)) => {
+                        let mut stderr = String::new();
+                        child.stderr.take().map(|mut pipe| pipe.read_to_string(&mut stderr));
+                        Some(Err(if stderr.trim().is_empty() { status.to_string() } else { stderr.trim().to_string() }))
+                    }
+                    Ok(None) if started.elapsed() > self.timeout => {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        Some(Err(format!("timed out after {} s", self.timeout.as_secs_f64())))
+                    }
+                    Ok(None) => None,
+                    Err(e) => Some(Err(e.to_string())),
+                };
+                match outcome {
+                    None => i += 1,
+                    Some(result) => {
+                        let (job, attempt, _, _) = running.swap_remove(i);
+                        if let Err(message) = result {
+                            self.retry(job, attempt, message, &mut queue, &mut failures, jobs);
+                        }
+                    }
+                }
+            }
+            if !running.is_empty() {
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            failures.sort();
+            Err(GraphvizError::Failed(failures))
+        }
+    }
+
+    fn spawn(&self, job: &GraphvizJob) -> std::io::Result<Child> {
+        Command::new("dot")
+            .arg(format!("-T{}", self.format))
+            .arg("-o")
+            .arg(&job.output)
+            .arg(&job.input)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+    }
+
+    fn retry(&self, job: usize, attempt: usize, message: String, queue: &mut VecDeque<(usize, usize)>, failures: &mut Vec<(PathBuf, String)>, jobs: &[GraphvizJob]) {
+        if attempt < self.retries {
+            queue.push_back((job, attempt + 1));
+        } else {
+            failures.push((jobs[job].input.clone(), message));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{Request, Response, Status};
+
+use crate::diff::RankDiffer;
+use crate::graph::{event_times, Edge};
+use crate::provenance::EdgeSource;
+use crate::rank::RankConfig;
+use crate::registry::{AlgorithmInfo, AlgorithmRegistry};
+use crate::run::{continue_scenario, RunOutput};
+use crate::scenario::Scenario;
+use crate::server::MAX_NODES;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// gRPC scoring service of proto/trust_flow.proto, for consumers that want ranks without HTTP and
+// JSON. Unlike `trust-flow serve`, it ranks a single graph that grows with every SubmitEdges call;
+// GetRanks ranks it at one time and StreamRankUpdates sends a snapshot after every so many
+// submitted events, like --pipe --every-events.
+
+pub mod proto {
+    tonic::include_proto!("trust_flow");
+}
+
+use proto::trust_flow_server::{TrustFlow, TrustFlowServer};
+use proto::{GetRanksRequest, RankSnapshot, StreamRankUpdatesRequest, SubmitEdgesRequest, SubmitEdgesResponse};
+
+// Snapshots a stream holds for a slow client before its ranking waits
+const STREAM_BUFFER: usize = 16;
+
+pub struct TrustFlowService {
+    graph: Arc<Mutex<Scenario>>,
+    rank_config: RankConfig,
+    num_of_edges: watch::Sender<usize>, // tells the streams about submitted events
+}
+
+impl TrustFlowService {
+    // Serves the graph of the scenario, usually one without edges, with the rank settings
+    pub fn new(mut scenario: Scenario, rank_config: RankConfig) -> Self {
+        scenario.num_of_nodes = scenario.num_of_nodes.max(num_of_nodes(&scenario.edges, &scenario.experts));
+        let num_of_edges = watch::Sender::new(scenario.edges.len());
+        TrustFlowService { graph: Arc::new(Mutex::new(scenario)), rank_config, num_of_edges }
+    }
+}
+
+// Serves the service on the listener until the listener fails
+pub async fn serve(listener: TcpListener, service: TrustFlowService) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(TrustFlowServer::new(service))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+}
+
+#[tonic::async_trait]
+impl TrustFlow for TrustFlowService {
+    // Adds all events or, if one is invalid, none
+    async fn submit_edges(&self, request: Request<SubmitEdgesRequest>) -> Result<Response<SubmitEdgesResponse>, Status> {
+        let events = request.into_inner().events;
+        let mut graph = self.graph.lock().unwrap();
+        let first_line = graph.edges.len() + 1;
+        let edges = events
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let node = |id: u64| usize::try_from(id).ok().filter(|&id| id < MAX_NODES).map(NodeId).ok_or_else(|| Status::invalid_argument(format!("node ids must be below {}: {}", MAX_NODES, id)));
+                if !event.time.is_finite() {
+                    return Err(Status::invalid_argument(format!("time must be a finite number: {}", event.time)));
+                }
+                let weight = event.weight.unwrap_or(1.0);
+                if !(weight > 0.0 && weight.is_finite()) {
+                    return Err(Status::invalid_argument(format!("invalid weight: {} (expected a positive number)", weight)));
+                }
+                let edge = Edge { source: node(event.source)?, target: node(event.target)?, time_of_creation: Time(event.time), weight };
+                Ok((edge, EdgeSource { line: first_line + i, event_id: event.id, metadata: Vec::new() }))
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+        for (edge, source) in edges {
+            let edge = graph.oriented(edge);
+            graph.edges.push(edge);
+            graph.edge_sources.push(source);
+        }
+        graph.num_of_nodes = graph.num_of_nodes.max(num_of_nodes(&graph.edges, &graph.experts));
+        self.num_of_edges.send_replace(graph.edges.len());
+        Ok(Response::new(SubmitEdgesResponse { nodes: graph.num_of_nodes as u64, edges: graph.edges.len() as u64 }))
+    }
+
+    async fn get_ranks(&self, request: Request<GetRanksRequest>) -> Result<Response<RankSnapshot>, Status> {
+        let request = request.into_inner();
+        let info = algorithm(&request.algorithm)?;
+        let scenario = self.graph.lock().unwrap().clone();
+        let time = match request.time {
+            Some(time) if time.is_finite() => Time(time),
+            Some(time) => return Err(Status::invalid_argument(format!("time must be a finite number: {}", time))),
+            None => newest_time(&scenario).ok_or_else(|| Status::failed_precondition("no time: pass time or submit edges first"))?,
+        };
+        let config = self.rank_config.clone();
+        let ranks = tokio::task::spawn_blocking(move || rank(&scenario, info, &config, time)).await.map_err(|e| Status::internal(e.to_string()))??;
+        Ok(Response::new(RankSnapshot { frame: 1, time: time.value(), ranks, changes: HashMap::new() }))
+    }
+
+    type StreamRankUpdatesStream = ReceiverStream<Result<RankSnapshot, Status>>;
+
+    // Snapshots of the events submitted after the call; the stream ends when the client drops it
+    async fn stream_rank_updates(&self, request: Request<StreamRankUpdatesRequest>) -> Result<Response<Self::StreamRankUpdatesStream>, Status> {
+        let request = request.into_inner();
+        if request.every_events == 0 {
+            return Err(Status::invalid_argument("every_events must be positive"));
+        }
+        let mut differ = match request.diff_threshold {
+            Some(threshold) if threshold >= 0.0 && threshold.is_finite() => Some(RankDiffer::new(threshold)),
+            Some(threshold) => return Err(Status::invalid_argument(format!("diff_threshold must be a non-negative number: {}", threshold))),
+            None => None,
+        };
+        let info = algorithm(&request.algorithm)?;
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        let (graph, config) = (Arc::clone(&self.graph), self.rank_config.clone());
+        let mut num_of_edges = self.num_of_edges.subscribe();
+        let mut reported = *num_of_edges.borrow_and_update();
+        tokio::spawn(async move {
+            let mut frame = 0;
+            loop {
+                tokio::select! {
+                    changed = num_of_edges.changed() => if changed.is_err() { return },
+                    _ = sender.closed() => return,
+                }
+                let submitted = *num_of_edges.borrow_and_update();
+                if ((submitted - reported) as u64) < request.every_events {
+                    continue;
+                }
+                reported = submitted;
+                let scenario = graph.lock().unwrap().clone();
+                let Some(time) = newest_time(&scenario) else { continue };
+                let config = config.clone();
+                let ranks = tokio::task::spawn_blocking(move || rank(&scenario, info, &config, time)).await.unwrap_or_else(|e| Err(Status::internal(e.to_string())));
+                let snapshot = ranks.map(|ranks| {
+                    let mut snapshot = RankSnapshot { frame: FrameIdx(frame).number() as u64, time: time.value(), ranks: Vec::new(), changes: HashMap::new() };
+                    match &mut differ {
+                        Some(differ) => snapshot.changes = differ.diff(&ranks).iter().map(|d| (d.node.index() as u64, d.rank)).collect(),
+                        None => snapshot.ranks = ranks,
+                    }
+                    snapshot
+                });
+                frame += 1;
+                // A diff without changes above the threshold is not sent
+                if snapshot.as_ref().is_ok_and(|s| differ.is_some() && s.changes.is_empty()) {
+                    continue;
+                }
+                if sender.send(snapshot).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+// The algorithm of a request; pagerank when none is given
+fn algorithm(id: &str) -> Result<&'static AlgorithmInfo, Status> {
+    AlgorithmRegistry::get(if id.is_empty() { "pagerank" } else { id }).map_err(Status::invalid_argument)
+}
+
+fn newest_time(scenario: &Scenario) -> Option<Time> {
+    event_times(&scenario.edges).last().copied()
+}
+
+fn num_of_nodes(edges: &[Edge], experts: &[NodeId]) -> usize {
+    edges.iter().flat_map(|e| [e.source, e.target]).chain(experts.iter().copied()).map(|n| n.index() + 1).max().unwrap_or(0)
+}
+
+// Ranks the graph in a run of one frame
+fn rank(scenario: &Scenario, info: &AlgorithmInfo, config: &RankConfig, time: Time) -> Result<Vec<f64>, Status> {
+    let mut output = RunOutput::new();
+    continue_scenario(scenario, info.create(config).as_ref(), &[time], &scenario.graph_options, &mut output, &mut |_| Ok(()))
+        .map_err(|e| Status::internal(format!("cannot rank the graph: {}", e)))?;
+    Ok(output.history.ranks_at(FrameIdx(0)).to_vec())
+}
@@ -0,0 +1,79 @@
+use crate::graph::CompiledGraph;
+
+// Result hashes that do not change when nodes are relabeled, for checking that
+// anonymization or reindexing steps did not alter the computation.
+// FNV-1a is used instead of std's hasher so hashes are stable across Rust versions.
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// Floats are rounded before hashing so that summation order noise does not change the hash
+const QUANTUM: f64 = 1e-9;
+
+// Number of Weisfeiler-Lehman refinement rounds
+const REFINEMENT_ROUNDS: usize = 3;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |h, &b| (h ^ b as u64).wrapping_mul(FNV_PRIME))
+}
+
+// Hash of arbitrary bytes, e.g. to tell whether a checkpoint belongs to the same run settings
+pub fn bytes_hash(bytes: &[u8]) -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, bytes)
+}
+
+fn hash_u64s(values: &[u64]) -> u64 {
+    values.iter().fold(FNV_OFFSET_BASIS, |h, v| fnv1a(h, &v.to_le_bytes()))
+}
+
+fn quantize(value: f64) -> u64 {
+    (value / QUANTUM).round() as i64 as u64
+}
+
+// Hash of the sorted multiset of rank values
+pub fn rank_multiset_hash(ranks: &[f64]) -> u64 {
+    let mut values: Vec<u64> = ranks.iter().map(|&r| quantize(r)).collect();
+    values.sort_unstable();
+    hash_u64s(&values)
+}
+
+// Weisfeiler-Lehman style hash of the weighted graph: every node starts with the given label
+// and is repeatedly relabeled with the sorted labels of its in- and out-neighbours (and the
+// weights of the connecting edges). The hash of the final sorted label multiset does not depend
+// on node numbering. Isomorphic graphs always hash equally; distinct graphs may rarely collide.
+pub fn canonical_graph_hash(graph: &CompiledGraph, node_labels: &[u64]) -> u64 {
+    let mut labels = node_labels.to_vec();
+    for _ in 0..REFINEMENT_ROUNDS {
+        let mut incoming: Vec<Vec<u64>> = vec![Vec::new(); graph.num_of_nodes];
+        let mut outgoing: Vec<Vec<u64>> = vec![Vec::new(); graph.num_of_nodes];
+        for edge in &graph.edges {
+            let weight = quantize(edge.weight);
+            incoming[edge.target.index()].push(hash_u64s(&[labels[edge.source.index()], weight]));
+            outgoing[edge.source.index()].push(hash_u64s(&[labels[edge.target.index()], weight]));
+        }
+        labels = (0..graph.num_of_nodes)
+            .map(|i| {
+                incoming[i].sort_unstable();
+                outgoing[i].sort_unstable();
+                let mut signature = vec![labels[i], incoming[i].len() as u64];
+                signature.extend(&incoming[i]);
+                signature.push(outgoing[i].len() as u64);
+                signature.extend(&outgoing[i]);
+                hash_u64s(&signature)
+            })
+            .collect();
+    }
+    labels.sort_unstable();
+    hash_u64s(&labels)
+}
+
+// Hash of a single frame: rank multiset plus the graph hash with every node labeled by its rank
+pub fn frame_hash(graph: &CompiledGraph, ranks: &[f64]) -> u64 {
+    let node_labels: Vec<u64> = ranks.iter().map(|&r| quantize(r)).collect();
+    hash_u64s(&[rank_multiset_hash(ranks), canonical_graph_hash(graph, &node_labels)])
+}
+
+// Hash of a whole run, frame hashes combined in frame order
+pub fn run_hash(frame_hashes: &[u64]) -> u64 {
+    hash_u64s(frame_hashes)
+}
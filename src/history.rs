@@ -0,0 +1,241 @@
+use std::io::{self, Write};
+
+use crate::types::{FrameIdx, NodeId, Time};
+
+// Rank vectors of every frame of a run, for per-node trajectories and trends
+#[derive(Debug, Clone, Default)]
+pub struct RankHistory {
+    times: Vec<Time>,
+    frames: Vec<Vec<f64>>,
+}
+
+// Rank change of a node between two consecutive frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankMove {
+    pub node: NodeId,
+    pub from: FrameIdx,
+    pub to: FrameIdx,
+    pub change: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Stable,
+}
+
+// Slopes (rank per unit time) smaller than this in magnitude count as stable
+const STABLE_SLOPE: f64 = 1e-3;
+
+impl RankHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, time: Time, ranks: Vec<f64>) {
+        self.times.push(time);
+        self.frames.push(ranks);
+    }
+
+    pub fn num_of_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn num_of_nodes(&self) -> usize {
+        self.frames.first().map_or(0, |ranks| ranks.len())
+    }
+
+    pub fn frames(&self) -> &[Vec<f64>] {
+        &self.frames
+    }
+
+    pub fn times(&self) -> &[Time] {
+        &self.times
+    }
+
+    pub fn ranks_at(&self, frame: FrameIdx) -> &[f64] {
+        &self.frames[frame.index()]
+    }
+
+    // Exponential moving average over the frames, s_k = smoothing * r_k + (1 - smoothing) * s_(k-1),
+    // which damps the jumps of abruptly appearing edges; 1 keeps the ranks as they are. Smoothed
+    // ranks still sum to 1, and nodes that appear in a later frame start from their own rank.
+    pub fn smoothed(&self, smoothing: f64) -> RankHistory {
+        let mut smoothed = RankHistory::new();
+        for (&time, ranks) in self.times.iter().zip(&self.frames) {
+            let previous = smoothed.frames.last();
+            let ranks = ranks
+                .iter()
+                .enumerate()
+                .map(|(node, &rank)| match previous.and_then(|previous| previous.get(node)) {
+                    Some(&average) => smoothing * rank + (1.0 - smoothing) * average,
+                    None => rank,
+                })
+                .collect();
+            smoothed.push(time, ranks);
+        }
+        smoothed
+    }
+
+    pub fn trajectory(&self, node: NodeId) -> Vec<(Time, f64)> {
+        self.times
+            .iter()
+            .zip(self.frames.iter())
+            .map(|(&time, ranks)| (time, ranks[node.index()]))
+            .collect()
+    }
+
+    // Rank change per unit time between consecutive frames (one value fewer than frames)
+    pub fn velocity(&self, node: NodeId) -> Vec<f64> {
+        self.trajectory(node)
+            .windows(2)
+            .map(|pair| {
+                let ((t0, r0), (t1, r1)) = (pair[0], pair[1]);
+                let elapsed = t1.elapsed_since(t0);
+                if elapsed > 0.0 { (r1 - r0) / elapsed } else { 0.0 }
+            })
+            .collect()
+    }
+
+    // Every node's rank change between every pair of consecutive frames
+    fn moves(&self) -> Vec<RankMove> {
+        let mut moves = Vec::new();
+        for (i, pair) in self.frames.windows(2).enumerate() {
+            for (node, (&old, &new)) in pair[0].iter().zip(pair[1].iter()).enumerate() {
+                moves.push(RankMove {
+                    node: NodeId(node),
+                    from: FrameIdx(i),
+                    to: FrameIdx(i + 1),
+                    change: new - old,
+                });
+            }
+        }
+        moves
+    }
+
+    pub fn largest_rises(&self, count: usize) -> Vec<RankMove> {
+        let mut moves: Vec<RankMove> = self.moves().into_iter().filter(|m| m.change > 0.0).collect();
+        moves.sort_by(|a, b| b.change.total_cmp(&a.change));
+        moves.truncate(count);
+        moves
+    }
+
+    pub fn largest_falls(&self, count: usize) -> Vec<RankMove> {
+        let mut moves: Vec<RankMove> = self.moves().into_iter().filter(|m| m.change < 0.0).collect();
+        moves.sort_by(|a, b| a.change.total_cmp(&b.change));
+        moves.truncate(count);
+        moves
+    }
+
+    // Per frame, whether no node's rank differs by `threshold` or more from its rank in the last
+    // kept frame. Comparing against the last kept frame rather than the previous one means slow
+    // drift still shows up once it adds up. The first frame is always kept.
+    pub fn elided_frames(&self, threshold: f64) -> Vec<bool> {
+        let mut kept = 0;
+        (0..self.frames.len())
+            .map(|frame| {
+                let change = self.frames[frame].iter().zip(&self.frames[kept]).map(|(new, old)| (new - old).abs()).fold(0.0, f64::max);
+                let elided = frame > 0 && change < threshold;
+                if !elided {
+                    kept = frame;
+                }
+                elided
+            })
+            .collect()
+    }
+
+    // Least-squares slope of the node's rank over the last `num_of_frames` frames
+    pub fn trend(&self, node: NodeId, num_of_frames: usize) -> Trend {
+        let trajectory = self.trajectory(node);
+        let recent = &trajectory[trajectory.len().saturating_sub(num_of_frames)..];
+        if recent.len() < 2 {
+            return Trend::Stable;
+        }
+        let n = recent.len() as f64;
+        let mean_t = recent.iter().map(|(t, _)| t.value()).sum::<f64>() / n;
+        let mean_r = recent.iter().map(|(_, r)| r).sum::<f64>() / n;
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for (t, r) in recent {
+            covariance += (t.value() - mean_t) * (r - mean_r);
+            variance += (t.value() - mean_t).powi(2);
+        }
+        let slope = if variance > 0.0 { covariance / variance } else { 0.0 };
+        if slope > STABLE_SLOPE {
+            Trend::Rising
+        } else if slope < -STABLE_SLOPE {
+            Trend::Falling
+        } else {
+            Trend::Stable
+        }
+    }
+
+    // "Movers and shakers": biggest single-frame rises and falls, net change over the run
+    // and the current trend of every node
+    pub fn write_movers_summary(&self, out: &mut dyn Write, count: usize, trend_frames: usize, names: &[String]) -> io::Result<()> {
+        writeln!(out, "Largest rises:")?;
+        for m in self.largest_rises(count) {
+            writeln!(out, "  node {:>3}  {:+.4}  (frame {} -> {})", names[m.node.index()], m.change, m.from.number(), m.to.number())?;
+        }
+        writeln!(out, "Largest falls:")?;
+        for m in self.largest_falls(count) {
+            writeln!(out, "  node {:>3}  {:+.4}  (frame {} -> {})", names[m.node.index()], m.change, m.from.number(), m.to.number())?;
+        }
+        if let (Some(first), Some(last)) = (self.frames.first(), self.frames.last()) {
+            writeln!(out, "Net change over the run and trend over the last {} frames:", trend_frames)?;
+            for node in 0..self.num_of_nodes() {
+                let trend = match self.trend(NodeId(node), trend_frames) {
+                    Trend::Rising => "rising",
+                    Trend::Falling => "falling",
+                    Trend::Stable => "stable",
+                };
+                writeln!(out, "  node {:>3}  {:.4} -> {:.4}  ({:+.4}, {})", names[node], first[node], last[node], last[node] - first[node], trend)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Long-format rank timeline: frame,time,node,label,rank rows, one per node and frame
+    pub fn write_timeline_csv(&self, out: &mut dyn Write, names: &[String]) -> io::Result<()> {
+        writeln!(out, "frame,time,node,label,rank")?;
+        for (frame, (time, ranks)) in self.times.iter().zip(&self.frames).enumerate() {
+            for (node, &rank) in ranks.iter().enumerate() {
+                writeln!(out, "{},{},{},{},{:.6}", FrameIdx(frame).number(), time, node, names[node], rank)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Reads back what write_timeline_csv wrote, e.g. to start a run from a previous run's ranks
+    pub fn read_timeline_csv(text: &str) -> Result<RankHistory, String> {
+        let mut history = RankHistory::new();
+        for (i, line) in text.lines().enumerate().skip(1).filter(|(_, line)| !line.trim().is_empty()) {
+            let error = |what: &str| format!("line {}: invalid {}: {}", i + 1, what, line);
+            let fields: Vec<&str> = line.split(',').collect();
+            let (Some(frame), Some(time), Some(node), Some(rank)) = (fields.first(), fields.get(1), fields.get(2), fields.last()) else {
+                return Err(error("row"));
+            };
+            let frame: usize = frame.parse().map_err(|_| error("frame"))?;
+            let node: usize = node.parse().map_err(|_| error("node"))?;
+            if frame > history.num_of_frames() {
+                history.push(Time(time.parse().map_err(|_| error("time"))?), Vec::new());
+            }
+            let ranks = history.frames.last_mut().ok_or_else(|| error("frame"))?;
+            ranks.resize(ranks.len().max(node + 1), 0.0);
+            ranks[node] = rank.parse().map_err(|_| error("rank"))?;
+        }
+        Ok(history)
+    }
+
+    // Node labels of a timeline written by write_timeline_csv; nodes without a row get their index
+    pub fn read_timeline_labels(text: &str) -> Vec<String> {
+        let mut labels: Vec<Option<String>> = Vec::new();
+        for fields in text.lines().skip(1).map(|line| line.split(',').collect::<Vec<_>>()).filter(|fields| fields.len() >= 5) {
+            let Ok(node) = fields[2].parse::<usize>() else { continue };
+            labels.resize(labels.len().max(node + 1), None);
+            labels[node].get_or_insert_with(|| fields[3..fields.len() - 1].join(","));
+        }
+        labels.into_iter().enumerate().map(|(node, label)| label.unwrap_or_else(|| node.to_string())).collect()
+    }
+}
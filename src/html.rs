@@ -0,0 +1,211 @@
+use std::io::{self, Write};
+
+use crate::json::Json;
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::types::{EdgeId, NodeId};
+
+// Self-contained HTML page with the temporal graph embedded as JSON and a small
+// dependency-free player: time slider, play button, node tooltips with rank values
+pub fn write_html_viewer(out: &mut dyn Write, scenario: &Scenario, output: &RunOutput, positions: &[(f64, f64)]) -> io::Result<()> {
+    // "</" must not appear inside the script element
+    let data = viewer_data(scenario, output, positions).to_string().replace("</", "<\\/");
+    write!(out, "{}", TEMPLATE.replace("/*DATA*/null", &data))
+}
+
+// The temporal graph and its ranks as the viewer reads them: nodes, edges and one entry per frame
+pub fn viewer_data(scenario: &Scenario, output: &RunOutput, positions: &[(f64, f64)]) -> Json {
+    let nodes = (0..scenario.num_of_nodes)
+        .map(|i| Json::object(vec![
+            ("id", i.into()),
+            ("name", scenario.node_label(NodeId(i)).into()),
+            ("category", scenario.node_info(NodeId(i)).and_then(|n| n.category.clone()).map_or(Json::Null, Json::from)),
+            ("x", positions[i].0.into()),
+            ("y", positions[i].1.into()),
+            ("expert", scenario.experts.iter().any(|e| e.index() == i).into()),
+        ]))
+        .collect();
+    let edges = scenario.edges
+        .iter()
+        .enumerate()
+        .map(|(id, e)| {
+            let mut fields = vec![
+                ("source", e.source.index().into()),
+                ("target", e.target.index().into()),
+                ("time", e.time_of_creation.value().into()),
+            ];
+            // Only edges with metadata from the input carry it
+            if let Some(source) = scenario.edge_source(EdgeId(id)).filter(|s| !s.metadata.is_empty()) {
+                fields.push(("metadata", Json::Object(source.metadata.iter().map(|(key, value)| (key.clone(), value.as_str().into())).collect())));
+            }
+            Json::object(fields)
+        })
+        .collect();
+    let frames = output.history.times()
+        .iter()
+        .zip(output.history.frames())
+        .zip(&output.edge_weights)
+        .map(|((time, ranks), weights)| Json::object(vec![
+            ("time", time.value().into()),
+            ("ranks", Json::numbers(ranks)),
+            ("weights", Json::numbers(weights)),
+            ("experts", Json::Array(scenario.experts_at(*time).iter().map(|e| e.index().into()).collect())),
+            ("annotations", Json::Array(scenario.annotations_at(*time).into_iter().map(Json::from).collect())),
+        ]))
+        .collect();
+    let annotations = scenario.annotations
+        .iter()
+        .map(|a| Json::object(vec![("time", a.time.value().into()), ("text", a.text.as_str().into())]))
+        .collect();
+    let mut fields = vec![
+        ("name", scenario.name.as_str().into()),
+        ("decay", scenario.decay_description().into()),
+        ("seed", scenario.seed.to_string().into()), // a string, JSON numbers cannot hold every u64
+        ("nodes", Json::Array(nodes)),
+        ("edges", Json::Array(edges)),
+        ("frames", Json::Array(frames)),
+        ("annotations", Json::Array(annotations)),
+    ];
+    // Only when enabled, so exports without ghost edges stay as they were
+    if scenario.style.ghost_edges {
+        fields.push(("ghost_edges", true.into()));
+    }
+    Json::object(fields)
+}
+
+const TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Trust flow</title>
+<style>
+  body { font-family: sans-serif; margin: 20px; }
+  #controls { margin: 10px 0; }
+  #slider { width: 400px; margin: 0; }
+  svg { border: 1px solid #DDDDDD; }
+  .label { font-size: 12px; pointer-events: none; }
+  #track { display: inline-block; vertical-align: middle; }
+  #timeline { position: relative; width: 400px; height: 14px; }
+  #timeline span { position: absolute; top: 0; width: 8px; height: 14px; margin-left: -4px; background: #CC6600; cursor: pointer; }
+  #caption { min-height: 1.2em; font-style: italic; }
+</style>
+</head>
+<body>
+<h2 id="title"></h2>
+<div id="controls">
+  <button id="play">Play</button>
+  <span id="track"><div id="timeline"></div><input id="slider" type="range" min="0" value="0"></span>
+  <span id="status"></span>
+  <div id="caption"></div>
+</div>
+<svg id="graph" width="640" height="640" viewBox="-1.4 -1.4 2.8 2.8">
+  <defs>
+    <marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="4" markerHeight="4" orient="auto">
+      <path d="M0,0 L10,5 L0,10 z" fill="#333333"></path>
+    </marker>
+  </defs>
+  <g id="edges"></g>
+  <g id="nodes"></g>
+</svg>
+<script>
+const data = /*DATA*/null;
+const NS = "http://www.w3.org/2000/svg";
+const radius = 0.12;
+const slider = document.getElementById("slider");
+const status = document.getElementById("status");
+document.getElementById("title").textContent = "Trust flow over time: " + data.name + " (edge decay: " + data.decay + ")";
+slider.max = data.frames.length - 1;
+
+// Annotation markers above the slider, at the first frame that shows them
+data.annotations.forEach(function (a) {
+  const index = data.frames.findIndex(function (f) { return f.time >= a.time; });
+  if (index < 0) {
+    return;
+  }
+  const marker = document.createElement("span");
+  marker.style.left = (data.frames.length > 1 ? index / (data.frames.length - 1) * 100 : 0) + "%";
+  marker.title = "time " + a.time + ": " + a.text;
+  marker.addEventListener("click", function () { show(index); });
+  document.getElementById("timeline").appendChild(marker);
+});
+
+const edgeElements = data.edges.map(function (e) {
+  const a = data.nodes[e.source], b = data.nodes[e.target];
+  const dx = b.x - a.x, dy = b.y - a.y, length = Math.hypot(dx, dy) || 1;
+  const line = document.createElementNS(NS, "line");
+  line.setAttribute("x1", a.x + dx / length * radius);
+  line.setAttribute("y1", -a.y - dy / length * radius);
+  line.setAttribute("x2", b.x - dx / length * radius);
+  line.setAttribute("y2", -b.y + dy / length * radius);
+  line.setAttribute("stroke", "#333333");
+  line.setAttribute("marker-end", "url(#arrow)");
+  document.getElementById("edges").appendChild(line);
+  return line;
+});
+
+const nodeElements = data.nodes.map(function (n) {
+  const group = document.createElementNS(NS, "g");
+  const circle = document.createElementNS(NS, "circle");
+  circle.setAttribute("cx", n.x);
+  circle.setAttribute("cy", -n.y);
+  circle.setAttribute("r", radius);
+  circle.setAttribute("stroke", n.expert ? "darkgreen" : "#333333");
+  circle.setAttribute("stroke-width", n.expert ? 0.04 : 0.01);
+  const tooltip = document.createElementNS(NS, "title");
+  circle.appendChild(tooltip);
+  const label = document.createElementNS(NS, "text");
+  label.setAttribute("x", n.x);
+  label.setAttribute("y", -n.y + 0.03);
+  label.setAttribute("text-anchor", "middle");
+  label.setAttribute("font-size", "0.08");
+  label.setAttribute("class", "label");
+  group.appendChild(circle);
+  group.appendChild(label);
+  document.getElementById("nodes").appendChild(group);
+  return { circle: circle, tooltip: tooltip, label: label };
+});
+
+function show(index) {
+  const frame = data.frames[index];
+  frame.ranks.forEach(function (rank, i) {
+    const level = Math.round((1 - Math.min(Math.max(rank, 0), 1)) * 255);
+    nodeElements[i].circle.setAttribute("fill", "rgb(" + level + "," + level + ",255)");
+    const expert = frame.experts.indexOf(i) >= 0;
+    nodeElements[i].circle.setAttribute("stroke", expert ? "darkgreen" : "#333333");
+    nodeElements[i].circle.setAttribute("stroke-width", expert ? 0.04 : 0.01);
+    const node = data.nodes[i];
+    nodeElements[i].tooltip.textContent = "node " + node.name + (node.category ? " (" + node.category + ")" : "") + "\nrank " + rank.toFixed(4);
+    nodeElements[i].label.textContent = node.name + " (" + rank.toFixed(2) + ")";
+  });
+  frame.weights.forEach(function (w, i) {
+    // Edges yet to be created are previewed as dashed ghosts
+    const upcoming = data.ghost_edges && w === 0 && data.edges[i].time > frame.time;
+    edgeElements[i].setAttribute("visibility", w > 0 || upcoming ? "visible" : "hidden");
+    edgeElements[i].setAttribute("stroke", upcoming ? "#c8c8c8" : "#333333");
+    edgeElements[i].setAttribute("stroke-dasharray", upcoming ? "0.03 0.02" : "none");
+    edgeElements[i].setAttribute("stroke-width", upcoming ? 0.01 : 0.03 * w);
+  });
+  slider.value = index;
+  status.textContent = "Frame " + (index + 1) + "/" + data.frames.length + ", time " + frame.time;
+  document.getElementById("caption").textContent = frame.annotations.join("; ");
+}
+
+let timer = null;
+document.getElementById("play").addEventListener("click", function () {
+  if (timer) {
+    clearInterval(timer);
+    timer = null;
+    this.textContent = "Play";
+    return;
+  }
+  this.textContent = "Pause";
+  timer = setInterval(function () {
+    show((Number(slider.value) + 1) % data.frames.length);
+  }, 500);
+});
+slider.addEventListener("input", function () { show(Number(slider.value)); });
+show(0);
+</script>
+</body>
+</html>
+"##;
@@ -0,0 +1,74 @@
+use std::io::{self, Write};
+
+use crate::rank::{RankConfig, RankIteration};
+use crate::scenario::Scenario;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// Per frame, the fraction of every node's rank that originates from teleportation onto experts,
+// as opposed to organic flow (uniform teleportation, initial mass) moved along the edges.
+// Computed with a tagged-mass iteration running next to the custom PageRank variant.
+#[derive(Debug, Clone, Default)]
+pub struct ExpertInfluence {
+    pub times: Vec<Time>,
+    pub ranks: Vec<Vec<f64>>,
+    pub shares: Vec<Vec<f64>>, // per frame and node, in [0, 1]; 0 for nodes without rank
+}
+
+impl ExpertInfluence {
+    // Share of the rank (mass) held by the given nodes, all nodes when None, that originates
+    // from expert teleportation in a frame
+    pub fn total_share(&self, frame: FrameIdx, nodes: Option<&[NodeId]>) -> f64 {
+        let included = |node: usize| nodes.is_none_or(|nodes| nodes.contains(&NodeId(node)));
+        let ranks = &self.ranks[frame.index()];
+        let shares = &self.shares[frame.index()];
+        let (mut expert_mass, mut total) = (0.0, 0.0);
+        for node in (0..ranks.len()).filter(|&node| included(node)) {
+            expert_mass += ranks[node] * shares[node];
+            total += ranks[node];
+        }
+        if total > 0.0 { expert_mass / total } else { 0.0 }
+    }
+}
+
+pub fn expert_influence(scenario: &Scenario, config: &RankConfig, frame_times: &[Time]) -> ExpertInfluence {
+    let mut influence = ExpertInfluence::default();
+    for &time in frame_times {
+        let edge_weights = scenario.edge_weights(time);
+        let graph = scenario.frame_graph(time, &edge_weights).unwrap();
+        let teleportation_targets = scenario.teleportation_targets(time);
+        let mut tagged_targets = vec![0.0; scenario.num_of_nodes];
+        for expert in scenario.experts_at(time) {
+            tagged_targets[expert.index()] = teleportation_targets[expert.index()];
+        }
+
+        let mut iteration = RankIteration::new(&graph, &teleportation_targets, config);
+        let mut tagged_mass = vec![0.0; scenario.num_of_nodes];
+        while iteration.next().is_some() {
+            tagged_mass = iteration.step_tagged(&tagged_mass, &tagged_targets);
+        }
+        let ranks = iteration.into_ranks();
+        let shares = ranks
+            .iter()
+            .zip(&tagged_mass)
+            .map(|(&rank, &tagged)| if rank > 0.0 { (tagged / rank).clamp(0.0, 1.0) } else { 0.0 })
+            .collect();
+
+        influence.times.push(time);
+        influence.ranks.push(ranks);
+        influence.shares.push(shares);
+    }
+    influence
+}
+
+// frame,time,node,rank,expert_share rows, plus one row per frame with node "total"
+pub fn write_influence_csv(out: &mut dyn Write, scenario: &Scenario, influence: &ExpertInfluence) -> io::Result<()> {
+    writeln!(out, "frame,time,node,rank,expert_share")?;
+    for (frame, time) in influence.times.iter().enumerate() {
+        let frame_idx = FrameIdx(frame);
+        for (node, (rank, share)) in scenario.node_labels().iter().zip(influence.ranks[frame].iter().zip(&influence.shares[frame])) {
+            writeln!(out, "{},{},{},{:.6},{:.6}", frame_idx.number(), time, node, rank, share)?;
+        }
+        writeln!(out, "{},{},total,{:.6},{:.6}", frame_idx.number(), time, influence.ranks[frame].iter().sum::<f64>(), influence.total_share(frame_idx, None))?;
+    }
+    Ok(())
+}
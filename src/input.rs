@@ -0,0 +1,299 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use crate::graph::Edge;
+use crate::json::Json;
+use crate::provenance::EdgeSource;
+use crate::types::{NodeId, Time};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventFormat {
+    #[default]
+    Jsonl, // {"source": 0, "target": 1, "time": 2.5} per line, optional "weight", "count" or "amount", "id" and a "metadata" object
+    Csv,   // source,target,time[,id] per line, or the columns named by a header; other named columns are metadata
+}
+
+// How the interaction count (or amount) of an event becomes the initial weight of its edge, so
+// that repeated interactions earn more trust than a single one. Each maps 1 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightTransform {
+    Linear, // the count itself
+    Log,    // log2(1 + count): 10 interactions weigh about 3.5
+    Sqrt,   // square root: 10 interactions weigh about 3.2
+}
+
+impl WeightTransform {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "linear" => Ok(WeightTransform::Linear),
+            "log" => Ok(WeightTransform::Log),
+            "sqrt" => Ok(WeightTransform::Sqrt),
+            _ => Err(format!("unknown weight transform: {} (expected linear, log or sqrt)", name)),
+        }
+    }
+
+    pub fn weight(self, count: f64) -> f64 {
+        match self {
+            WeightTransform::Linear => count,
+            WeightTransform::Log => (1.0 + count).log2(),
+            WeightTransform::Sqrt => count.sqrt(),
+        }
+    }
+}
+
+// An edge event together with the identifier and metadata the input gave it, if any
+#[derive(Debug, Clone)]
+pub struct EdgeEvent {
+    pub edge: Edge,
+    pub id: Option<String>,
+    pub metadata: Vec<(String, String)>,
+}
+
+impl EdgeEvent {
+    // The edge and where it came from, for the scenario's edges and edge sources
+    pub fn into_parts(self, line: usize) -> (Edge, EdgeSource) {
+        (self.edge, EdgeSource { line, event_id: self.id, metadata: self.metadata })
+    }
+}
+
+// Positions of the CSV columns
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CsvColumns {
+    source: usize,
+    target: usize,
+    time: usize,
+    weight: Option<usize>,
+    count: Option<usize>,
+    id: Option<usize>,
+    metadata: Vec<(usize, String)>, // every other column and its name
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        CsvColumns { source: 0, target: 1, time: 2, weight: None, count: None, id: Some(3), metadata: Vec::new() }
+    }
+}
+
+impl CsvColumns {
+    // A header names the columns in any order: source, target, time (or timestamp), and optionally
+    // weight, count (or amount) and id. Any other named column is edge metadata.
+    fn from_header(fields: &[&str]) -> Result<Self, String> {
+        let column = |names: &[&str]| fields.iter().position(|f| names.contains(f));
+        let required = |names: &[&str]| column(names).ok_or_else(|| format!("CSV header has no '{}' column", names[0]));
+        let known = ["source", "target", "time", "timestamp", "weight", "count", "amount", "id"];
+        let metadata = fields.iter().enumerate().filter(|(_, f)| !f.is_empty() && !known.contains(f)).map(|(i, f)| (i, f.to_string())).collect();
+        Ok(CsvColumns {
+            source: required(&["source"])?,
+            target: required(&["target"])?,
+            time: required(&["time", "timestamp"])?,
+            weight: column(&["weight"]),
+            count: column(&["count", "amount"]),
+            id: column(&["id"]),
+            metadata,
+        })
+    }
+}
+
+// Parses edge events line by line, remembering the CSV header
+#[derive(Debug, Clone, Default)]
+pub struct EventParser {
+    format: EventFormat,
+    columns: CsvColumns,
+    weight_transform: Option<WeightTransform>, // weighs events without a weight by their count; counts are ignored without one
+}
+
+impl EventParser {
+    pub fn new(format: EventFormat) -> Self {
+        EventParser { format, columns: CsvColumns::default(), weight_transform: None }
+    }
+
+    pub fn weight_transform(mut self, weight_transform: Option<WeightTransform>) -> Self {
+        self.weight_transform = weight_transform;
+        self
+    }
+
+    // Forgets the CSV header, for input that starts over
+    fn restart(&mut self) {
+        self.columns = CsvColumns::default();
+    }
+
+    // Weight of an event with the given weight and count fields, either of them possibly absent
+    fn event_weight(&self, weight: Option<f64>, count: Option<f64>) -> Result<f64, String> {
+        match (weight, count, self.weight_transform) {
+            (Some(weight), _, _) => parse_weight(weight),
+            (None, Some(count), Some(transform)) if count > 0.0 && count.is_finite() => Ok(transform.weight(count)),
+            (None, Some(count), Some(_)) => Err(format!("invalid count: {} (expected a positive number)", count)),
+            _ => Ok(1.0),
+        }
+    }
+
+    // Ok(None) for blank lines, comments and CSV headers
+    pub fn parse_line(&mut self, line: &str) -> Result<Option<EdgeEvent>, String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+        match self.format {
+            EventFormat::Jsonl => {
+                let event = Json::parse(line)?;
+                let field = |name: &str| {
+                    event.get(name).and_then(Json::as_f64).ok_or_else(|| format!("missing numeric field '{}'", name))
+                };
+                let optional = |name: &str| event.get(name).map(|_| field(name)).transpose();
+                // Indices beyond 2^53 cannot be told apart as JSON numbers
+                let node = |name: &str| {
+                    let index = field(name)?;
+                    if index >= 0.0 && index.fract() == 0.0 && index < (1u64 << 53) as f64 { Ok(NodeId(index as usize)) } else { Err(format!("invalid {}: {} (expected a node index)", name, index)) }
+                };
+                let edge = Edge {
+                    source: node("source")?,
+                    target: node("target")?,
+                    time_of_creation: Time(field("time")?),
+                    weight: self.event_weight(optional("weight")?, optional("count")?.or(optional("amount")?))?,
+                };
+                let id = match event.get("id") {
+                    Some(Json::String(id)) => Some(id.clone()),
+                    Some(Json::Null) | None => None,
+                    Some(id) => Some(id.to_string()),
+                };
+                let metadata = match event.get("metadata") {
+                    Some(Json::Object(fields)) => fields.iter().map(|(key, value)| (key.clone(), value.as_str().map_or_else(|| value.to_string(), str::to_string))).collect(),
+                    Some(Json::Null) | None => Vec::new(),
+                    Some(other) => return Err(format!("metadata must be an object, not {}", other)),
+                };
+                Ok(Some(EdgeEvent { edge, id, metadata }))
+            }
+            EventFormat::Csv => {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                if fields.contains(&"source") {
+                    self.columns = CsvColumns::from_header(&fields)?;
+                    return Ok(None);
+                }
+                let columns = &self.columns;
+                let field = |i: usize, what: &str| fields.get(i).copied().ok_or_else(|| format!("missing {} in: {}", what, line));
+                let source = field(columns.source, "source")?;
+                let target = field(columns.target, "target")?;
+                let time = field(columns.time, "time")?;
+                let optional = |column: Option<usize>, what: &str| {
+                    column.and_then(|i| fields.get(i)).filter(|v| !v.is_empty()).map(|v| v.parse::<f64>().map_err(|_| format!("invalid {}: {}", what, v))).transpose()
+                };
+                let edge = Edge {
+                    source: NodeId(source.parse().map_err(|_| format!("invalid source: {}", source))?),
+                    target: NodeId(target.parse().map_err(|_| format!("invalid target: {}", target))?),
+                    time_of_creation: Time(time.parse().map_err(|_| format!("invalid time: {}", time))?),
+                    weight: self.event_weight(optional(columns.weight, "weight")?, optional(columns.count, "count")?)?,
+                };
+                let id = columns.id.and_then(|i| fields.get(i)).filter(|id| !id.is_empty()).map(|id| id.to_string());
+                let metadata = columns.metadata.iter().filter_map(|(i, key)| fields.get(*i).filter(|v| !v.is_empty()).map(|v| (key.clone(), v.to_string()))).collect();
+                Ok(Some(EdgeEvent { edge, id, metadata }))
+            }
+        }
+    }
+}
+
+fn parse_weight(weight: f64) -> Result<f64, String> {
+    if weight > 0.0 && weight.is_finite() {
+        Ok(weight)
+    } else {
+        Err(format!("invalid weight: {} (expected a positive number)", weight))
+    }
+}
+
+// Parses a single edge event with the default CSV columns; Ok(None) for blank lines, comments and CSV headers
+pub fn parse_edge_event(line: &str, format: EventFormat) -> Result<Option<EdgeEvent>, String> {
+    EventParser::new(format).parse_line(line)
+}
+
+// Parses all edge events, with the line each one came from; errors carry the 1-based line number
+pub fn parse_edge_events(text: &str, mut parser: EventParser) -> Result<(Vec<Edge>, Vec<EdgeSource>), String> {
+    let mut edges = Vec::new();
+    let mut sources = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if let Some(event) = parser.parse_line(line).map_err(|e| format!("line {}: {}", i + 1, e))? {
+            let (edge, source) = event.into_parts(i + 1);
+            edges.push(edge);
+            sources.push(source);
+        }
+    }
+    Ok((edges, sources))
+}
+
+// Bytes just before the read offset that are compared on every poll to notice rewritten files
+const FINGERPRINT_LENGTH: usize = 256;
+
+// Edges read by one poll of an EventTail
+#[derive(Debug, Clone, Default)]
+pub struct TailBatch {
+    pub edges: Vec<Edge>,
+    pub sources: Vec<EdgeSource>,
+    pub restarted: bool, // the file was rewritten and these are all of its edges, replacing earlier batches
+}
+
+// Follows a growing event file, like `tail -f`: every poll parses the complete lines appended
+// since the previous one. A trailing line without a newline is kept until it is finished.
+pub struct EventTail {
+    path: PathBuf,
+    parser: EventParser,
+    offset: u64,
+    fingerprint: Vec<u8>, // last bytes before `offset`
+    pending: String,
+    lines_read: usize,
+}
+
+impl EventTail {
+    pub fn new(path: impl Into<PathBuf>, parser: EventParser) -> Self {
+        EventTail {
+            path: path.into(),
+            parser,
+            offset: 0,
+            fingerprint: Vec::new(),
+            pending: String::new(),
+            lines_read: 0,
+        }
+    }
+
+    // Edges appended since the last poll, with the lines they came from. A file that shrank or
+    // whose already read end changed (e.g. saved from an editor) is read again from the start.
+    pub fn poll(&mut self) -> Result<TailBatch, String> {
+        let describe = |e: io::Error| format!("{}: {}", self.path.display(), e);
+        let mut file = File::open(&self.path).map_err(describe)?;
+        let length = file.metadata().map_err(describe)?.len();
+        let mut restarted = length < self.offset;
+        if !restarted && !self.fingerprint.is_empty() {
+            let mut current = vec![0; self.fingerprint.len()];
+            file.seek(SeekFrom::Start(self.offset - current.len() as u64)).map_err(describe)?;
+            file.read_exact(&mut current).map_err(describe)?;
+            restarted = current != self.fingerprint;
+        }
+        if restarted {
+            self.offset = 0;
+            self.fingerprint.clear();
+            self.pending.clear();
+            self.lines_read = 0;
+            self.parser.restart();
+        }
+
+        file.seek(SeekFrom::Start(self.offset)).map_err(describe)?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended).map_err(describe)?;
+        self.offset += appended.len() as u64;
+        self.fingerprint.extend_from_slice(&appended);
+        let excess = self.fingerprint.len().saturating_sub(FINGERPRINT_LENGTH);
+        self.fingerprint.drain(..excess);
+        self.pending.push_str(&String::from_utf8_lossy(&appended));
+
+        let mut batch = TailBatch { restarted, ..TailBatch::default() };
+        let Some(last_newline) = self.pending.rfind('\n') else { return Ok(batch) };
+        let complete: String = self.pending.drain(..=last_newline).collect();
+        for line in complete.lines() {
+            self.lines_read += 1;
+            if let Some(event) = self.parser.parse_line(line).map_err(|e| format!("line {}: {}", self.lines_read, e))? {
+                let (edge, source) = event.into_parts(self.lines_read);
+                batch.edges.push(edge);
+                batch.sources.push(source);
+            }
+        }
+        Ok(batch)
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use crate::graph::Edge;
+use crate::provenance::EdgeSource;
+use crate::reviews::{parse_timestamp, scenario_in_days, SECONDS_PER_DAY};
+use crate::scenario::{Scenario, ScenarioError};
+use crate::types::{NodeId, Time};
+
+// Web of trust: every key signature certifying someone else's key is an edge from the signing key
+// to the signed one, created when the signature was made. Time is measured in days since the Unix
+// epoch and certifications halve every SIGNATURE_HALF_LIFE days.
+pub const SIGNATURE_HALF_LIFE: f64 = 730.0;
+
+// Keys by ID and the certifications between them, at most one per signer and key (the earliest)
+#[derive(Debug, Default)]
+struct WebOfTrust {
+    keys: HashMap<String, NodeId>,
+    names: Vec<Option<String>>,
+    certifications: HashMap<(NodeId, NodeId), (f64, f64, EdgeSource)>, // signer and key to day, weight and source
+}
+
+impl WebOfTrust {
+    fn key(&mut self, id: &str) -> NodeId {
+        let next = NodeId(self.names.len());
+        let node = *self.keys.entry(id.to_uppercase()).or_insert(next);
+        if node == next {
+            self.names.push(None);
+        }
+        node
+    }
+
+    // The first user ID seen for a key names it
+    fn name(&mut self, key: NodeId, name: &str) {
+        let slot = &mut self.names[key.index()];
+        if slot.is_none() && !name.is_empty() {
+            *slot = Some(name.to_string());
+        }
+    }
+
+    // Self-signatures do not count
+    fn certify(&mut self, signer: NodeId, key: NodeId, day: f64, weight: f64, source: EdgeSource) {
+        if signer == key {
+            return;
+        }
+        let certification = self.certifications.entry((signer, key)).or_insert((day, weight, source.clone()));
+        if day < certification.0 {
+            *certification = (day, weight, source);
+        }
+    }
+
+    fn into_scenario(self, name: &str, ids: &HashMap<NodeId, String>) -> Result<Scenario, ScenarioError> {
+        if self.certifications.is_empty() {
+            return Err(ScenarioError { line: 0, message: "no signatures found".to_string() });
+        }
+        let mut certifications: Vec<_> = self.certifications.into_iter().collect();
+        certifications.sort_by(|(a, (a_day, ..)), (b, (b_day, ..))| a_day.total_cmp(b_day).then(a.cmp(b)));
+        let (edges, sources) = certifications
+            .into_iter()
+            .map(|((signer, key), (day, weight, source))| (Edge { source: signer, target: key, time_of_creation: Time(day), weight }, source))
+            .unzip();
+        let names = self.names.into_iter().enumerate().map(|(node, name)| name.or_else(|| ids.get(&NodeId(node)).cloned()).unwrap_or_default()).collect();
+        Ok(scenario_in_days(name, names, edges, sources, SIGNATURE_HALF_LIFE))
+    }
+}
+
+// Key listings as `gpg --list-sigs --with-colons` writes them. Signatures of class 0x10 to 0x13 on
+// a user ID certify the key; a certification the signer revoked (class 0x30) is dropped, and a
+// revoked key leaves the web of trust when it was revoked. Keys are named by their first user ID,
+// signers outside the listing by their key ID.
+pub fn read_gpg_signatures(text: &str, name: &str) -> Result<Scenario, ScenarioError> {
+    let mut web = WebOfTrust::default();
+    let mut ids = HashMap::new();
+    let mut revocations = Vec::new(); // signer and key of revoked certifications
+    let mut leaves = Vec::new();
+    let mut key: Option<NodeId> = None;
+    let mut on_user_id = false;
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: String| ScenarioError { line: i + 1, message };
+        let fields: Vec<&str> = line.split(':').collect();
+        let field = |n: usize| fields.get(n - 1).copied().unwrap_or("");
+        match field(1) {
+            "pub" | "sec" => {
+                let node = web.key(field(5));
+                ids.insert(node, field(5).to_uppercase());
+                key = Some(node);
+                on_user_id = false;
+            }
+            "uid" => {
+                let key = key.ok_or_else(|| error("user ID before any key".to_string()))?;
+                web.name(key, &unescape(field(10)));
+                on_user_id = true;
+            }
+            "sub" | "ssb" => on_user_id = false,
+            "sig" | "rev" => {
+                let Some(key) = key else { return Err(error("signature before any key".to_string())) };
+                let class = field(11).get(..2).unwrap_or("");
+                let seconds = parse_seconds(field(6)).ok_or_else(|| error(format!("invalid signature date: {}", field(6))))?;
+                let signer = web.key(field(5));
+                ids.entry(signer).or_insert_with(|| field(5).to_uppercase());
+                web.name(signer, &unescape(field(10)));
+                match (field(1), class) {
+                    ("sig", "10" | "11" | "12" | "13") if on_user_id => {
+                        let source = EdgeSource { line: i + 1, event_id: None, metadata: vec![("level".to_string(), certification_level(class).to_string())] };
+                        web.certify(signer, key, seconds / SECONDS_PER_DAY, 1.0, source);
+                    }
+                    ("rev", "30") if on_user_id => revocations.push((signer, key)),
+                    ("rev", "20") if signer == key => leaves.push((key, seconds / SECONDS_PER_DAY)),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    for revocation in revocations {
+        web.certifications.remove(&revocation);
+    }
+    let mut scenario = web.into_scenario(name, &ids)?;
+    for (key, day) in leaves {
+        scenario.node_info[key.index()].leaves = Some(Time(day));
+    }
+    Ok(scenario)
+}
+
+// Signatures as CSV rows signer,signee,time[,weight] with an optional header line; signers and
+// signees are key IDs, fingerprints or names, times are seconds since the Unix epoch or ISO 8601
+// UTC dates, and weights default to 1
+pub fn read_signature_csv(text: &str, name: &str) -> Result<Scenario, ScenarioError> {
+    let mut web = WebOfTrust::default();
+    let mut ids = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: String| ScenarioError { line: i + 1, message };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [signer, signee, time, rest @ ..] = fields.as_slice() else {
+            return Err(error("expected signer,signee,time[,weight]".to_string()));
+        };
+        let Some(seconds) = parse_seconds(time) else {
+            if i == 0 {
+                continue; // header
+            }
+            return Err(error(format!("invalid signature time: {}", time)));
+        };
+        let weight = match rest.first() {
+            Some(weight) => weight.parse::<f64>().ok().filter(|w| w.is_finite() && *w > 0.0).ok_or_else(|| error(format!("invalid weight: {}", weight)))?,
+            None => 1.0,
+        };
+        let (signer_node, signee_node) = (web.key(signer), web.key(signee));
+        ids.entry(signer_node).or_insert_with(|| signer.to_string());
+        ids.entry(signee_node).or_insert_with(|| signee.to_string());
+        web.certify(signer_node, signee_node, seconds / SECONDS_PER_DAY, weight, EdgeSource { line: i + 1, event_id: None, metadata: Vec::new() });
+    }
+    web.into_scenario(name, &ids)
+}
+
+// Seconds since the Unix epoch, or an ISO 8601 UTC date or time
+fn parse_seconds(text: &str) -> Option<f64> {
+    if let Ok(seconds) = text.parse::<f64>() {
+        return Some(seconds).filter(|s| s.is_finite());
+    }
+    if text.contains('T') { parse_timestamp(text) } else { parse_timestamp(&format!("{}T00:00:00", text)) }
+}
+
+fn certification_level(class: &str) -> &'static str {
+    match class {
+        "11" => "persona",
+        "12" => "casual",
+        "13" => "positive",
+        _ => "generic",
+    }
+}
+
+// gpg escapes colons and other special characters in user IDs as \xHH
+fn unescape(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let hex = tail.strip_prefix(b"x").and_then(|hex| hex.get(..2)).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match hex {
+            Some(escaped) if byte == b'\\' => {
+                bytes.push(escaped);
+                rest = &tail[3..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
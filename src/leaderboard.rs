@@ -0,0 +1,72 @@
+use std::io::{self, Write};
+
+use crate::graph::Edge;
+use crate::history::RankHistory;
+use crate::types::{FrameIdx, NodeId};
+
+// Ranks closer than this are tied. Ranks that are equal in theory often differ in the last bits,
+// depending on the order the iteration added them up in, which would order them arbitrarily.
+pub const TIE_TOLERANCE: f64 = 1e-12;
+
+// How nodes with tied ranks are ordered in leaderboards and top-K lists. Whatever the rule,
+// nodes it cannot tell apart go by node id, so the order is the same on every run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    #[default]
+    NodeId,       // lower id first
+    InDegree,     // more weighted incoming edges in the frame first
+    PreviousRank, // higher rank in the previous frame first; the first frame goes by id
+}
+
+impl TieBreak {
+    pub fn parse(text: &str) -> Result<TieBreak, String> {
+        match text {
+            "id" => Ok(TieBreak::NodeId),
+            "in-degree" => Ok(TieBreak::InDegree),
+            "previous-rank" => Ok(TieBreak::PreviousRank),
+            _ => Err(format!("unknown tie-break: {} (expected id, in-degree or previous-rank)", text)),
+        }
+    }
+
+    // Key of every node that decides between tied ranks, higher first; empty for node id order.
+    // `weights` are the frame's edge weights and `previous` the ranks of the frame before.
+    pub fn keys(&self, num_of_nodes: usize, edges: &[Edge], weights: &[f64], previous: Option<&[f64]>) -> Vec<f64> {
+        match self {
+            TieBreak::NodeId => Vec::new(),
+            TieBreak::InDegree => {
+                let mut in_degrees = vec![0.0; num_of_nodes];
+                for (edge, _) in edges.iter().zip(weights).filter(|&(_, &w)| w > 0.0) {
+                    in_degrees[edge.target.index()] += 1.0;
+                }
+                in_degrees
+            }
+            TieBreak::PreviousRank => previous.map(<[f64]>::to_vec).unwrap_or_default(),
+        }
+    }
+}
+
+// Nodes by rank, highest first. Runs of ranks within TIE_TOLERANCE of the first one of the run are
+// tied and ordered by `keys` (higher first), then by node id.
+pub fn rank_order(ranks: &[f64], keys: &[f64]) -> Vec<NodeId> {
+    let mut order: Vec<usize> = (0..ranks.len()).collect();
+    order.sort_by(|&a, &b| ranks[b].total_cmp(&ranks[a]));
+    let key = |node: usize| keys.get(node).copied().unwrap_or(0.0);
+    let mut start = 0;
+    while start < order.len() {
+        let end = start + order[start..].iter().take_while(|&&node| ranks[order[start]] - ranks[node] <= TIE_TOLERANCE).count();
+        order[start..end].sort_by(|&a, &b| key(b).total_cmp(&key(a)).then(a.cmp(&b)));
+        start = end;
+    }
+    order.into_iter().map(NodeId).collect()
+}
+
+// Every frame's nodes in rank order, one row per position from 1, with the frame's order given
+pub fn write_leaderboard_csv(out: &mut dyn Write, history: &RankHistory, orders: &[Vec<NodeId>], names: &[String]) -> io::Result<()> {
+    writeln!(out, "frame,time,position,node,label,rank")?;
+    for (frame, ((time, ranks), order)) in history.times().iter().zip(history.frames()).zip(orders).enumerate() {
+        for (position, node) in order.iter().enumerate() {
+            writeln!(out, "{},{},{},{},{},{:.6}", FrameIdx(frame).number(), time, position + 1, node.index(), names[node.index()], ranks[node.index()])?;
+        }
+    }
+    Ok(())
+}
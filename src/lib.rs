@@ -0,0 +1,104 @@
+pub mod types;
+pub mod graph;
+pub mod decay;
+pub mod window;
+pub mod temporal;
+pub mod timeline;
+pub mod rank;
+pub mod precision;
+pub mod montecarlo;
+pub mod ranker;
+pub mod pairwise;
+pub mod trace;
+pub mod baselines;
+pub mod propagation;
+pub mod capacity;
+pub mod clipping;
+pub mod bipartite;
+pub mod temporalrank;
+pub mod compare;
+pub mod sensitivity;
+pub mod bootstrap;
+pub mod sweep;
+pub mod dense;
+pub mod dot;
+pub mod export;
+pub mod tikz;
+pub mod naming;
+pub mod graphviz;
+pub mod style;
+pub mod nodes;
+pub mod provenance;
+pub mod random;
+pub mod registry;
+pub mod scenario;
+pub mod builder;
+pub mod repl;
+pub mod script;
+pub mod simulation;
+pub mod assertions;
+pub mod audit;
+pub mod spectral;
+pub mod bench;
+pub mod hashing;
+pub mod history;
+pub mod rundiff;
+pub mod leaderboard;
+pub mod stabilization;
+pub mod anomalies;
+pub mod collusion;
+pub mod trustingness;
+pub mod scaling;
+pub mod tiers;
+pub mod influence;
+pub mod seeds;
+pub mod simrank;
+pub mod communities;
+pub mod coarsen;
+pub mod clusters;
+pub mod focus;
+pub mod rollup;
+pub mod metrics;
+pub mod stats;
+pub mod graphstats;
+pub mod explain;
+pub mod flux;
+pub mod diff;
+pub mod topk;
+pub mod changes;
+pub mod sink;
+pub mod chart;
+pub mod checkpoint;
+pub mod json;
+pub mod run;
+pub mod batch;
+pub mod profile;
+pub mod html;
+pub mod input;
+pub mod edgefile;
+pub mod xml;
+pub mod graphml;
+pub mod gexf;
+pub mod reviews;
+pub mod keyring;
+pub mod cypher;
+pub mod transition;
+pub mod server;
+pub mod monitoring;
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "sqlite")]
+pub mod store;
+#[cfg(feature = "nats")]
+pub mod nats;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(any(feature = "s3", feature = "gcs"))]
+pub mod bucket;
+#[cfg(feature = "parquet")]
+pub mod parquet;
@@ -1,177 +1,3405 @@
+use std::env;
 use std::fs::{self, File};
-use std::io::Write;
-use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+use std::io::{self, BufRead, Read, Write};
+use std::net::TcpListener;
+use std::process;
+use std::thread;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use log::{debug, info, warn, Level, LevelFilter, Log, Metadata, Record};
+
+use trust_flow::bench::{bench_suite, write_bench_table, BENCH_SIZES};
+use trust_flow::chart::{top_nodes, write_rank_chart};
+use trust_flow::checkpoint::{run_fingerprint, write_checkpoint, Checkpoint, CheckpointWriter};
+use trust_flow::compare::{kendall_tau, spearman};
+use trust_flow::decay::{decay_constant_for_half_life, half_life};
+use trust_flow::transition::{write_teleport_csv, write_teleport_mtx, write_transition_csv, write_transition_mtx, TransitionMatrix};
+use trust_flow::cypher::write_cypher;
+use trust_flow::dense::DenseMatrix;
+use trust_flow::audit::{write_audit_csv, FrameAudit, DEFAULT_AUDIT_TOLERANCE};
+use trust_flow::spectral::{write_spectral_csv, SpectralEstimate, DEFAULT_DIAGNOSTICS_TOLERANCE};
+use trust_flow::changes::{FrameChanges, DEFAULT_FADED_WEIGHT};
+use trust_flow::diff::{write_diff_line, write_diff_stream, RankDiffer};
+use trust_flow::topk::{TopKTracker, DEFAULT_TOP_K_TOLERANCE};
+use trust_flow::dot::{write_dot, write_dot_group_shares, write_dot_small_multiples, FrameRender};
+use trust_flow::export::{ExportedRun, ExporterRegistry};
+use trust_flow::stats::{alarms_at, concentration_alarms, ConcentrationAlarm, FrameStats, RaisedAlarm};
+use trust_flow::graphstats::GraphStats;
+use trust_flow::tikz::TikzExporter;
+use trust_flow::graphviz::{GraphvizJob, GraphvizPool};
+use trust_flow::hashing::run_hash;
+use trust_flow::gexf::write_gexf;
+use trust_flow::graphml::write_graphml;
+use trust_flow::html::write_html_viewer;
+use trust_flow::edgefile::{convert_events, EdgeFile, OutOfCoreRanker};
+use trust_flow::graph::{event_times, Edge, DEFAULT_DECAY_CONSTANT};
+use trust_flow::explain::FrameExplainer;
+use trust_flow::flux::{flux_history, write_flux_csv};
+use trust_flow::influence::{expert_influence, write_influence_csv};
+use trust_flow::input::{parse_edge_events, EventFormat, EventParser, EventTail, WeightTransform};
+use trust_flow::json::Json;
+use trust_flow::repl::{run_repl, Session};
+use trust_flow::registry::{AlgorithmRegistry, COHITS, PAGERANK_VARIANT, TEMPORAL_PAGERANK};
+use trust_flow::naming::FrameNames;
+#[cfg(feature = "nats")]
+use trust_flow::nats::{NatsConfig, NatsConnection};
+#[cfg(feature = "grpc")]
+use trust_flow::grpc::{self, TrustFlowService};
+use trust_flow::run::{continue_scenario_parallel, run_scenario, DampingSchedule, DynamicAuthority, InitialRanks, RunOutput, WarmStart, DEFAULT_AUTHORITY_SMOOTHING};
+use trust_flow::scenario::Scenario;
+use trust_flow::seeds::{rank_expert_groups, write_group_ranks_csv};
+use trust_flow::sweep::{run_sweep, write_sweep_csv, write_sweep_heatmap, SweepGrid, SweepMetric};
+use trust_flow::sensitivity::{seed_sensitivity, write_sensitivity_csv, Perturbation};
+use trust_flow::bootstrap::{bootstrap_run, write_bootstrap_json, BootstrapOptions, Resampling};
+use trust_flow::server::Server;
+#[cfg(feature = "parquet")]
+use trust_flow::parquet::{write_edge_weight_timeline_parquet, write_rank_timeline_parquet};
+use trust_flow::monitoring::{serve_metrics, ServiceMetrics};
+#[cfg(feature = "sqlite")]
+use trust_flow::store::{write_stored_ranks_csv, RankQuery, RunStore};
+use trust_flow::history::RankHistory;
+use trust_flow::profile::{write_profile_csv, FrameProfile, Phase, PHASES};
+use trust_flow::reviews::read_git_repository;
+use trust_flow::rundiff::{diff_histories, largest_movers, write_diff_report, write_rank_correlation_csv, write_rank_deltas_csv, DEFAULT_DIFF_REPORT_NODES};
+use trust_flow::leaderboard::{rank_order, write_leaderboard_csv, TieBreak};
+use trust_flow::scaling::ScoreScaling;
+use trust_flow::style::{ColorScale, EdgeSampling, RenderStyle, DEFAULT_THUMBNAIL_SIZE};
+use trust_flow::focus::{FocusView, DEFAULT_FOCUS_HOPS};
+use trust_flow::tiers::{write_tiers, Tier, TierThresholds};
+use trust_flow::simulation::{simulate, tournament, SimulationConfig};
+use trust_flow::simrank::{write_similarity_csv, SimRank, Similarity};
+use trust_flow::temporal::{earliest_arrival, latest_departure};
+use trust_flow::metrics::{centrality, write_metrics_csv, Centrality};
+use trust_flow::bipartite::{write_bipartite_csv, CoHits};
+use trust_flow::temporalrank::TemporalPageRank;
+use trust_flow::clusters::{community_clusters, ClusterBy};
+use trust_flow::rollup::{group_edges, group_layout, group_trust, write_group_trust_csv, Rollup};
+use trust_flow::coarsen::{coarsen, Coarsening};
+use trust_flow::communities::{community_borders, label_propagation, write_communities_csv, CommunityTracker};
+use trust_flow::sink::{manifest_files, FileSystemSink, ManifestSink, Sink, MANIFEST_FILE};
+#[cfg(any(feature = "s3", feature = "gcs"))]
+use trust_flow::bucket::ObjectStoreSink;
+use trust_flow::nodes::{read_node_table, NodeRow};
+use trust_flow::provenance::{edge_tooltips, write_edge_drill_down};
+use trust_flow::random::DEFAULT_SEED;
+use trust_flow::pairwise::{trust_between, PairwiseTrust};
+use trust_flow::ranker::{LocalScoring, Personalization, Ranker, DEFAULT_LOCAL_WALKS};
+use trust_flow::precision::Precision;
+use trust_flow::rank::{Backend, OutflowNormalization, PageRankVariant, RankConfig, RankIteration, RankStep, RankingAlgorithm, Solver};
+use trust_flow::collusion::{find_rings, write_rings_jsonl, CollusionRing, RingCriteria};
+use trust_flow::anomalies::{write_anomalies_json, AnomalyDetector};
+use trust_flow::trustingness::{trustingness, write_trustingness_csv};
+use trust_flow::stabilization::{consecutive_taus, stabilization_frame, write_stabilization_csv, StabilizationCriterion};
+use trust_flow::trace::{write_iterations_csv, write_step_table};
+use trust_flow::types::{EdgeId, FrameIdx, NodeId, Time};
 
 const OUTPUT_FOLDER: &str = "output";
-const DECAY_CONSTANT: f64 = 0.1;
-const EXPERT_TELEPORT_FRACTION: f64 = 0.8; // fraction of teleported rank (mass) directed to experts
-                    
-#[derive(Debug)]
-struct Edge {
-    source: usize,
-    target: usize,
-    time_of_creation: usize, // Discrete time
-}
-
-fn exponential_decay(t1:usize, t0:usize, weight_at_t0:f64, decay_constant:f64) -> f64 {
-    return weight_at_t0 * (- ((t1-t0) as f64) * decay_constant).exp();
-} 
-
-fn pagerank_variant(
-    edges: &[Edge],
-    weights: &[f64],
-    num_of_nodes: usize,
-    num_of_iterations: usize,
-    damping_factor: f64,
-    teleportation_targets: &[f64],
-) -> Vec<f64> {
-    // Rank flow is analogous to mass flow. 
-    // Total rank (mass) is conserved.
-    // Expert nodes have higher intrinsic rank (mass).
-
-    // Initial uniform rank (mass) distribution over nodes
-    let mut rank_values = vec![1.0 / num_of_nodes as f64; num_of_nodes];
-
-    let mut initial_outflow_values = vec![0.0; num_of_nodes];
-    for edge in edges {
-        initial_outflow_values[edge.source] += 1.0;
-    }
-
-    for _ in 0..num_of_iterations {
-        // New rank (mass) values are calculated starting with teleportation inflow contribution
-        let mut new_rank_values = teleportation_targets
-            .iter()
-            .map(|&t| (1.0 - damping_factor) * t)
-            .collect::<Vec<f64>>();
-
-        // Rank (mass) outflows along edges with speed propotional to edge weights
-        let mut outflow_values = vec![0.0; num_of_nodes];
-        for (edge, &w) in edges.iter().zip(weights.iter()) {
-            outflow_values[edge.source] += w;
-            new_rank_values[edge.target] +=
-                damping_factor *
-                rank_values[edge.source] *
-                (w / initial_outflow_values[edge.source]);
-        }
-
-        // We redistribute dangling rank (mass) uniformly
-        let mut dangling_rank = 0.0;
-        for i in 0..num_of_nodes {
-            let rank = rank_values[i];
-            let initial_outflow = initial_outflow_values[i];
-            if initial_outflow > 0.0 {
-                let outflow = outflow_values[i];
-                let allocated = damping_factor * rank * (outflow / initial_outflow);
-                dangling_rank += damping_factor * rank - allocated;
-            } else {
-                dangling_rank += damping_factor * rank;
-            }
-        }
-        let dangling_share = dangling_rank / num_of_nodes as f64;
-        for new_rank in new_rank_values.iter_mut() {
-            *new_rank += dangling_share;
-        }
-
-        rank_values = new_rank_values;
-    }
-
-    // let total_mass: f64 = rank_values.iter().sum();
-    // println!("Total rank mass after {} iterations: {}", num_of_iterations, total_mass);
-    rank_values
-}
-
-fn write_dot(pathname: &str, node_ranks: &[f64], edges: &[Edge], weights: &[f64], experts: &[usize], positions: &[(f64, f64)], current_frame: usize, total_frames: usize, algorithm: &str, decay_desc: &str) {
-    let mut file = File::create(pathname).unwrap();
-    writeln!(file, "digraph G {{").unwrap();
-    writeln!(file, "  nodesep=0.8;").unwrap();
-    writeln!(file, "  graph [layout=neato, overlap=false, splines=true, pad=\"1.0,1.0\", fontsize=20];").unwrap();
-    writeln!(file, "  labelloc=\"t\";").unwrap();
-    writeln!(file, "  labeljust=\"l\";").unwrap();
-    writeln!(file, "  labelfontsize=26;").unwrap();
-    writeln!(file, "  label=\"Trust flow over time\nAlgorithm: {}\nEdge decay: {}\nFrame: {}/{}\";", algorithm, decay_desc, current_frame, total_frames).unwrap();
-    for i in 0..node_ranks.len() {
-        let r = node_ranks[i].clamp(0.0, 1.0);
-        let level = ((1.0 - r) * 255.0) as u8;
-        let fill_color = format!("#{:02X}{:02X}{:02X}", level, level, 255u8);
-        let label_text = format!("{} ({:.2})", i, node_ranks[i]);
-        let (x, y) = positions[i];
-        if experts.contains(&i) {
-            writeln!(file,
-                "  {} [label=\"{}\", shape=circle, style=filled, fillcolor=\"{}\", color=\"darkgreen\", penwidth=8, fontsize=20, pos=\"{:.2},{:.2}!\", pin=true];",
-                i, label_text, fill_color, x, y
-            ).unwrap();
-        } else {
-            writeln!(file,
-                "  {} [label=\"{}\", shape=circle, style=filled, fillcolor=\"{}\", fontsize=20, pos=\"{:.2},{:.2}!\", pin=true];",
-                i, label_text, fill_color, x, y
-            ).unwrap();
+const CHECKPOINT_FOLDER: &str = "checkpoints";
+const CACHE_FOLDER: &str = "cache";
+// Ranks stored by `run` in each scenario's folder
+const RESULTS_FILE: &str = "results.jsonl";
+const DEFAULT_LISTEN_ADDRESS: &str = "127.0.0.1:8080";
+// Longest paths listed by --explain-paths
+const EXPLAIN_MAX_HOPS: usize = 3;
+// Similar nodes per node printed by --similar and exported by --export simrank
+const DEFAULT_SIMILAR_TOP: usize = 5;
+// Residual per node that the reverse push of --score-local leaves
+const SCORE_TOLERANCE: f64 = 1e-6;
+// Nodes whose ranks the sweep subcommand reports unless --sweep-nodes says otherwise
+const DEFAULT_SWEEP_NODES: usize = 3;
+// Random expert substitutions of the sensitivity subcommand, besides leaving out every expert
+const DEFAULT_SUBSTITUTIONS: usize = 10;
+// Share of the bootstrap samples inside the rank intervals unless --confidence says otherwise
+const DEFAULT_CONFIDENCE: f64 = 0.95;
+// Seconds a Graphviz process may run per attempt, and the attempts after a failed one, unless
+// --graphviz-timeout and --graphviz-retries say otherwise
+const DEFAULT_GRAPHVIZ_TIMEOUT: f64 = 60.0;
+const DEFAULT_GRAPHVIZ_RETRIES: usize = 1;
+// Consecutive frames that must keep the rank ordering unless --stable-frames says otherwise
+const DEFAULT_STABLE_FRAMES: usize = 3;
+// Earlier rank changes of a node before its changes are checked for anomalies, unless --anomaly-history says otherwise
+const DEFAULT_ANOMALY_HISTORY: usize = 3;
+
+// Printed with argument errors, and with OPTIONS_HELP for --help
+const USAGE: &str = "\
+Usage: trust-flow [COMMAND] [OPTIONS]
+
+Ranks the scenarios in scenarios/ (or given with --scenario) and writes their frames to output/.
+
+Commands:
+  run | render | export | analyze   rank and store results, or work from the stored results
+  serve                             HTTP API (--listen, default 127.0.0.1:8080)
+  query                             stored ranks of a node (--node, --db) or trust between two (--from, --to)
+  repl                              build a scenario interactively
+  sweep | sensitivity               ranks under a grid of parameters, or under substituted experts
+  convert-edges IN OUT              convert edge events between JSONL, CSV and the binary edge file
+  diff BEFORE AFTER                 compare two rank timelines (ranks.csv)
+  stats                             statistics of the edge events of --input
+  algorithms list                   available algorithms and their parameters
+";
+
+const OPTIONS_HELP: &str = "\
+Input:
+  --scenario FILE            scenario file; repeat for several
+  --git-repo PATH            review history of a git repository as a scenario
+  --input FILE               edge events (- for stdin); --input-format jsonl|csv
+  --pipe                     stream events from stdin; --every-events N or --every-seconds S for snapshots
+  --nats HOST:PORT           consume events from NATS: --nats-subject, --nats-publish, --nats-creds FILE,
+                             --nats-tls, --nats-ca FILE
+  --grpc HOST:PORT           gRPC scoring service
+  --watch FILE               re-run when the file changes; --watch-interval SECONDS
+  --edge-file FILE           rank a binary edge file
+  --nodes FILE               node table with names, organizations and categories
+  --expert NODE              expert node; repeat for several
+  --weight-transform KIND    linear, log or sqrt of event counts
+  --reverse-edges            edges point from endorsee to endorser
+  --symmetrize               rank every edge in both directions
+  --since TIME, --until TIME limit the events read
+
+Ranking:
+  --algorithm ID             algorithm of the frames (see algorithms list)
+  --damping SCHEDULE         damping factor, a range such as 0.5..0.9 or per-frame values
+  --decay K, --half-life T   decay constant of edge weights
+  --frame-times LIST         comma-separated frame times
+  --backend edge-list|dense  --solver jacobi|gauss-seidel|monte-carlo|exact  --precision f32|f64
+  --outflow-normalization lifetime|current|none
+  --relaxation W  --walks N  --seed N  --max-hops N  --node-capacity X  --max-source-share X
+  --rank-floor X  --prune-below W  --fast-exp  --decay-threads N  --jobs N, -j N
+  --warm-start STEPS  --node-decay K  --initial-ranks SPEC  --authority N  --authority-smoothing X
+  --time-respecting  --tie-break id|in-degree|previous-rank  --scale SCALING
+
+Output:
+  --output FOLDER|URL        output folder, or an s3:// or gs:// bucket; --parallel-uploads N
+  --run-id ID|auto           write into a subfolder of the output; --clean removes the previous run
+  --export FORMAT            html, json, csv, graphml, gexf, provenance, cypher, simrank, metrics,
+                             trustingness, leaderboard, flux, parquet, transition, mtx or tikz
+  --tikz-frames LIST         frame numbers of --export tikz
+  --graphviz FORMAT          render frames, e.g. png or svg: --graphviz-jobs, --graphviz-timeout,
+                             --graphviz-retries
+  --thumbnails, --thumbnail-size INCHES
+  --focus NODE, --hops N     draw the neighborhood of one node
+  --frame-names TEMPLATE  --render-diff X  --faded-weight W  --ghost-edges  --auto-layout
+  --rank-deltas  --frame-stats  --edge-flux  --edge-top N  --edge-min-weight W
+  --elide-below X  --elide-mode skip|duplicate  --smooth X  --chart-nodes LIST  --chart-top N
+  --cluster community|category|org  --rollup org|category  --rollup-graph  --preview SPEC
+  --animate-iterations TIME  --dump-iterations TIME  --trace TIME
+  --cache, --cache-dir DIR   reuse the results of unchanged runs
+  --checkpoint, --checkpoint-dir DIR, --resume
+  --results FILE  --summary FILE  --db FILE  --hash  --metrics-address HOST:PORT
+
+Analysis:
+  --diff-threshold X  --top-k N  --top-k-tolerance X  --compare LIST  --compare-format dot|csv
+  --tournament LIST  --expert-groups panels|pie  --tiers LOWER,UPPER  --tier-quantiles LOWER,UPPER
+  --communities  --influence  --collusion  --ring-size N  --ring-density X  --ring-external X
+  --anomalies Z  --anomaly-history N  --stabilization TAU  --stable-frames N  --truncate-stable
+  --alarm RULE  --alarm-banner  --bootstrap N  --bootstrap-noise X  --confidence X
+  --explain NODE  --explain-frame N  --explain-paths N  --similar NODE  --similar-top N
+  --reach NODE  --reach-time TIME  --score NODE  --score-time TIME  --score-local N
+  --trust-between A,B  --local-trust NODE  --trust-method LIST  --sweep-damping LIST
+  --sweep-decay LIST  --sweep-nodes LIST  --substitutions N  --audit  --audit-tolerance X
+  --diagnostics  --diagnostics-tolerance X  --spectral-radius  --profile  --bench
+  --listen HOST:PORT  --node NODE  --scenario-name NAME  --from NODE  --to NODE  --at TIME
+
+  --quiet, -q  --verbose, -v, -vv  --help, -h
+";
+#[cfg(feature = "sqlite")]
+const DEFAULT_DATABASE: &str = "runs.sqlite";
+#[cfg(feature = "nats")]
+const DEFAULT_NATS_SUBJECT: &str = "trust.events";
+
+#[derive(PartialEq, Default)]
+enum CompareFormat {
+    #[default]
+    Dot, // small multiples, one panel per algorithm
+    Csv, // pairwise rank correlations per frame
+}
+
+// How --expert-groups draws the expert groups' personalizations
+#[derive(PartialEq, Clone, Copy)]
+enum GroupView {
+    Panels, // small multiples, one panel per group
+    Pie,    // one graph, every node a pie of the groups' shares of its rank
+}
+
+// Stage of a scenario run. `run` stores the ranks of every frame; `render`, `export` and `analyze`
+// work on the stored results instead of ranking again.
+#[derive(PartialEq, Clone, Copy, Default)]
+enum Command {
+    #[default]
+    All, // rank, render, export and analyze in one go, storing nothing
+    Run,
+    Render,
+    Export,
+    Analyze,
+}
+
+impl Command {
+    fn reads_results(self) -> bool {
+        matches!(self, Command::Render | Command::Export | Command::Analyze)
+    }
+
+    fn renders(self) -> bool {
+        matches!(self, Command::All | Command::Render)
+    }
+
+    fn exports(self) -> bool {
+        matches!(self, Command::All | Command::Export)
+    }
+
+    fn analyzes(self) -> bool {
+        matches!(self, Command::All | Command::Analyze)
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    help: bool, // prints USAGE and OPTIONS_HELP instead of running
+    animate_iterations_at: Option<Time>, // also render the power iteration itself at this time step
+    clean: bool, // removes the files of the previous run and the folders of the scenarios before writing
+    run_id: Option<String>, // writes into a subfolder of the output named after the run
+    audit: bool, // checks rank (mass) conservation in every frame and writes an audit report
+    audit_tolerance: Option<f64>, // deviation the audit accepts as rounding
+    render_diff: Option<f64>, // frames show what changed since the previous one, nodes whose rank moved more than this outlined
+    faded_weight: f64, // edges decaying below this weight count as faded in frames that show changes
+    dump_iterations_at: Option<Time>, // write the rank vector after every iteration at this time step
+    trace_at: Option<Time>, // print step-by-step numeric tables of the power iteration at this time step
+    algorithm: Option<String>, // ranks the frames with this algorithm instead of the custom PageRank variant
+    compare: Vec<String>, // algorithms to run side by side
+    compare_format: CompareFormat,
+    expert_groups: Option<GroupView>, // rank the scenarios' expert groups side by side
+    frame_times: Option<Vec<Time>>, // points in time at which frames are sampled
+    scenario_files: Vec<String>, // the built-in example runs when empty
+    git_repositories: Vec<String>, // repositories whose review trailers become scenarios, next to the scenario files
+    print_hash: bool, // print a result hash that does not depend on node numbering
+    chart_nodes: Vec<NodeId>, // nodes plotted in the rank-over-time chart; top ranked nodes when empty
+    chart_top: Option<usize>,
+    profile: bool, // writes the wall time of every frame by phase and the peak memory to profile.csv
+    tie_break: TieBreak, // orders nodes with tied ranks in the rank chart and the leaderboard export
+    exports: Vec<String>, // additional export formats
+    tikz_frames: Vec<FrameIdx>, // frames of the TikZ export; all when empty
+    output: Option<String>, // output folder, or s3:// / gs:// bucket URL
+    max_parallel_uploads: Option<usize>,
+    pipe: bool, // read edge events from stdin, write rank snapshots to stdout
+    every_events: Option<usize>, // in pipe mode, stream a snapshot after this many new events
+    every_seconds: Option<f64>,  // in pipe mode, stream a snapshot after this much wall time
+    nats: Option<String>, // NATS server (host:port) to consume edge events from
+    nats_subject: Option<String>, // subject of the edge events
+    nats_publish: Option<String>, // subject rank snapshots are published on, stdout when not given
+    nats_credentials: Option<String>, // .creds file for NATS; user and password or a token come from the environment
+    nats_tls: bool,
+    nats_ca: Option<String>, // PEM file of further certificate authorities for --nats-tls
+    grpc: Option<String>, // address the gRPC service listens on
+    metrics_address: Option<String>, // in streaming modes, serve Prometheus metrics on this address
+    input_format: EventFormat,
+    weight_transform: Option<WeightTransform>, // initial edge weights from the count (or amount) of edge events
+    experts: Vec<NodeId>, // expert nodes in pipeline mode
+    diff_threshold: Option<f64>, // emit only rank changes larger than this instead of full rank vectors
+    top_k: Option<usize>, // in streaming modes, emit the approximate top K of an incremental tracker instead of full rank vectors
+    top_k_tolerance: Option<f64>, // error bound of the top-K tracker's ranks
+    watch: Option<String>, // edge event file to follow
+    checkpoint_folder: Option<String>, // save run state after every frame into this folder
+    resume: bool, // continue runs from their checkpoints
+    summary: Option<String>, // file the machine-readable summary of the run is written to
+    cache_folder: Option<String>, // ranks of complete runs are kept in this folder and reused by runs with the same fingerprint
+    list_algorithms: bool, // `algorithms list` subcommand
+    serve: Option<String>, // `serve` subcommand: address the HTTP API listens on
+    database: Option<String>, // SQLite file that runs are recorded in, and that `query` reads
+    query: bool, // `query` subcommand: print stored ranks instead of running scenarios
+    repl: bool, // `repl` subcommand: build a scenario interactively
+    query_node: Option<String>,
+    query_scenario: Option<String>,
+    query_pair: (Option<String>, Option<String>), // truster and trustee: `query` prints their pairwise trust instead
+    query_time: Option<Time>, // time of the pairwise trust, the last frame by default
+    trust_methods: Vec<PairwiseTrust>, // every method by default
+    since: Option<Time>,
+    until: Option<Time>,
+    bench: bool, // print throughput numbers of the benchmark suite instead of running scenarios
+    watch_interval: Option<f64>, // seconds between polls of the watched file
+    node_table: Option<String>, // CSV file with node names, categories, positions and expert flags
+    influence: bool, // report how much of every node's rank originates from expert teleportation
+    backend: Backend,
+    outflow_normalization: OutflowNormalization,
+    solver: Solver,
+    relaxation: Option<f64>, // over-relaxation factor of the Gauss-Seidel solver
+    walks_per_node: Option<usize>, // random walks per node of the Monte Carlo solver
+    max_hops: Option<usize>, // hop limit of bounded propagation
+    node_capacity: Option<f64>, // per-node flow limit of the capacity-limited variant
+    max_source_share: Option<f64>, // per-source inflow share limit of the source-clipped variant
+    rank_floor: Option<f64>,    // rank every active node is guaranteed by the custom variant
+    precision: Precision,
+    spectral_radius: bool, // print the convergence factor of the power iteration in the last frame
+    diagnostics: bool, // estimate every frame's second eigenvalue and the iterations the power iteration needs
+    diagnostics_tolerance: Option<f64>, // target L1 error of those iteration counts
+    tournament: Vec<String>, // algorithms to pit against the agents of simulated scenarios
+    thumbnails: Option<f64>, // also renders every frame as a thumbnail of at most this many inches
+    focus: Option<String>, // frames only draw the neighborhood of this node
+    focus_hops: Option<usize>, // size of that neighborhood in hops
+    seed: Option<u64>, // overrides the seed of every scenario
+    verbosity: i32, // -1 with --quiet, raised by every --verbose
+    convert_edges: Option<(String, String)>, // `convert-edges` subcommand: edge events to a binary edge file
+    diff_runs: Option<(String, String)>, // `diff` subcommand: two exported rank timelines, before and after
+    stats: bool, // `stats` subcommand: statistics and anomalies of the --input edge events, without ranking
+    input: Option<String>, // edge event file of the `stats` subcommand, - for stdin
+    edge_file: Option<String>, // binary edge file to rank out of core
+    decay_constant: Option<f64>, // decay of edge file weights, also given as a half-life
+    jobs: usize, // frames ranked and rendered at the same time
+    fast_exp: bool, // edge weights of every scenario decay with the approximate exponential
+    decay_threads: Option<usize>, // threads that decay the edge weights of a frame, one per core for large graphs when not set
+    graphviz: Option<GraphvizPool>, // renders the written DOT files to images after the run
+    elision: Option<FrameElision>, // frames barely different from the last rendered one are not rendered
+    scaling: ScoreScaling, // scores shown in frames and exports
+    smoothing: Option<f64>, // weight of the newest frame in the moving average of shown and exported ranks
+    bootstrap: Option<BootstrapOptions>, // rank intervals of every frame from resampled edges or perturbed weights
+    tiers: Option<TierThresholds>, // classifies the scores of every frame into trust tiers
+    communities: bool, // detects communities in every frame, drawn as node borders in place of tiers
+    explain: Option<String>, // node (name or index) whose rank is decomposed into its sources
+    explain_frame: Option<usize>, // frame number of the explanation, the last frame by default
+    explain_paths: usize, // top contributing paths to list and highlight in a frame; 0 for none
+    similar: Option<String>, // node whose most similar nodes (SimRank) in the last frame are printed
+    similar_top: Option<usize>, // similar nodes per node printed and exported
+    symmetrize: bool, // treats the edges of every scenario as undirected
+    reverse_edges: bool, // turns the edges of every scenario and input around, for arrows from endorsee to endorser
+    prune_below: Option<f64>, // edge weight below which every scenario's edges are pruned
+    cluster: Option<ClusterBy>, // groups the nodes of every scenario's frames into clusters
+    rollup: Option<Rollup>, // writes the trust of every org or category per frame
+    rollup_graph: bool, // and renders a graph of the groups per frame
+    ghost_edges: bool, // frames of every scenario preview edges created after their time
+    edge_sampling: Option<EdgeSampling>, // frames of every scenario draw only these edges
+    auto_layout: bool, // only nodes with a fixed position are pinned in every scenario's frames
+    rank_deltas: bool, // frames of every scenario mark rank changes since the previous frame
+    frame_stats: bool, // frame labels of every scenario show rank concentration and edge totals
+    alarms: Vec<ConcentrationAlarm>, // added to every scenario's own alarms
+    frame_names: Option<FrameNames>, // overrides every scenario's frame names
+    alarm_banner: bool, // frames of every scenario show the alarms that go off in them
+    edge_flux: bool, // frames of every scenario color and label edges by the rank they carry
+    time_respecting: bool, // restricts rank flow of every scenario to time-respecting paths
+    warm_start: Option<WarmStart>, // ranks the frames of every scenario incrementally
+    initial_ranks: Option<InitialRanks>, // where the frames of every scenario start their iteration
+    authority: Option<DynamicAuthority>, // teleportation of every scenario follows the top nodes of the previous frame
+    damping: Option<DampingSchedule>, // per-frame damping factor of every scenario
+    sweep: Option<SweepGrid>, // `sweep` subcommand: grid of damping factors and decay constants
+    sweep_nodes: Vec<NodeId>, // nodes whose ranks the sweep reports; the top ones by default
+    sensitivity: Option<usize>, // `sensitivity` subcommand: random expert substitutions besides leaving out every expert
+    reach: Option<String>, // node whose time-respecting reachability is printed
+    reach_time: Option<Time>, // time of the reachability analysis, the last frame by default
+    score: Option<String>, // node whose trust score at a single time is printed
+    preview: Option<Coarsening>, // runs a coarsened version of every scenario instead
+    score_time: Option<Time>, // time of the score, the last frame by default
+    trust_between: Option<(String, String)>, // truster and trustee whose local trust at the score time is printed
+    local_trust: Option<String>, // node whose endorsees every scenario teleports to, instead of the experts
+    score_local: Option<usize>, // scenarios with more nodes than this are scored locally instead of ranking the frame
+    stabilization: Option<StabilizationCriterion>, // reports when the rank ordering stops changing
+    truncate_stable: bool, // renders no frames after the rank ordering has stabilized
+    anomalies: Option<AnomalyDetector>, // flags rank changes far outside a node's earlier changes
+    collusion: Option<RingCriteria>, // reports small communities that mostly endorse themselves
+    command: Command,
+    results: Option<String>, // stored results to render, export or analyze; {scenario}/results.jsonl in the output folder by default
+}
+
+// Rendering of frames whose ranks barely differ from those of the last rendered frame
+#[derive(Debug, Clone, Copy)]
+struct FrameElision {
+    threshold: f64, // largest rank change of any node below which a frame is elided
+    duplicate: bool, // write a copy of the last rendered frame instead of nothing, so frame numbering has no gaps
+}
+
+// An argument error unless the condition holds
+fn require(condition: bool, message: &str) -> Result<(), String> {
+    if condition { Ok(()) } else { Err(message.to_string()) }
+}
+
+fn parse_options(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options { jobs: 1, faded_weight: DEFAULT_FADED_WEIGHT, ..Options::default() };
+    let mut args = args.peekable();
+    if args.peek().map(String::as_str) == Some("algorithms") {
+        args.next();
+        match args.next().as_deref() {
+            Some("list") => options.list_algorithms = true,
+            other => return Err(format!("unknown algorithms command: {:?} (expected list)", other)),
+        }
+    }
+    match args.peek().map(String::as_str) {
+        Some("serve") => options.serve = Some(DEFAULT_LISTEN_ADDRESS.to_string()),
+        Some("query") => options.query = true,
+        Some("repl") => {
+            args.next();
+            options.repl = true;
+        }
+        Some("sweep") => {
+            args.next();
+            options.sweep = Some(SweepGrid::default());
+        }
+        Some("sensitivity") => {
+            args.next();
+            options.sensitivity = Some(DEFAULT_SUBSTITUTIONS);
+        }
+        Some("run") | Some("render") | Some("export") | Some("analyze") => {
+            options.command = match args.next().as_deref() {
+                Some("run") => Command::Run,
+                Some("render") => Command::Render,
+                Some("export") => Command::Export,
+                _ => Command::Analyze,
+            };
+        }
+        Some("convert-edges") => {
+            args.next();
+            let input = args.next().ok_or("convert-edges requires an input file (or - for stdin) and an output file")?;
+            let output = args.next().ok_or("convert-edges requires an output file")?;
+            options.convert_edges = Some((input, output));
+        }
+        Some("diff") => {
+            args.next();
+            let before = args.next().ok_or("diff requires two rank timelines (ranks.csv of --export csv), before and after")?;
+            let after = args.next().ok_or("diff requires a second rank timeline to compare with")?;
+            options.diff_runs = Some((before, after));
+        }
+        Some("stats") => {
+            args.next();
+            options.stats = true;
+        }
+        _ => {}
+    }
+    if options.serve.is_some() || options.query {
+        args.next();
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--animate-iterations" => {
+                let time = args.next().ok_or("--animate-iterations requires a time step")?;
+                options.animate_iterations_at = Some(Time(time.parse().map_err(|_| "time step must be a number")?));
+            }
+            "--audit" => options.audit = true,
+            "--audit-tolerance" => {
+                let tolerance = args.next().ok_or("--audit-tolerance requires a tolerance")?;
+                options.audit_tolerance = Some(tolerance.parse().map_err(|_| "tolerance must be a number")?);
+                options.audit = true;
+            }
+            "--render-diff" => {
+                let threshold = args.next().ok_or("--render-diff requires a rank change")?;
+                options.render_diff = Some(threshold.parse().map_err(|_| "rank change must be a number")?);
+            }
+            "--faded-weight" => {
+                let weight = args.next().ok_or("--faded-weight requires an edge weight")?;
+                options.faded_weight = weight.parse().map_err(|_| "edge weight must be a number")?;
+            }
+            "--dump-iterations" => {
+                let time = args.next().ok_or("--dump-iterations requires a time step")?;
+                options.dump_iterations_at = Some(Time(time.parse().map_err(|_| "time step must be a number")?));
+            }
+            "--trace" => {
+                let time = args.next().ok_or("--trace requires a time step")?;
+                options.trace_at = Some(Time(time.parse().map_err(|_| "time step must be a number")?));
+            }
+            "--frame-times" => {
+                let times = args.next().ok_or("--frame-times requires a comma-separated list of time points")?;
+                options.frame_times = Some(times.split(',').map(|t| t.trim().parse().map(Time).map_err(|_| "time points must be numbers")).collect::<Result<_, _>>()?);
+            }
+            "--algorithm" => {
+                let id = args.next().ok_or("--algorithm requires an algorithm such as temporal-pagerank")?;
+                options.algorithm = Some(AlgorithmRegistry::get(&id)?.id.to_string());
+            }
+            "--compare" => {
+                let algorithms = args.next().ok_or("--compare requires a comma-separated list of algorithms")?;
+                options.compare = algorithm_ids(&algorithms);
+            }
+            "--compare-format" => {
+                options.compare_format = match args.next().as_deref() {
+                    Some("dot") => CompareFormat::Dot,
+                    Some("csv") => CompareFormat::Csv,
+                    other => return Err(format!("unknown comparison format: {:?}", other)),
+                };
+            }
+            "--expert-groups" => {
+                let view = args.next().ok_or("--expert-groups requires a view (panels or pie)")?;
+                options.expert_groups = match view.as_str() {
+                    "panels" => Some(GroupView::Panels),
+                    "pie" => Some(GroupView::Pie),
+                    other => return Err(format!("unknown expert group view: {} (expected panels or pie)", other)),
+                };
+            }
+            "--export" => {
+                let format = args.next().ok_or("--export requires a format")?;
+                match format.as_str() {
+                    "html" | "json" | "csv" | "graphml" | "gexf" | "provenance" | "cypher" | "simrank" | "metrics" | "trustingness" | "leaderboard" | "flux" | "parquet" | "transition" | "mtx" | "tikz" => options.exports.push(format),
+                    _ => return Err(format!("unknown export format: {} (expected html, json, csv, graphml, gexf, provenance, cypher, simrank, metrics, trustingness, leaderboard, flux, parquet, transition, mtx or tikz)", format)),
+                }
+            }
+            "--tikz-frames" => {
+                let frames = args.next().ok_or("--tikz-frames requires frame numbers")?;
+                options.tikz_frames = frames
+                    .split(',')
+                    .map(|f| match f.trim().parse::<usize>() {
+                        Ok(number) if number > 0 => Ok(FrameIdx(number - 1)),
+                        _ => Err(format!("frame numbers must be positive integers: {}", f)),
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "--output" => options.output = Some(args.next().ok_or("--output requires a folder or bucket URL")?),
+            "--parallel-uploads" => {
+                let count = args.next().ok_or("--parallel-uploads requires a count")?;
+                options.max_parallel_uploads = Some(count.parse().map_err(|_| "upload count must be a positive integer")?);
+            }
+            "--clean" => options.clean = true,
+            "--run-id" => {
+                let id = args.next().ok_or("--run-id requires an id, or 'auto' for a timestamp")?;
+                require(!id.is_empty() && !id.contains('/') && id != "..", "--run-id must be a plain folder name")?;
+                options.run_id = Some(if id == "auto" { timestamp_run_id() } else { id });
+            }
+            "--pipe" => options.pipe = true,
+            "--nats" => options.nats = Some(args.next().ok_or("--nats requires a server address (host:port)")?),
+            "--nats-subject" => options.nats_subject = Some(args.next().ok_or("--nats-subject requires a subject")?),
+            "--nats-publish" => options.nats_publish = Some(args.next().ok_or("--nats-publish requires a subject")?),
+            "--nats-creds" => options.nats_credentials = Some(args.next().ok_or("--nats-creds requires a .creds file")?),
+            "--nats-tls" => options.nats_tls = true,
+            "--nats-ca" => options.nats_ca = Some(args.next().ok_or("--nats-ca requires a PEM file")?),
+            "--grpc" => options.grpc = Some(args.next().ok_or("--grpc requires an address such as 127.0.0.1:50051")?),
+            "--metrics-address" => options.metrics_address = Some(args.next().ok_or("--metrics-address requires an address (host:port)")?),
+            "--every-events" => {
+                let count: usize = args.next().and_then(|c| c.parse().ok()).ok_or("--every-events requires a positive count")?;
+                require(count > 0, "--every-events requires a positive count")?;
+                options.every_events = Some(count);
+            }
+            "--every-seconds" => {
+                let seconds: f64 = args.next().and_then(|s| s.parse().ok()).ok_or("--every-seconds requires a number of seconds")?;
+                require(seconds > 0.0, "--every-seconds must be positive")?;
+                options.every_seconds = Some(seconds);
+            }
+            "--input" => options.input = Some(args.next().ok_or("--input requires an edge event file (or - for stdin)")?),
+            "--input-format" => {
+                options.input_format = match args.next().as_deref() {
+                    Some("jsonl") => EventFormat::Jsonl,
+                    Some("csv") => EventFormat::Csv,
+                    other => return Err(format!("unknown input format: {:?}", other)),
+                };
+            }
+            "--weight-transform" => options.weight_transform = Some(WeightTransform::parse(&args.next().ok_or("--weight-transform requires linear, log or sqrt")?)?),
+            "--expert" => {
+                let node = args.next().ok_or("--expert requires a node")?;
+                options.experts.push(NodeId(node.parse().map_err(|_| "expert must be a node index")?));
+            }
+            "--diff-threshold" => {
+                let threshold = args.next().ok_or("--diff-threshold requires a rank change")?;
+                options.diff_threshold = Some(threshold.parse().map_err(|_| "rank change must be a number")?);
+            }
+            "--top-k" => {
+                let count: usize = args.next().and_then(|c| c.parse().ok()).ok_or("--top-k requires a positive count")?;
+                require(count > 0, "--top-k requires a positive count")?;
+                options.top_k = Some(count);
+            }
+            "--top-k-tolerance" => {
+                let tolerance: f64 = args.next().and_then(|t| t.parse().ok()).ok_or("--top-k-tolerance requires a rank error bound")?;
+                require(tolerance > 0.0, "--top-k-tolerance must be positive")?;
+                options.top_k_tolerance = Some(tolerance);
+            }
+            "--summary" => options.summary = Some(args.next().ok_or("--summary requires a file path")?),
+            "--cache" => options.cache_folder = Some(CACHE_FOLDER.to_string()),
+            "--cache-dir" => options.cache_folder = Some(args.next().ok_or("--cache-dir requires a folder")?),
+            "--results" => options.results = Some(args.next().ok_or("--results requires a file path")?),
+            "--checkpoint" => options.checkpoint_folder = Some(CHECKPOINT_FOLDER.to_string()),
+            "--checkpoint-dir" => options.checkpoint_folder = Some(args.next().ok_or("--checkpoint-dir requires a folder")?),
+            "--resume" => {
+                options.resume = true;
+                options.checkpoint_folder.get_or_insert_with(|| CHECKPOINT_FOLDER.to_string());
+            }
+            "--watch" => options.watch = Some(args.next().ok_or("--watch requires a file path")?),
+            "--watch-interval" => {
+                let seconds = args.next().ok_or("--watch-interval requires a number of seconds")?;
+                options.watch_interval = Some(seconds.parse().map_err(|_| "interval must be a number of seconds")?);
+            }
+            "--nodes" => options.node_table = Some(args.next().ok_or("--nodes requires a file path")?),
+            "--influence" => options.influence = true,
+            "--backend" => {
+                options.backend = match args.next().as_deref() {
+                    Some("edge-list") => Backend::EdgeList,
+                    Some("dense") => Backend::Dense,
+                    other => return Err(format!("unknown backend: {:?} (expected edge-list or dense)", other)),
+                };
+            }
+            "--outflow-normalization" => {
+                options.outflow_normalization = match args.next().as_deref() {
+                    Some("lifetime") => OutflowNormalization::Lifetime,
+                    Some("current") => OutflowNormalization::Current,
+                    Some("none") => OutflowNormalization::None,
+                    other => return Err(format!("unknown outflow normalization: {:?} (expected lifetime, current or none)", other)),
+                };
+            }
+            "--solver" => {
+                options.solver = match args.next().as_deref() {
+                    Some("jacobi") => Solver::Jacobi,
+                    Some("gauss-seidel") => Solver::GaussSeidel,
+                    Some("monte-carlo") => Solver::MonteCarlo,
+                    Some("exact") => Solver::Exact,
+                    other => return Err(format!("unknown solver: {:?} (expected jacobi, gauss-seidel, monte-carlo or exact)", other)),
+                };
+            }
+            "--walks" => {
+                let walks: usize = args.next().and_then(|w| w.parse().ok()).ok_or("--walks requires a number")?;
+                require(walks >= 2, "--walks must be at least 2 to estimate errors")?;
+                options.walks_per_node = Some(walks);
+            }
+            "--max-source-share" => {
+                let share: f64 = args.next().and_then(|s| s.parse().ok()).ok_or("--max-source-share requires a share")?;
+                require(share > 0.0 && share <= 1.0, "--max-source-share must be in (0, 1]")?;
+                options.max_source_share = Some(share);
+            }
+            "--node-capacity" => {
+                let capacity: f64 = args.next().and_then(|c| c.parse().ok()).ok_or("--node-capacity requires an amount of rank")?;
+                require(capacity > 0.0, "--node-capacity must be positive")?;
+                options.node_capacity = Some(capacity);
+            }
+            "--rank-floor" => {
+                let floor: f64 = args.next().and_then(|f| f.parse().ok()).ok_or("--rank-floor requires an amount of rank")?;
+                require((0.0..1.0).contains(&floor), "--rank-floor must be in [0, 1)")?;
+                options.rank_floor = Some(floor);
+            }
+            "--max-hops" => options.max_hops = Some(args.next().and_then(|h| h.parse().ok()).ok_or("--max-hops requires a number of hops")?),
+            "--relaxation" => {
+                let relaxation: f64 = args.next().and_then(|r| r.parse().ok()).ok_or("--relaxation requires a number")?;
+                require(relaxation > 0.0 && relaxation < 2.0, "--relaxation must be between 0 and 2")?;
+                options.relaxation = Some(relaxation);
+            }
+            "--precision" => {
+                options.precision = match args.next().as_deref() {
+                    Some("f32") => Precision::F32,
+                    Some("f64") => Precision::F64,
+                    other => return Err(format!("unknown precision: {:?} (expected f32 or f64)", other)),
+                };
+            }
+            "--spectral-radius" => options.spectral_radius = true,
+            "--diagnostics" => options.diagnostics = true,
+            "--diagnostics-tolerance" => {
+                let tolerance = args.next().ok_or("--diagnostics-tolerance requires a tolerance")?;
+                options.diagnostics_tolerance = Some(tolerance.parse().map_err(|_| "tolerance must be a number")?);
+                options.diagnostics = true;
+            }
+            "--bench" => options.bench = true,
+            "--seed" => options.seed = Some(args.next().and_then(|s| s.parse().ok()).ok_or("--seed requires a non-negative integer")?),
+            "--focus" => options.focus = Some(args.next().ok_or("--focus requires a node name or index")?),
+            "--hops" => options.focus_hops = Some(args.next().and_then(|h| h.parse().ok()).ok_or("--hops requires a number of hops")?),
+            "--thumbnails" => options.thumbnails = Some(options.thumbnails.unwrap_or(DEFAULT_THUMBNAIL_SIZE)),
+            "--thumbnail-size" => {
+                let size: f64 = args.next().and_then(|s| s.parse().ok()).ok_or("--thumbnail-size requires a size in inches")?;
+                require(size > 0.0, "--thumbnail-size must be positive")?;
+                options.thumbnails = Some(size);
+            }
+            "--tournament" => {
+                let algorithms = args.next().ok_or("--tournament requires a comma-separated list of algorithms")?;
+                options.tournament = algorithm_ids(&algorithms);
+            }
+            "--listen" => options.serve = Some(args.next().ok_or("--listen requires an address such as 127.0.0.1:8080")?),
+            "--db" => options.database = Some(args.next().ok_or("--db requires a database file")?),
+            "--node" => options.query_node = Some(args.next().ok_or("--node requires a node name or index")?),
+            "--scenario-name" => options.query_scenario = Some(args.next().ok_or("--scenario-name requires a scenario name")?),
+            "--from" => options.query_pair.0 = Some(args.next().ok_or("--from requires a node name or index")?),
+            "--to" => options.query_pair.1 = Some(args.next().ok_or("--to requires a node name or index")?),
+            "--at" => options.query_time = Some(Time(args.next().and_then(|t| t.parse().ok()).ok_or("--at requires a time")?)),
+            "--trust-method" => {
+                let methods = args.next().ok_or("--trust-method requires a comma-separated list of methods")?;
+                options.trust_methods = methods.split(',').map(|m| PairwiseTrust::parse(m.trim())).collect::<Result<_, _>>()?;
+            }
+            "--since" => {
+                let time = args.next().ok_or("--since requires a time")?;
+                options.since = Some(Time(time.parse().map_err(|_| "time must be a number")?));
+            }
+            "--until" => {
+                let time = args.next().ok_or("--until requires a time")?;
+                options.until = Some(Time(time.parse().map_err(|_| "time must be a number")?));
+            }
+            "--help" | "-h" => options.help = true,
+            "--quiet" | "-q" => options.verbosity = -1,
+            "--verbose" | "-v" => options.verbosity += 1,
+            "-vv" => options.verbosity += 2,
+            "--jobs" | "-j" => {
+                let jobs: usize = args.next().and_then(|j| j.parse().ok()).ok_or("--jobs requires a number of threads")?;
+                options.jobs = if jobs == 0 { thread::available_parallelism().map_or(1, |n| n.get()) } else { jobs };
+            }
+            "--fast-exp" => options.fast_exp = true,
+            "--decay-threads" => options.decay_threads = Some(args.next().and_then(|t| t.parse().ok()).ok_or("--decay-threads requires a number of threads (0 for automatic)")?),
+            "--elide-below" => {
+                let threshold: f64 = args.next().and_then(|t| t.parse().ok()).ok_or("--elide-below requires a rank change")?;
+                options.elision = Some(FrameElision { threshold, duplicate: options.elision.is_some_and(|e| e.duplicate) });
+            }
+            "--elide-mode" => {
+                let duplicate = match args.next().as_deref() {
+                    Some("skip") => false,
+                    Some("duplicate") => true,
+                    other => return Err(format!("unknown elide mode: {:?} (expected skip or duplicate)", other)),
+                };
+                options.elision = Some(FrameElision { threshold: options.elision.map_or(0.0, |e| e.threshold), duplicate });
+            }
+            "--smooth" => {
+                let smoothing: f64 = args.next().and_then(|s| s.parse().ok()).ok_or("--smooth requires a smoothing factor")?;
+                require(smoothing > 0.0 && smoothing <= 1.0, "--smooth must be in (0, 1]")?;
+                options.smoothing = Some(smoothing);
+            }
+            "--bootstrap" => {
+                let samples: usize = args.next().and_then(|n| n.parse().ok()).ok_or("--bootstrap requires a number of samples")?;
+                require(samples > 0, "--bootstrap requires at least one sample")?;
+                options.bootstrap = Some(BootstrapOptions { samples, confidence: DEFAULT_CONFIDENCE, resampling: Resampling::Edges });
+            }
+            "--bootstrap-noise" => {
+                let spread: f64 = args.next().and_then(|s| s.parse().ok()).ok_or("--bootstrap-noise requires a weight spread")?;
+                require(spread > 0.0 && spread <= 1.0, "--bootstrap-noise must be in (0, 1]")?;
+                options.bootstrap.as_mut().ok_or("--bootstrap-noise must follow --bootstrap")?.resampling = Resampling::Weights(spread);
+            }
+            "--confidence" => {
+                let confidence: f64 = args.next().and_then(|c| c.parse().ok()).ok_or("--confidence requires a share of the samples")?;
+                require(confidence > 0.0 && confidence < 1.0, "--confidence must be between 0 and 1")?;
+                options.bootstrap.as_mut().ok_or("--confidence must follow --bootstrap")?.confidence = confidence;
+            }
+            "--graphviz" => {
+                let format = args.next().ok_or("--graphviz requires an output format, e.g. png or svg")?;
+                require(!format.is_empty() && format.chars().all(|c| c.is_ascii_alphanumeric() || c == ':'), &format!("invalid Graphviz output format: {}", format))?;
+                let max_parallel = thread::available_parallelism().map_or(1, |n| n.get());
+                options.graphviz = Some(GraphvizPool { format, max_parallel, timeout: Duration::from_secs_f64(DEFAULT_GRAPHVIZ_TIMEOUT), retries: DEFAULT_GRAPHVIZ_RETRIES });
+            }
+            "--graphviz-jobs" => {
+                let jobs: usize = args.next().and_then(|n| n.parse().ok()).ok_or("--graphviz-jobs requires a number of processes")?;
+                require(jobs > 0, "--graphviz-jobs requires at least one process")?;
+                options.graphviz.as_mut().ok_or("--graphviz-jobs must follow --graphviz")?.max_parallel = jobs;
+            }
+            "--graphviz-timeout" => {
+                let seconds: f64 = args.next().and_then(|s| s.parse().ok()).ok_or("--graphviz-timeout requires a number of seconds")?;
+                require(seconds > 0.0, "--graphviz-timeout must be positive")?;
+                options.graphviz.as_mut().ok_or("--graphviz-timeout must follow --graphviz")?.timeout = Duration::from_secs_f64(seconds);
+            }
+            "--graphviz-retries" => {
+                let retries = args.next().and_then(|n| n.parse().ok()).ok_or("--graphviz-retries requires a number of attempts")?;
+                options.graphviz.as_mut().ok_or("--graphviz-retries must follow --graphviz")?.retries = retries;
+            }
+            "--stabilization" => {
+                let threshold: f64 = args.next().and_then(|t| t.parse().ok()).ok_or("--stabilization requires a Kendall tau")?;
+                require((-1.0..=1.0).contains(&threshold), "--stabilization must be between -1 and 1")?;
+                options.stabilization = Some(StabilizationCriterion { threshold, frames: DEFAULT_STABLE_FRAMES });
+            }
+            "--stable-frames" => {
+                let frames: usize = args.next().and_then(|n| n.parse().ok()).ok_or("--stable-frames requires a number of frames")?;
+                require(frames > 0, "--stable-frames requires at least one frame")?;
+                options.stabilization.as_mut().ok_or("--stable-frames must follow --stabilization")?.frames = frames;
+            }
+            "--truncate-stable" => options.truncate_stable = true,
+            "--anomalies" => {
+                let threshold: f64 = args.next().and_then(|z| z.parse().ok()).ok_or("--anomalies requires a number of standard deviations")?;
+                require(threshold > 0.0, "--anomalies must be positive")?;
+                options.anomalies = Some(AnomalyDetector { threshold, min_history: DEFAULT_ANOMALY_HISTORY });
+            }
+            "--collusion" => options.collusion = Some(RingCriteria::default()),
+            "--ring-size" => {
+                let size: usize = args.next().and_then(|n| n.parse().ok()).ok_or("--ring-size requires a number of nodes")?;
+                require(size >= 2, "--ring-size requires at least 2 nodes")?;
+                options.collusion.as_mut().ok_or("--ring-size must follow --collusion")?.max_size = size;
+            }
+            "--ring-density" => {
+                let density: f64 = args.next().and_then(|d| d.parse().ok()).ok_or("--ring-density requires a share of linked pairs")?;
+                require((0.0..=1.0).contains(&density), "--ring-density must be between 0 and 1")?;
+                options.collusion.as_mut().ok_or("--ring-density must follow --collusion")?.min_density = density;
+            }
+            "--ring-external" => {
+                let share: f64 = args.next().and_then(|s| s.parse().ok()).ok_or("--ring-external requires a share of incoming weight")?;
+                require((0.0..=1.0).contains(&share), "--ring-external must be between 0 and 1")?;
+                options.collusion.as_mut().ok_or("--ring-external must follow --collusion")?.max_external = share;
+            }
+            "--anomaly-history" => {
+                let frames: usize = args.next().and_then(|n| n.parse().ok()).ok_or("--anomaly-history requires a number of rank changes")?;
+                require(frames >= 2, "--anomaly-history requires at least 2 rank changes")?;
+                options.anomalies.as_mut().ok_or("--anomaly-history must follow --anomalies")?.min_history = frames;
+            }
+            "--scale" => options.scaling = ScoreScaling::parse(&args.next().ok_or("--scale requires a score scaling")?)?,
+            "--tiers" | "--tier-quantiles" => {
+                let bounds = args.next().ok_or_else(|| format!("{} requires two bounds, LOWER,UPPER", arg))?;
+                options.tiers = Some(TierThresholds::parse(&bounds, arg == "--tier-quantiles")?);
+            }
+            "--communities" => options.communities = true,
+            "--ghost-edges" => options.ghost_edges = true,
+            "--edge-top" => options.edge_sampling = Some(EdgeSampling::TopPerNode(args.next().and_then(|n| n.parse().ok()).ok_or("--edge-top requires a number of edges per node")?)),
+            "--edge-min-weight" => options.edge_sampling = Some(EdgeSampling::MinWeight(args.next().and_then(|w| w.parse().ok()).ok_or("--edge-min-weight requires a weight")?)),
+            "--auto-layout" => options.auto_layout = true,
+            "--rank-deltas" => options.rank_deltas = true,
+            "--frame-stats" => options.frame_stats = true,
+            "--alarm" => {
+                let alarm = args.next().ok_or("--alarm requires a rule such as 'top 3 above 0.6'")?;
+                options.alarms.push(ConcentrationAlarm::parse(&alarm).map_err(|e| format!("--alarm: {}", e))?);
+            }
+            "--alarm-banner" => options.alarm_banner = true,
+            "--frame-names" => {
+                let template = args.next().ok_or("--frame-names requires a template such as '{scenario}-{index:5}'")?;
+                options.frame_names = Some(FrameNames::parse(&template).map_err(|e| format!("--frame-names: {}", e))?);
+            }
+            "--edge-flux" => options.edge_flux = true,
+            "--explain" => options.explain = Some(args.next().ok_or("--explain requires a node name or index")?),
+            "--explain-frame" => {
+                let frame: usize = args.next().and_then(|f| f.parse().ok()).ok_or("--explain-frame requires a frame number")?;
+                require(frame >= 1, "frames are numbered from 1")?;
+                options.explain_frame = Some(frame);
+            }
+            "--explain-paths" => options.explain_paths = args.next().and_then(|p| p.parse().ok()).ok_or("--explain-paths requires a number of paths")?,
+            "--similar" => options.similar = Some(args.next().ok_or("--similar requires a node name or index")?),
+            "--similar-top" => options.similar_top = Some(args.next().and_then(|k| k.parse().ok()).ok_or("--similar-top requires a number of nodes")?),
+            "--warm-start" => {
+                let steps: usize = args.next().and_then(|n| n.parse().ok()).ok_or("--warm-start requires a number of iterations per frame")?;
+                require(steps >= 1, "--warm-start requires at least one iteration per frame")?;
+                options.warm_start = Some(WarmStart { steps: Some(steps), node_decay: options.warm_start.map_or(0.0, |w| w.node_decay) });
+            }
+            "--node-decay" => {
+                let node_decay: f64 = args.next().and_then(|k| k.parse().ok()).ok_or("--node-decay requires a decay constant")?;
+                require(node_decay >= 0.0, "--node-decay must not be negative")?;
+                options.warm_start = Some(WarmStart { node_decay, steps: options.warm_start.and_then(|w| w.steps) });
+            }
+            "--initial-ranks" => {
+                let initial = args.next().ok_or("--initial-ranks requires uniform, experts, priors, ranks separated by commas or a ranks.csv file")?;
+                options.initial_ranks = Some(InitialRanks::parse(&initial).unwrap_or_else(|_| initial_ranks_from_file(&initial)));
+            }
+            "--authority" => {
+                let top: usize = args.next().and_then(|k| k.parse().ok()).ok_or("--authority requires a number of nodes")?;
+                require(top >= 1, "--authority requires at least one node")?;
+                options.authority = Some(DynamicAuthority { top, smoothing: options.authority.map_or(DEFAULT_AUTHORITY_SMOOTHING, |a| a.smoothing) });
+            }
+            "--authority-smoothing" => {
+                let smoothing: f64 = args.next().and_then(|s| s.parse().ok()).ok_or("--authority-smoothing requires a number")?;
+                require((0.0..=1.0).contains(&smoothing), "--authority-smoothing must be between 0 and 1")?;
+                let top = options.authority.map(|a| a.top).ok_or("--authority-smoothing must follow --authority")?;
+                options.authority = Some(DynamicAuthority { top, smoothing });
+            }
+            "--damping" => {
+                let schedule = args.next().ok_or("--damping requires a damping factor, a range such as 0.5..0.9 or per-frame values")?;
+                options.damping = Some(DampingSchedule::parse(&schedule)?);
+            }
+            "--sweep-damping" | "--sweep-decay" => {
+                let list = args.next().ok_or_else(|| format!("{} requires a comma-separated list of values", arg))?;
+                let values: Vec<f64> = list.split(',').map(|v| v.trim().parse().map_err(|_| format!("{} values must be numbers", arg))).collect::<Result<_, _>>()?;
+                let grid = options.sweep.get_or_insert_with(SweepGrid::default);
+                if arg == "--sweep-damping" {
+                    require(values.iter().all(|&d| (0.0..1.0).contains(&d)), "damping factors must be in [0, 1)")?;
+                    grid.damping_factors = values;
+                } else {
+                    grid.decay_constants = values;
+                }
+            }
+            "--sweep-nodes" => {
+                let nodes = args.next().ok_or("--sweep-nodes requires a comma-separated list of node indices")?;
+                options.sweep_nodes = nodes.split(',').map(|n| n.trim().parse().map(NodeId).map_err(|_| "nodes must be indices")).collect::<Result<_, _>>()?;
+            }
+            "--substitutions" => options.sensitivity = Some(args.next().and_then(|n| n.parse().ok()).ok_or("--substitutions requires a number of random expert substitutions")?),
+            "--symmetrize" => options.symmetrize = true,
+            "--reverse-edges" => options.reverse_edges = true,
+            "--cluster" => options.cluster = Some(ClusterBy::parse(&args.next().ok_or("--cluster requires community, category or org")?)?),
+            "--rollup" => options.rollup = Some(Rollup::parse(&args.next().ok_or("--rollup requires org or category")?).map_err(|e| format!("--rollup: {}", e))?),
+            "--rollup-graph" => options.rollup_graph = true,
+            "--prune-below" => {
+                let epsilon: f64 = args.next().and_then(|e| e.parse().ok()).ok_or("--prune-below requires an edge weight")?;
+                require(epsilon >= 0.0, "--prune-below must not be negative")?;
+                options.prune_below = Some(epsilon);
+            }
+            "--time-respecting" => options.time_respecting = true,
+            "--reach" => options.reach = Some(args.next().ok_or("--reach requires a node name or index")?),
+            "--reach-time" => options.reach_time = Some(Time(args.next().and_then(|t| t.parse().ok()).ok_or("--reach-time requires a time")?)),
+            "--preview" => options.preview = Some(Coarsening::parse(&args.next().ok_or("--preview requires sample:FRACTION or communities")?).map_err(|e| format!("--preview: {}", e))?),
+            "--score" => options.score = Some(args.next().ok_or("--score requires a node name or index")?),
+            "--trust-between" => {
+                let pair = args.next().ok_or("--trust-between requires two nodes, e.g. alice,bob")?;
+                let (truster, trustee) = pair.split_once(',').ok_or("--trust-between requires two nodes separated by a comma")?;
+                options.trust_between = Some((truster.to_string(), trustee.to_string()));
+            }
+            "--local-trust" => options.local_trust = Some(args.next().ok_or("--local-trust requires a node name or index")?),
+            "--score-time" => options.score_time = Some(Time(args.next().and_then(|t| t.parse().ok()).ok_or("--score-time requires a time")?)),
+            "--score-local" => options.score_local = Some(args.next().and_then(|n| n.parse().ok()).ok_or("--score-local requires a number of nodes")?),
+            "--edge-file" => options.edge_file = Some(args.next().ok_or("--edge-file requires a file path")?),
+            "--decay" => {
+                let decay: f64 = args.next().and_then(|d| d.parse().ok()).ok_or("--decay requires a decay constant")?;
+                require(decay >= 0.0, "--decay must not be negative")?;
+                options.decay_constant = Some(decay);
+            }
+            "--half-life" => {
+                let half_life: f64 = args.next().and_then(|h| h.parse().ok()).ok_or("--half-life requires a number of time units")?;
+                require(half_life > 0.0, "--half-life must be positive")?;
+                options.decay_constant = Some(decay_constant_for_half_life(half_life));
+            }
+            "--hash" => options.print_hash = true,
+            "--chart-nodes" => {
+                let nodes = args.next().ok_or("--chart-nodes requires a comma-separated list of nodes")?;
+                options.chart_nodes = nodes.split(',').map(|n| n.trim().parse().map(NodeId).map_err(|_| "nodes must be indices")).collect::<Result<_, _>>()?;
+            }
+            "--chart-top" => {
+                let count = args.next().ok_or("--chart-top requires a node count")?;
+                options.chart_top = Some(count.parse().map_err(|_| "node count must be a non-negative integer")?);
+            }
+            "--profile" => options.profile = true,
+            "--tie-break" => options.tie_break = TieBreak::parse(&args.next().ok_or("--tie-break requires id, in-degree or previous-rank")?)?,
+            "--scenario" => options.scenario_files.push(args.next().ok_or("--scenario requires a file path")?),
+            "--git-repo" => options.git_repositories.push(args.next().ok_or("--git-repo requires a repository path")?),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    if options.precision == Precision::F32 {
+        require(options.backend == Backend::EdgeList && options.solver == Solver::Jacobi, "--precision f32 works with the edge-list backend and the Jacobi solver only")?;
+    }
+    if options.truncate_stable {
+        require(options.stabilization.is_some(), "--truncate-stable requires --stabilization")?;
+    }
+    if options.command.reads_results() {
+        require(!options.clean, "--clean would remove the stored results")?;
+    }
+    if options.command == Command::Export {
+        require(!options.exports.is_empty(), "export requires at least one --export format")?;
+    }
+    Ok(options)
+}
+
+// Comma-separated algorithm ids, checked against the registry
+fn algorithm_ids(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|id| AlgorithmRegistry::get(id.trim()).unwrap_or_else(|e| panic!("{}", e)).id.to_string())
+        .collect()
+}
+
+fn algorithm_by_name(id: &str, rank_config: &RankConfig) -> Box<dyn RankingAlgorithm> {
+    AlgorithmRegistry::create(id, rank_config).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Like algorithm_by_name, but with what the algorithm needs to know about the scenario beyond a frame's graph
+fn scenario_algorithm(scenario: &Scenario, id: &str, rank_config: &RankConfig) -> Box<dyn RankingAlgorithm> {
+    match id {
+        id if id == TEMPORAL_PAGERANK.id => Box::new(TemporalPageRank::with_stream(&scenario.edges)),
+        id if id == COHITS.id => Box::new(CoHits::with_items((0..scenario.num_of_nodes).map(|node| scenario.is_item(NodeId(node))).collect())),
+        _ => algorithm_by_name(id, rank_config),
+    }
+}
+
+// `trust-flow algorithms list`: every ranking algorithm with its parameters and references
+fn list_algorithms(rank_config: &RankConfig) {
+    for info in AlgorithmRegistry::all() {
+        println!("{} - {}", info.id, info.name);
+        println!("  {}", info.summary);
+        println!("  expert teleportation: {}", if info.uses_teleportation { "used" } else { "ignored" });
+        for parameter in info.parameters {
+            println!("  {} = {}  ({})", parameter.name, (parameter.value)(rank_config), parameter.description);
+        }
+        for reference in info.references {
+            println!("  see {}", reference);
+        }
+        println!();
+    }
+}
+
+// Stores a finished run in the --db database, if one was given
+#[cfg(feature = "sqlite")]
+fn record_run(options: &Options, scenario: &Scenario, algorithm: &str, settings: &str, output: &RunOutput) {
+    let Some(path) = &options.database else { return };
+    let recorded = RunStore::open(path).and_then(|mut store| store.record_run(scenario, algorithm, settings, output));
+    match recorded {
+        Ok(run) => info!("{}: recorded as run {} in {}", scenario.name, run, path),
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(2);
+        }
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn record_run(options: &Options, _: &Scenario, _: &str, _: &str, _: &RunOutput) {
+    if options.database.is_some() {
+        without_sqlite();
+    }
+}
+
+// `trust-flow query`: stored ranks of matching runs as CSV on stdout
+#[cfg(feature = "sqlite")]
+fn query_runs(options: &Options) {
+    let path = options.database.as_deref().unwrap_or(DEFAULT_DATABASE);
+    let query = RankQuery { node: options.query_node.clone(), scenario: options.query_scenario.clone(), since: options.since, until: options.until };
+    let ranks = RunStore::open(path).and_then(|store| store.query_ranks(&query)).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(2);
+    });
+    write_stored_ranks_csv(&mut io::stdout().lock(), &ranks).unwrap();
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn query_runs(_: &Options) {
+    without_sqlite();
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn without_sqlite() -> ! {
+    eprintln!("run databases need the sqlite feature: cargo build --features sqlite");
+    process::exit(2);
+}
+
+// Log records go to stderr: plain messages at info level, `LEVEL target: message` otherwise.
+// Lines are printed around the progress bar, if one is drawn.
+struct CliLogger {
+    level: LevelFilter,
+    progress: Mutex<Option<ProgressBar>>,
+}
+
+static LOGGER: OnceLock<CliLogger> = OnceLock::new();
+
+impl Log for CliLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = match record.level() {
+            Level::Info => record.args().to_string(),
+            level => format!("{:<5} {}: {}", level, record.target(), record.args()),
+        };
+        match &*self.progress.lock().unwrap() {
+            Some(bar) => bar.suspend(|| eprintln!("{}", line)),
+            None => eprintln!("{}", line),
         }
     }
 
-    for (e, &w) in edges.iter().zip(weights.iter()) {
-        if w == 0.0 {
-            writeln!(file,"  {} -> {} [style=invis];", e.source, e.target).unwrap();
+    fn flush(&self) {}
+}
+
+fn init_logging(verbosity: i32) {
+    let level = match verbosity {
+        ..0 => LevelFilter::Warn,
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    let logger = LOGGER.get_or_init(|| CliLogger { level, progress: Mutex::new(None) });
+    log::set_logger(logger).unwrap();
+    log::set_max_level(level);
+}
+
+// Progress bar over the frames of a run (hidden with --quiet or when stderr is not a terminal),
+// with the ranking time of every frame at debug level
+struct FrameProgress {
+    name: String,
+    bar: ProgressBar,
+    started: Instant,
+    frame_started: Instant,
+}
+
+impl FrameProgress {
+    fn start(name: &str, num_of_frames: usize, done: usize) -> Self {
+        let bar = ProgressBar::new(num_of_frames as u64).with_position(done as u64).with_message(name.to_string());
+        bar.set_style(ProgressStyle::with_template("{msg} [{bar:30}] frame {pos}/{len} ({elapsed}, eta {eta})").unwrap().progress_chars("=> "));
+        if log::max_level() < LevelFilter::Info {
+            bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        if let Some(logger) = LOGGER.get() {
+            *logger.progress.lock().unwrap() = Some(bar.clone());
+        }
+        let now = Instant::now();
+        FrameProgress { name: name.to_string(), bar, started: now, frame_started: now }
+    }
+
+    // `frame` is the number of frames done so far
+    fn frame_done(&mut self, frame: usize) {
+        debug!("scenario={} frame={} elapsed_ms={:.3}", self.name, frame, self.frame_started.elapsed().as_secs_f64() * 1000.0);
+        self.bar.set_position(frame as u64);
+        self.frame_started = Instant::now();
+    }
+
+    fn finish(self) {
+        self.bar.finish_and_clear();
+        if let Some(logger) = LOGGER.get() {
+            *logger.progress.lock().unwrap() = None;
+        }
+        info!("{}: ranked {} frames in {:.2} s", self.name, self.bar.position(), self.started.elapsed().as_secs_f64());
+    }
+}
+
+// Run id of --run-id auto: the UTC start time, e.g. 20261016-093015
+fn timestamp_run_id() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    // Civil date from days since 1970-01-01 (H. Hinnant's algorithm)
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let time_of_day = seconds % 86_400;
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}", year, month, day, time_of_day / 3_600, time_of_day / 60 % 60, time_of_day % 60)
+}
+
+// Relative '/'-separated path that stays below the output root
+fn inside_root(path: &str) -> bool {
+    !path.is_empty() && !path.starts_with('/') && path.split('/').all(|part| !part.is_empty() && part != "..")
+}
+
+// Files below a folder as '/'-separated paths relative to `root`
+fn files_below(root: &Path, folder: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(root.join(folder)) else { return Vec::new() };
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = format!("{}/{}", folder, entry.file_name().to_string_lossy());
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            files.extend(files_below(root, &path));
         } else {
-            let edgewidth = 8.0 * w;
-            writeln!(file,"  {} -> {} [penwidth={}];", e.source, e.target, edgewidth).unwrap();
+            files.push(path);
+        }
+    }
+    files
+}
+
+// --clean: removes the files listed in the manifest of the previous run, folders left empty by
+// that, and the folders of the scenarios about to run. Nothing else in the output folder is touched.
+fn clean_output(root: &Path, scenarios: &[Scenario]) {
+    let mut stale: Vec<String> = scenarios.iter().filter(|s| inside_root(&s.name)).flat_map(|s| files_below(root, &s.name)).collect();
+    if let Ok(manifest) = fs::read_to_string(root.join(MANIFEST_FILE)) {
+        let files = manifest_files(&manifest).unwrap_or_else(|e| {
+            eprintln!("{}: {}", root.join(MANIFEST_FILE).display(), e);
+            process::exit(2);
+        });
+        stale.extend(files.into_iter().filter(|file| inside_root(file)));
+        stale.push(MANIFEST_FILE.to_string());
+    }
+    stale.sort();
+    stale.dedup();
+    let mut removed = 0;
+    for file in &stale {
+        let path = file.split('/').fold(root.to_path_buf(), |p, part| p.join(part));
+        if fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+        // Only empty folders can be removed, so this stops at the first folder still in use
+        for folder in path.ancestors().skip(1).take_while(|folder| *folder != root) {
+            if fs::remove_dir(folder).is_err() {
+                break;
+            }
+        }
+    }
+    info!("{}: removed {} files of earlier runs", root.display(), removed);
+}
+
+// Renders every DOT file of the run next to it, e.g. frame_001.dot to frame_001.png
+fn render_with_graphviz(manifest: &mut ManifestSink, root: &Path, pool: &GraphvizPool) {
+    manifest.flush().unwrap();
+    let local = |file: &str| file.split('/').fold(root.to_path_buf(), |p, part| p.join(part));
+    let images: Vec<(String, String)> = manifest.written().iter().filter_map(|file| file.strip_suffix(".dot").map(|stem| (file.clone(), format!("{}.{}", stem, pool.format)))).collect();
+    let jobs: Vec<GraphvizJob> = images.iter().map(|(dot, image)| GraphvizJob { input: local(dot), output: local(image) }).collect();
+    let started = Instant::now();
+    if let Err(e) = pool.render(&jobs) {
+        eprintln!("{}", e);
+        process::exit(2);
+    }
+    for (_, image) in &images {
+        manifest.record(image);
+    }
+    info!("{} DOT files rendered to {} in {:.1} s", jobs.len(), pool.format, started.elapsed().as_secs_f64());
+}
+
+// profile.csv of a scenario, with `run` holding the time of its whole-run files, and the time
+// of every phase summed over the frames in the log
+fn write_profile(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput, mut run: FrameProfile) {
+    run.update_peak_memory();
+    let mut csv = Vec::new();
+    write_profile_csv(&mut csv, output.history.times(), &output.profile, &run).unwrap();
+    emit(sink, &format!("{}/profile.csv", scenario.name), &csv);
+    let mut total = run;
+    output.profile.iter().for_each(|profile| total.merge(profile));
+    let phases: Vec<String> = PHASES.iter().map(|&phase| format!("{} {:.1} ms", phase.name(), total.duration(phase).as_secs_f64() * 1000.0)).collect();
+    let memory = total.peak_memory.map_or(String::new(), |bytes| format!(", peak memory {:.1} MB", bytes as f64 / (1024.0 * 1024.0)));
+    info!("{}: {}{}", scenario.name, phases.join(", "), memory);
+}
+
+// Size, timing, per-frame convergence and statistics and the raised alarms of a scenario's run, for --summary
+fn scenario_summary(scenario: &Scenario, output: &RunOutput, elapsed: Duration) -> Json {
+    let frames = output
+        .history
+        .times()
+        .iter()
+        .zip(&output.convergence)
+        .enumerate()
+        .map(|(frame, (time, convergence))| {
+            Json::object(vec![
+                ("frame", FrameIdx(frame).number().into()),
+                ("time", time.value().into()),
+                ("iterations", convergence.map_or(Json::Null, |c| c.iterations.into())),
+                ("residual", convergence.map_or(Json::Null, |c| c.residual.into())),
+            ])
+        })
+        .collect();
+    Json::object(vec![
+        ("name", scenario.name.as_str().into()),
+        ("nodes", scenario.num_of_nodes.into()),
+        ("edges", scenario.edges.len().into()),
+        ("half_life", half_life(scenario.decay_constant).map_or(Json::Null, Json::from)),
+        ("frames", output.history.num_of_frames().into()),
+        ("seconds", elapsed.as_secs_f64().into()),
+        ("result_hash", format!("{:016x}", run_hash(&output.frame_hashes)).into()),
+        ("convergence", Json::Array(frames)),
+        ("statistics", Json::Array(FrameStats::of_run(scenario, output).iter().map(FrameStats::to_json).collect())),
+        ("alarms", Json::Array(concentration_alarms(scenario, output).iter().map(RaisedAlarm::to_json).collect())),
+    ])
+}
+
+// Machine-readable summary of the run for CI pipelines: the arguments and ranking settings, every
+// scenario's summary, the total runtime, the outcome and the written files
+fn write_summary(path: &str, manifest: &ManifestSink, settings: &str, scenarios: Vec<Json>, failed_assertions: usize, elapsed: Duration) {
+    let summary = Json::object(vec![
+        ("arguments", Json::Array(env::args().skip(1).map(Json::from).collect())),
+        ("settings", settings.into()),
+        ("scenarios", Json::Array(scenarios)),
+        ("seconds", elapsed.as_secs_f64().into()),
+        ("failed_assertions", failed_assertions.into()),
+        ("status", (if failed_assertions > 0 { 1 } else { 0 }).into()),
+        ("files", Json::Array(manifest.written().iter().map(|file| Json::from(manifest.location(file))).collect())),
+    ]);
+    fs::write(path, format!("{}\n", summary)).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(2);
+    });
+    info!("{} created", path);
+}
+
+// Writes the manifest of the run and waits for the sink, then points out files in the scenarios'
+// folders that the run did not write, e.g. frames of an earlier, longer run. The stages of a
+// split run (`run`, `render`, ...) each write only part of the files, so they point out nothing.
+fn finish_run(manifest: &mut ManifestSink, local_root: Option<&Path>, scenarios: &[Scenario], options: &Options) {
+    manifest.write_manifest(options.run_id.as_deref()).unwrap();
+    manifest.flush().unwrap();
+    let Some(root) = local_root.filter(|_| options.command == Command::All) else { return };
+    for scenario in scenarios.iter().filter(|s| inside_root(&s.name)) {
+        let stale = files_below(root, &scenario.name).into_iter().filter(|file| !manifest.written().contains(file)).count();
+        if stale > 0 {
+            warn!("{}: {} files in {} are left from an earlier run (--clean removes them, --run-id keeps runs apart)", scenario.name, stale, root.join(&scenario.name).display());
         }
     }
+}
+
+fn emit(sink: &mut dyn Sink, path: &str, contents: &[u8]) {
+    sink.write(path, contents).unwrap();
+    info!("{} created", sink.location(path));
+}
+
+fn plot_scenario(sink: &mut dyn Sink, scenario: &Scenario, algorithm: &dyn RankingAlgorithm, frame_times: &[Time], options: &Options) -> RunOutput {
+    let output = rank_scenario(scenario, algorithm, frame_times, options);
+    render_frames(sink, scenario, algorithm, frame_times, output, options)
+}
+
+fn rank_scenario(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, frame_times: &[Time], options: &Options) -> RunOutput {
+    let mut output = RunOutput::new();
+    let mut progress = FrameProgress::start(&scenario.name, frame_times.len(), 0);
+    continue_scenario_parallel(scenario, algorithm, frame_times, &scenario.graph_options, options.jobs, &mut output, &mut |output| {
+        progress.frame_done(output.history.num_of_frames());
+        Ok(())
+    }).unwrap();
+    progress.finish();
+    output
+}
 
-    writeln!(file, "}}").unwrap();
-    println!("{pathname} created");
+// Like rank_scenario, but starting from the frames of a checkpoint (if any) and saving the
+// run state after every newly ranked frame
+fn rank_scenario_with_checkpoint(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, frame_times: &[Time], options: &Options, checkpoint: Checkpoint, folder: &str) -> RunOutput {
+    let path = Checkpoint::path(Path::new(folder), &scenario.name);
+    let fail = |e: io::Error| -> ! {
+        eprintln!("{}: {}", path.display(), e);
+        process::exit(2);
+    };
+    let mut writer = CheckpointWriter::create(&path, &checkpoint).unwrap_or_else(|e| fail(e));
+    let mut output = checkpoint.output;
+    let mut progress = FrameProgress::start(&scenario.name, frame_times.len(), output.history.num_of_frames());
+    continue_scenario_parallel(scenario, algorithm, frame_times, &scenario.graph_options, options.jobs, &mut output, &mut |output| {
+        progress.frame_done(output.history.num_of_frames());
+        writer.push(output)
+    }).unwrap_or_else(|e| fail(e));
+    progress.finish();
+    output
 }
 
-fn plot_scenario(name: &str, edges: Vec<Edge>, num_of_nodes: usize, expert_nodes: Vec<usize>) {
-    let mut node_positions = Vec::with_capacity(num_of_nodes);
-    for i in 0..num_of_nodes {
-        let angle = 2.0 * PI * (i as f64) / (num_of_nodes as f64);
-        let x = angle.cos();
-        let y = angle.sin();
-        node_positions.push((x, y));
+// `run` stores the ranked frames in the checkpoint format, for the later stages to read
+fn save_results(sink: &mut dyn Sink, scenario: &Scenario, fingerprint: u64, frame_times: &[Time], output: &RunOutput) {
+    let results = Checkpoint { fingerprint, frame_times: frame_times.to_vec(), edges: scenario.edges.clone(), output: output.clone() };
+    let mut bytes = Vec::new();
+    write_checkpoint(&mut bytes, &results).unwrap();
+    emit(sink, &format!("{}/{}", scenario.name, RESULTS_FILE), &bytes);
+}
+
+// Ranks of a complete run with the same fingerprint, if the cache has them
+fn cached_results(folder: &str, name: &str, fingerprint: u64, frame_times: &[Time]) -> Option<Checkpoint> {
+    let path = Checkpoint::cache_path(Path::new(folder), fingerprint);
+    let text = fs::read_to_string(&path).ok()?;
+    match Checkpoint::read(&text) {
+        Ok(cached) if cached.fingerprint == fingerprint && cached.output.history.num_of_frames() == frame_times.len() => {
+            info!("{}: ranks taken from the cache ({})", name, path.display());
+            Some(cached)
+        }
+        Ok(_) => None,
+        Err(e) => {
+            warn!("{}: ignoring the cached ranks in {} ({})", name, path.display(), e);
+            None
+        }
     }
+}
+
+// A cache that cannot be written only costs the next run its ranking time
+fn cache_results(folder: &str, scenario: &Scenario, fingerprint: u64, frame_times: &[Time], output: &RunOutput) {
+    let path = Checkpoint::cache_path(Path::new(folder), fingerprint);
+    let cached = Checkpoint { fingerprint, frame_times: frame_times.to_vec(), edges: scenario.edges.clone(), output: output.clone() };
+    if let Err(e) = CheckpointWriter::create(&path, &cached) {
+        warn!("{}: cannot cache the ranks in {} ({})", scenario.name, path.display(), e);
+    }
+}
 
-    let mut teleportation_targets = vec![(1.0 - EXPERT_TELEPORT_FRACTION) / num_of_nodes as f64; num_of_nodes];
-    for &e in &expert_nodes {
-        teleportation_targets[e] += EXPERT_TELEPORT_FRACTION / expert_nodes.len() as f64;
+// Results stored by `run` for the same scenario, frame times and ranking settings
+fn stored_results(options: &Options, local_root: Option<&Path>, name: &str, fingerprint: u64, frame_times: &[Time]) -> Checkpoint {
+    let path = match (&options.results, local_root) {
+        (Some(pathname), _) => PathBuf::from(pathname),
+        (None, Some(root)) => root.join(name).join(RESULTS_FILE),
+        (None, None) => panic!("--results is required with bucket outputs"),
+    };
+    let fail = |message: String| -> ! {
+        eprintln!("{}: {}", path.display(), message);
+        process::exit(2);
+    };
+    let text = fs::read_to_string(&path).unwrap_or_else(|e| fail(format!("{} (store results with `trust-flow run` first)", e)));
+    let results = Checkpoint::read(&text).unwrap_or_else(|e| fail(e));
+    if results.fingerprint != fingerprint {
+        fail(format!("results of other inputs or settings than {} (run it again)", name));
     }
+    if results.output.history.num_of_frames() != frame_times.len() {
+        fail(format!("{} of {} frames stored", results.output.history.num_of_frames(), frame_times.len()));
+    }
+    info!("{}: using the results stored in {}", name, path.display());
+    results
+}
+
+// `frames.json` next to the frames: every rendered frame's number, time, full frame and, with
+// --thumbnails, thumbnail, paths relative to the output folder, so tools can map the files to
+// times whatever the frame names and a viewer can scrub through the thumbnails
+fn write_frame_index(out: &mut dyn Write, scenario: &Scenario, frame_times: &[Time], frames: &[usize], thumbnails: bool) -> io::Result<()> {
+    let entries = frames
+        .iter()
+        .map(|&frame| {
+            let file = scenario.frame_file(FrameIdx(frame), frame_times[frame], frame_times.len(), "dot");
+            let mut entry = vec![
+                ("frame", FrameIdx(frame).number().into()),
+                ("time", frame_times[frame].value().into()),
+                ("full", format!("{}/{}", scenario.name, file).into()),
+            ];
+            if thumbnails {
+                entry.push(("thumbnail", format!("{}/thumbnails/{}", scenario.name, file).into()));
+            }
+            Json::object(entry)
+        })
+        .collect();
+    writeln!(out, "{}", Json::object(vec![("frames", Json::Array(entries))]))
+}
+
+// Ranks as frames and exports show them: averaged over frames with --smooth, then scaled with --scale
+fn displayed_history(options: &Options, history: &RankHistory) -> RankHistory {
+    let smoothed = options.smoothing.map(|smoothing| history.smoothed(smoothing));
+    options.scaling.scale_history(smoothed.as_ref().unwrap_or(history))
+}
 
-    let max_time = 20;
-    for time in 0..=max_time {
-        let decayed_weights: Vec<f64> = edges.iter().map(|e| {
-            if e.time_of_creation <= time { exponential_decay(time, e.time_of_creation, 1.0, DECAY_CONSTANT) }
-            else { 0.0 }
-        }).collect();
+// DOT frames are serialized on up to `--jobs` threads, in batches of consecutive frames that are
+// written to the sink in order before the next batch starts. Elided frames are not serialized;
+// they are left out or written as a copy of the last rendered frame.
+fn render_frames(sink: &mut dyn Sink, scenario: &Scenario, algorithm: &dyn RankingAlgorithm, frame_times: &[Time], mut output: RunOutput, options: &Options) -> RunOutput {
+    let Scenario { name, edges, .. } = scenario;
+    let node_positions = scenario.layout();
+    let pinned = scenario.pinned_nodes();
+    let node_labels = scenario.node_labels();
+    let decay_description = scenario.decay_description();
+    let scores = displayed_history(options, &output.history);
+    let style = options.scaling.render_style(&scenario.style);
+    let communities = if options.communities || style.cluster == Some(ClusterBy::Community) { tracked_communities(scenario, &output) } else { Vec::new() };
+    let metadata_clusters = scenario.clusters();
+    let clusters = |frame: usize| match (style.cluster, communities.get(frame)) {
+        (Some(ClusterBy::Community), Some(communities)) => community_clusters(communities),
+        _ => metadata_clusters.clone(),
+    };
+    // Bootstrap intervals of the raw ranks, shown after the node names
+    let intervals = options.bootstrap.map(|bootstrap| {
+        let intervals = bootstrap_run(scenario, algorithm, &output, &bootstrap, scenario.seed);
+        let mut json = Vec::new();
+        write_bootstrap_json(&mut json, output.history.times(), &intervals, &node_labels, &bootstrap).unwrap();
+        emit(sink, &format!("{}/bootstrap.json", name), &json);
+        intervals
+    });
+    let frame_labels = |frame: usize| match &intervals {
+        Some(intervals) => node_labels
+            .iter()
+            .zip(&intervals[frame])
+            .map(|(label, interval)| format!("{} [{:.*}, {:.*}]", label, style.label_precision, interval.lower, style.label_precision, interval.upper))
+            .collect(),
+        None => node_labels.clone(),
+    };
+    let tooltips = if style.edge_tooltips { edge_tooltips(scenario) } else { Vec::new() };
+    // Rank carried along every edge, from the raw ranks
+    let fluxes = style.edge_flux.then(|| flux_history(scenario, algorithm, &output)).flatten();
+    if style.edge_flux && fluxes.is_none() {
+        warn!("{}: {} does not move rank along edges, frames show no edge flux", name, algorithm.name());
+    }
+    // Statistics of the raw ranks, which scaled scores would distort
+    let statistics = if style.frame_stats { FrameStats::of_run(scenario, &output) } else { Vec::new() };
+    let thumbnail_style = options.thumbnails.map(|size| style.thumbnail(size));
+    let focus = options.focus.as_deref().and_then(|node| {
+        let found = scenario.find_node(node);
+        if found.is_none() {
+            warn!("{}: no node {} to focus on, rendering the whole graph", name, node);
+        }
+        found
+    });
+    let render = |frame: usize, style: &RenderStyle, labels: &[String], profile: &mut FrameProfile| {
+        let started = Instant::now();
+        let ranks = scores.ranks_at(FrameIdx(frame));
+        let mut dot = Vec::new();
+        let scale = ColorScale::new(style, ranks, scores.frames());
+        let expert_nodes = scenario.experts_at(frame_times[frame]);
+        let changes = match options.render_diff {
+            Some(rank_threshold) if frame > 0 => {
+                let (previous, current) = (output.history.ranks_at(FrameIdx(frame - 1)), output.history.ranks_at(FrameIdx(frame)));
+                FrameChanges::between(&output.edge_weights[frame - 1], &output.edge_weights[frame], previous, current, rank_threshold, options.faded_weight)
+            }
+            _ => FrameChanges::default(),
+        };
+        let borders = match (communities.get(frame).filter(|_| options.communities), options.render_diff, options.tiers) {
+            (Some(communities), _, _) => community_borders(communities),
+            (None, Some(_), _) => changes.borders(),
+            (None, None, Some(tiers)) => tiers.classify(ranks).iter().map(Tier::border).collect(),
+            (None, None, None) => Vec::new(),
+        };
+        // Changes of the displayed scores, which the labels show
+        let deltas: Vec<f64> = match frame.checked_sub(1).filter(|_| style.rank_deltas) {
+            Some(previous) => ranks.iter().zip(scores.ranks_at(FrameIdx(previous))).map(|(r, p)| r - p).collect(),
+            None => Vec::new(),
+        };
+        let flux = fluxes.as_ref().map_or(&[][..], |fluxes| fluxes[frame].as_slice());
+        let (weights, active, upcoming, mut annotations) = (&output.edge_weights[frame], scenario.active_nodes(frame_times[frame]), scenario.upcoming_edges(frame_times[frame]), scenario.annotations_at(frame_times[frame]));
+        let stats_lines = statistics.get(frame).filter(|_| style.frame_stats).map_or(Vec::new(), FrameStats::label_lines);
+        annotations.extend(stats_lines.iter().map(String::as_str));
+        let alarms: Vec<String> = if style.alarm_banner { alarms_at(scenario, FrameIdx(frame), output.history.ranks_at(FrameIdx(frame)), weights, frame_times[frame]).iter().map(RaisedAlarm::message).collect() } else { Vec::new() };
+        let view = focus.map(|focus| FocusView::new(scenario.num_of_nodes, edges, weights, focus, options.focus_hops.unwrap_or(DEFAULT_FOCUS_HOPS)));
+        let view_layout = view.as_ref().map(FocusView::layout);
+        profile.add(Phase::Layout, started.elapsed());
+        profile.time(Phase::Rendering, || match (&view, view_layout) {
+            (Some(view), Some(view_layout)) => {
+                // Only the focus node's neighborhood, colored by the ranks of the full graph
+                let title = format!("{}, {} hop{} around {}", algorithm.name(), view.hops, if view.hops == 1 { "" } else { "s" }, node_labels[view.focus.index()]);
+                let (ranks, edges, weights, labels) = (view.node_values(ranks), view.edge_list(edges), view.edge_values(weights), view.node_values(labels));
+                let (flux, expert_nodes, active, borders) = (view.edge_values(flux), view.node_list(&expert_nodes), view.node_values(&active), view.node_values(&borders));
+                let (clusters, changed, upcoming, deltas, tooltips) = (view.clusters(&clusters(frame)), view.changes(&changes.edges), view.edge_values(&upcoming), view.node_values(&deltas), view.edge_values(&tooltips));
+                let render = FrameRender::new(&ranks, &edges, &weights, &labels, &view_layout)
+                    .experts(&expert_nodes)
+                    .frame(FrameIdx(frame), frame_times.len())
+                    .title(&title, &decay_description)
+                    .annotations(&annotations)
+                    .active(&active)
+                    .borders(&borders)
+                    .deltas(&deltas)
+                    .clusters(&clusters)
+                    .changed(&changed)
+                    .upcoming(&upcoming)
+                    .flux(&flux)
+                    .tooltips(&tooltips)
+                    .alarms(&alarms);
+                write_dot(&mut dot, &render, style, &scale).unwrap()
+            }
+            _ => {
+                let clusters = clusters(frame);
+                let render = FrameRender::new(ranks, edges, weights, labels, &node_positions)
+                    .experts(&expert_nodes)
+                    .frame(FrameIdx(frame), frame_times.len())
+                    .title(algorithm.name(), &decay_description)
+                    .annotations(&annotations)
+                    .active(&active)
+                    .pinned(&pinned)
+                    .borders(&borders)
+                    .deltas(&deltas)
+                    .clusters(&clusters)
+                    .changed(&changes.edges)
+                    .upcoming(&upcoming)
+                    .flux(flux)
+                    .tooltips(&tooltips)
+                    .alarms(&alarms);
+                write_dot(&mut dot, &render, style, &scale).unwrap()
+            }
+        });
+        dot
+    };
+    // A full frame and, with --thumbnails, its thumbnail
+    let render = |frame: usize| {
+        let mut profile = FrameProfile::default();
+        let full = render(frame, &style, &frame_labels(frame), &mut profile);
+        let thumbnail = thumbnail_style.as_ref().map(|thumbnail| render(frame, thumbnail, &node_labels, &mut profile));
+        (full, thumbnail, profile)
+    };
 
-        let num_of_iterations = 10;
-        let damping_factor = 0.5;
-        let ranks = pagerank_variant(
-            &edges,
-            &decayed_weights,
-            num_of_nodes,
-            num_of_iterations,
-            damping_factor,
-            &teleportation_targets,
-        );
-        let full_folder_pathname = OUTPUT_FOLDER.to_string() + "/" + name;
+    let num_of_frames = output.history.num_of_frames();
+    let elided = options.elision.map_or(vec![false; num_of_frames], |e| output.history.elided_frames(e.threshold));
+    // With --truncate-stable, the animation ends at the frame the ordering stabilized in
+    let stable = options.stabilization.filter(|_| options.truncate_stable).and_then(|criterion| stabilization_frame(&consecutive_taus(&output.history), &criterion));
+    let frames: Vec<usize> = (0..stable.map_or(num_of_frames, |frame| frame.index() + 1)).collect();
+    let mut last_dot = (Vec::new(), None);
+    let mut written = Vec::new();
+    output.profile.resize(num_of_frames, FrameProfile::default());
+    for batch in frames.chunks(options.jobs.max(1)) {
+        let kept: Vec<usize> = batch.iter().copied().filter(|&frame| !elided[frame]).collect();
+        let dots: Vec<(Vec<u8>, Option<Vec<u8>>, FrameProfile)> = if kept.len() <= 1 {
+            kept.iter().map(|&frame| render(frame)).collect()
+        } else {
+            thread::scope(|scope| {
+                let workers: Vec<_> = kept.iter().map(|&frame| scope.spawn(move || render(frame))).collect();
+                workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+            })
+        };
+        let mut dots = dots.into_iter();
+        for &frame in batch {
+            if !elided[frame] {
+                let (dot, thumbnail, profile) = dots.next().unwrap();
+                output.profile[frame].merge(&profile);
+                last_dot = (dot, thumbnail);
+            } else if !options.elision.is_some_and(|e| e.duplicate) {
+                continue;
+            }
+            let file = scenario.frame_file(FrameIdx(frame), frame_times[frame], num_of_frames, "dot");
+            output.profile[frame].time(Phase::Export, || {
+                emit(sink, &format!("{}/{}", name, file), &last_dot.0);
+                if let Some(thumbnail) = &last_dot.1 {
+                    emit(sink, &format!("{}/thumbnails/{}", name, file), thumbnail);
+                }
+            });
+            output.profile[frame].update_peak_memory();
+            written.push(frame);
+        }
+    }
+    let mut json = Vec::new();
+    write_frame_index(&mut json, scenario, frame_times, &written, options.thumbnails.is_some()).unwrap();
+    emit(sink, &format!("{}/frames.json", name), &json);
+    if let Some(frame) = stable {
+        info!("{}: {} of {} frames rendered, the rank ordering is stable from frame {}", name, frames.len(), num_of_frames, frame.number());
+    }
+    if let Some(elision) = options.elision {
+        let num_of_elided = elided.iter().filter(|&&e| e).count();
+        info!("{}: {} of {} frames elided (rank change below {}){}", name, num_of_elided, num_of_frames, elision.threshold, if elision.duplicate { ", written as copies" } else { "" });
+    }
+    output
+}
 
-        fs::create_dir_all(&full_folder_pathname).unwrap();
-        let filename = format!("{}/frame_{:03}.dot", &full_folder_pathname, time);
-        write_dot(&filename, &ranks, &edges, &decayed_weights, &expert_nodes, &node_positions, time + 1, max_time + 1, "Custom PageRank variant", "Exponential");
+// A saved checkpoint of the same run, if there is one
+fn resume_checkpoint(folder: &str, name: &str, fingerprint: u64) -> Option<Checkpoint> {
+    let path = Checkpoint::path(Path::new(folder), name);
+    let text = fs::read_to_string(&path).ok()?;
+    match Checkpoint::read(&text) {
+        Ok(checkpoint) if checkpoint.fingerprint == fingerprint => {
+            info!("{}: resuming after frame {} of {}", name, checkpoint.output.history.num_of_frames(), checkpoint.frame_times.len());
+            Some(checkpoint)
+        }
+        Ok(_) => {
+            warn!("{}: {} belongs to different inputs or settings, starting over", name, path.display());
+            None
+        }
+        Err(e) => {
+            warn!("{}: cannot resume from {} ({}), starting over", name, path.display(), e);
+            None
+        }
     }
 }
-fn main() {
 
-    {
-        let edges = vec![
-            Edge { source: 0, target: 1, time_of_creation: 1 },
-            Edge { source: 1, target: 2, time_of_creation: 2 },
-            Edge { source: 1, target: 3, time_of_creation: 3 },
-            Edge { source: 3, target: 4, time_of_creation: 4 },
-            Edge { source: 3, target: 5, time_of_creation: 5 },
-            Edge { source: 5, target: 1, time_of_creation: 6 },
-        ];
-        plot_scenario("trust-flow-example", edges, 6, vec![0]); 
+// Rows of the --nodes table, if one was given
+fn node_rows(options: &Options) -> Vec<NodeRow> {
+    let Some(pathname) = &options.node_table else { return Vec::new() };
+    let table = fs::read_to_string(pathname).map_err(|e| e.to_string()).and_then(|text| read_node_table(&text));
+    table.unwrap_or_else(|e| {
+        eprintln!("{}: {}", pathname, e);
+        process::exit(2);
+    })
+}
+
+// Ranks of the last frame of a previous run's ranks.csv (--export csv), for --initial-ranks
+fn initial_ranks_from_file(pathname: &str) -> InitialRanks {
+    let history = fs::read_to_string(pathname).map_err(|e| e.to_string()).and_then(|text| RankHistory::read_timeline_csv(&text));
+    let ranks = history.map(|history| history.frames().last().cloned()).unwrap_or_else(|e| {
+        eprintln!("{}: {}", pathname, e);
+        process::exit(2);
+    });
+    InitialRanks::Given(ranks.unwrap_or_else(|| {
+        eprintln!("{}: no ranks", pathname);
+        process::exit(2);
+    }))
+}
+
+// Applies the --nodes table to a scenario, which must have room for every node in it
+fn with_node_table(mut scenario: Scenario, rows: &[NodeRow]) -> Scenario {
+    scenario.apply_node_rows(rows.to_vec());
+    scenario.validate().unwrap_or_else(|e| {
+        eprintln!("{}: {}", scenario.name, e);
+        process::exit(2);
+    });
+    scenario
+}
+
+// Number of nodes needed to hold every node mentioned by the edges or the experts
+fn nodes_referenced(edges: &[Edge], experts: &[NodeId]) -> usize {
+    edges
+        .iter()
+        .flat_map(|e| [e.source, e.target])
+        .chain(experts.iter().copied())
+        .map(|n| n.index() + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+// Pipeline mode: edge events from stdin, one JSON rank snapshot per line on stdout,
+// diagnostics on stderr; nothing is written to disk
+// Edge event parser of the input format, weighing events by their count with --weight-transform
+fn event_parser(options: &Options, format: EventFormat) -> EventParser {
+    EventParser::new(format).weight_transform(options.weight_transform)
+}
+
+fn run_pipeline(options: &Options, algorithm: &dyn RankingAlgorithm) {
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text).unwrap();
+    let (edges, edge_sources) = parse_edge_events(&text, event_parser(options, options.input_format)).unwrap_or_else(|e| {
+        eprintln!("stdin: {}", e);
+        process::exit(2);
+    });
+
+    let rows = node_rows(options);
+    let num_of_nodes = nodes_referenced(&edges, &options.experts).max(rows.iter().map(|r| r.node.index() + 1).max().unwrap_or(0));
+    if num_of_nodes == 0 {
+        eprintln!("stdin: no edge events");
+        process::exit(2);
     }
+    let mut scenario = with_node_table(Scenario::new("stdin", num_of_nodes, options.experts.clone(), edges), &rows);
+    scenario.edge_sources = edge_sources;
+
+    let frame_times = options.frame_times.clone().unwrap_or_else(|| event_times(&scenario.edges));
 
+    let output = run_scenario(&scenario, algorithm, &frame_times, &scenario.graph_options);
+    let mut stdout = io::stdout().lock();
+    if let Some(threshold) = options.diff_threshold {
+        write_diff_stream(&mut stdout, &output.history, threshold).unwrap();
+        info!("{} edge events, {} nodes, {} snapshots", scenario.edges.len(), num_of_nodes, frame_times.len());
+        return;
+    }
+    for (frame, (time, ranks)) in output.history.times().iter().zip(output.history.frames()).enumerate() {
+        let snapshot = Json::object(vec![
+            ("frame", FrameIdx(frame).number().into()),
+            ("time", time.value().into()),
+            ("ranks", Json::numbers(ranks)),
+        ]);
+        writeln!(stdout, "{}", snapshot).unwrap();
+    }
+    info!("{} edge events, {} nodes, {} snapshots", scenario.edges.len(), num_of_nodes, frame_times.len());
+}
 
+// Streaming pipeline: edge events are read from stdin as they arrive and added to the graph, and a
+// snapshot of the newest event time is written every `--every-events` events and/or every
+// `--every-seconds` of wall time, provided something arrived since the previous one
+fn run_stream(options: &Options, pagerank: &PageRankVariant) {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let metrics = expose_metrics(options);
+    let mut stdout = io::stdout().lock();
+    stream_snapshots(options, pagerank, "stdin", receiver, false, &metrics, &mut |snapshot| {
+        stdout.write_all(snapshot)?;
+        stdout.flush()
+    });
+}
+
+// NATS service mode: edge events arrive as the lines of messages on a subject and are streamed
+// like stdin events; snapshots are published on another subject, or written to stdout. Invalid
+// events are skipped with a warning, so that one bad message does not stop the service.
+#[cfg(feature = "nats")]
+fn run_nats(options: &Options, pagerank: &PageRankVariant, address: &str) {
+    assert!(options.every_events.is_some() || options.every_seconds.is_some(), "--nats requires --every-events or --every-seconds");
+    let mut config = NatsConfig::new().require_tls(options.nats_tls);
+    if let Some(path) = &options.nats_credentials {
+        config = config.credentials_file(path);
+    }
+    if let (Ok(user), Ok(password)) = (env::var("NATS_USER"), env::var("NATS_PASSWORD")) {
+        config = config.user_and_password(&user, &password);
+    }
+    if let Ok(token) = env::var("NATS_TOKEN") {
+        config = config.token(&token);
+    }
+    if let Some(path) = &options.nats_ca {
+        config = config.root_certificates(path);
+    }
+    let connection = NatsConnection::connect(address, &config).unwrap_or_else(|e| {
+        eprintln!("{}: {}", address, e);
+        process::exit(2);
+    });
+    let subject = options.nats_subject.as_deref().unwrap_or(DEFAULT_NATS_SUBJECT);
+    let mut subscription = connection.subscribe(subject).unwrap();
+    let publisher = connection.publisher();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        while let Some(message) = subscription.next_message() {
+            for line in String::from_utf8_lossy(&message.payload).lines() {
+                if sender.send(line.to_string()).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    info!("consuming edge events of {} from {}", subject, address);
+    let metrics = expose_metrics(options);
+    let source = format!("NATS {}", subject);
+    match &options.nats_publish {
+        Some(output) => stream_snapshots(options, pagerank, &source, receiver, true, &metrics, &mut |snapshot| {
+            // A snapshot that cannot be published is lost, like one published while nobody listens
+            if let Err(e) = publisher.publish(output, snapshot.trim_ascii_end()) {
+                warn!("NATS: cannot publish on {}: {}", output, e);
+            }
+            Ok(())
+        }),
+        None => {
+            let mut stdout = io::stdout().lock();
+            stream_snapshots(options, pagerank, &source, receiver, true, &metrics, &mut |snapshot| {
+                stdout.write_all(snapshot)?;
+                stdout.flush()
+            });
+        }
+    }
+}
+
+#[cfg(not(feature = "nats"))]
+fn run_nats(_options: &Options, _pagerank: &PageRankVariant, _address: &str) {
+    eprintln!("NATS ingestion needs the nats feature: cargo build --features nats");
+    process::exit(2);
+}
+
+// gRPC scoring service: ranks one graph of the edge events submitted to it, with the command line's
+// experts and rank settings
+#[cfg(feature = "grpc")]
+fn run_grpc(options: &Options, pagerank: &PageRankVariant, address: &str) {
+    let mut scenario = Scenario::new("grpc", 0, options.experts.clone(), Vec::new());
+    scenario.reversed_edges = options.reverse_edges;
+    let service = TrustFlowService::new(scenario, pagerank.config.clone());
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(address).await.unwrap_or_else(|e| {
+            eprintln!("{}: {}", address, e);
+            process::exit(2);
+        });
+        info!("serving the trust-flow gRPC API on {}", address);
+        grpc::serve(listener, service).await.unwrap();
+    });
+}
+
+#[cfg(not(feature = "grpc"))]
+fn run_grpc(_options: &Options, _pagerank: &PageRankVariant, _address: &str) {
+    eprintln!("the gRPC service needs the grpc feature: cargo build --features grpc");
+    process::exit(2);
+}
+
+// Output to an s3:// or gs:// bucket
+#[cfg(any(feature = "s3", feature = "gcs"))]
+fn bucket_sink(url: &str, options: &Options) -> Box<dyn Sink> {
+    Box::new(ObjectStoreSink::new(url, options.max_parallel_uploads.unwrap_or(8)).unwrap_or_else(|e| {
+        eprintln!("{}: {}", url, e);
+        process::exit(2);
+    }))
+}
+
+#[cfg(not(any(feature = "s3", feature = "gcs")))]
+fn bucket_sink(_url: &str, _options: &Options) -> Box<dyn Sink> {
+    eprintln!("output to a bucket needs the s3 or gcs feature: cargo build --features s3");
+    process::exit(2);
+}
+
+// Rank and edge weight timelines, each a table of its own
+#[cfg(feature = "parquet")]
+fn export_parquet(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput) {
+    let mut ranks = Vec::new();
+    write_rank_timeline_parquet(&mut ranks, output, &scenario.node_labels()).unwrap();
+    emit(sink, &format!("{}/ranks.parquet", scenario.name), &ranks);
+    let mut edge_weights = Vec::new();
+    write_edge_weight_timeline_parquet(&mut edge_weights, scenario, output).unwrap();
+    emit(sink, &format!("{}/edge_weights.parquet", scenario.name), &edge_weights);
+}
+
+#[cfg(not(feature = "parquet"))]
+fn export_parquet(_sink: &mut dyn Sink, _scenario: &Scenario, _output: &RunOutput) {
+    eprintln!("--export parquet needs the parquet feature: cargo build --features parquet");
+    process::exit(2);
+}
+
+// Metrics of a streaming run, served at http://<--metrics-address>/metrics by a background thread
+fn expose_metrics(options: &Options) -> Arc<Mutex<ServiceMetrics>> {
+    let metrics = Arc::new(Mutex::new(ServiceMetrics::new()));
+    if let Some(address) = &options.metrics_address {
+        let listener = TcpListener::bind(address).unwrap_or_else(|e| {
+            eprintln!("{}: {}", address, e);
+            process::exit(2);
+        });
+        info!("Prometheus metrics on http://{}/metrics", address);
+        let shared = Arc::clone(&metrics);
+        thread::spawn(move || serve_metrics(&listener, &shared));
+    }
+    metrics
+}
+
+// Adds the edge events of `lines` to a growing graph and hands `publish` one JSON snapshot line
+// every `--every-events` events and/or every `--every-seconds`, and once the lines end
+fn stream_snapshots(
+    options: &Options,
+    pagerank: &PageRankVariant,
+    source: &str,
+    lines: mpsc::Receiver<String>,
+    skip_invalid: bool,
+    metrics: &Mutex<ServiceMetrics>,
+    publish: &mut dyn FnMut(&[u8]) -> io::Result<()>,
+) {
+    let rows = node_rows(options);
+    let table_nodes = rows.iter().map(|r| r.node.index() + 1).max().unwrap_or(0);
+    // Nodes are added as events refer to them, so the scenario is only valid from the first snapshot on
+    let mut scenario = Scenario::new(source, table_nodes, options.experts.clone(), Vec::new());
+    scenario.apply_node_rows(rows);
+    scenario.reversed_edges = options.reverse_edges;
+    let mut parser = event_parser(options, options.input_format);
+    let mut differ = options.diff_threshold.map(RankDiffer::new);
+    assert!(differ.is_none() || options.top_k.is_none(), "--top-k and --diff-threshold cannot be combined");
+    let mut tracker = options.top_k.map(|_| {
+        if options.outflow_normalization != OutflowNormalization::Current {
+            warn!("--top-k ranks as with --outflow-normalization current");
+        }
+        let experts: Vec<(NodeId, f64)> = options.experts.iter().map(|&e| (e, scenario.node_info(e).and_then(|info| info.teleport_weight).unwrap_or(1.0))).collect();
+        TopKTracker::new(&pagerank.config, scenario.decay_constant, &experts, table_nodes, options.top_k_tolerance.unwrap_or(DEFAULT_TOP_K_TOLERANCE))
+    });
+    let interval = options.every_seconds.map(Duration::from_secs_f64);
+    let mut deadline = interval.map(|interval| Instant::now() + interval);
+    let mut pending = 0; // events since the last snapshot
+    let mut frame = 0;
+    let mut line_number = 0;
+
+    loop {
+        let received = match deadline {
+            Some(deadline) => lines.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let closed = received == Err(RecvTimeoutError::Disconnected);
+        if let Ok(line) = received {
+            line_number += 1;
+            match parser.parse_line(&line) {
+                Ok(Some(event)) => {
+                    let (edge, source) = event.into_parts(line_number);
+                    scenario.edges.push(scenario.oriented(edge));
+                    scenario.edge_sources.push(source);
+                    if let Some(tracker) = &mut tracker {
+                        tracker.add_edge(scenario.edges.last().unwrap());
+                    }
+                    metrics.lock().unwrap().events_ingested(1);
+                    pending += 1;
+                }
+                Ok(None) => {}
+                Err(e) if skip_invalid => warn!("{}: line {}: {}", source, line_number, e),
+                Err(e) => {
+                    eprintln!("{}: line {}: {}", source, line_number, e);
+                    process::exit(2);
+                }
+            }
+        }
+
+        let due_by_count = options.every_events.is_some_and(|count| pending >= count);
+        let due_by_time = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        if pending > 0 && (due_by_count || due_by_time || closed) {
+            scenario.num_of_nodes = nodes_referenced(&scenario.edges, &options.experts).max(table_nodes);
+            let time = scenario.edges.iter().map(|e| e.time_of_creation).fold(Time(f64::NEG_INFINITY), |a, b| if b > a { b } else { a });
+            let started = Instant::now();
+            let mut snapshot = Vec::new();
+            match (&tracker, options.top_k) {
+                // The tracker is up to date with every event, so there is nothing left to rank
+                (Some(tracker), Some(k)) => {
+                    let top = Json::object(vec![
+                        ("frame", FrameIdx(frame).number().into()),
+                        ("time", time.value().into()),
+                        ("error_bound", tracker.error_bound().into()),
+                        ("top", Json::Array(tracker.top(k).iter().map(|node| node.to_json()).collect())),
+                    ]);
+                    writeln!(snapshot, "{}", top).unwrap();
+                    metrics.lock().unwrap().frame_computed(started.elapsed(), None);
+                }
+                _ => {
+                    let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+                    let (ranks, convergence) = pagerank.rank_converged(&graph, &scenario.teleportation_targets(time));
+                    metrics.lock().unwrap().frame_computed(started.elapsed(), convergence);
+                    match &mut differ {
+                        Some(differ) => write_diff_line(&mut snapshot, FrameIdx(frame), time.value(), &differ.diff(&ranks)).unwrap(),
+                        None => {
+                            let ranks = Json::object(vec![
+                                ("frame", FrameIdx(frame).number().into()),
+                                ("time", time.value().into()),
+                                ("ranks", Json::numbers(&ranks)),
+                            ]);
+                            writeln!(snapshot, "{}", ranks).unwrap();
+                        }
+                    }
+                }
+            }
+            // A diff without changes above the threshold has no line
+            if !snapshot.is_empty() {
+                publish(&snapshot).unwrap();
+            }
+            pending = 0;
+            frame += 1;
+        }
+        if due_by_time {
+            deadline = interval.map(|interval| Instant::now() + interval);
+        }
+        if closed {
+            break;
+        }
+    }
+    info!("{} edge events, {} nodes, {} snapshots", scenario.edges.len(), scenario.num_of_nodes, frame);
+}
+
+// frame,time,pruned rows with the number of edges each frame left out for weighing too little
+fn report_pruned_edges(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput) {
+    let mut csv = String::from("frame,time,pruned\n");
+    for (frame, (time, pruned)) in output.history.times().iter().zip(&output.pruned_edges).enumerate() {
+        csv.push_str(&format!("{},{},{}\n", FrameIdx(frame).number(), time, pruned));
+    }
+    emit(sink, &format!("{}/pruned_edges.csv", scenario.name), csv.as_bytes());
+    let total: usize = output.pruned_edges.iter().sum();
+    let most = output.pruned_edges.iter().copied().max().unwrap_or(0);
+    info!("{}: pruned {} edges below weight {} over {} frames, at most {} in one frame", scenario.name, total, scenario.graph_options.prune_below, output.pruned_edges.len(), most);
+}
+
+// frame,time,absorbed,removed rows with the rank (mass) that blacklisted nodes hold in each frame:
+// absorbed by absorbing nodes, and what removed nodes still get from spread dangling rank
+fn report_absorbed_rank(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput) {
+    let mut csv = String::from("frame,time,absorbed,removed\n");
+    let mut held = Vec::new();
+    for (frame, (&time, ranks)) in output.history.times().iter().zip(output.history.frames()).enumerate() {
+        let (absorbing, removed) = scenario.blacklisted_at(time);
+        let total = |nodes: &[NodeId]| nodes.iter().fold(0.0, |total, node| total + ranks[node.index()]);
+        held.push((total(&absorbing), total(&removed)));
+        csv.push_str(&format!("{},{},{:.6},{:.6}\n", FrameIdx(frame).number(), time, held[frame].0, held[frame].1));
+    }
+    emit(sink, &format!("{}/absorbed.csv", scenario.name), csv.as_bytes());
+    if let Some((absorbed, removed)) = held.last() {
+        info!("{}: blacklisted nodes absorb {:.1}% of the rank in the last frame, removed nodes hold {:.1}%", scenario.name, 100.0 * absorbed, 100.0 * removed);
+    }
+}
+
+// `trust-flow convert-edges events.csv edges.bin`: edge events to the binary edge file format,
+// streamed, so that inputs larger than memory can be converted
+fn convert_edges(options: &Options, input: &str, output: &str) {
+    let format = if input.ends_with(".csv") { EventFormat::Csv } else { options.input_format };
+    let min_num_of_nodes = options.experts.iter().map(|e| e.index() + 1).max().unwrap_or(0);
+    let converted = if input == "-" {
+        convert_events(&mut io::stdin().lock(), event_parser(options, format), output, min_num_of_nodes)
+    } else {
+        File::open(input).map_err(|e| e.to_string()).and_then(|file| convert_events(&mut io::BufReader::new(file), event_parser(options, format), output, min_num_of_nodes))
+    };
+    match converted {
+        Ok(count) => info!("{} edges written to {}", count, output),
+        Err(e) => {
+            eprintln!("{}: {}", input, e);
+            process::exit(2);
+        }
+    }
+}
+
+// `trust-flow stats`: sizes, degrees, time span, components and anomalies of an edge event file on
+// stdout, and as JSON in the --summary file, to check the data before ranking it
+fn report_graph_stats(options: &Options) {
+    let input = options.input.as_deref().expect("stats requires --input <edge event file> (or - for stdin)");
+    let format = if input.ends_with(".csv") { EventFormat::Csv } else { options.input_format };
+    let mut text = String::new();
+    let read = if input == "-" { io::stdin().read_to_string(&mut text).map(|_| ()) } else { fs::read_to_string(input).map(|t| text = t) };
+    let parsed = read.map_err(|e| e.to_string()).and_then(|()| parse_edge_events(&text, event_parser(options, format)));
+    let (edges, _) = parsed.unwrap_or_else(|e| {
+        eprintln!("{}: {}", input, e);
+        process::exit(2);
+    });
+    let mut scenario = Scenario::new(input, nodes_referenced(&edges, &options.experts), options.experts.clone(), Vec::new());
+    scenario.reversed_edges = options.reverse_edges;
+    scenario.edges = edges.into_iter().map(|edge| scenario.oriented(edge)).collect();
+    let stats = GraphStats::new(&scenario);
+    stats.write_report(&mut io::stdout().lock(), input).unwrap();
+    if let Some(pathname) = &options.summary {
+        fs::write(pathname, format!("{}\n", stats.to_json())).unwrap_or_else(|e| {
+            eprintln!("{}: {}", pathname, e);
+            process::exit(2);
+        });
+    }
+}
+
+// `trust-flow diff`: per-node rank changes and per-frame rank correlations between two exported
+// rank timelines, over the frames at the times both have, and a heatmap of the biggest movers
+fn diff_runs(sink: &mut dyn Sink, before: &str, after: &str) {
+    let read = |pathname: &str| {
+        let text = fs::read_to_string(pathname).unwrap_or_else(|e| {
+            eprintln!("{}: {}", pathname, e);
+            process::exit(2);
+        });
+        let history = RankHistory::read_timeline_csv(&text).unwrap_or_else(|e| {
+            eprintln!("{}: {}", pathname, e);
+            process::exit(2);
+        });
+        (history, RankHistory::read_timeline_labels(&text))
+    };
+    let ((before_history, mut labels), (after_history, after_labels)) = (read(before), read(after));
+    labels.extend(after_labels.into_iter().skip(labels.len()));
+    let diffs = diff_histories(&before_history, &after_history);
+    if diffs.is_empty() {
+        eprintln!("{} and {} have no frame at the same time", before, after);
+        process::exit(2);
+    }
+    let skipped = before_history.num_of_frames().max(after_history.num_of_frames()) - diffs.len();
+    if skipped > 0 {
+        warn!("{} frames are in only one of the timelines and left out", skipped);
+    }
+    let (mut deltas, mut correlations, mut report) = (Vec::new(), Vec::new(), Vec::new());
+    write_rank_deltas_csv(&mut deltas, &diffs, &labels).unwrap();
+    write_rank_correlation_csv(&mut correlations, &diffs).unwrap();
+    let movers = largest_movers(&diffs);
+    write_diff_report(&mut report, &diffs, &movers[..movers.len().min(DEFAULT_DIFF_REPORT_NODES)], &labels, &format!("{} vs {}", before, after)).unwrap();
+    emit(sink, "diff/rank_deltas.csv", &deltas);
+    emit(sink, "diff/rank_correlation.csv", &correlations);
+    emit(sink, "diff/diff_report.svg", &report);
+
+    let last = diffs.last().unwrap();
+    println!("{} frames compared; at time {}: Spearman {:.4}, Kendall tau {:.4}, L1 change {:.4}", diffs.len(), last.time, last.spearman, last.kendall_tau, last.l1_change);
+    for &node in movers.iter().take(5) {
+        let (time, delta) = diffs.iter().map(|diff| (diff.time, diff.delta(node))).max_by(|a, b| a.1.abs().total_cmp(&b.1.abs())).unwrap();
+        println!("  node {:>3}  {:+.4}  (at time {})", labels[node.index()], delta, time);
+    }
+}
+
+// Out-of-core mode: ranks a memory-mapped edge file frame by frame and writes one JSON rank
+// snapshot per line on stdout, like pipeline mode; nothing is rendered
+fn run_edge_file(options: &Options, config: &RankConfig, pathname: &str) {
+    let mut file = EdgeFile::open(pathname).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+    file.reversed = options.reverse_edges;
+    let frame_times = options.frame_times.clone().expect("--edge-file requires --frame-times");
+    let num_of_nodes = file.num_of_nodes.max(options.experts.iter().map(|e| e.index() + 1).max().unwrap_or(0));
+    assert!(num_of_nodes == file.num_of_nodes, "experts must be nodes of the edge file ({} nodes)", file.num_of_nodes);
+    let experts = if options.experts.is_empty() { vec![NodeId(0)] } else { options.experts.clone() };
+    let scenario = Scenario::new("edge-file", num_of_nodes, experts, Vec::new());
+    let end = Time(frame_times.iter().map(|t| t.value()).fold(0.0, f64::max));
+    let ranker = OutOfCoreRanker::new(&file, config, options.decay_constant.unwrap_or(DEFAULT_DECAY_CONSTANT)).decay_table_until(end);
+    info!("{}: {} edges, {} nodes{}", pathname, file.len(), num_of_nodes, if file.sorted_by_time { ", sorted by time" } else { "" });
+
+    let mut progress = FrameProgress::start(pathname, frame_times.len(), 0);
+    let mut stdout = io::stdout().lock();
+    for (frame, &time) in frame_times.iter().enumerate() {
+        let ranks = ranker.rank(time, &scenario.teleportation_targets(time));
+        let snapshot = Json::object(vec![
+            ("frame", FrameIdx(frame).number().into()),
+            ("time", time.value().into()),
+            ("ranks", Json::numbers(&ranks)),
+        ]);
+        writeln!(stdout, "{}", snapshot).unwrap();
+        stdout.flush().unwrap();
+        progress.frame_done(FrameIdx(frame).number());
+    }
+    progress.finish();
+}
+
+// Renders the power iteration within a single time step, one frame per iteration.
+// Iteration starts from the teleportation targets so rank (mass) is seen spreading from the experts.
+fn plot_iterations(sink: &mut dyn Sink, scenario: &Scenario, rank_config: &RankConfig, time: Time) {
+    let Scenario { name, edges, .. } = scenario;
+    let node_positions = scenario.layout();
+    let teleportation_targets = scenario.teleportation_targets(time);
+    let expert_nodes = &scenario.experts_at(time);
+
+    let edge_weights = scenario.edge_weights(time);
+    let graph = scenario.frame_graph(time, &edge_weights).unwrap();
+    let mut rank_config = rank_config.clone();
+    if rank_config.initial_ranks.is_none() {
+        rank_config.initial_ranks = Some(teleportation_targets.clone());
+    }
+
+    let folder = format!("{}-iterations-t{:03}", name, time);
+    let algorithm = format!("{}, power iteration at time {}", PAGERANK_VARIANT.name, time);
+
+    let mut iteration = RankIteration::new(&graph, &teleportation_targets, &rank_config);
+    let mut frames = vec![iteration.ranks().to_vec()];
+    frames.extend(iteration.by_ref().map(|step| step.ranks));
+    for (frame, ranks) in frames.iter().enumerate() {
+        let filename = format!("{}/{}", folder, scenario.frame_file(FrameIdx(frame), time, frames.len(), "dot"));
+        let mut dot = Vec::new();
+        let scale = ColorScale::new(&scenario.style, ranks, &frames);
+        let (labels, active, pinned, clusters, upcoming, annotations) = (scenario.node_labels(), scenario.active_nodes(time), scenario.pinned_nodes(), scenario.clusters(), scenario.upcoming_edges(time), scenario.annotations_at(time));
+        let decay_description = scenario.decay_description();
+        let render = FrameRender::new(ranks, edges, &edge_weights, &labels, &node_positions)
+            .experts(expert_nodes)
+            .frame(FrameIdx(frame), frames.len())
+            .title(&algorithm, &decay_description)
+            .annotations(&annotations)
+            .active(&active)
+            .pinned(&pinned)
+            .clusters(&clusters)
+            .upcoming(&upcoming);
+        write_dot(&mut dot, &render, &scenario.style, &scale).unwrap();
+        emit(sink, &filename, &dot);
+    }
+}
+
+// SimRank of every frame's decayed graph
+// Ranks of every expert group's personalization as CSV and as DOT frames in <scenario>/expert-groups/,
+// and per group the node outside it that it ranks highest in the last frame
+fn compare_expert_groups(sink: &mut dyn Sink, scenario: &Scenario, pagerank: &PageRankVariant, frame_times: &[Time], view: GroupView) {
+    if scenario.expert_groups.is_empty() {
+        warn!("{}: no expert groups to compare (add expert-group lines to the scenario)", scenario.name);
+        return;
+    }
+    let ranks = rank_expert_groups(scenario, pagerank, frame_times);
+    let mut csv = Vec::new();
+    write_group_ranks_csv(&mut csv, scenario, &ranks).unwrap();
+    emit(sink, &format!("{}/expert_groups.csv", scenario.name), &csv);
+
+    let (names, positions) = (scenario.node_labels(), scenario.layout());
+    let mut members: Vec<NodeId> = scenario.expert_groups.iter().flat_map(|group| group.members.iter().copied()).collect();
+    members.sort();
+    members.dedup();
+    let all_ranks: Vec<Vec<f64>> = ranks.ranks.iter().flatten().cloned().collect();
+    let decay_description = scenario.decay_description();
+    for (frame, &time) in frame_times.iter().enumerate() {
+        let (weights, annotations) = (scenario.edge_weights(time), scenario.annotations_at(time));
+        let render = FrameRender::new(&[], &scenario.edges, &weights, &names, &positions)
+            .experts(&members)
+            .frame(FrameIdx(frame), frame_times.len())
+            .title("Expert group comparison", &decay_description)
+            .annotations(&annotations);
+        let mut dot = Vec::new();
+        match view {
+            GroupView::Panels => {
+                let panels: Vec<(&str, Vec<f64>)> = ranks.groups.iter().map(String::as_str).zip(ranks.ranks[frame].iter().cloned()).collect();
+                let frame_ranks: Vec<f64> = panels.iter().flat_map(|(_, ranks)| ranks.iter().copied()).collect();
+                let scale = ColorScale::new(&scenario.style, &frame_ranks, &all_ranks);
+                write_dot_small_multiples(&mut dot, &panels, &render, &scenario.style, &scale).unwrap();
+            }
+            GroupView::Pie => {
+                let shares: Vec<Vec<f64>> = (0..scenario.num_of_nodes).map(|node| ranks.shares(FrameIdx(frame), NodeId(node))).collect();
+                let mean_ranks: Vec<f64> = (0..scenario.num_of_nodes).map(|node| ranks.ranks[frame].iter().map(|group| group[node]).sum::<f64>() / ranks.groups.len() as f64).collect();
+                write_dot_group_shares(&mut dot, &ranks.groups, &shares, &FrameRender { node_ranks: &mean_ranks, ..render }, &scenario.style).unwrap();
+            }
+        }
+        emit(sink, &format!("{}/expert-groups/{}", scenario.name, scenario.frame_file(FrameIdx(frame), time, frame_times.len(), "dot")), &dot);
+    }
+
+    let last = ranks.ranks.last().unwrap();
+    for (group, group_ranks) in scenario.expert_groups.iter().zip(last) {
+        let top = (0..scenario.num_of_nodes).filter(|&node| !group.members.contains(&NodeId(node))).max_by(|&a, &b| group_ranks[a].total_cmp(&group_ranks[b]));
+        match top {
+            Some(node) => println!("{}: expert group {} ranks {} highest outside the group in the last frame ({:.4})", scenario.name, group.name, names[node], group_ranks[node]),
+            None => println!("{}: expert group {} holds every node", scenario.name, group.name),
+        }
+    }
+}
+
+// Reverse ranks of every frame, from the raw ranks and scaled like them
+fn trustingness_history(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, output: &RunOutput, options: &Options) -> RankHistory {
+    let mut history = RankHistory::new();
+    for ((&time, weights), ranks) in output.history.times().iter().zip(&output.edge_weights).zip(output.history.frames()) {
+        history.push(time, trustingness(algorithm, &scenario.frame_graph(time, weights).unwrap(), ranks, options.outflow_normalization));
+    }
+    options.scaling.scale_history(&history)
+}
+
+// Co-HITS scores of the raters and items of a bipartite scenario in every frame, with the frame's
+// scheduled damping factor as the jump probability when the scenario has a schedule
+fn report_bipartite_scores(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput) {
+    let cohits = CoHits::with_items((0..scenario.num_of_nodes).map(|node| scenario.is_item(NodeId(node))).collect());
+    let times = output.history.times();
+    let frames: Vec<Vec<f64>> = times.iter().zip(&output.edge_weights).enumerate().map(|(frame, (&time, weights))| {
+        let graph = scenario.frame_graph(time, weights).unwrap();
+        let targets = scenario.teleportation_targets(time);
+        match scenario.damping_factor(frame, times.len()).and_then(|jump| cohits.with_damping(jump)) {
+            Some(scheduled) => scheduled.rank(&graph, &targets),
+            None => cohits.rank(&graph, &targets),
+        }
+    }).collect();
+    let labels = scenario.node_labels();
+    let mut csv = Vec::new();
+    write_bipartite_csv(&mut csv, times, &frames, &scenario.items, &labels).unwrap();
+    emit(sink, &format!("{}/bipartite.csv", scenario.name), &csv);
+
+    let Some(last) = frames.last() else { return };
+    let mut items = scenario.items.clone();
+    items.sort_by(|a, b| last[b.index()].total_cmp(&last[a.index()]));
+    let top: Vec<String> = items.iter().take(5).map(|&item| format!("{} ({:.3})", labels[item.index()], last[item.index()])).collect();
+    println!("{}: top items by Co-HITS at time {}: {}", scenario.name, times[times.len() - 1], top.join(", "));
+}
+
+// Every frame's second eigenvalue and the power iterations it takes to reach the target tolerance,
+// which is the rank config's own tolerance unless --diagnostics-tolerance says otherwise
+fn report_spectral_estimates(sink: &mut dyn Sink, scenario: &Scenario, rank_config: &RankConfig, output: &RunOutput, options: &Options) {
+    let tolerance = options.diagnostics_tolerance.unwrap_or(if rank_config.tolerance > 0.0 { rank_config.tolerance } else { DEFAULT_DIAGNOSTICS_TOLERANCE });
+    let estimates: Vec<SpectralEstimate> = output.history.times().iter().zip(&output.edge_weights).map(|(&time, weights)| {
+        let config = scheduled_rank_config(scenario, options, rank_config, time);
+        SpectralEstimate::of_frame(time, &scenario.frame_graph(time, weights).unwrap(), &scenario.teleportation_targets(time), &config, tolerance)
+    }).collect();
+    let mut csv = Vec::new();
+    write_spectral_csv(&mut csv, &estimates).unwrap();
+    emit(sink, &format!("{}/spectral.csv", scenario.name), &csv);
+
+    let Some((frame, slowest)) = estimates.iter().enumerate().max_by(|a, b| a.1.convergence_factor.total_cmp(&b.1.convergence_factor)) else {
+        return;
+    };
+    let over_budget = estimates.iter().filter(|e| e.iterations_needed.is_none_or(|n| n > rank_config.num_of_iterations)).count();
+    let needed = slowest.iterations_needed.map_or("never gets there".to_string(), |n| format!("takes {} power iterations", n));
+    println!("{}: slowest convergence in frame {} (time {}): |λ2| {:.4}, error shrinks by {:.4} per step, spectral gap {:.4}; an error below {:e} {}", scenario.name, FrameIdx(frame).number(), slowest.time, slowest.second_eigenvalue, slowest.convergence_factor, slowest.spectral_gap(), tolerance, needed);
+    println!("{}: {} of {} frames need more than the budget of {} iterations", scenario.name, over_budget, estimates.len(), rank_config.num_of_iterations);
+}
+
+// Every frame's transition matrix and teleportation vector, with the frame's scheduled damping factor
+fn transition_matrices(scenario: &Scenario, rank_config: &RankConfig, output: &RunOutput, options: &Options) -> Vec<TransitionMatrix> {
+    output.history.times().iter().zip(&output.edge_weights).map(|(&time, weights)| {
+        let config = scheduled_rank_config(scenario, options, rank_config, time);
+        TransitionMatrix::of_frame(time, &scenario.frame_graph(time, weights).unwrap(), &scenario.teleportation_targets(time), &config)
+    }).collect()
+}
+
+fn similarities(scenario: &Scenario, output: &RunOutput) -> Vec<(Time, Similarity)> {
+    output
+        .history
+        .times()
+        .iter()
+        .zip(&output.edge_weights)
+        .map(|(&time, weights)| (time, SimRank::default().compute(&scenario.frame_graph(time, weights).unwrap())))
+        .collect()
+}
+
+// Who could have influenced `node` by the given time, and whom it could have influenced, along
+// time-respecting paths
+fn print_reach(scenario: &Scenario, time: Time, node: &str) {
+    let Some(node) = scenario.find_node(node) else {
+        warn!("{}: no node {} to analyze", scenario.name, node);
+        return;
+    };
+    let others = |times: Vec<Option<Time>>| -> Vec<String> {
+        times.iter().enumerate().filter(|&(other, t)| other != node.index() && t.is_some()).map(|(other, _)| scenario.node_label(NodeId(other))).collect()
+    };
+    let influencers = others(latest_departure(&scenario.edges, scenario.num_of_nodes, node, time));
+    let influenced = others(earliest_arrival(&scenario.edges, scenario.num_of_nodes, &[node], time));
+    println!("Time-respecting paths of {} by time {}:", scenario.node_label(node), time);
+    println!("  could have been influenced by {} nodes: {}", influencers.len(), influencers.join(", "));
+    println!("  could have influenced {} nodes: {}", influenced.len(), influenced.join(", "));
+}
+
+// The node's score at one time, taken from the run when the time is a frame's and computed by a
+// Ranker otherwise
+fn print_score(scenario: &Scenario, pagerank: &PageRankVariant, output: &RunOutput, options: &Options, node: &str) {
+    let Some(node) = scenario.find_node(node) else {
+        warn!("{}: no node {} to score", scenario.name, node);
+        return;
+    };
+    let time = options.score_time.unwrap_or(*output.history.times().last().unwrap());
+    let mut ranker = Ranker::new(scenario, pagerank);
+    if let Some(above_nodes) = options.score_local {
+        ranker = ranker.local(LocalScoring { config: pagerank.config.clone(), above_nodes, tolerance: SCORE_TOLERANCE, walks: DEFAULT_LOCAL_WALKS });
+    }
+    let method = match output.history.times().iter().position(|&t| t == time) {
+        Some(frame) => {
+            ranker.remember(time, output.history.frames()[frame].clone());
+            "from the run"
+        }
+        None if ranker.is_local(time) => "local estimate",
+        None => "ranked",
+    };
+    println!("Trust score of {} at time {}: {:.6} ({})", scenario.node_label(node), time, ranker.score_of(node, time), method);
+}
+
+// How much one node should trust another at one time, from the truster's perspective: the
+// trustee's rank with teleportation at the truster's endorsees
+fn print_local_trust(scenario: &Scenario, pagerank: &PageRankVariant, output: &RunOutput, options: &Options, truster: &str, trustee: &str) {
+    let Some(truster) = scenario.find_node(truster) else {
+        warn!("{}: no node {} to trust from", scenario.name, truster);
+        return;
+    };
+    let Some(trustee) = scenario.find_node(trustee) else {
+        warn!("{}: no node {} to trust", scenario.name, trustee);
+        return;
+    };
+    let time = options.score_time.unwrap_or(*output.history.times().last().unwrap());
+    let ranks = Ranker::new(scenario, pagerank).personalized_ranks(&Personalization::local(scenario, truster, time), time);
+    let trust = ranks[trustee.index()];
+    let rank = 1 + ranks.iter().filter(|&&r| r > trust).count();
+    let endorsees: Vec<String> = scenario.endorsees(truster, time).iter().map(|&(node, _)| scenario.node_label(node)).collect();
+    let endorsees = if endorsees.is_empty() { "no one".to_string() } else { endorsees.join(", ") };
+    println!("Local trust of {} in {} at time {}: {:.6} (rank {} of {}; {} endorses {})", scenario.node_label(truster), scenario.node_label(trustee), time, trust, rank, ranks.len(), scenario.node_label(truster), endorsees);
+}
+
+// `trust-flow query --from alice --to bob`: how much one node should trust another, by every
+// requested pairwise method, without rendering anything
+fn print_trust_between(scenario: &Scenario, config: &RankConfig, options: &Options, truster: &str, trustee: &str) {
+    let Some(truster) = scenario.find_node(truster) else {
+        warn!("{}: no node {} to trust from", scenario.name, truster);
+        return;
+    };
+    let Some(trustee) = scenario.find_node(trustee) else {
+        warn!("{}: no node {} to trust", scenario.name, trustee);
+        return;
+    };
+    let time = options.query_time.unwrap_or_else(|| *frame_times(scenario, options).last().expect("scenario has no frames"));
+    let methods = if options.trust_methods.is_empty() { &PairwiseTrust::ALL[..] } else { &options.trust_methods[..] };
+    println!("Trust of {} in {} at time {} ({}):", scenario.node_label(truster), scenario.node_label(trustee), time, scenario.name);
+    for &method in methods {
+        let trust = trust_between(scenario, config, truster, trustee, time, method).unwrap_or_else(|e| panic!("{}: {}", scenario.name, e));
+        println!("  {:<13} {:.6}", method.name(), trust);
+    }
+}
+
+fn centralities(scenario: &Scenario, output: &RunOutput) -> Vec<Centrality> {
+    output.history.times().iter().zip(&output.edge_weights).map(|(&time, weights)| centrality(&scenario.frame_graph(time, weights).unwrap())).collect()
+}
+
+// Communities of every frame by label propagation, with ids kept stable from frame to frame
+fn tracked_communities(scenario: &Scenario, output: &RunOutput) -> Vec<Vec<usize>> {
+    let mut tracker = CommunityTracker::new();
+    output
+        .history
+        .times()
+        .iter()
+        .zip(&output.edge_weights)
+        .map(|(&time, weights)| tracker.track(&label_propagation(&scenario.frame_graph(time, weights).unwrap())))
+        .collect()
+}
+
+// The nodes most similar to `node` in the last frame: trusted by similar sets of endorsers
+fn print_similar(scenario: &Scenario, output: &RunOutput, count: usize, node: &str) {
+    let Some(node) = scenario.find_node(node) else {
+        warn!("{}: no node {} to compare", scenario.name, node);
+        return;
+    };
+    let last = output.history.num_of_frames() - 1;
+    let time = output.history.times()[last];
+    let similarity = SimRank::default().compute(&scenario.frame_graph(time, &output.edge_weights[last]).unwrap());
+    println!("Nodes most similar to {} at time {} (SimRank):", scenario.node_label(node), time);
+    for (other, value) in similarity.top_similar(node, count) {
+        println!("  {:<10} {:.4}", scenario.node_label(other), value);
+    }
+}
+
+// Decomposes a node's rank in one frame into its sources, printed and written next to the frames.
+// With --explain-paths, the frame is rendered once more with the top contributing paths highlighted.
+fn explain_node(sink: &mut dyn Sink, scenario: &Scenario, pagerank: &PageRankVariant, output: &RunOutput, options: &Options, node: &str) {
+    let Some(node) = scenario.find_node(node) else {
+        warn!("{}: no node {} to explain", scenario.name, node);
+        return;
+    };
+    let num_of_frames = output.history.num_of_frames();
+    let frame = FrameIdx(options.explain_frame.map_or(num_of_frames, |f| f.min(num_of_frames)) - 1);
+    let time = output.history.times()[frame.index()];
+    let explainer = FrameExplainer::new(scenario, &pagerank.config, time, output.history.ranks_at(frame));
+    let paths = explainer.top_paths(node, options.explain_paths, EXPLAIN_MAX_HOPS);
+
+    let mut text = Vec::new();
+    explainer.write_explanation(&mut text, node, &paths).unwrap();
+    print!("{}", String::from_utf8_lossy(&text));
+    let basename = format!("{}/explain_{}_frame_{:03}", scenario.name, scenario.node_label(node), frame.index());
+    emit(sink, &format!("{}.txt", basename), &text);
+
+    if !paths.is_empty() {
+        let scores = displayed_history(options, &output.history);
+        let style = options.scaling.render_style(&scenario.style);
+        let ranks = scores.ranks_at(frame);
+        let highlighted: Vec<EdgeId> = paths.iter().flat_map(|p| p.edges.iter().copied()).collect();
+        let scale = ColorScale::new(&style, ranks, scores.frames());
+        let algorithm = format!("{}, paths into node {}", pagerank.name(), scenario.node_label(node));
+        let mut dot = Vec::new();
+        let (labels, layout, experts, active, pinned, clusters, upcoming, annotations) = (scenario.node_labels(), scenario.layout(), scenario.experts_at(time), scenario.active_nodes(time), scenario.pinned_nodes(), scenario.clusters(), scenario.upcoming_edges(time), scenario.annotations_at(time));
+        let decay_description = scenario.decay_description();
+        let render = FrameRender::new(ranks, &scenario.edges, &output.edge_weights[frame.index()], &labels, &layout)
+            .experts(&experts)
+            .frame(frame, num_of_frames)
+            .title(&algorithm, &decay_description)
+            .annotations(&annotations)
+            .active(&active)
+            .pinned(&pinned)
+            .clusters(&clusters)
+            .highlighted(&highlighted)
+            .upcoming(&upcoming);
+        write_dot(&mut dot, &render, &style, &scale).unwrap();
+        emit(sink, &format!("{}.dot", basename), &dot);
+    }
+}
+
+// Teaching mode: per-iteration tables of inflow, outflow, dangling and teleported rank (mass),
+// printed and written next to the frames
+fn trace_iterations(sink: &mut dyn Sink, scenario: &Scenario, rank_config: &RankConfig, time: Time) {
+    let name = &scenario.name;
+    let teleportation_targets = scenario.teleportation_targets(time);
+    let edge_weights = scenario.edge_weights(time);
+    let graph = scenario.frame_graph(time, &edge_weights).unwrap();
+
+    let mut table = Vec::new();
+    writeln!(table, "Power iteration trace at time {}", time).unwrap();
+    let targets: Vec<String> = teleportation_targets.iter().map(|t| format!("{:.4}", t)).collect();
+    writeln!(table, "damping factor {}, teleportation targets [{}]", rank_config.damping_factor, targets.join(", ")).unwrap();
+    writeln!(table).unwrap();
+    let mut iteration = RankIteration::new(&graph, &teleportation_targets, rank_config);
+    let mut previous_ranks = iteration.ranks().to_vec();
+    for step in iteration.by_ref() {
+        write_step_table(&mut table, &previous_ranks, &step).unwrap();
+        previous_ranks = step.ranks;
+    }
+
+    print!("{}", String::from_utf8_lossy(&table));
+    emit(sink, &format!("{}-trace-t{:03}.txt", name, time), &table);
+}
+
+// Rank vector after every power iteration step at a single time step, as CSV
+fn dump_iterations(sink: &mut dyn Sink, scenario: &Scenario, rank_config: &RankConfig, time: Time) {
+    let teleportation_targets = scenario.teleportation_targets(time);
+    let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+    let mut iteration = RankIteration::new(&graph, &teleportation_targets, rank_config);
+    let initial_ranks = iteration.ranks().to_vec();
+    let steps: Vec<RankStep> = iteration.by_ref().collect();
+    let mut csv = Vec::new();
+    write_iterations_csv(&mut csv, &initial_ranks, &steps, &scenario.node_labels()).unwrap();
+    emit(sink, &format!("{}-iterations-t{:03}.csv", scenario.name, time), &csv);
+    info!("{}: {} iterations at time {}, final residual {:.3e}", scenario.name, steps.len(), time, steps.last().map_or(0.0, |step| step.residual));
+}
+
+// `trust-flow sweep`: the last frame under every combination of damping factor and decay constant,
+// as a CSV matrix and one heatmap per metric
+fn sweep_scenario(sink: &mut dyn Sink, scenario: &Scenario, pagerank: &PageRankVariant, time: Time, grid: &SweepGrid, nodes: &[NodeId]) {
+    let nodes = if nodes.is_empty() {
+        let ranks = pagerank.rank(&scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap(), &scenario.teleportation_targets(time));
+        let mut order = rank_order(&ranks, &[]);
+        order.truncate(DEFAULT_SWEEP_NODES);
+        order
+    } else {
+        nodes.to_vec()
+    };
+    let cells = run_sweep(scenario, &pagerank.config, time, grid, &nodes);
+    let labels: Vec<String> = nodes.iter().map(|&node| scenario.node_label(node)).collect();
+    let mut csv = Vec::new();
+    write_sweep_csv(&mut csv, &cells, &labels).unwrap();
+    emit(sink, &format!("{}/sweep.csv", scenario.name), &csv);
+
+    let mut metrics = vec![(SweepMetric::Iterations, "iterations".to_string(), "iterations to converge".to_string())];
+    metrics.extend(labels.iter().enumerate().map(|(i, label)| (SweepMetric::Rank(i), format!("rank_{}", label), format!("rank of node {}", label))));
+    for (metric, file, description) in metrics {
+        let mut svg = Vec::new();
+        write_sweep_heatmap(&mut svg, &cells, grid, metric, &format!("{}: {} at time {}", scenario.name, description, time)).unwrap();
+        emit(sink, &format!("{}/sweep_{}.svg", scenario.name, file), &svg);
+    }
+}
+
+// `trust-flow sensitivity`: how much the final ranks depend on the choice of experts
+fn sensitivity_scenario(sink: &mut dyn Sink, scenario: &Scenario, algorithm: &dyn RankingAlgorithm, time: Time, substitutions: usize) {
+    let results = seed_sensitivity(scenario, algorithm, time, substitutions, scenario.seed);
+    let mut csv = Vec::new();
+    write_sensitivity_csv(&mut csv, &results, &scenario.node_labels()).unwrap();
+    emit(sink, &format!("{}/sensitivity.csv", scenario.name), &csv);
+    if results.is_empty() {
+        println!("{}: no experts to perturb", scenario.name);
+        return;
+    }
+    let mean = |values: Vec<f64>| values.iter().sum::<f64>() / values.len() as f64;
+    println!("Seed sensitivity of {} at time {} ({} perturbations):", scenario.name, time, results.len());
+    println!("  mean L1 rank change {:.4}, mean Spearman {:.4}", mean(results.iter().map(|r| r.l1_change).collect()), mean(results.iter().map(|r| r.spearman).collect()));
+    let worst = results.iter().max_by(|a, b| a.l1_change.total_cmp(&b.l1_change)).unwrap();
+    let description = match worst.perturbation {
+        Perturbation::LeaveOut(expert) => format!("leaving out {}", scenario.node_label(expert)),
+        Perturbation::Substitute { removed, added } => format!("replacing {} with {}", scenario.node_label(removed), scenario.node_label(added)),
+    };
+    println!("  largest change {:.4} from {}", worst.l1_change, description);
+}
+
+// Reruns a simulated scenario once per algorithm and reports how well each resists the adversarial agents
+fn tournament_scenario(sink: &mut dyn Sink, scenario: &Scenario, config: &SimulationConfig, algorithms: &[Box<dyn RankingAlgorithm>]) {
+    let results = tournament(scenario, config, algorithms);
+    let mut csv = Vec::new();
+    writeln!(csv, "algorithm,adversarial_share,top_adversarial").unwrap();
+    println!("Tournament in {} ({} ticks):", scenario.name, config.ticks);
+    for result in &results {
+        writeln!(csv, "{},{:.6},{}", result.algorithm, result.adversarial_share, result.top_adversarial).unwrap();
+        println!("  {}: adversarial rank share {:.4}, {} adversarial node(s) in the top ranks", result.algorithm, result.adversarial_share, result.top_adversarial);
+    }
+    emit(sink, &format!("{}/tournament.csv", scenario.name), &csv);
+}
+
+// Ranks of one frame per algorithm, in comparison order
+type Panels<'a> = Vec<(&'a str, Vec<f64>)>;
+
+// Runs several algorithms on the same temporal graph
+fn compare_scenario(sink: &mut dyn Sink, scenario: &Scenario, algorithms: &[Box<dyn RankingAlgorithm>], format: &CompareFormat, frame_times: &[Time], scaling: ScoreScaling) {
+    let Scenario { name, edges, .. } = scenario;
+    let node_positions = scenario.layout();
+    let style = scaling.render_style(&scenario.style);
+
+    let folder = format!("{}-compare", name);
+    let mut csv = Vec::new();
+    writeln!(csv, "frame,time,algorithm_a,algorithm_b,spearman,kendall_tau").unwrap();
+
+    // Ranked up front, so that a global color scale covers every panel of every frame
+    let frames: Vec<(Vec<f64>, Panels)> = frame_times
+        .iter()
+        .map(|&time| {
+            let edge_weights = scenario.edge_weights(time);
+            let teleportation_targets = scenario.teleportation_targets(time);
+            let graph = scenario.frame_graph(time, &edge_weights).unwrap();
+            let panels = algorithms
+                .iter()
+                .map(|a| (a.name(), scaling.scale(&a.rank(&graph, &teleportation_targets))))
+                .collect();
+            (edge_weights, panels)
+        })
+        .collect();
+    let all_ranks: Vec<Vec<f64>> = frames.iter().flat_map(|(_, panels)| panels.iter().map(|(_, ranks)| ranks.clone())).collect();
+
+    for (frame, (&time, (edge_weights, panels))) in frame_times.iter().zip(&frames).enumerate() {
+        match format {
+            CompareFormat::Csv => {
+                for (i, (name_a, ranks_a)) in panels.iter().enumerate() {
+                    for (name_b, ranks_b) in &panels[i + 1..] {
+                        writeln!(csv, "{},{},{},{},{:.6},{:.6}", FrameIdx(frame).number(), time, name_a, name_b, spearman(ranks_a, ranks_b), kendall_tau(ranks_a, ranks_b)).unwrap();
+                    }
+                }
+            }
+            CompareFormat::Dot => {
+                let filename = format!("{}/{}", folder, scenario.frame_file(FrameIdx(frame), time, frame_times.len(), "dot"));
+                let mut dot = Vec::new();
+                let frame_ranks: Vec<f64> = panels.iter().flat_map(|(_, ranks)| ranks.iter().copied()).collect();
+                let scale = ColorScale::new(&style, &frame_ranks, &all_ranks);
+                let (experts, labels, annotations, decay_description) = (scenario.experts_at(time), scenario.node_labels(), scenario.annotations_at(time), scenario.decay_description());
+                let render = FrameRender::new(&[], edges, edge_weights, &labels, &node_positions)
+                    .experts(&experts)
+                    .frame(FrameIdx(frame), frame_times.len())
+                    .title("Algorithm comparison", &decay_description)
+                    .annotations(&annotations);
+                write_dot_small_multiples(&mut dot, panels, &render, &style, &scale).unwrap();
+                emit(sink, &filename, &dot);
+            }
+        }
+    }
+    if *format == CompareFormat::Csv {
+        emit(sink, &format!("{}/comparison.csv", folder), &csv);
+    }
+}
+
+// Rank settings at a single time under the scenario's damping schedule: the damping factor of the
+// frame at that time, the configured one between frames
+fn scheduled_rank_config(scenario: &Scenario, options: &Options, rank_config: &RankConfig, time: Time) -> RankConfig {
+    let frame_times = frame_times(scenario, options);
+    let damping_factor = frame_times.iter().position(|&t| t == time).and_then(|frame| scenario.damping_factor(frame, frame_times.len()));
+    rank_config.clone().damping_factor(damping_factor.unwrap_or(rank_config.damping_factor))
+}
+
+// Explicit --frame-times win over the scenario's window, which wins over the default 0, 1, ..., 20
+fn frame_times(scenario: &Scenario, options: &Options) -> Vec<Time> {
+    options.frame_times.clone().unwrap_or_else(|| scenario.frame_times())
+}
+
+fn example_scenario() -> Scenario {
+    let edges = vec![
+        Edge { source: NodeId(0), target: NodeId(1), time_of_creation: Time(1.0), weight: 1.0 },
+        Edge { source: NodeId(1), target: NodeId(2), time_of_creation: Time(2.0), weight: 1.0 },
+        Edge { source: NodeId(1), target: NodeId(3), time_of_creation: Time(3.0), weight: 1.0 },
+        Edge { source: NodeId(3), target: NodeId(4), time_of_creation: Time(4.0), weight: 1.0 },
+        Edge { source: NodeId(3), target: NodeId(5), time_of_creation: Time(5.0), weight: 1.0 },
+        Edge { source: NodeId(5), target: NodeId(1), time_of_creation: Time(6.0), weight: 1.0 },
+    ];
+    Scenario::new("trust-flow-example", 6, vec![NodeId(0)], edges)
+}
+
+// Checks rank (mass) conservation in every frame of a finished run and writes the audit report;
+// returns the number of frames that break an invariant
+fn audit_scenario(sink: &mut dyn Sink, scenario: &Scenario, rank_config: &RankConfig, output: &RunOutput, tolerance: f64) -> usize {
+    let num_of_frames = output.history.num_of_frames();
+    let audits: Vec<FrameAudit> = output.history.times()
+        .iter()
+        .zip(output.history.frames())
+        .zip(&output.edge_weights)
+        .enumerate()
+        .map(|(frame, ((&time, ranks), weights))| {
+            let graph = scenario.frame_graph(time, weights).unwrap();
+            // Replayed with the damping factor the frame was ranked with
+            let rank_config = rank_config.clone().damping_factor(scenario.damping_factor(frame, num_of_frames).unwrap_or(rank_config.damping_factor));
+            FrameAudit::new(time, &graph, &scenario.teleportation_targets(time), &rank_config, ranks)
+        })
+        .collect();
+    let mut csv = Vec::new();
+    write_audit_csv(&mut csv, &audits, tolerance).unwrap();
+    emit(sink, &format!("{}/audit.csv", scenario.name), &csv);
+
+    println!("Mass audit for {} (tolerance {:e}):", scenario.name, tolerance);
+    let mut failed = 0;
+    for (frame, audit) in audits.iter().enumerate() {
+        let violations = audit.violations(tolerance);
+        if !violations.is_empty() {
+            failed += 1;
+            println!("  FAIL  frame {} (time {}): {}", FrameIdx(frame).number(), audit.time, violations.join("; "));
+        }
+    }
+    let worst = |value: fn(&FrameAudit) -> f64| audits.iter().map(value).fold(0.0, f64::max);
+    println!(
+        "{} of {} frames conserve rank; largest deviations: total {:.3e}, per step {:.3e}, flows {:.3e}",
+        audits.len() - failed, audits.len(), worst(|a| (a.total_mass - 1.0).abs()), worst(|a| a.max_step_error), worst(|a| a.max_flow_error)
+    );
+    failed
+}
+
+// Checks the scenario's assertions against the rendered frames; returns the number of failures
+fn check_assertions(scenario: &Scenario, frames: &[Vec<f64>], times: &[Time]) -> usize {
+    if scenario.assertions.is_empty() {
+        return 0;
+    }
+    println!("Assertions for {}:", scenario.name);
+    let mut failed = 0;
+    for assertion in &scenario.assertions {
+        let outcome = assertion.check(frames, times, scenario.trusted_threshold());
+        let status = if outcome.passed { "PASS" } else { "FAIL" };
+        println!("  {}  line {}: {} ({})", status, assertion.line, assertion.text, outcome.detail);
+        if !outcome.passed {
+            failed += 1;
+        }
+    }
+    println!("{} passed, {} failed", scenario.assertions.len() - failed, failed);
+    failed
+}
+
+// Reports and printed analyses of a finished run, besides the ones of write_reports
+fn analyze_scenario(sink: &mut dyn Sink, scenario: &Scenario, pagerank: &PageRankVariant, frame_times: &[Time], output: &RunOutput, options: &Options) {
+    if scenario.graph_options.prune_below > 0.0 {
+        report_pruned_edges(sink, scenario, output);
+    }
+    if !scenario.blacklist.is_empty() {
+        report_absorbed_rank(sink, scenario, output);
+    }
+    if options.spectral_radius {
+        let time = frame_times[frame_times.len() - 1];
+        let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+        let radius = pagerank.config.damping_factor * DenseMatrix::edge_flow(&graph, pagerank.config.outflow_normalization).spectral_radius(200);
+        println!("{}: spectral radius of the damped edge flow at time {}: {:.4} (the iteration error shrinks by about this factor per step)", scenario.name, time, radius);
+    }
+    if !scenario.items.is_empty() {
+        report_bipartite_scores(sink, scenario, output);
+    }
+    if options.diagnostics {
+        report_spectral_estimates(sink, scenario, &pagerank.config, output, options);
+    }
+    if options.influence {
+        let influence = expert_influence(scenario, &pagerank.config, frame_times);
+        let mut csv = Vec::new();
+        write_influence_csv(&mut csv, scenario, &influence).unwrap();
+        emit(sink, &format!("{}/expert_influence.csv", scenario.name), &csv);
+        let last = FrameIdx(frame_times.len() - 1);
+        let non_experts: Vec<NodeId> = (0..scenario.num_of_nodes).map(NodeId).filter(|n| !scenario.experts_at(frame_times[last.index()]).contains(n)).collect();
+        println!("{}: {:.1}% of the non-expert rank in the last frame originates from expert teleportation", scenario.name, 100.0 * influence.total_share(last, Some(&non_experts)));
+    }
+    if let Some(time) = options.dump_iterations_at {
+        dump_iterations(sink, scenario, &scheduled_rank_config(scenario, options, &pagerank.config, time), time);
+    }
+    if let Some(time) = options.trace_at {
+        trace_iterations(sink, scenario, &scheduled_rank_config(scenario, options, &pagerank.config, time), time);
+    }
+    if !options.compare.is_empty() {
+        let algorithms: Vec<Box<dyn RankingAlgorithm>> = options.compare.iter().map(|a| algorithm_by_name(a, &pagerank.config)).collect();
+        compare_scenario(sink, scenario, &algorithms, &options.compare_format, frame_times, options.scaling);
+    }
+    if let Some(view) = options.expert_groups {
+        compare_expert_groups(sink, scenario, pagerank, frame_times, view);
+    }
+    if let Some(node) = &options.similar {
+        print_similar(scenario, output, options.similar_top.unwrap_or(DEFAULT_SIMILAR_TOP), node);
+    }
+    if let Some(node) = &options.reach {
+        print_reach(scenario, options.reach_time.unwrap_or(*output.history.times().last().unwrap()), node);
+    }
+    if let Some(node) = &options.score {
+        print_score(scenario, pagerank, output, options, node);
+    }
+    if let Some((truster, trustee)) = &options.trust_between {
+        print_local_trust(scenario, pagerank, output, options, truster, trustee);
+    }
+    if let Some(node) = &options.explain {
+        explain_node(sink, scenario, pagerank, output, options, node);
+    }
+    if let Some(criterion) = &options.stabilization {
+        report_stabilization(sink, scenario, output, criterion);
+    }
+    if let Some(detector) = &options.anomalies {
+        report_anomalies(sink, scenario, output, detector);
+    }
+    if let Some(criteria) = &options.collusion {
+        report_collusion_rings(sink, scenario, output, criteria);
+    }
+}
+
+// Writes the suspected collusion rings of every frame and prints those of the last one
+fn report_collusion_rings(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput, criteria: &RingCriteria) {
+    let rings: Vec<Vec<CollusionRing>> = output
+        .history
+        .times()
+        .iter()
+        .zip(&output.edge_weights)
+        .map(|(&time, weights)| {
+            let graph = scenario.frame_graph(time, weights).unwrap();
+            find_rings(&graph, &label_propagation(&graph), criteria)
+        })
+        .collect();
+    let labels = scenario.node_labels();
+    let mut jsonl = Vec::new();
+    write_rings_jsonl(&mut jsonl, output.history.times(), &rings, output.history.frames(), &labels).unwrap();
+    emit(sink, &format!("{}/collusion_rings.jsonl", scenario.name), &jsonl);
+    let last = rings.len() - 1;
+    println!("{}: {} suspected collusion rings in the last frame", scenario.name, rings[last].len());
+    for ring in &rings[last] {
+        let nodes: Vec<&str> = ring.nodes.iter().map(|n| labels[n.index()].as_str()).collect();
+        let rank: f64 = ring.nodes.iter().map(|&n| output.history.ranks_at(FrameIdx(last))[n.index()]).sum();
+        println!("  {}: {:.0}% of pairs linked, {:.0}% of incoming weight from outside, rank {:.4}", nodes.join(", "), 100.0 * ring.density, 100.0 * ring.external_share, rank);
+    }
+}
+
+// Logs an alert for every anomalous rank change and writes them all as a JSON report
+fn report_anomalies(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput, detector: &AnomalyDetector) {
+    let anomalies = detector.detect(&output.history);
+    let labels = scenario.node_labels();
+    for a in &anomalies {
+        warn!("{}: frame {} (time {}): rank of node {} changed by {:+.4}, {:.1} standard deviations from its usual change", scenario.name, a.frame.number(), a.time, labels[a.node.index()], a.change, a.score);
+    }
+    let mut json = Vec::new();
+    write_anomalies_json(&mut json, &anomalies, &labels, detector).unwrap();
+    emit(sink, &format!("{}/anomalies.json", scenario.name), &json);
+    let mut nodes: Vec<NodeId> = anomalies.iter().map(|a| a.node).collect();
+    nodes.sort();
+    nodes.dedup();
+    println!("{}: {} anomalous rank changes of {} nodes (more than {} standard deviations)", scenario.name, anomalies.len(), nodes.len(), detector.threshold);
+}
+
+// Prints the frame from which the rank ordering holds and writes the Kendall tau of every frame
+fn report_stabilization(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput, criterion: &StabilizationCriterion) {
+    let taus = consecutive_taus(&output.history);
+    let mut csv = Vec::new();
+    write_stabilization_csv(&mut csv, &output.history, &taus).unwrap();
+    emit(sink, &format!("{}/stabilization.csv", scenario.name), &csv);
+    match stabilization_frame(&taus, criterion) {
+        Some(frame) => println!("{}: rank ordering stable from frame {} (time {}): Kendall tau of at least {} over the next {} frames", scenario.name, frame.number(), output.history.times()[frame.index()], criterion.threshold, criterion.frames),
+        None => println!("{}: rank ordering never stable for {} frames in a row (Kendall tau of at least {})", scenario.name, criterion.frames, criterion.threshold),
+    }
+}
+
+// Rank chart, rank diffs and the requested exports of a finished run, as far as the command
+// includes rendering, analysis and exports
+fn write_reports(sink: &mut dyn Sink, scenario: &Scenario, pagerank: &PageRankVariant, output: &RunOutput, options: &Options) {
+    let raw = output;
+    // Frame hashes stay those of the raw ranks
+    let output = &RunOutput { history: displayed_history(options, &output.history), ..output.clone() };
+    if options.command.renders() {
+        write_rank_chart_report(sink, scenario, output, options);
+    }
+    if options.command.analyzes() {
+        write_analysis_reports(sink, scenario, output, options);
+    }
+    if options.command.exports() {
+        write_exports(sink, scenario, pagerank, raw, output, options);
+    }
+    // Group trust adds up raw ranks, which scaled scores may not
+    if let Some(rollup) = options.rollup.filter(|_| options.command.analyzes()) {
+        write_group_rollup(sink, scenario, pagerank, raw, &rollup, options);
+    }
+}
+
+// Trust of every group per frame and, with --rollup-graph, one DOT summary graph of the groups per frame
+fn write_group_rollup(sink: &mut dyn Sink, scenario: &Scenario, pagerank: &PageRankVariant, output: &RunOutput, rollup: &Rollup, options: &Options) {
+    let groups = group_trust(scenario, output.history.frames(), rollup);
+    if groups.is_empty() {
+        warn!("{}: no node has the metadata to roll up by", scenario.name);
+        return;
+    }
+    let times = output.history.times();
+    let mut csv = Vec::new();
+    write_group_trust_csv(&mut csv, times, &groups).unwrap();
+    emit(sink, &format!("{}/groups.csv", scenario.name), &csv);
+
+    if !options.rollup_graph {
+        return;
+    }
+    let trust: Vec<Vec<f64>> = (0..times.len()).map(|frame| groups.iter().map(|group| group.trust[frame]).collect()).collect();
+    let names: Vec<String> = groups.iter().map(|group| format!("{} ({})", group.label, group.members.len())).collect();
+    let positions = group_layout(groups.len());
+    let title = format!("{}, {}", pagerank.name(), rollup.description());
+    for (frame, &time) in times.iter().enumerate() {
+        let edges = group_edges(&groups, &scenario.edges, &output.edge_weights[frame], time);
+        let weights: Vec<f64> = edges.iter().map(|e| e.weight).collect();
+        let experts_at = scenario.experts_at(time);
+        let experts: Vec<NodeId> = (0..groups.len()).filter(|&g| groups[g].members.iter().any(|m| experts_at.contains(m))).map(NodeId).collect();
+        let scale = ColorScale::new(&scenario.style, &trust[frame], &trust);
+        let mut dot = Vec::new();
+        let decay_description = scenario.decay_description();
+        let render = FrameRender::new(&trust[frame], &edges, &weights, &names, &positions).experts(&experts).frame(FrameIdx(frame), times.len()).title(&title, &decay_description);
+        write_dot(&mut dot, &render, &scenario.style, &scale).unwrap();
+        emit(sink, &format!("{}/groups/{}", scenario.name, scenario.frame_file(FrameIdx(frame), time, times.len(), "dot")), &dot);
+    }
+}
+
+fn write_rank_chart_report(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput, options: &Options) {
+    let chart_nodes = if options.chart_nodes.is_empty() {
+        let last = output.history.num_of_frames().saturating_sub(1);
+        top_nodes(&output.history, options.chart_top.unwrap_or(5), &tie_keys(scenario, output, options.tie_break, last))
+    } else {
+        options.chart_nodes.clone()
+    };
+    let mut chart = Vec::new();
+    write_rank_chart(&mut chart, &output.history, &chart_nodes, &format!("{}: rank over time", scenario.name)).unwrap();
+    emit(sink, &format!("{}/rank_chart.svg", scenario.name), &chart);
+}
+
+// Keys that break ties between the ranks of a frame
+fn tie_keys(scenario: &Scenario, output: &RunOutput, tie_break: TieBreak, frame: usize) -> Vec<f64> {
+    let (frames, weights) = (output.history.frames(), output.edge_weights.get(frame).map_or(&[][..], Vec::as_slice));
+    tie_break.keys(scenario.num_of_nodes, &scenario.edges, weights, frame.checked_sub(1).map(|previous| frames[previous].as_slice()))
+}
+
+fn write_analysis_reports(sink: &mut dyn Sink, scenario: &Scenario, output: &RunOutput, options: &Options) {
+    if let Some(threshold) = options.diff_threshold {
+        let mut diffs = Vec::new();
+        write_diff_stream(&mut diffs, &output.history, threshold).unwrap();
+        emit(sink, &format!("{}/rank_diffs.jsonl", scenario.name), &diffs);
+    }
+
+    if let Some(thresholds) = &options.tiers {
+        let mut tiers = Vec::new();
+        write_tiers(&mut tiers, &output.history, thresholds, &scenario.node_labels()).unwrap();
+        emit(sink, &format!("{}/tiers.jsonl", scenario.name), &tiers);
+    }
+
+    if options.communities {
+        let mut communities = Vec::new();
+        write_communities_csv(&mut communities, output.history.times(), &tracked_communities(scenario, output), &scenario.node_labels()).unwrap();
+        emit(sink, &format!("{}/communities.csv", scenario.name), &communities);
+    }
+}
+
+// `raw` has the ranks before scaling, which reverse ranking starts from
+fn write_exports(sink: &mut dyn Sink, scenario: &Scenario, pagerank: &PageRankVariant, raw: &RunOutput, output: &RunOutput, options: &Options) {
+    let mut exporters = ExporterRegistry::new();
+    exporters.register(Box::new(TikzExporter { frames: options.tikz_frames.clone() }));
+    for format in &options.exports {
+        // Matrix Market holds one matrix per file, so every frame gets two
+        if format == "mtx" {
+            for (frame, matrix) in transition_matrices(scenario, &pagerank.config, output, options).iter().enumerate() {
+                let (mut transition, mut teleport) = (Vec::new(), Vec::new());
+                write_transition_mtx(&mut transition, matrix).unwrap();
+                write_teleport_mtx(&mut teleport, matrix).unwrap();
+                emit(sink, &format!("{}/transition_{:03}.mtx", scenario.name, frame), &transition);
+                emit(sink, &format!("{}/teleport_{:03}.mtx", scenario.name, frame), &teleport);
+            }
+            continue;
+        }
+        if format == "transition" {
+            let matrices = transition_matrices(scenario, &pagerank.config, output, options);
+            let (mut transition, mut teleport) = (Vec::new(), Vec::new());
+            write_transition_csv(&mut transition, &matrices).unwrap();
+            write_teleport_csv(&mut teleport, &matrices).unwrap();
+            emit(sink, &format!("{}/transition.csv", scenario.name), &transition);
+            emit(sink, &format!("{}/teleport.csv", scenario.name), &teleport);
+            continue;
+        }
+        if format == "parquet" {
+            export_parquet(sink, scenario, output);
+            continue;
+        }
+        if let Some(exporter) = exporters.get(format) {
+            let run = ExportedRun { scenario, output, algorithm: pagerank.name() };
+            exporter.export(sink, &run).unwrap_or_else(|e| panic!("{}: {} export: {}", scenario.name, format, e));
+            continue;
+        }
+        let mut exported = Vec::new();
+        match format.as_str() {
+            "html" => write_html_viewer(&mut exported, scenario, output, &scenario.layout()).unwrap(),
+            "graphml" => write_graphml(&mut exported, scenario, output).unwrap(),
+            "gexf" => write_gexf(&mut exported, scenario, output).unwrap(),
+            "provenance" => write_edge_drill_down(&mut exported, scenario, output).unwrap(),
+            "cypher" => write_cypher(&mut exported, scenario, output).unwrap(),
+            "simrank" => write_similarity_csv(&mut exported, &similarities(scenario, output), &scenario.node_labels(), options.similar_top.unwrap_or(DEFAULT_SIMILAR_TOP)).unwrap(),
+            "metrics" => write_metrics_csv(&mut exported, &output.history, &centralities(scenario, output), &scenario.node_labels()).unwrap(),
+            "trustingness" => write_trustingness_csv(&mut exported, &output.history, &trustingness_history(scenario, pagerank, raw, options), &scenario.node_labels()).unwrap(),
+            "leaderboard" => {
+                let orders: Vec<Vec<NodeId>> = output.history.frames().iter().enumerate().map(|(frame, ranks)| rank_order(ranks, &tie_keys(scenario, output, options.tie_break, frame))).collect();
+                write_leaderboard_csv(&mut exported, &output.history, &orders, &scenario.node_labels()).unwrap()
+            }
+            // Flux of the raw ranks, as the custom variant moves them
+            "flux" => write_flux_csv(&mut exported, scenario, raw, &flux_history(scenario, pagerank, raw).unwrap()).unwrap(),
+            _ => unreachable!(),
+        }
+        let filename = match format.as_str() {
+            "provenance" => format!("{}/edge_provenance.csv", scenario.name),
+            "simrank" => format!("{}/simrank.csv", scenario.name),
+            "metrics" => format!("{}/metrics.csv", scenario.name),
+            "trustingness" => format!("{}/trustingness.csv", scenario.name),
+            "leaderboard" => format!("{}/leaderboard.csv", scenario.name),
+            "flux" => format!("{}/flux.csv", scenario.name),
+            _ => format!("{}.{}", scenario.name, format),
+        };
+        emit(sink, &filename, &exported);
+    }
+}
+
+// Watch mode: follows a growing edge event file and re-renders every time rows are appended.
+// Each refresh prints the rank changes of the newest snapshot as a JSON line on stdout.
+fn run_watch(sink: &mut dyn Sink, options: &Options, algorithm: &PageRankVariant, pathname: &str) {
+    if is_scenario_file(pathname) {
+        run_watch_scenario(sink, options, algorithm, pathname);
+        return;
+    }
+    let format = if pathname.ends_with(".csv") { EventFormat::Csv } else { options.input_format };
+    let name = pathname.rsplit('/').next().and_then(|f| f.split('.').next()).unwrap_or("watch");
+    let interval = Duration::from_secs_f64(options.watch_interval.unwrap_or(2.0));
+    let mut tail = EventTail::new(pathname, event_parser(options, format));
+    let mut differ = RankDiffer::new(options.diff_threshold.unwrap_or(0.0));
+    let mut edges = Vec::new();
+    let mut edge_sources = Vec::new();
+    let rows = node_rows(options);
+    let table_nodes = rows.iter().map(|r| r.node.index() + 1).max().unwrap_or(0);
+
+    info!("watching {} (Ctrl-C to stop)", pathname);
+    loop {
+        let batch = tail.poll().unwrap_or_else(|e| {
+            eprintln!("{}: {}", pathname, e);
+            process::exit(2);
+        });
+        if batch.restarted {
+            info!("{} was rewritten, reading it again", pathname);
+            edges.clear();
+            edge_sources.clear();
+        }
+        if !batch.edges.is_empty() {
+            edges.extend(batch.edges);
+            edge_sources.extend(batch.sources);
+            let num_of_nodes = nodes_referenced(&edges, &options.experts).max(table_nodes);
+            let mut scenario = with_node_table(Scenario::new(name, num_of_nodes, options.experts.clone(), edges.clone()), &rows);
+            scenario.edge_sources = edge_sources.clone();
+            scenario.seed = options.seed.unwrap_or(scenario.seed);
+            scenario.time_respecting |= options.time_respecting;
+            scenario.graph_options.undirected |= options.symmetrize;
+            if let Some(epsilon) = options.prune_below {
+                scenario.prune_below(epsilon);
+            }
+            scenario.warm_start = options.warm_start.or(scenario.warm_start);
+            scenario.decay_kernel.fast_exp |= options.fast_exp;
+            scenario.decay_kernel.threads = options.decay_threads.unwrap_or(scenario.decay_kernel.threads);
+            if options.reverse_edges {
+                scenario.reverse_edges();
+                scenario.validate().unwrap_or_else(|e| panic!("--reverse-edges: {}: {}", scenario.name, e));
+            }
+            scenario.initial_ranks = options.initial_ranks.clone().or(scenario.initial_ranks.take());
+            if let Some(node) = &options.local_trust {
+                scenario.local_trust = Some(scenario.find_node(node).unwrap_or_else(|| panic!("--local-trust: {} has no node {}", scenario.name, node)));
+            }
+            scenario.authority = options.authority.or(scenario.authority);
+            scenario.damping = options.damping.clone().or(scenario.damping.take());
+            scenario.style.cluster = options.cluster.or(scenario.style.cluster);
+            scenario.style.ghost_edges |= options.ghost_edges;
+            scenario.style.edge_sampling = options.edge_sampling.or(scenario.style.edge_sampling);
+            scenario.style.auto_layout |= options.auto_layout;
+            scenario.style.rank_deltas |= options.rank_deltas;
+            scenario.style.frame_stats |= options.frame_stats;
+            scenario.style.alarm_banner |= options.alarm_banner;
+            scenario.alarms.extend(options.alarms.iter().copied());
+            if let Some(frame_names) = &options.frame_names {
+                scenario.frame_names = frame_names.clone();
+            }
+            scenario.style.edge_flux |= options.edge_flux;
+            let frame_times = options.frame_times.clone().unwrap_or_else(|| event_times(&edges));
+            refresh_watched(sink, options, algorithm, &mut differ, &scenario, &frame_times);
+            info!("{} edge events, {} nodes, {} snapshots", edges.len(), num_of_nodes, frame_times.len());
+        }
+        thread::sleep(interval);
+    }
+}
+
+// Watching a scenario file (or GraphML / GEXF graph) re-runs it whenever it is modified.
+// A version that does not parse is reported and skipped until the next change.
+fn run_watch_scenario(sink: &mut dyn Sink, options: &Options, algorithm: &PageRankVariant, pathname: &str) {
+    let interval = Duration::from_secs_f64(options.watch_interval.unwrap_or(2.0));
+    let rows = node_rows(options);
+    let mut differ = RankDiffer::new(options.diff_threshold.unwrap_or(0.0));
+    let mut last_modified = None;
+
+    info!("watching {} (Ctrl-C to stop)", pathname);
+    loop {
+        let modified = fs::metadata(pathname).and_then(|m| m.modified()).unwrap_or_else(|e| {
+            eprintln!("{}: {}", pathname, e);
+            process::exit(2);
+        });
+        if last_modified != Some(modified) {
+            last_modified = Some(modified);
+            match Scenario::from_file(pathname) {
+                Ok(scenario) => {
+                    let mut scenario = with_node_table(scenario, &rows);
+                    scenario.seed = options.seed.unwrap_or(scenario.seed);
+                    scenario.time_respecting |= options.time_respecting;
+                    scenario.graph_options.undirected |= options.symmetrize;
+                    if let Some(epsilon) = options.prune_below {
+                        scenario.prune_below(epsilon);
+                    }
+                    scenario.warm_start = options.warm_start.or(scenario.warm_start);
+                    scenario.decay_kernel.fast_exp |= options.fast_exp;
+                    scenario.decay_kernel.threads = options.decay_threads.unwrap_or(scenario.decay_kernel.threads);
+                    if options.reverse_edges {
+                        scenario.reverse_edges();
+                        scenario.validate().unwrap_or_else(|e| panic!("--reverse-edges: {}: {}", scenario.name, e));
+                    }
+                    scenario.initial_ranks = options.initial_ranks.clone().or(scenario.initial_ranks.take());
+                    if let Some(node) = &options.local_trust {
+                        scenario.local_trust = Some(scenario.find_node(node).unwrap_or_else(|| panic!("--local-trust: {} has no node {}", scenario.name, node)));
+                    }
+                    scenario.authority = options.authority.or(scenario.authority);
+                    scenario.damping = options.damping.clone().or(scenario.damping.take());
+                    scenario.style.cluster = options.cluster.or(scenario.style.cluster);
+                    scenario.style.ghost_edges |= options.ghost_edges;
+                    scenario.style.edge_sampling = options.edge_sampling.or(scenario.style.edge_sampling);
+                    scenario.style.auto_layout |= options.auto_layout;
+                    scenario.style.rank_deltas |= options.rank_deltas;
+                    scenario.style.frame_stats |= options.frame_stats;
+                    scenario.style.alarm_banner |= options.alarm_banner;
+                    scenario.alarms.extend(options.alarms.iter().copied());
+                    if let Some(frame_names) = &options.frame_names {
+                        scenario.frame_names = frame_names.clone();
+                    }
+                    scenario.style.edge_flux |= options.edge_flux;
+                    let frame_times = frame_times(&scenario, options);
+                    refresh_watched(sink, options, algorithm, &mut differ, &scenario, &frame_times);
+                    info!("{}: {} edges, {} frames", scenario.name, scenario.edges.len(), frame_times.len());
+                }
+                Err(e) => warn!("{}: {}", pathname, e),
+            }
+        }
+        thread::sleep(interval);
+    }
+}
+
+fn is_scenario_file(pathname: &str) -> bool {
+    [".scenario", ".graphml", ".gexf", ".gitlog", ".reviews.json", ".sigs", ".sigs.csv"].iter().any(|extension| pathname.ends_with(extension))
+}
+
+// Re-renders a watched scenario and prints the rank changes of its newest frame
+fn refresh_watched(sink: &mut dyn Sink, options: &Options, algorithm: &PageRankVariant, differ: &mut RankDiffer, scenario: &Scenario, frame_times: &[Time]) {
+    let output = plot_scenario(sink, scenario, algorithm, frame_times, options);
+    write_reports(sink, scenario, algorithm, &output, options);
+    sink.flush().unwrap();
+
+    let newest = output.history.num_of_frames() - 1;
+    let mut stdout = io::stdout().lock();
+    write_diff_line(&mut stdout, FrameIdx(newest), output.history.times()[newest].value(), &differ.diff(output.history.ranks_at(FrameIdx(newest)))).unwrap();
+    stdout.flush().unwrap();
+}
+
+fn main() {
+    let started = Instant::now();
+    let options = parse_options(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("trust-flow: {}\n\n{}", e, USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        print!("{}\n{}", USAGE, OPTIONS_HELP);
+        return;
+    }
+    init_logging(options.verbosity);
+    let pagerank = PageRankVariant {
+        config: RankConfig::new()
+            .iterations(10)
+            .damping_factor(0.5)
+            .backend(options.backend)
+            .outflow_normalization(options.outflow_normalization)
+            .solver(options.solver)
+            .relaxation(options.relaxation.unwrap_or(1.0))
+            .precision(options.precision)
+            .walks_per_node(options.walks_per_node.unwrap_or(RankConfig::default().walks_per_node))
+            .walk_seed(options.seed.unwrap_or(DEFAULT_SEED))
+            .max_hops(options.max_hops.unwrap_or(RankConfig::default().max_hops))
+            .node_capacity(options.node_capacity.unwrap_or(RankConfig::default().node_capacity))
+            .max_source_share(options.max_source_share.unwrap_or(RankConfig::default().max_source_share))
+            .rank_floor(options.rank_floor.unwrap_or(0.0)),
+    };
+
+    if options.list_algorithms {
+        list_algorithms(&pagerank.config);
+        return;
+    }
+    if options.query && options.query_pair == (None, None) {
+        query_runs(&options);
+        return;
+    }
+    if options.repl {
+        let mut session = Session::new("session");
+        run_repl(&mut io::stdin().lock(), &mut io::stdout(), &mut session, &pagerank).unwrap();
+        return;
+    }
+    if let Some((input, output)) = &options.convert_edges {
+        convert_edges(&options, input, output);
+        return;
+    }
+    if options.stats {
+        report_graph_stats(&options);
+        return;
+    }
+    if let Some(pathname) = &options.edge_file {
+        run_edge_file(&options, &pagerank.config, pathname);
+        return;
+    }
+    if let Some(address) = &options.serve {
+        let listener = TcpListener::bind(address).unwrap_or_else(|e| {
+            eprintln!("{}: {}", address, e);
+            process::exit(2);
+        });
+        info!("serving the trust-flow API on http://{}", address);
+        Server::new(pagerank.config.clone()).serve(&listener).unwrap();
+        return;
+    }
+    if options.bench {
+        let seed = options.seed.unwrap_or(DEFAULT_SEED);
+        let mut stdout = io::stdout().lock();
+        for size in BENCH_SIZES {
+            write_bench_table(&mut stdout, &bench_suite(size, seed)).unwrap();
+            writeln!(stdout).unwrap();
+            stdout.flush().unwrap();
+        }
+        return;
+    }
+    if let Some(address) = &options.nats {
+        run_nats(&options, &pagerank, address);
+        return;
+    }
+    if let Some(address) = &options.grpc {
+        run_grpc(&options, &pagerank, address);
+        return;
+    }
+    if options.pipe && (options.every_events.is_some() || options.every_seconds.is_some()) {
+        run_stream(&options, &pagerank);
+        return;
+    }
+    assert!(options.top_k.is_none(), "--top-k needs a streaming mode: --pipe with --every-events or --every-seconds, or --nats");
+    if options.pipe {
+        run_pipeline(&options, &pagerank);
+        return;
+    }
+
+    let output = options.output.as_deref().unwrap_or(OUTPUT_FOLDER);
+    let root = match &options.run_id {
+        Some(id) => format!("{}/{}", output.trim_end_matches('/'), id),
+        None => output.to_string(),
+    };
+    let is_bucket = root.starts_with("s3://") || root.starts_with("gs://");
+    let mut manifest = ManifestSink::new(if is_bucket {
+        bucket_sink(&root, &options)
+    } else {
+        Box::new(FileSystemSink::new(&root))
+    });
+    let local_root = (!is_bucket).then(|| Path::new(&root));
+    let sink: &mut dyn Sink = &mut manifest;
+
+    if let Some(pathname) = &options.watch {
+        run_watch(sink, &options, &pagerank, pathname);
+        return;
+    }
+    if let Some((before, after)) = &options.diff_runs {
+        diff_runs(sink, before, after);
+        finish_run(&mut manifest, local_root, &[], &options);
+        return;
+    }
+
+    let rows = node_rows(&options);
+    let scenarios: Vec<Scenario> = if options.scenario_files.is_empty() && options.git_repositories.is_empty() {
+        vec![example_scenario()]
+    } else {
+        let repositories = options.git_repositories.iter().map(|path| (path, read_git_repository(path)));
+        options.scenario_files.iter().map(|pathname| (pathname, Scenario::from_file(pathname))).chain(repositories).map(|(pathname, scenario)| {
+            scenario.unwrap_or_else(|e| {
+                eprintln!("{}: {}", pathname, e);
+                process::exit(2);
+            })
+        }).collect()
+    };
+    if options.clean {
+        let root = local_root.expect("--clean only works with local output folders");
+        clean_output(root, &scenarios);
+    }
+    // With checkpoints, a resumed scenario takes its edges from the checkpoint instead of simulating them again
+    let algorithm_info = options.algorithm.as_deref().map_or(&PAGERANK_VARIANT, |id| AlgorithmRegistry::get(id).unwrap());
+    let settings = format!("{}|{:?}", algorithm_info.name, pagerank.config);
+    let mut checkpoints: Vec<Option<Checkpoint>> = Vec::new();
+    let mut prepared = Vec::new();
+    for scenario in scenarios {
+        let mut scenario = with_node_table(scenario, &rows);
+        scenario.seed = options.seed.unwrap_or(scenario.seed);
+        scenario.time_respecting |= options.time_respecting;
+        scenario.graph_options.undirected |= options.symmetrize;
+        if let Some(epsilon) = options.prune_below {
+            scenario.prune_below(epsilon);
+        }
+        scenario.warm_start = options.warm_start.or(scenario.warm_start);
+        scenario.decay_kernel.fast_exp |= options.fast_exp;
+        scenario.decay_kernel.threads = options.decay_threads.unwrap_or(scenario.decay_kernel.threads);
+        if options.reverse_edges {
+            scenario.reverse_edges();
+            scenario.validate().unwrap_or_else(|e| panic!("--reverse-edges: {}: {}", scenario.name, e));
+        }
+        if scenario.looks_reversed() {
+            warn!("{}: the experts endorse nobody but are endorsed; if the edges point from endorsee to endorser, pass --reverse-edges or add 'edge-direction endorsee-to-endorser' to the scenario", scenario.name);
+        }
+        scenario.initial_ranks = options.initial_ranks.clone().or(scenario.initial_ranks.take());
+        if let Some(node) = &options.local_trust {
+            scenario.local_trust = Some(scenario.find_node(node).unwrap_or_else(|| panic!("--local-trust: {} has no node {}", scenario.name, node)));
+        }
+        scenario.authority = options.authority.or(scenario.authority);
+        scenario.damping = options.damping.clone().or(scenario.damping.take());
+        scenario.style.cluster = options.cluster.or(scenario.style.cluster);
+        scenario.style.ghost_edges |= options.ghost_edges;
+        scenario.style.edge_sampling = options.edge_sampling.or(scenario.style.edge_sampling);
+        scenario.style.auto_layout |= options.auto_layout;
+        scenario.style.rank_deltas |= options.rank_deltas;
+        scenario.style.frame_stats |= options.frame_stats;
+        scenario.style.alarm_banner |= options.alarm_banner;
+        scenario.alarms.extend(options.alarms.iter().copied());
+        if let Some(frame_names) = &options.frame_names {
+            scenario.frame_names = frame_names.clone();
+        }
+        scenario.style.edge_flux |= options.edge_flux;
+        if let Some(coarsening) = options.preview {
+            let preview = coarsen(&scenario, coarsening);
+            info!("{}: previewing {} nodes and {} edges instead of {} and {}", preview.name, preview.num_of_nodes, preview.edges.len(), scenario.num_of_nodes, scenario.edges.len());
+            scenario = preview;
+        }
+        // Stored and cached results take the place of a checkpoint; `run` and --cache keep one without a folder for its fingerprint
+        let mut checkpoint = if options.checkpoint_folder.is_some() || options.cache_folder.is_some() || options.command != Command::All {
+            let frame_times = frame_times(&scenario, &options);
+            let fingerprint = run_fingerprint(&scenario, &settings, &frame_times);
+            if options.command.reads_results() {
+                Some(stored_results(&options, local_root, &scenario.name, fingerprint, &frame_times))
+            } else if let Some(cached) = options.cache_folder.as_ref().and_then(|folder| cached_results(folder, &scenario.name, fingerprint, &frame_times)) {
+                Some(cached)
+            } else {
+                let saved = match &options.checkpoint_folder {
+                    Some(folder) if options.resume => resume_checkpoint(folder, &scenario.name, fingerprint),
+                    _ => None,
+                };
+                Some(saved.unwrap_or(Checkpoint { fingerprint, frame_times, edges: Vec::new(), output: RunOutput::new() }))
+            }
+        } else {
+            None
+        };
+        match &checkpoint {
+            Some(saved) if !saved.edges.is_empty() || options.command.reads_results() => scenario.edges = saved.edges.clone(),
+            _ => {
+                if let Some(simulation) = &scenario.simulation {
+                    if !options.tournament.is_empty() {
+                        let algorithms: Vec<Box<dyn RankingAlgorithm>> = options.tournament.iter().map(|a| algorithm_by_name(a, &pagerank.config)).collect();
+                        tournament_scenario(sink, &scenario, simulation, &algorithms);
+                    }
+                    scenario.edges = simulate(&scenario, simulation, &pagerank);
+                    info!("{}: simulated {} ticks, {} edges", scenario.name, simulation.ticks, scenario.edges.len());
+                }
+                if let Some(checkpoint) = &mut checkpoint {
+                    checkpoint.edges = scenario.edges.clone();
+                }
+            }
+        }
+        prepared.push(scenario);
+        checkpoints.push(checkpoint);
+    }
+    let scenarios = prepared;
+
+    if let (true, Some(truster), Some(trustee)) = (options.query, &options.query_pair.0, &options.query_pair.1) {
+        for scenario in &scenarios {
+            print_trust_between(scenario, &pagerank.config, &options, truster, trustee);
+        }
+        return;
+    }
+    if let Some(grid) = &options.sweep {
+        for scenario in &scenarios {
+            let time = *frame_times(scenario, &options).last().expect("scenario has no frames");
+            sweep_scenario(sink, scenario, &pagerank, time, grid, &options.sweep_nodes);
+        }
+        finish_run(&mut manifest, local_root, &scenarios, &options);
+        return;
+    }
+    if let Some(substitutions) = options.sensitivity {
+        for scenario in &scenarios {
+            let time = *frame_times(scenario, &options).last().expect("scenario has no frames");
+            sensitivity_scenario(sink, scenario, &pagerank, time, substitutions);
+        }
+        finish_run(&mut manifest, local_root, &scenarios, &options);
+        return;
+    }
+
+    let mut failed_assertions = 0;
+    let mut summaries = Vec::new();
+    for (scenario, checkpoint) in scenarios.iter().zip(checkpoints) {
+        let scenario_started = Instant::now();
+        let frame_times = frame_times(scenario, &options);
+        let fingerprint = checkpoint.as_ref().map(|c| c.fingerprint);
+        let chosen = options.algorithm.as_deref().map(|id| scenario_algorithm(scenario, id, &pagerank.config));
+        let algorithm: &dyn RankingAlgorithm = chosen.as_deref().unwrap_or(&pagerank);
+        let complete = checkpoint.as_ref().is_some_and(|c| options.command.reads_results() || c.output.history.num_of_frames() == frame_times.len());
+        let output = match (checkpoint, &options.checkpoint_folder) {
+            (Some(stored), _) if complete => stored.output,
+            (Some(checkpoint), Some(folder)) => rank_scenario_with_checkpoint(scenario, algorithm, &frame_times, &options, checkpoint, folder),
+            _ => rank_scenario(scenario, algorithm, &frame_times, &options),
+        };
+        if let (Some(folder), false) = (&options.cache_folder, complete) {
+            cache_results(folder, scenario, fingerprint.unwrap(), &frame_times, &output);
+        }
+        if options.command == Command::Run {
+            save_results(sink, scenario, fingerprint.unwrap(), &frame_times, &output);
+        }
+        if !options.command.reads_results() {
+            record_run(&options, scenario, algorithm_info.id, &settings, &output);
+        }
+        // Stages that only render or export leave the warnings to the stage that ranked or analyzes
+        if !options.command.reads_results() || options.command.analyzes() {
+            for alarm in concentration_alarms(scenario, &output) {
+                warn!("{}: frame {} (time {}): {}", scenario.name, alarm.frame.number(), alarm.time, alarm.message());
+            }
+        }
+        let output = if options.command.renders() { render_frames(sink, scenario, algorithm, &frame_times, output, &options) } else { output };
+        if options.print_hash {
+            println!("{} result hash: {:016x}", scenario.name, run_hash(&output.frame_hashes));
+        }
+        if let Some(time) = options.animate_iterations_at.filter(|_| options.command.renders()) {
+            plot_iterations(sink, scenario, &scheduled_rank_config(scenario, &options, &pagerank.config, time), time);
+        }
+        if options.command.analyzes() {
+            analyze_scenario(sink, scenario, &pagerank, &frame_times, &output, &options);
+        }
+        let mut run_profile = FrameProfile::default();
+        run_profile.time(Phase::Export, || write_reports(sink, scenario, &pagerank, &output, &options));
+        if options.profile {
+            write_profile(sink, scenario, &output, run_profile);
+        }
+        if options.command.analyzes() {
+            println!("Movers and shakers in {}:", scenario.name);
+            output.history.write_movers_summary(&mut std::io::stdout(), 5, 5, &scenario.node_labels()).unwrap();
+            failed_assertions += check_assertions(scenario, output.history.frames(), output.history.times());
+            if options.audit {
+                failed_assertions += audit_scenario(sink, scenario, &pagerank.config, &output, options.audit_tolerance.unwrap_or(DEFAULT_AUDIT_TOLERANCE));
+            }
+        }
+        if options.summary.is_some() {
+            summaries.push(scenario_summary(scenario, &output, scenario_started.elapsed()));
+        }
+    }
+
+    if let Some(pool) = options.graphviz.as_ref().filter(|_| options.command.renders()) {
+        render_with_graphviz(&mut manifest, local_root.expect("--graphviz only works with local output folders"), pool);
+    }
+    finish_run(&mut manifest, local_root, &scenarios, &options);
+    if let Some(path) = &options.summary {
+        write_summary(path, &manifest, &settings, summaries, failed_assertions, started.elapsed());
+    }
+
+    if failed_assertions > 0 {
+        process::exit(1);
+    }
 }
@@ -3,16 +3,12 @@
 GIF_VIEWER_PATH="/Applications/Lyn.app"  # OPTIONAL, image viewer binary
 
 cd "$(dirname "$0")"/..
-cargo run
+cargo run -- "$@" --graphviz png
 
 for scenario in output/*; do
   if [ -d "$scenario" ]; then
     echo "Creating GIF for this scenario: $scenario"
 
-    for f in "$scenario"/frame_*.dot; do
-      dot -Tpng "$f" -o "${f%.dot}.png"
-    done
-
     scenario_name=$(basename "$scenario")
     magick -delay 50 -loop 0 "$scenario"/frame_*.png "$scenario"/${scenario_name}.gif
     echo "Gif created: $scenario/${scenario_name}.gif"
@@ -0,0 +1,90 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::history::RankHistory;
+use crate::types::FrameIdx;
+
+// Structural importance of the nodes of one frame, to contrast with their trust scores. Both
+// metrics follow shortest directed paths where an edge is as long as the inverse of its current
+// weight, so strong recent trust makes nodes close and edges that decayed to zero are gone.
+#[derive(Debug, Clone)]
+pub struct Centrality {
+    pub betweenness: Vec<f64>, // share of shortest paths between other nodes through the node, 0 to 1
+    pub closeness: Vec<f64>,   // harmonic closeness: mean inverse distance to the other nodes, 0 when it reaches none
+}
+
+// Brandes' algorithm with Dijkstra's shortest paths from every node
+pub fn centrality(graph: &CompiledGraph) -> Centrality {
+    let n = graph.num_of_nodes;
+    let mut successors: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+    for edge in graph.edges.iter().filter(|e| e.weight > 0.0) {
+        successors[edge.source.index()].push((edge.target.index(), 1.0 / edge.weight));
+    }
+
+    let mut betweenness = vec![0.0; n];
+    let mut closeness = vec![0.0; n];
+    for source in 0..n {
+        let mut distance = vec![f64::INFINITY; n];
+        let mut paths = vec![0.0; n]; // number of shortest paths from the source
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut settled: Vec<usize> = Vec::new(); // by increasing distance
+        let mut done = vec![false; n];
+        distance[source] = 0.0;
+        paths[source] = 1.0;
+        // Non-negative floats order like their bit patterns
+        let mut queue = BinaryHeap::from([Reverse((0.0f64.to_bits(), source))]);
+        while let Some(Reverse((bits, node))) = queue.pop() {
+            if f64::from_bits(bits) > distance[node] || done[node] {
+                continue;
+            }
+            done[node] = true;
+            settled.push(node);
+            for &(next, length) in &successors[node] {
+                let candidate = distance[node] + length;
+                if candidate < distance[next] {
+                    distance[next] = candidate;
+                    paths[next] = paths[node];
+                    predecessors[next] = vec![node];
+                    queue.push(Reverse((candidate.to_bits(), next)));
+                } else if candidate == distance[next] {
+                    paths[next] += paths[node];
+                    predecessors[next].push(node);
+                }
+            }
+        }
+
+        closeness[source] = settled.iter().filter(|&&node| node != source).fold(0.0, |sum, &node| sum + 1.0 / distance[node]);
+        // Dependencies accumulate from the farthest nodes back towards the source
+        let mut dependency = vec![0.0; n];
+        for &node in settled.iter().rev() {
+            for &predecessor in &predecessors[node] {
+                dependency[predecessor] += paths[predecessor] / paths[node] * (1.0 + dependency[node]);
+            }
+            if node != source {
+                betweenness[node] += dependency[node];
+            }
+        }
+    }
+
+    if n > 1 {
+        closeness.iter_mut().for_each(|c| *c /= (n - 1) as f64);
+    }
+    if n > 2 {
+        let pairs = ((n - 1) * (n - 2)) as f64;
+        betweenness.iter_mut().for_each(|b| *b /= pairs);
+    }
+    Centrality { betweenness, closeness }
+}
+
+// frame,time,node,rank,betweenness,closeness rows, one per node and frame
+pub fn write_metrics_csv(out: &mut dyn Write, history: &RankHistory, centralities: &[Centrality], labels: &[String]) -> io::Result<()> {
+    writeln!(out, "frame,time,node,rank,betweenness,closeness")?;
+    for (frame, ((time, ranks), centrality)) in history.times().iter().zip(history.frames()).zip(centralities).enumerate() {
+        for (node, &rank) in ranks.iter().enumerate() {
+            writeln!(out, "{},{},{},{:.6},{:.6},{:.6}", FrameIdx(frame).number(), time, labels[node], rank, centrality.betweenness[node], centrality.closeness[node])?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::rank::Convergence;
+use crate::server::{read_request, set_timeouts, write_response, Response, REQUEST_TIMEOUT};
+
+// Upper bounds (seconds) of the buckets of the frame latency histogram
+const LATENCY_BUCKETS: [f64; 10] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0, 5.0];
+
+// Counters of a long-running service (`serve`, streaming `--pipe`, `--nats`), exposed to
+// Prometheus in its text format at /metrics
+#[derive(Debug, Clone, Default)]
+pub struct ServiceMetrics {
+    events_ingested: u64,
+    frames_computed: u64,
+    iterations: u64, // summed over the frames that report convergence
+    last_convergence: Option<Convergence>,
+    latency_counts: [u64; LATENCY_BUCKETS.len()], // frames per bucket, not cumulative; slower ones only count in the total
+    latency_sum: f64,
+}
+
+impl ServiceMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events_ingested(&mut self, count: usize) {
+        self.events_ingested += count as u64;
+    }
+
+    // A frame compiled and ranked in `latency`
+    pub fn frame_computed(&mut self, latency: Duration, convergence: Option<Convergence>) {
+        self.frames_computed += 1;
+        let seconds = latency.as_secs_f64();
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|&bound| seconds <= bound) {
+            self.latency_counts[bucket] += 1;
+        }
+        self.latency_sum += seconds;
+        if let Some(convergence) = convergence {
+            self.iterations += convergence.iterations as u64;
+            self.last_convergence = Some(convergence);
+        }
+    }
+
+    pub fn write_prometheus(&self, out: &mut dyn Write) -> io::Result<()> {
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| -> io::Result<()> {
+            writeln!(out, "# HELP {} {}", name, help)?;
+            writeln!(out, "# TYPE {} {}", name, kind)?;
+            writeln!(out, "{} {}", name, value)
+        };
+        metric("trust_flow_events_ingested_total", "counter", "Edge events added to graphs.", self.events_ingested.to_string())?;
+        metric("trust_flow_frames_computed_total", "counter", "Frames ranked.", self.frames_computed.to_string())?;
+        metric("trust_flow_iterations_total", "counter", "Power iteration steps of the frames that report them.", self.iterations.to_string())?;
+        if let Some(convergence) = self.last_convergence {
+            metric("trust_flow_last_iterations", "gauge", "Power iteration steps of the last frame that reported them.", convergence.iterations.to_string())?;
+            metric("trust_flow_last_residual", "gauge", "L1 rank change in the last step of that frame.", format!("{:e}", convergence.residual))?;
+        }
+
+        writeln!(out, "# HELP trust_flow_frame_seconds Wall time to compile and rank a frame.")?;
+        writeln!(out, "# TYPE trust_flow_frame_seconds histogram")?;
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(self.latency_counts) {
+            cumulative += count;
+            writeln!(out, "trust_flow_frame_seconds_bucket{{le=\"{}\"}} {}", bound, cumulative)?;
+        }
+        writeln!(out, "trust_flow_frame_seconds_bucket{{le=\"+Inf\"}} {}", self.frames_computed)?;
+        writeln!(out, "trust_flow_frame_seconds_sum {}", self.latency_sum)?;
+        writeln!(out, "trust_flow_frame_seconds_count {}", self.frames_computed)
+    }
+
+    pub fn response(&self) -> Response {
+        let mut body = Vec::new();
+        self.write_prometheus(&mut body).unwrap();
+        Response { status: 200, content_type: "text/plain; version=0.0.4", body }
+    }
+}
+
+// Answers GET /metrics on its own listener, for the modes that have no HTTP server of their own
+pub fn serve_metrics(listener: &TcpListener, metrics: &Mutex<ServiceMetrics>) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        if set_timeouts(&stream, REQUEST_TIMEOUT).is_err() {
+            continue;
+        }
+        let response = match read_request(&mut stream) {
+            Ok(request) if request.method == "GET" && request.path == "/metrics" => metrics.lock().unwrap().response(),
+            Ok(request) => Response::error(404, &format!("no such endpoint: {}", request.path)),
+            Err(_) => continue,
+        };
+        let _ = write_response(&mut stream, &response);
+    }
+    Ok(())
+}
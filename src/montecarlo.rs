@@ -0,0 +1,143 @@
+use log::debug;
+
+use crate::graph::CompiledGraph;
+use crate::random::Rng;
+use crate::rank::{apply_rank_adjustments, edge_fraction, outflow_normalizers, DanglingPolicy, RankConfig};
+
+// Approximation of the PageRank variant's fixed point by random walks, for graphs where power
+// iteration on every frame is too expensive. Every round starts as many walks as there are nodes,
+// spread over the nodes in proportion to their teleportation targets (one walk per node under
+// uniform teleportation). A walk follows an edge with the probability of the share of rank (mass)
+// the edge carries and goes where the dangling policy sends the rest; after every step it ends
+// with probability 1 - damping, i.e. restarts at a teleportation target. The share of visits
+// to a node is an unbiased estimate of its rank (the "complete path" estimator of K. Avrachenkov et al., Monte Carlo methods in PageRank computation, 2007).
+#[derive(Debug, Clone)]
+pub struct MonteCarloEstimate {
+    pub ranks: Vec<f64>,
+    pub standard_errors: Vec<f64>, // per node, from the spread of the estimates of the single rounds; 0 for nodes no walk reached
+    pub num_of_walks: usize,
+    pub num_of_steps: usize, // visits counted over all walks
+}
+
+impl MonteCarloEstimate {
+    pub fn max_standard_error(&self) -> f64 {
+        self.standard_errors.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+// Out-edges of every node with the cumulative probability of taking them; what is left dangles
+pub(crate) struct WalkGraph {
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    cumulative: Vec<f64>,
+}
+
+impl WalkGraph {
+    pub(crate) fn new(graph: &CompiledGraph, config: &RankConfig) -> Self {
+        let normalizers = outflow_normalizers(graph, config.outflow_normalization);
+        let mut edges: Vec<(usize, usize, f64)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.index(), e.target.index(), edge_fraction(e.weight, normalizers[e.source.index()])))
+            .filter(|&(_, _, fraction)| fraction > 0.0)
+            .collect();
+        edges.sort_by_key(|&(source, _, _)| source);
+
+        let mut offsets = vec![0; graph.num_of_nodes + 1];
+        for &(source, _, _) in &edges {
+            offsets[source + 1] += 1;
+        }
+        for i in 0..graph.num_of_nodes {
+            offsets[i + 1] += offsets[i];
+        }
+        let targets = edges.iter().map(|&(_, target, _)| target).collect();
+        let mut cumulative = Vec::with_capacity(edges.len());
+        for node in 0..graph.num_of_nodes {
+            let mut total = 0.0;
+            for &(_, _, fraction) in &edges[offsets[node]..offsets[node + 1]] {
+                total += fraction;
+                cumulative.push(total);
+            }
+        }
+        WalkGraph { offsets, targets, cumulative }
+    }
+
+    // Edge target for a uniform draw in [0, 1), None when the draw falls on the dangling share
+    pub(crate) fn follow(&self, node: usize, draw: f64) -> Option<usize> {
+        let (start, end) = (self.offsets[node], self.offsets[node + 1]);
+        let edge = start + self.cumulative[start..end].partition_point(|&c| c <= draw);
+        (edge < end).then(|| self.targets[edge])
+    }
+}
+
+pub fn monte_carlo_ranks(graph: &CompiledGraph, teleportation_targets: &[f64], config: &RankConfig) -> MonteCarloEstimate {
+    let num_of_nodes = graph.num_of_nodes;
+    let damping_factor = config.damping_factor;
+    let rounds = config.walks_per_node.max(2);
+    let walk_graph = WalkGraph::new(graph, config);
+    let total_teleport: f64 = teleportation_targets.iter().sum();
+    let mut teleport_cumulative = Vec::with_capacity(num_of_nodes);
+    let mut total = 0.0;
+    for &t in teleportation_targets {
+        total += t / total_teleport;
+        teleport_cumulative.push(total);
+    }
+    let sample_target = |draw: f64| teleport_cumulative.partition_point(|&c| c <= draw).min(num_of_nodes - 1);
+    // Every visit adds the same share of the teleported rank (mass)
+    let visit = (1.0 - damping_factor) / num_of_nodes as f64;
+
+    let active_nodes: Vec<usize> = (0..num_of_nodes).filter(|&node| graph.is_active(node)).collect();
+    let mut rng = Rng::new(config.walk_seed);
+    let mut sums = vec![0.0; num_of_nodes];
+    let mut squares = vec![0.0; num_of_nodes];
+    let mut round = vec![0.0; num_of_nodes];
+    let mut num_of_steps = 0;
+    for _ in 0..rounds {
+        // Systematic sampling: evenly spaced draws with one random offset per round
+        let offset = rng.next_f64();
+        for walk in 0..num_of_nodes {
+            let mut node = sample_target((walk as f64 + offset) / num_of_nodes as f64);
+            loop {
+                round[node] += visit;
+                num_of_steps += 1;
+                if !rng.chance(damping_factor) {
+                    break;
+                }
+                // A node that retains part of its rank keeps the walk with that probability
+                let conductance = graph.conductance(node);
+                if conductance < 1.0 && !rng.chance(conductance) {
+                    continue;
+                }
+                let draw = rng.next_f64();
+                node = match walk_graph.follow(node, draw) {
+                    Some(target) => target,
+                    None => match config.dangling_policy {
+                        DanglingPolicy::Uniform => active_nodes[rng.below(active_nodes.len())],
+                        DanglingPolicy::Teleport => sample_target(rng.next_f64()),
+                        DanglingPolicy::Retain => node,
+                    },
+                };
+            }
+        }
+        for i in 0..num_of_nodes {
+            sums[i] += round[i];
+            squares[i] += round[i] * round[i];
+            round[i] = 0.0;
+        }
+    }
+
+    let n = rounds as f64;
+    let mut ranks: Vec<f64> = sums.iter().map(|s| s / n).collect();
+    let standard_errors: Vec<f64> = sums
+        .iter()
+        .zip(&squares)
+        .map(|(s, sq)| ((sq - s * s / n) / (n - 1.0)).max(0.0).sqrt() / n.sqrt())
+        .collect();
+    // Reward and penalty amounts act on the estimate once, not on every step as in power iteration
+    if !graph.rank_adjustments.is_empty() {
+        apply_rank_adjustments(&mut ranks, &graph.rank_adjustments);
+    }
+    let estimate = MonteCarloEstimate { ranks, standard_errors, num_of_walks: rounds * num_of_nodes, num_of_steps };
+    debug!("nodes={} edges={} walks={} steps={} max_standard_error={:.3e}", num_of_nodes, graph.edges.len(), estimate.num_of_walks, num_of_steps, estimate.max_standard_error());
+    estimate
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+use crate::types::{FrameIdx, Time};
+
+// Template of the frame file names of the run without a `frame-names` line or --frame-names
+pub const DEFAULT_FRAME_NAMES: &str = "frame_{index}";
+
+// Width `{index}` is padded to at least, so runs of up to 1000 frames keep the names frame_000 to
+// frame_999
+const MIN_INDEX_WIDTH: usize = 3;
+
+// Names of the files frames are written to, without folder and extension, from a template such as
+// `{scenario}-{index:5}-t{time:2}`:
+//
+//   {scenario}   the scenario's name
+//   {index}      the frame's 0-based index, zero-padded to the width of the run's last index
+//   {index:5}    the index zero-padded to 5 digits
+//   {time}       the frame's time as written in the summary, e.g. 2.5
+//   {time:2}     the time with 2 decimals
+//
+// Names sort in frame order as long as the index comes before anything else that varies. The
+// template needs {index} or {time}, so that frames at different times get different names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameNames {
+    template: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Scenario,
+    Index(Option<usize>), // padding width, the run's when not given
+    Time(Option<usize>),  // decimals
+}
+
+impl Default for FrameNames {
+    fn default() -> Self {
+        FrameNames::parse(DEFAULT_FRAME_NAMES).unwrap()
+    }
+}
+
+impl fmt::Display for FrameNames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+impl FrameNames {
+    pub fn parse(template: &str) -> Result<FrameNames, String> {
+        if template.contains(['/', '\\']) {
+            return Err(format!("frame names are file names, not paths: {}", template));
+        }
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..].find('}').ok_or_else(|| format!("unclosed placeholder in frame names: {}", template))? + start;
+            let (name, argument) = match rest[start + 1..end].split_once(':') {
+                Some((name, argument)) => (name, Some(argument.parse::<usize>().map_err(|_| format!("expected a number after '{}:' in frame names, found '{}'", name, argument))?)),
+                None => (&rest[start + 1..end], None),
+            };
+            parts.push(match (name, argument) {
+                ("scenario", None) => Part::Scenario,
+                ("index", width) => Part::Index(width),
+                ("time", decimals) => Part::Time(decimals),
+                _ => return Err(format!("unknown placeholder in frame names: {{{}}} (expected {{scenario}}, {{index}} or {{time}})", &rest[start + 1..end])),
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        if !parts.iter().any(|part| matches!(part, Part::Index(_) | Part::Time(_))) {
+            return Err(format!("frame names need {{index}} or {{time}} to tell frames apart: {}", template));
+        }
+        Ok(FrameNames { template: template.to_string(), parts })
+    }
+
+    // Name of a frame of a run with `num_of_frames` frames
+    pub fn name(&self, scenario: &str, frame: FrameIdx, time: Time, num_of_frames: usize) -> String {
+        let run_width = num_of_frames.saturating_sub(1).to_string().len().max(MIN_INDEX_WIDTH);
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Scenario => scenario.to_string(),
+                Part::Index(width) => format!("{:0width$}", frame.index(), width = width.unwrap_or(run_width)),
+                Part::Time(Some(decimals)) => format!("{:.decimals$}", time.value(), decimals = decimals),
+                Part::Time(None) => time.to_string(),
+            })
+            .collect()
+    }
+}
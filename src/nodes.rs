@@ -0,0 +1,117 @@
+use crate::types::{NodeId, Time};
+
+// Optional metadata of a node; nodes without a name are shown by their index
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeInfo {
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub org: Option<String>, // organization the node belongs to
+    pub position: Option<(f64, f64)>, // fixed layout position, on the scale of the unit circle layout
+    pub teleport_weight: Option<f64>, // relative share of the experts' teleported rank (mass), 1 by default
+    pub prior: Option<f64>, // prior score (e.g. KYC level) that shapes the non-expert teleportation
+    pub conductance: Option<f64>, // share in [0, 1] of the received trust the node passes along its edges, 1 by default; it retains the rest
+    pub joins: Option<Time>,  // the node takes part in ranking and rendering from this time on; from the start when not given
+    pub leaves: Option<Time>, // and no longer from this time on
+}
+
+// One row of a node table: metadata plus whether the node is an expert
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRow {
+    pub node: NodeId,
+    pub info: NodeInfo,
+    pub expert: bool,
+}
+
+// Attributes of a `node` directive in a scenario file, e.g.
+//   node 3 name=alice category=moderator org=acme pos=0.5,-0.2 teleport=2 prior=0.7 conductance=0.4 expert
+pub fn parse_node_attributes<'a>(node: NodeId, attributes: impl Iterator<Item = &'a str>) -> Result<NodeRow, String> {
+    let mut row = NodeRow { node, info: NodeInfo::default(), expert: false };
+    for attribute in attributes {
+        match attribute.split_once('=') {
+            Some(("name", name)) => row.info.name = Some(name.to_string()),
+            Some(("category", category)) => row.info.category = Some(category.to_string()),
+            Some(("org", org)) => row.info.org = Some(org.to_string()),
+            Some(("pos", position)) => row.info.position = Some(parse_position(position)?),
+            Some(("teleport", weight)) => row.info.teleport_weight = Some(parse_weight(weight, "teleport weight")?),
+            Some(("prior", prior)) => row.info.prior = Some(parse_weight(prior, "prior")?),
+            Some(("conductance", conductance)) => row.info.conductance = Some(parse_share(conductance, "conductance")?),
+            Some(("joins", time)) => row.info.joins = Some(parse_time(time, "join time")?),
+            Some(("leaves", time)) => row.info.leaves = Some(parse_time(time, "leave time")?),
+            None if attribute == "expert" => row.expert = true,
+            _ => return Err(format!("unknown node attribute: {} (expected name=, category=, org=, pos=, teleport=, prior=, conductance=, joins=, leaves= or expert)", attribute)),
+        }
+    }
+    Ok(row)
+}
+
+// CSV node table with a header naming the columns, in any order:
+//   id,name,category,org,x,y,expert,teleport_weight,prior,conductance,joins,leaves
+// Only `id` is required; empty cells leave the attribute unset. A score file of external priors
+// is a node table too: id,prior
+pub fn read_node_table(text: &str) -> Result<Vec<NodeRow>, String> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'));
+    let (_, header) = lines.next().ok_or("node table is empty")?;
+    let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+    let column = |name: &str| columns.iter().position(|&c| c == name);
+    let id_column = column("id").ok_or("node table has no 'id' column")?;
+
+    let mut rows = Vec::new();
+    for (i, line) in lines {
+        let error = |message: String| format!("line {}: {}", i + 1, message);
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |name: &str| column(name).and_then(|c| fields.get(c)).copied().filter(|f| !f.is_empty());
+        let id = fields.get(id_column).ok_or_else(|| error("missing id".to_string()))?;
+        let node = NodeId(id.parse().map_err(|_| error(format!("invalid node id: {}", id)))?);
+        let mut info = NodeInfo {
+            name: field("name").map(str::to_string),
+            category: field("category").map(str::to_string),
+            org: field("org").map(str::to_string),
+            ..NodeInfo::default()
+        };
+        if let (Some(x), Some(y)) = (field("x"), field("y")) {
+            info.position = Some(parse_position(&format!("{},{}", x, y)).map_err(error)?);
+        }
+        if let Some(weight) = field("teleport_weight") {
+            info.teleport_weight = Some(parse_weight(weight, "teleport weight").map_err(error)?);
+        }
+        if let Some(prior) = field("prior") {
+            info.prior = Some(parse_weight(prior, "prior").map_err(error)?);
+        }
+        if let Some(conductance) = field("conductance") {
+            info.conductance = Some(parse_share(conductance, "conductance").map_err(error)?);
+        }
+        if let Some(time) = field("joins") {
+            info.joins = Some(parse_time(time, "join time").map_err(error)?);
+        }
+        if let Some(time) = field("leaves") {
+            info.leaves = Some(parse_time(time, "leave time").map_err(error)?);
+        }
+        let expert = matches!(field("expert"), Some("true" | "1" | "yes"));
+        rows.push(NodeRow { node, info, expert });
+    }
+    Ok(rows)
+}
+
+fn parse_position(text: &str) -> Result<(f64, f64), String> {
+    let invalid = || format!("invalid position: {} (expected x,y)", text);
+    let (x, y) = text.split_once(',').ok_or_else(invalid)?;
+    Ok((x.trim().parse().map_err(|_| invalid())?, y.trim().parse().map_err(|_| invalid())?))
+}
+
+fn parse_time(text: &str, what: &str) -> Result<Time, String> {
+    text.parse().map(Time).map_err(|_| format!("invalid {}: {}", what, text))
+}
+
+fn parse_weight(text: &str, what: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(weight) if weight >= 0.0 => Ok(weight),
+        _ => Err(format!("invalid {}: {} (expected a non-negative number)", what, text)),
+    }
+}
+
+fn parse_share(text: &str, what: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(share) if (0.0..=1.0).contains(&share) => Ok(share),
+        _ => Err(format!("invalid {}: {} (expected a number from 0 to 1)", what, text)),
+    }
+}
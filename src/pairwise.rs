@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+
+use crate::graph::{CompiledGraph, GraphError};
+use crate::rank::RankConfig;
+use crate::registry::{BOUNDED_PROPAGATION, PAGERANK_VARIANT};
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+
+// How trust_between estimates one node's trust in another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairwiseTrust {
+    Personalized, // the trustee's rank under the PageRank variant with all teleportation at the truster
+    MaxFlow,      // the most weight that can flow from the truster to the trustee, with the current edge weights as capacities (Advogato)
+    BoundedPaths, // trust energy that reaches the trustee from the truster within the hop limit (bounded propagation)
+}
+
+impl PairwiseTrust {
+    pub const ALL: [PairwiseTrust; 3] = [PairwiseTrust::Personalized, PairwiseTrust::MaxFlow, PairwiseTrust::BoundedPaths];
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        PairwiseTrust::ALL.into_iter().find(|method| method.name() == text).ok_or_else(|| format!("unknown pairwise trust method: {} (expected personalized, max-flow or paths)", text))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PairwiseTrust::Personalized => "personalized",
+            PairwiseTrust::MaxFlow => "max-flow",
+            PairwiseTrust::BoundedPaths => "paths",
+        }
+    }
+}
+
+// How much `truster` should trust `trustee` in the frame at the given time. Personalized PageRank
+// and bounded paths take the damping factor and hop limit of the config and give a share of the
+// truster's trust; max flow is in units of edge weight, and unbounded from a node to itself.
+pub fn trust_between(scenario: &Scenario, config: &RankConfig, truster: NodeId, trustee: NodeId, time: Time, method: PairwiseTrust) -> Result<f64, GraphError> {
+    let graph = scenario.frame_graph(time, &scenario.edge_weights(time))?;
+    let mut seed = vec![0.0; graph.num_of_nodes];
+    seed[truster.index()] = 1.0;
+    Ok(match method {
+        PairwiseTrust::Personalized => PAGERANK_VARIANT.create(config).rank(&graph, &seed)[trustee.index()],
+        PairwiseTrust::MaxFlow => max_flow(&graph, truster, trustee),
+        PairwiseTrust::BoundedPaths => BOUNDED_PROPAGATION.create(config).rank(&graph, &seed)[trustee.index()],
+    })
+}
+
+// Maximum flow from source to sink with the edge weights as capacities, by shortest augmenting
+// paths (Edmonds-Karp). Parallel edges add up, and self-loops and edges of inactive nodes carry nothing.
+pub fn max_flow(graph: &CompiledGraph, source: NodeId, sink: NodeId) -> f64 {
+    if source == sink {
+        return f64::INFINITY;
+    }
+    // Residual arcs in pairs: arc i ^ 1 is the reverse of arc i
+    let mut heads: Vec<usize> = Vec::new();
+    let mut residual: Vec<f64> = Vec::new();
+    let mut arcs: Vec<Vec<usize>> = vec![Vec::new(); graph.num_of_nodes];
+    for edge in &graph.edges {
+        let (from, to) = (edge.source.index(), edge.target.index());
+        if from == to || edge.weight <= 0.0 || !graph.is_active(from) || !graph.is_active(to) {
+            continue;
+        }
+        arcs[from].push(heads.len());
+        heads.push(to);
+        residual.push(edge.weight);
+        arcs[to].push(heads.len());
+        heads.push(from);
+        residual.push(0.0);
+    }
+
+    let mut flow = 0.0;
+    loop {
+        let mut via: Vec<Option<usize>> = vec![None; graph.num_of_nodes];
+        let mut queue = VecDeque::from([source.index()]);
+        while let Some(node) = queue.pop_front() {
+            for &arc in &arcs[node] {
+                let next = heads[arc];
+                if residual[arc] > 0.0 && next != source.index() && via[next].is_none() {
+                    via[next] = Some(arc);
+                    queue.push_back(next);
+                }
+            }
+        }
+        if via[sink.index()].is_none() {
+            return flow;
+        }
+        let path: Vec<usize> = std::iter::successors(via[sink.index()], |&arc| via[heads[arc ^ 1]]).collect();
+        let bottleneck = path.iter().map(|&arc| residual[arc]).fold(f64::INFINITY, f64::min);
+        for &arc in &path {
+            residual[arc] -= bottleneck;
+            residual[arc ^ 1] += bottleneck;
+        }
+        flow += bottleneck;
+    }
+}
@@ -0,0 +1,136 @@
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use parquet::basic::{Compression, ConvertedType, Repetition, Type as PhysicalType};
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type;
+
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::types::FrameIdx;
+
+// Flat tables of required (non-null) columns, written with the parquet crate as Snappy-compressed
+// row groups of up to ROW_GROUP_ROWS rows
+pub const ROW_GROUP_ROWS: usize = 1 << 20;
+
+pub enum ColumnValues {
+    Int64(Vec<i64>),
+    Double(Vec<f64>),
+    Text(Vec<String>),
+}
+
+pub struct Column {
+    pub name: &'static str,
+    pub values: ColumnValues,
+}
+
+impl Column {
+    fn len(&self) -> usize {
+        match &self.values {
+            ColumnValues::Int64(values) => values.len(),
+            ColumnValues::Double(values) => values.len(),
+            ColumnValues::Text(values) => values.len(),
+        }
+    }
+
+    fn schema(&self) -> parquet::errors::Result<Arc<Type>> {
+        let (physical_type, converted_type) = match self.values {
+            ColumnValues::Int64(_) => (PhysicalType::INT64, ConvertedType::NONE),
+            ColumnValues::Double(_) => (PhysicalType::DOUBLE, ConvertedType::NONE),
+            ColumnValues::Text(_) => (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8),
+        };
+        let column = Type::primitive_type_builder(self.name, physical_type).with_repetition(Repetition::REQUIRED).with_converted_type(converted_type);
+        Ok(Arc::new(column.build()?))
+    }
+}
+
+// Writes the columns, which must have the same number of rows, as one Parquet file
+pub fn write_parquet(out: impl Write + Send, columns: &[Column]) -> io::Result<()> {
+    let num_of_rows = columns.first().map_or(0, Column::len);
+    assert!(columns.iter().all(|c| c.len() == num_of_rows), "Parquet columns differ in length");
+    write_row_groups(out, columns, num_of_rows).map_err(io::Error::other)
+}
+
+fn write_row_groups(out: impl Write + Send, columns: &[Column], num_of_rows: usize) -> parquet::errors::Result<()> {
+    let fields = columns.iter().map(Column::schema).collect::<Result<_, _>>()?;
+    let schema = Arc::new(Type::group_type_builder("schema").with_fields(fields).build()?);
+    let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+    let mut writer = SerializedFileWriter::new(out, schema, properties)?;
+    let mut start = 0;
+    // An empty table still gets its schema, in a file without row groups
+    while start < num_of_rows {
+        let rows = start..(start + ROW_GROUP_ROWS).min(num_of_rows);
+        let mut row_group = writer.next_row_group()?;
+        for column in columns {
+            let mut chunk = row_group.next_column()?.expect("a column chunk per schema column");
+            match &column.values {
+                ColumnValues::Int64(values) => chunk.typed::<Int64Type>().write_batch(&values[rows.clone()], None, None)?,
+                ColumnValues::Double(values) => chunk.typed::<DoubleType>().write_batch(&values[rows.clone()], None, None)?,
+                ColumnValues::Text(values) => {
+                    let values: Vec<ByteArray> = values[rows.clone()].iter().map(|v| ByteArray::from(v.as_str())).collect();
+                    chunk.typed::<ByteArrayType>().write_batch(&values, None, None)?
+                }
+            };
+            chunk.close()?;
+        }
+        row_group.close()?;
+        start = rows.end;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+// Long-format rank timeline: frame, time, node, label, rank rows, one per node and frame
+pub fn write_rank_timeline_parquet(out: impl Write + Send, output: &RunOutput, labels: &[String]) -> io::Result<()> {
+    let (mut frames, mut times, mut nodes, mut node_labels, mut ranks) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for (frame, (time, frame_ranks)) in output.history.times().iter().zip(output.history.frames()).enumerate() {
+        for (node, &rank) in frame_ranks.iter().enumerate() {
+            frames.push(FrameIdx(frame).number() as i64);
+            times.push(time.value());
+            nodes.push(node as i64);
+            node_labels.push(labels[node].clone());
+            ranks.push(rank);
+        }
+    }
+    write_parquet(
+        out,
+        &[
+            Column { name: "frame", values: ColumnValues::Int64(frames) },
+            Column { name: "time", values: ColumnValues::Double(times) },
+            Column { name: "node", values: ColumnValues::Int64(nodes) },
+            Column { name: "label", values: ColumnValues::Text(node_labels) },
+            Column { name: "rank", values: ColumnValues::Double(ranks) },
+        ],
+    )
+}
+
+// Long-format edge weight timeline: frame, time, edge, source, target, weight rows for the edges
+// that weigh something in a frame; edges not created yet, expired or pruned are left out
+pub fn write_edge_weight_timeline_parquet(out: impl Write + Send, scenario: &Scenario, output: &RunOutput) -> io::Result<()> {
+    let (mut frames, mut times, mut edges, mut sources, mut targets, mut weights) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for (frame, (time, frame_weights)) in output.history.times().iter().zip(&output.edge_weights).enumerate() {
+        for (edge, (&weight, e)) in frame_weights.iter().zip(&scenario.edges).enumerate() {
+            if weight > 0.0 {
+                frames.push(FrameIdx(frame).number() as i64);
+                times.push(time.value());
+                edges.push(edge as i64);
+                sources.push(e.source.index() as i64);
+                targets.push(e.target.index() as i64);
+                weights.push(weight);
+            }
+        }
+    }
+    write_parquet(
+        out,
+        &[
+            Column { name: "frame", values: ColumnValues::Int64(frames) },
+            Column { name: "time", values: ColumnValues::Double(times) },
+            Column { name: "edge", values: ColumnValues::Int64(edges) },
+            Column { name: "source", values: ColumnValues::Int64(sources) },
+            Column { name: "target", values: ColumnValues::Int64(targets) },
+            Column { name: "weight", values: ColumnValues::Double(weights) },
+        ],
+    )
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::types::{FrameIdx, Time};
+
+// Where the time of a frame goes, in the order the phases run in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Decay,     // edge weights at the frame's time and the compiled graph
+    Ranking,   // the ranking algorithm
+    Layout,    // what the frame draws: color scale, changes, borders, the focus view and its layout
+    Rendering, // writing the DOT files
+    Export,    // handing the frame's files to the output folder or bucket
+}
+
+pub const PHASES: [Phase; 5] = [Phase::Decay, Phase::Ranking, Phase::Layout, Phase::Rendering, Phase::Export];
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Decay => "decay",
+            Phase::Ranking => "ranking",
+            Phase::Layout => "layout",
+            Phase::Rendering => "rendering",
+            Phase::Export => "export",
+        }
+    }
+}
+
+// Wall time of every phase of one frame, and the peak memory of the process once it was done.
+// Frames ranked on several threads at once each count their own time.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FrameProfile {
+    pub durations: [Duration; PHASES.len()],
+    pub peak_memory: Option<u64>, // bytes; None where the operating system does not tell
+}
+
+impl FrameProfile {
+    pub fn add(&mut self, phase: Phase, duration: Duration) {
+        self.durations[phase as usize] += duration;
+    }
+
+    // Runs `f`, adding its wall time to the phase
+    pub fn time<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let started = Stopwatch::start();
+        let result = f();
+        self.add(phase, started.elapsed());
+        result
+    }
+
+    pub fn duration(&self, phase: Phase) -> Duration {
+        self.durations[phase as usize]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.durations.iter().sum()
+    }
+
+    // Adds the times of `other`, e.g. of a later stage of the same frame
+    pub fn merge(&mut self, other: &FrameProfile) {
+        for (duration, other) in self.durations.iter_mut().zip(other.durations) {
+            *duration += other;
+        }
+        self.peak_memory = self.peak_memory.max(other.peak_memory);
+    }
+
+    pub fn update_peak_memory(&mut self) {
+        self.peak_memory = peak_memory().max(self.peak_memory);
+    }
+}
+
+// Start of a timed phase. wasm32-unknown-unknown has no clock (Instant::now panics there), so in
+// the browser every phase takes no time.
+#[derive(Debug, Clone, Copy)]
+pub struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    started: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Stopwatch {
+            #[cfg(not(target_arch = "wasm32"))]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+// Peak resident memory of the process so far, from VmHWM in /proc/self/status on Linux
+pub fn peak_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes: u64 = line["VmHWM:".len()..].trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+// One row per frame with the milliseconds of every phase, and a last `run` row with the time of
+// the whole-run files (exports, charts and reports) under export
+pub fn write_profile_csv(out: &mut dyn Write, times: &[Time], frames: &[FrameProfile], run: &FrameProfile) -> io::Result<()> {
+    let names: Vec<String> = PHASES.iter().map(|phase| format!("{}_ms", phase.name())).collect();
+    writeln!(out, "frame,time,{},total_ms,peak_memory_mb", names.join(","))?;
+    let row = |profile: &FrameProfile| {
+        let durations: Vec<String> = PHASES.iter().map(|&phase| format!("{:.3}", profile.duration(phase).as_secs_f64() * 1000.0)).collect();
+        let memory = profile.peak_memory.map_or(String::new(), |bytes| format!("{:.1}", bytes as f64 / (1024.0 * 1024.0)));
+        format!("{},{:.3},{}", durations.join(","), profile.total().as_secs_f64() * 1000.0, memory)
+    };
+    // Frames that were not ranked or rendered in this process took no time
+    for (frame, time) in times.iter().enumerate() {
+        writeln!(out, "{},{},{}", FrameIdx(frame).number(), time, row(&frames.get(frame).copied().unwrap_or_default()))?;
+    }
+    writeln!(out, "run,,{}", row(run))
+}
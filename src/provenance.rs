@@ -0,0 +1,73 @@
+use std::io::{self, Write};
+
+use crate::graph::compile_graph;
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::types::{EdgeId, FrameIdx, NodeId};
+
+// Raw record an edge was read from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EdgeSource {
+    pub line: usize,              // 1-based line or row in the input, 0 when unknown
+    pub event_id: Option<String>, // identifier given in the input, if any
+    pub metadata: Vec<(String, String)>, // key-value pairs given in the input, such as the source system, in input order
+}
+
+impl EdgeSource {
+    // `key=value` pairs separated by semicolons, for a single CSV cell or tooltip
+    pub fn metadata_text(&self) -> String {
+        self.metadata.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<_>>().join("; ")
+    }
+}
+
+// Event id and metadata of every edge as `id=...; key=value` text, empty when the input gave neither
+pub fn edge_tooltips(scenario: &Scenario) -> Vec<String> {
+    (0..scenario.edges.len())
+        .map(|id| match scenario.edge_source(EdgeId(id)) {
+            Some(source) => {
+                let event_id = source.event_id.as_ref().map(|event_id| format!("id={}", event_id));
+                event_id.into_iter().chain((!source.metadata.is_empty()).then(|| source.metadata_text())).collect::<Vec<_>>().join("; ")
+            }
+            None => String::new(),
+        })
+        .collect()
+}
+
+// Every metadata key of the scenario's edges, in the order they first appear
+pub fn metadata_keys(scenario: &Scenario) -> Vec<&str> {
+    let mut keys: Vec<&str> = Vec::new();
+    for (key, _) in scenario.edge_sources.iter().flat_map(|source| &source.metadata) {
+        if !keys.contains(&key.as_str()) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+// Drill-down from the edges the ranking saw in every frame (after merging parallel edges)
+// back to the raw records they came from: one CSV row per frame, ranked edge and raw edge.
+pub fn write_edge_drill_down(out: &mut dyn Write, scenario: &Scenario, output: &RunOutput) -> io::Result<()> {
+    let field = |text: &str| {
+        if text.contains([',', '"', '\n']) { format!("\"{}\"", text.replace('"', "\"\"")) } else { text.to_string() }
+    };
+    let node = |node: NodeId| field(&scenario.node_label(node));
+    writeln!(out, "frame,time,source,target,ranked_weight,edge_id,created,edge_weight,line,event_id,metadata")?;
+    for (frame, (time, weights)) in output.history.times().iter().zip(&output.edge_weights).enumerate() {
+        let graph = compile_graph(&scenario.edges, weights, scenario.num_of_nodes, &scenario.graph_options)
+            .map_err(io::Error::other)?;
+        for compiled in graph.edges.iter().filter(|e| e.weight > 0.0) {
+            for &id in &compiled.sources {
+                let edge = &scenario.edges[id.index()];
+                let source = scenario.edge_source(id);
+                writeln!(out, "{},{},{},{},{},{},{},{},{},{},{}",
+                    FrameIdx(frame).number(), time, node(compiled.source), node(compiled.target), compiled.weight,
+                    id, edge.time_of_creation, weights[id.index()],
+                    source.map_or(0, |s| s.line),
+                    source.and_then(|s| s.event_id.as_deref()).map(field).unwrap_or_default(),
+                    source.map(|s| field(&s.metadata_text())).unwrap_or_default(),
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+// Python bindings, built with `maturin develop`, which turns on the python feature and links as an
+// extension module (see pyproject.toml):
+//
+//   import trust_flow
+//   graph = trust_flow.TemporalGraph(6, experts=[0], decay=0.1)
+//   graph.add_edge(0, 1, 1.0)
+//   times, ranks = trust_flow.rank(graph)  # ranks[frame, node]
+use numpy::{PyArray1, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::graph::{event_times, Edge, DEFAULT_DECAY_CONSTANT};
+use crate::rank::RankConfig;
+use crate::registry::AlgorithmRegistry;
+use crate::run::run_scenario;
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+use crate::window::TimeWindow;
+
+// Confirmation events and decay settings of one temporal graph, i.e. a scenario built from Python
+#[pyclass(name = "TemporalGraph")]
+pub struct PyTemporalGraph {
+    scenario: Scenario,
+}
+
+#[pymethods]
+impl PyTemporalGraph {
+    #[new]
+    #[pyo3(signature = (num_of_nodes, experts = Vec::new(), decay = DEFAULT_DECAY_CONSTANT))]
+    fn new(num_of_nodes: usize, experts: Vec<usize>, decay: f64) -> PyResult<Self> {
+        let mut scenario = Scenario::new("python", num_of_nodes, experts.into_iter().map(NodeId).collect(), Vec::new());
+        scenario.decay_constant = decay;
+        scenario.validate().map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyTemporalGraph { scenario })
+    }
+
+    #[pyo3(signature = (source, target, time, weight = 1.0))]
+    fn add_edge(&mut self, source: usize, target: usize, time: f64, weight: f64) -> PyResult<()> {
+        if source >= self.scenario.num_of_nodes || target >= self.scenario.num_of_nodes {
+            return Err(PyValueError::new_err(format!("edge {} -> {} refers to a node outside 0..{}", source, target, self.scenario.num_of_nodes)));
+        }
+        if weight.is_nan() || weight <= 0.0 {
+            return Err(PyValueError::new_err(format!("edge weight must be positive: {}", weight)));
+        }
+        self.scenario.edges.push(Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight });
+        Ok(())
+    }
+
+    // Exponential decay constant of edge weights; 0 disables decay
+    #[getter]
+    fn decay(&self) -> f64 {
+        self.scenario.decay_constant
+    }
+
+    #[setter]
+    fn set_decay(&mut self, decay: f64) {
+        self.scenario.decay_constant = decay;
+    }
+
+    // Only edges created in the last `size` time units count; frames every `stride` by default
+    #[pyo3(signature = (size, stride = 1.0))]
+    fn sliding_window(&mut self, size: f64, stride: f64) {
+        self.scenario.window = Some(TimeWindow::sliding(size, stride));
+    }
+
+    fn tumbling_window(&mut self, size: f64) {
+        self.scenario.window = Some(TimeWindow::tumbling(size));
+    }
+
+    fn clear_window(&mut self) {
+        self.scenario.window = None;
+    }
+
+    #[getter]
+    fn num_of_nodes(&self) -> usize {
+        self.scenario.num_of_nodes
+    }
+
+    #[getter]
+    fn num_of_edges(&self) -> usize {
+        self.scenario.edges.len()
+    }
+}
+
+// Frame times and ranks[frame, node]
+type RankArrays<'py> = (Bound<'py, PyArray1<f64>>, Bound<'py, PyArray2<f64>>);
+
+// Ranks the graph at every frame time (the distinct edge creation times by default) and returns the
+// frame times and a frames x nodes array of ranks
+#[pyfunction]
+#[pyo3(signature = (graph, frame_times = None, algorithm = "pagerank", damping_factor = 0.5, iterations = 10))]
+fn rank<'py>(
+    py: Python<'py>,
+    graph: &PyTemporalGraph,
+    frame_times: Option<Vec<f64>>,
+    algorithm: &str,
+    damping_factor: f64,
+    iterations: usize,
+) -> PyResult<RankArrays<'py>> {
+    let scenario = &graph.scenario;
+    let config = RankConfig::new().damping_factor(damping_factor).iterations(iterations);
+    let algorithm = AlgorithmRegistry::create(algorithm, &config).map_err(PyValueError::new_err)?;
+    let frame_times: Vec<Time> = match frame_times {
+        Some(times) => times.into_iter().map(Time).collect(),
+        None => event_times(&scenario.edges),
+    };
+    if frame_times.is_empty() {
+        return Err(PyValueError::new_err("no frame times: pass frame_times or add edges first"));
+    }
+
+    let output = run_scenario(scenario, algorithm.as_ref(), &frame_times, &scenario.graph_options);
+    let ranks = PyArray2::from_vec2(py, output.history.frames()).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let times = PyArray1::from_vec(py, frame_times.iter().map(|t| t.value()).collect());
+    Ok((times, ranks))
+}
+
+// Public so that tests can add the module to an embedded interpreter
+#[pymodule]
+pub fn trust_flow(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyTemporalGraph>()?;
+    m.add_function(wrap_pyfunction!(rank, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// Seed of every random process unless a scenario or --seed says otherwise
+pub const DEFAULT_SEED: u64 = 1;
+
+// Small deterministic pseudo-random number generator (SplitMix64), so that simulations
+// are reproducible from a seed without external dependencies
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in 0..n; n must be positive
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_f64() * n as f64) as usize % n
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    // Number of events of a Poisson process with the given mean (Knuth's method, fine for small means)
+    pub fn poisson(&mut self, mean: f64) -> usize {
+        let limit = (-mean).exp();
+        let mut count = 0;
+        let mut product = self.next_f64();
+        while product > limit {
+            count += 1;
+            product *= self.next_f64();
+        }
+        count
+    }
+
+    // Index drawn with probability proportional to its weight; None when all weights are zero
+    pub fn weighted(&mut self, weights: &[f64]) -> Option<usize> {
+        let total: f64 = weights.iter().filter(|w| **w > 0.0).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut remaining = self.next_f64() * total;
+        for (i, &w) in weights.iter().enumerate() {
+            if w <= 0.0 {
+                continue;
+            }
+            if remaining < w {
+                return Some(i);
+            }
+            remaining -= w;
+        }
+        weights.iter().rposition(|&w| w > 0.0)
+    }
+}
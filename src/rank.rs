@@ -0,0 +1,823 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use log::{debug, trace, warn};
+
+use crate::dense::DenseMatrix;
+use crate::graph::CompiledGraph;
+use crate::montecarlo::monte_carlo_ranks;
+use crate::precision::{pagerank_in, Precision};
+use crate::random::DEFAULT_SEED;
+use crate::registry::PAGERANK_VARIANT;
+use crate::types::NodeId;
+
+// What happens to rank (mass) that a node cannot push along its edges
+// (no outgoing edges, or outgoing edges that have decayed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DanglingPolicy {
+    Uniform,  // spread evenly over all nodes
+    Teleport, // spread according to the teleportation targets
+    Retain,   // kept by the dangling node itself
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    EdgeList, // iterate over the edge list on every step
+    Dense,    // multiply by a dense transition matrix built once per frame; for small graphs
+}
+
+// What a node's edge weights are divided by to get the fraction of its rank (mass) each edge carries.
+// This decides how much mass is dangling: with lifetime normalization a node whose confirmations
+// have decayed pushes little along its edges and the rest dangles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutflowNormalization {
+    #[default]
+    Lifetime, // sum of the edges' weights at creation, i.e. the number of confirmations ever given
+    Current,  // sum of the current (decayed) weights; nothing dangles while any edge has weight left
+    None,     // weights are fractions as they are; a node whose weights sum to more than 1 is scaled down to 1
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Solver {
+    #[default]
+    Jacobi,      // every step computes all new ranks from the previous step's ranks
+    GaussSeidel, // every step updates the ranks in place, node by node, using the already updated values
+    MonteCarlo,  // random walks estimate the fixed point; RankIteration steps like Jacobi
+    Exact,       // the fixed point from solving the linear system directly, for small graphs; RankIteration steps like Jacobi
+}
+
+// Largest graph the exact solver takes on, as the dense system needs n² memory and n³ time
+pub const EXACT_MAX_NODES: usize = 2000;
+
+#[derive(Debug, Clone)]
+pub struct RankConfig {
+    pub damping_factor: f64,
+    pub num_of_iterations: usize, // upper bound on the number of iterations
+    pub tolerance: f64,           // stop once the L1 change between iterations drops below this; 0.0 disables early stop
+    pub dangling_policy: DanglingPolicy,
+    pub backend: Backend,
+    pub outflow_normalization: OutflowNormalization,
+    pub solver: Solver,
+    pub relaxation: f64, // over-relaxation factor of the Gauss-Seidel solver, 1.0 for none; must be in (0, 2)
+    pub initial_ranks: Option<Vec<f64>>, // uniform distribution when not set
+    pub precision: Precision, // f32 iterates with the edge-list backend and the Jacobi solver
+    pub walks_per_node: usize, // Monte Carlo solver: walks started from each node with a teleportation target; at least 2
+    pub walk_seed: u64,        // Monte Carlo solver: seed of the walks, the same for every frame
+    pub max_hops: usize,       // hop limit of bounded propagation, which takes the damping factor as its spreading factor
+    pub node_capacity: f64,    // rank (mass) a node may emit or receive per step in the capacity-limited variant
+    pub max_source_share: f64, // share of a node's incoming rank (mass) per step a single source may bring in the source-clipped variant
+    pub rank_floor: f64,       // rank every active node is guaranteed, 0.0 for none; see floored_targets
+}
+
+impl Default for RankConfig {
+    fn default() -> Self {
+        RankConfig {
+            damping_factor: 0.5,
+            num_of_iterations: 10,
+            tolerance: 0.0,
+            dangling_policy: DanglingPolicy::Uniform,
+            backend: Backend::EdgeList,
+            outflow_normalization: OutflowNormalization::Lifetime,
+            solver: Solver::Jacobi,
+            relaxation: 1.0,
+            initial_ranks: None,
+            precision: Precision::F64,
+            walks_per_node: 100,
+            walk_seed: DEFAULT_SEED,
+            max_hops: 3,
+            node_capacity: 0.25,
+            max_source_share: 0.5,
+            rank_floor: 0.0,
+        }
+    }
+}
+
+impl RankConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn damping_factor(mut self, damping_factor: f64) -> Self {
+        self.damping_factor = damping_factor;
+        self
+    }
+
+    pub fn iterations(mut self, num_of_iterations: usize) -> Self {
+        self.num_of_iterations = num_of_iterations;
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn dangling_policy(mut self, dangling_policy: DanglingPolicy) -> Self {
+        self.dangling_policy = dangling_policy;
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn outflow_normalization(mut self, outflow_normalization: OutflowNormalization) -> Self {
+        self.outflow_normalization = outflow_normalization;
+        self
+    }
+
+    pub fn solver(mut self, solver: Solver) -> Self {
+        self.solver = solver;
+        self
+    }
+
+    pub fn relaxation(mut self, relaxation: f64) -> Self {
+        self.relaxation = relaxation;
+        self
+    }
+
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn walks_per_node(mut self, walks_per_node: usize) -> Self {
+        self.walks_per_node = walks_per_node;
+        self
+    }
+
+    pub fn walk_seed(mut self, walk_seed: u64) -> Self {
+        self.walk_seed = walk_seed;
+        self
+    }
+
+    pub fn max_hops(mut self, max_hops: usize) -> Self {
+        self.max_hops = max_hops;
+        self
+    }
+
+    pub fn node_capacity(mut self, node_capacity: f64) -> Self {
+        self.node_capacity = node_capacity;
+        self
+    }
+
+    pub fn max_source_share(mut self, max_source_share: f64) -> Self {
+        self.max_source_share = max_source_share;
+        self
+    }
+
+    pub fn rank_floor(mut self, rank_floor: f64) -> Self {
+        self.rank_floor = rank_floor;
+        self
+    }
+
+    pub fn initial_ranks(mut self, initial_ranks: Vec<f64>) -> Self {
+        self.initial_ranks = Some(initial_ranks);
+        self
+    }
+}
+
+// Per node, what its edge weights are divided by under the given normalization
+pub fn outflow_normalizers(graph: &CompiledGraph, normalization: OutflowNormalization) -> Vec<f64> {
+    let mut normalizers = vec![0.0; graph.num_of_nodes];
+    for edge in &graph.edges {
+        normalizers[edge.source.index()] += match normalization {
+            OutflowNormalization::Lifetime => edge.capacity,
+            OutflowNormalization::Current | OutflowNormalization::None => edge.weight,
+        };
+    }
+    if normalization == OutflowNormalization::None {
+        for normalizer in &mut normalizers {
+            *normalizer = normalizer.max(1.0);
+        }
+    }
+    normalizers
+}
+
+// Fraction of the source's rank (mass) an edge of the given weight carries per step, before damping
+pub fn edge_fraction(weight: f64, normalizer: f64) -> f64 {
+    if normalizer > 0.0 { weight / normalizer } else { 0.0 }
+}
+
+// Rank (mass) every compiled edge carries in one step from the given ranks: the damped share of
+// its source's rank that the source conducts, split over its edges by their fractions
+pub fn edge_flux(graph: &CompiledGraph, ranks: &[f64], config: &RankConfig) -> Vec<f64> {
+    let normalizers = outflow_normalizers(graph, config.outflow_normalization);
+    graph
+        .edges
+        .iter()
+        .map(|e| {
+            let source = e.source.index();
+            config.damping_factor * ranks[source] * graph.conductance(source) * edge_fraction(e.weight, normalizers[source])
+        })
+        .collect()
+}
+
+pub fn pagerank_variant(
+    graph: &CompiledGraph,
+    teleportation_targets: &[f64],
+    config: &RankConfig,
+) -> Vec<f64> {
+    pagerank_variant_converged(graph, teleportation_targets, config).0
+}
+
+// How the power iteration of a frame ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Convergence {
+    pub iterations: usize,
+    pub residual: f64, // L1 change of the last step
+}
+
+// pagerank_variant, also telling how it converged; None when no step was taken
+pub fn pagerank_variant_converged(graph: &CompiledGraph, teleportation_targets: &[f64], config: &RankConfig) -> (Vec<f64>, Option<Convergence>) {
+    let (graph, teleportation_targets) = rankable_inputs(graph, teleportation_targets);
+    let graph = graph.as_ref();
+    let mut iteration = RankIteration::new(graph, &teleportation_targets, config);
+    let mut convergence = None;
+    for step in iteration.by_ref() {
+        trace!("iteration={} residual={:.3e}", step.iteration, step.residual);
+        convergence = Some(Convergence { iterations: step.iteration, residual: step.residual });
+    }
+    if let Some(Convergence { iterations, residual }) = convergence {
+        let total_mass: f64 = iteration.ranks().iter().sum();
+        debug!("nodes={} edges={} iterations={} residual={:.3e} mass={:.6}", graph.num_of_nodes, graph.edges.len(), iterations, residual, total_mass);
+    }
+    (iteration.into_ranks(), convergence)
+}
+
+// Result of a single power iteration step
+#[derive(Debug, Clone)]
+pub struct RankStep {
+    pub iteration: usize, // 1-based
+    pub ranks: Vec<f64>,
+    pub residual: f64, // L1 change compared to the previous step
+    pub trace: StepTrace,
+}
+
+// Where each node's rank (mass) came from and went to during a step.
+// New rank = teleport_inflow + edge_inflow + dangling_inflow + event_adjustment;
+// (1 - damping) * old rank is teleported away, the rest leaves as edge_outflow + dangling_outflow.
+// Rank a node retains instead of conducting it counts as edge flow along a self-loop.
+#[derive(Debug, Clone)]
+pub struct StepTrace {
+    pub teleport_inflow: Vec<f64>,
+    pub edge_inflow: Vec<f64>,
+    pub dangling_inflow: Vec<f64>,
+    pub edge_outflow: Vec<f64>,
+    pub dangling_outflow: Vec<f64>,
+    pub event_adjustment: Vec<f64>, // net effect of reward and penalty events, including renormalization
+}
+
+// The solver loop as an iterator, one item per power iteration step.
+// Stops after config.num_of_iterations steps or once the residual drops below config.tolerance.
+pub struct RankIteration<'a> {
+    graph: &'a CompiledGraph,
+    teleportation_targets: &'a [f64],
+    config: &'a RankConfig,
+    rank_values: Vec<f64>,
+    outflow_normalizers: Vec<f64>,
+    edge_flow: Option<DenseMatrix>, // dense backend only
+    incoming: Vec<Vec<(usize, f64)>>, // Gauss-Seidel solver only: (source, fraction of its rank) per edge into each node
+    iteration: usize,
+    converged: bool,
+}
+
+impl<'a> RankIteration<'a> {
+    pub fn new(graph: &'a CompiledGraph, teleportation_targets: &'a [f64], config: &'a RankConfig) -> Self {
+        // Rank flow is analogous to mass flow. 
+        // Total rank (mass) is conserved; --audit checks it frame by frame.
+        // Expert nodes have higher intrinsic rank (mass).
+        let num_of_nodes = graph.num_of_nodes;
+
+        // Initial rank (mass) distribution over nodes, uniform unless configured otherwise
+        let rank_values = match &config.initial_ranks {
+            Some(initial_ranks) => initial_ranks.clone(),
+            None => graph.uniform_ranks(),
+        };
+
+        let outflow_normalizers = outflow_normalizers(graph, config.outflow_normalization);
+
+        let edge_flow = match config.backend {
+            Backend::EdgeList => None,
+            Backend::Dense => Some(DenseMatrix::edge_flow(graph, config.outflow_normalization)),
+        };
+
+        let mut incoming = Vec::new();
+        if config.solver == Solver::GaussSeidel {
+            incoming = vec![Vec::new(); num_of_nodes];
+            for edge in &graph.edges {
+                let source = edge.source.index();
+                incoming[edge.target.index()].push((source, graph.conductance(source) * edge_fraction(edge.weight, outflow_normalizers[source])));
+            }
+        }
+
+        RankIteration {
+            graph,
+            teleportation_targets,
+            config,
+            rank_values,
+            outflow_normalizers,
+            edge_flow,
+            incoming,
+            iteration: 0,
+            converged: false,
+        }
+    }
+
+    // Rank values after the last yielded step (initial ranks before the first one)
+    pub fn ranks(&self) -> &[f64] {
+        &self.rank_values
+    }
+
+    pub fn into_ranks(self) -> Vec<f64> {
+        self.rank_values
+    }
+
+    fn step(&self) -> (Vec<f64>, StepTrace) {
+        match self.config.solver {
+            Solver::Jacobi | Solver::MonteCarlo | Solver::Exact => self.step_from(&self.rank_values, self.teleportation_targets),
+            Solver::GaussSeidel => self.step_in_place(),
+        }
+    }
+
+    // One Gauss-Seidel sweep: nodes are updated in index order, each from the freshest values of
+    // the others, optionally over-relaxed. The fixed point is the same as that of the Jacobi step.
+    // Outflows in the trace are those of the ranks at the start of the sweep, so the trace only
+    // balances exactly at convergence.
+    fn step_in_place(&self) -> (Vec<f64>, StepTrace) {
+        let num_of_nodes = self.graph.num_of_nodes;
+        let damping_factor = self.config.damping_factor;
+        let relaxation = self.config.relaxation;
+        let previous_mass: f64 = self.rank_values.iter().sum();
+
+        let mut outflow_values = vec![0.0; num_of_nodes];
+        for edge in &self.graph.edges {
+            outflow_values[edge.source.index()] += edge.weight;
+        }
+        // Fraction of a node's damped rank that it passes on but cannot along its edges
+        let dangling_fraction: Vec<f64> = (0..num_of_nodes)
+            .map(|i| self.graph.conductance(i) * (1.0 - edge_fraction(outflow_values[i], self.outflow_normalizers[i])))
+            .collect();
+
+        let mut ranks = self.rank_values.clone();
+        let mut dangling_values: Vec<f64> = (0..num_of_nodes).map(|i| damping_factor * ranks[i] * dangling_fraction[i]).collect();
+        let edge_outflow: Vec<f64> = (0..num_of_nodes).map(|i| damping_factor * ranks[i] - dangling_values[i]).collect();
+        let dangling_outflow = dangling_values.clone();
+        let mut dangling_total: f64 = dangling_values.iter().sum();
+        let total_teleport: f64 = self.teleportation_targets.iter().sum();
+
+        let teleport_inflow: Vec<f64> = self.teleportation_targets.iter().map(|&t| (1.0 - damping_factor) * t).collect();
+        let num_of_active = self.graph.num_of_active_nodes();
+        let mut edge_inflow = vec![0.0; num_of_nodes];
+        let mut dangling_inflow = vec![0.0; num_of_nodes];
+        for i in 0..num_of_nodes {
+            // What the node retains stays as if along a self-loop
+            let retained = damping_factor * ranks[i] * (1.0 - self.graph.conductance(i));
+            edge_inflow[i] = retained + damping_factor * self.incoming[i].iter().map(|&(source, fraction)| ranks[source] * fraction).sum::<f64>();
+            dangling_inflow[i] = match self.config.dangling_policy {
+                DanglingPolicy::Uniform if self.graph.is_active(i) => dangling_total / num_of_active as f64,
+                DanglingPolicy::Uniform => 0.0,
+                DanglingPolicy::Teleport => dangling_total * self.teleportation_targets[i] / total_teleport,
+                DanglingPolicy::Retain => dangling_values[i],
+            };
+            let updated = teleport_inflow[i] + edge_inflow[i] + dangling_inflow[i];
+            ranks[i] = (1.0 - relaxation) * ranks[i] + relaxation * updated;
+
+            let dangling = damping_factor * ranks[i] * dangling_fraction[i];
+            dangling_total += dangling - dangling_values[i];
+            dangling_values[i] = dangling;
+        }
+
+        // In-place updates do not conserve mass within a sweep; rescaling does not move the fixed point
+        let mass: f64 = ranks.iter().sum();
+        if mass > 0.0 {
+            for rank in &mut ranks {
+                *rank *= previous_mass / mass;
+            }
+        }
+
+        let event_adjustment = vec![0.0; num_of_nodes];
+        let trace = StepTrace { teleport_inflow, edge_inflow, dangling_inflow, edge_outflow, dangling_outflow, event_adjustment };
+        (ranks, trace)
+    }
+
+    // Advances mass that only enters through `tagged_targets` (a part of the teleportation targets)
+    // by one step. The step is linear, so running it next to the main iteration from zero mass
+    // tracks how much of every node's rank originates from that part of the teleportation inflow.
+    pub fn step_tagged(&self, tagged_mass: &[f64], tagged_targets: &[f64]) -> Vec<f64> {
+        self.step_from(tagged_mass, tagged_targets).0
+    }
+
+    fn step_from(&self, rank_values: &[f64], teleport_inflow_targets: &[f64]) -> (Vec<f64>, StepTrace) {
+        let num_of_nodes = self.graph.num_of_nodes;
+        let damping_factor = self.config.damping_factor;
+        let outflow_normalizers = &self.outflow_normalizers;
+
+        // New rank (mass) values are calculated starting with teleportation inflow contribution
+        let teleport_inflow = teleport_inflow_targets
+            .iter()
+            .map(|&t| (1.0 - damping_factor) * t)
+            .collect::<Vec<f64>>();
+        let mut new_rank_values = teleport_inflow.clone();
+
+        // Only the conducted share of a node's rank leaves it; the rest it retains
+        let previous_values = rank_values;
+        let conducted_values: Vec<f64> = rank_values.iter().enumerate().map(|(i, &rank)| rank * self.graph.conductance(i)).collect();
+        let rank_values = &conducted_values[..];
+
+        // Rank (mass) outflows along edges with speed propotional to edge weights
+        let mut outflow_values = vec![0.0; num_of_nodes];
+        let mut edge_inflow = vec![0.0; num_of_nodes];
+        match &self.edge_flow {
+            Some(edge_flow) => {
+                for edge in &self.graph.edges {
+                    outflow_values[edge.source.index()] += edge.weight;
+                }
+                let flows = edge_flow.mul_vec(rank_values);
+                for (i, flow) in flows.into_iter().enumerate() {
+                    let flow = damping_factor * flow;
+                    new_rank_values[i] += flow;
+                    edge_inflow[i] = flow;
+                }
+            }
+            None => {
+                for edge in &self.graph.edges {
+                    let source = edge.source.index();
+                    let w = edge.weight;
+                    outflow_values[source] += w;
+                    let flow =
+                        damping_factor *
+                        rank_values[source] *
+                        edge_fraction(w, outflow_normalizers[source]);
+                    new_rank_values[edge.target.index()] += flow;
+                    edge_inflow[edge.target.index()] += flow;
+                }
+            }
+        }
+
+        // Dangling rank (mass) is whatever the node could not push along its edges
+        let mut edge_outflow = vec![0.0; num_of_nodes];
+        let mut dangling_values = vec![0.0; num_of_nodes];
+        for i in 0..num_of_nodes {
+            let rank = rank_values[i];
+            let normalizer = outflow_normalizers[i];
+            if normalizer > 0.0 {
+                let outflow = outflow_values[i];
+                let allocated = damping_factor * rank * (outflow / normalizer);
+                edge_outflow[i] = allocated;
+                dangling_values[i] = damping_factor * rank - allocated;
+            } else {
+                dangling_values[i] = damping_factor * rank;
+            }
+        }
+        let dangling_inflow = redistribute_dangling(self.graph, &dangling_values, self.teleportation_targets, self.config.dangling_policy);
+        for (new_rank, &d) in new_rank_values.iter_mut().zip(dangling_inflow.iter()) {
+            *new_rank += d;
+        }
+
+        // Retained rank (mass) is traced as if it went along a self-loop
+        if !self.graph.conductances.is_empty() {
+            for i in 0..num_of_nodes {
+                let retained = damping_factor * (previous_values[i] - rank_values[i]);
+                new_rank_values[i] += retained;
+                edge_inflow[i] += retained;
+                edge_outflow[i] += retained;
+            }
+        }
+
+        let trace = StepTrace {
+            teleport_inflow,
+            edge_inflow,
+            dangling_inflow,
+            edge_outflow,
+            dangling_outflow: dangling_values,
+            event_adjustment: vec![0.0; num_of_nodes],
+        };
+        (new_rank_values, trace)
+    }
+}
+
+impl Iterator for RankIteration<'_> {
+    type Item = RankStep;
+
+    fn next(&mut self) -> Option<RankStep> {
+        if self.converged || self.iteration >= self.config.num_of_iterations {
+            return None;
+        }
+
+        let (mut new_rank_values, mut trace) = self.step();
+        if !self.graph.rank_adjustments.is_empty() {
+            trace.event_adjustment = apply_rank_adjustments(&mut new_rank_values, &self.graph.rank_adjustments);
+        }
+        let residual: f64 = new_rank_values
+            .iter()
+            .zip(self.rank_values.iter())
+            .map(|(new, old)| (new - old).abs())
+            .sum();
+        self.rank_values = new_rank_values;
+        self.iteration += 1;
+        self.converged = residual < self.config.tolerance;
+
+        Some(RankStep {
+            iteration: self.iteration,
+            ranks: self.rank_values.clone(),
+            residual,
+            trace,
+        })
+    }
+}
+
+// Adds the per-iteration reward and penalty amounts (ranks cannot drop below zero), then rescales
+// so that the total rank (mass) is unchanged. Returns the net change of every node.
+pub(crate) fn apply_rank_adjustments(rank_values: &mut [f64], adjustments: &[f64]) -> Vec<f64> {
+    let mass: f64 = rank_values.iter().sum();
+    let before = rank_values.to_vec();
+    for (rank, adjustment) in rank_values.iter_mut().zip(adjustments) {
+        *rank = (*rank + adjustment).max(0.0);
+    }
+    let adjusted_mass: f64 = rank_values.iter().sum();
+    if adjusted_mass > 0.0 {
+        for rank in rank_values.iter_mut() {
+            *rank *= mass / adjusted_mass;
+        }
+    }
+    rank_values.iter().zip(before).map(|(after, before)| after - before).collect()
+}
+
+// Share of the dangling rank (mass) each node receives
+pub(crate) fn redistribute_dangling(graph: &CompiledGraph, dangling_values: &[f64], teleportation_targets: &[f64], policy: DanglingPolicy) -> Vec<f64> {
+    let dangling_rank: f64 = dangling_values.iter().sum();
+    match policy {
+        DanglingPolicy::Uniform => {
+            let dangling_share = dangling_rank / graph.num_of_active_nodes() as f64;
+            (0..dangling_values.len()).map(|node| if graph.is_active(node) { dangling_share } else { 0.0 }).collect()
+        }
+        DanglingPolicy::Teleport => {
+            let total_teleport: f64 = teleportation_targets.iter().sum();
+            teleportation_targets.iter().map(|&t| dangling_rank * t / total_teleport).collect()
+        }
+        DanglingPolicy::Retain => dangling_values.to_vec(),
+    }
+}
+
+// Common interface for everything that turns a frame's graph into a rank vector,
+// so the custom PageRank variant can be run side by side with other algorithms.
+// Algorithms are shared by the threads that rank frames in parallel.
+pub trait RankingAlgorithm: Sync {
+    fn name(&self) -> &str;
+    fn rank(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Vec<f64>;
+
+    // Incremental ranking that continues from the ranks of the previous frame, with `steps`
+    // iterations instead of the algorithm's own when given. Algorithms without iterations to
+    // warm-start rank the frame from scratch.
+    fn rank_from(&self, graph: &CompiledGraph, teleportation_targets: &[f64], _initial_ranks: &[f64], _steps: Option<usize>) -> Vec<f64> {
+        self.rank(graph, teleportation_targets)
+    }
+
+    // Ranks with how the iteration converged, for monitoring; None for algorithms without a
+    // power iteration to report on
+    fn rank_converged(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> (Vec<f64>, Option<Convergence>) {
+        (self.rank(graph, teleportation_targets), None)
+    }
+
+    // rank_converged starting from the given ranks instead of the algorithm's own start
+    fn rank_converged_from(&self, graph: &CompiledGraph, teleportation_targets: &[f64], initial_ranks: &[f64]) -> (Vec<f64>, Option<Convergence>) {
+        (self.rank_from(graph, teleportation_targets, initial_ranks, None), None)
+    }
+
+    // The same algorithm with another damping factor, for damping schedules; None for algorithms
+    // without one, which rank every frame as they are
+    fn with_damping(&self, _damping_factor: f64) -> Option<Box<dyn RankingAlgorithm>> {
+        None
+    }
+
+    // Rank (mass) moved along every compiled edge by one more step from the given (final) ranks;
+    // None for algorithms whose scores do not flow along edges
+    fn edge_flux(&self, _graph: &CompiledGraph, _ranks: &[f64]) -> Option<Vec<f64>> {
+        None
+    }
+}
+
+// The effective transition matrix of the custom variant as (source, target, probability) triplets
+// in row order: the share of a node's damped rank (mass) that moves to each node per step, along
+// its edges, as dangling rank under the dangling policy or, for nodes that do not conduct all of
+// it, back to the node itself, so every row sums to 1. The ranks are
+// the fixed point of ranks = (1 - damping) * teleportation + damping * P^T ranks, apart from
+// reward and penalty events.
+pub fn transition_triplets(graph: &CompiledGraph, teleportation_targets: &[f64], config: &RankConfig) -> Vec<(usize, usize, f64)> {
+    let num_of_nodes = graph.num_of_nodes;
+    let normalizers = outflow_normalizers(graph, config.outflow_normalization);
+    let mut outflow = vec![0.0; num_of_nodes];
+    for edge in &graph.edges {
+        outflow[edge.source.index()] += edge.weight;
+    }
+    let total_teleport: f64 = teleportation_targets.iter().sum();
+    let num_of_active = graph.num_of_active_nodes();
+
+    let mut triplets: Vec<(usize, usize, f64)> = graph.edges.iter().map(|edge| {
+        let source = edge.source.index();
+        (source, edge.target.index(), graph.conductance(source) * edge_fraction(edge.weight, normalizers[source]))
+    }).collect();
+    for source in 0..num_of_nodes {
+        // The share a node does not conduct stays on it
+        let conductance = graph.conductance(source);
+        if conductance < 1.0 {
+            triplets.push((source, source, 1.0 - conductance));
+        }
+        let dangling = conductance * (1.0 - edge_fraction(outflow[source], normalizers[source]));
+        if dangling == 0.0 {
+            continue;
+        }
+        match config.dangling_policy {
+            DanglingPolicy::Uniform => triplets.extend((0..num_of_nodes).filter(|&i| graph.is_active(i)).map(|i| (source, i, dangling / num_of_active as f64))),
+            DanglingPolicy::Teleport => triplets.extend((0..num_of_nodes).filter(|&i| teleportation_targets[i] > 0.0).map(|i| (source, i, dangling * teleportation_targets[i] / total_teleport))),
+            DanglingPolicy::Retain => triplets.push((source, source, dangling)),
+        }
+    }
+    // Parallel edges and dangling rank to a node's own successors add up to one entry
+    triplets.sort_by_key(|&(source, target, _)| (source, target));
+    let mut merged: Vec<(usize, usize, f64)> = Vec::with_capacity(triplets.len());
+    for (source, target, probability) in triplets {
+        match merged.last_mut() {
+            Some(last) if (last.0, last.1) == (source, target) => last.2 += probability,
+            _ => merged.push((source, target, probability)),
+        }
+    }
+    merged.retain(|&(_, _, probability)| probability != 0.0);
+    merged
+}
+
+// The PageRank variant's fixed point without iterating: the ranks solve
+// (I - d·F - d·D) r = (1 - d)·t, where F is the edge flow operator and D sends every node's dangling
+// share where the dangling policy puts it. There is no iteration count or tolerance to tune. Reward
+// and penalty amounts act on the solution once, not on every step as in power iteration. None when
+// the system is singular, which takes a damping factor of 1.
+pub fn exact_ranks(graph: &CompiledGraph, teleportation_targets: &[f64], config: &RankConfig) -> Option<Vec<f64>> {
+    let damping_factor = config.damping_factor;
+    let mut system = DenseMatrix::zeros(graph.num_of_nodes);
+    for i in 0..graph.num_of_nodes {
+        system.add(i, i, 1.0);
+    }
+    for (source, target, probability) in transition_triplets(graph, teleportation_targets, config) {
+        system.add(target, source, -damping_factor * probability);
+    }
+    let rhs: Vec<f64> = teleportation_targets.iter().map(|&t| (1.0 - damping_factor) * t).collect();
+    // Rounding can leave ranks that should be 0 slightly negative
+    let mut ranks: Vec<f64> = system.solve(&rhs)?.into_iter().map(|r| r.max(0.0)).collect();
+    if !graph.rank_adjustments.is_empty() {
+        apply_rank_adjustments(&mut ranks, &graph.rank_adjustments);
+    }
+    Some(ranks)
+}
+
+pub struct PageRankVariant {
+    pub config: RankConfig,
+}
+
+// Teleportation vector that guarantees every active node `config.rank_floor` of the rank. Every
+// step hands out (1 - d) t of it, so mixing λ = floor * n / (1 - d) of the uniform distribution
+// over the n active nodes into t leaves each at least the floor, and the rest of the rank goes
+// where t and the edges send it. λ is capped at 1, which caps the floor at (1 - d) / n; rank
+// adjustments (penalties) can still take a node below it.
+pub fn floored_targets(graph: &CompiledGraph, teleportation_targets: &[f64], config: &RankConfig) -> Vec<f64> {
+    let num_of_active = graph.num_of_active_nodes();
+    if config.rank_floor <= 0.0 || num_of_active == 0 {
+        return teleportation_targets.to_vec();
+    }
+    let uniform_share = (config.rank_floor * num_of_active as f64 / (1.0 - config.damping_factor)).min(1.0);
+    if config.rank_floor * num_of_active as f64 > 1.0 - config.damping_factor {
+        warn!("a rank floor of {} is more than {} active nodes can get at a damping factor of {}, lowering it to {:.6}", config.rank_floor, num_of_active, config.damping_factor, (1.0 - config.damping_factor) / num_of_active as f64);
+    }
+    teleportation_targets
+        .iter()
+        .enumerate()
+        .map(|(node, t)| (1.0 - uniform_share) * t + if graph.is_active(node) { uniform_share / num_of_active as f64 } else { 0.0 })
+        .collect()
+}
+
+// Inputs no ranking can be computed from; scenarios are validated before they get this far
+#[derive(Debug, Clone, PartialEq)]
+pub enum RankError {
+    TargetCount { nodes: usize, targets: usize },
+    InvalidTarget(NodeId), // negative or not finite
+    NodeOutOfRange { edge: usize, node: NodeId, nodes: usize }, // by compiled edge index
+    InvalidWeight(usize), // compiled edge weighing less than 0 or not finite
+}
+
+impl fmt::Display for RankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RankError::TargetCount { nodes, targets } => write!(f, "{} teleportation targets for {} nodes", targets, nodes),
+            RankError::InvalidTarget(node) => write!(f, "teleportation target of node {} is negative or not a number", node),
+            RankError::NodeOutOfRange { edge, node, nodes } => write!(f, "edge {} refers to node {}, but the graph has {} nodes", edge, node, nodes),
+            RankError::InvalidWeight(edge) => write!(f, "edge {} has a negative or non-finite weight", edge),
+        }
+    }
+}
+
+impl std::error::Error for RankError {}
+
+// What every algorithm takes for granted about its inputs
+pub fn check_rank_inputs(graph: &CompiledGraph, teleportation_targets: &[f64]) -> Result<(), RankError> {
+    if teleportation_targets.len() != graph.num_of_nodes {
+        return Err(RankError::TargetCount { nodes: graph.num_of_nodes, targets: teleportation_targets.len() });
+    }
+    if let Some(node) = teleportation_targets.iter().position(|&t| !(t >= 0.0 && t.is_finite())) {
+        return Err(RankError::InvalidTarget(NodeId(node)));
+    }
+    for (id, edge) in graph.edges.iter().enumerate() {
+        if let Some(&node) = [edge.source, edge.target].iter().find(|node| node.index() >= graph.num_of_nodes) {
+            return Err(RankError::NodeOutOfRange { edge: id, node, nodes: graph.num_of_nodes });
+        }
+        if !(edge.weight >= 0.0 && edge.weight.is_finite()) {
+            return Err(RankError::InvalidWeight(id));
+        }
+    }
+    Ok(())
+}
+
+// Ranks of a graph that does not come from a validated scenario: an error instead of a panic or
+// NaN ranks when the inputs do not fit together
+pub fn try_rank(algorithm: &dyn RankingAlgorithm, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Result<Vec<f64>, RankError> {
+    check_rank_inputs(graph, teleportation_targets)?;
+    Ok(algorithm.rank(graph, teleportation_targets))
+}
+
+// Degenerate graphs have well-defined ranks rather than NaN or lost rank (mass). A graph without
+// nodes has no ranks. Without edges, or with every node dangling, the rank goes where the
+// dangling policy and teleportation send it (uniform from uniform targets). A graph with no active
+// node is ranked as if all were active, as Scenario::active_nodes has it, and teleportation
+// targets that sum to 0 are replaced by the uniform distribution over the active nodes.
+pub fn rankable_inputs<'a>(graph: &'a CompiledGraph, teleportation_targets: &'a [f64]) -> (Cow<'a, CompiledGraph>, Cow<'a, [f64]>) {
+    let graph = if graph.num_of_nodes > 0 && graph.num_of_active_nodes() == 0 {
+        Cow::Owned(CompiledGraph { active_nodes: Vec::new(), ..graph.clone() })
+    } else {
+        Cow::Borrowed(graph)
+    };
+    let targets = if teleportation_targets.iter().sum::<f64>() > 0.0 {
+        Cow::Borrowed(teleportation_targets)
+    } else {
+        Cow::Owned(graph.uniform_ranks())
+    };
+    (graph, targets)
+}
+
+impl RankingAlgorithm for PageRankVariant {
+    fn name(&self) -> &str {
+        PAGERANK_VARIANT.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Vec<f64> {
+        let (graph, teleportation_targets) = rankable_inputs(graph, teleportation_targets);
+        let graph = graph.as_ref();
+        let teleportation_targets = &floored_targets(graph, &teleportation_targets, &self.config);
+        if self.config.solver == Solver::MonteCarlo {
+            return monte_carlo_ranks(graph, teleportation_targets, &self.config).ranks;
+        }
+        if self.config.solver == Solver::Exact {
+            let solved = if graph.num_of_nodes <= EXACT_MAX_NODES { exact_ranks(graph, teleportation_targets, &self.config) } else { None };
+            if let Some(ranks) = solved {
+                return ranks;
+            }
+            warn!("cannot solve for the ranks of {} nodes exactly (at most {}, with a damping factor below 1), iterating instead", graph.num_of_nodes, EXACT_MAX_NODES);
+            // The targets are floored already
+            return PageRankVariant { config: self.config.clone().solver(Solver::Jacobi).rank_floor(0.0) }.rank(graph, teleportation_targets);
+        }
+        match self.config.precision {
+            Precision::F64 => pagerank_variant(graph, teleportation_targets, &self.config),
+            Precision::F32 => pagerank_in::<f32>(graph, teleportation_targets, &self.config).into_iter().map(f64::from).collect(),
+        }
+    }
+
+    fn rank_from(&self, graph: &CompiledGraph, teleportation_targets: &[f64], initial_ranks: &[f64], steps: Option<usize>) -> Vec<f64> {
+        let mut config = self.config.clone().initial_ranks(initial_ranks.to_vec());
+        if let Some(steps) = steps {
+            config = config.iterations(steps);
+        }
+        PageRankVariant { config }.rank(graph, teleportation_targets)
+    }
+
+    fn rank_converged(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> (Vec<f64>, Option<Convergence>) {
+        match (self.config.solver, self.config.precision) {
+            (Solver::MonteCarlo | Solver::Exact, _) | (_, Precision::F32) => (self.rank(graph, teleportation_targets), None),
+            _ => {
+                let (graph, teleportation_targets) = rankable_inputs(graph, teleportation_targets);
+                pagerank_variant_converged(&graph, &floored_targets(&graph, &teleportation_targets, &self.config), &self.config)
+            }
+        }
+    }
+
+    fn rank_converged_from(&self, graph: &CompiledGraph, teleportation_targets: &[f64], initial_ranks: &[f64]) -> (Vec<f64>, Option<Convergence>) {
+        PageRankVariant { config: self.config.clone().initial_ranks(initial_ranks.to_vec()) }.rank_converged(graph, teleportation_targets)
+    }
+
+    fn with_damping(&self, damping_factor: f64) -> Option<Box<dyn RankingAlgorithm>> {
+        Some(Box::new(PageRankVariant { config: self.config.clone().damping_factor(damping_factor) }))
+    }
+
+    fn edge_flux(&self, graph: &CompiledGraph, ranks: &[f64]) -> Option<Vec<f64>> {
+        Some(edge_flux(graph, ranks, &self.config))
+    }
+}
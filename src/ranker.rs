@@ -0,0 +1,242 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use log::debug;
+
+use crate::graph::CompiledGraph;
+use crate::montecarlo::WalkGraph;
+use crate::rank::{edge_fraction, outflow_normalizers, DanglingPolicy, RankConfig, RankingAlgorithm};
+use crate::random::Rng;
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+
+// Random walks that estimate a frame's dangling rank (mass) for a local score
+pub const DEFAULT_LOCAL_WALKS: usize = 10_000;
+
+// Ranks from someone's perspective: teleportation starts at the seed nodes in proportion to their
+// weights instead of at the experts. Seeds are merged, sorted and normalized to sum to 1, so that
+// perspectives given with other weights or in another order are the same query.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Personalization {
+    seeds: Vec<(usize, u64)>, // node and the bits of its normalized weight
+}
+
+impl Personalization {
+    pub fn new(seeds: &[(NodeId, f64)]) -> Result<Self, String> {
+        if let Some((node, weight)) = seeds.iter().find(|(_, w)| !(w.is_finite() && *w > 0.0)) {
+            return Err(format!("seed {} must have a positive weight, not {}", node, weight));
+        }
+        let total: f64 = seeds.iter().map(|(_, w)| w).sum();
+        if total <= 0.0 {
+            return Err("no seeds".to_string());
+        }
+        let mut merged: Vec<(usize, f64)> = Vec::new();
+        for &(node, weight) in seeds {
+            match merged.iter_mut().find(|(n, _)| *n == node.index()) {
+                Some((_, merged_weight)) => *merged_weight += weight,
+                None => merged.push((node.index(), weight)),
+            }
+        }
+        merged.sort_by_key(|&(node, _)| node);
+        Ok(Personalization { seeds: merged.into_iter().map(|(node, weight)| (node, (weight / total).to_bits())).collect() })
+    }
+
+    // The perspective of a node: its endorsees at the time, weighted by its current trust in them,
+    // or the node itself when it endorses no one (see Scenario::local_teleportation_targets)
+    pub fn local(scenario: &Scenario, node: NodeId, time: Time) -> Self {
+        let seeds: Vec<(NodeId, f64)> = scenario.local_teleportation_targets(node, time).into_iter().enumerate().filter(|&(_, t)| t > 0.0).map(|(i, t)| (NodeId(i), t)).collect();
+        Personalization::new(&seeds).unwrap()
+    }
+
+    // Seeds in node order with their normalized weights
+    pub fn seeds(&self) -> impl Iterator<Item = (NodeId, f64)> + '_ {
+        self.seeds.iter().map(|&(node, bits)| (NodeId(node), f64::from_bits(bits)))
+    }
+
+    pub fn teleportation_targets(&self, num_of_nodes: usize) -> Vec<f64> {
+        let mut targets = vec![0.0; num_of_nodes];
+        for (node, weight) in self.seeds() {
+            targets[node.index()] = weight;
+        }
+        targets
+    }
+}
+
+// When and how the PageRank variant's score of a single node is computed locally instead of
+// ranking the whole frame
+#[derive(Debug, Clone)]
+pub struct LocalScoring {
+    pub config: RankConfig,
+    pub above_nodes: usize, // scenarios with more nodes than this are scored locally
+    pub tolerance: f64,     // residual the reverse push leaves per node; smaller is more exact and explores more of the graph
+    pub walks: usize,
+}
+
+// Trust scores of single nodes at single times. Frames are ranked as a whole by the algorithm, from
+// scratch (without the history that warm starts and dynamic authority build on), and every rank
+// vector is kept for later queries at the same time. With local scoring, large scenarios are
+// instead scored one node at a time from the part of the graph the node's rank comes from.
+pub struct Ranker<'a> {
+    scenario: &'a Scenario,
+    algorithm: &'a dyn RankingAlgorithm,
+    local: Option<LocalScoring>,
+    ranks: Mutex<HashMap<u64, Vec<f64>>>, // rank vectors by the bits of their time
+    personalized: Mutex<HashMap<(Personalization, u64), Vec<f64>>>,
+}
+
+impl<'a> Ranker<'a> {
+    pub fn new(scenario: &'a Scenario, algorithm: &'a dyn RankingAlgorithm) -> Self {
+        Ranker { scenario, algorithm, local: None, ranks: Mutex::new(HashMap::new()), personalized: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn local(mut self, local: LocalScoring) -> Self {
+        self.local = Some(local);
+        self
+    }
+
+    // Ranks already computed elsewhere, e.g. a frame of a run, for queries at that time
+    pub fn remember(&self, time: Time, ranks: Vec<f64>) {
+        self.ranks.lock().unwrap().insert(time.value().to_bits(), ranks);
+    }
+
+    pub fn ranks_at(&self, time: Time) -> Vec<f64> {
+        if let Some(ranks) = self.ranks.lock().unwrap().get(&time.value().to_bits()) {
+            return ranks.clone();
+        }
+        let ranks = self.algorithm.rank(&self.frame_graph(time), &self.scenario.teleportation_targets(time));
+        self.remember(time, ranks.clone());
+        ranks
+    }
+
+    // Looked up when the time's ranks are known, estimated locally when the scenario is large
+    // enough, and otherwise taken from ranking the frame
+    pub fn score_of(&self, node: NodeId, time: Time) -> f64 {
+        if let Some(ranks) = self.ranks.lock().unwrap().get(&time.value().to_bits()) {
+            return ranks[node.index()];
+        }
+        match &self.local {
+            Some(local) if self.scenario.num_of_nodes > local.above_nodes => local_score(&self.frame_graph(time), &self.scenario.teleportation_targets(time), node, local),
+            _ => self.ranks_at(time)[node.index()],
+        }
+    }
+
+    // Whether score_of estimates the node's score at the time rather than looking it up or ranking the frame
+    pub fn is_local(&self, time: Time) -> bool {
+        self.local.as_ref().is_some_and(|local| self.scenario.num_of_nodes > local.above_nodes) && !self.ranks.lock().unwrap().contains_key(&time.value().to_bits())
+    }
+
+    // Ranks of the frame at the time with teleportation at the seeds, kept for repeated queries
+    pub fn personalized_ranks(&self, personalization: &Personalization, time: Time) -> Vec<f64> {
+        let key = (personalization.clone(), time.value().to_bits());
+        if let Some(ranks) = self.personalized.lock().unwrap().get(&key) {
+            return ranks.clone();
+        }
+        let ranks = self.algorithm.rank(&self.frame_graph(time), &personalization.teleportation_targets(self.scenario.num_of_nodes));
+        self.personalized.lock().unwrap().insert(key, ranks.clone());
+        ranks
+    }
+
+    // How much `truster` should trust `trustee` at the time: the trustee's rank with teleportation
+    // at the truster's endorsees. Queries from the same truster at the same time share one ranking.
+    pub fn local_trust(&self, truster: NodeId, trustee: NodeId, time: Time) -> f64 {
+        self.personalized_ranks(&Personalization::local(self.scenario, truster, time), time)[trustee.index()]
+    }
+
+    fn frame_graph(&self, time: Time) -> CompiledGraph {
+        self.scenario.frame_graph(time, &self.scenario.edge_weights(time)).unwrap()
+    }
+}
+
+// Estimate of one node's rank at the fixed point of the PageRank variant. A reverse push
+// (R. Andersen et al., Local computation of PageRank contributions, 2007) walks the edges backwards
+// from the node and finds, for every node upstream, how much of a unit of rank (mass) injected there
+// reaches the node; contributions over k edges are damped by damping^k, so the push stops close to
+// the node. The rank injected everywhere is the teleportation inflow plus the redistributed dangling
+// rank, whose total is a single number per frame that random walks estimate. Dangling rank that
+// the retain policy keeps is followed as a self-loop instead. Reward and penalty events are left out.
+pub fn local_score(graph: &CompiledGraph, teleportation_targets: &[f64], node: NodeId, local: &LocalScoring) -> f64 {
+    let config = &local.config;
+    let num_of_nodes = graph.num_of_nodes;
+    let damping_factor = config.damping_factor;
+
+    // Setting up the edges is linear in the graph, but cheap next to iterating over them
+    let normalizers = outflow_normalizers(graph, config.outflow_normalization);
+    let mut outflow = vec![0.0; num_of_nodes];
+    let mut incoming: Vec<Vec<(usize, f64)>> = vec![Vec::new(); num_of_nodes]; // (source, damped fraction of its rank)
+    for edge in &graph.edges {
+        let source = edge.source.index();
+        outflow[source] += edge.weight;
+        incoming[edge.target.index()].push((source, damping_factor * edge_fraction(edge.weight, normalizers[source])));
+    }
+    let dangling_fraction: Vec<f64> = (0..num_of_nodes).map(|i| 1.0 - edge_fraction(outflow[i], normalizers[i])).collect();
+    if config.dangling_policy == DanglingPolicy::Retain {
+        for (i, &fraction) in dangling_fraction.iter().enumerate().filter(|&(_, &fraction)| fraction > 0.0) {
+            incoming[i].push((i, damping_factor * fraction));
+        }
+    }
+
+    let mut contributions: HashMap<usize, f64> = HashMap::new();
+    let mut residuals: HashMap<usize, f64> = HashMap::from([(node.index(), 1.0)]);
+    let mut queue = VecDeque::from([node.index()]);
+    while let Some(target) = queue.pop_front() {
+        let residual = residuals.remove(&target).unwrap_or(0.0);
+        *contributions.entry(target).or_default() += residual;
+        for &(source, fraction) in &incoming[target] {
+            let pending = residuals.entry(source).or_default();
+            let queued = *pending > local.tolerance;
+            *pending += fraction * residual;
+            if !queued && *pending > local.tolerance {
+                queue.push_back(source);
+            }
+        }
+    }
+
+    let total_teleport: f64 = teleportation_targets.iter().sum();
+    let num_of_active = graph.num_of_active_nodes();
+    let dangling_rank = match config.dangling_policy {
+        DanglingPolicy::Retain => 0.0,
+        _ => damping_factor * mean_dangling_fraction(graph, teleportation_targets, &dangling_fraction, local),
+    };
+    let dangling_share = |i: usize| match config.dangling_policy {
+        DanglingPolicy::Uniform if graph.is_active(i) => 1.0 / num_of_active as f64,
+        DanglingPolicy::Teleport => teleportation_targets[i] / total_teleport,
+        _ => 0.0,
+    };
+    let score = contributions.iter().map(|(&i, &c)| c * ((1.0 - damping_factor) * teleportation_targets[i] + dangling_rank * dangling_share(i))).sum();
+    debug!("node={} explored={} dangling_rank={:.6} score={:.6}", node.index(), contributions.len() + residuals.len(), dangling_rank, score);
+    score
+}
+
+// Where a walk that restarts at the teleportation targets with probability 1 - damping ends is
+// distributed like the ranks, so the mean dangling fraction at the ends weighs nodes by rank
+fn mean_dangling_fraction(graph: &CompiledGraph, teleportation_targets: &[f64], dangling_fraction: &[f64], local: &LocalScoring) -> f64 {
+    let config = &local.config;
+    let walk_graph = WalkGraph::new(graph, config);
+    let total_teleport: f64 = teleportation_targets.iter().sum();
+    let mut cumulative = 0.0;
+    let teleport_cumulative: Vec<f64> = teleportation_targets
+        .iter()
+        .map(|&t| {
+            cumulative += t / total_teleport;
+            cumulative
+        })
+        .collect();
+    let sample_target = |draw: f64| teleport_cumulative.partition_point(|&c| c <= draw).min(graph.num_of_nodes - 1);
+    let active_nodes: Vec<usize> = (0..graph.num_of_nodes).filter(|&node| graph.is_active(node)).collect();
+    let mut rng = Rng::new(config.walk_seed);
+    let walks = local.walks.max(1);
+    let mut total = 0.0;
+    for _ in 0..walks {
+        let mut node = sample_target(rng.next_f64());
+        while rng.chance(config.damping_factor) {
+            let draw = rng.next_f64();
+            node = match walk_graph.follow(node, draw) {
+                Some(target) => target,
+                None if config.dangling_policy == DanglingPolicy::Uniform => active_nodes[rng.below(active_nodes.len())],
+                None => sample_target(rng.next_f64()),
+            };
+        }
+        total += dangling_fraction[node];
+    }
+    total / walks as f64
+}
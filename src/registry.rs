@@ -0,0 +1,235 @@
+use std::sync::RwLock;
+
+use crate::baselines::{Hits, Katz, StandardPageRank, TrustRank, WeightedInDegree};
+use crate::bipartite::CoHits;
+use crate::capacity::CapacityLimited;
+use crate::clipping::SourceClipped;
+use crate::propagation::BoundedPropagation;
+use crate::rank::{PageRankVariant, RankConfig, RankingAlgorithm};
+use crate::scenario::EXPERT_TELEPORT_FRACTION;
+use crate::temporalrank::TemporalPageRank;
+
+// A tunable parameter of a ranking algorithm; its value is read from the algorithm's defaults
+// or, for the custom variant, from the rank configuration in use
+pub struct ParameterInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub value: fn(&RankConfig) -> String,
+}
+
+// Everything the tool knows about a ranking algorithm. CLI names, frame labels and the
+// `algorithms list` command all derive from these entries.
+pub struct AlgorithmInfo {
+    pub id: &'static str,   // used on the command line, e.g. --compare pagerank,katz
+    pub name: &'static str, // shown in frame labels and reports
+    pub summary: &'static str,
+    pub uses_teleportation: bool, // whether the expert teleportation targets affect the ranks
+    pub parameters: &'static [ParameterInfo],
+    pub references: &'static [&'static str],
+    create: fn(&RankConfig) -> Box<dyn RankingAlgorithm>,
+}
+
+impl AlgorithmInfo {
+    // An algorithm of another crate, for AlgorithmRegistry::register; it takes part in teleportation
+    // and has no parameters or references until they are set on the result
+    pub const fn new(id: &'static str, name: &'static str, summary: &'static str, create: fn(&RankConfig) -> Box<dyn RankingAlgorithm>) -> Self {
+        AlgorithmInfo { id, name, summary, uses_teleportation: true, parameters: &[], references: &[], create }
+    }
+
+    pub fn create(&self, config: &RankConfig) -> Box<dyn RankingAlgorithm> {
+        (self.create)(config)
+    }
+}
+
+pub const PAGERANK_VARIANT: AlgorithmInfo = AlgorithmInfo {
+    id: "pagerank",
+    name: "Custom PageRank variant",
+    summary: "PageRank with expert-weighted teleportation, configurable dangling-mass handling and decaying edges; total rank (mass) is conserved",
+    uses_teleportation: true,
+    parameters: &[
+        ParameterInfo { name: "damping_factor", description: "share of rank (mass) that flows along edges per step", value: |c| c.damping_factor.to_string() },
+        ParameterInfo { name: "iterations", description: "upper bound on power iteration steps", value: |c| c.num_of_iterations.to_string() },
+        ParameterInfo { name: "tolerance", description: "L1 change below which iteration stops early, 0 disables", value: |c| c.tolerance.to_string() },
+        ParameterInfo { name: "dangling_policy", description: "where rank that cannot leave along edges goes", value: |c| format!("{:?}", c.dangling_policy) },
+        ParameterInfo { name: "outflow_normalization", description: "what out-edge weights are divided by", value: |c| format!("{:?}", c.outflow_normalization) },
+        ParameterInfo { name: "solver", description: "Jacobi or Gauss-Seidel updates, or Monte Carlo random walks", value: |c| format!("{:?}", c.solver) },
+        ParameterInfo { name: "relaxation", description: "over-relaxation factor of the Gauss-Seidel solver", value: |c| c.relaxation.to_string() },
+        ParameterInfo { name: "walks_per_node", description: "random walks per node of the Monte Carlo solver", value: |c| c.walks_per_node.to_string() },
+        ParameterInfo { name: "backend", description: "edge list or dense matrix", value: |c| format!("{:?}", c.backend) },
+        ParameterInfo { name: "precision", description: "float type of the power iteration", value: |c| format!("{:?}", c.precision) },
+        ParameterInfo { name: "expert_teleport_fraction", description: "share of teleported rank directed to experts", value: |_| EXPERT_TELEPORT_FRACTION.to_string() },
+    ],
+    references: &["L. Page, S. Brin, R. Motwani, T. Winograd. The PageRank Citation Ranking: Bringing Order to the Web. Stanford InfoLab, 1999."],
+    create: |config| Box::new(PageRankVariant { config: config.clone() }),
+};
+
+pub const TRUSTRANK: AlgorithmInfo = AlgorithmInfo {
+    id: "trustrank",
+    name: "TrustRank",
+    summary: "classic personalized PageRank seeded by the teleportation targets, out-weights normalized to 1",
+    uses_teleportation: true,
+    parameters: &[
+        ParameterInfo { name: "damping_factor", description: "probability of following an edge", value: |_| TrustRank::default().damping_factor.to_string() },
+        ParameterInfo { name: "iterations", description: "power iteration steps", value: |_| TrustRank::default().num_of_iterations.to_string() },
+    ],
+    references: &["Z. Gyöngyi, H. Garcia-Molina, J. Pedersen. Combating Web Spam with TrustRank. VLDB 2004."],
+    create: |_| Box::new(TrustRank::default()),
+};
+
+pub const STANDARD_PAGERANK: AlgorithmInfo = AlgorithmInfo {
+    id: "standard",
+    name: "Standard PageRank",
+    summary: "textbook PageRank as in networkx: decayed out-weights normalized to 1, dangling rank and teleportation uniform",
+    uses_teleportation: false,
+    parameters: &[
+        ParameterInfo { name: "damping_factor", description: "probability of following an edge", value: |_| StandardPageRank::default().damping_factor.to_string() },
+        ParameterInfo { name: "max_iterations", description: "power iteration steps at most", value: |_| StandardPageRank::default().max_iterations.to_string() },
+        ParameterInfo { name: "tolerance", description: "stop once the L1 change is below tolerance times the number of nodes", value: |_| StandardPageRank::default().tolerance.to_string() },
+    ],
+    references: &["L. Page, S. Brin, R. Motwani, T. Winograd. The PageRank Citation Ranking: Bringing Order to the Web. Stanford InfoLab, 1999.", "networkx.pagerank (NetworkX link analysis)"],
+    create: |_| Box::new(StandardPageRank::default()),
+};
+
+pub const KATZ: AlgorithmInfo = AlgorithmInfo {
+    id: "katz",
+    name: "Katz centrality",
+    summary: "attenuated count of weighted walks ending at each node",
+    uses_teleportation: false,
+    parameters: &[
+        ParameterInfo { name: "attenuation", description: "weight of each further step; below 1 / spectral radius to converge", value: |_| Katz::default().attenuation.to_string() },
+        ParameterInfo { name: "iterations", description: "iteration steps", value: |_| Katz::default().num_of_iterations.to_string() },
+    ],
+    references: &["L. Katz. A New Status Index Derived from Sociometric Analysis. Psychometrika 18, 1953."],
+    create: |_| Box::new(Katz::default()),
+};
+
+pub const HITS: AlgorithmInfo = AlgorithmInfo {
+    id: "hits",
+    name: "HITS (authority)",
+    summary: "authority scores of Kleinberg's hubs and authorities",
+    uses_teleportation: false,
+    parameters: &[
+        ParameterInfo { name: "iterations", description: "iteration steps", value: |_| Hits::default().num_of_iterations.to_string() },
+    ],
+    references: &["J. Kleinberg. Authoritative Sources in a Hyperlinked Environment. Journal of the ACM 46, 1999."],
+    create: |_| Box::new(Hits::default()),
+};
+
+pub const WEIGHTED_IN_DEGREE: AlgorithmInfo = AlgorithmInfo {
+    id: "indegree",
+    name: "Weighted in-degree",
+    summary: "sum of the current weights of incoming edges",
+    uses_teleportation: false,
+    parameters: &[],
+    references: &[],
+    create: |_| Box::new(WeightedInDegree),
+};
+
+pub const COHITS: AlgorithmInfo = AlgorithmInfo {
+    id: "cohits",
+    name: "Co-HITS",
+    summary: "scores alternate between raters and the items they rate in a bipartite graph, each side mixed with its prior (raters: teleportation targets)",
+    uses_teleportation: true,
+    parameters: &[
+        ParameterInfo { name: "jump", description: "probability of following a rating instead of returning to the prior", value: |_| CoHits::default().jump.to_string() },
+        ParameterInfo { name: "iterations", description: "alternating propagation steps", value: |_| CoHits::default().num_of_iterations.to_string() },
+    ],
+    references: &["H. Deng, M. R. Lyu, I. King. A Generalized Co-HITS Algorithm and Its Application to Bipartite Graphs. KDD 2009."],
+    create: |_| Box::new(CoHits::default()),
+};
+
+pub const TEMPORAL_PAGERANK: AlgorithmInfo = AlgorithmInfo {
+    id: "temporal-pagerank",
+    name: "Temporal PageRank",
+    summary: "replays the frame's edges in creation order and counts only walks that follow them in time, with walks decaying while they wait at a node",
+    uses_teleportation: true,
+    parameters: &[
+        ParameterInfo { name: "alpha", description: "probability of a walk continuing at its next interaction; the damping factor", value: |_| TemporalPageRank::default().damping_factor.to_string() },
+        ParameterInfo { name: "beta", description: "share of the walks at a node that waits for a later interaction instead of moving on", value: |_| TemporalPageRank::default().in_walk_decay.to_string() },
+    ],
+    references: &["P. Rozenshtein, A. Gionis. Temporal PageRank. ECML PKDD 2016."],
+    create: |_| Box::new(TemporalPageRank::default()),
+};
+
+pub const BOUNDED_PROPAGATION: AlgorithmInfo = AlgorithmInfo {
+    id: "propagation",
+    name: "Bounded trust propagation",
+    summary: "trust energy spreads from the teleportation targets along weighted edges for a limited number of hops, attenuated at every hop",
+    uses_teleportation: true,
+    parameters: &[
+        ParameterInfo { name: "spreading_factor", description: "share of received trust passed on per hop; the damping factor", value: |c| c.damping_factor.to_string() },
+        ParameterInfo { name: "max_hops", description: "hops after which trust stops spreading", value: |c| c.max_hops.to_string() },
+    ],
+    references: &[
+        "C.-N. Ziegler, G. Lausen. Spreading Activation Models for Trust Propagation. IEEE EEE 2004.",
+        "R. Levien. Attack-Resistant Trust Metrics. In: Computing with Social Trust, Springer 2009.",
+    ],
+    create: |config| Box::new(BoundedPropagation { spreading_factor: config.damping_factor, max_hops: config.max_hops }),
+};
+
+pub const CAPACITY_LIMITED: AlgorithmInfo = AlgorithmInfo {
+    id: "capacity",
+    name: "Capacity-limited PageRank",
+    summary: "PageRank in which no node emits or receives more than a fixed amount of rank per step; the excess is teleported",
+    uses_teleportation: true,
+    parameters: &[
+        ParameterInfo { name: "damping_factor", description: "share of rank (mass) that flows along edges per step", value: |c| c.damping_factor.to_string() },
+        ParameterInfo { name: "iterations", description: "upper bound on iteration steps", value: |c| c.num_of_iterations.to_string() },
+        ParameterInfo { name: "node_capacity", description: "rank a node may emit and receive along edges per step", value: |c| c.node_capacity.to_string() },
+    ],
+    references: &[],
+    create: |config| Box::new(CapacityLimited { config: config.clone(), capacity: config.node_capacity }),
+};
+
+// Algorithms registered at run time, after the built-in ones; never removed, so they can be
+// handed out for the rest of the process
+static REGISTERED: RwLock<Vec<&'static AlgorithmInfo>> = RwLock::new(Vec::new());
+
+pub const SOURCE_CLIPPED: AlgorithmInfo = AlgorithmInfo {
+    id: "clipped",
+    name: "Source-clipped PageRank",
+    summary: "PageRank in which no single source brings a node more than a fixed share of its inflow per step; the excess is teleported",
+    uses_teleportation: true,
+    parameters: &[
+        ParameterInfo { name: "damping_factor", description: "share of rank (mass) that flows along edges per step", value: |c| c.damping_factor.to_string() },
+        ParameterInfo { name: "iterations", description: "upper bound on iteration steps", value: |c| c.num_of_iterations.to_string() },
+        ParameterInfo { name: "max_source_share", description: "share of a node's inflow per step a single source may bring", value: |c| c.max_source_share.to_string() },
+    ],
+    references: &[],
+    create: |config| Box::new(SourceClipped { config: config.clone(), max_share: config.max_source_share }),
+};
+
+// All ranking algorithms the tool can run: the built-in ones and those other crates register
+pub struct AlgorithmRegistry;
+
+impl AlgorithmRegistry {
+    pub const ALGORITHMS: [&'static AlgorithmInfo; 11] = [&PAGERANK_VARIANT, &TRUSTRANK, &STANDARD_PAGERANK, &HITS, &KATZ, &WEIGHTED_IN_DEGREE, &BOUNDED_PROPAGATION, &CAPACITY_LIMITED, &SOURCE_CLIPPED, &COHITS, &TEMPORAL_PAGERANK];
+
+    // Makes an algorithm available by its id everywhere the built-in ones are, e.g. to
+    // AlgorithmRegistry::create and the server's `algorithm` parameter
+    pub fn register(info: AlgorithmInfo) -> Result<&'static AlgorithmInfo, String> {
+        let mut registered = REGISTERED.write().unwrap();
+        if Self::ALGORITHMS.iter().chain(registered.iter()).any(|a| a.id == info.id) {
+            return Err(format!("algorithm {} is already registered", info.id));
+        }
+        let info: &'static AlgorithmInfo = Box::leak(Box::new(info));
+        registered.push(info);
+        Ok(info)
+    }
+
+    pub fn all() -> Vec<&'static AlgorithmInfo> {
+        Self::ALGORITHMS.iter().copied().chain(REGISTERED.read().unwrap().iter().copied()).collect()
+    }
+
+    pub fn get(id: &str) -> Result<&'static AlgorithmInfo, String> {
+        let algorithms = Self::all();
+        algorithms.iter().copied().find(|a| a.id == id).ok_or_else(|| {
+            let ids: Vec<&str> = algorithms.iter().map(|a| a.id).collect();
+            format!("unknown algorithm: {} (expected {})", id, ids.join(", "))
+        })
+    }
+
+    pub fn create(id: &str, config: &RankConfig) -> Result<Box<dyn RankingAlgorithm>, String> {
+        Self::get(id).map(|info| info.create(config))
+    }
+}
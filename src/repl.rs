@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::decay::decay_constant_for_half_life;
+use crate::graph::Edge;
+use crate::leaderboard::rank_order;
+use crate::nodes::NodeInfo;
+use crate::rank::RankingAlgorithm;
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+
+const HELP: &str = "\
+node NAME           add a node
+edge SOURCE TARGET  add an edge created now, adding its nodes if needed
+expert NAME         make a node an expert
+decay CONSTANT      set the decay constant of edge weights
+half-life UNITS     set the decay so that edge weights halve every UNITS
+advance STEP        move time forward by STEP
+time TIME           move time forward to TIME
+ranks               ranks at the current time, highest first
+edges               edges with their weights at the current time
+save FILE           write the session as a scenario file
+help                this list
+quit                end the session";
+
+// A scenario built up interactively: nodes are named, edges are created at the current time,
+// and time only moves forward. Every time the session has been at becomes a frame of the saved
+// scenario, so rendering it replays the session step by step.
+pub struct Session {
+    scenario: Scenario,
+    nodes: HashMap<String, NodeId>,
+    time: Time,
+    frame_times: Vec<Time>,
+}
+
+impl Session {
+    pub fn new(name: &str) -> Self {
+        Session { scenario: Scenario::new(name, 0, Vec::new(), Vec::new()), nodes: HashMap::new(), time: Time(0.0), frame_times: vec![Time(0.0)] }
+    }
+
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    // Runs one command line; the reply is what the command prints, if anything
+    pub fn execute(&mut self, line: &str, algorithm: &dyn RankingAlgorithm) -> Result<Option<String>, String> {
+        let tokens: Vec<&str> = line.split('#').next().unwrap().split_whitespace().collect();
+        let argument = |i: usize, what: &str| tokens.get(i).copied().ok_or_else(|| format!("{} requires {}", tokens[0], what));
+        let number = |i: usize, what: &str| argument(i, what)?.parse::<f64>().map_err(|_| format!("{} must be a number: {}", what, tokens[i]));
+        match tokens.first().copied() {
+            None => Ok(None),
+            Some("node") => {
+                self.node_id(argument(1, "a name")?);
+                Ok(None)
+            }
+            Some("edge") => {
+                let source = self.node_id(argument(1, "a source and a target")?);
+                let target = self.node_id(argument(2, "a source and a target")?);
+                self.scenario.edges.push(Edge { source, target, time_of_creation: self.time, weight: 1.0 });
+                Ok(None)
+            }
+            Some("expert") => {
+                let node = self.node_id(argument(1, "a name")?);
+                if !self.scenario.experts.contains(&node) {
+                    self.scenario.experts.push(node);
+                }
+                Ok(None)
+            }
+            Some("decay") => {
+                let decay_constant = number(1, "decay constant")?;
+                if decay_constant < 0.0 {
+                    return Err(format!("decay constant must not be negative: {}", decay_constant));
+                }
+                self.scenario.decay_constant = decay_constant;
+                Ok(None)
+            }
+            Some("half-life") => {
+                let half_life = number(1, "half-life")?;
+                if half_life <= 0.0 {
+                    return Err(format!("half-life must be positive: {}", half_life));
+                }
+                self.scenario.decay_constant = decay_constant_for_half_life(half_life);
+                Ok(None)
+            }
+            Some("advance") => {
+                let step = number(1, "step")?;
+                self.move_to(Time(self.time.value() + step))
+            }
+            Some("time") => self.move_to(Time(number(1, "time")?)),
+            Some("ranks") => Ok(Some(self.ranks(algorithm)?)),
+            Some("edges") => Ok(Some(self.edges())),
+            Some("save") => {
+                let pathname = argument(1, "a file name")?;
+                self.save(pathname).map_err(|e| format!("{}: {}", pathname, e))?;
+                Ok(Some(format!("saved {} nodes and {} edges to {}", self.scenario.num_of_nodes, self.scenario.edges.len(), pathname)))
+            }
+            Some("help") => Ok(Some(HELP.to_string())),
+            Some(other) => Err(format!("unknown command: {} (try help)", other)),
+        }
+    }
+
+    // The session as a scenario file, with a frame at every time the session has been at
+    pub fn write_scenario(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "name {}", self.scenario.name)?;
+        writeln!(out, "nodes {}", self.scenario.num_of_nodes)?;
+        for (node, info) in self.scenario.node_info.iter().enumerate() {
+            match info.joins {
+                Some(time) => writeln!(out, "node {} name={} joins={}", node, self.scenario.node_label(NodeId(node)), time)?,
+                None => writeln!(out, "node {} name={}", node, self.scenario.node_label(NodeId(node)))?,
+            }
+        }
+        for expert in &self.scenario.experts {
+            writeln!(out, "expert {}", expert)?;
+        }
+        writeln!(out, "decay {}", self.scenario.decay_constant)?;
+        let times: Vec<String> = self.frame_times.iter().map(Time::to_string).collect();
+        writeln!(out, "frames at {}", times.join(","))?;
+        for edge in &self.scenario.edges {
+            writeln!(out, "edge {} {} {}", edge.source, edge.target, edge.time_of_creation)?;
+        }
+        Ok(())
+    }
+
+    // The session takes the name of the file it is saved to
+    fn save(&mut self, pathname: &str) -> io::Result<()> {
+        if let Some(stem) = Path::new(pathname).file_stem() {
+            self.scenario.name = stem.to_string_lossy().into_owned();
+        }
+        let mut text = Vec::new();
+        self.write_scenario(&mut text)?;
+        fs::write(pathname, text)
+    }
+
+    fn move_to(&mut self, time: Time) -> Result<Option<String>, String> {
+        if time <= self.time {
+            return Err(format!("time only moves forward: {} is not after {}", time, self.time));
+        }
+        self.time = time;
+        self.frame_times.push(time);
+        Ok(Some(format!("time {}", time)))
+    }
+
+    fn ranks(&self, algorithm: &dyn RankingAlgorithm) -> Result<String, String> {
+        if self.scenario.num_of_nodes == 0 {
+            return Err("no nodes to rank yet".to_string());
+        }
+        let graph = self.scenario.frame_graph(self.time, &self.scenario.edge_weights(self.time)).map_err(|e| e.to_string())?;
+        let ranks = algorithm.rank(&graph, &self.scenario.teleportation_targets(self.time));
+        let lines: Vec<String> = rank_order(&ranks, &[])
+            .iter()
+            .map(|node| node.index())
+            .map(|node| {
+                let expert = if self.scenario.experts.contains(&NodeId(node)) { " (expert)" } else { "" };
+                format!("{:>10.6}  {}{}", ranks[node], self.scenario.node_label(NodeId(node)), expert)
+            })
+            .collect();
+        Ok(format!("ranks at time {}\n{}", self.time, lines.join("\n")))
+    }
+
+    fn edges(&self) -> String {
+        let weights = self.scenario.edge_weights(self.time);
+        let lines: Vec<String> = self
+            .scenario
+            .edges
+            .iter()
+            .zip(&weights)
+            .map(|(edge, weight)| format!("{} -> {} created at {}, weight {:.6}", self.scenario.node_label(edge.source), self.scenario.node_label(edge.target), edge.time_of_creation, weight))
+            .collect();
+        format!("{} edges at time {}\n{}", lines.len(), self.time, lines.join("\n"))
+    }
+
+    // Names are single words without `#`, which starts a comment in commands as in scenario files
+    fn node_id(&mut self, name: &str) -> NodeId {
+        if let Some(&node) = self.nodes.get(name) {
+            return node;
+        }
+        let node = NodeId(self.scenario.num_of_nodes);
+        self.scenario.num_of_nodes += 1;
+        // Nodes added after the start join when they are added, so earlier frames leave them out
+        let joins = (self.time > Time(0.0)).then_some(self.time);
+        self.scenario.node_info.push(NodeInfo { name: Some(name.to_string()), joins, ..NodeInfo::default() });
+        self.nodes.insert(name.to_string(), node);
+        node
+    }
+}
+
+// Reads commands until `quit` or the end of the input, with a prompt before every command.
+// A failing command is reported and the session goes on.
+pub fn run_repl(input: &mut dyn BufRead, out: &mut dyn Write, session: &mut Session, algorithm: &dyn RankingAlgorithm) -> io::Result<()> {
+    let mut line = String::new();
+    loop {
+        write!(out, "t={}> ", session.time)?;
+        out.flush()?;
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Ok(());
+        }
+        if matches!(line.trim(), "quit" | "exit") {
+            return Ok(());
+        }
+        match session.execute(&line, algorithm) {
+            Ok(Some(reply)) => writeln!(out, "{}", reply)?,
+            Ok(None) => {}
+            Err(message) => writeln!(out, "error: {}", message)?,
+        }
+    }
+}
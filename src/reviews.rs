@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::decay::decay_constant_for_half_life;
+use crate::graph::Edge;
+use crate::nodes::{NodeInfo, NodeRow};
+use crate::json::Json;
+use crate::provenance::EdgeSource;
+use crate::scenario::{FrameSchedule, Scenario, ScenarioError};
+use crate::types::{NodeId, Time};
+
+// Developer trust from code review: every review of a change is an edge from the reviewer to the
+// change's author, created when the change was merged. Time is measured in days since the Unix
+// epoch, review trust halves every REVIEW_HALF_LIFE days, and the frames cover the review history
+// in at most REVIEW_FRAMES steps of whole days.
+pub const REVIEW_HALF_LIFE: f64 = 180.0;
+pub const REVIEW_FRAMES: usize = 100;
+
+pub(crate) const SECONDS_PER_DAY: f64 = 86400.0;
+
+// `git log` output that read_git_log reads: one record per commit with its author, its commit
+// date (the merge date under rebase and squash merging) and its review trailers
+pub const GIT_LOG_FORMAT: &str = "commit %H%nauthor %aN <%aE>%ndate %ct%n%(trailers:key=Reviewed-by,key=Acked-by,unfold)";
+
+// Trailers that record a review of the commit
+const REVIEW_TRAILERS: [&str; 2] = ["Reviewed-by", "Acked-by"];
+
+// People by identity (lower-case email, or name without one) and the reviews between them
+#[derive(Debug, Default)]
+struct ReviewLog {
+    people: HashMap<String, NodeId>,
+    names: Vec<String>,
+    edges: Vec<Edge>,
+    sources: Vec<EdgeSource>,
+}
+
+impl ReviewLog {
+    // `Name <email>` or a plain name or login; the first spelling of a name is kept
+    fn person(&mut self, text: &str) -> NodeId {
+        let text = text.trim();
+        let (name, identity) = match text.split_once('<') {
+            Some((name, email)) => (name.trim(), email.trim_end_matches('>').trim().to_lowercase()),
+            None => (text, text.to_string()),
+        };
+        let next = NodeId(self.names.len());
+        let node = *self.people.entry(identity).or_insert(next);
+        if node == next {
+            self.names.push(if name.is_empty() { text.to_string() } else { name.to_string() });
+        }
+        node
+    }
+
+    // Self-reviews do not count
+    fn review(&mut self, reviewer: &str, author: NodeId, day: f64, event_id: &str, kind: &str) {
+        let reviewer = self.person(reviewer);
+        if reviewer == author {
+            return;
+        }
+        self.edges.push(Edge { source: reviewer, target: author, time_of_creation: Time(day), weight: 1.0 });
+        self.sources.push(EdgeSource { line: 0, event_id: Some(event_id.to_string()), metadata: vec![("review".to_string(), kind.to_string())] });
+    }
+
+    fn into_scenario(self, name: &str) -> Result<Scenario, ScenarioError> {
+        if self.edges.is_empty() {
+            return Err(ScenarioError { line: 0, message: "no reviews found".to_string() });
+        }
+        Ok(scenario_in_days(name, self.names, self.edges, self.sources, REVIEW_HALF_LIFE))
+    }
+}
+
+// Scenario of named people and edges created at times in days, decaying with the half-life (in
+// days), with frames of whole days over the edges' times, at most REVIEW_FRAMES of them
+pub(crate) fn scenario_in_days(name: &str, names: Vec<String>, edges: Vec<Edge>, sources: Vec<EdgeSource>, half_life: f64) -> Scenario {
+    let first = edges.iter().map(|e| e.time_of_creation.value()).fold(f64::INFINITY, f64::min).floor();
+    let last = edges.iter().map(|e| e.time_of_creation.value()).fold(f64::NEG_INFINITY, f64::max).ceil();
+    let mut scenario = Scenario::new(name, names.len(), Vec::new(), edges);
+    scenario.edge_sources = sources;
+    scenario.decay_constant = decay_constant_for_half_life(half_life);
+    let step = ((last - first) / REVIEW_FRAMES as f64).ceil().max(1.0);
+    scenario.frame_schedule = Some(FrameSchedule::Range { start: Some(Time(first)), end: Some(Time(last)), step: Some(step) });
+    let rows = names.into_iter().enumerate().map(|(node, name)| NodeRow { node: NodeId(node), info: NodeInfo { name: Some(name), ..NodeInfo::default() }, expert: false });
+    scenario.apply_node_rows(rows.collect());
+    scenario
+}
+
+// Commits as `git log --format=<GIT_LOG_FORMAT>` writes them; every Reviewed-by or Acked-by
+// trailer is a review of the commit's author
+pub fn read_git_log(text: &str, name: &str) -> Result<Scenario, ScenarioError> {
+    let mut log = ReviewLog::default();
+    let mut commit: Option<(String, Option<NodeId>, Option<f64>)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: String| ScenarioError { line: i + 1, message };
+        if let Some(hash) = line.strip_prefix("commit ") {
+            commit = Some((hash.trim().to_string(), None, None));
+            continue;
+        }
+        let Some((hash, author, day)) = commit.as_mut() else { continue };
+        if let Some(person) = line.strip_prefix("author ") {
+            *author = Some(log.person(person));
+        } else if let Some(seconds) = line.strip_prefix("date ") {
+            let seconds: f64 = seconds.trim().parse().map_err(|_| error(format!("invalid commit date: {}", seconds)))?;
+            *day = Some(seconds / SECONDS_PER_DAY);
+        } else if let Some((key, reviewer)) = line.split_once(':').filter(|(key, _)| REVIEW_TRAILERS.contains(&key.trim())) {
+            let (Some(author), Some(day)) = (*author, *day) else {
+                return Err(error(format!("commit {} has a review before its author and date", hash)));
+            };
+            let hash = hash.clone();
+            log.review(reviewer, author, day, &hash, key.trim());
+        }
+    }
+    log.into_scenario(name)
+}
+
+// Runs `git log` in the repository and reads its reviews; the scenario is named after the folder
+pub fn read_git_repository(path: &str) -> Result<Scenario, ScenarioError> {
+    let error = |message: String| ScenarioError { line: 0, message };
+    let output = Command::new("git")
+        .args(["-C", path, "log", "--no-merges", &format!("--format={}", GIT_LOG_FORMAT)])
+        .output()
+        .map_err(|e| error(format!("cannot run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(error(format!("git log failed: {}", String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    let name = path.trim_end_matches('/').rsplit('/').next().filter(|name| !name.is_empty() && *name != ".").unwrap_or("repository");
+    read_git_log(&String::from_utf8_lossy(&output.stdout), name)
+}
+
+// Merged pull requests as `gh pr list --state merged --json number,author,mergedAt,reviews`
+// writes them: every approving review is a review of the pull request's author, counted once
+// per reviewer and pull request
+pub fn read_github_reviews(text: &str, name: &str) -> Result<Scenario, ScenarioError> {
+    let error = |message: String| ScenarioError { line: 0, message };
+    let pulls = Json::parse(text).map_err(|e| error(format!("invalid GitHub export: {}", e)))?;
+    let pulls = pulls.as_array().ok_or_else(|| error("GitHub export is not an array of pull requests".to_string()))?;
+    let login = |value: &Json| value.get("author").and_then(|author| author.get("login")).and_then(Json::as_str).map(str::to_string);
+    let mut log = ReviewLog::default();
+    for pull in pulls {
+        let number = pull.get("number").and_then(Json::as_f64).map_or(String::new(), |number| format!("#{}", number));
+        let (Some(author), Some(merged)) = (login(pull), pull.get("mergedAt").and_then(Json::as_str)) else { continue };
+        let seconds = parse_timestamp(merged).ok_or_else(|| error(format!("pull request {}: invalid merge time: {}", number, merged)))?;
+        let author = log.person(&author);
+        let mut approvers: Vec<String> = pull
+            .get("reviews")
+            .and_then(Json::as_array)
+            .unwrap_or(&[])
+            .iter()
+            .filter(|review| review.get("state").and_then(Json::as_str) == Some("APPROVED"))
+            .filter_map(login)
+            .collect();
+        approvers.sort();
+        approvers.dedup();
+        for reviewer in approvers {
+            log.review(&reviewer, author, seconds / SECONDS_PER_DAY, &number, "approved");
+        }
+    }
+    log.into_scenario(name)
+}
+
+// Seconds since the Unix epoch of an ISO 8601 UTC time such as 2024-03-05T12:34:56Z
+pub(crate) fn parse_timestamp(text: &str) -> Option<f64> {
+    let (date, time) = text.trim_end_matches('Z').split_once('T')?;
+    let mut date = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.split(':').map(|part| part.parse::<f64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Days from the civil date (H. Hinnant's algorithm)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    Some(days as f64 * SECONDS_PER_DAY + hours * 3600.0 + minutes * 60.0 + seconds)
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::io::{self, Write};
+
+use crate::clusters::{metadata_clusters, ClusterBy};
+use crate::graph::Edge;
+use crate::scenario::Scenario;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// How the ranks of a group's members add up to the group's trust
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Sum,          // the group's share of the total rank
+    WeightedMean, // mean member rank, weighted by the members' teleport weights (1 by default)
+}
+
+// Group-level trust: nodes grouped by their category or org metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rollup {
+    pub by: ClusterBy,
+    pub aggregation: Aggregation,
+}
+
+impl Rollup {
+    // `org`, `category`, or either with `:sum` or `:mean`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (by, aggregation) = text.split_once(':').unwrap_or((text, "sum"));
+        let by = match ClusterBy::parse(by) {
+            Ok(ClusterBy::Community) | Err(_) => return Err(format!("unknown grouping: {} (expected org or category)", by)),
+            Ok(by) => by,
+        };
+        let aggregation = match aggregation {
+            "sum" => Aggregation::Sum,
+            "mean" => Aggregation::WeightedMean,
+            _ => return Err(format!("unknown aggregation: {} (expected sum or mean)", aggregation)),
+        };
+        Ok(Rollup { by, aggregation })
+    }
+
+    // For titles, e.g. "mean member rank by org"
+    pub fn description(&self) -> String {
+        let aggregation = match self.aggregation {
+            Aggregation::Sum => "summed member rank",
+            Aggregation::WeightedMean => "mean member rank",
+        };
+        let by = if self.by == ClusterBy::Category { "category" } else { "org" };
+        format!("{} by {}", aggregation, by)
+    }
+}
+
+// One group's trust in every frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupTrust {
+    pub label: String,
+    pub members: Vec<NodeId>,
+    pub trust: Vec<f64>, // by frame
+}
+
+// Trust of every group in every frame, groups in name order. Nodes without the metadata belong to no group.
+pub fn group_trust(scenario: &Scenario, frames: &[Vec<f64>], rollup: &Rollup) -> Vec<GroupTrust> {
+    let weight = |node: NodeId| match rollup.aggregation {
+        Aggregation::Sum => 1.0,
+        Aggregation::WeightedMean => scenario.node_info(node).and_then(|info| info.teleport_weight).unwrap_or(1.0),
+    };
+    metadata_clusters(&scenario.node_info, rollup.by)
+        .into_iter()
+        .map(|cluster| {
+            let total_weight: f64 = cluster.nodes.iter().map(|&node| weight(node)).sum();
+            let trust = frames
+                .iter()
+                .map(|ranks| {
+                    let weighted: f64 = cluster.nodes.iter().map(|&node| weight(node) * ranks[node.index()]).sum();
+                    match rollup.aggregation {
+                        Aggregation::Sum => weighted,
+                        Aggregation::WeightedMean if total_weight > 0.0 => weighted / total_weight,
+                        Aggregation::WeightedMean => 0.0,
+                    }
+                })
+                .collect();
+            GroupTrust { label: cluster.label, members: cluster.nodes, trust }
+        })
+        .collect()
+}
+
+// The trust between groups in a frame: one edge per ordered pair of groups, created at the
+// frame's time and weighing the summed current weight of the edges between their members.
+// Edges within a group and edges of ungrouped nodes are left out. Groups are numbered by their
+// position in `groups`.
+pub fn group_edges(groups: &[GroupTrust], edges: &[Edge], weights: &[f64], time: Time) -> Vec<Edge> {
+    let mut group_of: BTreeMap<NodeId, usize> = BTreeMap::new();
+    for (group, trust) in groups.iter().enumerate() {
+        for &node in &trust.members {
+            group_of.insert(node, group);
+        }
+    }
+    let mut summed: BTreeMap<(usize, usize), f64> = BTreeMap::new();
+    for (edge, &weight) in edges.iter().zip(weights) {
+        if let (Some(&source), Some(&target)) = (group_of.get(&edge.source), group_of.get(&edge.target))
+            && source != target
+            && weight > 0.0
+        {
+            *summed.entry((source, target)).or_default() += weight;
+        }
+    }
+    summed.into_iter().map(|((source, target), weight)| Edge { source: NodeId(source), target: NodeId(target), time_of_creation: time, weight }).collect()
+}
+
+// Groups on the unit circle, in name order
+pub fn group_layout(num_of_groups: usize) -> Vec<(f64, f64)> {
+    (0..num_of_groups)
+        .map(|i| {
+            let angle = 2.0 * PI * (i as f64) / (num_of_groups as f64);
+            (angle.cos(), angle.sin())
+        })
+        .collect()
+}
+
+// frame,time,group,members,trust rows
+pub fn write_group_trust_csv(out: &mut dyn Write, times: &[Time], groups: &[GroupTrust]) -> io::Result<()> {
+    writeln!(out, "frame,time,group,members,trust")?;
+    for (frame, time) in times.iter().enumerate() {
+        for group in groups {
+            writeln!(out, "{},{},{},{},{:.6}", FrameIdx(frame).number(), time, group.label, group.members.len(), group.trust[frame])?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,273 @@
+use std::io;
+use std::thread;
+
+use log::debug;
+
+use crate::graph::{compile_graph, CompiledGraph, GraphOptions};
+use crate::hashing::frame_hash;
+use crate::history::RankHistory;
+use crate::profile::{FrameProfile, Phase, Stopwatch};
+use crate::rank::{Convergence, RankingAlgorithm};
+use crate::scenario::{Scenario, EXPERT_TELEPORT_FRACTION};
+use crate::types::{NodeId, Time};
+
+// Per-frame results of a scenario run, shared by the exporters
+#[derive(Debug, Clone, Default)]
+pub struct RunOutput {
+    pub history: RankHistory,
+    pub edge_weights: Vec<Vec<f64>>, // per frame, indexed by EdgeId; pruned edges weigh 0
+    pub pruned_edges: Vec<usize>,    // per frame, edges left out for weighing less than the pruning threshold
+    pub convergence: Vec<Option<Convergence>>, // per frame; None for warm-started frames, algorithms without power iteration and frames resumed from a checkpoint
+    pub frame_hashes: Vec<u64>,      // relabeling-invariant hash of every frame
+    pub profile: Vec<FrameProfile>,  // per frame; empty for frames resumed from a checkpoint or read from stored results
+}
+
+impl RunOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// Ranks every frame of the scenario without rendering anything
+pub fn run_scenario(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, frame_times: &[Time], graph_options: &GraphOptions) -> RunOutput {
+    let mut output = RunOutput::new();
+    continue_scenario(scenario, algorithm, frame_times, graph_options, &mut output, &mut |_| Ok(())).unwrap();
+    output
+}
+
+// Ranks the frames that `output` does not hold yet, calling `after_frame` once each frame is complete
+pub fn continue_scenario(
+    scenario: &Scenario,
+    algorithm: &dyn RankingAlgorithm,
+    frame_times: &[Time],
+    graph_options: &GraphOptions,
+    output: &mut RunOutput,
+    after_frame: &mut dyn FnMut(&RunOutput) -> io::Result<()>,
+) -> io::Result<()> {
+    continue_scenario_parallel(scenario, algorithm, frame_times, graph_options, 1, output, after_frame)
+}
+
+// Incremental ranking: every frame continues from the ranks of the previous frame instead of
+// starting from scratch. In between, the carried-over ranks decay towards the frame's
+// teleportation vector, r = t + (r_previous - t) * exp(-node_decay * elapsed time), so trust a
+// node accumulated fades back to its baseline unless new edges keep it up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WarmStart {
+    pub steps: Option<usize>, // iterations per frame, the algorithm's own when not set
+    pub node_decay: f64,      // 0 carries ranks over unchanged
+}
+
+// Endogenous authority: instead of a fixed expert list, the top `top` nodes of every frame become
+// the experts of the next one. The first frame teleports to the scenario's own targets; after
+// that, t_k = smoothing * t_(k-1) + (1 - smoothing) * a_(k-1), where a_(k-1) directs the expert
+// fraction of teleported rank (mass) equally to the top nodes of frame k-1 and spreads the rest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DynamicAuthority {
+    pub top: usize,
+    pub smoothing: f64, // share of the previous teleportation vector that is kept, 0 follows the last frame only
+}
+
+pub const DEFAULT_AUTHORITY_SMOOTHING: f64 = 0.5;
+
+// Damping factor that changes from frame to frame, so that a noisy early period can teleport more
+// rank (mass) than a mature one. It replaces the algorithm's own damping factor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DampingSchedule {
+    Anneal { from: f64, to: f64 }, // linear from the first frame to the last
+    PerFrame(Vec<f64>),            // one value per frame; the last one holds for further frames
+}
+
+impl DampingSchedule {
+    // `0.5..0.9` anneals, `0.5,0.6,0.7` lists per-frame values, and a single value is constant
+    pub fn parse(text: &str) -> Result<DampingSchedule, String> {
+        let value = |text: &str| match text.trim().parse::<f64>() {
+            Ok(value) if (0.0..1.0).contains(&value) => Ok(value),
+            _ => Err(format!("invalid damping factor: {} (expected a number in [0, 1))", text)),
+        };
+        match text.split_once("..") {
+            Some((from, to)) => Ok(DampingSchedule::Anneal { from: value(from)?, to: value(to)? }),
+            None => Ok(DampingSchedule::PerFrame(text.split(',').map(value).collect::<Result<_, _>>()?)),
+        }
+    }
+
+    // Damping factor of frame `frame` (from 0) of a run of `num_of_frames` frames
+    pub fn damping_factor(&self, frame: usize, num_of_frames: usize) -> f64 {
+        match self {
+            DampingSchedule::Anneal { from, to } if num_of_frames > 1 => {
+                let progress = frame as f64 / (num_of_frames - 1) as f64;
+                from * (1.0 - progress) + to * progress
+            }
+            DampingSchedule::Anneal { from, .. } => *from,
+            DampingSchedule::PerFrame(values) => values[frame.min(values.len() - 1)],
+        }
+    }
+}
+
+// Where the power iteration of frames ranked from scratch starts, and of the first frame of a
+// warm-started run. With enough iterations the ranks do not depend on it, but with few iterations
+// or warm starts they keep part of the start.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitialRanks {
+    Uniform,         // the algorithm's own start
+    Experts,         // all rank (mass) on the frame's teleportation targets, mostly the experts
+    Priors,          // in proportion to the nodes' priors
+    Given(Vec<f64>), // e.g. the last frame of a previous run; nodes beyond its end start at 0
+}
+
+impl InitialRanks {
+    // `uniform`, `experts`, `priors` or a comma-separated rank per node
+    pub fn parse(text: &str) -> Result<InitialRanks, String> {
+        match text {
+            "uniform" => Ok(InitialRanks::Uniform),
+            "experts" => Ok(InitialRanks::Experts),
+            "priors" => Ok(InitialRanks::Priors),
+            _ => {
+                let rank = |text: &str| match text.trim().parse::<f64>() {
+                    Ok(rank) if rank >= 0.0 && rank.is_finite() => Ok(rank),
+                    _ => Err(format!("invalid initial rank: {} (expected uniform, experts, priors or non-negative ranks separated by commas)", text)),
+                };
+                Ok(InitialRanks::Given(text.split(',').map(rank).collect::<Result<_, _>>()?))
+            }
+        }
+    }
+
+    // Initial ranks of the frame's active nodes, normalized to sum to 1; None for the algorithm's
+    // own start, which is also where priors or given ranks that are all 0 fall back to
+    pub fn ranks(&self, scenario: &Scenario, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Option<Vec<f64>> {
+        let weights: Vec<f64> = match self {
+            InitialRanks::Uniform => return None,
+            InitialRanks::Experts => return Some(teleportation_targets.to_vec()),
+            InitialRanks::Priors => (0..graph.num_of_nodes).map(|node| scenario.prior(NodeId(node)).unwrap_or(0.0)).collect(),
+            InitialRanks::Given(ranks) => (0..graph.num_of_nodes).map(|node| ranks.get(node).copied().unwrap_or(0.0)).collect(),
+        };
+        let weights: Vec<f64> = weights.iter().enumerate().map(|(node, &w)| if graph.is_active(node) { w } else { 0.0 }).collect();
+        let total: f64 = weights.iter().sum();
+        (total > 0.0).then(|| weights.iter().map(|w| w / total).collect())
+    }
+}
+
+impl DynamicAuthority {
+    // Teleportation vector of the frame after those in `history`, replayed from the first frame
+    // so that a run resumed from a checkpoint gets the same vectors
+    pub fn teleportation_targets(&self, scenario: &Scenario, history: &RankHistory, time: Time) -> Vec<f64> {
+        let mut teleportation_targets = scenario.teleportation_targets(history.times().first().copied().unwrap_or(time));
+        for ranks in history.frames() {
+            let authority = self.authority_targets(ranks);
+            for (t, a) in teleportation_targets.iter_mut().zip(authority) {
+                *t = self.smoothing * *t + (1.0 - self.smoothing) * a;
+            }
+        }
+        teleportation_targets
+    }
+
+    // Expert-style teleportation vector with the highest-ranked nodes as experts; ties go to the lower index
+    fn authority_targets(&self, ranks: &[f64]) -> Vec<f64> {
+        let num_of_nodes = ranks.len();
+        let mut order: Vec<usize> = (0..num_of_nodes).collect();
+        order.sort_by(|&a, &b| ranks[b].total_cmp(&ranks[a]));
+        let top = self.top.min(num_of_nodes);
+        let mut targets = vec![(1.0 - EXPERT_TELEPORT_FRACTION) / num_of_nodes as f64; num_of_nodes];
+        for &node in &order[..top] {
+            targets[node] += EXPERT_TELEPORT_FRACTION / top as f64;
+        }
+        targets
+    }
+}
+
+// Like continue_scenario, with up to `jobs` frames ranked at the same time on their own threads.
+// Frames are independent, so the output is the same as that of a sequential run. They are ranked
+// in batches of `jobs` consecutive frames and added in time order, which keeps at most `jobs`
+// compiled graphs in memory and calls `after_frame` in frame order. Warm-started and dynamic
+// authority scenarios depend on the previous frames and are ranked one frame at a time.
+pub fn continue_scenario_parallel(
+    scenario: &Scenario,
+    algorithm: &dyn RankingAlgorithm,
+    frame_times: &[Time],
+    graph_options: &GraphOptions,
+    jobs: usize,
+    output: &mut RunOutput,
+    after_frame: &mut dyn FnMut(&RunOutput) -> io::Result<()>,
+) -> io::Result<()> {
+    let jobs = if scenario.warm_start.is_some() || scenario.authority.is_some() { 1 } else { jobs.max(1) };
+    for batch in frame_times[output.history.num_of_frames()..].chunks(jobs) {
+        // Under a damping schedule, every frame has an algorithm of its own
+        let first = output.history.num_of_frames();
+        let damped: Vec<Option<Box<dyn RankingAlgorithm>>> = (first..first + batch.len())
+            .map(|frame| algorithm.with_damping(scenario.damping_factor(frame, frame_times.len())?))
+            .collect();
+        let algorithms: Vec<&dyn RankingAlgorithm> = damped.iter().map(|damped| damped.as_deref().unwrap_or(algorithm)).collect();
+        let frames: Vec<RankedFrame> = if batch.len() == 1 {
+            vec![rank_frame(scenario, algorithms[0], batch[0], graph_options, Some(&output.history))]
+        } else {
+            thread::scope(|scope| {
+                let workers: Vec<_> = batch
+                    .iter()
+                    .zip(&algorithms)
+                    .map(|(&time, &algorithm)| scope.spawn(move || rank_frame(scenario, algorithm, time, graph_options, None)))
+                    .collect();
+                workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+            })
+        };
+        for frame in frames {
+            output.frame_hashes.push(frame.hash);
+            output.history.push(frame.time, frame.ranks);
+            output.edge_weights.push(frame.edge_weights);
+            output.pruned_edges.push(frame.pruned_edges);
+            output.convergence.push(frame.convergence);
+            output.profile.resize(output.history.num_of_frames() - 1, FrameProfile::default());
+            output.profile.push(frame.profile);
+            debug!("scenario={} frame={} time={} edges={} pruned={}", scenario.name, output.history.num_of_frames(), frame.time, frame.num_of_edges, frame.pruned_edges);
+            after_frame(output)?;
+        }
+    }
+    Ok(())
+}
+
+struct RankedFrame {
+    time: Time,
+    ranks: Vec<f64>,
+    edge_weights: Vec<f64>,
+    hash: u64,
+    num_of_edges: usize, // after compilation
+    pruned_edges: usize,
+    convergence: Option<Convergence>,
+    profile: FrameProfile,
+}
+
+// `history` holds the frames before, which warm-started and dynamic authority scenarios build on
+fn rank_frame(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, time: Time, graph_options: &GraphOptions, history: Option<&RankHistory>) -> RankedFrame {
+    let teleportation_targets = match (scenario.authority, history) {
+        (Some(authority), Some(history)) => authority.teleportation_targets(scenario, history, time),
+        _ => scenario.teleportation_targets(time),
+    };
+    let previous = history.and_then(|history| Some((*history.times().last()?, history.frames().last()?.as_slice())));
+    let mut profile = FrameProfile::default();
+    let (mut edge_weights, graph) = profile.time(Phase::Decay, || {
+        let edge_weights = scenario.edge_weights(time);
+        let mut graph = compile_graph(&scenario.layered_edges(&edge_weights), &edge_weights, scenario.num_of_nodes, graph_options).unwrap();
+        graph.rank_adjustments = scenario.rank_adjustments(time);
+        graph.active_nodes = scenario.active_nodes(time);
+        graph.conductances = scenario.conductances();
+        scenario.apply_blacklist(&mut graph, time);
+        (edge_weights, graph)
+    });
+    let ranked = Stopwatch::start();
+    let (ranks, convergence) = match (scenario.warm_start, previous) {
+        (Some(warm_start), Some((previous_time, previous_ranks))) => {
+            let retained = (-warm_start.node_decay * time.elapsed_since(previous_time)).exp();
+            let initial_ranks: Vec<f64> = previous_ranks.iter().zip(&teleportation_targets).map(|(&r, &t)| t + (r - t) * retained).collect();
+            (algorithm.rank_from(&graph, &teleportation_targets, &initial_ranks, warm_start.steps), None)
+        }
+        _ => match scenario.initial_ranks.as_ref().and_then(|initial| initial.ranks(scenario, &graph, &teleportation_targets)) {
+            Some(initial_ranks) => algorithm.rank_converged_from(&graph, &teleportation_targets, &initial_ranks),
+            None => algorithm.rank_converged(&graph, &teleportation_targets),
+        },
+    };
+    profile.add(Phase::Ranking, ranked.elapsed());
+    profile.update_peak_memory();
+    // Pruned edges are not rendered either
+    for w in edge_weights.iter_mut().filter(|w| graph_options.prunes(**w)) {
+        *w = 0.0;
+    }
+    RankedFrame { time, hash: frame_hash(&graph, &ranks), ranks, edge_weights, num_of_edges: graph.edges.len(), pruned_edges: graph.pruned_edges, convergence, profile }
+}
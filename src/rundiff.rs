@@ -0,0 +1,134 @@
+use std::io::{self, Write};
+
+use crate::compare::{kendall_tau, spearman};
+use crate::history::RankHistory;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// Nodes drawn in the diff report when the runs have more: those that moved most
+pub const DEFAULT_DIFF_REPORT_NODES: usize = 30;
+
+const CELL_WIDTH: f64 = 36.0;
+const CELL_HEIGHT: f64 = 18.0;
+const MARGIN_LEFT: f64 = 120.0;
+const MARGIN_TOP: f64 = 90.0; // title and the correlation row
+const MARGIN_BOTTOM: f64 = 50.0;
+
+// Colors of the largest rise and fall in the report; no change is white
+const RISE: (u8, u8, u8) = (0x2e, 0x7d, 0x32);
+const FALL: (u8, u8, u8) = (0xc6, 0x28, 0x28);
+
+// One frame of the `diff` subcommand: the ranks of the same time in two runs, e.g. before and
+// after a parameter or data change. Nodes missing from one run have rank 0 there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameDiff {
+    pub time: Time,
+    pub before: Vec<f64>,
+    pub after: Vec<f64>,
+    pub spearman: f64,    // NaN when either run ranks every node the same
+    pub kendall_tau: f64, // NaN when either run ranks every node the same
+    pub l1_change: f64,
+}
+
+impl FrameDiff {
+    pub fn delta(&self, node: NodeId) -> f64 {
+        self.after[node.index()] - self.before[node.index()]
+    }
+}
+
+// The frames of the two runs at the same times, in the order of `before`; frames of one run only
+// are left out
+pub fn diff_histories(before: &RankHistory, after: &RankHistory) -> Vec<FrameDiff> {
+    let num_of_nodes = before.num_of_nodes().max(after.num_of_nodes());
+    let padded = |ranks: &[f64]| {
+        let mut ranks = ranks.to_vec();
+        ranks.resize(num_of_nodes, 0.0);
+        ranks
+    };
+    before
+        .times()
+        .iter()
+        .zip(before.frames())
+        .filter_map(|(&time, ranks)| {
+            let frame = after.times().iter().position(|&t| t == time)?;
+            let (before, after) = (padded(ranks), padded(&after.frames()[frame]));
+            let l1_change = before.iter().zip(&after).map(|(a, b)| (b - a).abs()).sum();
+            Some(FrameDiff { time, spearman: spearman(&before, &after), kendall_tau: kendall_tau(&before, &after), l1_change, before, after })
+        })
+        .collect()
+}
+
+// Nodes by their largest rank change over all frames, biggest movers first
+pub fn largest_movers(diffs: &[FrameDiff]) -> Vec<NodeId> {
+    let num_of_nodes = diffs.first().map_or(0, |diff| diff.before.len());
+    let largest = |node: NodeId| diffs.iter().map(|diff| diff.delta(node).abs()).fold(0.0, f64::max);
+    let mut nodes: Vec<NodeId> = (0..num_of_nodes).map(NodeId).collect();
+    nodes.sort_by(|&a, &b| largest(b).total_cmp(&largest(a)).then(a.cmp(&b)));
+    nodes
+}
+
+// One row per frame and node with both ranks and their difference
+pub fn write_rank_deltas_csv(out: &mut dyn Write, diffs: &[FrameDiff], names: &[String]) -> io::Result<()> {
+    writeln!(out, "frame,time,node,label,before,after,delta")?;
+    for (frame, diff) in diffs.iter().enumerate() {
+        for node in (0..diff.before.len()).map(NodeId) {
+            writeln!(out, "{},{},{},{},{:.6},{:.6},{:+.6}", FrameIdx(frame).number(), diff.time, node.index(), names[node.index()], diff.before[node.index()], diff.after[node.index()], diff.delta(node))?;
+        }
+    }
+    Ok(())
+}
+
+// One row per frame with the rank-order correlations of the runs and the L1 distance of their ranks
+pub fn write_rank_correlation_csv(out: &mut dyn Write, diffs: &[FrameDiff]) -> io::Result<()> {
+    writeln!(out, "frame,time,spearman,kendall_tau,l1_change")?;
+    for (frame, diff) in diffs.iter().enumerate() {
+        writeln!(out, "{},{},{:.6},{:.6},{:.6}", FrameIdx(frame).number(), diff.time, diff.spearman, diff.kendall_tau, diff.l1_change)?;
+    }
+    Ok(())
+}
+
+// Heatmap of the rank changes of `nodes` (rows) over the frames (columns), rises in green and
+// falls in red, with each frame's Spearman correlation above it, as a standalone SVG document
+pub fn write_diff_report(out: &mut dyn Write, diffs: &[FrameDiff], nodes: &[NodeId], names: &[String], title: &str) -> io::Result<()> {
+    let largest = diffs.iter().flat_map(|diff| nodes.iter().map(|&node| diff.delta(node).abs())).fold(0.0, f64::max);
+    let width = MARGIN_LEFT + CELL_WIDTH * diffs.len() as f64 + 20.0;
+    let height = MARGIN_TOP + CELL_HEIGHT * nodes.len() as f64 + MARGIN_BOTTOM;
+
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\">", width, height)?;
+    writeln!(out, "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+    writeln!(out, "  <text x=\"10\" y=\"30\" font-size=\"16\">{}</text>", escape(title))?;
+    writeln!(out, "  <text x=\"10\" y=\"50\" font-size=\"12\">largest change {:.4}, green rises and red falls from before to after</text>", largest)?;
+    writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"end\">Spearman</text>", MARGIN_LEFT - 8.0, MARGIN_TOP - 10.0)?;
+    for (column, diff) in diffs.iter().enumerate() {
+        let x = MARGIN_LEFT + CELL_WIDTH * (column as f64 + 0.5);
+        let correlation = if diff.spearman.is_nan() { "-".to_string() } else { format!("{:.2}", diff.spearman) };
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"middle\">{}</text>", x, MARGIN_TOP - 10.0, correlation)?;
+    }
+    for (row, &node) in nodes.iter().enumerate() {
+        let y = MARGIN_TOP + CELL_HEIGHT * row as f64;
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"end\">{}</text>", MARGIN_LEFT - 8.0, y + CELL_HEIGHT / 2.0 + 4.0, escape(&names[node.index()]))?;
+        for (column, diff) in diffs.iter().enumerate() {
+            let x = MARGIN_LEFT + CELL_WIDTH * column as f64;
+            let delta = diff.delta(node);
+            let position = if largest > 0.0 { delta / largest } else { 0.0 };
+            writeln!(out, "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{}\" height=\"{}\" fill=\"{}\"><title>{} at time {}: {:.4} -> {:.4} ({:+.4})</title></rect>", x, y, CELL_WIDTH, CELL_HEIGHT, diverging(position), escape(&names[node.index()]), diff.time, diff.before[node.index()], diff.after[node.index()], delta)?;
+        }
+    }
+    let bottom = MARGIN_TOP + CELL_HEIGHT * nodes.len() as f64;
+    for (column, diff) in diffs.iter().enumerate() {
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"middle\">{}</text>", MARGIN_LEFT + CELL_WIDTH * (column as f64 + 0.5), bottom + 16.0, diff.time)?;
+    }
+    writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"13\" text-anchor=\"middle\">Time</text>", MARGIN_LEFT + CELL_WIDTH * diffs.len() as f64 / 2.0, bottom + 38.0)?;
+    writeln!(out, "</svg>")
+}
+
+// White at 0, RISE at 1 and FALL at -1
+fn diverging(position: f64) -> String {
+    let (r, g, b) = if position >= 0.0 { RISE } else { FALL };
+    let t = position.abs().min(1.0);
+    let mix = |c: u8| (255.0 + (c as f64 - 255.0) * t).round() as u8;
+    format!("#{:02X}{:02X}{:02X}", mix(r), mix(g), mix(b))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;")
+}
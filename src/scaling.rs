@@ -0,0 +1,93 @@
+use crate::history::RankHistory;
+use crate::style::{Normalization, RenderStyle};
+
+// Post-processing of ranks for display and export. Raw ranks sum to 1 and become tiny on big
+// graphs; scaled scores are computed per frame, so they stay readable at any graph size.
+// Ranking itself, assertions, checkpoints, hashes and the run database always use raw ranks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoreScaling {
+    #[default]
+    Raw,
+    MinMax,     // lowest rank of the frame 0, highest 1
+    ZScore,     // standard deviations from the frame's mean rank
+    Percentile, // share of the other nodes ranked lower (ties count half), 0 to 1
+    Log,        // base-10 logarithm, zero ranks at LOG_FLOOR
+}
+
+// Ranks below this are shown as this on a logarithmic scale
+const LOG_FLOOR: f64 = 1e-12;
+
+impl ScoreScaling {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "raw" => Ok(ScoreScaling::Raw),
+            "minmax" => Ok(ScoreScaling::MinMax),
+            "zscore" => Ok(ScoreScaling::ZScore),
+            "percentile" => Ok(ScoreScaling::Percentile),
+            "log" => Ok(ScoreScaling::Log),
+            _ => Err(format!("unknown score scaling: {} (expected raw, minmax, zscore, percentile or log)", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoreScaling::Raw => "raw",
+            ScoreScaling::MinMax => "minmax",
+            ScoreScaling::ZScore => "zscore",
+            ScoreScaling::Percentile => "percentile",
+            ScoreScaling::Log => "log",
+        }
+    }
+
+    // Scores of one frame's ranks
+    pub fn scale(&self, ranks: &[f64]) -> Vec<f64> {
+        let n = ranks.len() as f64;
+        match self {
+            ScoreScaling::Raw => ranks.to_vec(),
+            ScoreScaling::MinMax => {
+                let (min, max) = ranks.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &r| (lo.min(r), hi.max(r)));
+                // Equal ranks sit in the middle, as on a color scale without range
+                ranks.iter().map(|&r| if max > min { (r - min) / (max - min) } else { 0.5 }).collect()
+            }
+            ScoreScaling::ZScore => {
+                let mean = ranks.iter().sum::<f64>() / n;
+                let deviation = (ranks.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt();
+                ranks.iter().map(|&r| if deviation > 0.0 { (r - mean) / deviation } else { 0.0 }).collect()
+            }
+            ScoreScaling::Percentile => {
+                let mut sorted = ranks.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                ranks
+                    .iter()
+                    .map(|&r| {
+                        if ranks.len() < 2 {
+                            return 0.5;
+                        }
+                        let below = sorted.partition_point(|&s| s < r);
+                        let equal = sorted.partition_point(|&s| s <= r) - below - 1; // other nodes with the same rank
+                        (below as f64 + 0.5 * equal as f64) / (n - 1.0)
+                    })
+                    .collect()
+            }
+            ScoreScaling::Log => ranks.iter().map(|&r| r.max(LOG_FLOOR).log10()).collect(),
+        }
+    }
+
+    pub fn scale_history(&self, history: &RankHistory) -> RankHistory {
+        let mut scaled = RankHistory::new();
+        for (&time, ranks) in history.times().iter().zip(history.frames()) {
+            scaled.push(time, self.scale(ranks));
+        }
+        scaled
+    }
+
+    // Absolute and logarithmic colors assume ranks between 0 and 1; scaled scores are colored
+    // over their range in the run instead
+    pub fn render_style(&self, style: &RenderStyle) -> RenderStyle {
+        let mut style = style.clone();
+        if *self != ScoreScaling::Raw && matches!(style.normalization, Normalization::None | Normalization::Log) {
+            style.normalization = Normalization::Global;
+        }
+        style
+    }
+}
@@ -0,0 +1,1359 @@
+use std::borrow::Cow;
+use std::f64::consts::PI;
+use std::fmt;
+use std::fs;
+
+use crate::assertions::Assertion;
+use crate::builder::ScenarioBuilder;
+use crate::clusters::{metadata_clusters, Cluster, ClusterBy};
+use crate::decay::{decay_constant_for_half_life, describe_half_life, DecayKernel, DecayTable, DecayTableCache};
+use crate::gexf::read_gexf;
+use crate::graph::{compile_graph, edge_ids, exponential_decay, layer_factors, CompiledEdge, CompiledGraph, Edge, GraphError, GraphOptions, ParallelEdgePolicy, DEFAULT_DECAY_CONSTANT};
+use crate::graphml::read_graphml;
+use crate::keyring::{read_gpg_signatures, read_signature_csv};
+use crate::naming::FrameNames;
+use crate::nodes::{parse_node_attributes, NodeInfo, NodeRow};
+use crate::provenance::EdgeSource;
+use crate::random::DEFAULT_SEED;
+use crate::reviews::{read_git_log, read_github_reviews};
+use crate::run::{DampingSchedule, DynamicAuthority, InitialRanks, WarmStart, DEFAULT_AUTHORITY_SMOOTHING};
+use crate::script::{ScenarioScripts, Script};
+use crate::seeds::ExpertGroup;
+use crate::simulation::SimulationConfig;
+use crate::stats::ConcentrationAlarm;
+use crate::style::RenderStyle;
+use crate::types::{EdgeId, FrameIdx, NodeId, Time};
+use crate::temporal::time_respecting_edges;
+use crate::timeline::TemporalGraph;
+use crate::window::{windowed_weights, TimeWindow};
+
+// A scenario file is a plain text file with one directive per line, e.g.
+//
+//   # comments start with '#'
+//   name trust-flow-example
+//   nodes 6
+//   expert 0
+//   expert 3 weight 2 from 5 until 12  # optional teleport weight and period (from inclusive, until exclusive)
+//   expert 4 aging 0.05 since activity  # teleport weight decays from designation (or the expert's last endorsement)
+//   node 0 name=alice category=moderator org=acme pos=1,0 teleport=2 prior=0.7  # optional metadata, 'expert' makes it an expert
+//   node 5 joins=4 leaves=12  # the node only takes part in frames from time 4 until before 12
+//   join-on-first-edge  # nodes without a join time join when their first edge is created
+//   category vouch decay 0.01 weight 2  # edge category with its own decay constant and initial weight
+//   layer payments decay 0.05 weight 2  # parallel edge set with its own decay constant and share of every node's outflow
+//   edge 0 1 1.0        # source target time_of_creation [event_id] [category=vouch] [layer=payments]
+//   edge 1 2 3.0 tx-17 system=ledger  # any other key=value is metadata carried into exports
+//   penalize 3 0.05 at 9  # node, rank (mass) removed per iteration, time; 'reward' adds rank
+//   retract 0 1 at 9 by 0.5  # cut the remaining weight of the edges from 0 to 1 by half at time 9; all of it without 'by'
+//   parallel-edges sum  # merge parallel edges (sum or max of their weights, or 'cap 3' for a sum capped at 3); 'all' keeps them apart
+//   undirected          # every edge records mutual trust and counts in both directions
+//   edge-direction endorsee-to-endorser  # arrows point at the endorser, so trust flows against them; endorser-to-endorsee by default
+//   expert-group founders 0,1  # experts of a personalization to compare with other groups, see rank_expert_groups
+//   bipartite 4,5,6     # nodes 4 to 6 are items rated by the other nodes; edges go from raters to items
+//   prune 0.001         # edges weighing less are left out of ranking and rendering
+//   decay 0.1           # exponential decay constant, 0 disables decay
+//   fast-exp            # approximate exponential (relative error below 2e-7) for very large graphs
+//   window sliding 5 1  # only edges created in [t - 5, t], frames 1 time unit apart
+//   window tumbling 5   # non-overlapping buckets of 5 time units, one frame per bucket
+//   frames from 0 to 10 step 0.5  # sample frames at 0, 0.5, ..., 10; each part is optional
+//   frames at 0,2.5,7.25  # or at exactly these times
+//   frame-names {scenario}-{index:5}-t{time}  # file names of the frames, see FrameNames
+//   warm-start 2        # rank every frame incrementally from the last one, with 2 iterations
+//   node-decay 0.05     # carried-over ranks return to the teleportation baseline at this rate
+//   initial-ranks experts  # power iteration starts on the experts; 'priors', 'uniform' or a rank per node like 0.5,0.5,0
+//   local-trust 0       # ego-centric ranks: teleportation at the nodes node 0 endorses instead of at the experts
+//   authority 3 smoothing 0.5  # the top 3 nodes of every frame are the experts of the next
+//   damping 0.5..0.9    # damping factor annealed from the first frame to the last; or 0.5,0.7,0.9 per frame
+//   blacklist 4 from 9  # node 4 is banned from time 9 on and absorbs rank; 'remove' cuts it off instead
+//   time-respecting     # trust only flows along edges made after their source was reached from an expert
+//   annotate 9 attack begins  # caption shown from time 9 until the next annotation
+//   trusted-threshold 0.2
+//   assert rank 4 > rank 2
+//   assert trusted 1 by frame 5
+//   alarm top 3 above 0.6  # warn when the three highest ranked nodes hold more than 60% of the rank
+//   style colormap viridis
+//   script decay w0 / (1.0 + k * age)  # a Rhai script, with the scripting feature
+//   simulate ticks 30   # generate edges with a stochastic process, see SimulationConfig
+//   seed 7              # seed of every random process ('simulate seed 7' works too)
+pub const EXPERT_TELEPORT_FRACTION: f64 = 0.8; // fraction of teleported rank (mass) directed to experts
+
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub num_of_nodes: usize,
+    pub experts: Vec<NodeId>, // every node that is an expert at some point
+    pub expert_groups: Vec<ExpertGroup>, // alternative expert sets, ranked side by side on request
+    pub expert_terms: Vec<ExpertTerm>, // periods of experts that are not experts all the time
+    pub expert_aging: Vec<ExpertAging>, // experts whose teleport weight decays; at most one per expert
+    pub node_info: Vec<NodeInfo>, // indexed by node; may be shorter than the number of nodes
+    pub edges: Vec<Edge>,
+    pub reversed_edges: bool, // the input records edges from endorsee to endorser; they are stored turned around, the way trust flows
+    pub items: Vec<NodeId>, // the rated side of a bipartite rater → item graph, in order; empty for other graphs
+    pub edge_sources: Vec<EdgeSource>, // where each edge was read from, indexed by EdgeId; empty when unknown
+    pub edge_categories: Vec<EdgeCategory>,
+    pub edge_category: Vec<Option<usize>>, // index into edge_categories, indexed by EdgeId; edges beyond its end have none
+    pub edge_layers: Vec<EdgeLayer>,
+    pub edge_layer: Vec<Option<usize>>, // index into edge_layers, indexed by EdgeId; edges beyond its end have none
+    pub rank_events: Vec<RankEvent>,
+    pub corrections: Vec<EdgeCorrection>, // retractions of edges created before them
+    pub annotations: Vec<Annotation>, // in time order
+    pub graph_options: GraphOptions,
+    pub decay_constant: f64,
+    pub decay_kernel: DecayKernel, // how edge weights are decayed: exponential and threads
+    pub decay_table: DecayTableCache, // decay factors of whole ages, built by the first frame when edges are created at whole times
+    pub window: Option<TimeWindow>,
+    pub frame_schedule: Option<FrameSchedule>, // when frames are sampled; see frame_times
+    pub frame_names: FrameNames, // what the files of the frames are called
+    pub warm_start: Option<WarmStart>, // frames continue from the previous frame's ranks
+    pub initial_ranks: Option<InitialRanks>, // where frames ranked from scratch start; the algorithm's own start when not set
+    pub local_trust: Option<NodeId>, // whose endorsees teleportation goes to, instead of the experts
+    pub authority: Option<DynamicAuthority>, // teleportation follows the top nodes of the previous frame
+    pub damping: Option<DampingSchedule>, // per-frame damping factor instead of the algorithm's own
+    pub blacklist: Vec<Blacklisting>,
+    pub time_respecting: bool, // drop edges that are on no time-respecting path from the experts
+    pub join_on_first_edge: bool, // nodes without a join time join with their first edge
+    pub trusted_threshold: Option<f64>, // rank above which a node counts as trusted; uniform share by default
+    pub assertions: Vec<Assertion>,
+    pub alarms: Vec<ConcentrationAlarm>, // rank concentration logged as a warning, see concentration_alarms
+    pub style: RenderStyle,
+    pub scripts: ScenarioScripts,
+    pub simulation: Option<SimulationConfig>, // generate further edges with a stochastic process
+    pub seed: u64, // seed of every random process of the run, recorded in the exports
+}
+
+// Period during which a node is an expert; an open bound extends to the start or end of time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpertTerm {
+    pub node: NodeId,
+    pub from: Option<Time>,  // inclusive
+    pub until: Option<Time>, // exclusive
+}
+
+impl ExpertTerm {
+    pub fn contains(&self, time: Time) -> bool {
+        self.from.is_none_or(|from| from <= time) && self.until.is_none_or(|until| time < until)
+    }
+}
+
+// An expert whose share of the experts' teleported rank (mass) decays exponentially with the time
+// since it became an expert or last endorsed someone; what it loses is teleported like the rank
+// (mass) not directed to experts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpertAging {
+    pub node: NodeId,
+    pub decay_constant: f64,
+    pub since: AgingStart,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgingStart {
+    Designation, // the start of the expert's current term, or the first edge of the scenario
+    Activity,    // the expert's latest edge, or designation before it has any
+}
+
+// A banned node. From `from` on it absorbs the rank (mass) it receives and emits none along its
+// edges, or, when removed, loses all its edges and its share of teleported rank.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blacklisting {
+    pub node: NodeId,
+    pub from: Time,
+    pub removed: bool,
+}
+
+// Kind of interaction an edge records, e.g. a vouch, a transaction or a review. Kinds lose
+// relevance at different speeds, so each has its own decay constant and weight at creation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeCategory {
+    pub name: String,
+    pub decay_constant: Option<f64>, // the scenario's decay constant when not given
+    pub weight: f64,
+}
+
+// Evidence channel of a multiplex graph, e.g. code reviews or payments. Every layer has its own
+// decay, and its weight is its share of each node's outflow against the node's other layers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeLayer {
+    pub name: String,
+    pub decay_constant: Option<f64>, // the scenario's decay constant when not given
+    pub weight: f64,
+}
+
+// Exogenous change of a node's rank, e.g. a moderator's penalty for a confirmed violation.
+// It takes effect at its time and fades like an edge created at that time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankEvent {
+    pub node: NodeId,
+    pub amount: f64, // rank (mass) added per iteration; negative for penalties
+    pub time: Time,
+}
+
+// Amendment of the trust history, e.g. a retracted endorsement: from its time on, the edges from
+// source to target created until then keep only 1 - share of their weight, and keep decaying.
+// Several corrections of the same edges multiply.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeCorrection {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub time: Time,
+    pub share: f64, // of the remaining weight that is cut, in (0, 1]
+}
+
+impl EdgeCorrection {
+    // The factor the correction applies to an edge's weight at the given time
+    pub fn factor(&self, edge: &Edge, time: Time) -> f64 {
+        let applies = edge.source == self.source && edge.target == self.target && edge.time_of_creation <= self.time && self.time <= time;
+        if applies { 1.0 - self.share } else { 1.0 }
+    }
+}
+
+// Frames are sampled up to this time unless a schedule or simulation says otherwise
+pub const DEFAULT_END_TIME: f64 = 20.0;
+
+// When frames are sampled. Parts of a range left unset keep the defaults: from 0 to
+// DEFAULT_END_TIME (or the last simulated tick), one time unit apart.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameSchedule {
+    Range { start: Option<Time>, end: Option<Time>, step: Option<f64> },
+    Times(Vec<Time>),
+}
+
+// Human-readable caption of a point in time, e.g. "node 5 joins", for frame labels and the viewer timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub time: Time,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioError {
+    pub line: usize, // 1-based, 0 when the error is not tied to a line
+    pub message: String,
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: {}", self.line, self.message)
+        } else {
+            write!(f, "{}", self.message)
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+pub(crate) fn parse_number<T: std::str::FromStr>(token: Option<&str>, what: &str, line: usize) -> Result<T, ScenarioError> {
+    let token = token.ok_or_else(|| ScenarioError { line, message: format!("missing {}", what) })?;
+    token.parse().map_err(|_| ScenarioError { line, message: format!("invalid {}: {}", what, token) })
+}
+
+// A positive half-life, as the decay constant it stands for
+fn parse_half_life(token: Option<&str>, what: &str, line: usize) -> Result<f64, ScenarioError> {
+    let half_life: f64 = parse_number(token, what, line)?;
+    if half_life > 0.0 {
+        Ok(decay_constant_for_half_life(half_life))
+    } else {
+        Err(ScenarioError { line, message: format!("{} must be positive: {}", what, half_life) })
+    }
+}
+
+impl Scenario {
+    pub fn new(name: &str, num_of_nodes: usize, experts: Vec<NodeId>, edges: Vec<Edge>) -> Self {
+        Scenario {
+            name: name.to_string(),
+            num_of_nodes,
+            experts,
+            expert_groups: Vec::new(),
+            expert_terms: Vec::new(),
+            expert_aging: Vec::new(),
+            node_info: Vec::new(),
+            edges,
+            reversed_edges: false,
+            items: Vec::new(),
+            edge_sources: Vec::new(),
+            edge_categories: Vec::new(),
+            edge_category: Vec::new(),
+            edge_layers: Vec::new(),
+            edge_layer: Vec::new(),
+            rank_events: Vec::new(),
+            corrections: Vec::new(),
+            annotations: Vec::new(),
+            graph_options: GraphOptions::default(),
+            decay_constant: DEFAULT_DECAY_CONSTANT,
+            decay_kernel: DecayKernel::default(),
+            decay_table: DecayTableCache::default(),
+            window: None,
+            frame_schedule: None,
+            frame_names: FrameNames::default(),
+            warm_start: None,
+            initial_ranks: None,
+            local_trust: None,
+            damping: None,
+            blacklist: Vec::new(),
+            authority: None,
+            time_respecting: false,
+            join_on_first_edge: false,
+            trusted_threshold: None,
+            assertions: Vec::new(),
+            alarms: Vec::new(),
+            style: RenderStyle::default(),
+            scripts: ScenarioScripts::default(),
+            simulation: None,
+            seed: DEFAULT_SEED,
+        }
+    }
+
+    // Fluent construction with named nodes, see ScenarioBuilder
+    pub fn builder(name: &str) -> ScenarioBuilder {
+        ScenarioBuilder::new(name)
+    }
+
+    // Scenario files, or GraphML (.graphml) and GEXF (.gexf) graphs
+    pub fn from_file(pathname: &str) -> Result<Scenario, ScenarioError> {
+        let text = fs::read_to_string(pathname)
+            .map_err(|e| ScenarioError { line: 0, message: format!("cannot read {}: {}", pathname, e) })?;
+        let file_stem = pathname
+            .rsplit('/')
+            .next()
+            .and_then(|f| f.split('.').next())
+            .unwrap_or("scenario");
+        let mut scenario = if pathname.ends_with(".graphml") {
+            read_graphml(&text, file_stem)?
+        } else if pathname.ends_with(".gexf") {
+            read_gexf(&text, file_stem)?
+        } else if pathname.ends_with(".gitlog") {
+            read_git_log(&text, file_stem)?
+        } else if pathname.ends_with(".reviews.json") {
+            read_github_reviews(&text, file_stem)?
+        } else if pathname.ends_with(".sigs") {
+            read_gpg_signatures(&text, file_stem)?
+        } else if pathname.ends_with(".sigs.csv") {
+            read_signature_csv(&text, file_stem)?
+        } else {
+            Scenario::parse(&text)?
+        };
+        if scenario.name.is_empty() {
+            scenario.name = file_stem.to_string();
+        }
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError> {
+        let mut scenario = Scenario::new("", 0, Vec::new(), Vec::new());
+
+        for (i, raw_line) in text.lines().enumerate() {
+            let line = i + 1;
+            let content = raw_line.split('#').next().unwrap().trim();
+            let mut tokens = content.split_whitespace();
+            let Some(directive) = tokens.next() else { continue };
+            match directive {
+                "name" => scenario.name = tokens.collect::<Vec<_>>().join(" "),
+                "nodes" => scenario.num_of_nodes = parse_number(tokens.next(), "node count", line)?,
+                "expert" => {
+                    let node = NodeId(parse_number(tokens.next(), "expert node", line)?);
+                    let mut term = ExpertTerm { node, from: None, until: None };
+                    let mut aging: Option<ExpertAging> = None;
+                    while let Some(keyword) = tokens.next() {
+                        match keyword {
+                            "weight" => {
+                                let weight: f64 = parse_number(tokens.next(), "expert weight", line)?;
+                                scenario.apply_node_rows(vec![NodeRow {
+                                    node,
+                                    info: NodeInfo { teleport_weight: Some(weight), ..NodeInfo::default() },
+                                    expert: false,
+                                }]);
+                            }
+                            "from" => term.from = Some(Time(parse_number(tokens.next(), "expert start", line)?)),
+                            "until" => term.until = Some(Time(parse_number(tokens.next(), "expert end", line)?)),
+                            "aging" => {
+                                let decay_constant: f64 = parse_number(tokens.next(), "expert aging decay constant", line)?;
+                                if !(decay_constant >= 0.0 && decay_constant.is_finite()) {
+                                    return Err(ScenarioError { line, message: format!("expert aging decay constant must be non-negative: {}", decay_constant) });
+                                }
+                                aging = Some(ExpertAging { node, decay_constant, since: AgingStart::Designation });
+                            }
+                            "since" => {
+                                let since = match tokens.next() {
+                                    Some("designation") => AgingStart::Designation,
+                                    Some("activity") => AgingStart::Activity,
+                                    other => return Err(ScenarioError { line, message: format!("expected 'designation' or 'activity' after 'since', found {}", other.unwrap_or("nothing")) }),
+                                };
+                                let aging = aging.as_mut().ok_or_else(|| ScenarioError { line, message: "'since' requires 'aging' before it".to_string() })?;
+                                aging.since = since;
+                            }
+                            _ => return Err(ScenarioError { line, message: format!("expected 'weight', 'from', 'until', 'aging' or 'since', found {}", keyword) }),
+                        }
+                    }
+                    if let Some(aging) = aging {
+                        scenario.expert_aging.retain(|a| a.node != node);
+                        scenario.expert_aging.push(aging);
+                    }
+                    if !scenario.experts.contains(&node) {
+                        scenario.experts.push(node);
+                    }
+                    if term.from.is_some() || term.until.is_some() {
+                        scenario.expert_terms.push(term);
+                    }
+                }
+                "blacklist" => {
+                    let node = NodeId(parse_number(tokens.next(), "blacklisted node", line)?);
+                    let mut blacklisting = Blacklisting { node, from: Time(f64::NEG_INFINITY), removed: false };
+                    while let Some(keyword) = tokens.next() {
+                        match keyword {
+                            "from" => blacklisting.from = Time(parse_number(tokens.next(), "blacklisting start", line)?),
+                            "remove" => blacklisting.removed = true,
+                            _ => return Err(ScenarioError { line, message: format!("expected 'from' or 'remove', found {}", keyword) }),
+                        }
+                    }
+                    scenario.blacklist.push(blacklisting);
+                }
+                "node" => {
+                    let node = NodeId(parse_number(tokens.next(), "node", line)?);
+                    let row = parse_node_attributes(node, tokens).map_err(|message| ScenarioError { line, message })?;
+                    scenario.apply_node_rows(vec![row]);
+                }
+                "edge" => {
+                    let source = NodeId(parse_number(tokens.next(), "edge source", line)?);
+                    let target = NodeId(parse_number(tokens.next(), "edge target", line)?);
+                    let time_of_creation = Time(parse_number(tokens.next(), "edge time", line)?);
+                    let mut event_id = None;
+                    let mut category = None;
+                    let mut layer = None;
+                    let mut metadata = Vec::new();
+                    for token in tokens.by_ref() {
+                        if let Some(name) = token.strip_prefix("category=") {
+                            let index = scenario.edge_categories.iter().position(|c| c.name == name);
+                            category = Some(index.ok_or_else(|| ScenarioError { line, message: format!("unknown edge category: {} (declare it with a 'category' line first)", name) })?);
+                        } else if let Some(name) = token.strip_prefix("layer=") {
+                            let index = scenario.edge_layers.iter().position(|l| l.name == name);
+                            layer = Some(index.ok_or_else(|| ScenarioError { line, message: format!("unknown edge layer: {} (declare it with a 'layer' line first)", name) })?);
+                        } else if let Some((key, value)) = token.split_once('=') {
+                            metadata.push((key.to_string(), value.to_string()));
+                        } else {
+                            event_id = Some(token.to_string());
+                        }
+                    }
+                    let weight = category.map_or(1.0, |c| scenario.edge_categories[c].weight);
+                    scenario.edges.push(Edge { source, target, time_of_creation, weight });
+                    scenario.edge_sources.push(EdgeSource { line, event_id, metadata });
+                    scenario.edge_category.resize(scenario.edges.len() - 1, None);
+                    scenario.edge_category.push(category);
+                    scenario.edge_layer.resize(scenario.edges.len() - 1, None);
+                    scenario.edge_layer.push(layer);
+                }
+                "layer" => {
+                    let name = tokens.next().ok_or_else(|| ScenarioError { line, message: "missing layer name".to_string() })?;
+                    let mut layer = EdgeLayer { name: name.to_string(), decay_constant: None, weight: 1.0 };
+                    while let Some(key) = tokens.next() {
+                        match key {
+                            "decay" => layer.decay_constant = Some(parse_number(tokens.next(), "layer decay constant", line)?),
+                            "half-life" => layer.decay_constant = Some(parse_half_life(tokens.next(), "layer half-life", line)?),
+                            "weight" => layer.weight = parse_number(tokens.next(), "layer weight", line)?,
+                            other => return Err(ScenarioError { line, message: format!("expected 'decay', 'half-life' or 'weight' for an edge layer, found {}", other) }),
+                        }
+                    }
+                    if layer.weight < 0.0 {
+                        return Err(ScenarioError { line, message: format!("layer weight must not be negative: {}", layer.weight) });
+                    }
+                    match scenario.edge_layers.iter_mut().find(|l| l.name == name) {
+                        Some(existing) => *existing = layer,
+                        None => scenario.edge_layers.push(layer),
+                    }
+                }
+                "category" => {
+                    let name = tokens.next().ok_or_else(|| ScenarioError { line, message: "missing category name".to_string() })?;
+                    let mut category = EdgeCategory { name: name.to_string(), decay_constant: None, weight: 1.0 };
+                    while let Some(key) = tokens.next() {
+                        match key {
+                            "decay" => category.decay_constant = Some(parse_number(tokens.next(), "category decay constant", line)?),
+                            "half-life" => category.decay_constant = Some(parse_half_life(tokens.next(), "category half-life", line)?),
+                            "weight" => category.weight = parse_number(tokens.next(), "category weight", line)?,
+                            other => return Err(ScenarioError { line, message: format!("expected 'decay', 'half-life' or 'weight' for an edge category, found {}", other) }),
+                        }
+                    }
+                    match scenario.edge_categories.iter_mut().find(|c| c.name == name) {
+                        Some(existing) => *existing = category,
+                        None => scenario.edge_categories.push(category),
+                    }
+                }
+                "penalize" | "reward" => {
+                    let node = NodeId(parse_number(tokens.next(), "node", line)?);
+                    let amount: f64 = parse_number(tokens.next(), "rank amount", line)?;
+                    if amount < 0.0 {
+                        return Err(ScenarioError { line, message: format!("{} amount must not be negative", directive) });
+                    }
+                    if tokens.next() != Some("at") {
+                        return Err(ScenarioError { line, message: format!("expected '{} <node> <amount> at <time>'", directive) });
+                    }
+                    let time = Time(parse_number(tokens.next(), "event time", line)?);
+                    let amount = if directive == "penalize" { -amount } else { amount };
+                    scenario.rank_events.push(RankEvent { node, amount, time });
+                }
+                "retract" => {
+                    let source = NodeId(parse_number(tokens.next(), "retracted edge source", line)?);
+                    let target = NodeId(parse_number(tokens.next(), "retracted edge target", line)?);
+                    if tokens.next() != Some("at") {
+                        return Err(ScenarioError { line, message: "expected 'retract <source> <target> at <time> [by <share>]'".to_string() });
+                    }
+                    let time = Time(parse_number(tokens.next(), "retraction time", line)?);
+                    let share = match tokens.next() {
+                        Some("by") => parse_number(tokens.next(), "retracted share", line)?,
+                        None => 1.0,
+                        Some(other) => return Err(ScenarioError { line, message: format!("expected 'by <share>', found {}", other) }),
+                    };
+                    scenario.corrections.push(EdgeCorrection { source, target, time, share });
+                }
+                "annotate" => {
+                    let time = Time(parse_number(tokens.next(), "annotation time", line)?);
+                    let text = tokens.collect::<Vec<_>>().join(" ");
+                    if text.is_empty() {
+                        return Err(ScenarioError { line, message: "expected 'annotate <time> <text>'".to_string() });
+                    }
+                    let position = scenario.annotations.partition_point(|a| a.time <= time);
+                    scenario.annotations.insert(position, Annotation { time, text });
+                }
+                "parallel-edges" => {
+                    scenario.graph_options.parallel_edges = match tokens.next() {
+                        Some("sum") => ParallelEdgePolicy::SumWeights,
+                        Some("max") => ParallelEdgePolicy::KeepMax,
+                        Some("cap") => {
+                            let cap: f64 = parse_number(tokens.next(), "parallel edge weight cap", line)?;
+                            if cap <= 0.0 {
+                                return Err(ScenarioError { line, message: "parallel edge weight cap must be positive".to_string() });
+                            }
+                            ParallelEdgePolicy::SaturatingSum(cap)
+                        }
+                        Some("all") => ParallelEdgePolicy::KeepAll,
+                        other => return Err(ScenarioError { line, message: format!("expected 'sum', 'max', 'cap' or 'all' for parallel edges, found {:?}", other) }),
+                    };
+                }
+                "warm-start" => {
+                    let steps = match tokens.next() {
+                        Some(steps) => Some(parse_number(Some(steps), "warm start iterations", line)?),
+                        None => None,
+                    };
+                    scenario.warm_start = Some(WarmStart { steps, node_decay: scenario.warm_start.map_or(0.0, |w| w.node_decay) });
+                }
+                "initial-ranks" => {
+                    let initial = tokens.collect::<Vec<_>>().join("");
+                    scenario.initial_ranks = Some(InitialRanks::parse(&initial).map_err(|message| ScenarioError { line, message })?);
+                }
+                "expert-group" => {
+                    let name = tokens.next().ok_or_else(|| ScenarioError { line, message: "missing expert group name".to_string() })?.to_string();
+                    let list = tokens.collect::<Vec<_>>().join("");
+                    let mut members = list.split(',').map(|item| parse_number(Some(item), "expert group member", line).map(NodeId)).collect::<Result<Vec<_>, _>>()?;
+                    members.sort();
+                    members.dedup();
+                    if scenario.expert_groups.iter().any(|group| group.name == name) {
+                        return Err(ScenarioError { line, message: format!("expert group {} is defined twice", name) });
+                    }
+                    scenario.expert_groups.push(ExpertGroup { name, members });
+                }
+                "bipartite" => {
+                    let list = tokens.collect::<Vec<_>>().join("");
+                    for item in list.split(',') {
+                        scenario.items.push(NodeId(parse_number(Some(item), "item node", line)?));
+                    }
+                    scenario.items.sort();
+                    scenario.items.dedup();
+                }
+                "local-trust" => scenario.local_trust = Some(NodeId(parse_number(tokens.next(), "local trust node", line)?)),
+                "node-decay" => {
+                    let node_decay = parse_number(tokens.next(), "node decay constant", line)?;
+                    scenario.warm_start = Some(WarmStart { node_decay, steps: scenario.warm_start.and_then(|w| w.steps) });
+                }
+                "authority" => {
+                    let top = parse_number(tokens.next(), "number of authority nodes", line)?;
+                    let smoothing = match tokens.next() {
+                        Some("smoothing") => parse_number(tokens.next(), "authority smoothing", line)?,
+                        None => DEFAULT_AUTHORITY_SMOOTHING,
+                        Some(other) => return Err(ScenarioError { line, message: format!("expected 'smoothing' after the number of authority nodes, found {:?}", other) }),
+                    };
+                    scenario.authority = Some(DynamicAuthority { top, smoothing });
+                }
+                "damping" => {
+                    let schedule = tokens.next().ok_or_else(|| ScenarioError { line, message: "expected a damping factor or schedule".to_string() })?;
+                    scenario.damping = Some(DampingSchedule::parse(schedule).map_err(|message| ScenarioError { line, message })?);
+                }
+                "edge-direction" => {
+                    scenario.reversed_edges = match tokens.next() {
+                        Some("endorser-to-endorsee") => false,
+                        Some("endorsee-to-endorser") => true,
+                        other => return Err(ScenarioError { line, message: format!("expected 'endorser-to-endorsee' or 'endorsee-to-endorser' for the edge direction, found {:?}", other) }),
+                    };
+                }
+                "undirected" => scenario.graph_options.undirected = true,
+                "prune" => scenario.prune_below(parse_number(tokens.next(), "pruning threshold", line)?),
+                "time-respecting" => scenario.time_respecting = true,
+                "join-on-first-edge" => scenario.join_on_first_edge = true,
+                "decay" => scenario.decay_constant = parse_number(tokens.next(), "decay constant", line)?,
+                "half-life" => scenario.decay_constant = parse_half_life(tokens.next(), "half-life", line)?,
+                "fast-exp" => scenario.decay_kernel.fast_exp = true,
+                "window" => {
+                    scenario.window = Some(match tokens.next() {
+                        Some("sliding") => {
+                            let size = parse_number(tokens.next(), "window size", line)?;
+                            let stride = match tokens.next() {
+                                Some(stride) => parse_number(Some(stride), "window stride", line)?,
+                                None => 1.0,
+                            };
+                            TimeWindow::sliding(size, stride)
+                        }
+                        Some("tumbling") => TimeWindow::tumbling(parse_number(tokens.next(), "window size", line)?),
+                        other => return Err(ScenarioError { line, message: format!("expected 'sliding' or 'tumbling' window, found {:?}", other) }),
+                    });
+                }
+                "frames" => {
+                    if content.split_whitespace().nth(1) == Some("at") {
+                        tokens.next();
+                        let list = tokens.collect::<Vec<_>>().join("");
+                        let times = list.split(',').map(|t| parse_number(Some(t), "frame time", line).map(Time)).collect::<Result<_, _>>()?;
+                        scenario.frame_schedule = Some(FrameSchedule::Times(times));
+                        continue;
+                    }
+                    let (mut start, mut end, mut step) = match scenario.frame_schedule.take() {
+                        Some(FrameSchedule::Range { start, end, step }) => (start, end, step),
+                        _ => (None, None, None),
+                    };
+                    while let Some(keyword) = tokens.next() {
+                        match keyword {
+                            "from" => start = Some(Time(parse_number(tokens.next(), "first frame time", line)?)),
+                            "to" => end = Some(Time(parse_number(tokens.next(), "last frame time", line)?)),
+                            "step" => step = Some(parse_number(tokens.next(), "frame step", line)?),
+                            other => return Err(ScenarioError { line, message: format!("expected 'at', 'from', 'to' or 'step' for frames, found {:?}", other) }),
+                        }
+                    }
+                    scenario.frame_schedule = Some(FrameSchedule::Range { start, end, step });
+                }
+                "frame-names" => {
+                    let template = content["frame-names".len()..].trim();
+                    scenario.frame_names = FrameNames::parse(template).map_err(|message| ScenarioError { line, message })?;
+                }
+                "trusted-threshold" => scenario.trusted_threshold = Some(parse_number(tokens.next(), "threshold", line)?),
+                "style" => {
+                    let key = tokens.next().ok_or_else(|| ScenarioError { line, message: "missing style setting".to_string() })?;
+                    let value = tokens.collect::<Vec<_>>().join(" ");
+                    scenario.style.set(key, &value).map_err(|message| ScenarioError { line, message })?;
+                }
+                "script" => {
+                    let hook = tokens.next().ok_or_else(|| ScenarioError { line, message: "missing script hook".to_string() })?;
+                    let expression = content["script".len()..].trim()[hook.len()..].trim();
+                    scenario.scripts.set(hook, expression).map_err(|message| ScenarioError { line, message })?;
+                }
+                "seed" => scenario.seed = parse_number(tokens.next(), "seed", line)?,
+                "simulate" => {
+                    let setting = tokens.next().ok_or_else(|| ScenarioError { line, message: "missing simulation setting".to_string() })?;
+                    if setting == "seed" {
+                        scenario.seed = parse_number(tokens.next(), "seed", line)?;
+                        scenario.simulation.get_or_insert_with(SimulationConfig::default);
+                        continue;
+                    }
+                    let values: Vec<&str> = tokens.collect();
+                    scenario.simulation.get_or_insert_with(SimulationConfig::default).set(setting, &values).map_err(|message| ScenarioError { line, message })?;
+                }
+                "assert" => {
+                    let rest = content["assert".len()..].trim();
+                    scenario.assertions.push(Assertion::parse(rest, line)?);
+                }
+                "alarm" => {
+                    let rest = content["alarm".len()..].trim();
+                    scenario.alarms.push(ConcentrationAlarm::parse(rest).map_err(|message| ScenarioError { line, message })?);
+                }
+                _ => return Err(ScenarioError { line, message: format!("unknown directive: {}", directive) }),
+            }
+        }
+
+        if scenario.reversed_edges {
+            scenario.edges = scenario.edges.iter().map(Edge::reversed).collect();
+            scenario.corrections.iter_mut().for_each(|c| (c.source, c.target) = (c.target, c.source));
+        }
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    // Turns every edge around, for inputs whose arrows point from endorsee to endorser (or back)
+    pub fn reverse_edges(&mut self) {
+        self.edges = self.edges.iter().map(Edge::reversed).collect();
+        self.corrections.iter_mut().for_each(|c| (c.source, c.target) = (c.target, c.source));
+        self.reversed_edges = !self.reversed_edges;
+    }
+
+    // An edge as the input records it, pointed the way trust flows
+    pub fn oriented(&self, edge: Edge) -> Edge {
+        if self.reversed_edges { edge.reversed() } else { edge }
+    }
+
+    // Whether the edges probably point the wrong way: the experts, where trust starts, endorse
+    // nobody but are endorsed
+    pub fn looks_reversed(&self) -> bool {
+        let (mut outgoing, mut incoming) = (0, 0);
+        for edge in self.edges.iter().filter(|e| e.source != e.target) {
+            outgoing += usize::from(self.experts.contains(&edge.source));
+            incoming += usize::from(self.experts.contains(&edge.target));
+        }
+        !self.graph_options.undirected && outgoing == 0 && incoming > 0
+    }
+
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        let check = |node: NodeId, what: &str| {
+            if node.index() < self.num_of_nodes {
+                Ok(())
+            } else {
+                Err(ScenarioError { line: 0, message: format!("{} {} is out of range ({} nodes)", what, node, self.num_of_nodes) })
+            }
+        };
+        if self.num_of_nodes == 0 {
+            return Err(ScenarioError { line: 0, message: "scenario declares no nodes".to_string() });
+        }
+        if let Some(window) = &self.window
+            && (window.size <= 0.0 || window.stride <= 0.0) {
+            return Err(ScenarioError { line: 0, message: "window size and stride must be positive".to_string() });
+        }
+        if self.warm_start.is_some_and(|w| w.node_decay < 0.0 || w.steps == Some(0)) {
+            return Err(ScenarioError { line: 0, message: "node decay must not be negative and warm start needs at least one iteration".to_string() });
+        }
+        if let Some(FrameSchedule::Range { start, end, step }) = &self.frame_schedule {
+            if step.is_some_and(|step| step <= 0.0) {
+                return Err(ScenarioError { line: 0, message: "frame step must be positive".to_string() });
+            }
+            if let (Some(start), Some(end)) = (start, end)
+                && end < start {
+                return Err(ScenarioError { line: 0, message: format!("frames end at {} before they start at {}", end, start) });
+            }
+        }
+        if let Some(FrameSchedule::Times(times)) = &self.frame_schedule
+            && times.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err(ScenarioError { line: 0, message: "frame times must increase".to_string() });
+        }
+        if self.graph_options.prune_below < 0.0 {
+            return Err(ScenarioError { line: 0, message: "pruning threshold must not be negative".to_string() });
+        }
+        if self.authority.is_some_and(|a| a.top == 0 || !(0.0..=1.0).contains(&a.smoothing)) {
+            return Err(ScenarioError { line: 0, message: "authority needs at least one node and a smoothing between 0 and 1".to_string() });
+        }
+        for &expert in &self.experts {
+            check(expert, "expert")?;
+        }
+        for &member in self.expert_groups.iter().flat_map(|group| &group.members) {
+            check(member, "expert group member")?;
+        }
+        if let Some(node) = self.local_trust {
+            check(node, "local trust node")?;
+        }
+        if self.node_info.len() > self.num_of_nodes {
+            check(NodeId(self.node_info.len() - 1), "described node")?;
+        }
+        for edge in &self.edges {
+            check(edge.source, "edge source")?;
+            check(edge.target, "edge target")?;
+        }
+        for correction in &self.corrections {
+            check(correction.source, "retracted edge source")?;
+            check(correction.target, "retracted edge target")?;
+            if !(correction.share > 0.0 && correction.share <= 1.0) {
+                return Err(ScenarioError { line: 0, message: format!("retracted share must be in (0, 1]: {}", correction.share) });
+            }
+        }
+        for &item in &self.items {
+            check(item, "item")?;
+        }
+        if let Some((id, edge)) = edge_ids(&self.edges).find(|(_, e)| !self.items.is_empty() && (self.is_item(e.source) || !self.is_item(e.target))) {
+            return Err(ScenarioError { line: 0, message: format!("edge {} from {} to {} does not go from a rater to an item of the bipartite graph", id, edge.source, edge.target) });
+        }
+        if let Some(simulation) = &self.simulation {
+            for &attacker in &simulation.attackers {
+                check(attacker, "attacker")?;
+            }
+            for &(node, _) in &simulation.node_activity {
+                check(node, "simulated node")?;
+            }
+            for &(node, _) in &simulation.agents {
+                check(node, "agent")?;
+            }
+        }
+        for event in &self.rank_events {
+            check(event.node, "rewarded or penalized node")?;
+        }
+        for blacklisting in &self.blacklist {
+            check(blacklisting.node, "blacklisted node")?;
+        }
+        for assertion in &self.assertions {
+            for node in assertion.nodes() {
+                check(node, "asserted node")?;
+            }
+        }
+        Ok(())
+    }
+
+    // Leaves edges weighing less than `epsilon` out of every frame, including the DOT files,
+    // which then skip zero-weight edges instead of writing them invisible
+    pub fn prune_below(&mut self, epsilon: f64) {
+        self.graph_options.prune_below = epsilon;
+        self.style.invisible_edges = epsilon <= 0.0;
+    }
+
+    // The scenario's edges as an event log, for queries at any time and later changes
+    pub fn temporal_graph(&self) -> TemporalGraph {
+        TemporalGraph::from_edges(self.num_of_nodes, &self.edges)
+    }
+
+    // Graph of the frame at the given time, with the reward and penalty events in effect
+    pub fn frame_graph(&self, time: Time, edge_weights: &[f64]) -> Result<CompiledGraph, GraphError> {
+        let mut graph = compile_graph(&self.layered_edges(edge_weights), edge_weights, self.num_of_nodes, &self.graph_options)?;
+        graph.rank_adjustments = self.rank_adjustments(time);
+        graph.active_nodes = self.active_nodes(time);
+        graph.conductances = self.conductances();
+        self.apply_blacklist(&mut graph, time);
+        Ok(graph)
+    }
+
+    // Nodes blacklisted by the given time: those that absorb rank and those that are removed
+    pub fn blacklisted_at(&self, time: Time) -> (Vec<NodeId>, Vec<NodeId>) {
+        let (removed, absorbing): (Vec<&Blacklisting>, Vec<&Blacklisting>) = self.blacklist.iter().filter(|b| b.from <= time).partition(|b| b.removed);
+        (absorbing.iter().map(|b| b.node).collect(), removed.iter().map(|b| b.node).collect())
+    }
+
+    // Absorbing nodes lose their out-edges and keep their rank (mass) through a self-loop instead,
+    // so that the total stays 1; removed nodes lose all their edges. Only the self-loop has no
+    // scenario edge behind it.
+    pub fn apply_blacklist(&self, graph: &mut CompiledGraph, time: Time) {
+        let (absorbing, removed) = self.blacklisted_at(time);
+        if absorbing.is_empty() && removed.is_empty() {
+            return;
+        }
+        graph.edges.retain(|e| !absorbing.contains(&e.source) && !removed.contains(&e.source) && !removed.contains(&e.target));
+        for &node in &absorbing {
+            graph.edges.push(CompiledEdge { source: node, target: node, weight: 1.0, capacity: 1.0, sources: Vec::new() });
+        }
+    }
+
+    // Net rank (mass) added to each node per iteration at the given time; empty without events
+    pub fn rank_adjustments(&self, time: Time) -> Vec<f64> {
+        if self.rank_events.is_empty() {
+            return Vec::new();
+        }
+        let mut adjustments = vec![0.0; self.num_of_nodes];
+        for event in self.rank_events.iter().filter(|e| e.time <= time) {
+            adjustments[event.node.index()] += exponential_decay(time, event.time, event.amount, self.decay_constant);
+        }
+        adjustments
+    }
+
+    // Edge weights of the frame at the given time, indexed by EdgeId
+    pub fn edge_weights(&self, time: Time) -> Vec<f64> {
+        let weights = match (&self.scripts.decay, &self.window) {
+            (Some(decay), _) => edge_ids(&self.edges).map(|(id, e)| {
+                if !self.edge_exists(e, time) {
+                    return 0.0;
+                }
+                let age = time.elapsed_since(e.time_of_creation);
+                scripted_weight(decay.eval(&|name| match name {
+                    "age" => age,
+                    "k" => self.edge_decay_constant(id),
+                    _ => e.weight, // w0
+                }))
+            }).collect(),
+            (None, _) if !self.edge_categories.is_empty() || !self.edge_layers.is_empty() => {
+                let table = self.decay_table();
+                edge_ids(&self.edges).map(|(id, e)| {
+                    if !self.edge_exists(e, time) {
+                        return 0.0;
+                    }
+                    let (age, decay_constant) = (time.elapsed_since(e.time_of_creation), self.edge_decay_constant(id));
+                    let factor = table.filter(|t| t.fits(&self.decay_kernel, decay_constant)).and_then(|t| t.factor(age));
+                    e.weight * factor.unwrap_or_else(|| self.decay_kernel.exp(-age * decay_constant))
+                }).collect()
+            }
+            (None, Some(window)) => windowed_weights(&self.edges, time, window, self.decay_constant),
+            (None, None) => self.decay_kernel.decayed_weights_with(&self.edges, time, self.decay_constant, self.decay_table()),
+        };
+        let weights = if self.corrections.is_empty() { weights } else { self.corrected_weights(time, weights) };
+        let weights = if self.time_respecting { self.time_respecting_weights(time, weights) } else { weights };
+        let mut weights = match &self.scripts.weight {
+            Some(transform) => self.transformed_weights(transform, time, weights),
+            None => weights,
+        };
+        // Edges that carry nothing from or to blacklisted nodes, or nodes not in the graph
+        let (absorbing, removed) = self.blacklisted_at(time);
+        let active = self.active_nodes(time);
+        let inactive = |node: NodeId| active.get(node.index()) == Some(&false);
+        for (w, e) in weights.iter_mut().zip(&self.edges) {
+            if absorbing.contains(&e.source) || removed.contains(&e.source) || removed.contains(&e.target) || inactive(e.source) || inactive(e.target) {
+                *w = 0.0;
+            }
+        }
+        if self.edge_layers.is_empty() {
+            return weights;
+        }
+        let factors = self.layer_factors(&weights);
+        weights.into_iter().zip(factors).map(|(w, factor)| w * factor).collect()
+    }
+
+    // Decay factors of the scenario's decay constant for every whole age up to its last frame, when
+    // all edges are created at whole times. Built once and reused by every later frame; edges of
+    // categories or layers with their own decay constant are decayed directly.
+    fn decay_table(&self) -> Option<&DecayTable> {
+        let table = self.decay_table.get_or_build(|| {
+            let end = self.frame_times().last().copied().unwrap_or(Time(0.0));
+            DecayTable::for_creation_times(&self.decay_kernel, self.decay_constant, self.edges.iter().map(|e| e.time_of_creation), end)
+        });
+        table.filter(|t| t.fits(&self.decay_kernel, self.decay_constant))
+    }
+
+    fn layer_factors(&self, edge_weights: &[f64]) -> Vec<f64> {
+        let layer_weights: Vec<f64> = self.edge_layers.iter().map(|l| l.weight).collect();
+        layer_factors(&self.edges, edge_weights, &self.edge_layer, &layer_weights)
+    }
+
+    // The edges as the frame with the given weights compiles them: with layers, their weights at
+    // creation (capacities) are scaled like their current weights
+    pub fn layered_edges(&self, edge_weights: &[f64]) -> Cow<'_, [Edge]> {
+        if self.edge_layers.is_empty() {
+            return Cow::Borrowed(&self.edges);
+        }
+        let factors = self.layer_factors(edge_weights);
+        Cow::Owned(self.edges.iter().zip(factors).map(|(e, factor)| Edge { weight: e.weight * factor, ..e.clone() }).collect())
+    }
+
+    fn transformed_weights(&self, transform: &Script, time: Time, weights: Vec<f64>) -> Vec<f64> {
+        self.edges.iter().zip(weights).map(|(e, w)| {
+            if w == 0.0 {
+                return 0.0;
+            }
+            scripted_weight(transform.eval(&|name| match name {
+                "w" => w,
+                "age" => time.elapsed_since(e.time_of_creation),
+                "time" => time.value(),
+                "created" => e.time_of_creation.value(),
+                "source" => e.source.index() as f64,
+                _ => e.target.index() as f64, // target
+            }))
+        }).collect()
+    }
+
+    fn edge_exists(&self, edge: &Edge, time: Time) -> bool {
+        match &self.window {
+            Some(window) => window.contains(edge.time_of_creation, time),
+            None => edge.time_of_creation <= time,
+        }
+    }
+
+    // Decay constant of the edge's category, of its layer, or of the scenario
+    pub fn edge_decay_constant(&self, edge: EdgeId) -> f64 {
+        let category = self.edge_category.get(edge.index()).copied().flatten().and_then(|c| self.edge_categories[c].decay_constant);
+        let layer = || self.edge_layer.get(edge.index()).copied().flatten().and_then(|l| self.edge_layers[l].decay_constant);
+        category.or_else(layer).unwrap_or(self.decay_constant)
+    }
+
+    // Zeroes the weights of edges trust could not have flowed along by the given time: trust
+    // starts at the experts of that time, or at every node when there are none
+    fn time_respecting_weights(&self, time: Time, weights: Vec<f64>) -> Vec<f64> {
+        let experts = self.experts_at(time);
+        let seeds: Vec<NodeId> = if experts.is_empty() { (0..self.num_of_nodes).map(NodeId).collect() } else { experts };
+        let kept = if self.graph_options.undirected {
+            // Trust crosses an undirected edge in either direction
+            let num_of_edges = self.edges.len();
+            let mut both = self.edges.clone();
+            both.extend(self.edges.iter().map(|e| Edge { source: e.target, target: e.source, ..e.clone() }));
+            let kept = time_respecting_edges(&both, self.num_of_nodes, &seeds, time);
+            (0..num_of_edges).map(|i| kept[i] || kept[num_of_edges + i]).collect()
+        } else {
+            time_respecting_edges(&self.edges, self.num_of_nodes, &seeds, time)
+        };
+        weights.into_iter().zip(kept).map(|(w, kept)| if kept { w } else { 0.0 }).collect()
+    }
+
+    // Time at which the edge stops counting, if the scenario's window makes edges disappear
+    pub fn edge_end_time(&self, edge: &Edge) -> Option<Time> {
+        self.window.map(|window| Time(edge.time_of_creation.value() + window.size))
+    }
+
+    // Frame times dictated by the scenario's window, if it has one
+    // Times of the scenario's frames: an explicit list as given, otherwise a range. A window sets
+    // the spacing of a range itself, and its frames end at DEFAULT_END_TIME unless the range ends
+    // elsewhere; without a window, frames end at the last simulated tick, if any.
+    pub fn frame_times(&self) -> Vec<Time> {
+        let (start, end, step) = match &self.frame_schedule {
+            Some(FrameSchedule::Times(times)) => return times.clone(),
+            Some(FrameSchedule::Range { start, end, step }) => (start.unwrap_or(Time(0.0)), *end, step.unwrap_or(1.0)),
+            None => (Time(0.0), None, 1.0),
+        };
+        if let Some(times) = self.window_frame_times(start, end.unwrap_or(Time(DEFAULT_END_TIME))) {
+            return times;
+        }
+        let end = end.unwrap_or_else(|| Time(self.simulation.as_ref().map_or(DEFAULT_END_TIME, |simulation| simulation.ticks as f64)));
+        // Times are computed from the start rather than added up, and a rounding error does not drop the end
+        let last = ((end.value() - start.value()) / step + 1e-9).floor() as usize;
+        (0..=last).map(|k| Time(start.value() + k as f64 * step)).collect()
+    }
+
+    // File name of a frame of a run with `num_of_frames` frames, from the scenario's frame names
+    pub fn frame_file(&self, frame: FrameIdx, time: Time, num_of_frames: usize, extension: &str) -> String {
+        format!("{}.{}", self.frame_names.name(&self.name, frame, time, num_of_frames), extension)
+    }
+
+    pub fn window_frame_times(&self, start: Time, end: Time) -> Option<Vec<Time>> {
+        self.window.map(|window| window.frame_times(start, end))
+    }
+
+    pub fn decay_description(&self) -> String {
+        let decay = if self.scripts.decay.is_some() {
+            "Scripted".to_string()
+        } else if !self.edge_categories.is_empty() || !self.edge_layers.is_empty() {
+            // Edges outside every category and layer keep the scenario's half-life
+            let by = if self.edge_categories.is_empty() { "layer" } else { "category" };
+            match describe_half_life(self.decay_constant) {
+                Some(half_life) => format!("Exponential by {}, otherwise {}", by, half_life),
+                None => format!("Exponential by {}", by),
+            }
+        } else if let Some(half_life) = describe_half_life(self.decay_constant) {
+            format!("Exponential, {}", half_life)
+        } else {
+            "None".to_string()
+        };
+        let description = match &self.window {
+            Some(window) => format!("{}, {}", decay, window.description()),
+            None => decay,
+        };
+        if self.time_respecting { format!("{}, time-respecting", description) } else { description }
+    }
+
+    // Edges created after the given time, indexed by EdgeId, for frames that preview them with
+    // `style ghost-edges on`; empty otherwise
+    pub fn upcoming_edges(&self, time: Time) -> Vec<bool> {
+        if !self.style.ghost_edges {
+            return Vec::new();
+        }
+        self.edges.iter().map(|e| e.time_of_creation > time).collect()
+    }
+
+    // Captions of the latest annotated time not after `time`; none before the first annotation
+    pub fn annotations_at(&self, time: Time) -> Vec<&str> {
+        let current = &self.annotations[..self.annotations.partition_point(|a| a.time <= time)];
+        match current.last() {
+            Some(last) => current.iter().filter(|a| a.time == last.time).map(|a| a.text.as_str()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Experts at the given time: nodes without terms are experts throughout
+    pub fn experts_at(&self, time: Time) -> Vec<NodeId> {
+        self.experts
+            .iter()
+            .copied()
+            .filter(|&node| {
+                let mut terms = self.expert_terms.iter().filter(|term| term.node == node).peekable();
+                terms.peek().is_none() || terms.any(|term| term.contains(time))
+            })
+            .collect()
+    }
+
+    // Teleportation vector of the frame at the given time, following the experts of that moment;
+    // removed nodes get none of it
+    pub fn teleportation_targets(&self, time: Time) -> Vec<f64> {
+        let mut teleportation_targets = match self.local_trust {
+            Some(node) => self.local_teleportation_targets(node, time),
+            None => self.expert_teleportation_targets(time),
+        };
+        let (_, mut excluded) = self.blacklisted_at(time);
+        excluded.extend(self.active_nodes(time).iter().enumerate().filter(|(_, active)| !**active).map(|(node, _)| NodeId(node)));
+        excluded.sort();
+        excluded.dedup();
+        if excluded.is_empty() {
+            return teleportation_targets;
+        }
+        for node in &excluded {
+            teleportation_targets[node.index()] = 0.0;
+        }
+        let total: f64 = teleportation_targets.iter().sum();
+        if total > 0.0 {
+            teleportation_targets.iter_mut().for_each(|t| *t /= total);
+        } else {
+            // Every target is excluded: uniform over the nodes left
+            let num_of_left = self.num_of_nodes - excluded.len();
+            for (node, t) in teleportation_targets.iter_mut().enumerate() {
+                *t = if excluded.contains(&NodeId(node)) { 0.0 } else { 1.0 / num_of_left as f64 };
+            }
+        }
+        teleportation_targets
+    }
+
+    // Share of its damped rank (mass) each node passes on, from the nodes' conductance; empty when
+    // no node retains any
+    pub fn conductances(&self) -> Vec<f64> {
+        if self.node_info.iter().all(|info| info.conductance.is_none_or(|c| c == 1.0)) {
+            return Vec::new();
+        }
+        (0..self.num_of_nodes).map(|node| self.node_info.get(node).and_then(|info| info.conductance).unwrap_or(1.0)).collect()
+    }
+
+    // Decayed weights with the corrections made until the given time applied
+    fn corrected_weights(&self, time: Time, mut weights: Vec<f64>) -> Vec<f64> {
+        for correction in self.corrections.iter().filter(|c| c.time <= time) {
+            for (w, edge) in weights.iter_mut().zip(&self.edges) {
+                *w *= correction.factor(edge, time);
+            }
+        }
+        weights
+    }
+
+    // Whether each node takes part in the frame at the given time: from its join time (or its
+    // first edge with join-on-first-edge) until it leaves; empty when every node always does
+    pub fn active_nodes(&self, time: Time) -> Vec<bool> {
+        let has_lifecycle = self.join_on_first_edge || self.node_info.iter().any(|info| info.joins.is_some() || info.leaves.is_some());
+        if !has_lifecycle {
+            return Vec::new();
+        }
+        let mut joins: Vec<Option<Time>> = (0..self.num_of_nodes).map(|node| self.node_info.get(node).and_then(|info| info.joins)).collect();
+        if self.join_on_first_edge {
+            let mut first_edges: Vec<Option<Time>> = vec![None; self.num_of_nodes];
+            for e in &self.edges {
+                for node in [e.source, e.target] {
+                    let first = &mut first_edges[node.index()];
+                    if first.is_none_or(|t| e.time_of_creation < t) {
+                        *first = Some(e.time_of_creation);
+                    }
+                }
+            }
+            for (join, first_edge) in joins.iter_mut().zip(first_edges) {
+                // A node that never has an edge never joins
+                *join = join.or(first_edge).or(Some(Time(f64::INFINITY)));
+            }
+        }
+        let active: Vec<bool> = (0..self.num_of_nodes)
+            .map(|node| {
+                let leaves = self.node_info.get(node).and_then(|info| info.leaves);
+                joins[node].is_none_or(|joins| joins <= time) && leaves.is_none_or(|leaves| time < leaves)
+            })
+            .collect();
+        // A frame without any node in the graph ranks and shows all of them
+        if active.contains(&true) { active } else { Vec::new() }
+    }
+
+    // Nodes the given node endorses at the given time, with the current weights of its edges to them
+    // summed; its self-loops do not count
+    pub fn endorsees(&self, node: NodeId, time: Time) -> Vec<(NodeId, f64)> {
+        let mut endorsees: Vec<(NodeId, f64)> = Vec::new();
+        for (edge, weight) in self.edges.iter().zip(self.edge_weights(time)) {
+            if edge.source != node || edge.target == node || weight <= 0.0 {
+                continue;
+            }
+            match endorsees.iter_mut().find(|(endorsee, _)| *endorsee == edge.target) {
+                Some((_, total)) => *total += weight,
+                None => endorsees.push((edge.target, weight)),
+            }
+        }
+        endorsees.sort_by_key(|&(endorsee, _)| endorsee);
+        endorsees
+    }
+
+    // Ego-centric teleportation: to the node's endorsees in proportion to its current trust in
+    // them, so ranks tell how much the node should trust everyone else. A node that endorses no one
+    // at the time teleports to itself.
+    pub fn local_teleportation_targets(&self, node: NodeId, time: Time) -> Vec<f64> {
+        let mut teleportation_targets = vec![0.0; self.num_of_nodes];
+        let endorsees = self.endorsees(node, time);
+        let total: f64 = endorsees.iter().map(|(_, weight)| weight).sum();
+        if endorsees.is_empty() {
+            teleportation_targets[node.index()] = 1.0;
+        }
+        for (endorsee, weight) in endorsees {
+            teleportation_targets[endorsee.index()] = weight / total;
+        }
+        teleportation_targets
+    }
+
+    fn expert_teleportation_targets(&self, time: Time) -> Vec<f64> {
+        let num_of_nodes = self.num_of_nodes;
+        let experts = self.experts_at(time);
+        if let Some(teleport) = &self.scripts.teleport {
+            let weights: Vec<f64> = (0..num_of_nodes).map(|node| {
+                scripted_weight(teleport.eval(&|name| match name {
+                    "node" => node as f64,
+                    "expert" => if experts.contains(&NodeId(node)) { 1.0 } else { 0.0 },
+                    "n" => num_of_nodes as f64,
+                    "prior" => self.prior(NodeId(node)).unwrap_or(0.0),
+                    _ => experts.len() as f64, // experts
+                }))
+            }).collect();
+            let total: f64 = weights.iter().sum();
+            // A script that gives every node zero weight falls back to uniform teleportation
+            if total > 0.0 {
+                return weights.iter().map(|w| w / total).collect();
+            }
+            return vec![1.0 / num_of_nodes as f64; num_of_nodes];
+        }
+        // Rank (mass) not teleported to experts is spread uniformly, or in proportion to the nodes'
+        // priors when any node has one; all of it when there are no experts
+        let baseline = if experts.is_empty() { 1.0 } else { 1.0 - EXPERT_TELEPORT_FRACTION };
+        let priors: Vec<f64> = (0..num_of_nodes).map(|node| self.prior(NodeId(node)).unwrap_or(0.0)).collect();
+        let total_prior: f64 = priors.iter().sum();
+        let mut teleportation_targets = if total_prior > 0.0 {
+            priors.iter().map(|prior| baseline * prior / total_prior).collect()
+        } else {
+            vec![baseline / num_of_nodes as f64; num_of_nodes]
+        };
+        if experts.is_empty() {
+            return teleportation_targets;
+        }
+        // Experts share the expert fraction in proportion to their teleport weights (equally by default);
+        // what aging experts lose goes to the baseline
+        let expert_weight = |e: NodeId| self.node_info(e).and_then(|info| info.teleport_weight).unwrap_or(1.0);
+        let total_weight: f64 = experts.iter().map(|&e| expert_weight(e)).sum();
+        let mut aged = 0.0;
+        for &e in &experts {
+            let share = if total_weight > 0.0 { expert_weight(e) / total_weight } else { 1.0 / experts.len() as f64 };
+            let retained = self.expert_freshness(e, time);
+            teleportation_targets[e.index()] += EXPERT_TELEPORT_FRACTION * share * retained;
+            aged += EXPERT_TELEPORT_FRACTION * share * (1.0 - retained);
+        }
+        if aged > 0.0 {
+            for (target, prior) in teleportation_targets.iter_mut().zip(&priors) {
+                *target += if total_prior > 0.0 { aged * prior / total_prior } else { aged / num_of_nodes as f64 };
+            }
+        }
+        teleportation_targets
+    }
+
+    // Share of its teleport weight an expert keeps at the given time under its aging, 1 without
+    pub fn expert_freshness(&self, expert: NodeId, time: Time) -> f64 {
+        let Some(aging) = self.expert_aging.iter().find(|a| a.node == expert) else { return 1.0 };
+        // The term the expert is in, or the start of the history
+        let designated = self
+            .expert_terms
+            .iter()
+            .filter(|term| term.node == expert && term.contains(time))
+            .filter_map(|term| term.from.map(Time::value))
+            .reduce(f64::max)
+            .or_else(|| self.edges.iter().map(|e| e.time_of_creation.value()).reduce(f64::min));
+        let active = match aging.since {
+            AgingStart::Designation => None,
+            AgingStart::Activity => self.edges.iter().filter(|e| e.source == expert && e.time_of_creation <= time).map(|e| e.time_of_creation.value()).reduce(f64::max),
+        };
+        let start = match (designated, active) {
+            (Some(designated), Some(active)) => designated.max(active),
+            (Some(start), None) | (None, Some(start)) => start,
+            (None, None) => return 1.0,
+        };
+        (-aging.decay_constant * time.elapsed_since(Time(start))).exp()
+    }
+
+    // Merges rows of a node table into the scenario; experts flagged in the table are added
+    pub fn apply_node_rows(&mut self, rows: Vec<NodeRow>) {
+        for row in rows {
+            let index = row.node.index();
+            if self.node_info.len() <= index {
+                self.node_info.resize(index + 1, NodeInfo::default());
+            }
+            let info = &mut self.node_info[index];
+            info.name = row.info.name.or(info.name.take());
+            info.category = row.info.category.or(info.category.take());
+            info.org = row.info.org.or(info.org.take());
+            info.position = row.info.position.or(info.position);
+            info.teleport_weight = row.info.teleport_weight.or(info.teleport_weight);
+            info.prior = row.info.prior.or(info.prior);
+            info.conductance = row.info.conductance.or(info.conductance);
+            info.joins = row.info.joins.or(info.joins);
+            info.leaves = row.info.leaves.or(info.leaves);
+            if row.expert && !self.experts.contains(&row.node) {
+                self.experts.push(row.node);
+            }
+        }
+    }
+
+    // Clusters of the category or org metadata the style groups nodes by; community clusters
+    // depend on the frame and are left to the caller
+    pub fn clusters(&self) -> Vec<Cluster> {
+        match self.style.cluster {
+            Some(by) if by != ClusterBy::Community => metadata_clusters(&self.node_info, by),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn edge_source(&self, edge: EdgeId) -> Option<&EdgeSource> {
+        self.edge_sources.get(edge.index())
+    }
+
+    // Whether the node is on the rated side of a bipartite graph
+    pub fn is_item(&self, node: NodeId) -> bool {
+        self.items.binary_search(&node).is_ok()
+    }
+
+    pub fn node_info(&self, node: NodeId) -> Option<&NodeInfo> {
+        self.node_info.get(node.index())
+    }
+
+    // Damping factor of frame `frame` (from 0) of a run of `num_of_frames` frames, when the
+    // scenario schedules one
+    pub fn damping_factor(&self, frame: usize, num_of_frames: usize) -> Option<f64> {
+        self.damping.as_ref().map(|damping| damping.damping_factor(frame, num_of_frames))
+    }
+
+    pub fn prior(&self, node: NodeId) -> Option<f64> {
+        self.node_info(node).and_then(|info| info.prior)
+    }
+
+    // Name of the node if it has one, its index otherwise
+    pub fn node_label(&self, node: NodeId) -> String {
+        match self.node_info(node).and_then(|info| info.name.as_ref()) {
+            Some(name) => name.clone(),
+            None => node.to_string(),
+        }
+    }
+
+    // Node by its label or index
+    pub fn find_node(&self, name: &str) -> Option<NodeId> {
+        (0..self.num_of_nodes).map(NodeId).find(|&node| self.node_label(node) == name || node.to_string() == name)
+    }
+
+    pub fn node_labels(&self) -> Vec<String> {
+        (0..self.num_of_nodes).map(|i| self.node_label(NodeId(i))).collect()
+    }
+
+    // Nodes evenly spaced on the unit circle, except those with a fixed position. A bipartite
+    // graph has its raters in a column on the left and its items in one on the right.
+    pub fn layout(&self) -> Vec<(f64, f64)> {
+        let mut rows = vec![0; self.num_of_nodes];
+        let mut column_lengths = [0, 0];
+        for (node, row) in rows.iter_mut().enumerate() {
+            let column = usize::from(self.is_item(NodeId(node)));
+            *row = column_lengths[column];
+            column_lengths[column] += 1;
+        }
+        let column_position = |node: NodeId| {
+            let length = column_lengths[usize::from(self.is_item(node))];
+            let y = if length > 1 { 1.0 - 2.0 * rows[node.index()] as f64 / (length - 1) as f64 } else { 0.0 };
+            (if self.is_item(node) { 1.0 } else { -1.0 }, y)
+        };
+        (0..self.num_of_nodes)
+            .map(|i| {
+                match self.node_info(NodeId(i)).and_then(|info| info.position) {
+                    Some(position) => position,
+                    None if !self.items.is_empty() => column_position(NodeId(i)),
+                    None => {
+                        let angle = 2.0 * PI * (i as f64) / (self.num_of_nodes as f64);
+                        (angle.cos(), angle.sin())
+                    }
+                }
+            })
+            .collect()
+    }
+
+    // Which nodes stay at their layout position, for write_dot: with `style layout auto` only those
+    // with a fixed position; empty when all do
+    pub fn pinned_nodes(&self) -> Vec<bool> {
+        if !self.style.auto_layout {
+            return Vec::new();
+        }
+        (0..self.num_of_nodes).map(|i| self.node_info(NodeId(i)).is_some_and(|info| info.position.is_some())).collect()
+    }
+
+    pub fn trusted_threshold(&self) -> f64 {
+        self.trusted_threshold.unwrap_or(1.0 / self.num_of_nodes as f64)
+    }
+}
+
+// Scripts may produce anything; negative and undefined weights would break mass conservation
+fn scripted_weight(value: f64) -> f64 {
+    if value.is_finite() && value > 0.0 { value } else { 0.0 }
+}
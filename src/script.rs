@@ -0,0 +1,52 @@
+#[cfg(feature = "scripting")]
+pub use crate::scripting::RhaiScript as Script;
+
+// Variables available to each hook
+pub const DECAY_VARIABLES: [&str; 3] = ["age", "k", "w0"];
+pub const WEIGHT_VARIABLES: [&str; 6] = ["w", "age", "time", "created", "source", "target"];
+pub const TELEPORT_VARIABLES: [&str; 5] = ["node", "expert", "n", "experts", "prior"];
+
+// Without the scripting feature no script compiles, so there is never one to run
+#[cfg(not(feature = "scripting"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Script {}
+
+#[cfg(not(feature = "scripting"))]
+impl Script {
+    pub fn eval(&self, _variables: &dyn Fn(&str) -> f64) -> f64 {
+        match *self {}
+    }
+}
+
+// Modeling hooks a scenario can script instead of using the built-in behavior
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScenarioScripts {
+    pub decay: Option<Script>,    // weight of an existing edge by age, replacing exponential decay
+    pub weight: Option<Script>,   // transform applied to every existing edge's decayed weight
+    pub teleport: Option<Script>, // unnormalized teleportation weight of every node
+}
+
+impl ScenarioScripts {
+    // Compiles a `script <hook> <code>` line; `script <hook> rhai <code>` is accepted as well
+    pub fn set(&mut self, hook: &str, code: &str) -> Result<(), String> {
+        let (slot, variables): (_, &'static [&'static str]) = match hook {
+            "decay" => (&mut self.decay, &DECAY_VARIABLES),
+            "weight" => (&mut self.weight, &WEIGHT_VARIABLES),
+            "teleport" => (&mut self.teleport, &TELEPORT_VARIABLES),
+            _ => return Err(format!("unknown script hook: {} (expected decay, weight or teleport)", hook)),
+        };
+        let code = code.strip_prefix("rhai").filter(|rest| rest.starts_with(char::is_whitespace)).unwrap_or(code);
+        *slot = Some(compile(code.trim(), variables)?);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "scripting")]
+fn compile(code: &str, variables: &'static [&'static str]) -> Result<Script, String> {
+    Script::compile(code, variables)
+}
+
+#[cfg(not(feature = "scripting"))]
+fn compile(_code: &str, _variables: &'static [&'static str]) -> Result<Script, String> {
+    Err("script hooks need the scripting feature: cargo build --features scripting".to_string())
+}
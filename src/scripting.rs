@@ -0,0 +1,81 @@
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use log::info;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, Scope, AST};
+
+// Rhai scripts for the modeling hooks of scenario files, e.g.
+//   script decay let half_life = 2.0; w0 * 0.5 ** (age / half_life)
+// A script sees the hook's variables as floats and its value is the hook's result; integers and
+// booleans (1 when true) count as numbers. Scripts cannot import modules, their print and debug
+// output goes to the log, and a script that runs for more than MAX_OPERATIONS operations or fails
+// gives an undefined result.
+const MAX_OPERATIONS: u64 = 100_000;
+
+// One engine for all scripts; compiled scripts do not depend on the engine that compiled them
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut engine = Engine::new();
+        engine
+            .set_strict_variables(true)
+            .set_max_operations(MAX_OPERATIONS)
+            .set_module_resolver(DummyModuleResolver::new())
+            .on_print(|text| info!("script: {}", text))
+            .on_debug(|text, _, _| info!("script: {}", text));
+        engine
+    })
+}
+
+#[derive(Clone)]
+pub struct RhaiScript {
+    source: String,
+    ast: Arc<AST>,
+    variables: &'static [&'static str],
+}
+
+impl RhaiScript {
+    // Compiles a script that may only refer to the given variables
+    pub fn compile(source: &str, variables: &'static [&'static str]) -> Result<RhaiScript, String> {
+        let scope = scope(variables, &|_| 0.0);
+        let ast = engine().compile_with_scope(&scope, source).map_err(|e| format!("invalid Rhai script: {}", e))?;
+        Ok(RhaiScript { source: source.to_string(), ast: Arc::new(ast), variables })
+    }
+
+    // Runs the script with variable values looked up by name; NaN if it fails or returns no number
+    pub fn eval(&self, variables: &dyn Fn(&str) -> f64) -> f64 {
+        let mut scope = scope(self.variables, variables);
+        let Ok(value) = engine().eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast) else { return f64::NAN };
+        if let Ok(value) = value.as_float() {
+            value
+        } else if let Ok(value) = value.as_int() {
+            value as f64
+        } else if let Ok(value) = value.as_bool() {
+            if value { 1.0 } else { 0.0 }
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+fn scope(names: &[&str], values: &dyn Fn(&str) -> f64) -> Scope<'static> {
+    let mut scope = Scope::new();
+    for &name in names {
+        scope.push(name, values(name));
+    }
+    scope
+}
+
+// Scripts are told apart by their source, as scenarios compare their settings
+impl PartialEq for RhaiScript {
+    fn eq(&self, other: &RhaiScript) -> bool {
+        self.source == other.source && self.variables == other.variables
+    }
+}
+
+impl fmt::Debug for RhaiScript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RhaiScript({:?})", self.source)
+    }
+}
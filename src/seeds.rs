@@ -0,0 +1,71 @@
+use std::io::{self, Write};
+
+use crate::rank::RankingAlgorithm;
+use crate::scenario::Scenario;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// A named set of experts to compare with other sets, from `expert-group` lines in scenario files:
+//
+//   expert-group founders 0,1
+//   expert-group auditors 4,7
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpertGroup {
+    pub name: String,
+    pub members: Vec<NodeId>,
+}
+
+// Ranks of every expert group's personalization, frame by frame
+#[derive(Debug, Clone, Default)]
+pub struct GroupRanks {
+    pub groups: Vec<String>,
+    pub times: Vec<Time>,
+    pub ranks: Vec<Vec<Vec<f64>>>, // per frame, per group, per node
+}
+
+impl GroupRanks {
+    // How a node's rank in a frame splits over the groups: each group's rank of the node over the
+    // sum of the groups' ranks of it; equal shares for a node no group gives rank
+    pub fn shares(&self, frame: FrameIdx, node: NodeId) -> Vec<f64> {
+        let ranks: Vec<f64> = self.ranks[frame.index()].iter().map(|group| group[node.index()].max(0.0)).collect();
+        let total: f64 = ranks.iter().sum();
+        if total > 0.0 {
+            ranks.iter().map(|rank| rank / total).collect()
+        } else {
+            vec![1.0 / ranks.len() as f64; ranks.len()]
+        }
+    }
+}
+
+// Every frame's graph is compiled once and ranked once per group, with the group's members as the
+// experts in place of the scenario's. Members with expert terms are experts during them only, and
+// expert weights, priors and aging apply as they do to the scenario's own experts.
+pub fn rank_expert_groups(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, frame_times: &[Time]) -> GroupRanks {
+    let personalized: Vec<Scenario> = scenario
+        .expert_groups
+        .iter()
+        .map(|group| Scenario { experts: group.members.clone(), ..scenario.clone() })
+        .collect();
+    let mut ranks = GroupRanks { groups: scenario.expert_groups.iter().map(|group| group.name.clone()).collect(), ..GroupRanks::default() };
+    for &time in frame_times {
+        let edge_weights = scenario.edge_weights(time);
+        let graph = scenario.frame_graph(time, &edge_weights).unwrap();
+        ranks.ranks.push(personalized.iter().map(|group| algorithm.rank(&graph, &group.teleportation_targets(time))).collect());
+        ranks.times.push(time);
+    }
+    ranks
+}
+
+// frame,time,node,group,rank,share rows; the share is the group's part of the node's rank as
+// GroupRanks::shares splits it
+pub fn write_group_ranks_csv(out: &mut dyn Write, scenario: &Scenario, ranks: &GroupRanks) -> io::Result<()> {
+    writeln!(out, "frame,time,node,group,rank,share")?;
+    for (frame, time) in ranks.times.iter().enumerate() {
+        for (node, label) in scenario.node_labels().iter().enumerate() {
+            let shares = ranks.shares(FrameIdx(frame), NodeId(node));
+            for (g, group) in ranks.groups.iter().enumerate() {
+                writeln!(out, "{},{},{},{},{:.6},{:.6}", FrameIdx(frame).number(), time, label, group, ranks.ranks[frame][g][node], shares[g])?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use std::io::{self, Write};
+
+use crate::compare::{kendall_tau, spearman};
+use crate::random::Rng;
+use crate::rank::RankingAlgorithm;
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+
+// Change made to the expert (seed) set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perturbation {
+    LeaveOut(NodeId),                            // the expert is no expert at all
+    Substitute { removed: NodeId, added: NodeId }, // a non-expert takes over the expert's role and terms
+}
+
+// How far the final ranks move away from those of the unperturbed expert set
+#[derive(Debug, Clone)]
+pub struct SensitivityResult {
+    pub perturbation: Perturbation,
+    pub l1_change: f64,  // total absolute rank change
+    pub max_change: f64, // largest rank change of any node
+    pub spearman: f64,
+    pub kendall_tau: f64,
+}
+
+// Ranks the scenario at `time` once with its own experts, then once per perturbation: every expert
+// left out in turn, and `substitutions` random swaps of an expert for a non-expert drawn with `seed`
+pub fn seed_sensitivity(scenario: &Scenario, algorithm: &dyn RankingAlgorithm, time: Time, substitutions: usize, seed: u64) -> Vec<SensitivityResult> {
+    let rank = |scenario: &Scenario| {
+        let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+        algorithm.rank(&graph, &scenario.teleportation_targets(time))
+    };
+    let baseline = rank(scenario);
+
+    let mut perturbations: Vec<Perturbation> = scenario.experts.iter().map(|&expert| Perturbation::LeaveOut(expert)).collect();
+    let non_experts: Vec<NodeId> = (0..scenario.num_of_nodes).map(NodeId).filter(|node| !scenario.experts.contains(node)).collect();
+    if !scenario.experts.is_empty() && !non_experts.is_empty() {
+        let mut rng = Rng::new(seed);
+        for _ in 0..substitutions {
+            let removed = scenario.experts[rng.below(scenario.experts.len())];
+            let added = non_experts[rng.below(non_experts.len())];
+            perturbations.push(Perturbation::Substitute { removed, added });
+        }
+    }
+
+    perturbations
+        .into_iter()
+        .map(|perturbation| {
+            let ranks = rank(&perturbed(scenario, perturbation));
+            let changes: Vec<f64> = ranks.iter().zip(&baseline).map(|(a, b)| (a - b).abs()).collect();
+            SensitivityResult {
+                perturbation,
+                l1_change: changes.iter().sum(),
+                max_change: changes.iter().copied().fold(0.0, f64::max),
+                spearman: spearman(&baseline, &ranks),
+                kendall_tau: kendall_tau(&baseline, &ranks),
+            }
+        })
+        .collect()
+}
+
+fn perturbed(scenario: &Scenario, perturbation: Perturbation) -> Scenario {
+    let mut scenario = scenario.clone();
+    match perturbation {
+        Perturbation::LeaveOut(expert) => {
+            scenario.experts.retain(|&e| e != expert);
+            scenario.expert_terms.retain(|term| term.node != expert);
+            scenario.expert_aging.retain(|aging| aging.node != expert);
+        }
+        Perturbation::Substitute { removed, added } => {
+            for e in scenario.experts.iter_mut().filter(|e| **e == removed) {
+                *e = added;
+            }
+            for term in scenario.expert_terms.iter_mut().filter(|term| term.node == removed) {
+                term.node = added;
+            }
+            for aging in scenario.expert_aging.iter_mut().filter(|aging| aging.node == removed) {
+                aging.node = added;
+            }
+        }
+    }
+    scenario
+}
+
+// perturbation,removed,added,l1_change,max_change,spearman,kendall_tau rows
+pub fn write_sensitivity_csv(out: &mut dyn Write, results: &[SensitivityResult], labels: &[String]) -> io::Result<()> {
+    writeln!(out, "perturbation,removed,added,l1_change,max_change,spearman,kendall_tau")?;
+    for result in results {
+        let (kind, removed, added) = match result.perturbation {
+            Perturbation::LeaveOut(expert) => ("leave-out", &labels[expert.index()], ""),
+            Perturbation::Substitute { removed, added } => ("substitute", &labels[removed.index()], labels[added.index()].as_str()),
+        };
+        writeln!(out, "{},{},{},{:.6},{:.6},{:.6},{:.6}", kind, removed, added, result.l1_change, result.max_change, result.spearman, result.kendall_tau)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,617 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::decay::decay_constant_for_half_life;
+use crate::export::{write_frame_dot, ExportedRun};
+use crate::graph::event_times;
+use crate::input::{parse_edge_events, EventFormat, EventParser};
+use crate::json::Json;
+use crate::rank::RankConfig;
+use crate::registry::{AlgorithmInfo, AlgorithmRegistry};
+use crate::monitoring::ServiceMetrics;
+use crate::ranker::{Personalization, Ranker};
+use crate::run::{continue_scenario, RunOutput};
+use crate::scenario::Scenario;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// HTTP API of `trust-flow serve`, so that other services can rank graphs without shelling out:
+//
+//   POST /graphs?experts=0,3&format=csv        edge events (JSONL or CSV) or a scenario file (format=scenario)
+//   POST /graphs/{graph}/edges                 appends edge events to a graph
+//   POST /graphs/{graph}/runs?algorithm=pagerank&damping=0.5&iterations=10&max_hops=3&capacity=0.25&decay=0.1&half_life=7&frame_times=1,2,3
+//   GET  /graphs/{graph}/ranks?seeds=alice:2,bob&time=12&algorithm=pagerank   ranks from the seeds' perspective
+//   GET  /graphs/{graph}/trust?from=alice&to=bob&time=12&algorithm=pagerank    how much alice should trust bob
+//   GET  /runs/{run}                           every frame of a run
+//   GET  /runs/{run}/frames/{frame}            one frame as JSON; frames are numbered from 1
+//   GET  /runs/{run}/frames/{frame}.svg        one frame rendered by Graphviz
+//   GET  /algorithms
+//   GET  /metrics                              Prometheus metrics: events, frames, iterations, frame latency
+//
+// Graphs and runs are kept in memory and numbered from 0 in the order they were created.
+// A pool of workers serves the requests, each one at a time; graphs, runs and the cache are locked
+// only to look things up or store them, never while ranking or rendering. A client that stalls is
+// cut off after the request timeout, so that it holds up its worker only that long.
+
+const MAX_BODY_LENGTH: usize = 64 << 20;
+const MAX_HEADER_LENGTH: u64 = 64 << 10; // request line and headers together
+// Limits of a run, which occupies a worker while it is computed
+pub const MAX_ITERATIONS: usize = 10_000;
+pub const MAX_FRAMES: usize = 10_000;
+// Node indices of a graph are below this, so that one stray index cannot make a run allocate ranks
+// for billions of nodes
+pub const MAX_NODES: usize = 1 << 24;
+// How long reading a request or writing a response may wait for the client
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// Personalized rank vectors kept for repeated queries; all are dropped once this many are kept
+const MAX_CACHED_PERSONALIZED: usize = 1024;
+
+// Workers when the number of CPUs is unknown, and at least this many so that slow clients or runs
+// leave some for other requests
+const MIN_WORKERS: usize = 4;
+
+// What a personalized query's ranks depend on: the graph as far as it has grown, the algorithm,
+// the seeds and the bits of the time
+type PersonalizedQuery = (usize, usize, &'static str, Personalization, u64);
+
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, value: Json) -> Self {
+        Response { status, content_type: "application/json", body: value.to_string().into_bytes() }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Response::json(status, Json::object(vec![("error", message.into())]))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            500 => "Internal Server Error",
+            502 => "Bad Gateway",
+            _ => "Error",
+        }
+    }
+}
+
+struct Run {
+    scenario: Scenario, // the graph as it was ranked; later uploads do not change a run
+    algorithm: &'static str,
+    output: RunOutput,
+}
+
+// Graphs and runs of a server, with the rank settings of the command line as defaults. Graphs and
+// runs are shared with the requests that rank or render them, and a graph that grows meanwhile is
+// copied rather than changed under them.
+pub struct Server {
+    rank_config: RankConfig,
+    graphs: RwLock<Vec<Arc<Scenario>>>,
+    runs: RwLock<Vec<Arc<Run>>>,
+    personalized: Mutex<HashMap<PersonalizedQuery, Arc<Vec<f64>>>>,
+    metrics: Mutex<ServiceMetrics>,
+    request_timeout: Duration,
+    workers: usize,
+}
+
+impl Server {
+    pub fn new(rank_config: RankConfig) -> Self {
+        let workers = thread::available_parallelism().map_or(MIN_WORKERS, |n| n.get().max(MIN_WORKERS));
+        Server {
+            rank_config,
+            graphs: RwLock::new(Vec::new()),
+            runs: RwLock::new(Vec::new()),
+            personalized: Mutex::new(HashMap::new()),
+            metrics: Mutex::new(ServiceMetrics::new()),
+            request_timeout: REQUEST_TIMEOUT,
+            workers,
+        }
+    }
+
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    // Requests served at the same time; the number of CPUs, but at least 4, by default
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    // Accepts connections on every worker until the listener fails; a failing connection only loses
+    // its own request
+    pub fn serve(&self, listener: &TcpListener) -> io::Result<()> {
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.workers).map(|_| scope.spawn(|| self.accept(listener))).collect();
+            workers.into_iter().try_for_each(|worker| worker.join().unwrap())
+        })
+    }
+
+    fn accept(&self, listener: &TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let mut stream = stream?;
+            if set_timeouts(&stream, self.request_timeout).is_err() {
+                continue;
+            }
+            let response = match read_request(&mut stream) {
+                Ok(request) => self.handle(&request),
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => Response::error(400, &e.to_string()),
+                Err(_) => continue,
+            };
+            let _ = write_response(&mut stream, &response);
+        }
+        Ok(())
+    }
+
+    pub fn handle(&self, request: &Request) -> Response {
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        let result = match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["algorithms"]) => Ok(algorithms()),
+            ("GET", ["metrics"]) => Ok(self.metrics.lock().unwrap().response()),
+            ("POST", ["graphs"]) => self.create_graph(request),
+            ("POST", ["graphs", graph, "edges"]) => self.add_edges(graph, request),
+            ("POST", ["graphs", graph, "runs"]) => self.create_run(graph, request),
+            ("GET", ["graphs", graph, "ranks"]) => self.personalized_ranks(graph, request),
+            ("GET", ["graphs", graph, "trust"]) => self.local_trust(graph, request),
+            ("GET", ["runs", run]) => self.run(run).map(|run| Response::json(200, run_json(&run))),
+            ("GET", ["runs", run, "frames", frame]) => self.frame(run, frame),
+            (_, ["algorithms"] | ["metrics"] | ["graphs"] | ["graphs", _, "edges" | "runs" | "ranks" | "trust"] | ["runs", _] | ["runs", _, "frames", _]) => {
+                Err(Response::error(405, &format!("{} is not supported on {}", request.method, request.path)))
+            }
+            _ => Err(Response::error(404, &format!("no such endpoint: {}", request.path))),
+        };
+        result.unwrap_or_else(|response| response)
+    }
+
+    fn create_graph(&self, request: &Request) -> Result<Response, Response> {
+        let text = body_text(request)?;
+        let is_scenario = request.query.get("format").map(String::as_str) == Some("scenario");
+        let mut scenario = if is_scenario {
+            let scenario = Scenario::parse(text).map_err(|e| Response::error(400, &e.to_string()))?;
+            scenario.validate().map_err(|e| Response::error(400, &e.to_string()))?;
+            if scenario.num_of_nodes > MAX_NODES {
+                return Err(Response::error(400, &format!("a graph has at most {} nodes", MAX_NODES)));
+            }
+            scenario
+        } else {
+            let experts = match request.query.get("experts") {
+                Some(list) => list
+                    .split(',')
+                    .map(|n| n.trim().parse().ok().filter(|&n| n < MAX_NODES).map(NodeId).ok_or_else(|| Response::error(400, &format!("invalid expert: {}", n))))
+                    .collect::<Result<Vec<NodeId>, Response>>()?,
+                None => vec![NodeId(0)],
+            };
+            Scenario::new("", 0, experts, Vec::new())
+        };
+        if !is_scenario {
+            append_events(&mut scenario, text, event_format(request)?)?;
+        }
+        self.metrics.lock().unwrap().events_ingested(scenario.edges.len());
+        let mut graphs = self.graphs.write().unwrap();
+        let id = graphs.len();
+        if scenario.name.is_empty() {
+            scenario.name = format!("graph-{}", id);
+        }
+        let summary = graph_json(id, &scenario);
+        graphs.push(Arc::new(scenario));
+        Ok(Response::json(201, summary))
+    }
+
+    fn add_edges(&self, graph: &str, request: &Request) -> Result<Response, Response> {
+        let text = body_text(request)?;
+        let format = event_format(request)?;
+        let mut graphs = self.graphs.write().unwrap();
+        let id = index(graph, graphs.len(), "graph")?;
+        let scenario = Arc::make_mut(&mut graphs[id]);
+        let num_of_edges = scenario.edges.len();
+        append_events(scenario, text, format)?;
+        self.metrics.lock().unwrap().events_ingested(scenario.edges.len() - num_of_edges);
+        Ok(Response::json(200, graph_json(id, scenario)))
+    }
+
+    fn graph(&self, graph: &str) -> Result<(usize, Arc<Scenario>), Response> {
+        let graphs = self.graphs.read().unwrap();
+        index(graph, graphs.len(), "graph").map(|id| (id, Arc::clone(&graphs[id])))
+    }
+
+    fn create_run(&self, graph: &str, request: &Request) -> Result<Response, Response> {
+        let (id, scenario) = self.graph(graph)?;
+        let mut scenario = Scenario::clone(&scenario);
+        let query = &request.query;
+        let number = |key: &str| -> Result<Option<f64>, Response> {
+            query.get(key).map(|v| finite(v).ok_or_else(|| Response::error(400, &format!("{} must be a finite number: {}", key, v)))).transpose()
+        };
+
+        let mut config = self.rank_config.clone();
+        if let Some(damping) = number("damping")? {
+            if !(0.0..=1.0).contains(&damping) {
+                return Err(Response::error(400, "damping must be between 0 and 1"));
+            }
+            config = config.damping_factor(damping);
+        }
+        if let Some(iterations) = query.get("iterations") {
+            let iterations = iterations.parse().ok().filter(|&i| i <= MAX_ITERATIONS);
+            config = config.iterations(iterations.ok_or_else(|| Response::error(400, &format!("iterations must be an integer from 0 to {}", MAX_ITERATIONS)))?);
+        }
+        if let Some(max_hops) = query.get("max_hops") {
+            config = config.max_hops(max_hops.parse().map_err(|_| Response::error(400, &format!("max_hops must be a non-negative integer: {}", max_hops)))?);
+        }
+        if let Some(capacity) = number("capacity")? {
+            if capacity <= 0.0 {
+                return Err(Response::error(400, "capacity must be positive"));
+            }
+            config = config.node_capacity(capacity);
+        }
+        if let Some(share) = number("max_source_share")? {
+            if !(share > 0.0 && share <= 1.0) {
+                return Err(Response::error(400, "max_source_share must be in (0, 1]"));
+            }
+            config = config.max_source_share(share);
+        }
+        if let Some(floor) = number("floor")? {
+            if !(0.0..1.0).contains(&floor) {
+                return Err(Response::error(400, "floor must be in [0, 1)"));
+            }
+            config = config.rank_floor(floor);
+        }
+        if let Some(decay) = number("decay")? {
+            if decay < 0.0 {
+                return Err(Response::error(400, "decay must be non-negative"));
+            }
+            scenario.decay_constant = decay;
+        }
+        if let Some(half_life) = number("half_life")? {
+            if half_life <= 0.0 {
+                return Err(Response::error(400, "half_life must be positive"));
+            }
+            scenario.decay_constant = decay_constant_for_half_life(half_life);
+        }
+        let info = AlgorithmRegistry::get(query.get("algorithm").map_or("pagerank", String::as_str)).map_err(|e| Response::error(400, &e))?;
+        let frame_times: Vec<Time> = match query.get("frame_times") {
+            Some(list) => list
+                .split(',')
+                .map(|t| finite(t.trim()).map(Time).ok_or_else(|| Response::error(400, &format!("invalid frame time: {}", t))))
+                .collect::<Result<_, _>>()?,
+            None => event_times(&scenario.edges),
+        };
+        if frame_times.is_empty() {
+            return Err(Response::error(400, "no frame times: pass frame_times or upload edges first"));
+        }
+        if frame_times.len() > MAX_FRAMES {
+            return Err(Response::error(400, &format!("{} frames, but a run has at most {}; pass fewer frame_times", frame_times.len(), MAX_FRAMES)));
+        }
+
+        let mut output = RunOutput::new();
+        let mut frame_started = Instant::now();
+        continue_scenario(&scenario, info.create(&config).as_ref(), &frame_times, &scenario.graph_options, &mut output, &mut |output| {
+            self.metrics.lock().unwrap().frame_computed(frame_started.elapsed(), output.convergence.last().copied().flatten());
+            frame_started = Instant::now();
+            Ok(())
+        })
+        .map_err(|e| Response::error(500, &format!("cannot rank the graph: {}", e)))?;
+        let run = Run { scenario, algorithm: info.id, output };
+        let mut runs = self.runs.write().unwrap();
+        let summary = Json::object(vec![
+            ("run", runs.len().into()),
+            ("graph", id.into()),
+            ("algorithm", run.algorithm.into()),
+            ("frames", run.output.history.num_of_frames().into()),
+        ]);
+        runs.push(Arc::new(run));
+        Ok(Response::json(201, summary))
+    }
+
+    // Ranks of the graph at one time with teleportation at the seeds (nodes by label or index, each
+    // with an optional weight) instead of at the experts; the last event time by default
+    fn personalized_ranks(&self, graph: &str, request: &Request) -> Result<Response, Response> {
+        let (id, scenario) = self.graph(graph)?;
+        let query = &request.query;
+        let seeds = query
+            .get("seeds")
+            .ok_or_else(|| Response::error(400, "seeds is required, e.g. seeds=alice:2,bob"))?
+            .split(',')
+            .map(|seed| {
+                let (node, weight) = seed.split_once(':').unwrap_or((seed, "1"));
+                let node = scenario.find_node(node.trim()).ok_or_else(|| Response::error(400, &format!("no such node: {}", node)))?;
+                let weight = weight.trim().parse().map_err(|_| Response::error(400, &format!("invalid seed weight: {}", weight)))?;
+                Ok((node, weight))
+            })
+            .collect::<Result<Vec<(NodeId, f64)>, Response>>()?;
+        let personalization = Personalization::new(&seeds).map_err(|e| Response::error(400, &e))?;
+        let time = query_time(&scenario, request)?;
+        let info = AlgorithmRegistry::get(query.get("algorithm").map_or("pagerank", String::as_str)).map_err(|e| Response::error(400, &e))?;
+        let (ranks, cached) = self.rank_personalized(id, &scenario, info, personalization.clone(), time);
+        let seeds = personalization.seeds().map(|(node, weight)| Json::object(vec![("node", scenario.node_label(node).into()), ("weight", weight.into())])).collect();
+        Ok(Response::json(
+            200,
+            Json::object(vec![
+                ("graph", id.into()),
+                ("algorithm", info.id.into()),
+                ("time", time.value().into()),
+                ("seeds", Json::Array(seeds)),
+                ("cached", cached.into()),
+                ("ranks", Json::numbers(&ranks)),
+            ]),
+        ))
+    }
+
+    // Ego-centric trust of one node in another: the ranks with teleportation at the truster's
+    // endorsees (`from`, by label or index) at one time, the last event time by default
+    fn local_trust(&self, graph: &str, request: &Request) -> Result<Response, Response> {
+        let (id, scenario) = self.graph(graph)?;
+        let query = &request.query;
+        let node = |parameter: &str| -> Result<NodeId, Response> {
+            let name = query.get(parameter).ok_or_else(|| Response::error(400, &format!("{} is required, e.g. from=alice&to=bob", parameter)))?;
+            scenario.find_node(name).ok_or_else(|| Response::error(400, &format!("no such node: {}", name)))
+        };
+        let (truster, trustee) = (node("from")?, node("to")?);
+        let time = query_time(&scenario, request)?;
+        let info = AlgorithmRegistry::get(query.get("algorithm").map_or("pagerank", String::as_str)).map_err(|e| Response::error(400, &e))?;
+        let personalization = Personalization::local(&scenario, truster, time);
+        let (ranks, cached) = self.rank_personalized(id, &scenario, info, personalization.clone(), time);
+        let endorsees = personalization.seeds().filter(|&(node, _)| node != truster).map(|(node, weight)| Json::object(vec![("node", scenario.node_label(node).into()), ("weight", weight.into())])).collect();
+        Ok(Response::json(
+            200,
+            Json::object(vec![
+                ("graph", id.into()),
+                ("algorithm", info.id.into()),
+                ("time", time.value().into()),
+                ("from", scenario.node_label(truster).into()),
+                ("to", scenario.node_label(trustee).into()),
+                ("endorsees", Json::Array(endorsees)),
+                ("cached", cached.into()),
+                ("trust", ranks[trustee.index()].into()),
+                ("rank", (1 + ranks.iter().filter(|&&r| r > ranks[trustee.index()]).count()).into()),
+            ]),
+        ))
+    }
+
+    // Ranks graph `id` with the personalization unless the ranks are cached; returns them and
+    // whether they were cached. Requests that miss the cache at the same time each rank the graph.
+    fn rank_personalized(&self, id: usize, scenario: &Scenario, info: &AlgorithmInfo, personalization: Personalization, time: Time) -> (Arc<Vec<f64>>, bool) {
+        let key = (id, scenario.edges.len(), info.id, personalization, time.value().to_bits());
+        if let Some(ranks) = self.personalized.lock().unwrap().get(&key) {
+            return (Arc::clone(ranks), true);
+        }
+        let algorithm = info.create(&self.rank_config);
+        let ranks = Arc::new(Ranker::new(scenario, algorithm.as_ref()).personalized_ranks(&key.3, time));
+        let mut personalized = self.personalized.lock().unwrap();
+        if personalized.len() >= MAX_CACHED_PERSONALIZED {
+            personalized.clear();
+        }
+        personalized.insert(key, Arc::clone(&ranks));
+        (ranks, false)
+    }
+
+    fn run(&self, run: &str) -> Result<Arc<Run>, Response> {
+        let runs = self.runs.read().unwrap();
+        index(run, runs.len(), "run").map(|id| Arc::clone(&runs[id]))
+    }
+
+    fn frame(&self, run: &str, frame: &str) -> Result<Response, Response> {
+        let run = self.run(run)?;
+        let (number, svg) = match frame.strip_suffix(".svg") {
+            Some(number) => (number, true),
+            None => (frame.strip_suffix(".json").unwrap_or(frame), false),
+        };
+        let num_of_frames = run.output.history.num_of_frames();
+        let frame = match number.parse::<usize>() {
+            Ok(n) if (1..=num_of_frames).contains(&n) => FrameIdx(n - 1),
+            _ => return Err(Response::error(404, &format!("no such frame: {} (the run has frames 1 to {})", number, num_of_frames))),
+        };
+        if !svg {
+            return Ok(Response::json(200, frame_json(&run, frame)));
+        }
+
+        let algorithm = AlgorithmRegistry::get(run.algorithm).map_or(run.algorithm, |info| info.name);
+        let mut dot = Vec::new();
+        write_frame_dot(&mut dot, &ExportedRun { scenario: &run.scenario, output: &run.output, algorithm }, frame).unwrap();
+        let svg = render_svg(&dot).map_err(|e| Response::error(502, &format!("cannot render SVG with Graphviz: {}", e)))?;
+        Ok(Response { status: 200, content_type: "image/svg+xml", body: svg })
+    }
+}
+
+fn algorithms() -> Response {
+    let algorithms = AlgorithmRegistry::all()
+        .into_iter()
+        .map(|info| Json::object(vec![("id", info.id.into()), ("name", info.name.into()), ("summary", info.summary.into())]))
+        .collect();
+    Response::json(200, Json::Array(algorithms))
+}
+
+fn graph_json(id: usize, scenario: &Scenario) -> Json {
+    Json::object(vec![
+        ("graph", id.into()),
+        ("name", scenario.name.as_str().into()),
+        ("nodes", scenario.num_of_nodes.into()),
+        ("edges", scenario.edges.len().into()),
+    ])
+}
+
+fn frame_json(run: &Run, frame: FrameIdx) -> Json {
+    Json::object(vec![
+        ("frame", frame.number().into()),
+        ("time", run.output.history.times()[frame.index()].value().into()),
+        ("ranks", Json::numbers(run.output.history.ranks_at(frame))),
+    ])
+}
+
+fn run_json(run: &Run) -> Json {
+    let frames = (0..run.output.history.num_of_frames()).map(|f| frame_json(run, FrameIdx(f))).collect();
+    Json::object(vec![
+        ("graph", run.scenario.name.as_str().into()),
+        ("algorithm", run.algorithm.into()),
+        ("decay", run.scenario.decay_description().into()),
+        ("frames", Json::Array(frames)),
+    ])
+}
+
+// Parses edge events and adds them to a graph, growing it to every node they mention
+fn append_events(scenario: &mut Scenario, text: &str, format: EventFormat) -> Result<(), Response> {
+    let (edges, sources) = parse_edge_events(text, EventParser::new(format)).map_err(|e| Response::error(400, &e))?;
+    if let Some(node) = edges.iter().flat_map(|e| [e.source, e.target]).find(|n| n.index() >= MAX_NODES) {
+        return Err(Response::error(400, &format!("node indices must be below {}: {}", MAX_NODES, node.index())));
+    }
+    let num_of_nodes = edges
+        .iter()
+        .flat_map(|e| [e.source, e.target])
+        .chain(scenario.experts.iter().copied())
+        .map(|n| n.index() + 1)
+        .max()
+        .unwrap_or(0);
+    scenario.num_of_nodes = scenario.num_of_nodes.max(num_of_nodes);
+    if scenario.edge_sources.len() == scenario.edges.len() {
+        scenario.edge_sources.extend(sources);
+    }
+    let edges: Vec<_> = edges.into_iter().map(|edge| scenario.oriented(edge)).collect();
+    scenario.edges.extend(edges);
+    Ok(())
+}
+
+// CSV when asked for by `format=csv` or a text/csv body, JSONL otherwise
+fn event_format(request: &Request) -> Result<EventFormat, Response> {
+    match request.query.get("format").map(String::as_str) {
+        Some("csv") => Ok(EventFormat::Csv),
+        Some("jsonl") => Ok(EventFormat::Jsonl),
+        Some(other) => Err(Response::error(400, &format!("unknown edge event format: {} (expected jsonl or csv)", other))),
+        None if request.content_type.as_deref().is_some_and(|t| t.starts_with("text/csv")) => Ok(EventFormat::Csv),
+        None => Ok(EventFormat::Jsonl),
+    }
+}
+
+fn body_text(request: &Request) -> Result<&str, Response> {
+    std::str::from_utf8(&request.body).map_err(|_| Response::error(400, "request body is not UTF-8"))
+}
+
+fn finite(text: &str) -> Option<f64> {
+    text.parse().ok().filter(|x: &f64| x.is_finite())
+}
+
+fn index(segment: &str, len: usize, what: &str) -> Result<usize, Response> {
+    segment.parse().ok().filter(|&i| i < len).ok_or_else(|| Response::error(404, &format!("no such {}: {}", what, segment)))
+}
+
+// The time a query asks about; the graph's last event time by default
+fn query_time(scenario: &Scenario, request: &Request) -> Result<Time, Response> {
+    match request.query.get("time") {
+        Some(t) => finite(t).map(Time).ok_or_else(|| Response::error(400, &format!("time must be a finite number: {}", t))),
+        None => event_times(&scenario.edges).last().copied().ok_or_else(|| Response::error(400, "no time: pass time or upload edges first")),
+    }
+}
+
+// Pipes DOT through Graphviz, which has to be installed like for the rendered GIFs
+pub fn render_svg(dot: &[u8]) -> io::Result<Vec<u8>> {
+    let mut child = Command::new("dot").arg("-Tsvg").stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    child.stdin.take().unwrap().write_all(dot)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(output.stdout)
+}
+
+// Keeps a silent or slow client from holding up the requests after it
+pub fn set_timeouts(stream: &TcpStream, timeout: Duration) -> io::Result<()> {
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// Reads one HTTP/1.x request with a Content-Length body (no chunked uploads)
+pub fn read_request(stream: &mut TcpStream) -> io::Result<Request> {
+    let mut reader = BufReader::new(stream.take(MAX_HEADER_LENGTH));
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request { method: method.to_string(), path: percent_decode(path), query: parse_query(query), ..Request::default() };
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(invalid("request headers too long or cut short"));
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else { continue };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.trim().parse().map_err(|_| invalid("invalid Content-Length"))?,
+            "content-type" => request.content_type = Some(value.trim().to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY_LENGTH {
+        return Err(invalid("request body too large"));
+    }
+    reader.get_mut().set_limit(content_length as u64);
+    request.body = vec![0; content_length];
+    reader.read_exact(&mut request.body)?;
+    Ok(request)
+}
+
+pub fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", response.status, response.reason(), response.content_type, response.body.len())?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+pub fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let hex = |i: usize| bytes.get(i).and_then(|&b| (b as char).to_digit(16));
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], hex(i + 1), hex(i + 2)) {
+            (b'%', Some(high), Some(low)) => {
+                decoded.push((high * 16 + low) as u8);
+                i += 2;
+            }
+            (b'+', _, _) => decoded.push(b' '),
+            (byte, _, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,102 @@
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::types::{FrameIdx, NodeId, Time};
+
+// SimRank over the decayed graph: two nodes are similar when they are trusted by similar
+// endorsers. Weighted version of Jeh and Widom's recursion,
+//   s(a, b) = importance * Σ w(i, a) w(j, b) s(i, j) / (W(a) W(b)),   s(a, a) = 1,
+// where w are the current edge weights and W(a) the total weight into a. Nodes nobody
+// endorses are similar to no other node. Memory and time are quadratic in the number of nodes.
+pub struct SimRank {
+    pub importance: f64, // how much similarity of endorsers carries over, below 1
+    pub num_of_iterations: usize,
+}
+
+impl Default for SimRank {
+    fn default() -> Self {
+        SimRank { importance: 0.8, num_of_iterations: 5 }
+    }
+}
+
+// Pairwise similarities of one frame, symmetric with ones on the diagonal
+#[derive(Debug, Clone)]
+pub struct Similarity {
+    num_of_nodes: usize,
+    values: Vec<f64>, // row-major
+}
+
+impl Similarity {
+    pub fn get(&self, a: NodeId, b: NodeId) -> f64 {
+        self.values[a.index() * self.num_of_nodes + b.index()]
+    }
+
+    // The `count` nodes most similar to `node`, most similar first, leaving out dissimilar ones
+    pub fn top_similar(&self, node: NodeId, count: usize) -> Vec<(NodeId, f64)> {
+        let mut similar: Vec<(NodeId, f64)> = (0..self.num_of_nodes)
+            .map(NodeId)
+            .filter(|&other| other != node)
+            .map(|other| (other, self.get(node, other)))
+            .filter(|&(_, similarity)| similarity > 0.0)
+            .collect();
+        similar.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        similar.truncate(count);
+        similar
+    }
+}
+
+impl SimRank {
+    pub fn compute(&self, graph: &CompiledGraph) -> Similarity {
+        let n = graph.num_of_nodes;
+        // Endorsers of every node with their share of its incoming weight
+        let mut in_weight = vec![0.0; n];
+        for edge in &graph.edges {
+            in_weight[edge.target.index()] += edge.weight;
+        }
+        let mut endorsers: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+        for edge in graph.edges.iter().filter(|e| e.weight > 0.0) {
+            let target = edge.target.index();
+            endorsers[target].push((edge.source.index(), edge.weight / in_weight[target]));
+        }
+
+        let mut values = identity(n);
+        for _ in 0..self.num_of_iterations {
+            let mut next = identity(n);
+            for a in 0..n {
+                for b in a + 1..n {
+                    let mut sum = 0.0;
+                    for &(i, wi) in &endorsers[a] {
+                        for &(j, wj) in &endorsers[b] {
+                            sum += wi * wj * values[i * n + j];
+                        }
+                    }
+                    next[a * n + b] = self.importance * sum;
+                    next[b * n + a] = self.importance * sum;
+                }
+            }
+            values = next;
+        }
+        Similarity { num_of_nodes: n, values }
+    }
+}
+
+fn identity(n: usize) -> Vec<f64> {
+    let mut values = vec![0.0; n * n];
+    for i in 0..n {
+        values[i * n + i] = 1.0;
+    }
+    values
+}
+
+// frame,time,node,similar_node,similarity rows: the `count` most similar nodes of every node in every frame
+pub fn write_similarity_csv(out: &mut dyn Write, frames: &[(Time, Similarity)], labels: &[String], count: usize) -> io::Result<()> {
+    writeln!(out, "frame,time,node,similar_node,similarity")?;
+    for (frame, (time, similarity)) in frames.iter().enumerate() {
+        for node in (0..similarity.num_of_nodes).map(NodeId) {
+            for (other, value) in similarity.top_similar(node, count) {
+                writeln!(out, "{},{},{},{},{:.6}", FrameIdx(frame).number(), time, labels[node.index()], labels[other.index()], value)?;
+            }
+        }
+    }
+    Ok(())
+}
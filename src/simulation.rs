@@ -0,0 +1,255 @@
+use crate::graph::Edge;
+use crate::random::Rng;
+use crate::rank::RankingAlgorithm;
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+
+// Built-in agent strategies, assigned to nodes with `simulate agents` and `simulate attackers`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentKind {
+    Honest,
+    Random,
+    Adversarial,
+}
+
+// How attackers choose whom to endorse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackerStrategy {
+    Collude, // endorse a random fellow attacker, forming a mutual endorsement ring
+    Boost,   // all endorse the first attacker, pushing a single node up
+}
+
+// Generative model: every tick, each node's agent endorses others a Poisson-distributed number of times.
+// Honest agents pick targets in proportion to their current rank with probability `preferential`
+// (uniformly otherwise), so reputation feeds back into who gets endorsed next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationConfig {
+    pub ticks: usize,
+    pub activity: f64,                     // mean endorsements per node per tick
+    pub node_activity: Vec<(NodeId, f64)>, // per-node overrides of `activity`
+    pub preferential: f64,                 // probability of rank-proportional target choice
+    pub attackers: Vec<NodeId>,
+    pub attacker_strategy: AttackerStrategy,
+    pub agents: Vec<(NodeId, AgentKind)>, // strategy overrides; other nodes are honest unless attackers
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            ticks: 20,
+            activity: 0.2,
+            node_activity: Vec::new(),
+            preferential: 0.5,
+            attackers: Vec::new(),
+            attacker_strategy: AttackerStrategy::Collude,
+            agents: Vec::new(),
+        }
+    }
+}
+
+impl SimulationConfig {
+    // Applies one `simulate <setting> <values...>` line
+    pub fn set(&mut self, setting: &str, values: &[&str]) -> Result<(), String> {
+        let value = |i: usize| values.get(i).copied().ok_or_else(|| format!("simulate {} requires a value", setting));
+        let number = |i: usize| value(i).and_then(|v| v.parse::<f64>().map_err(|_| format!("invalid number for simulate {}: {}", setting, v)));
+        let node = |text: &str| text.parse().map(NodeId).map_err(|_| format!("invalid node: {}", text));
+        match setting {
+            "ticks" => self.ticks = value(0)?.parse().map_err(|_| format!("invalid tick count: {}", value(0).unwrap_or_default()))?,
+            "activity" if values.len() >= 2 => self.node_activity.push((node(value(0)?)?, number(1)?)),
+            "activity" => self.activity = number(0)?,
+            "preferential" => self.preferential = number(0)?,
+            "attackers" => {
+                self.attackers = value(0)?.split(',').map(|n| node(n.trim())).collect::<Result<_, _>>()?;
+                if let Some(&strategy) = values.get(1) {
+                    self.attacker_strategy = match strategy {
+                        "collude" => AttackerStrategy::Collude,
+                        "boost" => AttackerStrategy::Boost,
+                        _ => return Err(format!("unknown attacker strategy: {} (expected collude or boost)", strategy)),
+                    };
+                }
+            }
+            "agents" => {
+                let kind = match value(1)? {
+                    "honest" => AgentKind::Honest,
+                    "random" => AgentKind::Random,
+                    "adversarial" => AgentKind::Adversarial,
+                    kind => return Err(format!("unknown agent strategy: {} (expected honest, random or adversarial)", kind)),
+                };
+                for n in value(0)?.split(',') {
+                    self.agents.push((node(n.trim())?, kind));
+                }
+            }
+            _ => return Err(format!("unknown simulation setting: {}", setting)),
+        }
+        Ok(())
+    }
+
+    fn activity_of(&self, node: NodeId) -> f64 {
+        self.node_activity.iter().rev().find(|(n, _)| *n == node).map_or(self.activity, |&(_, rate)| rate)
+    }
+
+    pub fn agent_kind(&self, node: NodeId) -> AgentKind {
+        match self.agents.iter().rev().find(|(n, _)| *n == node) {
+            Some(&(_, kind)) => kind,
+            None if self.attackers.contains(&node) => AgentKind::Adversarial,
+            None => AgentKind::Honest,
+        }
+    }
+
+    // One agent per node, built from the configured strategies
+    pub fn agents(&self, num_of_nodes: usize) -> Vec<Box<dyn Agent>> {
+        let ring: Vec<NodeId> = (0..num_of_nodes).map(NodeId).filter(|&n| self.agent_kind(n) == AgentKind::Adversarial).collect();
+        (0..num_of_nodes)
+            .map(NodeId)
+            .map(|node| -> Box<dyn Agent> {
+                let activity = self.activity_of(node);
+                match self.agent_kind(node) {
+                    AgentKind::Honest => Box::new(HonestAgent { activity, preferential: self.preferential }),
+                    AgentKind::Random => Box::new(RandomAgent { activity }),
+                    AgentKind::Adversarial => Box::new(AdversarialAgent { activity, strategy: self.attacker_strategy, ring: ring.clone() }),
+                }
+            })
+            .collect()
+    }
+}
+
+// What an agent can see when deciding whom to endorse
+pub struct Observation<'a> {
+    pub node: NodeId,
+    pub tick: usize,
+    pub ranks: &'a [f64], // ranks of all nodes after the previous tick
+}
+
+// Decides whom a node endorses in each tick of a simulation
+pub trait Agent {
+    fn name(&self) -> &str;
+    fn endorse(&mut self, observation: &Observation, rng: &mut Rng) -> Vec<NodeId>;
+}
+
+// Endorses rank-proportionally with probability `preferential`, uniformly otherwise
+pub struct HonestAgent {
+    pub activity: f64,
+    pub preferential: f64,
+}
+
+impl Agent for HonestAgent {
+    fn name(&self) -> &str {
+        "honest"
+    }
+
+    fn endorse(&mut self, observation: &Observation, rng: &mut Rng) -> Vec<NodeId> {
+        (0..rng.poisson(self.activity))
+            .filter_map(|_| {
+                let preferential = rng.chance(self.preferential);
+                pick_other(observation, rng, |i| if preferential { observation.ranks[i] } else { 1.0 })
+            })
+            .collect()
+    }
+}
+
+// Endorses uniformly random nodes, ignoring ranks
+pub struct RandomAgent {
+    pub activity: f64,
+}
+
+impl Agent for RandomAgent {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn endorse(&mut self, observation: &Observation, rng: &mut Rng) -> Vec<NodeId> {
+        (0..rng.poisson(self.activity)).filter_map(|_| pick_other(observation, rng, |_| 1.0)).collect()
+    }
+}
+
+// Member of a group of attackers that only endorse each other
+pub struct AdversarialAgent {
+    pub activity: f64,
+    pub strategy: AttackerStrategy,
+    pub ring: Vec<NodeId>, // all attackers, including this one
+}
+
+impl Agent for AdversarialAgent {
+    fn name(&self) -> &str {
+        "adversarial"
+    }
+
+    fn endorse(&mut self, observation: &Observation, rng: &mut Rng) -> Vec<NodeId> {
+        let ring = &self.ring;
+        let strategy = self.strategy;
+        (0..rng.poisson(self.activity))
+            .filter_map(|_| {
+                pick_other(observation, rng, |i| match strategy {
+                    AttackerStrategy::Collude => if ring.contains(&NodeId(i)) { 1.0 } else { 0.0 },
+                    AttackerStrategy::Boost => if ring.first() == Some(&NodeId(i)) { 1.0 } else { 0.0 },
+                })
+            })
+            .collect()
+    }
+}
+
+// Another node drawn with probability proportional to the given weight
+fn pick_other(observation: &Observation, rng: &mut Rng, weight: impl Fn(usize) -> f64) -> Option<NodeId> {
+    let weights: Vec<f64> = (0..observation.ranks.len())
+        .map(|i| if i == observation.node.index() { 0.0 } else { weight(i) })
+        .collect();
+    rng.weighted(&weights).map(NodeId)
+}
+
+// Runs the configured simulation on top of the scenario's own edges and returns all edges, scenario edges first
+pub fn simulate(scenario: &Scenario, config: &SimulationConfig, algorithm: &dyn RankingAlgorithm) -> Vec<Edge> {
+    simulate_agents(scenario, &mut config.agents(scenario.num_of_nodes), config.ticks, scenario.seed, algorithm)
+}
+
+// Runs a simulation with one agent per node. Ranks are recomputed after every tick with the
+// given algorithm and observed by the agents in the next tick.
+pub fn simulate_agents(scenario: &Scenario, agents: &mut [Box<dyn Agent>], ticks: usize, seed: u64, algorithm: &dyn RankingAlgorithm) -> Vec<Edge> {
+    let mut rng = Rng::new(seed);
+    let mut world = scenario.clone();
+
+    for tick in 1..=ticks {
+        let time = Time(tick as f64);
+        let observed = Time((tick - 1) as f64);
+        let weights = world.edge_weights(observed);
+        let graph = world.frame_graph(observed, &weights).unwrap();
+        let ranks = algorithm.rank(&graph, &world.teleportation_targets(observed));
+
+        for (i, agent) in agents.iter_mut().enumerate() {
+            let source = NodeId(i);
+            for target in agent.endorse(&Observation { node: source, tick, ranks: &ranks }, &mut rng) {
+                world.edges.push(Edge { source, target, time_of_creation: time, weight: 1.0 });
+            }
+        }
+    }
+    world.edges
+}
+
+// Outcome of one algorithm in a tournament
+#[derive(Debug, Clone)]
+pub struct TournamentResult {
+    pub algorithm: String,
+    pub adversarial_share: f64, // total rank of the adversarial nodes after the last tick
+    pub top_adversarial: usize, // adversarial nodes among the `adversaries` top-ranked nodes
+}
+
+// Runs the same simulation (same agents and scenario seed) once per algorithm, with each algorithm providing the
+// ranks the agents react to, and measures how much rank the adversarial agents capture under it
+pub fn tournament(scenario: &Scenario, config: &SimulationConfig, algorithms: &[Box<dyn RankingAlgorithm>]) -> Vec<TournamentResult> {
+    let adversaries: Vec<NodeId> = (0..scenario.num_of_nodes).map(NodeId).filter(|&n| config.agent_kind(n) == AgentKind::Adversarial).collect();
+    let time = Time(config.ticks as f64);
+    algorithms
+        .iter()
+        .map(|algorithm| {
+            let world = Scenario { edges: simulate(scenario, config, algorithm.as_ref()), ..scenario.clone() };
+            let graph = world.frame_graph(time, &world.edge_weights(time)).unwrap();
+            let ranks = algorithm.rank(&graph, &world.teleportation_targets(time));
+            let mut order: Vec<usize> = (0..ranks.len()).collect();
+            order.sort_by(|&a, &b| ranks[b].total_cmp(&ranks[a]));
+            TournamentResult {
+                algorithm: algorithm.name().to_string(),
+                adversarial_share: adversaries.iter().map(|n| ranks[n.index()]).sum::<f64>() / ranks.iter().sum::<f64>(),
+                top_adversarial: order.iter().take(adversaries.len()).filter(|&&i| adversaries.contains(&NodeId(i))).count(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::json::Json;
+
+// Destination for generated files (frames, reports, snapshots).
+// Paths are relative and '/'-separated; each sink decides where they end up.
+pub trait Sink {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()>;
+
+    // Blocks until everything written so far has reached its destination
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    // Human-readable location of a written path, for progress messages
+    fn location(&self, path: &str) -> String {
+        path.to_string()
+    }
+}
+
+// Writes below a root folder, creating intermediate folders as needed
+pub struct FileSystemSink {
+    root: PathBuf,
+}
+
+impl FileSystemSink {
+    pub fn new(root: &str) -> Self {
+        FileSystemSink { root: PathBuf::from(root) }
+    }
+}
+
+impl Sink for FileSystemSink {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        let full_path = path.split('/').fold(self.root.clone(), |p, part| p.join(part));
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(full_path, contents)
+    }
+
+    fn location(&self, path: &str) -> String {
+        format!("{}/{}", self.root.display(), path)
+    }
+}
+
+// Name of the file that lists the files of a run, at the root of the output
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+// Passes writes on to another sink and remembers their paths, so that the files belonging to a
+// run can be told apart from leftovers of earlier runs in the same place
+pub struct ManifestSink {
+    inner: Box<dyn Sink>,
+    written: BTreeSet<String>,
+}
+
+impl ManifestSink {
+    pub fn new(inner: Box<dyn Sink>) -> Self {
+        ManifestSink { inner, written: BTreeSet::new() }
+    }
+
+    pub fn written(&self) -> &BTreeSet<String> {
+        &self.written
+    }
+
+    // A file written next to the sink's files by another program, e.g. an image rendered by Graphviz
+    pub fn record(&mut self, path: &str) {
+        self.written.insert(path.to_string());
+    }
+
+    // Writes MANIFEST_FILE: {"run_id":"...","created_at":<seconds since the epoch>,"files":["<path>",...]}
+    pub fn write_manifest(&mut self, run_id: Option<&str>) -> io::Result<()> {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let manifest = Json::object(vec![
+            ("run_id", run_id.map_or(Json::Null, Json::from)),
+            ("created_at", (created_at as f64).into()),
+            ("files", Json::Array(self.written.iter().map(|path| Json::from(path.as_str())).collect())),
+        ]);
+        self.inner.write(MANIFEST_FILE, format!("{}\n", manifest).as_bytes())
+    }
+}
+
+impl Sink for ManifestSink {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        self.inner.write(path, contents)?;
+        self.written.insert(path.to_string());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn location(&self, path: &str) -> String {
+        self.inner.location(path)
+    }
+}
+
+// Files listed in a manifest written by ManifestSink
+pub fn manifest_files(manifest: &str) -> Result<Vec<String>, String> {
+    let json = Json::parse(manifest)?;
+    let files = json.get("files").and_then(Json::as_array).ok_or("manifest without a file list")?;
+    files.iter().map(|file| file.as_str().map(str::to_string).ok_or_else(|| "manifest lists a file that is not a string".to_string())).collect()
+}
+
+// Keeps everything in memory, e.g. to inspect generated content without touching disk
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        self.files.get(path).map(|c| c.as_slice())
+    }
+}
+
+impl Sink for MemorySink {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        self.files.insert(path.to_string(), contents.to_vec());
+        Ok(())
+    }
+}
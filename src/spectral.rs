@@ -0,0 +1,98 @@
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::rank::{transition_triplets, RankConfig};
+use crate::types::{FrameIdx, Time};
+
+// Target L1 error of the iteration budget estimate when the rank config has no tolerance of its own
+pub const DEFAULT_DIAGNOSTICS_TOLERANCE: f64 = 1e-6;
+
+// Power iteration steps of the second eigenvalue estimate
+const EIGENVALUE_ITERATIONS: usize = 400;
+
+// Convergence outlook of one frame's power iteration. The error of the iterate shrinks as
+// e' = damping * P^T e, where P is the effective transition matrix (see transition_triplets), and
+// stays orthogonal to the all-ones vector, so after the transients it shrinks by damping * |λ2|
+// per step, λ2 being the second largest eigenvalue of P in modulus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpectralEstimate {
+    pub time: Time,
+    pub damping_factor: f64,
+    pub second_eigenvalue: f64,           // |λ2| of P, in [0, 1]; 1 when the graph has several closed components or a cycle
+    pub convergence_factor: f64,          // damping * |λ2|, the factor by which the error shrinks per step
+    pub iterations_needed: Option<usize>, // steps from any start until the L1 error is below the tolerance; None when it never gets there
+}
+
+impl SpectralEstimate {
+    pub fn of_frame(time: Time, graph: &CompiledGraph, teleportation_targets: &[f64], config: &RankConfig, tolerance: f64) -> Self {
+        let second_eigenvalue = second_eigenvalue(graph.num_of_nodes, &transition_triplets(graph, teleportation_targets, config));
+        let convergence_factor = config.damping_factor * second_eigenvalue;
+        SpectralEstimate { time, damping_factor: config.damping_factor, second_eigenvalue, convergence_factor, iterations_needed: iterations_needed(convergence_factor, tolerance) }
+    }
+
+    // 1 - damping * |λ2|
+    pub fn spectral_gap(&self) -> f64 {
+        1.0 - self.convergence_factor
+    }
+}
+
+// |λ2| of a row-stochastic matrix given as (source, target, probability) triplets, by power
+// iteration of P^T on vectors that sum to 0, which leaves out the eigenvalue 1 of the stationary
+// distribution. The growth is averaged over the second half of the steps, so complex and
+// negative eigenvalues, whose iterates rotate or flip sign, are measured by their modulus too.
+pub fn second_eigenvalue(num_of_nodes: usize, triplets: &[(usize, usize, f64)]) -> f64 {
+    if num_of_nodes < 2 {
+        return 0.0;
+    }
+    // Not symmetric, so that the start vector is unlikely to miss the second eigenvector
+    let mut vector: Vec<f64> = (0..num_of_nodes).map(|i| ((i * 7919) % 104729) as f64 / 104729.0 + i as f64 / num_of_nodes as f64).collect();
+    project(&mut vector);
+    let mut log_growth = 0.0;
+    for step in 0..EIGENVALUE_ITERATIONS {
+        let mut next = vec![0.0; num_of_nodes];
+        for &(source, target, probability) in triplets {
+            next[target] += probability * vector[source];
+        }
+        project(&mut next);
+        let norm: f64 = next.iter().map(|v| v.abs()).sum();
+        if norm < 1e-300 {
+            return 0.0; // the error dies out after finitely many steps
+        }
+        if step >= EIGENVALUE_ITERATIONS / 2 {
+            log_growth += norm.ln(); // the previous vector has norm 1
+        }
+        vector = next.iter().map(|v| v / norm).collect();
+    }
+    (log_growth / (EIGENVALUE_ITERATIONS - EIGENVALUE_ITERATIONS / 2) as f64).exp().min(1.0)
+}
+
+// Removes the rounding drift off the vectors that sum to 0
+fn project(vector: &mut [f64]) {
+    let mean = vector.iter().sum::<f64>() / vector.len() as f64;
+    vector.iter_mut().for_each(|v| *v -= mean);
+}
+
+// Steps until an initial L1 error of at most 2 (between any two distributions) shrinks below
+// `tolerance` at `convergence_factor` per step
+pub fn iterations_needed(convergence_factor: f64, tolerance: f64) -> Option<usize> {
+    if tolerance >= 2.0 {
+        Some(0)
+    } else if convergence_factor <= 0.0 {
+        Some(1)
+    } else if convergence_factor >= 1.0 || tolerance <= 0.0 {
+        None
+    } else {
+        Some(((tolerance / 2.0).ln() / convergence_factor.ln()).ceil() as usize)
+    }
+}
+
+// frame,time,damping,second_eigenvalue,convergence_factor,spectral_gap,iterations_needed rows;
+// iterations_needed is empty when the iteration does not converge
+pub fn write_spectral_csv(out: &mut dyn Write, estimates: &[SpectralEstimate]) -> io::Result<()> {
+    writeln!(out, "frame,time,damping,second_eigenvalue,convergence_factor,spectral_gap,iterations_needed")?;
+    for (frame, estimate) in estimates.iter().enumerate() {
+        let iterations = estimate.iterations_needed.map_or(String::new(), |n| n.to_string());
+        writeln!(out, "{},{},{},{:.6},{:.6},{:.6},{}", FrameIdx(frame).number(), estimate.time, estimate.damping_factor, estimate.second_eigenvalue, estimate.convergence_factor, estimate.spectral_gap(), iterations)?;
+    }
+    Ok(())
+}
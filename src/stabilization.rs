@@ -0,0 +1,36 @@
+use std::io::{self, Write};
+
+use crate::compare::kendall_tau;
+use crate::history::RankHistory;
+use crate::types::FrameIdx;
+
+// The rank ordering counts as stable once the Kendall tau between consecutive frames stays at or
+// above `threshold` for `frames` frames in a row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StabilizationCriterion {
+    pub threshold: f64,
+    pub frames: usize,
+}
+
+// Kendall tau between every frame and the next one; NaN where either frame ranks all nodes alike
+pub fn consecutive_taus(history: &RankHistory) -> Vec<f64> {
+    history.frames().windows(2).map(|pair| kendall_tau(&pair[0], &pair[1])).collect()
+}
+
+// First frame whose ordering the next `frames` frames keep, if the run has one
+pub fn stabilization_frame(taus: &[f64], criterion: &StabilizationCriterion) -> Option<FrameIdx> {
+    let frames = criterion.frames.max(1);
+    (0..taus.len().saturating_sub(frames - 1)).find(|&start| taus[start..start + frames].iter().all(|&tau| tau >= criterion.threshold)).map(FrameIdx)
+}
+
+// frame,time,kendall_tau rows, the tau against the previous frame; empty for the first frame
+pub fn write_stabilization_csv(out: &mut dyn Write, history: &RankHistory, taus: &[f64]) -> io::Result<()> {
+    writeln!(out, "frame,time,kendall_tau")?;
+    for (frame, time) in history.times().iter().enumerate() {
+        match frame.checked_sub(1).map(|previous| taus[previous]) {
+            Some(tau) => writeln!(out, "{},{},{:.6}", FrameIdx(frame).number(), time, tau)?,
+            None => writeln!(out, "{},{},", FrameIdx(frame).number(), time)?,
+        }
+    }
+    Ok(())
+}
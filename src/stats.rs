@@ -0,0 +1,166 @@
+use std::fmt;
+
+use crate::json::Json;
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::types::{FrameIdx, Time};
+
+// How concentrated a frame's rank is and how much of the graph carries it, to follow inequality
+// over time. Only nodes in the graph at the frame's time count.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameStats {
+    pub gini: f64,         // 0 when every node ranks the same, towards 1 when one node holds all rank
+    pub entropy: f64,      // of the ranks as a distribution, in bits; log2 of the node count when uniform
+    pub active_edges: usize,
+    pub total_weight: f64, // decayed weight of all edges
+}
+
+impl FrameStats {
+    // `active` tells per node whether it is in the graph (empty when all are); ranks below 0
+    // count as 0
+    pub fn new(ranks: &[f64], weights: &[f64], active: &[bool]) -> Self {
+        let mut ranks: Vec<f64> = ranks.iter().enumerate().filter(|&(node, _)| active.get(node) != Some(&false)).map(|(_, &rank)| rank.max(0.0)).collect();
+        FrameStats {
+            gini: gini(&mut ranks),
+            entropy: entropy(&ranks),
+            active_edges: weights.iter().filter(|&&w| w > 0.0).count(),
+            total_weight: weights.iter().sum(),
+        }
+    }
+
+    // Statistics of every frame of a run, from the ranks it stores
+    pub fn of_run(scenario: &Scenario, output: &RunOutput) -> Vec<FrameStats> {
+        let history = &output.history;
+        history.frames().iter().zip(history.times()).zip(&output.edge_weights).map(|((ranks, &time), weights)| FrameStats::new(ranks, weights, &scenario.active_nodes(time))).collect()
+    }
+
+    // Lines added to the frame label with `style frame-stats on`
+    pub fn label_lines(&self) -> Vec<String> {
+        vec![
+            format!("Gini: {:.2}, entropy: {:.2} bits", self.gini, self.entropy),
+            format!("Active edges: {}, total weight: {:.2}", self.active_edges, self.total_weight),
+        ]
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("gini", self.gini.into()),
+            ("entropy", self.entropy.into()),
+            ("active_edges", self.active_edges.into()),
+            ("total_weight", self.total_weight.into()),
+        ])
+    }
+}
+
+// Mean absolute difference of all pairs over twice the mean, from the sorted values
+fn gini(values: &mut [f64]) -> f64 {
+    let n = values.len() as f64;
+    let total: f64 = values.iter().sum();
+    if values.len() < 2 || total <= 0.0 {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let weighted: f64 = values.iter().enumerate().map(|(i, &v)| (i + 1) as f64 * v).sum();
+    (2.0 * weighted / (n * total) - (n + 1.0) / n).max(0.0)
+}
+
+fn entropy(values: &[f64]) -> f64 {
+    let total: f64 = values.iter().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    -values.iter().filter(|&&v| v > 0.0).map(|&v| v / total).map(|p| p * p.log2()).sum::<f64>()
+}
+
+// Rank concentration that is worth a warning, from `alarm` lines in scenario files:
+//
+//   alarm top 3 above 0.6    # the three highest ranked nodes hold more than 60% of the rank
+//   alarm gini above 0.5
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConcentrationAlarm {
+    TopShare { nodes: usize, share: f64 },
+    Gini(f64),
+}
+
+impl fmt::Display for ConcentrationAlarm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConcentrationAlarm::TopShare { nodes, share } => write!(f, "top {} above {}", nodes, share),
+            ConcentrationAlarm::Gini(gini) => write!(f, "gini above {}", gini),
+        }
+    }
+}
+
+impl ConcentrationAlarm {
+    pub fn parse(text: &str) -> Result<ConcentrationAlarm, String> {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let threshold = |token: &str| token.parse::<f64>().ok().filter(|t| (0.0..=1.0).contains(t)).ok_or_else(|| format!("alarm threshold must be between 0 and 1: {}", token));
+        match tokens.as_slice() {
+            ["top", nodes, "above", share] => {
+                let nodes = nodes.parse().ok().filter(|&n| n > 0).ok_or_else(|| format!("alarm node count must be a positive integer: {}", nodes))?;
+                Ok(ConcentrationAlarm::TopShare { nodes, share: threshold(share)? })
+            }
+            ["gini", "above", gini] => Ok(ConcentrationAlarm::Gini(threshold(gini)?)),
+            _ => Err(format!("expected 'top <nodes> above <share>' or 'gini above <value>', found '{}'", text)),
+        }
+    }
+
+    // The measured value when it is above the threshold
+    fn check(&self, ranks: &[f64], active: &[bool], stats: &FrameStats) -> Option<f64> {
+        let (value, threshold) = match *self {
+            ConcentrationAlarm::TopShare { nodes, share } => {
+                let mut ranks: Vec<f64> = ranks.iter().enumerate().filter(|&(node, _)| active.get(node) != Some(&false)).map(|(_, &rank)| rank.max(0.0)).collect();
+                let total: f64 = ranks.iter().sum();
+                ranks.sort_by(|a, b| b.total_cmp(a));
+                let top: f64 = ranks.iter().take(nodes).sum();
+                (if total > 0.0 { top / total } else { 0.0 }, share)
+            }
+            ConcentrationAlarm::Gini(gini) => (stats.gini, gini),
+        };
+        (value > threshold).then_some(value)
+    }
+}
+
+// An alarm going off in a frame, with the value that set it off
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaisedAlarm {
+    pub frame: FrameIdx,
+    pub time: Time,
+    pub alarm: ConcentrationAlarm,
+    pub value: f64,
+}
+
+impl RaisedAlarm {
+    pub fn message(&self) -> String {
+        match self.alarm {
+            ConcentrationAlarm::TopShare { nodes: 1, share } => format!("the top node holds {:.1}% of the rank (alarm above {}%)", self.value * 100.0, (share * 1000.0).round() / 10.0),
+            ConcentrationAlarm::TopShare { nodes, share } => format!("the top {} nodes hold {:.1}% of the rank (alarm above {}%)", nodes, self.value * 100.0, (share * 1000.0).round() / 10.0),
+            ConcentrationAlarm::Gini(gini) => format!("Gini coefficient {:.2} (alarm above {})", self.value, gini),
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("frame", self.frame.number().into()),
+            ("time", self.time.value().into()),
+            ("alarm", self.alarm.to_string().into()),
+            ("value", self.value.into()),
+        ])
+    }
+}
+
+// The scenario's alarms that go off, frame by frame, from the raw ranks of a run
+pub fn concentration_alarms(scenario: &Scenario, output: &RunOutput) -> Vec<RaisedAlarm> {
+    let history = &output.history;
+    (0..history.num_of_frames()).flat_map(|frame| alarms_at(scenario, FrameIdx(frame), history.ranks_at(FrameIdx(frame)), &output.edge_weights[frame], history.times()[frame])).collect()
+}
+
+// The scenario's alarms that go off in one frame
+pub fn alarms_at(scenario: &Scenario, frame: FrameIdx, ranks: &[f64], weights: &[f64], time: Time) -> Vec<RaisedAlarm> {
+    if scenario.alarms.is_empty() {
+        return Vec::new();
+    }
+    let active = scenario.active_nodes(time);
+    let stats = FrameStats::new(ranks, weights, &active);
+    scenario.alarms.iter().filter_map(|&alarm| alarm.check(ranks, &active, &stats).map(|value| RaisedAlarm { frame, time, alarm, value })).collect()
+}
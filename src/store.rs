@@ -0,0 +1,149 @@
+// Run database: every run's scenario, parameters and per-frame node ranks in one SQLite file,
+// so that results of many runs (other settings, other days' data) can be compared later.
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+
+use crate::hashing::run_hash;
+use crate::run::RunOutput;
+use crate::scenario::Scenario;
+use crate::types::{FrameIdx, NodeId, Time};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        scenario TEXT NOT NULL,
+        algorithm TEXT NOT NULL,
+        parameters TEXT NOT NULL, -- rank settings, as in checkpoint fingerprints
+        decay TEXT NOT NULL,
+        seed TEXT NOT NULL,       -- a string, SQLite integers cannot hold every u64
+        result_hash TEXT NOT NULL,
+        created_at INTEGER NOT NULL -- seconds since the Unix epoch
+    );
+    CREATE TABLE IF NOT EXISTS nodes (
+        run INTEGER NOT NULL REFERENCES runs(id),
+        node INTEGER NOT NULL,
+        label TEXT NOT NULL,
+        expert INTEGER NOT NULL,
+        PRIMARY KEY (run, node)
+    );
+    CREATE TABLE IF NOT EXISTS edges (
+        run INTEGER NOT NULL REFERENCES runs(id),
+        edge INTEGER NOT NULL,
+        source INTEGER NOT NULL,
+        target INTEGER NOT NULL,
+        time REAL NOT NULL,
+        weight REAL NOT NULL,
+        PRIMARY KEY (run, edge)
+    );
+    CREATE TABLE IF NOT EXISTS ranks (
+        run INTEGER NOT NULL REFERENCES runs(id),
+        frame INTEGER NOT NULL, -- numbered from 1, like in rendered output
+        time REAL NOT NULL,
+        node INTEGER NOT NULL,
+        rank REAL NOT NULL,
+        PRIMARY KEY (run, frame, node)
+    );
+    CREATE INDEX IF NOT EXISTS ranks_by_node ON ranks (node, time);
+";
+
+// Rank of a node in one frame of a stored run
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredRank {
+    pub run: i64,
+    pub scenario: String,
+    pub algorithm: String,
+    pub frame: usize,
+    pub time: Time,
+    pub node: NodeId,
+    pub label: String,
+    pub rank: f64,
+}
+
+// Which stored ranks a query returns; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct RankQuery {
+    pub node: Option<String>, // node name or index
+    pub scenario: Option<String>,
+    pub since: Option<Time>, // frames at or after this time
+    pub until: Option<Time>, // frames at or before this time
+}
+
+pub struct RunStore {
+    connection: Connection,
+}
+
+impl RunStore {
+    // Opens the database, creating it and its tables when missing
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(RunStore { connection })
+    }
+
+    // Stores a finished run in one transaction and returns its id
+    pub fn record_run(&mut self, scenario: &Scenario, algorithm: &str, parameters: &str, output: &RunOutput) -> rusqlite::Result<i64> {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO runs (scenario, algorithm, parameters, decay, seed, result_hash, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![scenario.name, algorithm, parameters, scenario.decay_description(), scenario.seed.to_string(), format!("{:016x}", run_hash(&output.frame_hashes)), created_at],
+        )?;
+        let run = transaction.last_insert_rowid();
+        {
+            let mut insert_node = transaction.prepare("INSERT INTO nodes (run, node, label, expert) VALUES (?1, ?2, ?3, ?4)")?;
+            for node in (0..scenario.num_of_nodes).map(NodeId) {
+                insert_node.execute(params![run, node.index() as i64, scenario.node_label(node), scenario.experts.contains(&node)])?;
+            }
+            let mut insert_edge = transaction.prepare("INSERT INTO edges (run, edge, source, target, time, weight) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+            for (i, edge) in scenario.edges.iter().enumerate() {
+                insert_edge.execute(params![run, i as i64, edge.source.index() as i64, edge.target.index() as i64, edge.time_of_creation.value(), edge.weight])?;
+            }
+            let mut insert_rank = transaction.prepare("INSERT INTO ranks (run, frame, time, node, rank) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+            for (frame, (time, ranks)) in output.history.times().iter().zip(output.history.frames()).enumerate() {
+                for (node, rank) in ranks.iter().enumerate() {
+                    insert_rank.execute(params![run, FrameIdx(frame).number() as i64, time.value(), node as i64, rank])?;
+                }
+            }
+        }
+        transaction.commit()?;
+        Ok(run)
+    }
+
+    // Matching ranks of every stored run, ordered by run, time and node
+    pub fn query_ranks(&self, query: &RankQuery) -> rusqlite::Result<Vec<StoredRank>> {
+        let mut statement = self.connection.prepare(
+            "SELECT r.run, runs.scenario, runs.algorithm, r.frame, r.time, r.node, n.label, r.rank
+             FROM ranks r
+             JOIN runs ON runs.id = r.run
+             JOIN nodes n ON n.run = r.run AND n.node = r.node
+             WHERE (?1 IS NULL OR n.label = ?1 OR CAST(n.node AS TEXT) = ?1)
+               AND (?2 IS NULL OR runs.scenario = ?2)
+               AND (?3 IS NULL OR r.time >= ?3)
+               AND (?4 IS NULL OR r.time <= ?4)
+             ORDER BY r.run, r.time, r.node",
+        )?;
+        let rows = statement.query_map(params![query.node, query.scenario, query.since.map(Time::value), query.until.map(Time::value)], |row| {
+            Ok(StoredRank {
+                run: row.get(0)?,
+                scenario: row.get(1)?,
+                algorithm: row.get(2)?,
+                frame: row.get::<_, i64>(3)? as usize,
+                time: Time(row.get(4)?),
+                node: NodeId(row.get::<_, i64>(5)? as usize),
+                label: row.get(6)?,
+                rank: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+pub fn write_stored_ranks_csv(out: &mut dyn Write, ranks: &[StoredRank]) -> io::Result<()> {
+    writeln!(out, "run,scenario,algorithm,frame,time,node,label,rank")?;
+    for r in ranks {
+        writeln!(out, "{},{},{},{},{},{},{},{:.6}", r.run, r.scenario, r.algorithm, r.frame, r.time, r.node, r.label, r.rank)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,528 @@
+use crate::clusters::ClusterBy;
+use crate::graph::Edge;
+
+// How rendered frames look; set from `style <key> <value>` lines in scenario files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    Blues,   // white to blue
+    Greys,   // white to black
+    Reds,    // white to red
+    Viridis, // perceptually uniform, dark purple to yellow
+    Magma,   // perceptually uniform, black to light yellow
+}
+
+// Samples of matplotlib's perceptually uniform colormaps at 0, 0.1, ..., 1
+const VIRIDIS: [(u8, u8, u8); 11] = [
+    (0x44, 0x01, 0x54), (0x48, 0x24, 0x75), (0x41, 0x44, 0x87), (0x35, 0x5F, 0x8D), (0x2A, 0x78, 0x8E), (0x21, 0x91, 0x8C),
+    (0x22, 0xA8, 0x84), (0x44, 0xBF, 0x70), (0x7A, 0xD1, 0x51), (0xBD, 0xDF, 0x26), (0xFD, 0xE7, 0x25),
+];
+const MAGMA: [(u8, u8, u8); 11] = [
+    (0x00, 0x00, 0x04), (0x14, 0x0E, 0x36), (0x3B, 0x0F, 0x70), (0x64, 0x1A, 0x80), (0x8C, 0x29, 0x81), (0xB7, 0x37, 0x79),
+    (0xDE, 0x49, 0x68), (0xF7, 0x70, 0x5C), (0xFE, 0x9F, 0x6D), (0xFE, 0xCF, 0x92), (0xFC, 0xFD, 0xBF),
+];
+
+fn interpolate(samples: &[(u8, u8, u8)], position: f64) -> String {
+    let scaled = position * (samples.len() - 1) as f64;
+    let i = (scaled.floor() as usize).min(samples.len() - 2);
+    let t = scaled - i as f64;
+    let (r0, g0, b0) = samples[i];
+    let (r1, g1, b1) = samples[i + 1];
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    format!("#{:02X}{:02X}{:02X}", mix(r0, r1), mix(g0, g1), mix(b0, b1))
+}
+
+impl Colormap {
+    // Fill color for a position in [0, 1]
+    pub fn color(&self, position: f64) -> String {
+        let p = position.clamp(0.0, 1.0);
+        let level = ((1.0 - p) * 255.0) as u8;
+        match self {
+            Colormap::Blues => format!("#{:02X}{:02X}{:02X}", level, level, 255u8),
+            Colormap::Greys => format!("#{:02X}{:02X}{:02X}", level, level, level),
+            Colormap::Reds => format!("#{:02X}{:02X}{:02X}", 255u8, level, level),
+            Colormap::Viridis => interpolate(&VIRIDIS, p),
+            Colormap::Magma => interpolate(&MAGMA, p),
+        }
+    }
+
+    // Whether labels on this color need a light font to stay readable
+    pub fn is_dark(&self, position: f64) -> bool {
+        match self {
+            Colormap::Blues | Colormap::Reds => false,
+            Colormap::Greys => position > 0.6,
+            Colormap::Viridis | Colormap::Magma => position < 0.5,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Blues => "blues",
+            Colormap::Greys => "greys",
+            Colormap::Reds => "reds",
+            Colormap::Viridis => "viridis",
+            Colormap::Magma => "magma",
+        }
+    }
+}
+
+// How ranks are mapped onto the colormap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    None,     // ranks as they are, 0 to 1
+    PerFrame, // min to max of the frame being drawn
+    Global,   // min to max over all frames of the run
+    Log,      // like Global, on a logarithmic scale
+}
+
+impl Normalization {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Normalization::None => "absolute",
+            Normalization::PerFrame => "per-frame",
+            Normalization::Global => "global",
+            Normalization::Log => "log",
+        }
+    }
+}
+
+// Smallest rank shown on a logarithmic scale; zero ranks are drawn with the lowest color
+const LOG_SCALE_FLOOR: f64 = 1e-6;
+
+// Colormap together with the rank range it spans, fixed for one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorScale {
+    pub colormap: Colormap,
+    pub min: f64,
+    pub max: f64,
+    pub log: bool,
+}
+
+impl ColorScale {
+    // `frame` holds the ranks drawn in this frame, `run` the ranks of every frame of the run
+    pub fn new(style: &RenderStyle, frame: &[f64], run: &[Vec<f64>]) -> Self {
+        let range = |values: &mut dyn Iterator<Item = f64>| {
+            values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)))
+        };
+        let (min, max, log) = match style.normalization {
+            Normalization::None => (0.0, 1.0, false),
+            Normalization::PerFrame => {
+                let (lo, hi) = range(&mut frame.iter().copied());
+                (lo, hi, false)
+            }
+            Normalization::Global => {
+                let (lo, hi) = range(&mut run.iter().flatten().copied());
+                (lo, hi, false)
+            }
+            Normalization::Log => {
+                let (lo, hi) = range(&mut run.iter().flatten().copied().filter(|&r| r > 0.0));
+                (lo.max(LOG_SCALE_FLOOR), hi.max(LOG_SCALE_FLOOR), true)
+            }
+        };
+        if min.is_finite() && max.is_finite() {
+            ColorScale { colormap: style.colormap, min, max, log }
+        } else {
+            ColorScale { colormap: style.colormap, min: 0.0, max: 1.0, log }
+        }
+    }
+
+    // Position of a rank on the colormap, in [0, 1]
+    pub fn position(&self, rank: f64) -> f64 {
+        if self.min == 0.0 && self.max == 1.0 && !self.log {
+            return rank.clamp(0.0, 1.0);
+        }
+        if self.max <= self.min {
+            return 0.5;
+        }
+        let p = if self.log {
+            (rank.max(self.min).ln() - self.min.ln()) / (self.max.ln() - self.min.ln())
+        } else {
+            (rank - self.min) / (self.max - self.min)
+        };
+        p.clamp(0.0, 1.0)
+    }
+
+    // Rank shown at a position of the colormap, for legend ticks
+    pub fn value_at(&self, position: f64) -> f64 {
+        if self.log {
+            (self.min.ln() + position * (self.max.ln() - self.min.ln())).exp()
+        } else {
+            self.min + position * (self.max - self.min)
+        }
+    }
+
+    pub fn color(&self, rank: f64) -> String {
+        self.colormap.color(self.position(rank))
+    }
+
+    // Whether the label of a node with this rank needs a light font
+    pub fn is_dark(&self, rank: f64) -> bool {
+        self.colormap.is_dark(self.position(rank))
+    }
+
+    // Extra node attributes (leading ", ") keeping the label readable on dark fills
+    pub fn font_color(&self, rank: f64) -> &'static str {
+        if self.is_dark(rank) { ", fontcolor=\"white\"" } else { "" }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeWidth {
+    Linear, // proportional to the edge weight
+    Sqrt,   // emphasizes weak edges
+    Log,    // log(1 + weight), flattens differences between strong edges
+}
+
+impl EdgeWidth {
+    pub fn width(&self, weight: f64, scale: f64) -> f64 {
+        match self {
+            EdgeWidth::Linear => scale * weight,
+            EdgeWidth::Sqrt => scale * weight.sqrt(),
+            EdgeWidth::Log => scale * weight.ln_1p() / 2f64.ln(),
+        }
+    }
+}
+
+// Which edges of a dense frame are drawn, so it does not turn into a hairball; the ranks still
+// come from every edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeSampling {
+    TopPerNode(usize), // edges among the N heaviest of their source's or their target's edges
+    MinWeight(f64),    // edges weighing at least this much
+}
+
+impl EdgeSampling {
+    // `top 3` or `min 0.5`
+    pub fn parse(text: &str) -> Result<EdgeSampling, String> {
+        let invalid = || format!("invalid edge sampling: {} (expected top N, min WEIGHT or none)", text);
+        let (kind, amount) = text.split_once(' ').map(|(kind, amount)| (kind, amount.trim())).ok_or_else(invalid)?;
+        match kind {
+            "top" => Ok(EdgeSampling::TopPerNode(amount.parse().map_err(|_| invalid())?)),
+            "min" => Ok(EdgeSampling::MinWeight(amount.parse().map_err(|_| invalid())?)),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            EdgeSampling::TopPerNode(count) => format!("top {} per node", count),
+            EdgeSampling::MinWeight(weight) => format!("weight {} and up", weight),
+        }
+    }
+
+    // Whether each edge is drawn. Edges without weight are kept, they are drawn invisible or as
+    // ghosts as before; ties between equal weights go to the lower edge id.
+    pub fn kept(&self, num_of_nodes: usize, edges: &[Edge], weights: &[f64]) -> Vec<bool> {
+        match *self {
+            EdgeSampling::MinWeight(min) => weights.iter().map(|&w| w == 0.0 || w >= min).collect(),
+            EdgeSampling::TopPerNode(count) => {
+                let mut incident = vec![Vec::new(); num_of_nodes];
+                for (id, (edge, _)) in edges.iter().zip(weights).enumerate().filter(|&(_, (_, &w))| w > 0.0) {
+                    incident[edge.source.index()].push(id);
+                    if edge.target != edge.source {
+                        incident[edge.target.index()].push(id);
+                    }
+                }
+                let mut kept: Vec<bool> = weights.iter().map(|&w| w == 0.0).collect();
+                for mut ids in incident {
+                    ids.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]).then(a.cmp(&b)));
+                    ids.iter().take(count).for_each(|&id| kept[id] = true);
+                }
+                kept
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelFormat {
+    IdAndRank, // "3 (0.25)"
+    Id,
+    Rank,
+    None,
+}
+
+// Arrows of rising and falling ranks with `style rank-deltas on`
+// Largest side of thumbnail frames, in inches
+pub const DEFAULT_THUMBNAIL_SIZE: f64 = 3.0;
+
+const RISE_COLOR: &str = "#2e7d32";
+const FALL_COLOR: &str = "#c62828";
+
+// Edges with `style edge-flux on`, at least this opaque (of 255) so that small flows stay visible
+const FLUX_COLOR: &str = "#e65100";
+const FLUX_MIN_ALPHA: f64 = 48.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderStyle {
+    pub colormap: Colormap,
+    pub normalization: Normalization,
+    pub legend: bool, // color legend and parameter box in every frame
+    pub node_size_scale: f64, // extra node width (inches) at rank 1; 0.0 keeps Graphviz's default size
+    pub expert_color: String,
+    pub expert_pen_width: f64,
+    pub edge_width: EdgeWidth,
+    pub edge_width_scale: f64,
+    pub background: Option<String>,
+    pub font_size: f64,
+    pub title_font_size: f64,
+    pub label_format: LabelFormat,
+    pub label_precision: usize,
+    pub invisible_edges: bool, // zero-weight edges are written with style=invis instead of left out
+    pub ghost_edges: bool, // edges created after the frame's time are drawn dashed, as a preview
+    pub rank_deltas: bool, // nodes carry an arrow with their rank change since the previous frame
+    pub edge_tooltips: bool, // edges carry the event ids and metadata of their raw records as tooltips
+    pub edge_flux: bool, // edges are colored and labeled by the rank they carry
+    pub frame_stats: bool, // the frame label adds the rank concentration, active edges and total edge weight
+    pub alarm_banner: bool, // frames where concentration alarms go off show them in a banner
+    pub cluster: Option<ClusterBy>, // nodes grouped into labeled Graphviz clusters
+    pub canvas_size: Option<f64>, // largest side of the drawing in inches, scaled down to fit; None for its natural size
+    pub auto_layout: bool, // only nodes with a fixed position are pinned; Graphviz places the others, starting from the default layout
+    pub edge_sampling: Option<EdgeSampling>, // every edge is drawn when None
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        RenderStyle {
+            colormap: Colormap::Blues,
+            normalization: Normalization::None,
+            legend: true,
+            node_size_scale: 0.0,
+            expert_color: "darkgreen".to_string(),
+            expert_pen_width: 8.0,
+            edge_width: EdgeWidth::Linear,
+            edge_width_scale: 8.0,
+            background: None,
+            font_size: 20.0,
+            title_font_size: 26.0,
+            label_format: LabelFormat::IdAndRank,
+            label_precision: 2,
+            invisible_edges: true,
+            ghost_edges: false,
+            rank_deltas: false,
+            edge_tooltips: false,
+            edge_flux: false,
+            frame_stats: false,
+            alarm_banner: false,
+            cluster: None,
+            canvas_size: None,
+            auto_layout: false,
+            edge_sampling: None,
+        }
+    }
+}
+
+impl RenderStyle {
+    // Applies one `style <key> <value>` setting
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let number = |value: &str| value.parse::<f64>().map_err(|_| format!("invalid number for style {}: {}", key, value));
+        match key {
+            "colormap" => {
+                self.colormap = match value {
+                    "blues" => Colormap::Blues,
+                    "greys" | "grays" => Colormap::Greys,
+                    "reds" => Colormap::Reds,
+                    "viridis" => Colormap::Viridis,
+                    "magma" => Colormap::Magma,
+                    _ => return Err(format!("unknown colormap: {} (expected blues, greys, reds, viridis or magma)", value)),
+                }
+            }
+            "normalize" => {
+                self.normalization = match value {
+                    "none" => Normalization::None,
+                    "frame" => Normalization::PerFrame,
+                    "global" => Normalization::Global,
+                    "log" => Normalization::Log,
+                    _ => return Err(format!("unknown normalization: {} (expected none, frame, global or log)", value)),
+                }
+            }
+            "legend" => {
+                self.legend = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("expected 'on' or 'off' for style legend, found {}", value)),
+                }
+            }
+            "invisible-edges" => {
+                self.invisible_edges = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("expected 'on' or 'off' for style invisible-edges, found {}", value)),
+                }
+            }
+            "ghost-edges" => {
+                self.ghost_edges = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("expected 'on' or 'off' for style ghost-edges, found {}", value)),
+                }
+            }
+            "rank-deltas" => {
+                self.rank_deltas = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("expected 'on' or 'off' for style rank-deltas, found {}", value)),
+                }
+            }
+            "frame-stats" => {
+                self.frame_stats = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("expected 'on' or 'off' for style frame-stats, found {}", value)),
+                }
+            }
+            "alarm-banner" => {
+                self.alarm_banner = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("expected 'on' or 'off' for style alarm-banner, found {}", value)),
+                }
+            }
+            "edge-tooltips" => {
+                self.edge_tooltips = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("expected 'on' or 'off' for style edge-tooltips, found {}", value)),
+                }
+            }
+            "edge-flux" => {
+                self.edge_flux = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("expected 'on' or 'off' for style edge-flux, found {}", value)),
+                }
+            }
+            "layout" => {
+                self.auto_layout = match value {
+                    "fixed" => false,
+                    "auto" => true,
+                    _ => return Err(format!("expected 'fixed' or 'auto' for style layout, found {}", value)),
+                }
+            }
+            "edge-sampling" => self.edge_sampling = if value == "none" { None } else { Some(EdgeSampling::parse(value)?) },
+            "cluster" => self.cluster = if value == "none" { None } else { Some(ClusterBy::parse(value)?) },
+            "node-size-scale" => self.node_size_scale = number(value)?,
+            "expert-color" => self.expert_color = color(value),
+            "expert-penwidth" => self.expert_pen_width = number(value)?,
+            "edge-width" => {
+                self.edge_width = match value {
+                    "linear" => EdgeWidth::Linear,
+                    "sqrt" => EdgeWidth::Sqrt,
+                    "log" => EdgeWidth::Log,
+                    _ => return Err(format!("unknown edge width function: {} (expected linear, sqrt or log)", value)),
+                }
+            }
+            "edge-width-scale" => self.edge_width_scale = number(value)?,
+            "background" => self.background = Some(color(value)),
+            "font-size" => self.font_size = number(value)?,
+            "title-font-size" => self.title_font_size = number(value)?,
+            "label" => {
+                self.label_format = match value {
+                    "id-rank" => LabelFormat::IdAndRank,
+                    "id" => LabelFormat::Id,
+                    "rank" => LabelFormat::Rank,
+                    "none" => LabelFormat::None,
+                    _ => return Err(format!("unknown label format: {} (expected id-rank, id, rank or none)", value)),
+                }
+            }
+            "label-precision" => {
+                self.label_precision = value.parse().map_err(|_| format!("invalid label precision: {}", value))?
+            }
+            _ => return Err(format!("unknown style setting: {}", key)),
+        }
+        Ok(())
+    }
+
+    pub fn node_label(&self, node: impl std::fmt::Display, rank: f64) -> String {
+        let precision = self.label_precision;
+        match self.label_format {
+            LabelFormat::IdAndRank => format!("{} ({:.*})", node, precision, rank),
+            LabelFormat::Id => node.to_string(),
+            LabelFormat::Rank => format!("{:.*}", precision, rank),
+            LabelFormat::None => String::new(),
+        }
+    }
+
+    // Extra node attributes (leading ", ") sizing the node by its rank, if enabled
+    pub fn node_size(&self, rank: f64) -> String {
+        self.node_width(rank).map_or(String::new(), |width| format!(", width={:.2}, fixedsize=true", width))
+    }
+
+    // Node width in inches by rank, or None for Graphviz's default of 0.75
+    pub fn node_width(&self, rank: f64) -> Option<f64> {
+        (self.node_size_scale > 0.0).then(|| 0.75 + self.node_size_scale * rank.clamp(0.0, 1.0))
+    }
+
+    // Graph-level attributes shared by all frame kinds
+    // External label (leading ", ") with a green up or red down arrow and the rank change, unless
+    // the change rounds to 0 at the label precision
+    pub fn rank_delta(&self, delta: f64) -> String {
+        let precision = self.label_precision;
+        if delta.abs() < 0.5 * 10f64.powi(-(precision as i32)) {
+            return String::new();
+        }
+        let (arrow, color) = if delta > 0.0 { ("▲", RISE_COLOR) } else { ("▼", FALL_COLOR) };
+        format!(", xlabel=<<FONT COLOR=\"{}\">{} {:+.*}</FONT>>", color, arrow, precision, delta)
+    }
+
+    // Tooltip attribute (leading ", ") of an edge, none for an empty text
+    pub fn edge_tooltip(&self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        format!(", tooltip=\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    // Color and label attributes (leading ", ") of an edge carrying `flux`, from a faint to an opaque
+    // FLUX_COLOR at the frame's largest flux; none for edges without flux
+    pub fn edge_flux(&self, flux: f64, max_flux: f64) -> String {
+        if flux <= 0.0 || max_flux <= 0.0 {
+            return String::new();
+        }
+        let alpha = FLUX_MIN_ALPHA + ((255.0 - FLUX_MIN_ALPHA) * (flux / max_flux).min(1.0)).round();
+        format!(", color=\"{}{:02x}\", label=\"{:.*}\", fontsize={}", FLUX_COLOR, alpha as u8, self.label_precision + 1, flux, self.font_size * 0.6)
+    }
+
+    // Style of thumbnail frames for scrubbing through a run: node names without ranks, no legend,
+    // arrows, tooltips, flux labels or statistics, and half-size fonts on a canvas of at most `size` inches
+    pub fn thumbnail(&self, size: f64) -> RenderStyle {
+        RenderStyle {
+            legend: false,
+            font_size: self.font_size / 2.0,
+            title_font_size: self.title_font_size / 2.0,
+            label_format: if self.label_format == LabelFormat::None { LabelFormat::None } else { LabelFormat::Id },
+            rank_deltas: false,
+            edge_tooltips: false,
+            edge_flux: false,
+            frame_stats: false,
+            canvas_size: Some(size),
+            ..self.clone()
+        }
+    }
+
+    pub fn graph_attributes(&self) -> String {
+        let background = match &self.background {
+            Some(color) => format!(", bgcolor=\"{}\"", color),
+            None => String::new(),
+        };
+        let background = match self.canvas_size {
+            Some(size) => format!("{}, size=\"{},{}\"", background, size, size),
+            None => background,
+        };
+        // neato does not draw clusters; fdp does and also keeps the pinned positions
+        let layout = if self.cluster.is_some() { "fdp" } else { "neato" };
+        // Rank deltas are external labels, which Graphviz would otherwise drop where they overlap
+        let forcelabels = if self.rank_deltas { ", forcelabels=true" } else { "" };
+        format!("layout={}, overlap=false, splines=true, pad=\"1.0,1.0\", fontsize={}{}{}", layout, self.font_size, background, forcelabels)
+    }
+}
+
+// Graphviz color name, or a hex RGB value written without the leading '#'
+// (which would start a comment in scenario files)
+fn color(value: &str) -> String {
+    let value = value.trim_matches('"');
+    if value.len() == 6 && value.chars().all(|c| c.is_ascii_hexdigit()) {
+        format!("#{}", value)
+    } else {
+        value.to_string()
+    }
+}
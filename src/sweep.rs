@@ -0,0 +1,126 @@
+use std::io::{self, Write};
+
+use crate::rank::{RankConfig, RankIteration};
+use crate::scenario::Scenario;
+use crate::style::Colormap;
+use crate::types::{NodeId, Time};
+
+// Iterations run until the L1 change drops below SWEEP_TOLERANCE, but at most SWEEP_MAX_ITERATIONS
+pub const SWEEP_TOLERANCE: f64 = 1e-9;
+pub const SWEEP_MAX_ITERATIONS: usize = 1000;
+
+const CELL_WIDTH: f64 = 80.0;
+const CELL_HEIGHT: f64 = 40.0;
+const MARGIN_LEFT: f64 = 90.0;
+const MARGIN_TOP: f64 = 60.0;
+const MARGIN_BOTTOM: f64 = 60.0;
+
+// Damping factors and decay constants to run a scenario with, every combination once
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepGrid {
+    pub damping_factors: Vec<f64>,
+    pub decay_constants: Vec<f64>,
+}
+
+impl Default for SweepGrid {
+    fn default() -> Self {
+        SweepGrid { damping_factors: vec![0.3, 0.5, 0.7, 0.85], decay_constants: vec![0.0, 0.05, 0.1, 0.2] }
+    }
+}
+
+// Result of one combination in the last frame
+#[derive(Debug, Clone)]
+pub struct SweepCell {
+    pub damping_factor: f64,
+    pub decay_constant: f64,
+    pub iterations: usize, // until convergence, SWEEP_MAX_ITERATIONS if it did not converge
+    pub ranks: Vec<f64>,   // of the selected nodes, at convergence
+}
+
+// What a heatmap shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepMetric {
+    Iterations,
+    Rank(usize), // rank of the i-th selected node
+}
+
+impl SweepCell {
+    pub fn value(&self, metric: SweepMetric) -> f64 {
+        match metric {
+            SweepMetric::Iterations => self.iterations as f64,
+            SweepMetric::Rank(i) => self.ranks[i],
+        }
+    }
+}
+
+// Ranks the scenario's frame at `time` with the PageRank variant for every combination of the
+// grid, the other settings taken from `config`. Cells are ordered by decay constant, then damping factor.
+pub fn run_sweep(scenario: &Scenario, config: &RankConfig, time: Time, grid: &SweepGrid, nodes: &[NodeId]) -> Vec<SweepCell> {
+    let teleportation_targets = scenario.teleportation_targets(time);
+    let mut cells = Vec::new();
+    for &decay_constant in &grid.decay_constants {
+        let decayed = Scenario { decay_constant, ..scenario.clone() };
+        let graph = decayed.frame_graph(time, &decayed.edge_weights(time)).unwrap();
+        for &damping_factor in &grid.damping_factors {
+            let config = config.clone().damping_factor(damping_factor).iterations(SWEEP_MAX_ITERATIONS).tolerance(SWEEP_TOLERANCE);
+            let mut iterations = 0;
+            let mut ranks = Vec::new();
+            for step in RankIteration::new(&graph, &teleportation_targets, &config) {
+                iterations = step.iteration;
+                ranks = step.ranks;
+            }
+            let ranks = nodes.iter().map(|node| ranks.get(node.index()).copied().unwrap_or(0.0)).collect();
+            cells.push(SweepCell { damping_factor, decay_constant, iterations, ranks });
+        }
+    }
+    cells
+}
+
+// damping_factor,decay_constant,iterations,<rank of every selected node> rows
+pub fn write_sweep_csv(out: &mut dyn Write, cells: &[SweepCell], labels: &[String]) -> io::Result<()> {
+    let columns: Vec<String> = labels.iter().map(|label| format!("rank_{}", label)).collect();
+    writeln!(out, "damping_factor,decay_constant,iterations,{}", columns.join(","))?;
+    for cell in cells {
+        let ranks: Vec<String> = cell.ranks.iter().map(|r| format!("{:.6}", r)).collect();
+        writeln!(out, "{},{},{},{}", cell.damping_factor, cell.decay_constant, cell.iterations, ranks.join(","))?;
+    }
+    Ok(())
+}
+
+// Heatmap of one metric as a standalone SVG document: damping factors across, decay constants
+// down, colored on the viridis map from the smallest to the largest value and labeled with it
+pub fn write_sweep_heatmap(out: &mut dyn Write, cells: &[SweepCell], grid: &SweepGrid, metric: SweepMetric, title: &str) -> io::Result<()> {
+    let values: Vec<f64> = cells.iter().map(|cell| cell.value(metric)).collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let position = |value: f64| if max > min { (value - min) / (max - min) } else { 0.5 };
+    let width = MARGIN_LEFT + CELL_WIDTH * grid.damping_factors.len() as f64 + 20.0;
+    let height = MARGIN_TOP + CELL_HEIGHT * grid.decay_constants.len() as f64 + MARGIN_BOTTOM;
+
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\">", width, height)?;
+    writeln!(out, "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+    writeln!(out, "  <text x=\"{}\" y=\"30\" font-size=\"16\">{}</text>", 10.0, title.replace('&', "&amp;").replace('<', "&lt;"))?;
+    for (row, &decay_constant) in grid.decay_constants.iter().enumerate() {
+        let y = MARGIN_TOP + CELL_HEIGHT * row as f64;
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" text-anchor=\"end\">{}</text>", MARGIN_LEFT - 8.0, y + CELL_HEIGHT / 2.0 + 4.0, decay_constant)?;
+        for (column, &damping_factor) in grid.damping_factors.iter().enumerate() {
+            let Some(cell) = cells.iter().find(|c| c.damping_factor == damping_factor && c.decay_constant == decay_constant) else { continue };
+            let x = MARGIN_LEFT + CELL_WIDTH * column as f64;
+            let p = position(cell.value(metric));
+            let text_color = if Colormap::Viridis.is_dark(p) { "white" } else { "black" };
+            let label = match metric {
+                SweepMetric::Iterations => format!("{}", cell.iterations),
+                SweepMetric::Rank(_) => format!("{:.3}", cell.value(metric)),
+            };
+            writeln!(out, "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>", x, y, CELL_WIDTH, CELL_HEIGHT, Colormap::Viridis.color(p))?;
+            writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" text-anchor=\"middle\" fill=\"{}\">{}</text>", x + CELL_WIDTH / 2.0, y + CELL_HEIGHT / 2.0 + 4.0, text_color, label)?;
+        }
+    }
+    let bottom = MARGIN_TOP + CELL_HEIGHT * grid.decay_constants.len() as f64;
+    for (column, &damping_factor) in grid.damping_factors.iter().enumerate() {
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" text-anchor=\"middle\">{}</text>", MARGIN_LEFT + CELL_WIDTH * (column as f64 + 0.5), bottom + 18.0, damping_factor)?;
+    }
+    writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"14\" text-anchor=\"middle\">Damping factor</text>", MARGIN_LEFT + CELL_WIDTH * grid.damping_factors.len() as f64 / 2.0, bottom + 42.0)?;
+    writeln!(out, "  <text x=\"16\" y=\"{0:.1}\" font-size=\"14\" text-anchor=\"middle\" transform=\"rotate(-90 16 {0:.1})\">Decay constant</text>", MARGIN_TOP + CELL_HEIGHT * grid.decay_constants.len() as f64 / 2.0)?;
+    writeln!(out, "</svg>")
+}
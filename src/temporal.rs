@@ -0,0 +1,73 @@
+use crate::graph::Edge;
+use crate::types::{NodeId, Time};
+
+// Time-respecting paths: an endorsement can only pass on trust its source received before it
+// was made, so a path must use edges in order of non-decreasing creation time. Every edge counts
+// as a contact at its creation time; edges created after `until` do not count.
+
+// Earliest time at which each node can be reached from the `origins` along time-respecting
+// paths, None for nodes that cannot be reached by `until`. Origins are reached from the start.
+pub fn earliest_arrival(edges: &[Edge], num_of_nodes: usize, origins: &[NodeId], until: Time) -> Vec<Option<Time>> {
+    let mut arrival = vec![None; num_of_nodes];
+    for &origin in origins {
+        arrival[origin.index()] = Some(Time(f64::NEG_INFINITY));
+    }
+    for group in same_time_groups(edges, until) {
+        // Edges of the same time chain with each other, so a group is swept until nothing changes
+        loop {
+            let mut changed = false;
+            for edge in &group {
+                let reached = arrival[edge.source.index()].is_some_and(|t: Time| t <= edge.time_of_creation);
+                if reached && arrival[edge.target.index()].is_none() {
+                    arrival[edge.target.index()] = Some(edge.time_of_creation);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+    arrival
+}
+
+// Latest time at which each node can start a time-respecting path that reaches `node` by
+// `until`, None for nodes that could not have influenced it; `node` itself gets `until`
+pub fn latest_departure(edges: &[Edge], num_of_nodes: usize, node: NodeId, until: Time) -> Vec<Option<Time>> {
+    let mut departure = vec![None; num_of_nodes];
+    departure[node.index()] = Some(until);
+    for group in same_time_groups(edges, until).iter().rev() {
+        loop {
+            let mut changed = false;
+            for edge in group {
+                let leads_on = departure[edge.target.index()].is_some_and(|t: Time| edge.time_of_creation <= t);
+                if leads_on && departure[edge.source.index()].is_none() {
+                    departure[edge.source.index()] = Some(edge.time_of_creation);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+    departure
+}
+
+// Edges created by `until`, grouped by creation time in increasing order. Since groups are
+// visited in order, the first time a node is reached is its earliest (or latest) one.
+fn same_time_groups(edges: &[Edge], until: Time) -> Vec<Vec<&Edge>> {
+    let mut sorted: Vec<&Edge> = edges.iter().filter(|e| e.time_of_creation <= until).collect();
+    sorted.sort_by(|a, b| a.time_of_creation.value().total_cmp(&b.time_of_creation.value()));
+    sorted.chunk_by(|a, b| a.time_of_creation == b.time_of_creation).map(|group| group.to_vec()).collect()
+}
+
+// Which edges lie on a time-respecting path from the seeds: an edge counts once its source has
+// been reached by the time the edge was created, seeds being reached from the start
+pub fn time_respecting_edges(edges: &[Edge], num_of_nodes: usize, seeds: &[NodeId], until: Time) -> Vec<bool> {
+    let arrival = earliest_arrival(edges, num_of_nodes, seeds, until);
+    edges
+        .iter()
+        .map(|e| e.time_of_creation <= until && arrival[e.source.index()].is_some_and(|t| t <= e.time_of_creation))
+        .collect()
+}
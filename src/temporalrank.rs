@@ -0,0 +1,85 @@
+use crate::graph::{CompiledGraph, Edge};
+use crate::rank::RankingAlgorithm;
+use crate::registry::TEMPORAL_PAGERANK;
+use crate::types::Time;
+
+// Temporal PageRank (Rozenshtein and Gionis): instead of ranking a snapshot of decayed weights,
+// the interactions of the frame are replayed in time order, and only walks that follow them in
+// that order (time-respecting walks) carry rank. Every interaction u → v starts a walk at u and
+// extends the walks that have arrived at u so far, all of which count towards v's rank; a share
+// 1 - β of them moves on to v and the rest waits at u for a later interaction, which is the
+// decay within a walk (Algorithm 1 of the paper):
+//   r_u += (1 - α) h_u,  s_u += (1 - α) h_u,  r_v += α s_u,  s_v += α (1 - β) s_u,  s_u *= β
+// With β = 1 the walks all move on instead (s_v += α s_u, s_u = 0). r is the rank, s the walks
+// waiting at each node, and h_u n times u's share of the teleportation targets, 1 for uniform
+// targets, which gives the published algorithm; with experts, walks only start at them.
+pub struct TemporalPageRank {
+    pub damping_factor: f64, // α, the probability of a walk continuing at its next interaction
+    pub in_walk_decay: f64,  // β, the share of the walks at a node that waits for a later interaction out of it
+    pub creation_times: Option<Vec<Time>>, // indexed by scenario edge; when not given, interactions are replayed in scenario edge order
+}
+
+impl Default for TemporalPageRank {
+    fn default() -> Self {
+        TemporalPageRank { damping_factor: 0.85, in_walk_decay: 0.5, creation_times: None }
+    }
+}
+
+impl TemporalPageRank {
+    // Replays the interactions by the creation times of the scenario's edges
+    pub fn with_stream(edges: &[Edge]) -> Self {
+        TemporalPageRank { creation_times: Some(edges.iter().map(|e| e.time_of_creation).collect()), ..TemporalPageRank::default() }
+    }
+
+    // The frame's interactions in time order: one (source, target) per scenario edge behind a
+    // compiled edge of positive weight, ties broken by scenario edge order
+    pub fn interactions(&self, graph: &CompiledGraph) -> Vec<(usize, usize)> {
+        let mut interactions: Vec<(Time, usize, usize, usize)> = Vec::new();
+        for edge in graph.edges.iter().filter(|e| e.weight > 0.0 && e.source != e.target) {
+            for id in &edge.sources {
+                let time = self.creation_times.as_ref().and_then(|times| times.get(id.0).copied()).unwrap_or(Time(0.0));
+                interactions.push((time, id.0, edge.source.index(), edge.target.index()));
+            }
+        }
+        interactions.sort_by(|a, b| a.0.value().total_cmp(&b.0.value()).then(a.1.cmp(&b.1)));
+        interactions.into_iter().map(|(_, _, source, target)| (source, target)).collect()
+    }
+}
+
+impl RankingAlgorithm for TemporalPageRank {
+    fn name(&self) -> &str {
+        TEMPORAL_PAGERANK.name
+    }
+
+    fn rank(&self, graph: &CompiledGraph, teleportation_targets: &[f64]) -> Vec<f64> {
+        let num_of_nodes = graph.num_of_nodes;
+        let total_target: f64 = teleportation_targets.iter().sum();
+        let start: Vec<f64> = teleportation_targets.iter().map(|t| if total_target > 0.0 { t / total_target * num_of_nodes as f64 } else { 1.0 }).collect();
+        let (alpha, beta) = (self.damping_factor, self.in_walk_decay);
+        let mut rank_values = vec![0.0; num_of_nodes];
+        let mut waiting = vec![0.0; num_of_nodes];
+        for (source, target) in self.interactions(graph) {
+            rank_values[source] += (1.0 - alpha) * start[source];
+            waiting[source] += (1.0 - alpha) * start[source];
+            rank_values[target] += alpha * waiting[source];
+            if beta < 1.0 {
+                waiting[target] += alpha * (1.0 - beta) * waiting[source];
+                waiting[source] *= beta;
+            } else {
+                waiting[target] += alpha * waiting[source];
+                waiting[source] = 0.0;
+            }
+        }
+        let total: f64 = rank_values.iter().sum();
+        if total > 0.0 {
+            rank_values.iter().map(|r| r / total).collect()
+        } else {
+            // Without interactions, all the rank stays with the walks' starting points
+            start.iter().map(|s| s / num_of_nodes as f64).collect()
+        }
+    }
+
+    fn with_damping(&self, damping_factor: f64) -> Option<Box<dyn RankingAlgorithm>> {
+        Some(Box::new(TemporalPageRank { damping_factor, in_walk_decay: self.in_walk_decay, creation_times: self.creation_times.clone() }))
+    }
+}
@@ -0,0 +1,98 @@
+use std::io::{self, Write};
+
+use crate::history::RankHistory;
+use crate::json::Json;
+use crate::scaling::ScoreScaling;
+use crate::types::FrameIdx;
+
+// Trust tier of a node in one frame, for policy decisions downstream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tier {
+    Trusted,
+    Neutral,
+    Suspect,
+}
+
+impl Tier {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tier::Trusted => "trusted",
+            Tier::Neutral => "neutral",
+            Tier::Suspect => "suspect",
+        }
+    }
+
+    // Border of the node in rendered frames; neutral nodes keep the default border
+    pub fn border(&self) -> Option<&'static str> {
+        match self {
+            Tier::Trusted => Some("green3"),
+            Tier::Neutral => None,
+            Tier::Suspect => Some("red3"),
+        }
+    }
+}
+
+// How scores map to tiers: suspect below the lower bound, trusted at or above the upper one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TierThresholds {
+    Scores { suspect_below: f64, trusted_from: f64 },    // fixed bounds on the scores
+    Quantiles { suspect_below: f64, trusted_from: f64 }, // bounds on each frame's percentile ranks, 0 to 1
+}
+
+impl TierThresholds {
+    // Parses "LOWER,UPPER"
+    pub fn parse(text: &str, quantiles: bool) -> Result<Self, String> {
+        let bounds: Vec<f64> = text.split(',').map(|b| b.trim().parse().map_err(|_| format!("invalid tier bound: {}", b))).collect::<Result<_, _>>()?;
+        let [suspect_below, trusted_from] = bounds[..] else {
+            return Err(format!("expected two tier bounds, LOWER,UPPER: {}", text));
+        };
+        if suspect_below > trusted_from {
+            return Err(format!("lower tier bound {} is above the upper one {}", suspect_below, trusted_from));
+        }
+        if quantiles {
+            if !(0.0..=1.0).contains(&suspect_below) || !(0.0..=1.0).contains(&trusted_from) {
+                return Err(format!("tier quantiles must be between 0 and 1: {}", text));
+            }
+            Ok(TierThresholds::Quantiles { suspect_below, trusted_from })
+        } else {
+            Ok(TierThresholds::Scores { suspect_below, trusted_from })
+        }
+    }
+
+    // Tiers of one frame's scores
+    pub fn classify(&self, scores: &[f64]) -> Vec<Tier> {
+        let (values, suspect_below, trusted_from) = match *self {
+            TierThresholds::Scores { suspect_below, trusted_from } => (scores.to_vec(), suspect_below, trusted_from),
+            TierThresholds::Quantiles { suspect_below, trusted_from } => (ScoreScaling::Percentile.scale(scores), suspect_below, trusted_from),
+        };
+        values
+            .iter()
+            .map(|&v| {
+                if v >= trusted_from {
+                    Tier::Trusted
+                } else if v < suspect_below {
+                    Tier::Suspect
+                } else {
+                    Tier::Neutral
+                }
+            })
+            .collect()
+    }
+}
+
+// One JSON line per frame with every node's tier:
+// {"frame":1,"time":0,"tiers":[{"node":0,"label":"0","score":0.5,"tier":"trusted"}]}
+pub fn write_tiers(out: &mut dyn Write, history: &RankHistory, thresholds: &TierThresholds, labels: &[String]) -> io::Result<()> {
+    for (frame, (time, scores)) in history.times().iter().zip(history.frames()).enumerate() {
+        let tiers = thresholds
+            .classify(scores)
+            .iter()
+            .zip(scores)
+            .enumerate()
+            .map(|(node, (tier, &score))| Json::object(vec![("node", node.into()), ("label", labels[node].as_str().into()), ("score", score.into()), ("tier", tier.name().into())]))
+            .collect();
+        let line = Json::object(vec![("frame", FrameIdx(frame).number().into()), ("time", time.value().into()), ("tiers", Json::Array(tiers))]);
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use std::io::{self, Write};
+
+use crate::export::{ExportedRun, Exporter};
+use crate::sink::Sink;
+use crate::style::ColorScale;
+use crate::types::{FrameIdx, NodeId};
+
+// Graphviz color names that LaTeX's xcolor does not know, as HTML colors
+const X11_COLORS: [(&str, &str); 9] = [
+    ("darkgreen", "006400"), ("darkred", "8B0000"), ("darkblue", "00008B"), ("navy", "000080"), ("gold", "FFD700"),
+    ("forestgreen", "228B22"), ("firebrick", "B22222"), ("crimson", "DC143C"), ("grey", "808080"),
+];
+
+// Selected frames as standalone TikZ figures for papers: <scenario>/frame_NNN.tex, named like the
+// DOT frames, with the positions, fill colors, labels, expert borders and edge widths of the DOT
+// frames. The title goes into comments, to be used as the caption. All frames when none are
+// selected.
+#[derive(Debug, Clone, Default)]
+pub struct TikzExporter {
+    pub frames: Vec<FrameIdx>,
+}
+
+impl Exporter for TikzExporter {
+    fn format(&self) -> &str {
+        "tikz"
+    }
+
+    fn export(&self, sink: &mut dyn Sink, run: &ExportedRun) -> io::Result<()> {
+        let num_of_frames = run.output.history.num_of_frames();
+        let frames: Vec<FrameIdx> = if self.frames.is_empty() { (0..num_of_frames).map(FrameIdx).collect() } else { self.frames.clone() };
+        for frame in frames {
+            if frame.index() >= num_of_frames {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("frame {} selected, but the run has {} frames", frame.number(), num_of_frames)));
+            }
+            let mut tex = Vec::new();
+            write_tikz(&mut tex, run, frame)?;
+            let time = run.output.history.times()[frame.index()];
+            sink.write(&format!("{}/{}", run.scenario.name, run.scenario.frame_file(frame, time, num_of_frames, "tex")), &tex)?;
+        }
+        Ok(())
+    }
+}
+
+// One frame as a LaTeX document of the standalone class; layout units are inches, as in the DOT
+// frames, and labels use the document's font
+pub fn write_tikz(out: &mut dyn Write, run: &ExportedRun, frame: FrameIdx) -> io::Result<()> {
+    let (scenario, history, style) = (run.scenario, &run.output.history, &run.scenario.style);
+    let ranks = history.ranks_at(frame);
+    let weights = &run.output.edge_weights[frame.index()];
+    let time = history.times()[frame.index()];
+    let scale = ColorScale::new(style, ranks, history.frames());
+    let (experts, active, names, positions) = (scenario.experts_at(time), scenario.active_nodes(time), scenario.node_labels(), scenario.layout());
+
+    writeln!(out, "% Trust flow over time")?;
+    writeln!(out, "% Algorithm: {}", run.algorithm)?;
+    writeln!(out, "% Edge decay: {}", scenario.decay_description())?;
+    writeln!(out, "% Frame: {}/{} (time {})", frame.number(), history.num_of_frames(), time)?;
+    for annotation in scenario.annotations_at(time) {
+        writeln!(out, "% {}", annotation)?;
+    }
+    writeln!(out, "\\documentclass[tikz, border=2pt]{{standalone}}")?;
+    writeln!(out, "\\usetikzlibrary{{arrows.meta}}")?;
+    writeln!(out, "\\begin{{document}}")?;
+    writeln!(out, "\\begin{{tikzpicture}}[x=1in, y=1in, >={{Stealth}}]")?;
+    let expert_color = color_name(out, &style.expert_color, "expert")?;
+    for (i, &rank) in ranks.iter().enumerate() {
+        let (x, y) = positions[i];
+        // Nodes out of the graph keep their place for edges, like invisible DOT nodes
+        if active.get(i) == Some(&false) {
+            writeln!(out, "  \\coordinate (n{}) at ({:.2}, {:.2});", i, x, y)?;
+            continue;
+        }
+        writeln!(out, "  \\definecolor{{rank{}}}{{HTML}}{{{}}}", i, scale.color(rank).trim_start_matches('#'))?;
+        let border = if experts.contains(&NodeId(i)) {
+            format!("draw={}, line width={}pt", expert_color, style.expert_pen_width)
+        } else {
+            "draw=black".to_string()
+        };
+        let text = if scale.is_dark(rank) { ", text=white" } else { "" };
+        writeln!(out,
+            "  \\node[circle, fill=rank{}, {}{}, minimum size={:.2}in] (n{}) at ({:.2}, {:.2}) {{{}}};",
+            i, border, text, style.node_width(rank).unwrap_or(0.75), i, x, y, escape(&style.node_label(&names[i], rank))
+        )?;
+    }
+
+    // Edges without weight or left out by the style's edge sampling are not drawn
+    let kept = style.edge_sampling.map_or(Vec::new(), |sampling| sampling.kept(ranks.len(), &scenario.edges, weights));
+    for (id, (e, &w)) in scenario.edges.iter().zip(weights.iter()).enumerate() {
+        if w == 0.0 || kept.get(id) == Some(&false) {
+            continue;
+        }
+        let width = style.edge_width.width(w, style.edge_width_scale);
+        let path = if e.source == e.target { " edge[loop above]" } else { " --" };
+        writeln!(out, "  \\draw[->, line width={:.2}pt] (n{}){} (n{});", width, e.source.index(), path, e.target.index())?;
+    }
+    writeln!(out, "\\end{{tikzpicture}}")?;
+    writeln!(out, "\\end{{document}}")
+}
+
+// Name of a Graphviz color in the figure, defining it first unless xcolor knows it
+fn color_name(out: &mut dyn Write, color: &str, name: &str) -> io::Result<String> {
+    let hex = match color.strip_prefix('#') {
+        Some(hex) => Some(hex),
+        None => X11_COLORS.iter().find(|(x11, _)| color.eq_ignore_ascii_case(x11)).map(|(_, hex)| *hex),
+    };
+    match hex {
+        Some(hex) => {
+            writeln!(out, "  \\definecolor{{{}}}{{HTML}}{{{}}}", name, hex)?;
+            Ok(name.to_string())
+        }
+        None => Ok(color.to_string()),
+    }
+}
+
+// Node names are free text; LaTeX's special characters are written as text
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,126 @@
+use crate::graph::{compile_graph, exponential_decay, CompiledGraph, Edge, GraphError, GraphOptions};
+use crate::types::{EdgeId, NodeId, Time};
+
+// Change of one edge at a point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeEventKind {
+    Added,           // the edge is created with its initial weight
+    Reweighted(f64), // the edge's weight is set anew and decays from then on
+    Removed,         // the edge weighs 0 from then on, until it is reweighted
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphEvent {
+    pub time: Time,
+    pub edge: EdgeId,
+    pub kind: EdgeEventKind,
+}
+
+// Temporal graph kept as an append-only log of edge events, which may arrive out of time order.
+// Every edge also keeps its own changes sorted by time, so the graph at any time takes one
+// binary search per edge instead of a replay of the log. Events of the same time apply in the
+// order they were appended.
+#[derive(Debug, Clone, Default)]
+pub struct TemporalGraph {
+    num_of_nodes: usize,
+    endpoints: Vec<(NodeId, NodeId)>,      // indexed by EdgeId
+    changes: Vec<Vec<(Time, Option<f64>)>>, // per edge, the weight set at each time; None when removed
+    log: Vec<GraphEvent>,
+}
+
+impl TemporalGraph {
+    pub fn new(num_of_nodes: usize) -> Self {
+        TemporalGraph { num_of_nodes, ..Self::default() }
+    }
+
+    // Every edge is added at its creation time, with the same EdgeId
+    pub fn from_edges(num_of_nodes: usize, edges: &[Edge]) -> Self {
+        let mut graph = TemporalGraph::new(num_of_nodes);
+        for edge in edges {
+            graph.add_edge(edge.source, edge.target, edge.time_of_creation, edge.weight);
+        }
+        graph
+    }
+
+    pub fn num_of_nodes(&self) -> usize {
+        self.num_of_nodes
+    }
+
+    pub fn num_of_edges(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    // The log in the order the events were appended
+    pub fn events(&self) -> &[GraphEvent] {
+        &self.log
+    }
+
+    // Adds a new edge; nodes beyond the graph's current size are added with it
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, time: Time, weight: f64) -> EdgeId {
+        let edge = EdgeId(self.endpoints.len());
+        self.num_of_nodes = self.num_of_nodes.max(source.index() + 1).max(target.index() + 1);
+        self.endpoints.push((source, target));
+        self.changes.push(Vec::new());
+        self.record(GraphEvent { time, edge, kind: EdgeEventKind::Added }, Some(weight));
+        edge
+    }
+
+    pub fn reweight_edge(&mut self, edge: EdgeId, time: Time, weight: f64) -> Result<(), GraphError> {
+        self.check(edge)?;
+        self.record(GraphEvent { time, edge, kind: EdgeEventKind::Reweighted(weight) }, Some(weight));
+        Ok(())
+    }
+
+    pub fn remove_edge(&mut self, edge: EdgeId, time: Time) -> Result<(), GraphError> {
+        self.check(edge)?;
+        self.record(GraphEvent { time, edge, kind: EdgeEventKind::Removed }, None);
+        Ok(())
+    }
+
+    // Weight the edge was last set to by the given time and when, None if it does not exist then
+    pub fn weight_set_at(&self, edge: EdgeId, time: Time) -> Option<(Time, f64)> {
+        let changes = &self.changes[edge.index()];
+        let current = changes.partition_point(|&(t, _)| t <= time).checked_sub(1)?;
+        let (set_at, weight) = changes[current];
+        weight.map(|weight| (set_at, weight))
+    }
+
+    // The edges as they stand at the given time, indexed by EdgeId. An edge carries the weight it
+    // was last set to (its capacity) as of when it was set; an edge that does not exist at that
+    // time carries the weight it had or will first have, as scenario edges created later do.
+    pub fn edges_at(&self, time: Time) -> Vec<Edge> {
+        (0..self.num_of_edges())
+            .map(|i| {
+                let (source, target) = self.endpoints[i];
+                let changes = &self.changes[i];
+                let current = changes.partition_point(|&(t, _)| t <= time);
+                let set = changes[..current].iter().rev().chain(&changes[current..]).find(|(_, weight)| weight.is_some());
+                let (time_of_creation, weight) = set.map_or((changes[0].0, 0.0), |&(t, weight)| (t, weight.unwrap_or(0.0)));
+                Edge { source, target, time_of_creation, weight }
+            })
+            .collect()
+    }
+
+    // Decayed weights at the given time, indexed by EdgeId; 0 for edges that do not exist then
+    pub fn weights_at(&self, time: Time, decay_constant: f64) -> Vec<f64> {
+        (0..self.num_of_edges())
+            .map(|i| self.weight_set_at(EdgeId(i), time).map_or(0.0, |(set_at, weight)| exponential_decay(time, set_at, weight, decay_constant)))
+            .collect()
+    }
+
+    // Graph of the frame at the given time, as the ranking algorithms see it
+    pub fn snapshot_at(&self, time: Time, decay_constant: f64, options: &GraphOptions) -> Result<CompiledGraph, GraphError> {
+        compile_graph(&self.edges_at(time), &self.weights_at(time, decay_constant), self.num_of_nodes, options)
+    }
+
+    fn record(&mut self, event: GraphEvent, weight: Option<f64>) {
+        let changes = &mut self.changes[event.edge.index()];
+        let position = changes.partition_point(|&(t, _)| t <= event.time);
+        changes.insert(position, (event.time, weight));
+        self.log.push(event);
+    }
+
+    fn check(&self, edge: EdgeId) -> Result<(), GraphError> {
+        if edge.index() < self.num_of_edges() { Ok(()) } else { Err(GraphError::UnknownEdge(edge)) }
+    }
+}
@@ -0,0 +1,378 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::graph::Edge;
+use crate::json::Json;
+use crate::rank::{DanglingPolicy, RankConfig};
+use crate::scenario::EXPERT_TELEPORT_FRACTION;
+use crate::types::{NodeId, Time};
+
+// Error bound the tracker keeps its estimates within when not given
+pub const DEFAULT_TOP_K_TOLERANCE: f64 = 1e-4;
+
+// Stored weights are rebased on a later time before their scale grows past e^64
+const MAX_WEIGHT_EXPONENT: f64 = 64.0;
+
+// Approximate ranks of a growing graph, kept up to date edge event by edge event, so that the
+// highest ranked nodes can be read at any time without ranking the whole graph.
+//
+// The ranks are those of the custom variant with current outflow normalization (the fixed point
+// of r = (1 - d) t + d M r) on the graph of every event so far. Exponential decay scales all of a
+// node's edges alike, so it leaves the fractions, and with them the ranks, as they are between
+// events. Every node has an estimate p and a residual; the ranks are p plus the residuals spread
+// through the graph, r = p + G·residual, and every column of G is a distribution, so no estimate
+// is further from its rank than the sum of the residuals' magnitudes. A new edge changes the
+// source's fractions, which the residuals of its successors absorb, and nodes whose residual grows
+// past tolerance / 2n push it: a share 1 - d joins their estimate and the rest moves on to their
+// successors (forward push). Each event thus costs work near the edge rather than a pass over the
+// graph. Rank teleported or dangling evenly over all nodes is held in one uniform residual and only
+// handed to the nodes once it exceeds tolerance / 2.
+//
+// Experts share EXPERT_TELEPORT_FRACTION of the teleported rank by their weights, as in scenarios;
+// node priors, node lifetimes and rank adjustments are not taken into account.
+#[derive(Debug, Clone)]
+pub struct TopKTracker {
+    damping_factor: f64,
+    decay_constant: f64,
+    dangling_policy: DanglingPolicy,
+    tolerance: f64,
+    experts: Vec<(usize, f64)>, // teleported share of each expert
+    uniform_share: f64,         // of the teleported rank, spread evenly over all nodes
+    reference_time: Option<Time>, // stored weights are the weights at this time
+    outgoing: Vec<Vec<(usize, f64)>>, // per node: successors and the weights of the edges to them
+    outflow: Vec<f64>,          // per node: sum of the weights of its edges, 0 for dangling nodes
+    estimates: Vec<f64>,
+    residuals: Vec<f64>,
+    uniform_residual: f64,      // residual of every node on top of its own, times the node count
+    dangling_estimate: f64,     // sum of the estimates of dangling nodes
+    residual_sum: f64,          // sum of the magnitudes of the nodes' own residuals
+    queue: VecDeque<usize>,
+    queued: Vec<bool>,
+    order: BTreeSet<Ranked>,
+    pushes: u64,
+}
+
+// A node in the top K by its estimate, with the interval its rank lies in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopNode {
+    pub node: NodeId,
+    pub rank: f64, // estimate
+    pub lower: f64,
+    pub upper: f64,
+    pub certain: bool, // the bounds leave no node outside the top K room to outrank it
+}
+
+impl TopNode {
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("node", self.node.index().into()),
+            ("rank", self.rank.into()),
+            ("lower", self.lower.into()),
+            ("upper", self.upper.into()),
+            ("certain", self.certain.into()),
+        ])
+    }
+}
+
+// Estimate and node, ordered by estimate and, among equal estimates, by node index from the end
+// so that the lower index comes first when reading from the top
+#[derive(Debug, Clone, Copy)]
+struct Ranked(f64, usize);
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(other.1.cmp(&self.1))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl TopKTracker {
+    // Tracker of a graph without edges yet, over at least `num_of_nodes` nodes and the experts,
+    // which come with their teleport weights
+    pub fn new(config: &RankConfig, decay_constant: f64, experts: &[(NodeId, f64)], num_of_nodes: usize, tolerance: f64) -> Self {
+        assert!((0.0..1.0).contains(&config.damping_factor), "top-K tracking needs a damping factor below 1: {}", config.damping_factor);
+        assert!(tolerance > 0.0, "top-K tolerance must be positive: {}", tolerance);
+        let total_weight: f64 = experts.iter().map(|&(_, weight)| weight).sum();
+        let mut tracker = TopKTracker {
+            damping_factor: config.damping_factor,
+            decay_constant,
+            dangling_policy: config.dangling_policy,
+            tolerance,
+            experts: experts
+                .iter()
+                .map(|&(node, weight)| (node.index(), EXPERT_TELEPORT_FRACTION * if total_weight > 0.0 { weight / total_weight } else { 1.0 / experts.len() as f64 }))
+                .collect(),
+            uniform_share: if experts.is_empty() { 1.0 } else { 1.0 - EXPERT_TELEPORT_FRACTION },
+            reference_time: None,
+            outgoing: Vec::new(),
+            outflow: Vec::new(),
+            estimates: Vec::new(),
+            residuals: Vec::new(),
+            uniform_residual: 0.0,
+            dangling_estimate: 0.0,
+            residual_sum: 0.0,
+            queue: VecDeque::new(),
+            queued: Vec::new(),
+            order: BTreeSet::new(),
+            pushes: 0,
+        };
+        // Before any push, the residuals are the teleportation targets
+        tracker.uniform_residual = tracker.uniform_share;
+        let expert_nodes = experts.iter().map(|&(node, _)| node.index() + 1).max().unwrap_or(0);
+        tracker.ensure_nodes(num_of_nodes.max(expert_nodes));
+        for i in 0..tracker.experts.len() {
+            let (expert, share) = tracker.experts[i];
+            tracker.add_residual(expert, share);
+        }
+        tracker.settle();
+        tracker
+    }
+
+    pub fn num_of_nodes(&self) -> usize {
+        self.estimates.len()
+    }
+
+    // Adds nodes without edges up to the given count
+    pub fn ensure_nodes(&mut self, num_of_nodes: usize) {
+        while self.num_of_nodes() < num_of_nodes {
+            self.add_node();
+        }
+        self.settle();
+    }
+
+    // Adds an edge event and brings the estimates back within the tolerance
+    pub fn add_edge(&mut self, edge: &Edge) {
+        let (source, target) = (edge.source.index(), edge.target.index());
+        self.ensure_nodes(source.max(target) + 1);
+        let weight = self.stored_weight(edge);
+        if !(weight > 0.0 && weight.is_finite()) {
+            return;
+        }
+        self.change_column(source, |tracker| {
+            match tracker.outgoing[source].iter_mut().find(|(successor, _)| *successor == target) {
+                Some((_, total)) => *total += weight,
+                None => tracker.outgoing[source].push((target, weight)),
+            }
+            tracker.outflow[source] += weight;
+        });
+        self.settle();
+    }
+
+    pub fn estimate(&self, node: NodeId) -> f64 {
+        self.estimates[node.index()]
+    }
+
+    pub fn estimates(&self) -> &[f64] {
+        &self.estimates
+    }
+
+    // How far any estimate may be from its rank, at most about the tolerance
+    pub fn error_bound(&self) -> f64 {
+        (self.residual_sum + self.uniform_residual.abs()).max(0.0)
+    }
+
+    // Pushes so far, the work the tracker has done
+    pub fn pushes(&self) -> u64 {
+        self.pushes
+    }
+
+    // The k nodes with the highest estimates, highest first; equal estimates go to the lower index
+    pub fn top(&self, k: usize) -> Vec<TopNode> {
+        let bound = self.error_bound();
+        let mut ranked: Vec<Ranked> = self.order.iter().rev().take(k + 1).copied().collect();
+        let next = if ranked.len() > k { ranked.pop().map(|Ranked(rank, _)| rank) } else { None };
+        ranked
+            .into_iter()
+            .map(|Ranked(rank, node)| TopNode {
+                node: NodeId(node),
+                rank,
+                lower: (rank - bound).max(0.0),
+                upper: rank + bound,
+                certain: next.is_none_or(|next| rank - bound > next + bound),
+            })
+            .collect()
+    }
+
+    // Weight of the edge relative to the reference time, which the first edge sets; the ratio of
+    // any two edges' weights is that of their decayed weights at every later time
+    fn stored_weight(&mut self, edge: &Edge) -> f64 {
+        if self.decay_constant == 0.0 {
+            return edge.weight;
+        }
+        let reference_time = *self.reference_time.get_or_insert(edge.time_of_creation);
+        let mut exponent = self.decay_constant * (edge.time_of_creation.value() - reference_time.value());
+        if exponent > MAX_WEIGHT_EXPONENT {
+            self.rebase(edge.time_of_creation);
+            exponent = 0.0;
+        }
+        edge.weight * exponent.exp()
+    }
+
+    // Scales the stored weights down to the given reference time. Fractions stay as they are,
+    // except for nodes whose edges have all become too light to store, which now dangle.
+    fn rebase(&mut self, time: Time) {
+        let factor = (-self.decay_constant * (time.value() - self.reference_time.map_or(time.value(), Time::value))).exp();
+        self.reference_time = Some(time);
+        for node in 0..self.num_of_nodes() {
+            if self.outflow[node] <= 0.0 {
+                continue;
+            }
+            if self.outflow[node] * factor > 0.0 {
+                for (_, weight) in &mut self.outgoing[node] {
+                    *weight *= factor;
+                }
+                self.outflow[node] = self.outgoing[node].iter().map(|&(_, weight)| weight).sum();
+            } else {
+                self.change_column(node, |tracker| {
+                    tracker.outgoing[node].clear();
+                    tracker.outflow[node] = 0.0;
+                });
+            }
+        }
+    }
+
+    fn add_node(&mut self) {
+        let n = self.num_of_nodes();
+        self.outgoing.push(Vec::new());
+        self.outflow.push(0.0);
+        self.estimates.push(0.0);
+        self.residuals.push(0.0);
+        self.queued.push(false);
+        self.order.insert(Ranked(0.0, n));
+        if n == 0 {
+            return;
+        }
+        // The residuals' uniform part, from the teleportation targets and from rank dangling
+        // evenly, is now spread over one more node. So is the uniform residual, which therefore
+        // differs from that part by less, and the new node takes the difference.
+        let dangling_uniform = match self.dangling_policy {
+            DanglingPolicy::Uniform => 1.0,
+            DanglingPolicy::Teleport => self.uniform_share,
+            DanglingPolicy::Retain => 0.0,
+        };
+        let uniform_part = self.uniform_share + self.carried() * dangling_uniform * self.dangling_estimate;
+        let difference = self.uniform_residual - uniform_part;
+        self.add_residual(n, -difference / n as f64);
+        self.uniform_residual = difference * (n + 1) as f64 / n as f64 + uniform_part;
+    }
+
+    // Share of an estimate its successors' residuals hold for it: d / (1 - d)
+    fn carried(&self) -> f64 {
+        self.damping_factor / (1.0 - self.damping_factor)
+    }
+
+    fn is_dangling(&self, node: usize) -> bool {
+        self.outflow[node] <= 0.0
+    }
+
+    // Changes the node's edges and moves the residuals its estimate holds in its successors to
+    // the new ones
+    fn change_column(&mut self, node: usize, change: impl FnOnce(&mut Self)) {
+        let carried = self.carried() * self.estimates[node];
+        let was_dangling = self.is_dangling(node);
+        self.spread(node, -carried);
+        change(self);
+        match (was_dangling, self.is_dangling(node)) {
+            (true, false) => self.dangling_estimate -= self.estimates[node],
+            (false, true) => self.dangling_estimate += self.estimates[node],
+            _ => {}
+        }
+        self.spread(node, carried);
+    }
+
+    // Adds rank to the residuals of where the node sends its rank: its successors by the weights
+    // of its edges, or where the dangling policy puts it
+    fn spread(&mut self, node: usize, amount: f64) {
+        if amount == 0.0 {
+            return;
+        }
+        if !self.is_dangling(node) {
+            for i in 0..self.outgoing[node].len() {
+                let (successor, weight) = self.outgoing[node][i];
+                self.add_residual(successor, amount * weight / self.outflow[node]);
+            }
+            return;
+        }
+        match self.dangling_policy {
+            DanglingPolicy::Uniform => self.uniform_residual += amount,
+            DanglingPolicy::Teleport => {
+                for i in 0..self.experts.len() {
+                    let (expert, share) = self.experts[i];
+                    self.add_residual(expert, amount * share);
+                }
+                self.uniform_residual += amount * self.uniform_share;
+            }
+            DanglingPolicy::Retain => self.add_residual(node, amount),
+        }
+    }
+
+    fn add_residual(&mut self, node: usize, amount: f64) {
+        let previous = self.residuals[node];
+        self.residuals[node] += amount;
+        self.residual_sum += self.residuals[node].abs() - previous.abs();
+        if self.residuals[node].abs() > self.push_threshold() && !self.queued[node] {
+            self.queued[node] = true;
+            self.queue.push_back(node);
+        }
+    }
+
+    fn push_threshold(&self) -> f64 {
+        self.tolerance / (2 * self.num_of_nodes()) as f64
+    }
+
+    fn push(&mut self, node: usize) {
+        let residual = self.residuals[node];
+        self.residuals[node] = 0.0;
+        self.residual_sum -= residual.abs();
+        let estimate = self.estimates[node] + (1.0 - self.damping_factor) * residual;
+        self.set_estimate(node, estimate);
+        self.spread(node, self.damping_factor * residual);
+        self.pushes += 1;
+    }
+
+    fn set_estimate(&mut self, node: usize, estimate: f64) {
+        self.order.remove(&Ranked(self.estimates[node], node));
+        if self.is_dangling(node) {
+            self.dangling_estimate += estimate - self.estimates[node];
+        }
+        self.estimates[node] = estimate;
+        self.order.insert(Ranked(estimate, node));
+    }
+
+    // Pushes until every residual is below the push threshold and the uniform residual below
+    // half the tolerance
+    fn settle(&mut self) {
+        loop {
+            while let Some(node) = self.queue.pop_front() {
+                self.queued[node] = false;
+                if self.residuals[node].abs() > self.push_threshold() {
+                    self.push(node);
+                }
+            }
+            let n = self.num_of_nodes();
+            if n == 0 || self.uniform_residual.abs() <= self.tolerance / 2.0 {
+                return;
+            }
+            let share = self.uniform_residual / n as f64;
+            self.uniform_residual = 0.0;
+            for node in 0..n {
+                self.add_residual(node, share);
+            }
+            // Summed afresh now and then, against rounding drift
+            self.residual_sum = self.residuals.iter().map(|r| r.abs()).sum();
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::{self, Write};
+
+use crate::rank::RankStep;
+use crate::types::NodeId;
+
+// Human-readable per-iteration tables of how rank (mass) moves, meant for tiny graphs.
+// `previous_ranks` are the ranks the step started from.
+pub fn write_step_table(out: &mut dyn Write, previous_ranks: &[f64], step: &RankStep) -> io::Result<()> {
+    let trace = &step.trace;
+    writeln!(out, "Iteration {} (residual {:.6})", step.iteration, step.residual)?;
+    writeln!(out, "{:>6} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "node", "rank", "edge out", "dangling", "teleport", "edge in", "dangl. in", "events", "new rank")?;
+
+    let mut totals = [0.0; 8];
+    for (i, &rank) in previous_ranks.iter().enumerate() {
+        let row = [
+            rank,
+            trace.edge_outflow[i],
+            trace.dangling_outflow[i],
+            trace.teleport_inflow[i],
+            trace.edge_inflow[i],
+            trace.dangling_inflow[i],
+            trace.event_adjustment[i],
+            step.ranks[i],
+        ];
+        for (total, value) in totals.iter_mut().zip(row.iter()) {
+            *total += value;
+        }
+        write_row(out, &NodeId(i).to_string(), &row)?;
+    }
+    write_row(out, "total", &totals)?;
+    writeln!(out)
+}
+
+// Rank vector after every step as CSV: iteration,residual,<rank of every node>, starting with
+// iteration 0, the ranks the iteration starts from, which has no residual
+pub fn write_iterations_csv(out: &mut dyn Write, initial_ranks: &[f64], steps: &[RankStep], labels: &[String]) -> io::Result<()> {
+    let columns: Vec<String> = labels.iter().map(|label| format!("rank_{}", label)).collect();
+    writeln!(out, "iteration,residual,{}", columns.join(","))?;
+    let ranks: Vec<String> = initial_ranks.iter().map(|r| format!("{:.6}", r)).collect();
+    writeln!(out, "0,,{}", ranks.join(","))?;
+    for step in steps {
+        let ranks: Vec<String> = step.ranks.iter().map(|r| format!("{:.6}", r)).collect();
+        writeln!(out, "{},{:.6e},{}", step.iteration, step.residual, ranks.join(","))?;
+    }
+    Ok(())
+}
+
+fn write_row(out: &mut dyn Write, name: &str, values: &[f64; 8]) -> io::Result<()> {
+    write!(out, "{:>6}", name)?;
+    for value in values {
+        write!(out, " {:>10.6}", value)?;
+    }
+    writeln!(out)
+}
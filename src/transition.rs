@@ -0,0 +1,80 @@
+use std::io::{self, Write};
+
+use crate::graph::CompiledGraph;
+use crate::rank::{transition_triplets, RankConfig};
+use crate::types::{FrameIdx, Time};
+
+// A frame's ranking problem as plain matrices, for checking the ranks against reference
+// implementations such as networkx or igraph: the effective transition matrix P (see
+// transition_triplets), the teleportation vector and the damping factor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionMatrix {
+    pub time: Time,
+    pub num_of_nodes: usize,
+    pub damping_factor: f64,
+    pub entries: Vec<(usize, usize, f64)>, // (source, target, probability) in row order
+    pub teleportation: Vec<f64>,
+}
+
+impl TransitionMatrix {
+    pub fn of_frame(time: Time, graph: &CompiledGraph, teleportation_targets: &[f64], config: &RankConfig) -> Self {
+        TransitionMatrix {
+            time,
+            num_of_nodes: graph.num_of_nodes,
+            damping_factor: config.damping_factor,
+            entries: transition_triplets(graph, teleportation_targets, config),
+            teleportation: teleportation_targets.to_vec(),
+        }
+    }
+}
+
+// frame,time,damping,source,target,probability rows (nodes by index) of every frame's transition matrix,
+// with full precision
+pub fn write_transition_csv(out: &mut dyn Write, matrices: &[TransitionMatrix]) -> io::Result<()> {
+    writeln!(out, "frame,time,damping,source,target,probability")?;
+    for (frame, matrix) in matrices.iter().enumerate() {
+        for &(source, target, probability) in &matrix.entries {
+            writeln!(out, "{},{},{},{},{},{}", FrameIdx(frame).number(), matrix.time, matrix.damping_factor, source, target, probability)?;
+        }
+    }
+    Ok(())
+}
+
+// frame,time,node,probability rows of every frame's teleportation vector
+pub fn write_teleport_csv(out: &mut dyn Write, matrices: &[TransitionMatrix]) -> io::Result<()> {
+    writeln!(out, "frame,time,node,probability")?;
+    for (frame, matrix) in matrices.iter().enumerate() {
+        for (node, probability) in matrix.teleportation.iter().enumerate() {
+            writeln!(out, "{},{},{},{}", FrameIdx(frame).number(), matrix.time, node, probability)?;
+        }
+    }
+    Ok(())
+}
+
+// The transition matrix in Matrix Market coordinate format (scipy.io.mmread, igraph), rows by
+// source; indices start at 1 there, so node i is row and column i + 1
+pub fn write_transition_mtx(out: &mut dyn Write, matrix: &TransitionMatrix) -> io::Result<()> {
+    writeln!(out, "%%MatrixMarket matrix coordinate real general")?;
+    write_mtx_comments(out, matrix)?;
+    writeln!(out, "{} {} {}", matrix.num_of_nodes, matrix.num_of_nodes, matrix.entries.len())?;
+    for &(source, target, probability) in &matrix.entries {
+        writeln!(out, "{} {} {}", source + 1, target + 1, probability)?;
+    }
+    Ok(())
+}
+
+// The teleportation vector in Matrix Market array format, as a column
+pub fn write_teleport_mtx(out: &mut dyn Write, matrix: &TransitionMatrix) -> io::Result<()> {
+    writeln!(out, "%%MatrixMarket matrix array real general")?;
+    write_mtx_comments(out, matrix)?;
+    writeln!(out, "{} 1", matrix.num_of_nodes)?;
+    for probability in &matrix.teleportation {
+        writeln!(out, "{}", probability)?;
+    }
+    Ok(())
+}
+
+fn write_mtx_comments(out: &mut dyn Write, matrix: &TransitionMatrix) -> io::Result<()> {
+    writeln!(out, "% trust-flow frame at time {}, damping {}", matrix.time, matrix.damping_factor)?;
+    writeln!(out, "% ranks = (1 - damping) * teleport + damping * transpose(transition) * ranks")
+}
@@ -0,0 +1,43 @@
+use std::io::{self, Write};
+
+use crate::graph::{CompiledEdge, CompiledGraph};
+use crate::history::RankHistory;
+use crate::rank::{edge_fraction, outflow_normalizers, OutflowNormalization, RankingAlgorithm};
+use crate::types::FrameIdx;
+
+// The frame's graph with every edge pointing from the endorsed node back to the endorser.
+// Reward and penalty events adjust trust received, so they are left out.
+pub fn transposed(graph: &CompiledGraph) -> CompiledGraph {
+    let edges = graph.edges.iter().map(|e| CompiledEdge { source: e.target, target: e.source, ..e.clone() }).collect();
+    CompiledGraph { edges, rank_adjustments: Vec::new(), active_nodes: graph.active_nodes.clone(), conductances: graph.conductances.clone(), ..*graph }
+}
+
+// Reverse ranks ("trustingness"): how much trust a node emits into well-ranked nodes. Every node
+// passes its rank back to its endorsers in proportion to their share of its incoming weight; what
+// a node receives this way is where the algorithm's teleportation starts on the transposed graph,
+// so good judges of trust rank high, while well-trusted nodes that endorse nobody only get what
+// the algorithm spreads over all nodes, such as uniformly redistributed dangling rank.
+pub fn trustingness(algorithm: &dyn RankingAlgorithm, graph: &CompiledGraph, ranks: &[f64], normalization: OutflowNormalization) -> Vec<f64> {
+    let reversed = transposed(graph);
+    let normalizers = outflow_normalizers(&reversed, normalization);
+    let mut emitted = vec![0.0; graph.num_of_nodes];
+    for edge in &reversed.edges {
+        let source = edge.source.index();
+        emitted[edge.target.index()] += ranks[source] * edge_fraction(edge.weight, normalizers[source]);
+    }
+    let total: f64 = emitted.iter().sum();
+    // Without any endorsement every node is as trusting as any other
+    let teleportation_targets = if total > 0.0 { emitted.iter().map(|e| e / total).collect() } else { graph.uniform_ranks() };
+    algorithm.rank(&reversed, &teleportation_targets)
+}
+
+// frame,time,node,rank,trustingness rows, the regular rank next to the reverse one
+pub fn write_trustingness_csv(out: &mut dyn Write, history: &RankHistory, trustingness: &RankHistory, labels: &[String]) -> io::Result<()> {
+    writeln!(out, "frame,time,node,rank,trustingness")?;
+    for (frame, ((time, ranks), trusting)) in history.times().iter().zip(history.frames()).zip(trustingness.frames()).enumerate() {
+        for (node, &rank) in ranks.iter().enumerate() {
+            writeln!(out, "{},{},{},{:.6},{:.6}", FrameIdx(frame).number(), time, labels[node], rank, trusting[node])?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+
+// Strongly typed indices and time values.
+// All of these used to be plain usize, which made it easy to pass a node index
+// where a timestamp was expected (and vice versa).
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FrameIdx(pub usize);
+
+// Continuous time, e.g. seconds since the Unix epoch or abstract simulation ticks
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Time(pub f64);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl EdgeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl FrameIdx {
+    pub fn index(self) -> usize {
+        self.0
+    }
+
+    // Frames are numbered from 1 in rendered output
+    pub fn number(self) -> usize {
+        self.0 + 1
+    }
+}
+
+impl Time {
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    // Time elapsed since an earlier point in time; never negative
+    pub fn elapsed_since(self, earlier: Time) -> f64 {
+        (self.0 - earlier.0).max(0.0)
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for EdgeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for FrameIdx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
@@ -0,0 +1,96 @@
+// Browser bindings, built for wasm32-unknown-unknown with `wasm-pack build --target web --features wasm`.
+// Only the in-memory part of the library is used: scenarios come in as text or edge by edge,
+// ranks go out as a flat Float64Array or as the JSON the HTML viewer reads.
+//
+//   const flow = TrustFlow.parse(scenarioText);
+//   const ranks = flow.rank();                  // ranks[frame * flow.numOfNodes + node]
+//   const data = JSON.parse(flow.viewerData()); // { nodes, edges, frames: [{ time, ranks, weights }] }
+use wasm_bindgen::prelude::*;
+
+use crate::graph::{event_times, Edge};
+use crate::html::viewer_data;
+use crate::rank::RankConfig;
+use crate::registry::AlgorithmRegistry;
+use crate::run::{run_scenario, RunOutput};
+use crate::scenario::Scenario;
+use crate::types::{NodeId, Time};
+
+#[wasm_bindgen]
+pub struct TrustFlow {
+    scenario: Scenario,
+}
+
+#[wasm_bindgen]
+impl TrustFlow {
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_of_nodes: usize, experts: Vec<u32>) -> Result<TrustFlow, JsError> {
+        let experts = experts.into_iter().map(|e| NodeId(e as usize)).collect();
+        let scenario = Scenario::new("browser", num_of_nodes, experts, Vec::new());
+        scenario.validate().map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(TrustFlow { scenario })
+    }
+
+    // A scenario in the text format of scenario files
+    pub fn parse(text: &str) -> Result<TrustFlow, JsError> {
+        let scenario = Scenario::parse(text).map_err(|e| JsError::new(&e.to_string()))?;
+        scenario.validate().map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(TrustFlow { scenario })
+    }
+
+    #[wasm_bindgen(js_name = addEdge)]
+    pub fn add_edge(&mut self, source: usize, target: usize, time: f64, weight: f64) -> Result<(), JsError> {
+        if source >= self.scenario.num_of_nodes || target >= self.scenario.num_of_nodes {
+            return Err(JsError::new(&format!("edge {} -> {} refers to a node outside 0..{}", source, target, self.scenario.num_of_nodes)));
+        }
+        if weight.is_nan() || weight <= 0.0 {
+            return Err(JsError::new(&format!("edge weight must be positive: {}", weight)));
+        }
+        self.scenario.edges.push(Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight });
+        Ok(())
+    }
+
+    // Exponential decay constant of edge weights; 0 disables decay
+    #[wasm_bindgen(getter)]
+    pub fn decay(&self) -> f64 {
+        self.scenario.decay_constant
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_decay(&mut self, decay: f64) {
+        self.scenario.decay_constant = decay;
+    }
+
+    #[wasm_bindgen(getter, js_name = numOfNodes)]
+    pub fn num_of_nodes(&self) -> usize {
+        self.scenario.num_of_nodes
+    }
+
+    // Ranks of every frame, frame after frame; frames at the distinct edge creation times by default
+    pub fn rank(&self, frame_times: Option<Vec<f64>>, algorithm: Option<String>) -> Result<Vec<f64>, JsError> {
+        let output = self.run(frame_times, algorithm)?;
+        Ok(output.history.frames().concat())
+    }
+
+    // The graph, its frames and their ranks as the JSON embedded in `--export html` pages
+    #[wasm_bindgen(js_name = viewerData)]
+    pub fn viewer_data(&self, frame_times: Option<Vec<f64>>, algorithm: Option<String>) -> Result<String, JsError> {
+        let output = self.run(frame_times, algorithm)?;
+        Ok(viewer_data(&self.scenario, &output, &self.scenario.layout()).to_string())
+    }
+}
+
+impl TrustFlow {
+    fn run(&self, frame_times: Option<Vec<f64>>, algorithm: Option<String>) -> Result<RunOutput, JsError> {
+        let config = RankConfig::new();
+        let algorithm = AlgorithmRegistry::create(algorithm.as_deref().unwrap_or("pagerank"), &config).map_err(|e| JsError::new(&e))?;
+        let frame_times = match frame_times {
+            Some(times) => times.into_iter().map(Time).collect(),
+            None if self.scenario.frame_schedule.is_some() => self.scenario.frame_times(),
+            None => event_times(&self.scenario.edges),
+        };
+        if frame_times.is_empty() {
+            return Err(JsError::new("no frame times: pass frame times or add edges first"));
+        }
+        Ok(run_scenario(&self.scenario, algorithm.as_ref(), &frame_times, &self.scenario.graph_options))
+    }
+}
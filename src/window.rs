@@ -0,0 +1,70 @@
+use crate::graph::{exponential_decay, Edge};
+use crate::types::Time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowKind {
+    Sliding,  // frame at time t sees edges created in [t - size, t]
+    Tumbling, // non-overlapping buckets; frame at time t sees edges created in (t - size, t]
+}
+
+// Restricts each frame to recently created edges, instead of (or on top of) pure decay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeWindow {
+    pub kind: WindowKind,
+    pub size: f64,
+    pub stride: f64, // time between consecutive frames; equal to size for tumbling windows
+}
+
+impl TimeWindow {
+    pub fn sliding(size: f64, stride: f64) -> Self {
+        TimeWindow { kind: WindowKind::Sliding, size, stride }
+    }
+
+    pub fn tumbling(size: f64) -> Self {
+        TimeWindow { kind: WindowKind::Tumbling, size, stride: size }
+    }
+
+    pub fn contains(&self, time_of_creation: Time, frame_time: Time) -> bool {
+        let start = frame_time.value() - self.size;
+        match self.kind {
+            WindowKind::Sliding => start <= time_of_creation.value() && time_of_creation <= frame_time,
+            WindowKind::Tumbling => start < time_of_creation.value() && time_of_creation <= frame_time,
+        }
+    }
+
+    // Frame times from `start` up to and including `end`, one stride apart.
+    // Tumbling windows are aligned so that every frame closes a bucket.
+    pub fn frame_times(&self, start: Time, end: Time) -> Vec<Time> {
+        let first = match self.kind {
+            WindowKind::Sliding => start.value(),
+            WindowKind::Tumbling => (start.value() / self.size).ceil() * self.size,
+        };
+        let mut times = Vec::new();
+        let mut k = 0;
+        loop {
+            let t = first + k as f64 * self.stride;
+            if t > end.value() {
+                break;
+            }
+            times.push(Time(t));
+            k += 1;
+        }
+        times
+    }
+
+    pub fn description(&self) -> String {
+        match self.kind {
+            WindowKind::Sliding => format!("sliding window {} (stride {})", self.size, self.stride),
+            WindowKind::Tumbling => format!("tumbling window {}", self.size),
+        }
+    }
+}
+
+// Edge weights at the given time when only edges inside the window count.
+// With a positive decay constant, edges inside the window still decay from their creation time.
+pub fn windowed_weights(edges: &[Edge], time: Time, window: &TimeWindow, decay_constant: f64) -> Vec<f64> {
+    edges.iter().map(|e| {
+        if window.contains(e.time_of_creation, time) { exponential_decay(time, e.time_of_creation, e.weight, decay_constant) }
+        else { 0.0 }
+    }).collect()
+}
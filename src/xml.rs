@@ -0,0 +1,91 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+// GraphML/GEXF documents read into a tree of elements, attributes and text
+
+#[derive(Debug, Clone, Default)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| local_name(&c.name) == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |c| local_name(&c.name) == name)
+    }
+
+    // All descendants (depth first) with the given local name
+    pub fn descendants_named<'a>(&'a self, name: &str, found: &mut Vec<&'a Element>) {
+        for child in &self.children {
+            if local_name(&child.name) == name {
+                found.push(child);
+            }
+            child.descendants_named(name, found);
+        }
+    }
+}
+
+// Element name without namespace prefix
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Parses a document with quick-xml and returns its root element. Processing instructions,
+// comments and doctypes are skipped; CDATA sections count as text.
+pub fn parse(text: &str) -> Result<Element, String> {
+    let mut reader = Reader::from_str(text);
+    let mut stack: Vec<Element> = vec![Element::default()];
+    loop {
+        let event = reader.read_event().map_err(|e| format!("{} at byte {}", e, reader.error_position()))?;
+        match event {
+            Event::Start(tag) => stack.push(element(&tag)?),
+            Event::Empty(tag) => {
+                let element = element(&tag)?;
+                stack.last_mut().unwrap().children.push(element);
+            }
+            Event::End(_) => {
+                // quick-xml has checked that the closing tag matches
+                let element = stack.pop().filter(|_| !stack.is_empty()).ok_or("unexpected closing tag")?;
+                stack.last_mut().unwrap().children.push(element);
+            }
+            Event::Text(text) => stack.last_mut().unwrap().text.push_str(&text.unescape().map_err(|e| e.to_string())?),
+            Event::CData(data) => stack.last_mut().unwrap().text.push_str(&String::from_utf8_lossy(&data)),
+            Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+            Event::Eof => break,
+        }
+    }
+    if stack.len() != 1 {
+        return Err(format!("unclosed element <{}>", stack.last().unwrap().name));
+    }
+    stack.pop().unwrap().children.into_iter().next().ok_or_else(|| "empty document".to_string())
+}
+
+fn element(tag: &BytesStart) -> Result<Element, String> {
+    let name = String::from_utf8_lossy(tag.name().as_ref()).into_owned();
+    let attributes = tag
+        .attributes()
+        .map(|attribute| {
+            let attribute = attribute.map_err(|e| format!("malformed attribute in <{}>: {}", name, e))?;
+            let value = attribute.unescape_value().map_err(|e| format!("invalid attribute value in <{}>: {}", name, e))?;
+            Ok((String::from_utf8_lossy(attribute.key.as_ref()).into_owned(), value.into_owned()))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Element { name, attributes, ..Element::default() })
+}
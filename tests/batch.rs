@@ -0,0 +1,35 @@
+// The batch API ranks a scenario in memory and returns typed results, for services that embed
+// the ranking instead of reading its files.
+use trust_flow::batch::{run_scenario, RunConfig};
+use trust_flow::rank::RankConfig;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn chain() -> Scenario {
+    Scenario::builder("chain").node("alice").edge("alice", "bob").at(1.0).edge("bob", "carol").at(2.0).expert("alice").frames_at(&[0.0, 1.0, 2.0]).build().unwrap()
+}
+
+#[test]
+fn frames_hold_the_ranks_of_every_node() {
+    let result = run_scenario(&RunConfig::new(chain()).rank_config(RankConfig::default().iterations(100).tolerance(1e-12))).unwrap();
+    assert_eq!(result.labels, ["alice", "bob", "carol"]);
+    assert_eq!(result.frames.iter().map(|f| f.time).collect::<Vec<_>>(), [Time(0.0), Time(1.0), Time(2.0)]);
+    let last = result.frames.last().unwrap();
+    assert_eq!(last.active_edges, 2);
+    assert!((last.ranks.values().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert_eq!(last.ranking()[0].0, NodeId(0));
+    assert!(last.convergence.is_some());
+    assert_eq!(result.final_rank("carol"), Some(last.ranks[&NodeId(2)]));
+    // The first frame comes before any edge
+    assert!(result.warnings.iter().any(|w| w.contains("frame 1") && w.contains("no edges")), "{:?}", result.warnings);
+}
+
+#[test]
+fn unconverged_frames_and_bad_input_are_reported() {
+    let result = run_scenario(&RunConfig::new(chain()).rank_config(RankConfig::default().iterations(1).tolerance(1e-12))).unwrap();
+    assert!(result.warnings.iter().any(|w| w.contains("did not converge")), "{:?}", result.warnings);
+    assert!(run_scenario(&RunConfig::new(chain()).algorithm("nonsense")).is_err());
+    assert!(run_scenario(&RunConfig::new(chain()).frame_times(vec![Time(2.0), Time(1.0)])).is_err());
+    let katz = run_scenario(&RunConfig::new(chain()).algorithm("katz")).unwrap();
+    assert_eq!(katz.algorithm, "Katz centrality");
+}
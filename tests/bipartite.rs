@@ -0,0 +1,22 @@
+// Co-HITS on a small rater → item graph, against its fixed point worked out by hand
+use trust_flow::bipartite::CoHits;
+use trust_flow::graph::{compile_graph, Edge, GraphOptions};
+use trust_flow::rank::RankingAlgorithm;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight: 1.0 }
+}
+
+#[test]
+fn scores_alternate_between_raters_and_items() {
+    // Rater 0 rates items 2 and 3, rater 1 only item 3. With a jump of 0.8 the fixed point is
+    // x = (9/14, 5/14) for the raters and y = (5/14, 9/14) for the items, each side holding half.
+    let edges = [edge(0, 2), edge(0, 3), edge(1, 3)];
+    let graph = compile_graph(&edges, &[1.0; 3], 4, &GraphOptions::default()).unwrap();
+    let cohits = CoHits::with_items(vec![false, false, true, true]);
+    assert_eq!(cohits.sides(&graph), CoHits::default().sides(&graph));
+    let ranks = cohits.rank(&graph, &[0.5, 0.5, 0.0, 0.0]);
+    let expected = [9.0 / 28.0, 5.0 / 28.0, 5.0 / 28.0, 9.0 / 28.0];
+    assert!(ranks.iter().zip(&expected).all(|(r, e)| (r - e).abs() < 1e-12), "{:?}", ranks);
+}
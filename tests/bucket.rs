@@ -0,0 +1,59 @@
+// Output written to a bucket must arrive under the URL's prefix, large files included, whatever
+// order the parallel uploads finish in. An in-memory store stands in for S3 and GCS.
+#![cfg(any(feature = "s3", feature = "gcs"))]
+use std::sync::Arc;
+
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use trust_flow::bucket::{ObjectStoreSink, PART_SIZE};
+use trust_flow::sink::{ManifestSink, Sink};
+
+fn read(store: &InMemory, path: &str) -> Vec<u8> {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async { store.get(&Path::from(path)).await.unwrap().bytes().await.unwrap().to_vec() })
+}
+
+#[test]
+fn files_arrive_under_the_prefix() {
+    let store = Arc::new(InMemory::new());
+    let mut sink = ObjectStoreSink::with_store(store.clone(), "memory://bucket/runs/42/", 2).unwrap();
+    assert_eq!(sink.location("frames/1.dot"), "memory://bucket/runs/42/frames/1.dot");
+    for i in 1..=5 {
+        sink.write(&format!("frames/{}.dot", i), format!("digraph {} {{}}", i).as_bytes()).unwrap();
+    }
+    sink.flush().unwrap();
+    for i in 1..=5 {
+        assert_eq!(read(&store, &format!("runs/42/frames/{}.dot", i)), format!("digraph {} {{}}", i).as_bytes());
+    }
+}
+
+#[test]
+fn large_files_are_uploaded_in_parts() {
+    let store = Arc::new(InMemory::new());
+    let contents: Vec<u8> = (0..2 * PART_SIZE + 1).map(|i| (i % 251) as u8).collect();
+    let mut sink = ObjectStoreSink::with_store(store.clone(), "memory://bucket", 8).unwrap();
+    sink.write("ranks.csv", &contents).unwrap();
+    sink.write("small.csv", b"node,rank\n").unwrap();
+    drop(sink);
+    assert!(read(&store, "ranks.csv") == contents);
+    assert_eq!(read(&store, "small.csv"), b"node,rank\n");
+}
+
+#[test]
+fn the_manifest_lists_the_uploads() {
+    let store = Arc::new(InMemory::new());
+    let mut manifest = ManifestSink::new(Box::new(ObjectStoreSink::with_store(store.clone(), "memory://bucket/out", 4).unwrap()));
+    manifest.write("a.json", b"{}").unwrap();
+    manifest.write("b/c.json", b"[]").unwrap();
+    manifest.write_manifest(Some("42")).unwrap();
+    manifest.flush().unwrap();
+    let text = String::from_utf8(read(&store, "out/manifest.json")).unwrap();
+    assert!(text.contains("\"files\":[\"a.json\",\"b/c.json\"]") && text.contains("\"run_id\":\"42\""), "{}", text);
+}
+
+#[test]
+fn other_urls_are_rejected() {
+    assert!(ObjectStoreSink::new("ftp://bucket/prefix", 1).is_err());
+    assert!(ObjectStoreSink::new("output/runs", 1).is_err());
+}
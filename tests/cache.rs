@@ -0,0 +1,71 @@
+// --cache: a run with the same scenario, ranking settings and frame times takes its ranks from the
+// cache, even when it exports another format or restyles the frames, while changed edges or settings rank again under a
+// fingerprint of their own
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SCENARIO: &str = "nodes 4\nexpert 0\nedge 0 1 1\nedge 1 2 2\nedge 2 3 3\nedge 3 0 4\nedge 1 3 5\n";
+const HIT: &str = "chain: ranks taken from the cache";
+
+// Runs the chain scenario with its cache in `cache`; returns stderr
+fn run(folder: &Path, output: &str, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_trust-flow"))
+        .current_dir(folder)
+        .args(["--scenario", "chain.scenario", "--cache-dir", "cache", "--output", output])
+        .args(args)
+        .output()
+        .expect("failed to run trust-flow");
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    assert!(output.status.success(), "{}", stderr);
+    stderr
+}
+
+fn read(folder: &Path, path: &str) -> String {
+    fs::read_to_string(folder.join(path)).unwrap()
+}
+
+fn num_of_cached_runs(folder: &Path) -> usize {
+    fs::read_dir(folder.join("cache")).unwrap().count()
+}
+
+#[test]
+fn unchanged_runs_reuse_the_cached_ranks() {
+    let folder: PathBuf = env::temp_dir().join(format!("trust-flow-cache-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder).unwrap();
+    fs::write(folder.join("chain.scenario"), SCENARIO).unwrap();
+
+    let stderr = run(&folder, "first", &["--export", "csv"]);
+    assert!(!stderr.contains(HIT), "{}", stderr);
+    assert_eq!(num_of_cached_runs(&folder), 1);
+
+    let stderr = run(&folder, "again", &["--export", "csv"]);
+    assert!(stderr.contains(HIT), "{}", stderr);
+    assert_eq!(read(&folder, "again/chain/ranks.csv"), read(&folder, "first/chain/ranks.csv"));
+    // Neither exports nor the render style are part of the fingerprint
+    let stderr = run(&folder, "json", &["--export", "json"]);
+    assert!(stderr.contains(HIT), "{}", stderr);
+    fs::write(folder.join("chain.scenario"), format!("{}style colormap viridis\n", SCENARIO)).unwrap();
+    let stderr = run(&folder, "restyled", &["--export", "csv"]);
+    assert!(stderr.contains(HIT), "{}", stderr);
+    assert_eq!(read(&folder, "restyled/chain/ranks.csv"), read(&folder, "first/chain/ranks.csv"));
+    assert_eq!(num_of_cached_runs(&folder), 1);
+
+    // Other ranking settings
+    let stderr = run(&folder, "damped", &["--export", "csv", "--damping", "0.7"]);
+    assert!(!stderr.contains(HIT), "{}", stderr);
+    assert_eq!(num_of_cached_runs(&folder), 2);
+    assert_ne!(read(&folder, "damped/chain/ranks.csv"), read(&folder, "first/chain/ranks.csv"));
+    assert!(run(&folder, "damped-again", &["--export", "csv", "--damping", "0.7"]).contains(HIT));
+    assert_eq!(read(&folder, "damped-again/chain/ranks.csv"), read(&folder, "damped/chain/ranks.csv"));
+
+    // Another edge in the scenario
+    fs::write(folder.join("chain.scenario"), format!("{}edge 2 0 6\n", SCENARIO)).unwrap();
+    let stderr = run(&folder, "edited", &["--export", "csv"]);
+    assert!(!stderr.contains(HIT), "{}", stderr);
+    assert_eq!(num_of_cached_runs(&folder), 3);
+    assert_ne!(read(&folder, "edited/chain/ranks.csv"), read(&folder, "first/chain/ranks.csv"));
+    fs::remove_dir_all(&folder).unwrap();
+}
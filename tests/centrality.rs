@@ -0,0 +1,61 @@
+// Betweenness and closeness on small hand-checked graphs, where an edge is as long as the inverse
+// of its weight: paths through a node, shortest paths of equal length sharing it, and heavier
+// edges making a longer detour the shortest path
+use trust_flow::graph::{CompiledGraph, Edge};
+use trust_flow::history::RankHistory;
+use trust_flow::metrics::{centrality, write_metrics_csv};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+// Undecayed graph of the edges
+fn graph(num_of_nodes: usize, edges: Vec<Edge>) -> CompiledGraph {
+    let mut scenario = Scenario::new("centrality", num_of_nodes, vec![NodeId(0)], edges);
+    scenario.decay_constant = 0.0;
+    scenario.frame_graph(Time(0.0), &scenario.edge_weights(Time(0.0))).unwrap()
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert!(actual.len() == expected.len() && actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} instead of {:?}", actual, expected);
+}
+
+#[test]
+fn a_path_runs_through_its_middle() {
+    // 0 → 1 → 2: of the 2 · 1 ordered pairs of other nodes, only 0 to 2 passes 1. Node 0 is 1
+    // and 2 away from the others, node 1 is 1 away from 2, and node 2 reaches nobody.
+    let metrics = centrality(&graph(3, vec![edge(0, 1, 1.0), edge(1, 2, 1.0)]));
+    assert_close(&metrics.betweenness, &[0.0, 0.5, 0.0]);
+    assert_close(&metrics.closeness, &[(1.0 + 1.0 / 2.0) / 2.0, 1.0 / 2.0, 0.0]);
+}
+
+#[test]
+fn shortest_paths_of_equal_length_share_the_betweenness() {
+    // 0 reaches 3 through 1 and through 2, both of length 2, while the direct edge of weight 0.25
+    // is 4 long. Each middle node carries half of 1 path out of 3 · 2 pairs.
+    let edges = vec![edge(0, 1, 1.0), edge(1, 3, 1.0), edge(0, 2, 1.0), edge(2, 3, 1.0), edge(0, 3, 0.25)];
+    let metrics = centrality(&graph(4, edges));
+    assert_close(&metrics.betweenness, &[0.0, 1.0 / 12.0, 1.0 / 12.0, 0.0]);
+    assert_close(&metrics.closeness, &[(1.0 + 1.0 + 1.0 / 2.0) / 3.0, 1.0 / 3.0, 1.0 / 3.0, 0.0]);
+}
+
+#[test]
+fn heavy_edges_make_short_paths() {
+    // Edges of weight 2 make 0 → 2 → 3 1 long, shorter than 0 → 1 → 3, so only 2 is in between
+    let edges = vec![edge(0, 1, 1.0), edge(1, 3, 1.0), edge(0, 2, 2.0), edge(2, 3, 2.0), edge(0, 3, 0.25)];
+    let metrics = centrality(&graph(4, edges));
+    assert_close(&metrics.betweenness, &[0.0, 0.0, 1.0 / 6.0, 0.0]);
+    assert_close(&metrics.closeness, &[(1.0 + 2.0 + 1.0) / 3.0, 1.0 / 3.0, 2.0 / 3.0, 0.0]);
+
+    let mut history = RankHistory::new();
+    history.push(Time(2.0), vec![0.4, 0.2, 0.3, 0.1]);
+    let labels: Vec<String> = ["a", "b", "c", "d"].map(String::from).to_vec();
+    let mut csv = Vec::new();
+    write_metrics_csv(&mut csv, &history, &[metrics], &labels).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "frame,time,node,rank,betweenness,closeness\n1,2,a,0.400000,0.000000,1.333333\n1,2,b,0.200000,0.000000,0.333333\n1,2,c,0.300000,0.166667,0.666667\n1,2,d,0.100000,0.000000,0.000000\n"
+    );
+}
@@ -0,0 +1,75 @@
+// --checkpoint and --resume: a run interrupted partway and resumed from its checkpoint ends with the
+// same frames and ranks as a run that was never interrupted, and a checkpoint written with other
+// settings is not resumed
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SCENARIO: &str = "nodes 4\nexpert 0\nedge 0 1 1\nedge 1 2 2\nedge 2 3 3\nedge 3 0 4\nedge 1 3 5\n";
+
+fn folder(name: &str) -> PathBuf {
+    let folder = env::temp_dir().join(format!("trust-flow-checkpoint-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+// Runs the chain scenario with checkpoints in `checkpoints`; returns stderr
+fn run(folder: &Path, checkpoints: &str, output: &str, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_trust-flow"))
+        .current_dir(folder)
+        .args(["--scenario", "chain.scenario", "--checkpoint-dir", checkpoints, "--output", output, "--export", "csv"])
+        .args(args)
+        .output()
+        .expect("failed to run trust-flow");
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    assert!(output.status.success(), "{}", stderr);
+    stderr
+}
+
+fn read(folder: &Path, path: &str) -> String {
+    fs::read_to_string(folder.join(path)).unwrap()
+}
+
+// The header and the first `frames` frames of a checkpoint, followed by half of the next frame's
+// line as a write cut off by the interruption would leave it
+fn interrupted(checkpoint: &str, frames: usize) -> String {
+    let lines: Vec<&str> = checkpoint.lines().collect();
+    let cut_off = &lines[frames + 1][..lines[frames + 1].len() / 2];
+    format!("{}\n{}", lines[..=frames].join("\n"), cut_off)
+}
+
+#[test]
+fn resumed_runs_equal_uninterrupted_runs() {
+    let folder = folder("resume");
+    fs::write(folder.join("chain.scenario"), SCENARIO).unwrap();
+    run(&folder, "whole", "whole-out", &[]);
+    let checkpoint = read(&folder, "whole/chain.checkpoint.jsonl");
+    assert_eq!(checkpoint.lines().count(), 1 + 21, "header and frames 0 to 20");
+
+    fs::create_dir_all(folder.join("resumed")).unwrap();
+    fs::write(folder.join("resumed/chain.checkpoint.jsonl"), interrupted(&checkpoint, 8)).unwrap();
+    let stderr = run(&folder, "resumed", "resumed-out", &["--resume"]);
+    assert!(stderr.contains("chain: resuming after frame 8 of 21"), "{}", stderr);
+    // The checkpoint holds the full-precision ranks and edge weights of every frame
+    assert_eq!(read(&folder, "resumed/chain.checkpoint.jsonl"), checkpoint);
+    assert_eq!(read(&folder, "resumed-out/chain/ranks.csv"), read(&folder, "whole-out/chain/ranks.csv"));
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn checkpoints_of_other_settings_are_not_resumed() {
+    let folder = folder("settings");
+    fs::write(folder.join("chain.scenario"), SCENARIO).unwrap();
+    run(&folder, "checkpoints", "out", &[]);
+    let checkpoint = read(&folder, "checkpoints/chain.checkpoint.jsonl");
+    fs::write(folder.join("checkpoints/chain.checkpoint.jsonl"), interrupted(&checkpoint, 8)).unwrap();
+
+    let stderr = run(&folder, "checkpoints", "out", &["--resume", "--damping", "0.7"]);
+    assert!(!stderr.contains("resuming"), "{}", stderr);
+    run(&folder, "fresh", "fresh-out", &["--damping", "0.7"]);
+    assert_eq!(read(&folder, "checkpoints/chain.checkpoint.jsonl"), read(&folder, "fresh/chain.checkpoint.jsonl"));
+    assert_ne!(read(&folder, "checkpoints/chain.checkpoint.jsonl"), checkpoint);
+    fs::remove_dir_all(&folder).unwrap();
+}
@@ -0,0 +1,41 @@
+// Argument errors are reported with the usage text and exit code 2 instead of a panic, and --help
+// lists the options
+use std::process::{Command, Output};
+
+fn trust_flow(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_trust-flow")).args(args).output().expect("failed to run trust-flow")
+}
+
+fn assert_rejected(args: &[&str], message: &str) {
+    let output = trust_flow(args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{:?}: {}", args, stderr);
+    assert!(stderr.contains(message) && stderr.contains("Usage: trust-flow"), "{:?}: {}", args, stderr);
+    assert!(!stderr.contains("panicked"), "{:?}: {}", args, stderr);
+}
+
+#[test]
+fn help_lists_the_options() {
+    for flag in ["--help", "-h"] {
+        let output = trust_flow(&[flag]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.starts_with("Usage: trust-flow") && stdout.contains("--decay"), "{}", stdout);
+    }
+}
+
+#[test]
+fn unknown_arguments_are_rejected() {
+    assert_rejected(&["--bogus"], "unknown argument: --bogus");
+}
+
+#[test]
+fn missing_values_are_rejected() {
+    assert_rejected(&["--decay"], "--decay requires a decay constant");
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    assert_rejected(&["--decay", "-1"], "--decay must not be negative");
+    assert_rejected(&["--jobs", "many"], "--jobs");
+}
@@ -0,0 +1,62 @@
+// Source clipping against a whale endorser: no single source may bring a node more than a share
+// of its inflow, so a node propped up by one heavy endorser loses most of that support while
+// nodes endorsed by many keep theirs.
+use trust_flow::clipping::SourceClipped;
+use trust_flow::graph::Edge;
+use trust_flow::rank::{PageRankVariant, RankConfig, RankingAlgorithm};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+fn config() -> RankConfig {
+    RankConfig::new().iterations(200).tolerance(1e-14)
+}
+
+// Ranks of the scenario's graph at time 0 under the algorithm
+fn ranks(scenario: &Scenario, algorithm: &dyn RankingAlgorithm) -> Vec<f64> {
+    let time = Time(0.0);
+    algorithm.rank(&scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap(), &scenario.teleportation_targets(time))
+}
+
+// Expert 0 is a whale that endorses only node 4, a newcomer nobody else vouches for. Nodes 1 to 3
+// endorse each other and node 5, which thus has several endorsers of similar rank.
+fn whale() -> Scenario {
+    let edges = vec![edge(0, 4, 10.0), edge(1, 2, 1.0), edge(2, 3, 1.0), edge(3, 1, 1.0), edge(1, 5, 1.0), edge(2, 5, 1.0), edge(3, 5, 1.0), edge(4, 0, 1.0), edge(5, 1, 1.0)];
+    let mut scenario = Scenario::new("whale", 6, vec![NodeId(0)], edges);
+    scenario.decay_constant = 0.0;
+    scenario
+}
+
+#[test]
+fn a_single_whale_endorser_is_clipped() {
+    let scenario = whale();
+    let plain = ranks(&scenario, &PageRankVariant { config: config() });
+    let clipped = ranks(&scenario, &SourceClipped { config: config(), max_share: 0.4 });
+    assert!((clipped.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(clipped[4] < 0.6 * plain[4], "the whale's protégé keeps {} of {}", clipped[4], plain[4]);
+    // Three endorsers with a third of the inflow each stay below the cap
+    assert!(clipped[5] >= plain[5] * 0.99, "node 5 lost rank: {} instead of {}", clipped[5], plain[5]);
+}
+
+#[test]
+fn parallel_edges_of_one_source_count_together() {
+    // Splitting the whale's endorsement into many parallel edges does not get around the cap
+    let mut split = whale();
+    split.edges[0].weight = 1.0;
+    split.edges.extend((0..9).map(|_| edge(0, 4, 1.0)));
+    let clipped = ranks(&whale(), &SourceClipped { config: config(), max_share: 0.4 });
+    let split_clipped = ranks(&split, &SourceClipped { config: config(), max_share: 0.4 });
+    assert!((clipped[4] - split_clipped[4]).abs() < 1e-9, "{} vs {}", clipped[4], split_clipped[4]);
+}
+
+#[test]
+fn a_share_of_one_gives_the_custom_variant() {
+    let scenario = whale();
+    let plain = ranks(&scenario, &PageRankVariant { config: config() });
+    let clipped = ranks(&scenario, &SourceClipped { config: config(), max_share: 1.0 });
+    let difference: f64 = plain.iter().zip(&clipped).map(|(a, b)| (a - b).abs()).sum();
+    assert!(difference < 1e-9, "ranks differ by {}", difference);
+}
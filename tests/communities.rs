@@ -0,0 +1,57 @@
+// Communities on small hand-checked graphs: label propagation separates two triangles joined by a
+// weak bridge, and tracked ids follow communities across frames as they move, merge and split
+use trust_flow::communities::{community_borders, label_propagation, write_communities_csv, CommunityTracker};
+use trust_flow::graph::{CompiledGraph, Edge};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+// Undecayed graph of the edges
+fn graph(num_of_nodes: usize, edges: Vec<Edge>) -> CompiledGraph {
+    let mut scenario = Scenario::new("communities", num_of_nodes, vec![NodeId(0)], edges);
+    scenario.decay_constant = 0.0;
+    scenario.frame_graph(Time(0.0), &scenario.edge_weights(Time(0.0))).unwrap()
+}
+
+#[test]
+fn triangles_joined_by_a_weak_bridge_are_two_communities() {
+    // Triangles 0-1-2 and 3-4-5, the bridge 2 → 3 of weight 0.5 and node 6 without edges. In the
+    // first sweep 0 takes 1's label (the smaller of a 1:1 tie), 2 takes it too (2 against 0.5),
+    // 3 takes 4's label (1 against 1 for 5, 0.5 for 2's), and 5 takes it as well; the second
+    // sweep changes nothing
+    let edges = vec![edge(0, 1, 1.0), edge(1, 2, 1.0), edge(2, 0, 1.0), edge(3, 4, 1.0), edge(4, 5, 1.0), edge(5, 3, 1.0), edge(2, 3, 0.5)];
+    assert_eq!(label_propagation(&graph(7, edges.clone())), [0, 0, 0, 1, 1, 1, 2]);
+
+    // A bridge of weight 3 pulls 2 over instead: it weighs 3 against 2 for 0 and 1's label, and
+    // 3 keeps its own label, which 4 (on a 1:1 tie with 5's) and then 5 adopt
+    let mut heavy = edges;
+    heavy[6].weight = 3.0;
+    assert_eq!(label_propagation(&graph(7, heavy)), [0, 0, 1, 1, 1, 1, 2]);
+}
+
+#[test]
+fn tracked_ids_follow_the_communities() {
+    let mut tracker = CommunityTracker::new();
+    assert_eq!(tracker.track(&[0, 0, 0, 1, 1, 1, 2]), [0, 0, 0, 1, 1, 1, 2]);
+    // Numbered the other way round, with node 0 moved: {0, 4, 5} overlaps id 1 most, {1, 2, 3} id 0
+    assert_eq!(tracker.track(&[0, 1, 1, 1, 0, 0, 2]), [1, 0, 0, 0, 1, 1, 2]);
+    // A merged community overlaps ids 0 and 1 by three nodes each and keeps the smaller
+    assert_eq!(tracker.track(&[0, 0, 0, 0, 0, 0, 1]), [0, 0, 0, 0, 0, 0, 2]);
+    // After a split, the part that comes first keeps id 0 and the other gets the fresh id 3
+    let ids = tracker.track(&[0, 0, 0, 1, 1, 1, 2]);
+    assert_eq!(ids, [0, 0, 0, 3, 3, 3, 2]);
+
+    let borders = community_borders(&ids);
+    assert_eq!(borders[0], borders[2]);
+    assert_eq!(borders[3], borders[5]);
+    assert!(borders[0].is_some() && borders[3].is_some() && borders[0] != borders[3]);
+    assert_eq!(borders[6], None, "a node alone has no border");
+
+    let labels: Vec<String> = ["a", "b", "c"].map(String::from).to_vec();
+    let mut csv = Vec::new();
+    write_communities_csv(&mut csv, &[Time(0.0), Time(1.5)], &[vec![0, 0, 1], vec![0, 2, 2]], &labels).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(), "frame,time,node,community\n1,0,a,0\n1,0,b,0\n1,0,c,1\n2,1.5,a,0\n2,1.5,b,2\n2,1.5,c,2\n");
+}
@@ -0,0 +1,32 @@
+// Node conductance: a node passes that share of the rank it holds along its edges and keeps the
+// rest, the same way in every solver and backend
+use trust_flow::graph::Edge;
+use trust_flow::nodes::NodeInfo;
+use trust_flow::rank::{Backend, PageRankVariant, RankConfig, RankingAlgorithm, Solver};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight: 1.0 }
+}
+
+fn ranks(conductance: Option<f64>, config: RankConfig) -> Vec<f64> {
+    let mut scenario = Scenario::new("cycle", 2, vec![NodeId(0)], vec![edge(0, 1), edge(1, 0)]);
+    scenario.decay_constant = 0.0;
+    scenario.node_info = vec![NodeInfo { conductance, ..NodeInfo::default() }, NodeInfo::default()];
+    let time = Time(1.0);
+    let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+    PageRankVariant { config }.rank(&graph, &[1.0, 0.0])
+}
+
+#[test]
+fn a_half_conducting_node_keeps_half_of_its_rank() {
+    // r0 = 1/2 + 1/2 (r0/2 + r1) and r1 = 1/2 · r0/2, against r0 = 1/2 + r1/2 and r1 = r0/2 without
+    let converged = RankConfig::new().iterations(200).tolerance(1e-15);
+    for config in [converged.clone(), converged.clone().solver(Solver::GaussSeidel), converged.clone().solver(Solver::Exact), converged.backend(Backend::Dense)] {
+        for (conductance, expected) in [(Some(0.5), [0.8, 0.2]), (None, [2.0 / 3.0, 1.0 / 3.0])] {
+            let ranks = ranks(conductance, config.clone());
+            assert!(ranks.iter().zip(&expected).all(|(r, e)| (r - e).abs() < 1e-12), "{:?} ({:?}): {:?}", conductance, config, ranks);
+        }
+    }
+}
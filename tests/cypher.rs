@@ -0,0 +1,63 @@
+// --export cypher: the statements MERGE one node per graph node and one relationship per edge on
+// the scenario name and ids, quote strings for Cypher and carry the per-frame ranks and weights
+use trust_flow::cypher::write_cypher;
+use trust_flow::rank::{PageRankVariant, RankConfig, Solver};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+
+// Two nodes endorsing each other from time 0 and 1 without decay, ranked before and after
+const SCENARIO: &str = "name it's\nnodes 2\nexpert 0\nnode 1 name=o'brien category=peer\ndecay 0\nedge 0 1 0\nedge 1 0 1\nframes at -1,1\n";
+
+fn cypher() -> Vec<String> {
+    let scenario = Scenario::parse(SCENARIO).unwrap();
+    let algorithm = PageRankVariant { config: RankConfig::new().solver(Solver::Exact) };
+    let output = run_scenario(&scenario, &algorithm, &scenario.frame_times(), &scenario.graph_options);
+    let mut out = Vec::new();
+    write_cypher(&mut out, &scenario, &output).unwrap();
+    String::from_utf8(out).unwrap().lines().map(str::to_string).collect()
+}
+
+// The statement up to its `n.ranks` list, and the ranks in it
+fn split_ranks(statement: &str) -> (&str, Vec<f64>) {
+    let (start, ranks) = statement.split_once(", n.ranks = [").unwrap();
+    let (ranks, _) = ranks.split_once(']').unwrap();
+    (start, ranks.split(", ").map(|r| r.parse().unwrap()).collect())
+}
+
+fn assert_ranks(actual: &[f64], expected: &[f64]) {
+    assert!(actual.len() == expected.len() && actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} instead of {:?}", actual, expected);
+}
+
+#[test]
+fn graph_nodes_and_edges_are_merged() {
+    let lines = cypher();
+    assert_eq!(lines.len(), 7, "{:#?}", lines);
+    assert_eq!(lines[0], "// trust-flow export of it's");
+    assert_eq!(lines[1], "CREATE INDEX trust_node IF NOT EXISTS FOR (n:TrustNode) ON (n.scenario, n.id);");
+    assert_eq!(lines[2], "MERGE (g:TrustGraph {scenario: 'it\\'s'}) SET g.decay = 'None', g.seed = '1', g.frame_times = [-1.0, 1.0];");
+    assert_eq!(
+        lines[5],
+        "MATCH (a:TrustNode {scenario: 'it\\'s', id: 0}), (b:TrustNode {scenario: 'it\\'s', id: 1}) MERGE (a)-[r:TRUSTS {id: 0}]->(b) SET r.created = 0.0, r.end = null, r.weight = 1.0, r.weights = [0.0, 1.0];"
+    );
+    assert_eq!(
+        lines[6],
+        "MATCH (a:TrustNode {scenario: 'it\\'s', id: 1}), (b:TrustNode {scenario: 'it\\'s', id: 0}) MERGE (a)-[r:TRUSTS {id: 1}]->(b) SET r.created = 1.0, r.end = null, r.weight = 1.0, r.weights = [0.0, 1.0];"
+    );
+}
+
+#[test]
+fn nodes_carry_their_ranks() {
+    let lines = cypher();
+    let (expert, expert_ranks) = split_ranks(&lines[3]);
+    let (peer, peer_ranks) = split_ranks(&lines[4]);
+    assert_eq!(expert, "MERGE (n:TrustNode {scenario: 'it\\'s', id: 0}) SET n.label = '0', n.category = null, n.expert = true");
+    assert_eq!(peer, "MERGE (n:TrustNode {scenario: 'it\\'s', id: 1}) SET n.label = 'o\\'brien', n.category = 'peer', n.expert = false");
+    // At -1 both nodes dangle: r0 = 0.5 · 0.9 + 0.5 / 2. At 1 they endorse each other:
+    // r0 = 0.45 + 0.5 · r1 and r1 = 0.05 + 0.5 · r0, so r0 = 0.475 / 0.75
+    assert_ranks(&expert_ranks, &[0.7, 0.475 / 0.75]);
+    assert_ranks(&peer_ranks, &[0.3, 1.0 - 0.475 / 0.75]);
+    // The last frame's rank repeats the last entry of the list
+    for (line, ranks) in [(&lines[3], expert_ranks), (&lines[4], peer_ranks)] {
+        assert!(line.ends_with(&format!(", n.rank = {};", ranks[1])), "{}", line);
+    }
+}
@@ -0,0 +1,92 @@
+// Edge weight decay: half-lives, the batched and parallel kernel, the fast exponential and the
+// lookup table of whole ages, each against weights worked out by hand
+use std::f64::consts::LN_2;
+
+use trust_flow::decay::{decay_constant_for_half_life, describe_half_life, fast_exp, half_life, DecayKernel, DecayTable, PARALLEL_DECAY_MIN_EDGES};
+use trust_flow::graph::Edge;
+use trust_flow::random::Rng;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+const TOLERANCE: f64 = 1e-12;
+
+fn edge(time: f64, weight: f64) -> Edge {
+    Edge { source: NodeId(0), target: NodeId(1), time_of_creation: Time(time), weight }
+}
+
+fn assert_weights(actual: &[f64], expected: &[f64], what: &str) {
+    assert_eq!(actual.len(), expected.len(), "{}", what);
+    assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() <= TOLERANCE.max(2e-7 * e)), "{}: {:?} instead of {:?}", what, actual, expected);
+}
+
+#[test]
+fn weights_halve_every_half_life() {
+    let k = decay_constant_for_half_life(2.0);
+    assert!((k - LN_2 / 2.0).abs() < TOLERANCE);
+    assert!((half_life(k).unwrap() - 2.0).abs() < TOLERANCE);
+    assert_eq!(half_life(0.0), None);
+    assert_eq!(describe_half_life(LN_2 / 3.4657).as_deref(), Some("halves every 3.466"));
+
+    let mut scenario = Scenario::new("halving", 2, Vec::new(), vec![edge(0.0, 4.0), edge(1.0, 4.0)]);
+    scenario.decay_constant = k;
+    assert_weights(&scenario.edge_weights(Time(0.0)), &[4.0, 0.0], "at 0");
+    assert_weights(&scenario.edge_weights(Time(2.0)), &[2.0, 4.0 * 0.5f64.sqrt()], "at 2");
+    assert_weights(&scenario.edge_weights(Time(5.0)), &[4.0 * 0.5f64.powf(2.5), 1.0], "at 5");
+}
+
+#[test]
+fn kernels_agree_with_the_exponential() {
+    // e^(-ln 2 · age): 8 halves three times by time 3, 2 halves twice, and the edge of time 5 does not exist yet
+    let edges = [edge(0.0, 8.0), edge(1.0, 2.0), edge(3.0, 1.0), edge(5.0, 1.0), edge(2.5, 1.0)];
+    let expected = [1.0, 0.5, 1.0, 0.0, 0.5f64.sqrt()];
+    for kernel in [DecayKernel::default(), DecayKernel { fast_exp: false, threads: 3 }, DecayKernel { fast_exp: true, threads: 0 }] {
+        assert_weights(&kernel.decayed_weights(&edges, Time(3.0), LN_2), &expected, &format!("{:?}", kernel));
+    }
+}
+
+#[test]
+fn parallel_decay_matches_sequential_decay() {
+    let mut rng = Rng::new(0xDECA);
+    let edges: Vec<Edge> = (0..PARALLEL_DECAY_MIN_EDGES + 1000).map(|_| edge(20.0 * rng.next_f64(), 0.5 + rng.next_f64())).collect();
+    let time = Time(10.0);
+    for fast_exp in [false, true] {
+        let sequential = DecayKernel { fast_exp, threads: 1 }.decayed_weights(&edges, time, 0.3);
+        for threads in [0, 3] {
+            assert!(DecayKernel { fast_exp, threads }.decayed_weights(&edges, time, 0.3) == sequential, "{} threads change the weights", threads);
+        }
+    }
+}
+
+#[test]
+fn fast_exp_stays_close_to_exp() {
+    assert_eq!(fast_exp(0.0), 1.0);
+    assert!((fast_exp(-LN_2) - 0.5).abs() < 1e-7);
+    assert!((fast_exp(1.0) - std::f64::consts::E).abs() < 2e-7 * std::f64::consts::E);
+    for step in 0..=1400 {
+        let x = -700.0 + step as f64;
+        assert!((fast_exp(x) - x.exp()).abs() <= 2e-7 * x.exp(), "fast_exp({}) is {}, exp is {}", x, fast_exp(x), x.exp());
+    }
+    assert_eq!(fast_exp(-1000.0), 0.0);
+}
+
+#[test]
+fn decay_tables_hold_the_factors_of_whole_ages() {
+    let kernel = DecayKernel::default();
+    let table = DecayTable::new(&kernel, LN_2, 4);
+    assert_eq!(table.max_age(), 4);
+    assert_weights(&[0.0, 1.0, 3.0].map(|age| table.factor(age).unwrap()), &[1.0, 0.5, 0.125], "table");
+    assert_eq!([2.5, 5.0, -1.0].map(|age| table.factor(age)), [None, None, None]);
+    assert!(table.fits(&kernel, LN_2) && !table.fits(&kernel, 0.5) && !table.fits(&DecayKernel { fast_exp: true, threads: 0 }, LN_2));
+
+    // Ticks 1 and 3 reach ages up to 9 - 1 by time 9; a creation time between ticks or no decay gets no table
+    let ticks = [Time(1.0), Time(3.0)];
+    assert_eq!(DecayTable::for_creation_times(&kernel, LN_2, ticks, Time(9.0)).map(|t| t.max_age()), Some(8));
+    assert_eq!(DecayTable::for_creation_times(&kernel, LN_2, [Time(1.0), Time(1.5)], Time(9.0)), None);
+    assert_eq!(DecayTable::for_creation_times(&kernel, 0.0, ticks, Time(9.0)), None);
+
+    // Looked-up weights are the computed ones, and ages beyond the table are computed
+    let edges = [edge(1.0, 8.0), edge(3.0, 2.0)];
+    let small = DecayTable::new(&kernel, LN_2, 2);
+    assert_weights(&kernel.decayed_weights_with(&edges, Time(4.0), LN_2, Some(&small)), &[1.0, 1.0], "looked up");
+    assert_eq!(kernel.decayed_weights_with(&edges, Time(4.0), LN_2, Some(&small)), kernel.decayed_weights(&edges, Time(4.0), LN_2));
+}
@@ -0,0 +1,84 @@
+// Empty and degenerate graphs get well-defined ranks from every algorithm, and inputs that do not
+// fit together are reported as typed errors instead of panics or NaN ranks.
+use trust_flow::graph::{compile_graph, CompiledGraph, Edge, GraphError, GraphOptions};
+use trust_flow::rank::{try_rank, DanglingPolicy, PageRankVariant, RankConfig, RankError, RankingAlgorithm, Solver};
+use trust_flow::registry::AlgorithmRegistry;
+use trust_flow::types::{EdgeId, NodeId, Time};
+
+const TOLERANCE: f64 = 1e-9;
+
+fn edge(source: usize, target: usize) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight: 1.0 }
+}
+
+fn graph(num_of_nodes: usize, edges: &[Edge]) -> CompiledGraph {
+    compile_graph(edges, &vec![1.0; edges.len()], num_of_nodes, &GraphOptions::default()).unwrap()
+}
+
+fn assert_distribution(ranks: &[f64], what: &str) {
+    assert!(ranks.iter().all(|r| r.is_finite() && *r >= 0.0), "{}: {:?}", what, ranks);
+    assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-6, "{}: {:?}", what, ranks);
+}
+
+#[test]
+fn empty_graphs_have_no_ranks() {
+    for info in AlgorithmRegistry::all() {
+        let algorithm = AlgorithmRegistry::create(info.id, &RankConfig::default()).unwrap();
+        assert_eq!(algorithm.rank(&graph(0, &[]), &[]), Vec::<f64>::new(), "{}", info.id);
+    }
+    for solver in [Solver::Jacobi, Solver::GaussSeidel, Solver::MonteCarlo, Solver::Exact] {
+        let algorithm = PageRankVariant { config: RankConfig::default().solver(solver) };
+        assert!(algorithm.rank(&graph(0, &[]), &[]).is_empty(), "{:?}", solver);
+    }
+}
+
+#[test]
+fn graphs_without_edges_rank_uniformly() {
+    for info in AlgorithmRegistry::all() {
+        let algorithm = AlgorithmRegistry::create(info.id, &RankConfig::default()).unwrap();
+        let ranks = algorithm.rank(&graph(4, &[]), &[0.25; 4]);
+        assert!(ranks.iter().all(|r| (r - 0.25).abs() < TOLERANCE), "{}: {:?}", info.id, ranks);
+    }
+}
+
+#[test]
+fn degenerate_graphs_keep_all_rank() {
+    let all_dangling = graph(3, &[edge(0, 1), edge(0, 2)]);
+    let mut inactive = graph(3, &[edge(0, 1)]);
+    inactive.active_nodes = vec![false; 3];
+    for info in AlgorithmRegistry::all() {
+        let algorithm = AlgorithmRegistry::create(info.id, &RankConfig::default()).unwrap();
+        assert_distribution(&algorithm.rank(&all_dangling, &[1.0 / 3.0; 3]), &format!("{} with dangling nodes", info.id));
+        assert_distribution(&algorithm.rank(&inactive, &[0.0; 3]), &format!("{} without active nodes", info.id));
+        assert_distribution(&algorithm.rank(&all_dangling, &[0.0; 3]), &format!("{} without teleportation targets", info.id));
+    }
+}
+
+#[test]
+fn teleportation_targets_without_rank_are_uniform() {
+    let graph = graph(3, &[edge(0, 1)]);
+    for policy in [DanglingPolicy::Uniform, DanglingPolicy::Teleport, DanglingPolicy::Retain] {
+        let algorithm = PageRankVariant { config: RankConfig::default().dangling_policy(policy) };
+        assert_eq!(algorithm.rank(&graph, &[0.0; 3]), algorithm.rank(&graph, &[1.0 / 3.0; 3]), "{:?}", policy);
+    }
+}
+
+#[test]
+fn inputs_that_do_not_fit_are_errors() {
+    let algorithm = PageRankVariant { config: RankConfig::default() };
+    let graph = graph(3, &[edge(0, 1)]);
+    assert_eq!(try_rank(&algorithm, &graph, &[0.5; 2]), Err(RankError::TargetCount { nodes: 3, targets: 2 }));
+    assert_eq!(try_rank(&algorithm, &graph, &[0.5, f64::NAN, 0.5]), Err(RankError::InvalidTarget(NodeId(1))));
+    assert_eq!(try_rank(&algorithm, &graph, &[0.5, -0.5, 1.0]), Err(RankError::InvalidTarget(NodeId(1))));
+
+    let mut negative = graph.clone();
+    negative.edges[0].weight = -1.0;
+    assert_eq!(try_rank(&algorithm, &negative, &[0.5; 3]), Err(RankError::InvalidWeight(0)));
+    let mut dangling_edge = graph.clone();
+    dangling_edge.edges[0].target = NodeId(7);
+    assert_eq!(try_rank(&algorithm, &dangling_edge, &[0.5; 3]), Err(RankError::NodeOutOfRange { edge: 0, node: NodeId(7), nodes: 3 }));
+    assert!(try_rank(&algorithm, &graph, &[1.0 / 3.0; 3]).is_ok());
+
+    let error = compile_graph(&[edge(0, 1), edge(1, 3)], &[1.0, 1.0], 3, &GraphOptions::default()).unwrap_err();
+    assert_eq!(error, GraphError::NodeOutOfRange(EdgeId(1)));
+}
@@ -0,0 +1,62 @@
+// The dense matrix behind --backend dense, --solver exact and --spectral-radius, on systems small
+// enough to check by hand; with the nalgebra feature the same checks run against nalgebra.
+use trust_flow::dense::DenseMatrix;
+use trust_flow::graph::{compile_graph, Edge, GraphOptions};
+use trust_flow::rank::{Backend, PageRankVariant, RankConfig, RankingAlgorithm};
+use trust_flow::types::{NodeId, Time};
+
+fn matrix(rows: &[&[f64]]) -> DenseMatrix {
+    let mut matrix = DenseMatrix::zeros(rows.len());
+    for (row, values) in rows.iter().enumerate() {
+        for (column, &value) in values.iter().enumerate() {
+            matrix.add(row, column, value);
+        }
+    }
+    matrix
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} != {:?}", actual, expected);
+}
+
+#[test]
+fn products_and_solutions() {
+    let m = matrix(&[&[2.0, 1.0], &[1.0, 3.0]]);
+    assert_eq!(m.size(), 2);
+    assert_eq!(m.get(1, 0), 1.0);
+    assert_close(&m.mul_vec(&[1.0, -1.0]), &[1.0, -2.0]);
+    // 2x + y = 3, x + 3y = 5
+    assert_close(&m.solve(&[3.0, 5.0]).unwrap(), &[0.8, 1.4]);
+    // A zero in the first pivot position takes a row swap
+    assert_close(&matrix(&[&[0.0, 1.0], &[1.0, 0.0]]).solve(&[2.0, 3.0]).unwrap(), &[3.0, 2.0]);
+    assert_eq!(DenseMatrix::zeros(0).solve(&[]), Some(Vec::new()));
+}
+
+#[test]
+fn singular_systems_have_no_solution() {
+    assert_eq!(matrix(&[&[1.0, 2.0], &[2.0, 4.0]]).solve(&[1.0, 2.0]), None);
+    assert_eq!(DenseMatrix::zeros(3).solve(&[0.0; 3]), None);
+}
+
+#[test]
+fn spectral_radius() {
+    // A 2-cycle passes all rank along: eigenvalues 1 and -1
+    assert!((matrix(&[&[0.0, 1.0], &[1.0, 0.0]]).spectral_radius(50) - 1.0).abs() < 1e-12);
+    assert!((matrix(&[&[0.5, 0.0], &[0.0, 0.25]]).spectral_radius(200) - 0.5).abs() < 1e-9);
+    // Nilpotent, as for a directed acyclic graph
+    assert_eq!(matrix(&[&[0.0, 0.0], &[1.0, 0.0]]).spectral_radius(10), 0.0);
+    assert_eq!(DenseMatrix::zeros(0).spectral_radius(10), 0.0);
+}
+
+#[test]
+fn the_dense_backend_ranks_like_the_edge_list() {
+    // r0 = 1/2 + r1/2 and r1 = r0/2 on a 2-cycle, with the edge 0 → 1 recorded twice
+    let edges = [(0, 1), (0, 1), (1, 0)].map(|(source, target)| Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight: 1.0 });
+    let graph = compile_graph(&edges, &[1.0; 3], 2, &GraphOptions::default()).unwrap();
+    let config = RankConfig::new().iterations(200).tolerance(1e-15);
+    let edge_list = PageRankVariant { config: config.clone() }.rank(&graph, &[1.0, 0.0]);
+    let dense = PageRankVariant { config: config.backend(Backend::Dense) }.rank(&graph, &[1.0, 0.0]);
+    assert_close(&dense, &[2.0 / 3.0, 1.0 / 3.0]);
+    assert_close(&dense, &edge_list);
+}
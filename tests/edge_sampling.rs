@@ -0,0 +1,46 @@
+// `style edge-sampling` thins out the edges drawn in frames of dense graphs; the ranks still come
+// from every edge, and the edges left out are written invisible like edges without weight.
+use trust_flow::dot::{write_dot, FrameRender};
+use trust_flow::scenario::Scenario;
+use trust_flow::style::{ColorScale, EdgeSampling};
+use trust_flow::types::Time;
+
+// Node 0 trusts 1, 2 and 3 with falling weights, and 3 trusts 2 a little
+const SCENARIO: &str = "nodes 4\nexpert 0\ncategory a weight 3\ncategory b weight 2\ncategory c weight 0.5\nedge 0 1 0 category=a\nedge 0 2 0 category=b\nedge 0 3 0\nedge 3 2 0 category=c\n";
+
+// The edge lines of the scenario's frame at time 0
+fn edge_lines(text: &str) -> Vec<String> {
+    let scenario = Scenario::parse(text).unwrap();
+    let time = Time(0.0);
+    let ranks = vec![0.25; scenario.num_of_nodes];
+    let scale = ColorScale::new(&scenario.style, &ranks, std::slice::from_ref(&ranks));
+    let mut dot = Vec::new();
+    let (weights, labels, layout, pinned) = (scenario.edge_weights(time), scenario.node_labels(), scenario.layout(), scenario.pinned_nodes());
+    let frame = FrameRender::new(&ranks, &scenario.edges, &weights, &labels, &layout).experts(&scenario.experts).pinned(&pinned);
+    write_dot(&mut dot, &frame, &scenario.style, &scale).unwrap();
+    String::from_utf8(dot).unwrap().lines().filter(|line| line.contains(" -> ")).map(|line| line.trim().to_string()).collect()
+}
+
+fn drawn(lines: &[String]) -> Vec<&str> {
+    lines.iter().filter(|line| !line.contains("invis")).map(|line| &line[..line.find(" [").unwrap()]).collect()
+}
+
+#[test]
+fn every_edge_is_drawn_by_default() {
+    assert_eq!(drawn(&edge_lines(SCENARIO)), ["0 -> 1", "0 -> 2", "0 -> 3", "3 -> 2"]);
+}
+
+#[test]
+fn top_per_node_keeps_the_heaviest_edges_of_every_node() {
+    // 0 keeps 0 -> 1, 2 keeps 0 -> 2 and 3 keeps 0 -> 3; nobody keeps 3 -> 2
+    let lines = edge_lines(&format!("{}style edge-sampling top 1\n", SCENARIO));
+    assert_eq!(drawn(&lines), ["0 -> 1", "0 -> 2", "0 -> 3"]);
+    assert_eq!(lines.len(), 4, "left out edges stay in the frame, invisible");
+}
+
+#[test]
+fn min_weight_keeps_the_edges_at_or_above_it() {
+    assert_eq!(drawn(&edge_lines(&format!("{}style edge-sampling min 2\n", SCENARIO))), ["0 -> 1", "0 -> 2"]);
+    assert_eq!(EdgeSampling::parse("min 0.5"), Ok(EdgeSampling::MinWeight(0.5)));
+    assert!(EdgeSampling::parse("most 3").is_err());
+}
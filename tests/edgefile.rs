@@ -0,0 +1,109 @@
+// Memory-mapped edge files: converted events read back as the same edges, and ranking them out of
+// core gives the ranks of the in-memory ranking of the plain events, from the library and the CLI
+use std::env;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use trust_flow::edgefile::{convert_events, EdgeFile, OutOfCoreRanker};
+use trust_flow::graph::Edge;
+use trust_flow::input::{parse_edge_events, EventFormat, EventParser};
+use trust_flow::rank::{DanglingPolicy, OutflowNormalization, PageRankVariant, RankConfig, RankingAlgorithm};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+// Weighted events with a parallel edge, a self-loop, a dangling node 4 and creation times between ticks
+const SORTED: &str = "source,target,time,weight\n0,1,0,1\n1,2,1,2\n2,0,1,1\n1,2,2.5,1\n3,3,3,1\n2,3,4,0.5\n3,4,6,1\n";
+const UNSORTED: &str = "source,target,time,weight\n3,4,6,1\n1,2,1,2\n0,1,0,1\n2,3,4,0.5\n2,0,1,1\n1,2,2.5,1\n3,3,3,1\n";
+const TIMES: [f64; 5] = [0.0, 1.0, 2.5, 4.0, 10.0];
+
+fn path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("trust-flow-edgefile-{}-{}.bin", name, std::process::id()))
+}
+
+fn convert(events: &str, name: &str) -> (EdgeFile, PathBuf) {
+    let path = path(name);
+    let parser = EventParser::new(EventFormat::Csv);
+    assert_eq!(convert_events(&mut Cursor::new(events), parser, path.to_str().unwrap(), 0).unwrap(), 7);
+    (EdgeFile::open(path.to_str().unwrap()).unwrap(), path)
+}
+
+fn plain(events: &str) -> Vec<Edge> {
+    parse_edge_events(events, EventParser::new(EventFormat::Csv)).unwrap().0
+}
+
+fn fields(edges: impl IntoIterator<Item = Edge>) -> Vec<(NodeId, NodeId, f64, f64)> {
+    edges.into_iter().map(|e| (e.source, e.target, e.time_of_creation.value(), e.weight)).collect()
+}
+
+fn assert_ranks(actual: &[f64], expected: &[f64], context: &str) {
+    assert!(actual.len() == expected.len() && actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} instead of {:?} ({})", actual, expected, context);
+}
+
+#[test]
+fn converted_events_read_back_unchanged() {
+    for (events, name, sorted) in [(SORTED, "sorted", true), (UNSORTED, "unsorted", false)] {
+        let (file, path) = convert(events, name);
+        assert_eq!((file.num_of_nodes, file.len(), file.sorted_by_time), (5, 7, sorted), "{}", name);
+        assert_eq!(fields(file.edges()), fields(plain(events)), "{}", name);
+        drop(file);
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn out_of_core_ranks_match_the_plain_read() {
+    for (events, name) in [(SORTED, "sorted-ranks"), (UNSORTED, "unsorted-ranks")] {
+        let (file, path) = convert(events, name);
+        let scenario = Scenario::new("plain", 5, vec![NodeId(0)], plain(events));
+        for outflow_normalization in [OutflowNormalization::Lifetime, OutflowNormalization::Current, OutflowNormalization::None] {
+            for dangling_policy in [DanglingPolicy::Uniform, DanglingPolicy::Teleport, DanglingPolicy::Retain] {
+                let config = RankConfig::new().outflow_normalization(outflow_normalization).dangling_policy(dangling_policy);
+                let in_memory = PageRankVariant { config: config.clone() };
+                // Without a decay table, and with one that only covers the frames at whole times
+                let rankers = [OutOfCoreRanker::new(&file, &config, scenario.decay_constant), OutOfCoreRanker::new(&file, &config, scenario.decay_constant).decay_table_until(Time(10.0))];
+                for time in TIMES.map(Time) {
+                    let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+                    let expected = in_memory.rank(&graph, &scenario.teleportation_targets(time));
+                    for ranker in &rankers {
+                        let context = format!("{} at {}, {:?}", name, time, config);
+                        assert_ranks(&ranker.rank(time, &scenario.teleportation_targets(time)), &expected, &context);
+                    }
+                }
+            }
+        }
+        drop(file);
+        fs::remove_file(path).unwrap();
+    }
+}
+
+fn trust_flow(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_trust-flow")).args(args).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+#[test]
+fn edge_file_snapshots_match_pipe_snapshots() {
+    let path = path("cli");
+    let path = path.to_str().unwrap();
+    trust_flow(&["convert-edges", "-", path, "--input-format", "csv"], UNSORTED);
+    let frame_times = TIMES.map(|t| t.to_string()).join(",");
+    let mapped = trust_flow(&["--edge-file", path, "--frame-times", &frame_times, "--expert", "0"], "");
+    let piped = trust_flow(&["--pipe", "--input-format", "csv", "--frame-times", &frame_times, "--expert", "0"], UNSORTED);
+    let snapshots = |output: &Output| -> Vec<Vec<f64>> {
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.split_once("\"ranks\":[").unwrap().1.trim_end_matches("]}").split(',').map(|r| r.parse().unwrap()).collect())
+            .collect()
+    };
+    let (mapped, piped) = (snapshots(&mapped), snapshots(&piped));
+    assert_eq!(mapped.len(), TIMES.len());
+    for (mapped, piped) in mapped.iter().zip(&piped) {
+        assert_ranks(mapped, piped, "edge file and pipe");
+    }
+    fs::remove_file(path).unwrap();
+}
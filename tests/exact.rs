@@ -0,0 +1,61 @@
+// --solver exact against fixed points worked out by hand, and against the power iteration run to
+// convergence on a graph with weights, parallel edges and a self-loop
+use trust_flow::graph::{compile_graph, CompiledGraph, Edge, GraphOptions};
+use trust_flow::rank::{exact_ranks, DanglingPolicy, PageRankVariant, RankConfig, RankingAlgorithm, Solver};
+use trust_flow::types::{NodeId, Time};
+
+const TOLERANCE: f64 = 1e-12;
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+fn graph(num_of_nodes: usize, edges: &[Edge]) -> CompiledGraph {
+    compile_graph(edges, &edges.iter().map(|e| e.weight).collect::<Vec<_>>(), num_of_nodes, &GraphOptions::default()).unwrap()
+}
+
+fn assert_ranks(actual: &[f64], expected: &[f64], what: &str) {
+    assert_eq!(actual.len(), expected.len(), "{}", what);
+    assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < TOLERANCE), "{}: {:?} instead of {:?}", what, actual, expected);
+}
+
+#[test]
+fn two_node_cycle() {
+    // r0 = 1/2 + r1/2 and r1 = r0/2 at a damping factor of 1/2
+    let ranks = exact_ranks(&graph(2, &[edge(0, 1, 1.0), edge(1, 0, 1.0)]), &[1.0, 0.0], &RankConfig::new()).unwrap();
+    assert_ranks(&ranks, &[2.0 / 3.0, 1.0 / 3.0], "cycle");
+}
+
+#[test]
+fn dangling_rank_goes_where_the_policy_sends_it() {
+    // 0 → 1 → 2, with node 2 dangling and all teleportation at node 0
+    let chain = graph(3, &[edge(0, 1, 1.0), edge(1, 2, 1.0)]);
+    for (policy, expected) in [
+        (DanglingPolicy::Teleport, [4.0 / 7.0, 2.0 / 7.0, 1.0 / 7.0]),
+        (DanglingPolicy::Retain, [0.5, 0.25, 0.25]),
+        (DanglingPolicy::Uniform, [9.0 / 17.0, 5.0 / 17.0, 3.0 / 17.0]),
+    ] {
+        let ranks = exact_ranks(&chain, &[1.0, 0.0, 0.0], &RankConfig::new().dangling_policy(policy)).unwrap();
+        assert_ranks(&ranks, &expected, &format!("{:?}", policy));
+    }
+}
+
+#[test]
+fn exact_ranks_match_the_converged_iteration() {
+    let edges = [edge(0, 1, 2.0), edge(0, 1, 1.0), edge(0, 2, 0.5), edge(1, 2, 1.0), edge(2, 0, 1.0), edge(2, 2, 1.0), edge(3, 1, 1.0)];
+    let graph = graph(4, &edges);
+    let targets = [0.5, 0.0, 0.25, 0.25];
+    for policy in [DanglingPolicy::Uniform, DanglingPolicy::Teleport, DanglingPolicy::Retain] {
+        let config = RankConfig::new().damping_factor(0.85).dangling_policy(policy);
+        let iterated = PageRankVariant { config: config.clone().iterations(500).tolerance(1e-15) }.rank(&graph, &targets);
+        let exact = PageRankVariant { config: config.solver(Solver::Exact) }.rank(&graph, &targets);
+        assert_ranks(&exact, &iterated, &format!("{:?}", policy));
+    }
+}
+
+#[test]
+fn singular_systems_have_no_exact_solution() {
+    // Without damping, the cycle's system I - F has no unique solution
+    let cycle = graph(2, &[edge(0, 1, 1.0), edge(1, 0, 1.0)]);
+    assert_eq!(exact_ranks(&cycle, &[1.0, 0.0], &RankConfig::new().damping_factor(1.0)), None);
+}
@@ -0,0 +1,39 @@
+// Aging experts lose their share of teleportation to the baseline as their designation or their
+// latest edge grows older
+use trust_flow::graph::Edge;
+use trust_flow::scenario::{AgingStart, ExpertAging, Scenario};
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, time: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight: 1.0 }
+}
+
+// Expert 0 in the chain 0 → 1 → 2, halving its weight every time unit
+fn scenario(since: AgingStart, edges: Vec<Edge>) -> Scenario {
+    let mut scenario = Scenario::new("aging", 3, vec![NodeId(0)], edges);
+    scenario.expert_aging = vec![ExpertAging { node: NodeId(0), decay_constant: std::f64::consts::LN_2, since }];
+    scenario
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} instead of {:?}", actual, expected);
+}
+
+#[test]
+fn experts_age_from_their_designation() {
+    let scenario = scenario(AgingStart::Designation, vec![edge(0, 1, 0.0), edge(1, 2, 0.0)]);
+    assert_eq!(scenario.expert_freshness(NodeId(0), Time(0.0)), 1.0);
+    assert!((scenario.expert_freshness(NodeId(0), Time(2.0)) - 0.25).abs() < 1e-12);
+    assert_eq!(scenario.expert_freshness(NodeId(1), Time(2.0)), 1.0);
+    // The expert keeps a quarter of its 0.8, and the other 0.6 is spread like the baseline 0.2
+    let baseline = 0.2 / 3.0;
+    assert_close(&scenario.teleportation_targets(Time(2.0)), &[baseline + 0.4, baseline + 0.2, baseline + 0.2]);
+}
+
+#[test]
+fn active_experts_age_from_their_latest_edge() {
+    let scenario = scenario(AgingStart::Activity, vec![edge(0, 1, 0.0), edge(1, 2, 0.0), edge(0, 2, 1.0)]);
+    assert!((scenario.expert_freshness(NodeId(0), Time(2.0)) - 0.5).abs() < 1e-12);
+    let baseline = 0.2 / 3.0;
+    assert_close(&scenario.teleportation_targets(Time(2.0)), &[baseline + 0.4 + 0.4 / 3.0, baseline + 0.4 / 3.0, baseline + 0.4 / 3.0]);
+}
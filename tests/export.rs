@@ -0,0 +1,86 @@
+// Exporters write the artifacts of a finished run through a sink; library users register their
+// own formats next to the built-in DOT, JSON and CSV ones.
+use std::io;
+
+use trust_flow::export::{ExportedRun, Exporter, ExporterRegistry};
+use trust_flow::graph::Edge;
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::sink::{MemorySink, Sink};
+use trust_flow::tikz::TikzExporter;
+use trust_flow::types::{FrameIdx, NodeId, Time};
+
+// One line per frame with the top node
+struct TopNodeExporter;
+
+impl Exporter for TopNodeExporter {
+    fn format(&self) -> &str {
+        "top"
+    }
+
+    fn export(&self, sink: &mut dyn Sink, run: &ExportedRun) -> io::Result<()> {
+        let mut text = String::new();
+        for ranks in run.output.history.frames() {
+            let top = (0..ranks.len()).max_by(|&a, &b| ranks[a].total_cmp(&ranks[b])).unwrap();
+            text.push_str(&format!("{}\n", run.scenario.node_label(NodeId(top))));
+        }
+        sink.write(&format!("{}/top.txt", run.scenario.name), text.as_bytes())
+    }
+}
+
+fn exported(formats: &[&str]) -> Result<MemorySink, String> {
+    let edges = vec![
+        Edge { source: NodeId(0), target: NodeId(1), time_of_creation: Time(0.0), weight: 1.0 },
+        Edge { source: NodeId(1), target: NodeId(2), time_of_creation: Time(1.0), weight: 1.0 },
+    ];
+    let scenario = Scenario::new("chain", 3, vec![NodeId(0)], edges);
+    let algorithm = PageRankVariant { config: RankConfig::default() };
+    let output = run_scenario(&scenario, &algorithm, &[Time(0.0), Time(1.0)], &scenario.graph_options);
+    let mut registry = ExporterRegistry::new();
+    registry.register(Box::new(TopNodeExporter));
+    let mut sink = MemorySink::new();
+    registry.export(&mut sink, &ExportedRun { scenario: &scenario, output: &output, algorithm: "test" }, formats)?;
+    Ok(sink)
+}
+
+#[test]
+fn custom_exporters_run_next_to_the_built_in_ones() {
+    let sink = exported(&["dot", "json", "csv", "top"]).unwrap();
+    let files: Vec<&str> = sink.files.keys().map(String::as_str).collect();
+    assert_eq!(files, ["chain.json", "chain/frame_000.dot", "chain/frame_001.dot", "chain/ranks.csv", "chain/top.txt"]);
+    assert!(String::from_utf8_lossy(&sink.files["chain/frame_001.dot"]).starts_with("digraph G {"));
+    assert_eq!(sink.files["chain/top.txt"].split(|&b| b == b'\n').count(), 3);
+}
+
+#[test]
+fn unknown_formats_are_reported() {
+    let error = exported(&["svg"]).unwrap_err();
+    assert!(error.contains("dot, json, csv, tikz, top"), "{}", error);
+}
+
+#[test]
+fn tikz_figures_match_the_dot_frames() {
+    let sink = exported(&["tikz"]).unwrap();
+    let tex = String::from_utf8_lossy(&sink.files["chain/frame_001.tex"]).into_owned();
+    assert!(tex.contains("\\documentclass[tikz, border=2pt]{standalone}"), "{}", tex);
+    let dot = String::from_utf8_lossy(&exported(&["dot"]).unwrap().files["chain/frame_001.dot"]).into_owned();
+    // Same fill color and position for the expert, drawn with the expert border
+    let fill = dot.lines().find(|line| line.starts_with("  0 [")).unwrap().split("fillcolor=\"#").nth(1).unwrap()[..6].to_string();
+    assert!(tex.contains(&format!("\\definecolor{{rank0}}{{HTML}}{{{}}}", fill)), "{}", tex);
+    assert!(tex.contains("(n0) at (1.00, 0.00)"), "{}", tex);
+    assert!(tex.contains("draw=expert, line width=8pt"), "{}", tex);
+    assert_eq!(tex.matches("\\draw[->").count(), 2);
+}
+
+#[test]
+fn tikz_frames_can_be_selected() {
+    let edges = vec![Edge { source: NodeId(0), target: NodeId(1), time_of_creation: Time(0.0), weight: 1.0 }];
+    let scenario = Scenario::new("pair", 2, vec![NodeId(0)], edges);
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::default() }, &[Time(0.0), Time(1.0), Time(2.0)], &scenario.graph_options);
+    let run = ExportedRun { scenario: &scenario, output: &output, algorithm: "test" };
+    let mut sink = MemorySink::new();
+    TikzExporter { frames: vec![FrameIdx(2)] }.export(&mut sink, &run).unwrap();
+    assert_eq!(sink.files.keys().collect::<Vec<_>>(), ["pair/frame_002.tex"]);
+    assert!(TikzExporter { frames: vec![FrameIdx(3)] }.export(&mut sink, &run).is_err());
+}
@@ -0,0 +1,88 @@
+// The C interface ranks like the library, reports misuse as statuses with a message, and the
+// header declares every function it exports.
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::ptr;
+
+use trust_flow::ffi::*;
+use trust_flow::graph::Edge;
+use trust_flow::rank::{PageRankVariant, RankConfig, RankingAlgorithm};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(tf_last_error()) }.to_str().unwrap().to_string()
+}
+
+#[test]
+fn ranks_match_the_library() {
+    let edges = [(0, 1, 0.0, 1.0), (1, 2, 1.0, 2.0), (2, 0, 2.0, 1.0), (1, 3, 2.5, 1.0)];
+    let graph = tf_graph_new(4);
+    let mut scores = [0.0; 4];
+    unsafe {
+        assert_eq!(tf_graph_add_expert(graph, 0), TfStatus::Ok);
+        assert_eq!(tf_graph_set_decay(graph, 0.2), TfStatus::Ok);
+        for &(source, target, time, weight) in &edges {
+            assert_eq!(tf_graph_add_edge(graph, source, target, time, weight), TfStatus::Ok);
+        }
+        assert_eq!(tf_graph_num_of_nodes(graph), 4);
+        assert_eq!(tf_graph_rank(graph, 3.0, ptr::null()), TfStatus::Ok);
+        assert_eq!(tf_graph_scores(graph, scores.as_mut_ptr(), scores.len()), TfStatus::Ok);
+    }
+
+    let edges = edges.iter().map(|&(source, target, time, weight)| Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight }).collect();
+    let mut scenario = Scenario::new("library", 4, vec![NodeId(0)], edges);
+    scenario.decay_constant = 0.2;
+    let frame = scenario.frame_graph(Time(3.0), &scenario.edge_weights(Time(3.0))).unwrap();
+    let expected = PageRankVariant { config: RankConfig::new() }.rank(&frame, &scenario.teleportation_targets(Time(3.0)));
+    assert_eq!(scores.to_vec(), expected);
+
+    // Other algorithms by their id
+    let id = CString::new("cohits").unwrap();
+    unsafe {
+        assert_eq!(tf_graph_rank(graph, 3.0, id.as_ptr()), TfStatus::Ok);
+        tf_graph_free(graph);
+    }
+}
+
+#[test]
+fn misuse_is_reported_with_a_message() {
+    let graph = tf_graph_new(2);
+    let mut scores = [0.0; 1];
+    unsafe {
+        assert_eq!(tf_graph_scores(graph, scores.as_mut_ptr(), 2), TfStatus::NotRanked);
+        assert_eq!(tf_graph_add_edge(graph, 0, 2, 0.0, 1.0), TfStatus::InvalidArgument);
+        assert!(last_error().contains("outside 0..2"), "{}", last_error());
+        assert_eq!(tf_graph_add_edge(graph, 0, 1, 0.0, -1.0), TfStatus::InvalidArgument);
+        assert_eq!(tf_graph_add_expert(graph, 5), TfStatus::InvalidArgument);
+        assert_eq!(tf_graph_set_decay(graph, f64::NAN), TfStatus::InvalidArgument);
+        let unknown = CString::new("no-such-algorithm").unwrap();
+        assert_eq!(tf_graph_rank(graph, 0.0, unknown.as_ptr()), TfStatus::InvalidArgument);
+        assert!(last_error().contains("no-such-algorithm"), "{}", last_error());
+
+        assert_eq!(tf_graph_add_edge(graph, 0, 1, 0.0, 1.0), TfStatus::Ok);
+        assert_eq!(tf_graph_rank(graph, 0.0, ptr::null()), TfStatus::Ok);
+        assert_eq!(tf_graph_scores(graph, scores.as_mut_ptr(), scores.len()), TfStatus::InvalidArgument);
+        assert_eq!(tf_graph_scores(graph, ptr::null_mut(), 2), TfStatus::NullPointer);
+
+        assert_eq!(tf_graph_add_edge(ptr::null_mut(), 0, 1, 0.0, 1.0), TfStatus::NullPointer);
+        assert_eq!(tf_graph_num_of_nodes(ptr::null()), 0);
+        tf_graph_free(ptr::null_mut());
+        tf_graph_free(graph);
+    }
+}
+
+#[test]
+fn the_header_declares_every_exported_function() {
+    let source = fs::read_to_string("src/ffi.rs").unwrap();
+    let header = fs::read_to_string("include/trust_flow.h").unwrap();
+    let exported: Vec<&str> = source.lines().filter_map(|line| line.split("extern \"C\" fn ").nth(1)).map(|rest| rest.split('(').next().unwrap()).collect();
+    assert!(exported.len() >= 9, "{:?}", exported);
+    for function in exported {
+        assert!(header.contains(&format!(" {}(", function)) || header.contains(&format!("*{}(", function)), "include/trust_flow.h lacks {}; regenerate it with cbindgen", function);
+    }
+    // And describes them as the source does
+    for doc in source.lines().filter_map(|line| line.trim_start().strip_prefix("/// ")) {
+        assert!(header.contains(&format!("// {}\n", doc)), "include/trust_flow.h lacks \"{}\"; regenerate it with cbindgen", doc);
+    }
+}
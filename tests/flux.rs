@@ -0,0 +1,36 @@
+// Edge flux: the damped rank every edge carries in one step, split over the scenario edges a
+// compiled edge merges by their weights
+use trust_flow::flux::scenario_edge_flux;
+use trust_flow::graph::{compile_graph, Edge, GraphOptions, ParallelEdgePolicy};
+use trust_flow::rank::RankConfig;
+use trust_flow::registry::PAGERANK_VARIANT;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} instead of {:?}", actual, expected);
+}
+
+#[test]
+fn flux_follows_the_rank_and_the_edge_fractions() {
+    // 0 endorses 1 twice, with weights 1 and 3, and 1 endorses 0; the ranks are (2/3, 1/3)
+    let edges = [edge(0, 1, 1.0), edge(0, 1, 3.0), edge(1, 0, 1.0)];
+    let weights: Vec<f64> = edges.iter().map(|e| e.weight).collect();
+    let algorithm = PAGERANK_VARIANT.create(&RankConfig::new().iterations(200).tolerance(1e-15));
+    for (parallel_edges, compiled) in [
+        (ParallelEdgePolicy::KeepAll, vec![1.0 / 12.0, 0.25, 1.0 / 6.0]),
+        (ParallelEdgePolicy::SumWeights, vec![1.0 / 3.0, 1.0 / 6.0]),
+    ] {
+        let options = GraphOptions { parallel_edges, ..GraphOptions::default() };
+        let graph = compile_graph(&edges, &weights, 2, &options).unwrap();
+        let ranks = algorithm.rank(&graph, &[1.0, 0.0]);
+        assert_close(&ranks, &[2.0 / 3.0, 1.0 / 3.0]);
+        let flux = algorithm.edge_flux(&graph, &ranks).unwrap();
+        assert_close(&flux, &compiled);
+        assert_close(&scenario_edge_flux(&graph, &flux, &weights), &[1.0 / 12.0, 0.25, 1.0 / 6.0]);
+    }
+}
@@ -0,0 +1,34 @@
+// --focus: the nodes within a number of hops of the focus node in either direction, renumbered in
+// their original order along with the edges among them
+use trust_flow::focus::FocusView;
+use trust_flow::graph::Edge;
+use trust_flow::types::{EdgeId, NodeId, Time};
+
+fn edge(source: usize, target: usize) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight: 1.0 }
+}
+
+// The path 0 → 1 → 2 → 3 → 4 and 5 → 2
+fn edges() -> Vec<Edge> {
+    vec![edge(0, 1), edge(1, 2), edge(2, 3), edge(3, 4), edge(5, 2)]
+}
+
+#[test]
+fn the_view_holds_the_neighborhood() {
+    let view = FocusView::new(6, &edges(), &[1.0; 5], NodeId(2), 1);
+    assert_eq!(view.nodes, [NodeId(1), NodeId(2), NodeId(3), NodeId(5)]);
+    assert_eq!(view.distances, [1, 0, 1, 1]);
+    assert_eq!(view.edges, [EdgeId(1), EdgeId(2), EdgeId(4)]);
+    assert_eq!((view.node(NodeId(5)), view.node(NodeId(0))), (Some(NodeId(3)), None));
+    let renumbered: Vec<(usize, usize)> = view.edge_list(&edges()).iter().map(|e| (e.source.index(), e.target.index())).collect();
+    assert_eq!(renumbered, [(0, 1), (1, 2), (3, 1)]);
+    assert_eq!(view.node_values(&[0.0, 0.1, 0.2, 0.3, 0.4, 0.5]), [0.1, 0.2, 0.3, 0.5]);
+}
+
+#[test]
+fn edges_without_weight_lead_nowhere() {
+    let view = FocusView::new(6, &edges(), &[1.0, 1.0, 0.0, 1.0, 1.0], NodeId(2), 2);
+    assert_eq!(view.nodes, [NodeId(0), NodeId(1), NodeId(2), NodeId(5)]);
+    assert_eq!(view.distances, [2, 1, 0, 1]);
+    assert_eq!(FocusView::new(6, &edges(), &[1.0; 5], NodeId(4), 0).nodes, [NodeId(4)]);
+}
@@ -0,0 +1,125 @@
+// Golden-file tests: the DOT frames and JSON the binary emits for the bundled example scenario must
+// not change unnoticed, since downstream tooling consumes them. After an intended change of the
+// output, `UPDATE_GOLDEN=1 cargo test --test golden` rewrites the files in tests/golden.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SCENARIO: &str = "trust-flow-example";
+
+fn golden_folder() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(SCENARIO)
+}
+
+// Runs the example scenario with the HTML viewer, rank diffs and the extra arguments, returns the output folder
+fn run_example(name: &str, extra: &[&str]) -> PathBuf {
+    let output = env::temp_dir().join(format!("trust-flow-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&output);
+    let scenario = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios").join(format!("{}.scenario", SCENARIO));
+    let status = Command::new(env!("CARGO_BIN_EXE_trust-flow"))
+        .arg("--scenario").arg(&scenario)
+        .arg("--output").arg(&output)
+        .args(["--export", "html", "--diff-threshold", "0.01", "--quiet"])
+        .args(extra)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .expect("failed to run trust-flow");
+    assert!(status.success(), "trust-flow exited with {}", status);
+    output
+}
+
+// The JSON embedded in the HTML viewer, one line
+fn viewer_json(html: &str) -> String {
+    let start = html.find("const data = ").expect("viewer without data") + "const data = ".len();
+    let end = start + html[start..].find(";\n").expect("unterminated viewer data");
+    format!("{}\n", &html[start..end])
+}
+
+// The checked files by name: every DOT frame, the rank diffs and the viewer data
+fn emitted_files(output: &Path) -> Vec<(String, String)> {
+    let folder = output.join(SCENARIO);
+    let mut names: Vec<String> = fs::read_dir(&folder)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".dot") || name.ends_with(".jsonl"))
+        .collect();
+    names.sort();
+    let mut files: Vec<(String, String)> = names.into_iter().map(|name| (name.clone(), fs::read_to_string(folder.join(&name)).unwrap())).collect();
+    let html = fs::read_to_string(output.join(format!("{}.html", SCENARIO))).unwrap();
+    files.push(("viewer.json".to_string(), viewer_json(&html)));
+    files
+}
+
+#[test]
+fn example_output_matches_golden_files() {
+    let output = run_example("golden", &[]);
+    let files = emitted_files(&output);
+    fs::remove_dir_all(&output).unwrap();
+    let golden = golden_folder();
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        let _ = fs::remove_dir_all(&golden);
+        fs::create_dir_all(&golden).unwrap();
+        for (name, content) in &files {
+            fs::write(golden.join(name), content).unwrap();
+        }
+        return;
+    }
+
+    let mut expected: Vec<String> = fs::read_dir(&golden)
+        .unwrap_or_else(|_| panic!("no golden files in {}; create them with UPDATE_GOLDEN=1", golden.display()))
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    expected.sort();
+    let emitted: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(emitted, expected, "the example emits different files than the golden ones");
+    for (name, content) in &files {
+        let golden_content = fs::read_to_string(golden.join(name)).unwrap();
+        if let Some((line, (got, want))) = content.lines().zip(golden_content.lines()).enumerate().find(|(_, (got, want))| got != want) {
+            panic!("{} differs from its golden file at line {}:\n  emitted: {}\n  golden:  {}", name, line + 1, got, want);
+        }
+        assert_eq!(content.lines().count(), golden_content.lines().count(), "{} has a different number of lines than its golden file", name);
+    }
+}
+
+#[test]
+fn thumbnails_leave_the_full_frames_alone() {
+    let output = run_example("thumbnails", &["--thumbnails"]);
+    let files = emitted_files(&output);
+    let folder = output.join(SCENARIO);
+    let index = fs::read_to_string(folder.join("frames.json")).unwrap();
+    let manifest = fs::read_to_string(output.join("manifest.json")).unwrap();
+    let thumbnails: Vec<String> = files.iter().map(|(name, _)| name.clone()).filter(|name| name.starts_with("frame_")).map(|name| format!("{}/thumbnails/{}", SCENARIO, name)).collect();
+    let thumbnail = fs::read_to_string(output.join(&thumbnails[0])).unwrap();
+    fs::remove_dir_all(&output).unwrap();
+    for (name, content) in &files {
+        assert_eq!(content, &fs::read_to_string(golden_folder().join(name)).unwrap(), "{} changed with thumbnails", name);
+    }
+    for path in &thumbnails {
+        assert!(index.contains(&format!("\"thumbnail\":\"{}\"", path)), "frames.json does not list {}", path);
+        assert!(manifest.contains(&format!("\"{}\"", path)), "the manifest does not list {}", path);
+    }
+    assert!(thumbnail.contains("size=\"3,3\"") && !thumbnail.contains("legend"), "thumbnail keeps full-size details:\n{}", thumbnail);
+}
+
+#[test]
+fn frame_names_are_listed_with_their_times() {
+    let output = run_example("frame-names", &["--frame-names", "{scenario}-{index:4}-t{time}"]);
+    let folder = output.join(SCENARIO);
+    let index = fs::read_to_string(folder.join("frames.json")).unwrap();
+    let first = folder.join(format!("{}-0000-t0.dot", SCENARIO)).exists();
+    let default = folder.join("frame_000.dot").exists();
+    fs::remove_dir_all(&output).unwrap();
+    assert!(first && !default, "frames are not named from the template");
+    assert!(index.contains(&format!("{{\"frame\":1,\"time\":0,\"full\":\"{}/{}-0000-t0.dot\"}}", SCENARIO, SCENARIO)), "{}", index);
+    assert!(!index.contains("thumbnail"), "{}", index);
+}
+
+#[test]
+fn ghost_edges_are_only_flagged_when_enabled() {
+    let output = run_example("ghost-edges", &["--ghost-edges"]);
+    let html = fs::read_to_string(output.join(format!("{}.html", SCENARIO))).unwrap();
+    fs::remove_dir_all(&output).unwrap();
+    assert!(viewer_json(&html).trim_end().ends_with(",\"ghost_edges\":true}"), "the viewer data does not enable ghost edges");
+}
@@ -0,0 +1,57 @@
+// GraphML and GEXF inputs: nodes in document order, experts, labels and edge times read from small
+// fixtures, escaped text and CDATA included, and malformed documents rejected with a message
+use trust_flow::graph::Edge;
+use trust_flow::graphml::read_graphml;
+use trust_flow::gexf::read_gexf;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::NodeId;
+
+fn fixture(name: &str) -> Scenario {
+    Scenario::from_file(&format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+}
+
+fn edges(scenario: &Scenario) -> Vec<(usize, usize, f64)> {
+    scenario.edges.iter().map(|e: &Edge| (e.source.index(), e.target.index(), e.time_of_creation.value())).collect()
+}
+
+fn name(scenario: &Scenario, node: usize) -> Option<&str> {
+    scenario.node_info(NodeId(node)).and_then(|info| info.name.as_deref())
+}
+
+#[test]
+fn graphml_fixture() {
+    let scenario = fixture("small.graphml");
+    assert_eq!((scenario.name.as_str(), scenario.num_of_nodes, scenario.seed), ("small", 3, 7));
+    assert_eq!(scenario.experts, [NodeId(0)]);
+    assert_eq!((name(&scenario, 0), name(&scenario, 1), name(&scenario, 2)), (Some("Alice & Co"), Some("Bob <B>"), None));
+    assert_eq!(edges(&scenario), [(0, 1, 1.5), (1, 2, 0.0)]);
+    assert_eq!(scenario.edge_sources[0].event_id.as_deref(), Some("e0"));
+    assert_eq!(scenario.edge_sources[0].metadata, [("kind".to_string(), "vouch".to_string())]);
+}
+
+#[test]
+fn gexf_fixture() {
+    let scenario = fixture("small.gexf");
+    assert_eq!((scenario.num_of_nodes, scenario.seed), (3, 11));
+    assert_eq!(scenario.experts, [NodeId(0)]);
+    // Node b's label is only its index
+    assert_eq!((name(&scenario, 0), name(&scenario, 1), name(&scenario, 2)), (Some("Ann \"A\""), None, Some("Cy")));
+    assert_eq!(scenario.node_info(NodeId(0)).and_then(|info| info.category.as_deref()), Some("core"));
+    assert_eq!(edges(&scenario), [(0, 1, 2.0), (1, 2, 3.5), (2, 0, 0.0)]);
+}
+
+#[test]
+fn malformed_documents_are_rejected() {
+    let graph = "<graphml><graph><node id=\"a\"/><node id=\"b\"/><edge source=\"a\" target=\"b\"/></graph></graphml>";
+    assert_eq!(read_graphml(graph, "ok").unwrap().edges.len(), 1);
+    for (text, message) in [
+        ("<graphml><graph></graphml>", "invalid GraphML"),
+        ("<graphml><graph><node id=\"a></graph></graphml>", "invalid GraphML"),
+        ("<graphml/>", "no <graph>"),
+        ("<graphml><graph><edge source=\"a\" target=\"b\"/></graph></graphml>", "unknown source"),
+    ] {
+        let error = read_graphml(text, "bad").err().unwrap();
+        assert!(error.message.contains(message), "{}: {}", text, error.message);
+    }
+    assert!(read_gexf("<gexf><graph><nodes></graph></gexf>", "bad").err().unwrap().message.contains("invalid GEXF"));
+}
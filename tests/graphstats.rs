@@ -0,0 +1,83 @@
+// Edge event statistics count what the data holds and flag what often means it is broken, before
+// anything is ranked.
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use trust_flow::graph::Edge;
+use trust_flow::graphstats::{strongly_connected_components, DataAnomaly, DegreeDistribution, GraphStats};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, time: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight: 1.0 }
+}
+
+#[test]
+fn counts_degrees_and_components() {
+    // A cycle 0 -> 1 -> 2 -> 0 with a tail to 3, a repeated edge and a self-loop at 3
+    let edges = vec![edge(0, 1, 0.0), edge(1, 2, 1.0), edge(2, 0, 2.0), edge(2, 3, 3.0), edge(0, 1, 4.0), edge(3, 3, 5.0)];
+    let stats = GraphStats::new(&Scenario::new("stats", 5, vec![NodeId(0)], edges));
+    assert_eq!((stats.num_of_nodes, stats.num_of_events, stats.num_of_edges), (5, 6, 5));
+    assert_eq!(stats.total_weight, 6.0);
+    assert_eq!(stats.time_span, Some((Time(0.0), Time(5.0))));
+    assert_eq!((stats.out_degree.min, stats.out_degree.max, stats.out_degree.median), (0, 2, 1.0));
+    assert_eq!(stats.in_degree.histogram, vec![1, 3, 1]);
+    // Node 4 is in no event; the self-loop keeps node 3 from dangling
+    assert_eq!(stats.dangling_nodes, 1);
+    assert_eq!((stats.components, stats.largest_component, stats.single_node_components), (3, 3, 2));
+    assert_eq!(stats.anomalies, vec![DataAnomaly::SelfLoops(1), DataAnomaly::IsolatedNodes(1)]);
+}
+
+#[test]
+fn degree_histograms_double_their_buckets() {
+    let degrees = DegreeDistribution::new(&[0, 1, 2, 3, 4, 7, 8, 20]);
+    assert_eq!(degrees.histogram, vec![1, 1, 2, 2, 1, 1]);
+    assert_eq!((degrees.min, degrees.max, degrees.median, degrees.mean), (0, 20, 3.5, 45.0 / 8.0));
+    assert_eq!(DegreeDistribution::new(&[]).histogram, Vec::<usize>::new());
+}
+
+#[test]
+fn long_paths_do_not_overflow_the_stack() {
+    let n = 200_000;
+    let mut edges: Vec<Edge> = (0..n - 1).map(|i| edge(i, i + 1, 0.0)).collect();
+    let components = strongly_connected_components(n, &edges);
+    assert_eq!(components.iter().collect::<std::collections::HashSet<_>>().len(), n);
+    edges.push(edge(n - 1, 0, 0.0));
+    assert!(strongly_connected_components(n, &edges).iter().all(|&c| c == 0));
+}
+
+#[test]
+fn suspicious_data_is_reported() {
+    let mut edges = vec![edge(1, 0, 3.0), edge(1, 0, 3.0), edge(2, 0, 1.0)];
+    // Node 0, the expert, is endorsed by everyone and endorses nobody
+    edges.extend((3..12).map(|source| edge(source, 0, 4.0)));
+    let anomalies = GraphStats::new(&Scenario::new("stats", 12, vec![NodeId(0)], edges)).anomalies;
+    assert!(anomalies.contains(&DataAnomaly::DuplicateEvents(1)), "{:?}", anomalies);
+    assert!(anomalies.contains(&DataAnomaly::OutOfOrder(1)), "{:?}", anomalies);
+    assert!(anomalies.contains(&DataAnomaly::LooksReversed), "{:?}", anomalies);
+    assert!(anomalies.iter().any(|a| matches!(a, DataAnomaly::DominantNode { node: NodeId(0), share } if *share == 1.0)), "{:?}", anomalies);
+}
+
+#[test]
+fn stats_subcommand_reports_an_edge_file() {
+    let folder = env::temp_dir().join(format!("trust-flow-stats-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    let input = folder.join("edges.csv");
+    fs::write(&input, "source,target,time\n0,1,0\n1,0,1\n1,2,2\n2,2,3\n").unwrap();
+    let summary = folder.join("stats.json");
+    let output = Command::new(env!("CARGO_BIN_EXE_trust-flow"))
+        .args(["stats", "--input"])
+        .arg(&input)
+        .arg("--summary")
+        .arg(&summary)
+        .output()
+        .expect("failed to run trust-flow");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.contains("4 edge events, 3 nodes"), "{}", report);
+    assert!(report.contains("self-loops: 1"), "{}", report);
+    let json = fs::read_to_string(&summary).unwrap();
+    assert!(json.starts_with("{\"nodes\":3,\"events\":4,\"edges\":4,"), "{}", json);
+    fs::remove_dir_all(&folder).unwrap();
+}
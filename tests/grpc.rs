@@ -0,0 +1,101 @@
+// A client of the gRPC service submits edges, asks for ranks and follows rank updates; the ranks
+// must be those of a run of the same graph.
+#![cfg(feature = "grpc")]
+use tokio::net::TcpListener;
+use tonic::Code;
+use trust_flow::grpc::proto::trust_flow_client::TrustFlowClient;
+use trust_flow::grpc::proto::{EdgeEvent, GetRanksRequest, StreamRankUpdatesRequest, SubmitEdgesRequest};
+use trust_flow::grpc::{serve, TrustFlowService};
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{FrameIdx, NodeId, Time};
+
+const TOLERANCE: f64 = 1e-12;
+
+type Client = TrustFlowClient<tonic::transport::Channel>;
+
+// Starts a service without edges on a free port and connects to it
+async fn connect() -> Client {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let service = TrustFlowService::new(Scenario::new("grpc", 0, vec![NodeId(0)], Vec::new()), RankConfig::default());
+    tokio::spawn(serve(listener, service));
+    TrustFlowClient::connect(format!("http://{}", address)).await.unwrap()
+}
+
+fn event(source: u64, target: u64, time: f64) -> EdgeEvent {
+    EdgeEvent { source, target, time, weight: None, id: None }
+}
+
+async fn submit(client: &mut Client, events: Vec<EdgeEvent>) -> Result<(u64, u64), Code> {
+    let response = client.submit_edges(SubmitEdgesRequest { events }).await.map_err(|status| status.code())?.into_inner();
+    Ok((response.nodes, response.edges))
+}
+
+// Ranks of the scenario's graph in a run with one frame at the time
+fn expected_ranks(scenario: &str, time: f64) -> Vec<f64> {
+    let scenario = Scenario::parse(scenario).unwrap();
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::default() }, &[Time(time)], &scenario.graph_options);
+    output.history.ranks_at(FrameIdx(0)).to_vec()
+}
+
+fn assert_close(got: &[f64], want: &[f64]) {
+    assert!(got.len() == want.len() && got.iter().zip(want).all(|(a, b)| (a - b).abs() < TOLERANCE), "{:?} != {:?}", got, want);
+}
+
+#[tokio::test]
+async fn ranks_are_those_of_a_run() {
+    let mut client = connect().await;
+    let status = client.get_ranks(GetRanksRequest { time: None, algorithm: String::new() }).await.unwrap_err();
+    assert_eq!(status.code(), Code::FailedPrecondition);
+
+    assert_eq!(submit(&mut client, vec![event(0, 1, 0.0), event(1, 2, 1.0)]).await, Ok((3, 2)));
+    let snapshot = client.get_ranks(GetRanksRequest { time: None, algorithm: String::new() }).await.unwrap().into_inner();
+    assert_eq!((snapshot.frame, snapshot.time), (1, 1.0));
+    assert_close(&snapshot.ranks, &expected_ranks("nodes 3\nexpert 0\nedge 0 1 0\nedge 1 2 1\n", 1.0));
+
+    let earlier = client.get_ranks(GetRanksRequest { time: Some(0.5), algorithm: "pagerank".to_string() }).await.unwrap().into_inner();
+    assert_close(&earlier.ranks, &expected_ranks("nodes 3\nexpert 0\nedge 0 1 0\nedge 1 2 1\n", 0.5));
+}
+
+#[tokio::test]
+async fn invalid_requests_change_nothing() {
+    let mut client = connect().await;
+    assert_eq!(submit(&mut client, vec![event(0, 1, 0.0)]).await, Ok((2, 1)));
+    assert_eq!(submit(&mut client, vec![event(1, 2, 1.0), event(2, 0, f64::NAN)]).await, Err(Code::InvalidArgument));
+    assert_eq!(submit(&mut client, vec![EdgeEvent { weight: Some(0.0), ..event(1, 2, 1.0) }]).await, Err(Code::InvalidArgument));
+    assert_eq!(submit(&mut client, vec![event(1, u64::MAX, 1.0)]).await, Err(Code::InvalidArgument));
+    assert_eq!(submit(&mut client, vec![EdgeEvent { weight: Some(2.0), id: Some("e2".to_string()), ..event(1, 2, 1.0) }]).await, Ok((3, 2)));
+
+    let status = client.get_ranks(GetRanksRequest { time: Some(f64::INFINITY), algorithm: String::new() }).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let status = client.get_ranks(GetRanksRequest { time: None, algorithm: "nonsense".to_string() }).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let status = client.stream_rank_updates(StreamRankUpdatesRequest { every_events: 0, diff_threshold: None, algorithm: String::new() }).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let status = client.stream_rank_updates(StreamRankUpdatesRequest { every_events: 1, diff_threshold: Some(-1.0), algorithm: String::new() }).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn updates_follow_the_submitted_events() {
+    let mut client = connect().await;
+    let mut updates = client.stream_rank_updates(StreamRankUpdatesRequest { every_events: 2, diff_threshold: None, algorithm: String::new() }).await.unwrap().into_inner();
+    let mut changes = client.stream_rank_updates(StreamRankUpdatesRequest { every_events: 1, diff_threshold: Some(0.0), algorithm: String::new() }).await.unwrap().into_inner();
+
+    submit(&mut client, vec![event(0, 1, 0.0)]).await.unwrap();
+    let first = changes.message().await.unwrap().unwrap();
+    assert_eq!((first.frame, first.time), (1, 0.0));
+    let ranks = expected_ranks("nodes 2\nexpert 0\nedge 0 1 0\n", 0.0);
+    assert!(first.ranks.is_empty() && first.changes.len() == 2, "{:?}", first);
+    assert!((first.changes[&1] - ranks[1]).abs() < TOLERANCE);
+
+    submit(&mut client, vec![event(1, 2, 1.0)]).await.unwrap();
+    let snapshot = updates.message().await.unwrap().unwrap();
+    assert_eq!((snapshot.frame, snapshot.time), (1, 1.0), "the first snapshot comes after two events");
+    assert_close(&snapshot.ranks, &expected_ranks("nodes 3\nexpert 0\nedge 0 1 0\nedge 1 2 1\n", 1.0));
+    let second = changes.message().await.unwrap().unwrap();
+    assert_eq!(second.frame, 2);
+    assert!(second.changes.contains_key(&2), "{:?}", second);
+}
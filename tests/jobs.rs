@@ -0,0 +1,91 @@
+// --jobs: frames ranked on several threads give the output of a single-job run, frame by frame and
+// file by file, including under a damping schedule and for warm-started scenarios
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::{continue_scenario_parallel, run_scenario, RunOutput};
+use trust_flow::scenario::Scenario;
+
+const SCENARIO: &str = "name chain\nnodes 5\nexpert 0\nedge 0 1 1\nedge 1 2 2\nedge 2 3 3\nedge 3 0 4\nedge 1 3 5\nedge 3 4 6\nframes from 0 to 12 step 0.5\n";
+
+fn parallel(scenario: &Scenario, jobs: usize) -> RunOutput {
+    let algorithm = PageRankVariant { config: RankConfig::new() };
+    let frame_times = scenario.frame_times();
+    let mut output = RunOutput::new();
+    let mut ranked = Vec::new();
+    continue_scenario_parallel(scenario, &algorithm, &frame_times, &scenario.graph_options, jobs, &mut output, &mut |output| {
+        ranked.push(*output.history.times().last().unwrap());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(ranked, frame_times, "frames are handed on in time order");
+    output
+}
+
+#[test]
+fn parallel_runs_equal_the_sequential_run() {
+    for extra in ["", "damping 0.5..0.9\n", "warm-start 2\nnode-decay 0.05\n"] {
+        let scenario = Scenario::parse(&format!("{}{}", SCENARIO, extra)).unwrap();
+        let sequential = run_scenario(&scenario, &PageRankVariant { config: RankConfig::new() }, &scenario.frame_times(), &scenario.graph_options);
+        assert_eq!(sequential.history.num_of_frames(), 25);
+        for jobs in [1, 2, 4, 7, 32] {
+            let output = parallel(&scenario, jobs);
+            assert_eq!(output.history.times(), sequential.history.times(), "{:?} with {} jobs", extra, jobs);
+            assert_eq!(output.history.frames(), sequential.history.frames(), "{:?} with {} jobs", extra, jobs);
+            assert_eq!(output.edge_weights, sequential.edge_weights, "{:?} with {} jobs", extra, jobs);
+            assert_eq!(output.frame_hashes, sequential.frame_hashes, "{:?} with {} jobs", extra, jobs);
+        }
+    }
+}
+
+// Every file under the folder, with its path relative to `root`
+fn files(folder: &Path, root: &Path) -> Vec<(String, Vec<u8>)> {
+    let mut found = Vec::new();
+    for entry in fs::read_dir(folder).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            found.extend(files(&path, root));
+        } else {
+            found.push(file(&path, root));
+        }
+    }
+    found.sort();
+    found
+}
+
+// Relative path and contents; the manifest's creation time is left out
+fn file(path: &Path, root: &Path) -> (String, Vec<u8>) {
+    let name = path.strip_prefix(root).unwrap().to_string_lossy().to_string();
+    let contents = fs::read(path).unwrap();
+    if name == "manifest.json" {
+        let text = String::from_utf8(contents).unwrap();
+        let (_, files) = text.split_once(",\"files\":").unwrap();
+        return (name, files.as_bytes().to_vec());
+    }
+    (name, contents)
+}
+
+#[test]
+fn parallel_runs_write_the_same_files() {
+    let folder: PathBuf = env::temp_dir().join(format!("trust-flow-jobs-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder).unwrap();
+    fs::write(folder.join("chain.scenario"), SCENARIO).unwrap();
+    for jobs in ["1", "4"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_trust-flow"))
+            .current_dir(&folder)
+            .args(["--scenario", "chain.scenario", "--export", "csv", "--export", "json", "--jobs", jobs, "--output"])
+            .arg(format!("jobs-{}", jobs))
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+    let (single, parallel) = (folder.join("jobs-1"), folder.join("jobs-4"));
+    let (single, parallel) = (files(&single, &single), files(&parallel, &parallel));
+    assert_eq!(single.len(), 25 + 3 + 2, "DOT frames, rank timeline, viewer data, chart and manifest");
+    assert!(single == parallel, "{:?} differ", single.iter().zip(&parallel).filter(|(a, b)| a != b).map(|(a, _)| &a.0).collect::<Vec<_>>());
+    fs::remove_dir_all(&folder).unwrap();
+}
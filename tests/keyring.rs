@@ -0,0 +1,45 @@
+// Key signatures become trust edges from the signing key to the signed one, created on the day
+// the signature was made.
+use trust_flow::keyring::{read_gpg_signatures, read_signature_csv};
+use trust_flow::types::{NodeId, Time};
+
+const LISTING: &str = "\
+pub:-:255:22:AAAA000000000001:864000:::-:::scESC::::::23::0:
+fpr:::::::::A1:
+uid:-::::864000::H1::Alice <alice@example.org>::::::::::0:
+sig:::22:AAAA000000000001:864000::::Alice <alice@example.org>:13x:::::8:
+sig:::22:BBBB000000000002:1728000::::Bob <bob@example.org>:12x:::::8:
+sig:::22:CCCC000000000003:2592000::::Carol \\x3a C <carol@example.org>:10x:::::8:
+sig:::22:BBBB000000000002:3456000::::Bob <bob@example.org>:13x:::::8:
+sub:-:255:18:AAAA0000000000FF:864000::::::e::::::25:
+sig:::22:CCCC000000000003:864000::::Carol:18x:::::8:
+pub:-:255:22:BBBB000000000002:864000:::-:::scESC::::::23::0:
+rev:::22:BBBB000000000002:4320000::::Bob <bob@example.org>:20x:::::8:
+uid:-::::864000::H2::Bob <bob@example.org>::::::::::0:
+sig:::22:AAAA000000000001:1728000::::Alice <alice@example.org>:10x:::::8:
+rev:::22:AAAA000000000001:2592000::::Alice <alice@example.org>:30x:::::8:
+sig:::22:CCCC000000000003:2592000::::Carol \\x3a C <carol@example.org>:11x:::::8:
+";
+
+#[test]
+fn certifications_point_from_signer_to_key() {
+    let scenario = read_gpg_signatures(LISTING, "wot").unwrap();
+    let edges: Vec<_> = scenario.edges.iter().map(|e| (e.source, e.target, e.time_of_creation.value())).collect();
+    // Self-signatures, subkey bindings, the later signature by the same signer and Alice's revoked
+    // certification of Bob do not count
+    assert_eq!(edges, [(NodeId(1), NodeId(0), 20.0), (NodeId(2), NodeId(0), 30.0), (NodeId(2), NodeId(1), 30.0)]);
+    assert_eq!(scenario.edge_sources[0].metadata, [("level".to_string(), "casual".to_string())]);
+    assert_eq!(scenario.node_label(NodeId(2)), "Carol : C <carol@example.org>");
+    // Bob revoked his key on day 50
+    assert_eq!(scenario.node_info[1].leaves, Some(Time(50.0)));
+}
+
+#[test]
+fn signature_csv_names_keys_by_their_ids() {
+    let csv = "signer,signee,time,weight\nalice,bob,1970-01-11,2\nBob,carol,1728000\n";
+    let scenario = read_signature_csv(csv, "wot").unwrap();
+    let edges: Vec<_> = scenario.edges.iter().map(|e| (e.source, e.target, e.time_of_creation.value(), e.weight)).collect();
+    assert_eq!(edges, [(NodeId(0), NodeId(1), 10.0, 2.0), (NodeId(1), NodeId(2), 20.0, 1.0)]);
+    assert_eq!(scenario.node_label(NodeId(2)), "carol");
+    assert!(read_signature_csv("signer,signee,time\nalice,bob,yesterday\n", "wot").is_err());
+}
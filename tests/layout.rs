@@ -0,0 +1,38 @@
+// With `style layout auto`, frames pin only the nodes that have a fixed position and let Graphviz
+// place the others, starting from the default layout; without it every node stays where it is.
+use trust_flow::dot::{write_dot, FrameRender};
+use trust_flow::scenario::Scenario;
+use trust_flow::style::ColorScale;
+use trust_flow::types::Time;
+
+const SCENARIO: &str = "nodes 3\nexpert 0\nnode 1 pos=0.5,-0.2\nedge 0 1 1\nedge 1 2 2\n";
+
+// The pos attributes of the nodes in the scenario's last frame, by node
+fn node_positions(text: &str) -> Vec<String> {
+    let scenario = Scenario::parse(text).unwrap();
+    let time = Time(2.0);
+    let ranks = vec![1.0 / 3.0; scenario.num_of_nodes];
+    let scale = ColorScale::new(&scenario.style, &ranks, std::slice::from_ref(&ranks));
+    let mut dot = Vec::new();
+    let (weights, labels, layout, pinned) = (scenario.edge_weights(time), scenario.node_labels(), scenario.layout(), scenario.pinned_nodes());
+    let frame = FrameRender::new(&ranks, &scenario.edges, &weights, &labels, &layout).experts(&scenario.experts).pinned(&pinned);
+    write_dot(&mut dot, &frame, &scenario.style, &scale).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    (0..scenario.num_of_nodes)
+        .map(|node| {
+            let line = dot.lines().find(|line| line.starts_with(&format!("  {} [", node))).unwrap();
+            line[line.find("pos=").unwrap()..line.len() - 2].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn every_node_is_pinned_by_default() {
+    assert_eq!(node_positions(SCENARIO), ["pos=\"1.00,0.00!\", pin=true", "pos=\"0.50,-0.20!\", pin=true", "pos=\"-0.50,-0.87!\", pin=true"]);
+}
+
+#[test]
+fn auto_layout_pins_only_fixed_positions() {
+    let positions = node_positions(&format!("{}style layout auto\n", SCENARIO));
+    assert_eq!(positions, ["pos=\"1.00,0.00\"", "pos=\"0.50,-0.20!\", pin=true", "pos=\"-0.50,-0.87\""]);
+}
@@ -0,0 +1,30 @@
+// Leaderboard order: ranks within TIE_TOLERANCE of each other tie, and ties go by the tie-break
+// key, then by node id
+use trust_flow::graph::Edge;
+use trust_flow::leaderboard::{rank_order, TieBreak};
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight: 1.0 }
+}
+
+fn order(ranks: &[f64], keys: &[f64]) -> Vec<usize> {
+    rank_order(ranks, keys).iter().map(|node| node.index()).collect()
+}
+
+#[test]
+fn ties_are_broken_by_the_key_then_by_id() {
+    // Nodes 0, 2 and 3 tie, node 3 only by rounding noise
+    let ranks = [0.3, 0.1, 0.3, 0.3 + 1e-13, 0.2];
+    let edges = [edge(1, 0), edge(0, 3), edge(2, 3), edge(4, 2)];
+    let weights = [1.0, 1.0, 1.0, 0.0]; // 4 → 2 has decayed away
+    let previous = [0.1, 0.5, 0.9, 0.2, 0.0];
+    let keys = |tie_break: TieBreak| tie_break.keys(5, &edges, &weights, Some(&previous));
+    assert_eq!(order(&ranks, &keys(TieBreak::NodeId)), [0, 2, 3, 4, 1]);
+    assert_eq!(keys(TieBreak::InDegree), [1.0, 0.0, 0.0, 2.0, 0.0]);
+    assert_eq!(order(&ranks, &keys(TieBreak::InDegree)), [3, 0, 2, 4, 1]);
+    assert_eq!(order(&ranks, &keys(TieBreak::PreviousRank)), [2, 3, 0, 4, 1]);
+    // The first frame has no previous ranks and goes by id
+    assert_eq!(TieBreak::PreviousRank.keys(5, &edges, &weights, None), Vec::<f64>::new());
+    assert_eq!(TieBreak::parse("previous-rank"), Ok(TieBreak::PreviousRank));
+}
@@ -0,0 +1,44 @@
+// Ego-centric local trust: teleportation goes to the truster's endorsees in proportion to its
+// current trust in them, and the ranks under it are the truster's trust in everyone else
+use trust_flow::graph::Edge;
+use trust_flow::rank::{DanglingPolicy, RankConfig};
+use trust_flow::ranker::Ranker;
+use trust_flow::registry::PAGERANK_VARIANT;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+// Node 0 endorses 1 once and 2 three times (and itself, which does not count); 1 endorses 2
+fn scenario() -> Scenario {
+    let mut scenario = Scenario::new("local", 3, vec![NodeId(1)], vec![edge(0, 1, 1.0), edge(0, 2, 3.0), edge(0, 0, 5.0), edge(1, 2, 1.0)]);
+    scenario.decay_constant = 0.0;
+    scenario
+}
+
+#[test]
+fn teleportation_goes_to_the_endorsees() {
+    let scenario = scenario();
+    let time = Time(1.0);
+    assert_eq!(scenario.endorsees(NodeId(0), time), [(NodeId(1), 1.0), (NodeId(2), 3.0)]);
+    assert_eq!(scenario.local_teleportation_targets(NodeId(0), time), [0.0, 0.25, 0.75]);
+    // Node 2 endorses no one and trusts itself
+    assert_eq!(scenario.local_teleportation_targets(NodeId(2), time), [0.0, 0.0, 1.0]);
+    assert_eq!(Scenario { local_trust: Some(NodeId(0)), ..scenario }.teleportation_targets(time), [0.0, 0.25, 0.75]);
+}
+
+#[test]
+fn local_trust_ranks_from_the_truster() {
+    let scenario = scenario();
+    let config = RankConfig::new().dangling_policy(DanglingPolicy::Teleport).iterations(200).tolerance(1e-15);
+    let algorithm = PAGERANK_VARIANT.create(&config);
+    let ranker = Ranker::new(&scenario, algorithm.as_ref());
+    // r1 = 1/8 + r2/8 and r2 = 3/8 + r1/2 + 3 r2/8, as 2 dangles back to 0's endorsees
+    let expected = [0.0, 2.0 / 9.0, 7.0 / 9.0];
+    for (node, expected) in expected.iter().enumerate() {
+        let trust = ranker.local_trust(NodeId(0), NodeId(node), Time(1.0));
+        assert!((trust - expected).abs() < 1e-12, "node 0 trusts {} {} instead of {}", node, trust, expected);
+    }
+}
@@ -0,0 +1,72 @@
+// --solver monte-carlo: the random-walk estimate converges to the Jacobi fixed point as the number
+// of walks grows, its standard errors cover the actual error, and a seed reproduces a run
+use trust_flow::graph::{CompiledGraph, Edge};
+use trust_flow::montecarlo::monte_carlo_ranks;
+use trust_flow::rank::{DanglingPolicy, PageRankVariant, RankConfig, RankingAlgorithm, Solver};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+const TIME: Time = Time(6.0);
+
+fn edge(source: usize, target: usize, time: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight: 1.0 }
+}
+
+// Two cycles joined at node 2, a parallel edge, and node 5 that endorses nobody
+fn scenario() -> Scenario {
+    let edges = vec![edge(0, 1, 0.0), edge(1, 2, 1.0), edge(2, 0, 2.0), edge(2, 3, 3.0), edge(3, 4, 4.0), edge(4, 2, 5.0), edge(4, 2, 5.5), edge(3, 5, 6.0)];
+    Scenario::new("walks", 6, vec![NodeId(0)], edges)
+}
+
+fn graph(scenario: &Scenario) -> CompiledGraph {
+    scenario.frame_graph(TIME, &scenario.edge_weights(TIME)).unwrap()
+}
+
+// Fully converged power iteration
+fn jacobi(scenario: &Scenario, config: &RankConfig) -> Vec<f64> {
+    PageRankVariant { config: config.clone().iterations(200) }.rank(&graph(scenario), &scenario.teleportation_targets(TIME))
+}
+
+fn max_error(estimate: &[f64], exact: &[f64]) -> f64 {
+    estimate.iter().zip(exact).map(|(e, x)| (e - x).abs()).fold(0.0, f64::max)
+}
+
+#[test]
+fn estimates_converge_to_the_jacobi_ranks() {
+    let scenario = scenario();
+    for dangling_policy in [DanglingPolicy::Uniform, DanglingPolicy::Teleport, DanglingPolicy::Retain] {
+        let config = RankConfig::new().dangling_policy(dangling_policy);
+        let exact = jacobi(&scenario, &config);
+        let errors: Vec<f64> = [100, 1000, 20000]
+            .map(|walks| {
+                let estimate = monte_carlo_ranks(&graph(&scenario), &scenario.teleportation_targets(TIME), &config.clone().walks_per_node(walks));
+                assert_eq!(estimate.num_of_walks, walks * 6);
+                // Within four standard errors of the fixed point, plus rounding
+                for (node, ((rank, exact), error)) in estimate.ranks.iter().zip(&exact).zip(&estimate.standard_errors).enumerate() {
+                    assert!((rank - exact).abs() <= 4.0 * error + 1e-12, "node {} with {} walks ({:?}): {} instead of {} ± {}", node, walks, dangling_policy, rank, exact, error);
+                }
+                max_error(&estimate.ranks, &exact)
+            })
+            .to_vec();
+        assert!(errors[2] < 0.005, "{:?}: error {} with 20000 walks per node", dangling_policy, errors[2]);
+        assert!(errors[2] < errors[0], "{:?}: errors {:?}", dangling_policy, errors);
+    }
+}
+
+#[test]
+fn the_solver_ranks_like_jacobi() {
+    let scenario = scenario();
+    let config = RankConfig::new().solver(Solver::MonteCarlo).walks_per_node(20000);
+    let ranks = PageRankVariant { config }.rank(&graph(&scenario), &scenario.teleportation_targets(TIME));
+    // Every visit counts the same, so the ranks only sum to 1 in expectation
+    assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 0.01);
+    assert!(max_error(&ranks, &jacobi(&scenario, &RankConfig::new())) < 0.005);
+}
+
+#[test]
+fn seeds_reproduce_the_walks() {
+    let scenario = scenario();
+    let run = |seed: u64| monte_carlo_ranks(&graph(&scenario), &scenario.teleportation_targets(TIME), &RankConfig::new().walks_per_node(50).walk_seed(seed)).ranks;
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
@@ -0,0 +1,49 @@
+// Frame files are named from a template with the scenario's name, the zero-padded frame index and
+// the frame's time; frames.json maps the names back to times.
+use trust_flow::export::{DotExporter, ExportedRun, Exporter};
+use trust_flow::naming::FrameNames;
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::sink::MemorySink;
+use trust_flow::types::{FrameIdx, Time};
+
+#[test]
+fn the_default_names_pad_to_the_run_length() {
+    let names = FrameNames::default();
+    assert_eq!(names.name("example", FrameIdx(7), Time(7.0), 21), "frame_007");
+    assert_eq!(names.name("example", FrameIdx(7), Time(7.0), 1000), "frame_007");
+    // Beyond 1000 frames the index gets wider, so the names still sort in frame order
+    assert_eq!(names.name("example", FrameIdx(7), Time(7.0), 1001), "frame_0007");
+    assert_eq!(names.name("example", FrameIdx(1000), Time(1000.0), 1001), "frame_1000");
+}
+
+#[test]
+fn templates_combine_scenario_index_and_time() {
+    let names = FrameNames::parse("{scenario}-{index:5}-t{time}").unwrap();
+    assert_eq!(names.name("example", FrameIdx(12), Time(2.5), 21), "example-00012-t2.5");
+    let names = FrameNames::parse("t{time:2}").unwrap();
+    assert_eq!(names.name("example", FrameIdx(0), Time(0.5), 21), "t0.50");
+    assert_eq!(names.to_string(), "t{time:2}");
+}
+
+#[test]
+fn invalid_templates_are_rejected() {
+    assert!(FrameNames::parse("{scenario}").is_err(), "frames need an index or a time");
+    assert!(FrameNames::parse("frame_{number}").is_err());
+    assert!(FrameNames::parse("frame_{index:x}").is_err());
+    assert!(FrameNames::parse("frame_{index").is_err());
+    assert!(FrameNames::parse("frames/{index}").is_err());
+    assert!(Scenario::parse("nodes 2\nframe-names {scenario}\n").is_err());
+}
+
+#[test]
+fn scenarios_name_their_exported_frames() {
+    let scenario = Scenario::parse("nodes 2\nexpert 0\nedge 0 1 0\nframe-names {scenario}-{index:4}-t{time}\n").unwrap();
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::default() }, &[Time(0.0), Time(1.5)], &scenario.graph_options);
+    let mut sink = MemorySink::new();
+    DotExporter.export(&mut sink, &ExportedRun { scenario: &scenario, output: &output, algorithm: "test" }).unwrap();
+    let files: Vec<String> = sink.files.keys().cloned().collect();
+    let name = &scenario.name;
+    assert_eq!(files, [format!("{}/{}-0000-t0.dot", name, name), format!("{}/{}-0001-t1.5.dot", name, name)]);
+}
@@ -0,0 +1,88 @@
+// The NATS client against a scripted server: it logs in with its credentials, receives the
+// messages of its subscription without their headers, and when the server drops the connection it
+// reconnects and subscribes again instead of ending the stream.
+#![cfg(feature = "nats")]
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use trust_flow::nats::{reconnect_delay, NatsConfig, NatsConnection, NatsMessage, MAX_RECONNECT_DELAY};
+
+const INFO: &str = "INFO {\"server_id\":\"NCX\",\"version\":\"2.10.14\",\"proto\":1,\"headers\":true,\"max_payload\":1048576}\r\n";
+
+// Accepts one client and answers its PINGs until it subscribes; returns the CONNECT line and the
+// subscription's sid
+fn accept(listener: &TcpListener) -> (TcpStream, String, String) {
+    let (mut stream, _) = listener.accept().unwrap();
+    stream.write_all(INFO.as_bytes()).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut connect = String::new();
+    loop {
+        let mut line = String::new();
+        assert!(reader.read_line(&mut line).unwrap() > 0, "the client hung up");
+        let line = line.trim_end();
+        if line.starts_with("CONNECT ") {
+            connect = line.to_string();
+        } else if line == "PING" {
+            stream.write_all(b"PONG\r\n").unwrap();
+        } else if let Some(subscription) = line.strip_prefix("SUB ") {
+            let parts: Vec<&str> = subscription.split_whitespace().collect();
+            assert_eq!(parts[0], "trust.events", "{}", line);
+            return (stream, connect, parts.last().unwrap().to_string());
+        }
+    }
+}
+
+fn message(payload: &str) -> Option<NatsMessage> {
+    Some(NatsMessage { subject: "trust.events".to_string(), payload: payload.as_bytes().to_vec() })
+}
+
+#[test]
+fn subscriptions_survive_a_lost_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let server = thread::spawn(move || {
+        let (mut stream, connect, sid) = accept(&listener);
+        write!(stream, "MSG trust.events {} 8\r\n0,1,0.5\n\r\n", sid).unwrap();
+        // Headers come before the payload and are dropped
+        write!(stream, "HMSG trust.events {} 33 41\r\nNATS/1.0\r\nNats-Msg-Id: edge-7\r\n\r\n1,2,1.0\n\r\n", sid).unwrap();
+        thread::sleep(Duration::from_millis(200));
+        drop(stream);
+
+        let (mut stream, reconnect, sid) = accept(&listener);
+        write!(stream, "MSG trust.events {} _INBOX.def 16\r\n2,0,1.5\n0,2,2.0\n\r\n", sid).unwrap();
+        thread::sleep(Duration::from_millis(200));
+        (connect, reconnect)
+    });
+
+    let config = NatsConfig::new().user_and_password("alice", "secret");
+    let connection = NatsConnection::connect(&address, &config).unwrap();
+    let mut subscription = connection.subscribe("trust.events").unwrap();
+    assert_eq!(subscription.next_message(), message("0,1,0.5\n"));
+    assert_eq!(subscription.next_message(), message("1,2,1.0\n"));
+    assert_eq!(subscription.next_message(), message("2,0,1.5\n0,2,2.0\n"));
+
+    let (connect, reconnect) = server.join().unwrap();
+    for line in [connect, reconnect] {
+        assert!(line.contains("\"user\":\"alice\"") && line.contains("\"pass\":\"secret\"") && line.contains("\"name\":\"trust-flow\""), "{}", line);
+    }
+}
+
+#[test]
+fn missing_credentials_files_are_reported() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let error = NatsConnection::connect(&address, &NatsConfig::new().credentials_file("no/such.creds")).err().unwrap();
+    assert!(error.to_string().contains("no/such.creds"), "{}", error);
+}
+
+#[test]
+fn reconnections_back_off() {
+    assert_eq!(reconnect_delay(1), Duration::ZERO);
+    assert_eq!(reconnect_delay(2), Duration::from_millis(100));
+    assert_eq!(reconnect_delay(3), Duration::from_millis(200));
+    assert_eq!(reconnect_delay(6), Duration::from_millis(1600));
+    assert_eq!(reconnect_delay(9), MAX_RECONNECT_DELAY);
+    assert_eq!(reconnect_delay(usize::MAX), MAX_RECONNECT_DELAY);
+}
@@ -0,0 +1,96 @@
+// The standard PageRank must reproduce networkx.pagerank on small fixture graphs, so the textbook
+// baseline can be trusted before it is compared with the custom variant. The expected ranks come from
+// networkx 3.6 (the pure Python implementation, default tol=1e-6 and max_iter=100) on a DiGraph
+// holding the frame's decayed weights, w * exp(-k * age), as the "weight" attribute; edges created
+// after the frame are left out of it.
+use trust_flow::graph::Edge;
+use trust_flow::rank::{RankConfig, RankingAlgorithm};
+use trust_flow::registry::STANDARD_PAGERANK;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+const TOLERANCE: f64 = 1e-12;
+
+struct Fixture {
+    name: &'static str,
+    num_of_nodes: usize,
+    decay_constant: f64,
+    time: f64,
+    edges: &'static [(usize, usize, f64, f64)], // (source, target, time of creation, weight)
+    expected: &'static [(f64, &'static [f64])], // networkx ranks by damping factor (alpha)
+}
+
+const FIXTURES: [Fixture; 3] = [
+    // Node 4 is dangling
+    Fixture {
+        name: "dangling",
+        num_of_nodes: 5,
+        decay_constant: 0.0,
+        time: 0.0,
+        edges: &[(0, 1, 0.0, 1.0), (0, 2, 0.0, 1.0), (1, 2, 0.0, 1.0), (2, 0, 0.0, 1.0), (3, 2, 0.0, 1.0), (2, 4, 0.0, 1.0)],
+        expected: &[
+            (0.85, &[0.21420088236227422, 0.15745005250504357, 0.3477338820546813, 0.06641430071572674, 0.21420088236227422]),
+            (0.5, &[0.19834690082324616, 0.1694216518768155, 0.3140497887976021, 0.11983475767909015, 0.19834690082324616]),
+        ],
+    },
+    // Decayed weights, a self-loop, and an edge from node 5 created after the frame
+    Fixture {
+        name: "decayed",
+        num_of_nodes: 6,
+        decay_constant: 0.2,
+        time: 10.0,
+        edges: &[(0, 1, 0.0, 2.0), (0, 2, 4.0, 1.0), (1, 2, 1.0, 3.0), (2, 0, 2.0, 1.5), (2, 3, 9.0, 0.5), (3, 3, 5.0, 1.0), (3, 4, 6.0, 2.0), (4, 0, 8.0, 1.0), (5, 1, 12.0, 1.0)],
+        expected: &[
+            (0.85, &[0.24581742332922352, 0.1280222269033134, 0.24799580699610083, 0.1995573267096942, 0.1494810024694353, 0.029126213592233084]),
+            (0.5, &[0.21465977458265972, 0.14170952602815862, 0.2182932889814927, 0.1797495510271973, 0.15467876847129872, 0.09090909090919286]),
+        ],
+    },
+    // Node 3 has no edges at all
+    Fixture {
+        name: "isolated",
+        num_of_nodes: 4,
+        decay_constant: 0.1,
+        time: 3.0,
+        edges: &[(0, 1, 0.0, 1.0), (1, 0, 1.0, 2.0), (1, 2, 2.0, 0.5)],
+        expected: &[
+            (0.85, &[0.3526482121439868, 0.3915996348688253, 0.1639047618406767, 0.091847391146511]),
+            (0.5, &[0.2985099872224122, 0.32172505485704767, 0.2072943627949358, 0.17247059512560436]),
+        ],
+    },
+];
+
+fn scenario(fixture: &Fixture) -> Scenario {
+    let edges = fixture.edges.iter().map(|&(source, target, created, weight)| Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(created), weight }).collect();
+    let mut scenario = Scenario::new(fixture.name, fixture.num_of_nodes, Vec::new(), edges);
+    scenario.decay_constant = fixture.decay_constant;
+    scenario
+}
+
+#[test]
+fn standard_pagerank_matches_networkx() {
+    for fixture in &FIXTURES {
+        let scenario = scenario(fixture);
+        let time = Time(fixture.time);
+        let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+        let standard = STANDARD_PAGERANK.create(&RankConfig::new());
+        for &(damping_factor, expected) in fixture.expected {
+            let algorithm: Box<dyn RankingAlgorithm> = standard.with_damping(damping_factor).unwrap();
+            let ranks = algorithm.rank(&graph, &scenario.teleportation_targets(time));
+            for (node, (rank, networkx)) in ranks.iter().zip(expected).enumerate() {
+                assert!((rank - networkx).abs() < TOLERANCE, "{} with damping {}: node {} has rank {}, networkx gives {}", fixture.name, damping_factor, node, rank, networkx);
+            }
+        }
+    }
+}
+
+#[test]
+fn standard_pagerank_ignores_experts() {
+    let fixture = &FIXTURES[1];
+    let mut scenario = scenario(fixture);
+    let time = Time(fixture.time);
+    let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+    let standard = STANDARD_PAGERANK.create(&RankConfig::new());
+    let without_experts = standard.rank(&graph, &scenario.teleportation_targets(time));
+    scenario.experts = vec![NodeId(5)];
+    assert_eq!(standard.rank(&graph, &scenario.teleportation_targets(time)), without_experts);
+}
@@ -0,0 +1,46 @@
+// Pairwise trust between two nodes as the maximum flow of edge weight from one to the other, on a
+// network whose minimum cut is known
+use trust_flow::graph::{compile_graph, CompiledGraph, Edge, GraphOptions};
+use trust_flow::pairwise::{max_flow, trust_between, PairwiseTrust};
+use trust_flow::rank::RankConfig;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+// The cut between {0, 1} and {2, 3} carries 1 + 1 + 5 = 7: paths 0 → 1 → 3, 0 → 2 → 3 and 0 → 1 → 2 → 3
+fn network() -> Vec<Edge> {
+    vec![edge(0, 1, 10.0), edge(1, 3, 1.0), edge(0, 2, 1.0), edge(2, 3, 10.0), edge(1, 2, 5.0)]
+}
+
+fn graph(num_of_nodes: usize, edges: &[Edge]) -> CompiledGraph {
+    compile_graph(edges, &edges.iter().map(|e| e.weight).collect::<Vec<_>>(), num_of_nodes, &GraphOptions::default()).unwrap()
+}
+
+#[test]
+fn max_flow_is_the_minimum_cut() {
+    let network = network();
+    assert_eq!(max_flow(&graph(4, &network), NodeId(0), NodeId(3)), 7.0);
+    // Nothing flows against the edges
+    assert_eq!(max_flow(&graph(4, &network), NodeId(3), NodeId(0)), 0.0);
+    assert_eq!(max_flow(&graph(4, &network), NodeId(1), NodeId(1)), f64::INFINITY);
+    // Parallel edges add up, self-loops carry nothing
+    let mut more = network.clone();
+    more.extend([edge(1, 3, 1.0), edge(3, 3, 100.0)]);
+    assert_eq!(max_flow(&graph(4, &more), NodeId(0), NodeId(3)), 8.0);
+}
+
+#[test]
+fn trust_between_takes_the_frame_weights() {
+    let mut scenario = Scenario::new("network", 4, vec![NodeId(0)], network());
+    scenario.decay_constant = 0.0;
+    let config = RankConfig::new();
+    assert_eq!(trust_between(&scenario, &config, NodeId(0), NodeId(3), Time(1.0), PairwiseTrust::MaxFlow).unwrap(), 7.0);
+    // Once the 0 → 1 edge has been cut down to 2, less can get through
+    scenario.edges[0].weight = 2.0;
+    assert_eq!(trust_between(&scenario, &config, NodeId(0), NodeId(3), Time(1.0), PairwiseTrust::MaxFlow).unwrap(), 3.0);
+    assert_eq!(PairwiseTrust::parse("max-flow"), Ok(PairwiseTrust::MaxFlow));
+    assert!(PairwiseTrust::parse("flow").is_err());
+}
@@ -0,0 +1,119 @@
+// The Parquet exports are read back with the parquet crate's record reader: the schema and every
+// value must come out as they went in, across row groups too.
+#![cfg(feature = "parquet")]
+use std::env;
+use std::fs::{self, File};
+
+use parquet::basic::{ConvertedType, Type};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::{Field, Row};
+use trust_flow::parquet::{write_edge_weight_timeline_parquet, write_parquet, write_rank_timeline_parquet, Column, ColumnValues, ROW_GROUP_ROWS};
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{FrameIdx, Time};
+
+// The edge 2 -> 0 only appears at time 1; frames are numbered from 1
+const SCENARIO: &str = "nodes 3\nexpert 0\nedge 0 1 0\nedge 1 2 0\nedge 2 0 1\n";
+
+// Writes the file to disk and reads it back: its columns' names and types, and its rows
+fn read_back(name: &str, write: impl FnOnce(&mut Vec<u8>)) -> (Vec<(String, Type, ConvertedType)>, Vec<Row>) {
+    let mut bytes = Vec::new();
+    write(&mut bytes);
+    let path = env::temp_dir().join(format!("trust-flow-{}-{}.parquet", name, std::process::id()));
+    fs::write(&path, &bytes).unwrap();
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let schema = reader.metadata().file_metadata().schema_descr_ptr();
+    let columns = schema.columns().iter().map(|c| (c.name().to_string(), c.physical_type(), c.converted_type())).collect();
+    let rows = reader.get_row_iter(None).unwrap().map(Result::unwrap).collect();
+    fs::remove_file(&path).unwrap();
+    (columns, rows)
+}
+
+fn fields(row: &Row) -> Vec<Field> {
+    row.get_column_iter().map(|(_, field)| field.clone()).collect()
+}
+
+#[test]
+fn columns_round_trip() {
+    let (columns, rows) = read_back("columns", |out| {
+        write_parquet(out, &[
+            Column { name: "id", values: ColumnValues::Int64(vec![-1, 0, i64::MAX]) },
+            Column { name: "score", values: ColumnValues::Double(vec![0.5, -2.25, 1e300]) },
+            Column { name: "name", values: ColumnValues::Text(vec!["a".to_string(), String::new(), "ünïcode".to_string()]) },
+        ])
+        .unwrap()
+    });
+    assert_eq!(columns, [
+        ("id".to_string(), Type::INT64, ConvertedType::NONE),
+        ("score".to_string(), Type::DOUBLE, ConvertedType::NONE),
+        ("name".to_string(), Type::BYTE_ARRAY, ConvertedType::UTF8),
+    ]);
+    let rows: Vec<Vec<Field>> = rows.iter().map(fields).collect();
+    assert_eq!(rows, [
+        [Field::Long(-1), Field::Double(0.5), Field::Str("a".to_string())],
+        [Field::Long(0), Field::Double(-2.25), Field::Str(String::new())],
+        [Field::Long(i64::MAX), Field::Double(1e300), Field::Str("ünïcode".to_string())],
+    ]);
+
+    let (columns, rows) = read_back("empty", |out| write_parquet(out, &[Column { name: "id", values: ColumnValues::Int64(Vec::new()) }]).unwrap());
+    assert_eq!(columns, [("id".to_string(), Type::INT64, ConvertedType::NONE)]);
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn long_tables_are_split_into_row_groups() {
+    let path = env::temp_dir().join(format!("trust-flow-row-groups-{}.parquet", std::process::id()));
+    write_parquet(File::create(&path).unwrap(), &[Column { name: "id", values: ColumnValues::Int64((0..ROW_GROUP_ROWS as i64 + 2).collect()) }]).unwrap();
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+    let row_groups: Vec<i64> = reader.metadata().row_groups().iter().map(|g| g.num_rows()).collect();
+    assert_eq!(row_groups, [ROW_GROUP_ROWS as i64, 2]);
+    let last: Vec<Vec<Field>> = reader.get_row_group(1).unwrap().get_row_iter(None).unwrap().map(|row| fields(&row.unwrap())).collect();
+    assert_eq!(last, [[Field::Long(ROW_GROUP_ROWS as i64)], [Field::Long(ROW_GROUP_ROWS as i64 + 1)]]);
+}
+
+#[test]
+fn rank_timeline_matches_the_run() {
+    let scenario = Scenario::parse(SCENARIO).unwrap();
+    let times = [Time(0.0), Time(1.0)];
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::default() }, &times, &scenario.graph_options);
+    let labels = scenario.node_labels();
+    let (columns, rows) = read_back("ranks", |out| write_rank_timeline_parquet(out, &output, &labels).unwrap());
+    let names: Vec<&str> = columns.iter().map(|(name, _, _)| name.as_str()).collect();
+    assert_eq!(names, ["frame", "time", "node", "label", "rank"]);
+    assert_eq!(rows.len(), times.len() * 3);
+    for (row, (frame, node)) in rows.iter().zip((0..times.len()).flat_map(|frame| (0..3).map(move |node| (frame, node)))) {
+        assert_eq!(fields(row), [
+            Field::Long(FrameIdx(frame).number() as i64),
+            Field::Double(times[frame].0),
+            Field::Long(node as i64),
+            Field::Str(labels[node].clone()),
+            Field::Double(output.history.ranks_at(FrameIdx(frame))[node]),
+        ]);
+    }
+}
+
+#[test]
+fn edge_weight_timeline_leaves_out_missing_edges() {
+    let scenario = Scenario::parse(SCENARIO).unwrap();
+    let times = [Time(0.0), Time(1.0)];
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::default() }, &times, &scenario.graph_options);
+    let (columns, rows) = read_back("edge-weights", |out| write_edge_weight_timeline_parquet(out, &scenario, &output).unwrap());
+    assert_eq!(columns.iter().map(|(name, physical, _)| (name.as_str(), *physical)).collect::<Vec<_>>(), [
+        ("frame", Type::INT64),
+        ("time", Type::DOUBLE),
+        ("edge", Type::INT64),
+        ("source", Type::INT64),
+        ("target", Type::INT64),
+        ("weight", Type::DOUBLE),
+    ]);
+    let edges: Vec<(i64, i64, i64, i64)> = rows
+        .iter()
+        .map(|row| match fields(row)[..] {
+            [Field::Long(frame), _, Field::Long(edge), Field::Long(source), Field::Long(target), Field::Double(weight)] if weight > 0.0 => (frame, edge, source, target),
+            ref other => panic!("unexpected row {:?}", other),
+        })
+        .collect();
+    assert_eq!(edges, [(1, 0, 0, 1), (1, 1, 1, 2), (2, 0, 0, 1), (2, 1, 1, 2), (2, 2, 2, 0)]);
+}
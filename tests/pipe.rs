@@ -0,0 +1,107 @@
+// --pipe: edge events on stdin, one JSON rank snapshot per line on stdout for every event time or
+// every --frame-times time, the same ranks the library computes for the events; a snapshot streamed
+// with --every-events equals the batch run of the events received so far
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use trust_flow::graph::Edge;
+use trust_flow::rank::{PageRankVariant, RankConfig, Solver};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn pipe(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_trust-flow"))
+        .arg("--pipe")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run trust-flow");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+// (frame, time, ranks) of every snapshot line
+fn snapshots(output: &Output) -> Vec<(usize, f64, Vec<f64>)> {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let rest = line.strip_prefix("{\"frame\":").unwrap_or_else(|| panic!("{}", line));
+            let (frame, rest) = rest.split_once(",\"time\":").unwrap();
+            let (time, rest) = rest.split_once(",\"ranks\":[").unwrap();
+            let ranks = rest.strip_suffix("]}").unwrap().split(',').map(|r| r.parse().unwrap()).collect();
+            (frame.parse().unwrap(), time.parse().unwrap(), ranks)
+        })
+        .collect()
+}
+
+fn assert_ranks(actual: &[f64], expected: &[f64]) {
+    assert!(actual.len() == expected.len() && actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} instead of {:?}", actual, expected);
+}
+
+#[test]
+fn snapshots_follow_the_event_times() {
+    let output = pipe(&["--input-format", "csv", "--expert", "0", "--solver", "exact"], "source,target,time\n0,1,1\n1,2,2\n");
+    let snapshots = snapshots(&output);
+    assert_eq!(snapshots.iter().map(|(frame, time, _)| (*frame, *time)).collect::<Vec<_>>(), [(1, 1.0), (2, 2.0)]);
+    // At time 1 only 0 → 1 exists, undecayed: r0 = 13/30 + (r1 + r2)/6, r2 = 1/30 + (r1 + r2)/6
+    assert_ranks(&snapshots[0].2, &[18.0 / 35.0, 13.0 / 35.0, 4.0 / 35.0]);
+
+    let edges = [(0, 1, 1.0), (1, 2, 2.0)].map(|(source, target, time)| Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight: 1.0 });
+    let scenario = Scenario::new("stdin", 3, vec![NodeId(0)], edges.to_vec());
+    let algorithm = PageRankVariant { config: RankConfig::new().solver(Solver::Exact) };
+    let expected = run_scenario(&scenario, &algorithm, &[Time(1.0), Time(2.0)], &scenario.graph_options);
+    for ((_, _, ranks), expected) in snapshots.iter().zip(expected.history.frames()) {
+        assert_ranks(ranks, expected);
+    }
+}
+
+#[test]
+fn json_events_at_given_frame_times() {
+    let events = "{\"source\": 0, \"target\": 1, \"time\": 1}\n{\"source\": 1, \"target\": 0, \"time\": 1, \"weight\": 2}\n";
+    let snapshots = snapshots(&pipe(&["--expert", "0", "--frame-times", "0,1"], events));
+    assert_eq!(snapshots.iter().map(|(frame, time, _)| (*frame, *time)).collect::<Vec<_>>(), [(1, 0.0), (2, 1.0)]);
+    // Before any edge both nodes dangle: r0 = 0.5 · (0.8 + 0.1) + 0.5 / 2
+    assert_ranks(&snapshots[0].2, &[0.7, 0.3]);
+    assert!((snapshots[1].2.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn streamed_snapshots_equal_the_batch_frames() {
+    // Experts 0 and 3 make all four nodes known from the first event on, as in the batch runs
+    let events = ["0,1,1", "1,2,2", "2,3,3", "3,0,4", "1,0,5", "0,2,6"];
+    let text = |events: &[&str]| format!("source,target,time\n{}\n", events.join("\n"));
+    let common = ["--input-format", "csv", "--expert", "0", "--expert", "3"];
+    for (every, received) in [("1", vec![1, 2, 3, 4, 5, 6]), ("4", vec![4, 6])] {
+        let streamed = snapshots(&pipe(&[&common[..], &["--every-events", every]].concat(), &text(&events)));
+        assert_eq!(streamed.len(), received.len(), "--every-events {}", every);
+        // Each snapshot is the last frame of a batch run on the events received so far
+        for ((_, time, ranks), received) in streamed.iter().zip(received) {
+            let batch = snapshots(&pipe(&common, &text(&events[..received])));
+            let (_, batch_time, expected) = batch.last().unwrap();
+            assert_eq!(time, batch_time);
+            assert_ranks(ranks, expected);
+        }
+    }
+
+    // Normalized by the current weights, later events do not change earlier frames, so every
+    // snapshot equals the frame of a single batch run at its time
+    let current = [&common[..], &["--outflow-normalization", "current"]].concat();
+    let batch = snapshots(&pipe(&current, &text(&events)));
+    let streamed = snapshots(&pipe(&[&current[..], &["--every-events", "1"]].concat(), &text(&events)));
+    assert_eq!(streamed.len(), batch.len());
+    for ((_, time, ranks), (_, batch_time, expected)) in streamed.iter().zip(&batch) {
+        assert_eq!(time, batch_time);
+        assert_ranks(ranks, expected);
+    }
+}
+
+#[test]
+fn empty_input_is_an_error() {
+    let output = pipe(&[], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty() && String::from_utf8_lossy(&output.stderr).contains("no edge events"));
+}
@@ -0,0 +1,43 @@
+// Algorithms of other crates register with the registry and then run like the built-in ones,
+// on the decayed graph of every frame.
+use trust_flow::graph::{CompiledGraph, Edge};
+use trust_flow::rank::RankingAlgorithm;
+use trust_flow::registry::{AlgorithmInfo, AlgorithmRegistry, PAGERANK_VARIANT};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+// Number of endorsers per node, whatever their weight
+struct Endorsers;
+
+impl RankingAlgorithm for Endorsers {
+    fn name(&self) -> &str {
+        "Endorsers"
+    }
+
+    fn rank(&self, graph: &CompiledGraph, _teleportation_targets: &[f64]) -> Vec<f64> {
+        let mut counts = vec![0.0; graph.num_of_nodes];
+        for edge in graph.edges.iter().filter(|e| e.weight > 0.0) {
+            counts[edge.target.index()] += 1.0;
+        }
+        counts
+    }
+}
+
+#[test]
+fn registered_algorithms_rank_every_frame() {
+    let info = AlgorithmRegistry::register(AlgorithmInfo::new("endorsers", "Endorsers", "number of endorsers", |_| Box::new(Endorsers))).unwrap();
+    assert_eq!(AlgorithmRegistry::get("endorsers").unwrap().name, info.name);
+    assert!(AlgorithmRegistry::all().iter().any(|a| a.id == "endorsers"));
+    assert!(AlgorithmRegistry::register(AlgorithmInfo::new("endorsers", "Again", "", |_| Box::new(Endorsers))).is_err());
+    assert!(AlgorithmRegistry::register(AlgorithmInfo::new(PAGERANK_VARIANT.id, "Again", "", |_| Box::new(Endorsers))).is_err());
+
+    let edges = vec![
+        Edge { source: NodeId(0), target: NodeId(2), time_of_creation: Time(0.0), weight: 1.0 },
+        Edge { source: NodeId(1), target: NodeId(2), time_of_creation: Time(1.0), weight: 1.0 },
+    ];
+    let scenario = Scenario::new("plugin", 3, vec![NodeId(0)], edges);
+    let algorithm = AlgorithmRegistry::create("endorsers", &Default::default()).unwrap();
+    let output = run_scenario(&scenario, algorithm.as_ref(), &[Time(0.0), Time(1.0)], &scenario.graph_options);
+    assert_eq!(output.history.frames(), [vec![0.0, 0.0, 1.0], vec![0.0, 0.0, 2.0]]);
+}
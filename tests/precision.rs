@@ -0,0 +1,53 @@
+// f32 ranks must stay close to the f64 reference, and the generic core in f64 must match RankIteration
+use trust_flow::bench::bench_scenario;
+use trust_flow::graph::{compile_graph, CompiledGraph, GraphOptions};
+use trust_flow::precision::pagerank_in;
+use trust_flow::rank::{pagerank_variant, DanglingPolicy, OutflowNormalization, RankConfig};
+use trust_flow::types::Time;
+
+fn frame(num_of_edges: usize, seed: u64) -> (CompiledGraph, Vec<f64>) {
+    let scenario = bench_scenario(num_of_edges, seed);
+    let time = Time(60.0);
+    let weights = scenario.edge_weights(time);
+    let graph = compile_graph(&scenario.edges, &weights, scenario.num_of_nodes, &GraphOptions::default()).unwrap();
+    (graph, scenario.teleportation_targets(time))
+}
+
+fn max_difference(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max)
+}
+
+fn configs() -> Vec<RankConfig> {
+    let mut configs = Vec::new();
+    for dangling_policy in [DanglingPolicy::Uniform, DanglingPolicy::Teleport, DanglingPolicy::Retain] {
+        for outflow_normalization in [OutflowNormalization::Lifetime, OutflowNormalization::Current, OutflowNormalization::None] {
+            configs.push(RankConfig::new().iterations(50).damping_factor(0.85).dangling_policy(dangling_policy).outflow_normalization(outflow_normalization));
+        }
+    }
+    configs
+}
+
+#[test]
+fn generic_f64_core_matches_rank_iteration() {
+    let (graph, targets) = frame(2_000, 3);
+    for config in configs() {
+        let reference = pagerank_variant(&graph, &targets, &config);
+        let generic = pagerank_in::<f64>(&graph, &targets, &config);
+        assert!(max_difference(&reference, &generic) < 1e-12, "{:?}", config);
+    }
+}
+
+#[test]
+fn f32_ranks_diverge_little_from_f64() {
+    for (num_of_edges, seed) in [(1_000, 1), (20_000, 2)] {
+        let (graph, targets) = frame(num_of_edges, seed);
+        for config in configs() {
+            let reference = pagerank_variant(&graph, &targets, &config);
+            let single: Vec<f64> = pagerank_in::<f32>(&graph, &targets, &config).into_iter().map(f64::from).collect();
+            let largest = reference.iter().copied().fold(0.0, f64::max);
+            assert!(max_difference(&reference, &single) < 1e-4 * largest, "{} edges: max difference {:e} against largest rank {:e}, {:?}", num_of_edges, max_difference(&reference, &single), largest, config);
+            let mass: f64 = single.iter().sum();
+            assert!((mass - reference.iter().sum::<f64>()).abs() < 1e-4, "rank (mass) drifted to {} ({:?})", mass, config);
+        }
+    }
+}
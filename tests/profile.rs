@@ -0,0 +1,40 @@
+// With --profile, every frame records the wall time of its phases and profile.csv has one row per
+// frame plus a `run` row for the whole-run files.
+use std::time::Duration;
+
+use trust_flow::graph::GraphOptions;
+use trust_flow::profile::{write_profile_csv, FrameProfile, Phase};
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::Time;
+
+const SCENARIO: &str = "nodes 3\nexpert 0\nedge 0 1 1\nedge 1 2 2\n";
+
+#[test]
+fn every_ranked_frame_has_a_profile() {
+    let scenario = Scenario::parse(SCENARIO).unwrap();
+    let frame_times = [Time(0.0), Time(1.0), Time(2.0)];
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::new() }, &frame_times, &GraphOptions::default());
+    assert_eq!(output.profile.len(), frame_times.len());
+    for profile in &output.profile {
+        assert_eq!(profile.total(), profile.duration(Phase::Decay) + profile.duration(Phase::Ranking), "ranking alone neither lays out nor renders");
+    }
+}
+
+#[test]
+fn profile_csv_has_a_row_per_frame_and_one_for_the_run() {
+    let mut frame = FrameProfile::default();
+    frame.add(Phase::Ranking, Duration::from_millis(3));
+    frame.merge(&FrameProfile { durations: [Duration::from_millis(1); 5], peak_memory: Some(4 * 1024 * 1024) });
+    let run = FrameProfile::default();
+    let mut csv = Vec::new();
+    // The second frame was resumed from a checkpoint and took no time here
+    write_profile_csv(&mut csv, &[Time(0.0), Time(1.0)], &[frame], &run).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "frame,time,decay_ms,ranking_ms,layout_ms,rendering_ms,export_ms,total_ms,peak_memory_mb");
+    assert_eq!(lines[1], "1,0,1.000,4.000,1.000,1.000,1.000,8.000,4.0");
+    assert_eq!(lines[2], "2,1,0.000,0.000,0.000,0.000,0.000,0.000,");
+    assert_eq!(lines[3], "run,,0.000,0.000,0.000,0.000,0.000,0.000,");
+}
@@ -0,0 +1,118 @@
+// Invariants of the ranking core on random temporal graphs. proptest generates the graphs and, when
+// an invariant fails, shrinks the graph to a minimal one that still breaks it.
+use proptest::prelude::*;
+use proptest::sample::Index;
+use trust_flow::graph::{Edge, GraphOptions, ParallelEdgePolicy, SelfLoopPolicy};
+use trust_flow::rank::{Backend, DanglingPolicy, OutflowNormalization, RankConfig, Solver};
+use trust_flow::registry::{AlgorithmRegistry, CAPACITY_LIMITED, PAGERANK_VARIANT, SOURCE_CLIPPED, TRUSTRANK};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+const CASES: u32 = 64;
+const TOLERANCE: f64 = 1e-9;
+const END: f64 = 20.0; // edges are created in [0, END)
+
+// Random scenario: up to 30 nodes, up to 120 edges with random creation times and weights
+// (self-loops and parallel edges included), a random expert set that may be empty and a random decay
+fn scenarios() -> impl Strategy<Value = Scenario> {
+    let parallel_edges = prop::sample::select(vec![ParallelEdgePolicy::KeepAll, ParallelEdgePolicy::SumWeights, ParallelEdgePolicy::KeepMax, ParallelEdgePolicy::SaturatingSum(2.0)]);
+    (1..=30usize).prop_flat_map(move |num_of_nodes| {
+        let edge = (0..num_of_nodes, 0..num_of_nodes, 0.0..END, 0.1..3.1)
+            .prop_map(|(source, target, time, weight)| Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight });
+        let options = (any::<bool>(), parallel_edges.clone(), prop::bool::weighted(0.2)).prop_map(|(keep_self_loops, parallel_edges, undirected)| GraphOptions {
+            self_loops: if keep_self_loops { SelfLoopPolicy::Keep } else { SelfLoopPolicy::Ignore },
+            parallel_edges,
+            undirected,
+            prune_below: 0.0,
+        });
+        (prop::collection::vec(edge, 0..=120), prop::collection::vec(prop::bool::weighted(0.2), num_of_nodes), 0.0..0.3, options).prop_map(
+            move |(edges, is_expert, decay_constant, graph_options)| {
+                let experts = (0..num_of_nodes).filter(|&node| is_expert[node]).map(NodeId).collect();
+                let mut scenario = Scenario::new("random", num_of_nodes, experts, edges);
+                scenario.decay_constant = decay_constant;
+                scenario.graph_options = graph_options;
+                scenario
+            },
+        )
+    })
+}
+
+fn times() -> impl Strategy<Value = Time> {
+    (0.0..END * 1.2).prop_map(Time)
+}
+
+// Every combination of dangling policy, outflow normalization, solver and backend the custom variant supports
+fn configs() -> Vec<RankConfig> {
+    let mut configs = Vec::new();
+    for dangling_policy in [DanglingPolicy::Uniform, DanglingPolicy::Teleport, DanglingPolicy::Retain] {
+        for outflow_normalization in [OutflowNormalization::Lifetime, OutflowNormalization::Current, OutflowNormalization::None] {
+            let config = RankConfig::new().dangling_policy(dangling_policy).outflow_normalization(outflow_normalization);
+            configs.push(config.clone());
+            configs.push(config.clone().solver(Solver::GaussSeidel));
+            configs.push(config.clone().solver(Solver::Exact));
+            configs.push(config.backend(Backend::Dense));
+        }
+    }
+    configs
+}
+
+fn rank(scenario: &Scenario, time: Time, config: &RankConfig) -> Vec<f64> {
+    let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+    PAGERANK_VARIANT.create(config).rank(&graph, &scenario.teleportation_targets(time))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn ranks_sum_to_one(scenario in scenarios(), time in times()) {
+        for config in configs() {
+            let total: f64 = rank(&scenario, time, &config).iter().sum();
+            prop_assert!((total - 1.0).abs() < TOLERANCE, "ranks sum to {} ({:?})", total, config);
+        }
+        // The algorithms that conserve rank (mass) like the custom variant
+        let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+        for info in [&TRUSTRANK, &CAPACITY_LIMITED, &SOURCE_CLIPPED] {
+            let total: f64 = info.create(&RankConfig::new()).rank(&graph, &scenario.teleportation_targets(time)).iter().sum();
+            prop_assert!((total - 1.0).abs() < TOLERANCE, "{} ranks sum to {}", info.name, total);
+        }
+    }
+
+    #[test]
+    fn ranks_are_non_negative(scenario in scenarios(), time in times()) {
+        for config in configs() {
+            let ranks = rank(&scenario, time, &config);
+            prop_assert!(ranks.iter().all(|&r| r >= 0.0), "negative rank in {:?} ({:?})", ranks, config);
+        }
+        let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+        for info in AlgorithmRegistry::ALGORITHMS {
+            let ranks = info.create(&RankConfig::new()).rank(&graph, &scenario.teleportation_targets(time));
+            prop_assert!(ranks.iter().all(|&r| r >= 0.0), "{} gives a negative rank in {:?}", info.name, ranks);
+        }
+    }
+
+    #[test]
+    fn incoming_edge_never_leaves_a_node_without_rank(mut scenario in scenarios(), time in times(), source: Index, target: Index) {
+        let (source, target) = (NodeId(source.index(scenario.num_of_nodes)), NodeId(target.index(scenario.num_of_nodes)));
+        scenario.edges.push(Edge { source, target, time_of_creation: Time(0.0), weight: 1.0 });
+        for config in configs() {
+            let ranks = rank(&scenario, time, &config);
+            prop_assert!(ranks[target.index()] > 0.0, "node {} has no rank despite an incoming edge ({:?})", target, config);
+        }
+    }
+
+    #[test]
+    fn edge_weights_decay_monotonically(scenario in scenarios(), earlier in 0.0..END, elapsed in 0.0..END, faster_by in 0.0..1.0) {
+        let (earlier, later) = (Time(earlier), Time(earlier + elapsed));
+        let (at_earlier, at_later) = (scenario.edge_weights(earlier), scenario.edge_weights(later));
+        let faster = Scenario { decay_constant: scenario.decay_constant + faster_by, ..scenario.clone() };
+        let faster_at_later = faster.edge_weights(later);
+        for (id, edge) in scenario.edges.iter().enumerate() {
+            prop_assert!(at_earlier[id] <= edge.weight + TOLERANCE, "edge {} grew above its initial weight", id);
+            if edge.time_of_creation <= earlier {
+                prop_assert!(at_later[id] <= at_earlier[id] + TOLERANCE, "edge {} grew from {} to {} between {} and {}", id, at_earlier[id], at_later[id], earlier, later);
+            }
+            prop_assert!(faster_at_later[id] <= at_later[id] + TOLERANCE, "edge {} decays slower with a larger decay constant", id);
+        }
+    }
+}
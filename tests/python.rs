@@ -0,0 +1,85 @@
+// Smoke test of the Python bindings on an embedded interpreter: a TemporalGraph built from Python
+// rejects invalid edges and ranks like the same scenario in Rust
+#![cfg(feature = "python")]
+use std::sync::Once;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use trust_flow::graph::Edge;
+use trust_flow::python::trust_flow as module;
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+fn python<R>(f: impl for<'py> FnOnce(Python<'py>) -> R) -> R {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        pyo3::append_to_inittab!(module);
+        pyo3::prepare_freethreaded_python();
+    });
+    Python::with_gil(f)
+}
+
+#[test]
+fn graphs_check_their_edges() {
+    python(|py| {
+        py.run(
+            c"
+import trust_flow
+graph = trust_flow.TemporalGraph(3, experts=[0], decay=0.0)
+graph.add_edge(0, 1, 1.0)
+graph.add_edge(1, 2, 2.0, weight=2.0)
+assert (graph.num_of_nodes, graph.num_of_edges, graph.decay) == (3, 2, 0.0)
+for edge in [(0, 3, 1.0), (0, 1, 1.0, 0.0)]:
+    try:
+        graph.add_edge(*edge)
+        raise AssertionError(f'{edge} was accepted')
+    except ValueError:
+        pass
+assert graph.num_of_edges == 2
+",
+            None,
+            None,
+        )
+        .unwrap();
+    });
+}
+
+#[test]
+fn ranks_match_the_library() {
+    let ranks: Option<(Vec<f64>, Vec<Vec<f64>>)> = python(|py| {
+        // rank returns numpy arrays, so it needs numpy installed in the interpreter
+        if py.import("numpy").is_err() {
+            eprintln!("numpy is not installed, skipping the rank arrays");
+            return None;
+        }
+        let locals = PyDict::new(py);
+        py.run(
+            c"
+import trust_flow
+graph = trust_flow.TemporalGraph(3, experts=[0], decay=0.1)
+graph.add_edge(0, 1, 1.0)
+graph.add_edge(1, 2, 2.0, weight=2.0)
+times, ranks = trust_flow.rank(graph)
+times, ranks = times.tolist(), ranks.tolist()
+",
+            None,
+            Some(&locals),
+        )
+        .unwrap();
+        let get = |name: &str| locals.get_item(name).unwrap().unwrap();
+        Some((get("times").extract().unwrap(), get("ranks").extract().unwrap()))
+    });
+    let Some((times, ranks)) = ranks else { return };
+
+    let edges = vec![
+        Edge { source: NodeId(0), target: NodeId(1), time_of_creation: Time(1.0), weight: 1.0 },
+        Edge { source: NodeId(1), target: NodeId(2), time_of_creation: Time(2.0), weight: 2.0 },
+    ];
+    let mut scenario = Scenario::new("python", 3, vec![NodeId(0)], edges);
+    scenario.decay_constant = 0.1;
+    let expected = run_scenario(&scenario, &PageRankVariant { config: RankConfig::new() }, &[Time(1.0), Time(2.0)], &scenario.graph_options);
+    assert_eq!(times, [1.0, 2.0]);
+    assert_eq!(ranks, expected.history.frames());
+}
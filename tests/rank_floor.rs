@@ -0,0 +1,40 @@
+// --rank-floor mixes enough of the uniform distribution into teleportation that every active node
+// keeps at least the floor, including one nobody endorses
+use trust_flow::graph::{compile_graph, Edge, GraphOptions};
+use trust_flow::rank::{floored_targets, DanglingPolicy, PageRankVariant, RankConfig, RankingAlgorithm, Solver};
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight: 1.0 }
+}
+
+fn assert_ranks(actual: &[f64], expected: &[f64], what: &str) {
+    assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{}: {:?} instead of {:?}", what, actual, expected);
+}
+
+#[test]
+fn every_node_keeps_the_floor() {
+    // 0 ⇄ 1 and node 2 on its own, keeping its rank, with all teleportation at 0
+    let edges = [edge(0, 1), edge(1, 0)];
+    let graph = compile_graph(&edges, &[1.0, 1.0], 3, &GraphOptions::default()).unwrap();
+    let targets = [1.0, 0.0, 0.0];
+    let config = RankConfig::new().dangling_policy(DanglingPolicy::Retain).iterations(200).tolerance(1e-15);
+    assert_eq!(floored_targets(&graph, &targets, &config), targets);
+    let unfloored = PageRankVariant { config: config.clone() }.rank(&graph, &targets);
+    assert_ranks(&unfloored, &[2.0 / 3.0, 1.0 / 3.0, 0.0], "no floor");
+
+    // A floor of 0.1 takes λ = 0.1 · 3 / (1 - 0.5) = 0.6 of uniform teleportation
+    let floored = config.rank_floor(0.1);
+    assert_ranks(&floored_targets(&graph, &targets, &floored), &[0.6, 0.2, 0.2], "targets");
+    for config in [floored.clone(), floored.clone().solver(Solver::Exact)] {
+        let ranks = PageRankVariant { config: config.clone() }.rank(&graph, &targets);
+        assert_ranks(&ranks, &[7.0 / 15.0, 1.0 / 3.0, 0.2], &format!("{:?}", config.solver));
+    }
+}
+
+#[test]
+fn floors_are_capped_by_the_damping_factor() {
+    let graph = compile_graph(&[], &[], 2, &GraphOptions::default()).unwrap();
+    // Above (1 - d) / n, teleportation becomes uniform
+    assert_eq!(floored_targets(&graph, &[1.0, 0.0], &RankConfig::new().rank_floor(0.5)), [0.5, 0.5]);
+}
@@ -0,0 +1,30 @@
+// Retractions cut a share of the weight of the edges between two nodes created up to their time,
+// and the cut weight keeps decaying with the edge
+use trust_flow::graph::Edge;
+use trust_flow::scenario::{EdgeCorrection, Scenario};
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, time: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight: 1.0 }
+}
+
+fn assert_weights(actual: &[f64], expected: &[f64]) {
+    assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} instead of {:?}", actual, expected);
+}
+
+#[test]
+fn retractions_cut_earlier_edges() {
+    // 0 → 1 at times 0 and 3 and 1 → 0 at time 0, halving every time unit
+    let mut scenario = Scenario::new("retractions", 2, Vec::new(), vec![edge(0, 1, 0.0), edge(0, 1, 3.0), edge(1, 0, 0.0)]);
+    scenario.decay_constant = std::f64::consts::LN_2;
+    assert_weights(&scenario.edge_weights(Time(4.0)), &[1.0 / 16.0, 0.5, 1.0 / 16.0]);
+
+    // Three quarters of 0 → 1 retracted at time 2 leaves the later edge and the reverse one alone
+    scenario.corrections.push(EdgeCorrection { source: NodeId(0), target: NodeId(1), time: Time(2.0), share: 0.75 });
+    assert_weights(&scenario.edge_weights(Time(4.0)), &[1.0 / 64.0, 0.5, 1.0 / 16.0]);
+    assert_weights(&scenario.edge_weights(Time(5.0)), &[1.0 / 128.0, 0.25, 1.0 / 32.0]);
+
+    // Half of what is left at time 3, the later edge included
+    scenario.corrections.push(EdgeCorrection { source: NodeId(0), target: NodeId(1), time: Time(3.0), share: 0.5 });
+    assert_weights(&scenario.edge_weights(Time(4.0)), &[1.0 / 128.0, 0.25, 1.0 / 16.0]);
+}
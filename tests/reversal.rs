@@ -0,0 +1,36 @@
+// --reverse-edges: an input whose arrows point from endorsee to endorser, turned around, ranks
+// like the same graph recorded the right way, retractions included
+use trust_flow::graph::Edge;
+use trust_flow::rank::RankConfig;
+use trust_flow::registry::PAGERANK_VARIANT;
+use trust_flow::scenario::{EdgeCorrection, Scenario};
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, time: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight: 1.0 }
+}
+
+fn ranks(scenario: &Scenario, time: Time) -> Vec<f64> {
+    let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+    PAGERANK_VARIANT.create(&RankConfig::new()).rank(&graph, &scenario.teleportation_targets(time))
+}
+
+#[test]
+fn reversed_input_ranks_like_the_original() {
+    // Expert 0 endorses 1 and 2, and 1 endorses 2; half of 0 → 1 is retracted at time 2
+    let mut original = Scenario::new("chain", 3, vec![NodeId(0)], vec![edge(0, 1, 0.0), edge(0, 2, 1.0), edge(1, 2, 1.0)]);
+    original.corrections.push(EdgeCorrection { source: NodeId(0), target: NodeId(1), time: Time(2.0), share: 0.5 });
+    let mut reversed = original.clone();
+    reversed.edges = original.edges.iter().map(Edge::reversed).collect();
+    reversed.corrections[0] = EdgeCorrection { source: NodeId(1), target: NodeId(0), ..original.corrections[0] };
+    assert!(reversed.looks_reversed() && !original.looks_reversed());
+
+    reversed.reverse_edges();
+    assert!(reversed.reversed_edges);
+    let ends = |edges: &[Edge]| edges.iter().map(|e| (e.source, e.target)).collect::<Vec<_>>();
+    assert_eq!(ends(&reversed.edges), ends(&original.edges));
+    assert_eq!(ends(&[reversed.oriented(edge(0, 1, 0.0))]), [(NodeId(1), NodeId(0))]);
+    for time in [Time(1.0), Time(3.0)] {
+        assert_eq!(ranks(&reversed, time), ranks(&original, time));
+    }
+}
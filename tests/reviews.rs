@@ -0,0 +1,53 @@
+// Review histories become trust graphs: every review is an edge from the reviewer to the author
+// of the change, created on the day it was merged.
+use trust_flow::reviews::{read_git_log, read_github_reviews};
+use trust_flow::types::NodeId;
+
+const GIT_LOG: &str = "\
+commit 1111
+author Alice <alice@example.org>
+date 864000
+Reviewed-by: Bob <BOB@example.org>
+Reviewed-by: Alice <alice@example.org>
+
+commit 2222
+author Bob <bob@example.org>
+date 1728000
+Acked-by: Carol <carol@example.org>
+Reviewed-by: Alice <alice@example.org>
+";
+
+#[test]
+fn git_trailers_point_from_reviewer_to_author() {
+    let scenario = read_git_log(GIT_LOG, "log").unwrap();
+    assert_eq!(scenario.node_info.len(), 3);
+    let edges: Vec<_> = scenario.edges.iter().map(|e| (e.source, e.target, e.time_of_creation.value())).collect();
+    // Alice's review of her own commit does not count, and emails identify people case-insensitively
+    assert_eq!(edges, [(NodeId(1), NodeId(0), 10.0), (NodeId(2), NodeId(1), 20.0), (NodeId(0), NodeId(1), 20.0)]);
+    assert_eq!(scenario.edge_sources[1].event_id.as_deref(), Some("2222"));
+    assert_eq!(scenario.node_label(NodeId(2)), "Carol");
+}
+
+#[test]
+fn a_log_without_reviews_is_an_error() {
+    assert!(read_git_log("commit 1111\nauthor Alice <alice@example.org>\ndate 0\n", "log").is_err());
+}
+
+#[test]
+fn approvals_of_merged_pull_requests_count_once_per_reviewer() {
+    let export = r#"[
+        {"number": 7, "author": {"login": "alice"}, "mergedAt": "1970-01-11T00:00:00Z", "reviews": [
+            {"author": {"login": "bob"}, "state": "COMMENTED"},
+            {"author": {"login": "bob"}, "state": "APPROVED"},
+            {"author": {"login": "bob"}, "state": "APPROVED"},
+            {"author": {"login": "carol"}, "state": "CHANGES_REQUESTED"}
+        ]},
+        {"number": 8, "author": {"login": "carol"}, "mergedAt": null, "reviews": [
+            {"author": {"login": "alice"}, "state": "APPROVED"}
+        ]}
+    ]"#;
+    let scenario = read_github_reviews(export, "pulls").unwrap();
+    let edges: Vec<_> = scenario.edges.iter().map(|e| (e.source, e.target, e.time_of_creation.value())).collect();
+    assert_eq!(edges, [(NodeId(1), NodeId(0), 10.0)]);
+    assert_eq!(scenario.node_label(NodeId(1)), "bob");
+}
@@ -0,0 +1,36 @@
+// Group-level trust: members' ranks summed, or averaged by their teleport weights, per org
+use trust_flow::nodes::NodeInfo;
+use trust_flow::rollup::{group_trust, Rollup};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::NodeId;
+
+// Nodes 0 and 2 in org a, node 1 in org b and node 3 in none
+fn scenario() -> Scenario {
+    let mut scenario = Scenario::new("orgs", 4, Vec::new(), Vec::new());
+    scenario.node_info = [Some("a"), Some("b"), Some("a"), None]
+        .into_iter()
+        .map(|org| NodeInfo { org: org.map(str::to_string), ..NodeInfo::default() })
+        .collect();
+    scenario
+}
+
+#[test]
+fn summed_trust_is_the_groups_share_of_the_rank() {
+    let groups = group_trust(&scenario(), &[vec![0.1, 0.2, 0.3, 0.4]], &Rollup::parse("org").unwrap());
+    assert_eq!(groups.iter().map(|group| group.label.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(groups[0].members, [NodeId(0), NodeId(2)]);
+    assert_eq!(groups[1].members, [NodeId(1)]);
+    assert!((groups[0].trust[0] - 0.4).abs() < 1e-12, "{:?}", groups[0]);
+    assert_eq!(groups[1].trust, [0.2]);
+}
+
+#[test]
+fn mean_trust_is_weighted_by_teleport_weights() {
+    let mut scenario = scenario();
+    scenario.node_info[0].teleport_weight = Some(3.0);
+    // (3 · 0.1 + 0.3) / 4
+    let groups = group_trust(&scenario, &[vec![0.1, 0.2, 0.3, 0.4]], &Rollup::parse("org:mean").unwrap());
+    assert!((groups[0].trust[0] - 0.15).abs() < 1e-12, "{:?}", groups[0]);
+    assert_eq!(groups[1].trust, [0.2]);
+    assert!(Rollup::parse("org:median").is_err() && Rollup::parse("community").is_err());
+}
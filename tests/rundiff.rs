@@ -0,0 +1,45 @@
+// The `diff` subcommand compares two exported rank timelines frame by frame, over the times both
+// have; nodes missing from one of them rank 0 there.
+use trust_flow::history::RankHistory;
+use trust_flow::rundiff::{diff_histories, largest_movers};
+use trust_flow::types::{NodeId, Time};
+
+fn history(frames: &[(f64, &[f64])]) -> RankHistory {
+    let mut history = RankHistory::new();
+    for &(time, ranks) in frames {
+        history.push(Time(time), ranks.to_vec());
+    }
+    history
+}
+
+// Round trip through the ranks.csv of --export csv
+fn exported(history: &RankHistory, names: &[String]) -> RankHistory {
+    let mut csv = Vec::new();
+    history.write_timeline_csv(&mut csv, names).unwrap();
+    let text = String::from_utf8(csv).unwrap();
+    assert_eq!(RankHistory::read_timeline_labels(&text), names);
+    RankHistory::read_timeline_csv(&text).unwrap()
+}
+
+#[test]
+fn a_run_diffed_with_itself_does_not_change() {
+    let before = history(&[(0.0, &[0.5, 0.3, 0.2]), (1.0, &[0.2, 0.3, 0.5])]);
+    let names: Vec<String> = ["a", "b", "c"].iter().map(|name| name.to_string()).collect();
+    for diff in diff_histories(&before, &exported(&before, &names)) {
+        assert_eq!(diff.l1_change, 0.0);
+        assert_eq!((diff.spearman, diff.kendall_tau), (1.0, 1.0));
+    }
+}
+
+#[test]
+fn only_frames_at_the_same_times_are_compared() {
+    let before = history(&[(0.0, &[0.5, 0.5]), (1.0, &[0.6, 0.4]), (2.0, &[0.7, 0.3])]);
+    let after = history(&[(1.0, &[0.3, 0.3, 0.4]), (2.0, &[0.7, 0.2, 0.1]), (3.0, &[1.0, 0.0, 0.0])]);
+    let diffs = diff_histories(&before, &after);
+    assert_eq!(diffs.iter().map(|diff| diff.time).collect::<Vec<_>>(), [Time(1.0), Time(2.0)]);
+    // Node 2 is new in the second run
+    assert!((diffs[0].delta(NodeId(2)) - 0.4).abs() < 1e-12);
+    assert!((diffs[0].l1_change - 0.8).abs() < 1e-12);
+    assert!((diffs[0].spearman + 3f64.sqrt() / 2.0).abs() < 1e-12);
+    assert_eq!(largest_movers(&diffs), [NodeId(2), NodeId(0), NodeId(1)]);
+}
@@ -0,0 +1,42 @@
+// Every bundled scenario file with assertions is a regression test of the ranking: its expected
+// ranks, orders and approximate values must keep holding after changes to the algorithms.
+use std::fs;
+use std::path::Path;
+
+use trust_flow::assertions::{assert_scenario_file, check_scenario, Assertion, Condition, When};
+use trust_flow::scenario::Scenario;
+use trust_flow::types::{NodeId, Time};
+
+#[test]
+fn bundled_scenarios_pass_their_assertions() {
+    let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+    for entry in fs::read_dir(folder).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|extension| extension == "scenario") {
+            assert_scenario_file(path.to_str().unwrap(), "pagerank");
+        }
+    }
+}
+
+#[test]
+fn orders_approximate_values_and_times_are_parsed() {
+    let assertion = Assertion::parse("order 0 > 1 > 3 at time 6", 1).unwrap();
+    assert_eq!(assertion.condition, Condition::Order(vec![NodeId(0), NodeId(1), NodeId(3)]));
+    assert_eq!(assertion.when, When::AtTime(Time(6.0)));
+    let assertion = Assertion::parse("rank 2 = 0.25 within 0.01 by time 4", 1).unwrap();
+    assert_eq!(assertion.condition, Condition::Approx { node: NodeId(2), value: 0.25, tolerance: 0.01 });
+    assert_eq!(assertion.when, When::ByTime(Time(4.0)));
+    assert!(Assertion::parse("order 0", 1).is_err());
+    assert!(Assertion::parse("0.5 = rank 1", 1).is_err());
+    assert!(Assertion::parse("rank 1 > rank 2 at minute 3", 1).is_err());
+}
+
+#[test]
+fn failed_assertions_report_the_actual_ranks() {
+    let scenario = Scenario::parse("nodes 3\nexpert 0\nedge 0 1 0\nedge 1 2 1\nassert order 2 > 1\nassert rank 0 = 1 within 0.01 at time 1\nassert rank 0 > rank 2 at time 0.5\nassert rank 1 > rank 2 by time 1\n").unwrap();
+    let outcomes = check_scenario(&scenario, "pagerank").unwrap();
+    let passed: Vec<bool> = outcomes.iter().map(|o| o.passed).collect();
+    assert_eq!(passed, [false, false, false, true]);
+    assert!(outcomes[0].detail.contains("rank 2 = 0."), "{}", outcomes[0].detail);
+    assert_eq!(outcomes[2].detail, "no frame at time 0.5");
+}
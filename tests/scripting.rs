@@ -0,0 +1,72 @@
+// Rhai scripts in scenario hooks replace the built-in decay, weights and teleportation, and must
+// not be able to stall or break a run. Without the scripting feature, scenarios with them are rejected.
+use trust_flow::scenario::Scenario;
+#[cfg(feature = "scripting")]
+use trust_flow::types::Time;
+
+#[cfg(feature = "scripting")]
+const TOLERANCE: f64 = 1e-12;
+
+// Edges 0 → 1 at time 0, 1 → 2 at time 1 and 2 → 0 at time 1.5, all of weight 1
+const GRAPH: &str = "nodes 3\nexpert 0\nedge 0 1 0\nedge 1 2 1\nedge 2 0 1.5\n";
+
+#[cfg(feature = "scripting")]
+fn with_script(lines: &str) -> Scenario {
+    Scenario::parse(&format!("{}{}\n", GRAPH, lines)).unwrap()
+}
+
+#[cfg(feature = "scripting")]
+fn assert_close(got: &[f64], want: &[f64]) {
+    assert!(got.len() == want.len() && got.iter().zip(want).all(|(a, b)| (a - b).abs() < TOLERANCE), "{:?} != {:?}", got, want);
+}
+
+#[test]
+#[cfg(feature = "scripting")]
+fn decay_scripts_replace_exponential_decay() {
+    // Ages 2.5, 1.5 and 1 at time 2.5
+    assert_close(&with_script("script decay w0 / (1.0 + age)").edge_weights(Time(2.5)), &[1.0 / 3.5, 1.0 / 2.5, 0.5]);
+    assert_close(&with_script("decay 0.5\nscript decay w0 / (1.0 + k * age)").edge_weights(Time(2.5)), &[1.0 / 2.25, 1.0 / 1.75, 1.0 / 1.5]);
+    // Ages 2, 1 and 0.5 at time 2
+    assert_close(&with_script("script decay if age < 1.0 { w0 } else { w0 / 2.0 }").edge_weights(Time(2.0)), &[0.5, 0.5, 1.0]);
+    let halving = with_script("script decay let half_life = 2.0; w0 * 0.5 ** (age / half_life)");
+    assert_close(&halving.edge_weights(Time(2.0)), &[0.5, 0.5f64.sqrt(), 0.5f64.powf(0.25)]);
+    // Edges do not exist before they are created, whatever the script says
+    assert_close(&with_script("script decay w0").edge_weights(Time(1.0)), &[1.0, 1.0, 0.0]);
+    assert_close(&with_script("script decay rhai w0").edge_weights(Time(1.0)), &[1.0, 1.0, 0.0]);
+}
+
+#[test]
+#[cfg(feature = "scripting")]
+fn weight_and_teleport_scripts() {
+    let capped = with_script("decay 0\nscript weight let capped = min(w, 0.5); capped + source / 10.0");
+    assert_close(&capped.edge_weights(Time(2.0)), &[0.5, 0.6, 0.7]);
+    let experts = with_script("script teleport (if expert == 1.0 { 0.8 / experts } else { 0.0 }) + 0.2 / n");
+    assert_close(&experts.teleportation_targets(Time(2.0)), &[0.8 + 0.2 / 3.0, 0.2 / 3.0, 0.2 / 3.0]);
+    assert_close(&with_script("script teleport node == 2.0").teleportation_targets(Time(2.0)), &[0.0, 0.0, 1.0]);
+}
+
+#[test]
+#[cfg(feature = "scripting")]
+fn scripts_only_see_their_hooks_variables() {
+    assert!(Scenario::parse(&format!("{}script decay w * age\n", GRAPH)).is_err());
+    assert!(Scenario::parse(&format!("{}script weight w * (\n", GRAPH)).is_err());
+    assert!(Scenario::parse(&format!("{}script teleport prior + expert\n", GRAPH)).is_ok());
+    assert!(Scenario::parse(&format!("{}script rank w\n", GRAPH)).is_err());
+}
+
+#[test]
+#[cfg(feature = "scripting")]
+fn failing_scripts_count_as_zero() {
+    let plain = Scenario::parse(GRAPH).unwrap();
+    for script in ["loop { }", "import \"weights\" as m; w", "\"heavy\"", "throw \"no weight\""] {
+        let scenario = with_script(&format!("script weight {}", script));
+        assert_eq!(scenario.edge_weights(Time(2.0)), [0.0; 3], "{}", script);
+        assert_eq!(scenario.teleportation_targets(Time(2.0)), plain.teleportation_targets(Time(2.0)));
+    }
+}
+
+#[test]
+fn scripts_need_the_scripting_feature() {
+    let scenario = Scenario::parse(&format!("{}script decay w0\n", GRAPH));
+    assert_eq!(scenario.is_ok(), cfg!(feature = "scripting"));
+}
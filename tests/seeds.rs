@@ -0,0 +1,73 @@
+// Expert groups are alternative expert sets; each is ranked as its own personalization of the same
+// graphs, and their shares of every node's rank can be drawn as pies.
+use trust_flow::dot::{write_dot_group_shares, FrameRender};
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::seeds::{rank_expert_groups, write_group_ranks_csv, ExpertGroup};
+use trust_flow::types::{FrameIdx, NodeId, Time};
+
+const TOLERANCE: f64 = 1e-12;
+
+// Two chains, 0 -> 1 -> 2 and 3 -> 4 -> 5, each with its own group at the start
+const TWO_CHAINS: &str = "nodes 6\nexpert 0\nedge 0 1 0\nedge 1 2 0\nedge 3 4 0\nedge 4 5 0\nexpert-group left 0\nexpert-group right 3,4\n";
+
+#[test]
+fn expert_groups_are_read_from_scenarios() {
+    let scenario = Scenario::parse(TWO_CHAINS).unwrap();
+    assert_eq!(scenario.expert_groups, [
+        ExpertGroup { name: "left".to_string(), members: vec![NodeId(0)] },
+        ExpertGroup { name: "right".to_string(), members: vec![NodeId(3), NodeId(4)] },
+    ]);
+    assert_eq!(scenario.experts, [NodeId(0)], "groups leave the scenario's experts alone");
+    assert!(Scenario::parse("nodes 3\nexpert-group a 0\nexpert-group a 1\n").is_err());
+    assert!(Scenario::parse("nodes 3\nexpert-group a 0,5\n").is_err());
+    assert!(Scenario::parse("nodes 3\nexpert-group a\n").is_err());
+}
+
+#[test]
+fn every_group_ranks_like_a_scenario_with_its_members_as_experts() {
+    let scenario = Scenario::parse(TWO_CHAINS).unwrap();
+    let algorithm = PageRankVariant { config: RankConfig::default() };
+    let times = [Time(0.0), Time(2.0)];
+    let ranks = rank_expert_groups(&scenario, &algorithm, &times);
+    assert_eq!(ranks.groups, ["left", "right"]);
+    assert_eq!(ranks.times, times);
+    for (g, group) in scenario.expert_groups.iter().enumerate() {
+        let personalized = Scenario { experts: group.members.clone(), ..scenario.clone() };
+        let output = run_scenario(&personalized, &algorithm, &times, &personalized.graph_options);
+        for frame in 0..times.len() {
+            let expected = output.history.ranks_at(FrameIdx(frame));
+            assert!(ranks.ranks[frame][g].iter().zip(expected).all(|(a, b)| (a - b).abs() < TOLERANCE), "{} in frame {}", group.name, frame);
+        }
+    }
+}
+
+#[test]
+fn shares_follow_the_groups_reach() {
+    let scenario = Scenario::parse(TWO_CHAINS).unwrap();
+    let ranks = rank_expert_groups(&scenario, &PageRankVariant { config: RankConfig::default() }, &[Time(0.0)]);
+    let left = ranks.shares(FrameIdx(0), NodeId(2));
+    let right = ranks.shares(FrameIdx(0), NodeId(5));
+    assert!((left.iter().sum::<f64>() - 1.0).abs() < TOLERANCE);
+    assert!(left[0] > left[1] && right[1] > right[0], "{:?} {:?}", left, right);
+
+    let mut csv = Vec::new();
+    write_group_ranks_csv(&mut csv, &scenario, &ranks).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().next(), Some("frame,time,node,group,rank,share"));
+    assert_eq!(csv.lines().count(), 1 + 6 * 2);
+}
+
+#[test]
+fn pies_split_nodes_by_group() {
+    let scenario = Scenario::parse(TWO_CHAINS).unwrap();
+    let shares = vec![vec![0.25, 0.75]; 2];
+    let names = ["a".to_string(), "b".to_string()];
+    let frame = FrameRender::new(&[0.5, 0.5], &scenario.edges[..1], &[1.0], &names, &[(0.0, 0.0), (1.0, 0.0)]).experts(&[NodeId(0)]).title("", "none");
+    let mut dot = Vec::new();
+    write_dot_group_shares(&mut dot, &["left".to_string(), "right".to_string()], &shares, &frame, &scenario.style).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.contains("style=wedged, fillcolor=\"#1f77b4;0.250:#ff7f0e\""), "{}", dot);
+    assert!(dot.contains(">left<") && dot.contains(">right<"), "{}", dot);
+}
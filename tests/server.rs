@@ -0,0 +1,153 @@
+// The HTTP API rejects parameters that would make a run meaningless or hold the server up, with a
+// 400 rather than a panic, and a silent client or a long run does not keep others waiting.
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use trust_flow::rank::RankConfig;
+use trust_flow::server::{Request, Response, Server, MAX_FRAMES, MAX_ITERATIONS, MAX_NODES};
+
+fn request(method: &str, path: &str, query: &[(&str, &str)], body: &str) -> Request {
+    Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.iter().map(|&(key, value)| (key.to_string(), value.to_string())).collect::<BTreeMap<_, _>>(),
+        content_type: None,
+        body: body.as_bytes().to_vec(),
+    }
+}
+
+// A server with graph 0: a small cycle with node 0 as the expert
+fn server() -> Server {
+    let server = Server::new(RankConfig::new());
+    let created = server.handle(&request("POST", "/graphs", &[("experts", "0"), ("format", "csv")], "source,target,time\n0,1,1\n1,2,2\n2,0,3\n"));
+    assert_eq!(created.status, 201, "{}", body(&created));
+    server
+}
+
+fn body(response: &Response) -> String {
+    String::from_utf8_lossy(&response.body).into_owned()
+}
+
+fn assert_rejected(server: &Server, method: &str, path: &str, query: &[(&str, &str)], message: &str) {
+    let response = server.handle(&request(method, path, query, ""));
+    assert_eq!(response.status, 400, "{} {} {:?}: {}", method, path, query, body(&response));
+    assert!(body(&response).contains(message), "{} {} {:?}: {}", method, path, query, body(&response));
+}
+
+#[test]
+fn runs_reject_invalid_numbers() {
+    let server = server();
+    for (key, value, message) in [
+        ("decay", "nan", "decay must be a finite number"),
+        ("decay", "inf", "decay must be a finite number"),
+        ("decay", "-0.5", "decay must be non-negative"),
+        ("half_life", "nan", "half_life must be a finite number"),
+        ("half_life", "0", "half_life must be positive"),
+        ("capacity", "nan", "capacity must be a finite number"),
+        ("damping", "nan", "damping must be a finite number"),
+        ("frame_times", "1,nan", "invalid frame time"),
+        ("frame_times", "inf", "invalid frame time"),
+    ] {
+        assert_rejected(&server, "POST", "/graphs/0/runs", &[(key, value)], message);
+    }
+    // Nothing was kept of the rejected runs
+    assert_eq!(server.handle(&request("GET", "/runs/0", &[], "")).status, 404);
+
+    let created = server.handle(&request("POST", "/graphs/0/runs", &[("decay", "0.1")], ""));
+    assert_eq!(created.status, 201, "{}", body(&created));
+    let frame = body(&server.handle(&request("GET", "/runs/0/frames/3", &[], "")));
+    assert!(frame.contains("\"ranks\":[0.") && !frame.contains("null"), "{}", frame);
+}
+
+#[test]
+fn runs_are_limited_in_size() {
+    let server = server();
+    let too_many = (MAX_ITERATIONS + 1).to_string();
+    assert_rejected(&server, "POST", "/graphs/0/runs", &[("iterations", &too_many)], "iterations must be an integer from 0 to");
+    let frame_times = (0..=MAX_FRAMES).map(|t| t.to_string()).collect::<Vec<_>>().join(",");
+    assert_rejected(&server, "POST", "/graphs/0/runs", &[("frame_times", &frame_times)], "a run has at most");
+    let limit = MAX_ITERATIONS.to_string();
+    assert_eq!(server.handle(&request("POST", "/graphs/0/runs", &[("iterations", &limit), ("frame_times", "3")], "")).status, 201);
+}
+
+#[test]
+fn graphs_are_limited_in_size() {
+    let server = server();
+    for (format, events, message) in [
+        ("csv", format!("source,target,time\n0,{},1\n", MAX_NODES), "node indices must be below"),
+        ("csv", "source,target,time\n0,1000000000000,1\n".to_string(), "node indices must be below"),
+        ("jsonl", "{\"source\": -1, \"target\": 0, \"time\": 1}\n".to_string(), "invalid source: -1"),
+        ("jsonl", "{\"source\": 0, \"target\": 1e20, \"time\": 1}\n".to_string(), "invalid target: 100000000000000000000"),
+        ("jsonl", "{\"source\": 0.5, \"target\": 1, \"time\": 1}\n".to_string(), "invalid source: 0.5"),
+    ] {
+        let response = server.handle(&request("POST", "/graphs/0/edges", &[("format", format)], &events));
+        assert_eq!(response.status, 400, "{}", body(&response));
+        assert!(body(&response).contains(message), "{}", body(&response));
+    }
+    let experts = MAX_NODES.to_string();
+    assert_rejected(&server, "POST", "/graphs", &[("experts", &experts)], "invalid expert");
+
+    let largest = format!("source,target,time\n0,{},4\n", MAX_NODES - 1);
+    let added = server.handle(&request("POST", "/graphs/0/edges", &[("format", "csv")], &largest));
+    assert_eq!(added.status, 200, "{}", body(&added));
+    assert!(body(&added).contains(&format!("\"nodes\":{},\"edges\":4", MAX_NODES)), "{}", body(&added));
+}
+
+#[test]
+fn queries_reject_times_that_are_not_finite() {
+    let server = server();
+    for time in ["nan", "inf", "-inf", "soon"] {
+        assert_rejected(&server, "GET", "/graphs/0/ranks", &[("seeds", "0"), ("time", time)], "time must be a finite number");
+        assert_rejected(&server, "GET", "/graphs/0/trust", &[("from", "0"), ("to", "1"), ("time", time)], "time must be a finite number");
+    }
+    assert_eq!(server.handle(&request("GET", "/graphs/0/ranks", &[("seeds", "0"), ("time", "2.5")], "")).status, 200);
+}
+
+fn get(address: &str, path: &str) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: test\r\n\r\n", path).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn silent_clients_time_out() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || Server::new(RankConfig::new()).workers(1).request_timeout(Duration::from_millis(200)).serve(&listener));
+
+    // Connects and never sends a request
+    let _silent = TcpStream::connect(&address).unwrap();
+    let started = Instant::now();
+    let response = get(&address, "/algorithms");
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert!(started.elapsed() < Duration::from_secs(5), "answered after {:?}", started.elapsed());
+
+    // Headers without an end
+    let mut stream = TcpStream::connect(&address).unwrap();
+    write!(stream, "GET /algorithms HTTP/1.1\r\n").unwrap();
+    let header = format!("X-Padding: {}\r\n", "a".repeat(1000));
+    while stream.write_all(header.as_bytes()).is_ok() && started.elapsed() < Duration::from_secs(5) {}
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    assert!(response.is_empty() || response.starts_with("HTTP/1.1 400"), "{}", response);
+    assert!(get(&address, "/algorithms").starts_with("HTTP/1.1 200 OK"));
+}
+
+#[test]
+fn requests_are_served_while_others_wait() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || Server::new(RankConfig::new()).workers(3).serve(&listener));
+
+    // Two clients that hold their workers until the request timeout of 10 seconds
+    let _silent = [TcpStream::connect(&address).unwrap(), TcpStream::connect(&address).unwrap()];
+    let started = Instant::now();
+    assert!(get(&address, "/algorithms").starts_with("HTTP/1.1 200 OK"));
+    assert!(get(&address, "/metrics").starts_with("HTTP/1.1 200 OK"));
+    assert!(started.elapsed() < Duration::from_secs(5), "answered after {:?}", started.elapsed());
+}
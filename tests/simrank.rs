@@ -0,0 +1,65 @@
+// SimRank on small graphs with hand-checked similarities: nodes endorsed by the same nodes are
+// similar, similarity spreads one level of endorsers per iteration, and weights split it
+use trust_flow::graph::{CompiledGraph, Edge};
+use trust_flow::scenario::Scenario;
+use trust_flow::simrank::{write_similarity_csv, SimRank};
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+// Undecayed graph of the edges
+fn graph(num_of_nodes: usize, edges: Vec<Edge>) -> CompiledGraph {
+    let mut scenario = Scenario::new("simrank", num_of_nodes, vec![NodeId(0)], edges);
+    scenario.decay_constant = 0.0;
+    scenario.frame_graph(Time(0.0), &scenario.edge_weights(Time(0.0))).unwrap()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-12, "{} instead of {}", actual, expected);
+}
+
+#[test]
+fn nodes_with_the_same_endorser_are_similar() {
+    // 0 endorses 1 and 2: s(1, 2) = 0.8 · s(0, 0); 0 has no endorsers, so it is similar to nobody
+    let similarity = SimRank::default().compute(&graph(3, vec![edge(0, 1, 1.0), edge(0, 2, 1.0)]));
+    assert_close(similarity.get(NodeId(1), NodeId(2)), 0.8);
+    assert_close(similarity.get(NodeId(2), NodeId(1)), 0.8);
+    assert_close(similarity.get(NodeId(0), NodeId(1)), 0.0);
+    for node in 0..3 {
+        assert_close(similarity.get(NodeId(node), NodeId(node)), 1.0);
+    }
+    assert_eq!(similarity.top_similar(NodeId(1), 5), [(NodeId(2), 0.8)]);
+    assert_eq!(similarity.top_similar(NodeId(0), 5), []);
+}
+
+#[test]
+fn similarity_spreads_one_level_per_iteration() {
+    // 0 → 1 → 3 and 0 → 2 → 4: s(3, 4) = 0.8 · s(1, 2) = 0.64, once s(1, 2) has been found
+    let graph = graph(5, vec![edge(0, 1, 1.0), edge(0, 2, 1.0), edge(1, 3, 1.0), edge(2, 4, 1.0)]);
+    let once = SimRank { importance: 0.8, num_of_iterations: 1 }.compute(&graph);
+    assert_close(once.get(NodeId(1), NodeId(2)), 0.8);
+    assert_close(once.get(NodeId(3), NodeId(4)), 0.0);
+    let converged = SimRank::default().compute(&graph);
+    assert_close(converged.get(NodeId(3), NodeId(4)), 0.64);
+    assert_close(converged.get(NodeId(1), NodeId(4)), 0.0);
+    let top = converged.top_similar(NodeId(3), 1);
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].0, NodeId(4));
+    assert_close(top[0].1, 0.64);
+}
+
+#[test]
+fn weights_split_the_similarity() {
+    // 2 is endorsed by 0 alone, 3 by 0 with weight 3 and by 1 with weight 1:
+    // s(2, 3) = 0.5 · (3/4 · s(0, 0) + 1/4 · s(0, 1)) = 0.375
+    let graph = graph(4, vec![edge(0, 2, 1.0), edge(0, 3, 3.0), edge(1, 3, 1.0)]);
+    let similarity = SimRank { importance: 0.5, num_of_iterations: 5 }.compute(&graph);
+    assert_close(similarity.get(NodeId(2), NodeId(3)), 0.375);
+
+    let labels: Vec<String> = ["a", "b", "c", "d"].map(String::from).to_vec();
+    let mut csv = Vec::new();
+    write_similarity_csv(&mut csv, &[(Time(0.0), similarity)], &labels, 3).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(), "frame,time,node,similar_node,similarity\n1,0,c,d,0.375000\n1,0,d,c,0.375000\n");
+}
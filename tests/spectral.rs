@@ -0,0 +1,38 @@
+// Convergence outlook of the power iteration from the second eigenvalue of the transition matrix,
+// on graphs whose spectrum is known
+use trust_flow::graph::{compile_graph, Edge, GraphOptions};
+use trust_flow::rank::RankConfig;
+use trust_flow::spectral::{iterations_needed, second_eigenvalue, SpectralEstimate};
+use trust_flow::types::{NodeId, Time};
+
+#[test]
+fn second_eigenvalues_of_known_matrices() {
+    // A directed cycle keeps rotating the error, and a complete graph forgets it in one step
+    let cycle: Vec<(usize, usize, f64)> = (0..5).map(|i| (i, (i + 1) % 5, 1.0)).collect();
+    assert!((second_eigenvalue(5, &cycle) - 1.0).abs() < 1e-9);
+    let complete: Vec<(usize, usize, f64)> = (0..5).flat_map(|i| (0..5).map(move |j| (i, j, 0.2))).collect();
+    assert_eq!(second_eigenvalue(5, &complete), 0.0);
+    // Two nodes that each keep 3/4 and pass on 1/4: eigenvalues 1 and 1/2
+    let lazy = [(0, 0, 0.75), (0, 1, 0.25), (1, 0, 0.25), (1, 1, 0.75)];
+    assert!((second_eigenvalue(2, &lazy) - 0.5).abs() < 1e-9);
+    assert_eq!(second_eigenvalue(1, &[(0, 0, 1.0)]), 0.0);
+}
+
+#[test]
+fn iterations_needed_for_a_tolerance() {
+    // 2 · 0.5^n < 1e-6 from n = 21 on
+    assert_eq!(iterations_needed(0.5, 1e-6), Some(21));
+    assert_eq!(iterations_needed(0.0, 1e-6), Some(1));
+    assert_eq!(iterations_needed(0.5, 2.0), Some(0));
+    assert_eq!(iterations_needed(1.0, 1e-6), None);
+}
+
+#[test]
+fn frames_of_a_cycle_converge_at_the_damping_factor() {
+    let edges = [0, 1].map(|i| Edge { source: NodeId(i), target: NodeId(1 - i), time_of_creation: Time(0.0), weight: 1.0 });
+    let graph = compile_graph(&edges, &[1.0, 1.0], 2, &GraphOptions::default()).unwrap();
+    let estimate = SpectralEstimate::of_frame(Time(3.0), &graph, &[0.5, 0.5], &RankConfig::new(), 1e-6);
+    assert!((estimate.second_eigenvalue - 1.0).abs() < 1e-9, "{:?}", estimate);
+    assert!((estimate.convergence_factor - 0.5).abs() < 1e-9 && (estimate.spectral_gap() - 0.5).abs() < 1e-9, "{:?}", estimate);
+    assert_eq!(estimate.iterations_needed, Some(21));
+}
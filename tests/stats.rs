@@ -0,0 +1,81 @@
+// Per-frame statistics follow how concentrated the rank is; `style frame-stats on` adds them to
+// the frame label, and alarms warn when the concentration passes a threshold.
+use trust_flow::export::{write_frame_dot, ExportedRun};
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::stats::{concentration_alarms, ConcentrationAlarm, FrameStats};
+use trust_flow::types::{FrameIdx, Time};
+
+const TOLERANCE: f64 = 1e-12;
+
+#[test]
+fn equal_ranks_are_not_concentrated() {
+    let stats = FrameStats::new(&[0.25; 4], &[1.0, 0.5, 0.0], &[]);
+    assert!(stats.gini.abs() < TOLERANCE);
+    assert!((stats.entropy - 2.0).abs() < TOLERANCE);
+    assert_eq!(stats.active_edges, 2);
+    assert!((stats.total_weight - 1.5).abs() < TOLERANCE);
+}
+
+#[test]
+fn one_node_holding_all_rank_is_fully_concentrated() {
+    let stats = FrameStats::new(&[0.0, 0.0, 0.0, 1.0], &[], &[]);
+    assert!((stats.gini - 0.75).abs() < TOLERANCE);
+    assert!(stats.entropy.abs() < TOLERANCE);
+}
+
+#[test]
+fn nodes_out_of_the_graph_do_not_count() {
+    let stats = FrameStats::new(&[0.5, 0.5, 0.0], &[], &[true, true, false]);
+    assert!(stats.gini.abs() < TOLERANCE);
+    assert!((stats.entropy - 1.0).abs() < TOLERANCE);
+}
+
+#[test]
+fn frame_labels_show_the_statistics_when_enabled() {
+    let frame = |text: &str| {
+        let scenario = Scenario::parse(text).unwrap();
+        let algorithm = PageRankVariant { config: RankConfig::default() };
+        let output = run_scenario(&scenario, &algorithm, &[Time(1.0)], &scenario.graph_options);
+        let mut dot = Vec::new();
+        write_frame_dot(&mut dot, &ExportedRun { scenario: &scenario, output: &output, algorithm: "test" }, FrameIdx(0)).unwrap();
+        String::from_utf8(dot).unwrap()
+    };
+    let scenario = "nodes 3\nexpert 0\nedge 0 1 0\nedge 1 2 0\n";
+    assert!(!frame(scenario).contains("Gini"));
+    let dot = frame(&format!("{}style frame-stats on\n", scenario));
+    assert!(dot.contains("\nGini: "), "{}", dot);
+    assert!(dot.contains("\nActive edges: 2, total weight: "), "{}", dot);
+}
+
+// Node 0 is the expert and endorses 1, which endorses 2; 0 holds much of the rank
+const ALARMED: &str = "nodes 4\nexpert 0\nedge 0 1 0\nedge 1 2 0\nalarm top 1 above 0.3\nalarm gini above 0.9\n";
+
+#[test]
+fn alarms_go_off_when_rank_is_concentrated() {
+    let scenario = Scenario::parse(ALARMED).unwrap();
+    assert_eq!(scenario.alarms, [ConcentrationAlarm::TopShare { nodes: 1, share: 0.3 }, ConcentrationAlarm::Gini(0.9)]);
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::default() }, &[Time(0.0), Time(1.0)], &scenario.graph_options);
+    let alarms = concentration_alarms(&scenario, &output);
+    assert_eq!(alarms.len(), 2, "{:?}", alarms);
+    assert!(alarms.iter().all(|alarm| alarm.alarm == scenario.alarms[0] && alarm.value > 0.3));
+    assert!(alarms[0].message().starts_with("the top node holds "), "{}", alarms[0].message());
+}
+
+#[test]
+fn alarm_banners_are_drawn_on_request() {
+    let scenario = Scenario::parse(&format!("{}style alarm-banner on\n", ALARMED)).unwrap();
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::default() }, &[Time(1.0)], &scenario.graph_options);
+    let mut dot = Vec::new();
+    write_frame_dot(&mut dot, &ExportedRun { scenario: &scenario, output: &output, algorithm: "test" }, FrameIdx(0)).unwrap();
+    let banner = String::from_utf8(dot).unwrap().lines().find(|line| line.starts_with("  alarms [")).map(str::to_string);
+    assert!(banner.is_some_and(|banner| banner.contains("the top node holds ")));
+}
+
+#[test]
+fn invalid_alarms_are_rejected() {
+    assert!(ConcentrationAlarm::parse("top 0 above 0.5").is_err());
+    assert!(ConcentrationAlarm::parse("top 3 above 60").is_err());
+    assert!(Scenario::parse("nodes 2\nalarm bottom 3 below 0.1\n").is_err());
+}
@@ -0,0 +1,60 @@
+// Run database round trip: ranks recorded in a SQLite file are read back unchanged after the file
+// is reopened, and queries filter them by node name or index, scenario and time
+#![cfg(feature = "sqlite")]
+use std::env;
+use std::fs;
+
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::{run_scenario, RunOutput};
+use trust_flow::scenario::Scenario;
+use trust_flow::store::{write_stored_ranks_csv, RankQuery, RunStore};
+use trust_flow::types::{NodeId, Time};
+
+fn run(text: &str) -> (Scenario, RunOutput) {
+    let scenario = Scenario::parse(text).unwrap();
+    let output = run_scenario(&scenario, &PageRankVariant { config: RankConfig::new() }, &scenario.frame_times(), &scenario.graph_options);
+    (scenario, output)
+}
+
+#[test]
+fn recorded_runs_are_read_back() {
+    let path = env::temp_dir().join(format!("trust-flow-store-{}.sqlite", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let (chain, chain_output) = run("name chain\nnodes 3\nexpert 0\nnode 1 name=alice\nedge 0 1 1\nedge 1 2 2\nframes from 0 to 2 step 1\n");
+    let (pair, pair_output) = run("name pair\nnodes 2\nexpert 0\nnode 1 name=alice\nedge 0 1 0\nframes at 0,5\n");
+    {
+        let mut store = RunStore::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(store.record_run(&chain, "pagerank", "d=0.5", &chain_output).unwrap(), 1);
+        assert_eq!(store.record_run(&pair, "pagerank", "d=0.5", &pair_output).unwrap(), 2);
+    }
+
+    let store = RunStore::open(path.to_str().unwrap()).unwrap();
+    let alice = store.query_ranks(&RankQuery { node: Some("alice".to_string()), ..RankQuery::default() }).unwrap();
+    let stored: Vec<(i64, &str, usize, f64, f64)> = alice.iter().map(|r| (r.run, r.scenario.as_str(), r.frame, r.time.value(), r.rank)).collect();
+    let (chain_ranks, pair_ranks) = (chain_output.history.frames(), pair_output.history.frames());
+    assert_eq!(
+        stored,
+        [
+            (1, "chain", 1, 0.0, chain_ranks[0][1]),
+            (1, "chain", 2, 1.0, chain_ranks[1][1]),
+            (1, "chain", 3, 2.0, chain_ranks[2][1]),
+            (2, "pair", 1, 0.0, pair_ranks[0][1]),
+            (2, "pair", 2, 5.0, pair_ranks[1][1]),
+        ]
+    );
+    assert!(alice.iter().all(|r| r.node == NodeId(1) && r.label == "alice" && r.algorithm == "pagerank"));
+
+    // Index, scenario and time bounds, inclusive at both ends
+    let query = RankQuery { node: Some("2".to_string()), scenario: Some("chain".to_string()), since: Some(Time(1.0)), until: Some(Time(2.0)) };
+    let node_2: Vec<(usize, f64)> = store.query_ranks(&query).unwrap().iter().map(|r| (r.frame, r.rank)).collect();
+    assert_eq!(node_2, [(2, chain_ranks[1][2]), (3, chain_ranks[2][2])]);
+    let every_rank = store.query_ranks(&RankQuery::default()).unwrap();
+    assert_eq!(every_rank.len(), 3 * 3 + 2 * 2);
+
+    let mut csv = Vec::new();
+    let query = RankQuery { node: Some("1".to_string()), scenario: Some("pair".to_string()), since: None, until: Some(Time(0.0)) };
+    write_stored_ranks_csv(&mut csv, &store.query_ranks(&query).unwrap()).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(), format!("run,scenario,algorithm,frame,time,node,label,rank\n2,pair,pagerank,1,0,1,alice,{:.6}\n", pair_ranks[0][1]));
+    drop(store);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,36 @@
+// Temporal PageRank only carries rank along walks whose interactions come in time order
+use trust_flow::graph::Edge;
+use trust_flow::rank::RankingAlgorithm;
+use trust_flow::scenario::Scenario;
+use trust_flow::temporalrank::TemporalPageRank;
+use trust_flow::types::{NodeId, Time};
+
+// 0 → 1 at time 1 and 1 → 2 at the given time, ranked at time 3 with walks starting at 0
+fn chain(later: f64) -> Vec<f64> {
+    let edges = vec![
+        Edge { source: NodeId(0), target: NodeId(1), time_of_creation: Time(1.0), weight: 1.0 },
+        Edge { source: NodeId(1), target: NodeId(2), time_of_creation: Time(later), weight: 1.0 },
+    ];
+    let scenario = Scenario::new("chain", 3, Vec::new(), edges);
+    let graph = scenario.frame_graph(Time(3.0), &scenario.edge_weights(Time(3.0))).unwrap();
+    let temporal = TemporalPageRank::with_stream(&scenario.edges);
+    assert_eq!(temporal.interactions(&graph), if later < 1.0 { [(1, 2), (0, 1)] } else { [(0, 1), (1, 2)] });
+    temporal.rank(&graph, &[1.0, 0.0, 0.0])
+}
+
+fn assert_ranks(actual: &[f64], expected: &[f64]) {
+    assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-12), "{:?} instead of {:?}", actual, expected);
+}
+
+#[test]
+fn walks_follow_interactions_in_time_order() {
+    // With α = 0.85 and β = 0.5, 0 → 1 gives r0 = 0.45 and r1 = 0.85 · 0.45 and leaves
+    // 0.85 · 0.5 · 0.45 of the walks waiting at 1
+    let (r0, r1) = (0.45, 0.3825);
+    // 1 → 2 before 0 → 1 has no walks at 1 to carry
+    assert_ranks(&chain(0.0), &[r0 / (r0 + r1), r1 / (r0 + r1), 0.0]);
+    // After it, it carries the waiting walks on to 2
+    let r2 = 0.85 * 0.19125;
+    let total = r0 + r1 + r2;
+    assert_ranks(&chain(2.0), &[r0 / total, r1 / total, r2 / total]);
+}
@@ -0,0 +1,102 @@
+// The incremental top-K tracker stays within its error bound of the exact ranks of the graph of
+// all events so far, whatever the order and spacing of the events.
+use trust_flow::graph::Edge;
+use trust_flow::random::Rng;
+use trust_flow::rank::{exact_ranks, DanglingPolicy, OutflowNormalization, RankConfig};
+use trust_flow::scenario::Scenario;
+use trust_flow::topk::TopKTracker;
+use trust_flow::types::{NodeId, Time};
+
+const DECAY_CONSTANT: f64 = 0.1;
+
+fn random_edges(seed: u64, num_of_nodes: usize, count: usize) -> Vec<Edge> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|i| Edge { source: NodeId(rng.below(num_of_nodes)), target: NodeId(rng.below(num_of_nodes)), time_of_creation: Time(i as f64 * 0.5), weight: 1.0 + rng.below(3) as f64 })
+        .collect()
+}
+
+// Ranks of the custom variant with current outflow normalization at the time of the last edge
+fn exact(edges: &[Edge], num_of_nodes: usize, experts: &[NodeId], dangling_policy: DanglingPolicy) -> Vec<f64> {
+    let mut scenario = Scenario::new("topk", num_of_nodes, experts.to_vec(), edges.to_vec());
+    scenario.decay_constant = DECAY_CONSTANT;
+    let time = edges.iter().map(|e| e.time_of_creation).fold(Time(0.0), |a, b| if b > a { b } else { a });
+    let graph = scenario.frame_graph(time, &scenario.edge_weights(time)).unwrap();
+    let config = RankConfig::new().outflow_normalization(OutflowNormalization::Current).dangling_policy(dangling_policy);
+    exact_ranks(&graph, &scenario.teleportation_targets(time), &config).unwrap()
+}
+
+fn assert_within_bound(tracker: &TopKTracker, ranks: &[f64], what: &str) {
+    let bound = tracker.error_bound();
+    for (node, rank) in ranks.iter().enumerate() {
+        let estimate = tracker.estimate(NodeId(node));
+        assert!((estimate - rank).abs() <= bound + 1e-12, "{}: node {} estimate {} rank {} bound {}", what, node, estimate, rank, bound);
+    }
+}
+
+#[test]
+fn estimates_stay_within_the_bound_of_the_exact_ranks() {
+    let num_of_nodes = 30;
+    let edges = random_edges(7, num_of_nodes, 200);
+    for dangling_policy in [DanglingPolicy::Uniform, DanglingPolicy::Teleport, DanglingPolicy::Retain] {
+        for experts in [vec![], vec![NodeId(0), NodeId(3)]] {
+            let config = RankConfig::new().dangling_policy(dangling_policy);
+            let weighted: Vec<(NodeId, f64)> = experts.iter().map(|&e| (e, 1.0)).collect();
+            let mut tracker = TopKTracker::new(&config, DECAY_CONSTANT, &weighted, 0, 1e-4);
+            for (i, edge) in edges.iter().enumerate() {
+                tracker.add_edge(edge);
+                assert!(tracker.error_bound() <= 1e-4 * 1.01, "{:?}: bound {} after {} events", dangling_policy, tracker.error_bound(), i + 1);
+                if i % 50 == 49 {
+                    // Nodes appear as events refer to them
+                    let seen = tracker.num_of_nodes();
+                    assert_within_bound(&tracker, &exact(&edges[..=i], seen, &experts, dangling_policy), &format!("{:?}, experts {:?}, {} events", dangling_policy, experts, i + 1));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn events_far_apart_keep_their_relative_weights() {
+    let config = RankConfig::default();
+    let mut tracker = TopKTracker::new(&config, DECAY_CONSTANT, &[(NodeId(0), 1.0)], 4, 1e-6);
+    // Far enough apart that the stored weights are rebased, and the oldest ones no longer count
+    let edges: Vec<Edge> = [(0, 1, 0.0), (1, 2, 0.0), (0, 2, 700.0), (2, 3, 1400.0), (0, 1, 1400.5), (3, 0, 10_000.0)]
+        .iter()
+        .map(|&(source, target, time)| Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(time), weight: 1.0 })
+        .collect();
+    for (i, edge) in edges.iter().enumerate() {
+        tracker.add_edge(edge);
+        assert_within_bound(&tracker, &exact(&edges[..=i], 4, &[NodeId(0)], DanglingPolicy::Uniform), &format!("{} events", i + 1));
+    }
+}
+
+#[test]
+fn the_top_nodes_come_with_intervals() {
+    let config = RankConfig::default();
+    let mut tracker = TopKTracker::new(&config, 0.0, &[], 0, 1e-6);
+    for source in 1..6 {
+        tracker.add_edge(&Edge { source: NodeId(source), target: NodeId(0), time_of_creation: Time(0.0), weight: 1.0 });
+    }
+    tracker.add_edge(&Edge { source: NodeId(0), target: NodeId(5), time_of_creation: Time(0.0), weight: 1.0 });
+    let top = tracker.top(2);
+    assert_eq!(top.iter().map(|t| t.node).collect::<Vec<_>>(), vec![NodeId(0), NodeId(5)]);
+    assert!(top.iter().all(|t| t.lower <= t.rank && t.rank <= t.upper && t.certain), "{:?}", top);
+    // Nodes 1 to 4 rank the same, so no bound can tell which of them is third
+    let top = tracker.top(3);
+    assert!((1..5).contains(&top[2].node.index()), "{:?}", top);
+    assert!(!top[2].certain, "{:?}", top);
+    assert_eq!(tracker.top(10).len(), 6);
+}
+
+#[test]
+fn estimates_and_residuals_hold_all_rank() {
+    let config = RankConfig::default();
+    let mut tracker = TopKTracker::new(&config, DECAY_CONSTANT, &[(NodeId(2), 2.0), (NodeId(5), 1.0)], 0, 1e-5);
+    for edge in random_edges(11, 50, 300) {
+        tracker.add_edge(&edge);
+    }
+    let total: f64 = tracker.estimates().iter().sum();
+    assert!((1.0 - total).abs() <= tracker.error_bound() + 1e-12, "total {} bound {}", total, tracker.error_bound());
+    assert!(tracker.estimates().iter().all(|&e| e >= -tracker.error_bound()), "{:?}", tracker.estimates());
+}
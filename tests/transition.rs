@@ -0,0 +1,42 @@
+// The effective transition matrix of the custom variant: edge fractions by the outflow
+// normalization, and dangling rank where the dangling policy sends it
+use trust_flow::graph::{compile_graph, CompiledGraph, Edge, GraphOptions};
+use trust_flow::rank::{transition_triplets, DanglingPolicy, OutflowNormalization, RankConfig};
+use trust_flow::types::{NodeId, Time};
+
+fn edge(source: usize, target: usize, weight: f64) -> Edge {
+    Edge { source: NodeId(source), target: NodeId(target), time_of_creation: Time(0.0), weight }
+}
+
+// 0 endorses 1 once and 2 three times, 1 endorses 2 and 2 dangles; teleportation goes to 0
+fn graph(weight_of_1_to_2: f64) -> CompiledGraph {
+    let edges = [edge(0, 1, 1.0), edge(0, 2, 3.0), edge(1, 2, weight_of_1_to_2)];
+    compile_graph(&edges, &edges.iter().map(|e| e.weight).collect::<Vec<_>>(), 3, &GraphOptions::default()).unwrap()
+}
+
+fn triplets(graph: &CompiledGraph, config: RankConfig) -> Vec<(usize, usize, f64)> {
+    transition_triplets(graph, &[1.0, 0.0, 0.0], &config)
+}
+
+#[test]
+fn dangling_rows_follow_the_policy() {
+    let edges = [(0, 1, 0.25), (0, 2, 0.75), (1, 2, 1.0)];
+    let third = 1.0 / 3.0;
+    for (policy, dangling) in [
+        (DanglingPolicy::Uniform, vec![(2, 0, third), (2, 1, third), (2, 2, third)]),
+        (DanglingPolicy::Teleport, vec![(2, 0, 1.0)]),
+        (DanglingPolicy::Retain, vec![(2, 2, 1.0)]),
+    ] {
+        let expected: Vec<(usize, usize, f64)> = edges.iter().copied().chain(dangling).collect();
+        assert_eq!(triplets(&graph(1.0), RankConfig::new().dangling_policy(policy)), expected, "{:?}", policy);
+    }
+}
+
+#[test]
+fn weights_below_one_dangle_without_normalization() {
+    // Node 1 passes on half of its rank along its edge of weight 1/2 and the other half dangles
+    let config = RankConfig::new().outflow_normalization(OutflowNormalization::None).dangling_policy(DanglingPolicy::Teleport);
+    assert_eq!(triplets(&graph(0.5), config), [(0, 1, 0.25), (0, 2, 0.75), (1, 0, 0.5), (1, 2, 0.5), (2, 0, 1.0)]);
+    // Normalized by the lifetime weights, the edge takes all of it
+    assert_eq!(triplets(&graph(0.5), RankConfig::new().dangling_policy(DanglingPolicy::Teleport)), [(0, 1, 0.25), (0, 2, 0.75), (1, 2, 1.0), (2, 0, 1.0)]);
+}
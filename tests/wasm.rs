@@ -0,0 +1,33 @@
+// Smoke test of the browser bindings, run with `wasm-pack test --node --features wasm`: a TrustFlow
+// parsed from scenario text or filled edge by edge ranks like the library and rejects invalid edges
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use trust_flow::rank::{PageRankVariant, RankConfig};
+use trust_flow::run::run_scenario;
+use trust_flow::scenario::Scenario;
+use trust_flow::types::Time;
+use trust_flow::wasm::TrustFlow;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const SCENARIO: &str = "nodes 3\nexpert 0\ndecay 0.1\nedge 0 1 1\nedge 1 2 2\n";
+
+#[wasm_bindgen_test]
+fn parsed_scenarios_rank_like_the_library() {
+    let flow = TrustFlow::parse(SCENARIO).unwrap();
+    assert_eq!(flow.num_of_nodes(), 3);
+    let scenario = Scenario::parse(SCENARIO).unwrap();
+    let expected = run_scenario(&scenario, &PageRankVariant { config: RankConfig::new() }, &[Time(1.0), Time(2.0)], &scenario.graph_options);
+    assert_eq!(flow.rank(None, None).unwrap(), expected.history.frames().concat());
+    assert!(flow.viewer_data(Some(vec![1.0]), None).unwrap().contains("\"frames\":[{"));
+}
+
+#[wasm_bindgen_test]
+fn edges_are_checked() {
+    let mut flow = TrustFlow::new(2, vec![0]).unwrap();
+    assert!(flow.add_edge(0, 2, 1.0, 1.0).is_err());
+    assert!(flow.add_edge(0, 1, 1.0, 0.0).is_err());
+    assert!(flow.rank(None, None).is_err(), "no edges, so no frame times");
+    // Before any edge both nodes dangle: r0 = 0.5 · (0.8 + 0.1) + 0.5 / 2
+    flow.add_edge(0, 1, 1.0, 1.0).unwrap();
+    let ranks = flow.rank(Some(vec![0.0]), None).unwrap();
+    assert!((ranks[0] - 0.7).abs() < 1e-12 && (ranks[1] - 0.3).abs() < 1e-12, "{:?}", ranks);
+}
@@ -0,0 +1,117 @@
+// --watch: appended events, rewritten event files and saved scenario files are picked up while the
+// tool runs, and every re-run prints the newest frame's rank changes and rewrites the output files
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+// The watching process, stopped when the test ends, and the rank change lines it prints
+struct Watch {
+    child: Child,
+    lines: Receiver<String>,
+}
+
+impl Watch {
+    fn start(watched: &Path, output: &Path) -> Watch {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_trust-flow"))
+            .arg("--watch")
+            .arg(watched)
+            .args(["--watch-interval", "0.05", "--expert", "0", "--solver", "exact", "--export", "csv", "--output"])
+            .arg(output)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to run trust-flow");
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok).filter(|line| line.starts_with("{\"frame\"")) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Watch { child, lines }
+    }
+
+    fn next_line(&self) -> String {
+        self.lines.recv_timeout(Duration::from_secs(20)).expect("no re-run within 20 seconds")
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// (node, rank) of every change in a line
+fn changes(line: &str) -> Vec<(usize, f64)> {
+    line.split("{\"node\":")
+        .skip(1)
+        .map(|change| {
+            let (node, rest) = change.split_once(",\"rank\":").unwrap();
+            (node.parse().unwrap(), rest.split(',').next().unwrap().parse().unwrap())
+        })
+        .collect()
+}
+
+fn folder(name: &str) -> PathBuf {
+    let folder = env::temp_dir().join(format!("trust-flow-watch-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+#[test]
+fn appended_and_rewritten_events_are_re_run() {
+    let folder = folder("events");
+    let (events, output) = (folder.join("edges.csv"), folder.join("out"));
+    fs::write(&events, "source,target,time\n0,1,1\n").unwrap();
+    let watch = Watch::start(&events, &output);
+
+    // r0 = 0.45 + r1/4 and r1 = 1 - r0 with node 1 dangling
+    let line = watch.next_line();
+    assert!(line.starts_with("{\"frame\":1,\"time\":1,"), "{}", line);
+    let ranks = changes(&line);
+    assert!(ranks.len() == 2 && (ranks[0].1 - 0.56).abs() < 1e-12 && (ranks[1].1 - 0.44).abs() < 1e-12, "{}", line);
+    assert_eq!(fs::read_to_string(output.join("edges/ranks.csv")).unwrap(), "frame,time,node,label,rank\n1,1,0,0,0.560000\n1,1,1,1,0.440000\n");
+
+    // An appended event adds a frame and a node
+    OpenOptions::new().append(true).open(&events).unwrap().write_all(b"1,2,2\n").unwrap();
+    let line = watch.next_line();
+    assert!(line.starts_with("{\"frame\":2,\"time\":2,") && changes(&line).iter().any(|&(node, _)| node == 2), "{}", line);
+    let ranks = fs::read_to_string(output.join("edges/ranks.csv")).unwrap();
+    assert_eq!(ranks.lines().filter(|row| row.starts_with("2,2,")).count(), 3, "{}", ranks);
+
+    // A shorter file is read again from the start
+    fs::write(&events, "source,target,time\n1,0,5\n").unwrap();
+    let line = watch.next_line();
+    assert!(line.starts_with("{\"frame\":1,\"time\":5,"), "{}", line);
+    drop(watch);
+    fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn saved_scenarios_are_re_run() {
+    let folder = folder("scenario");
+    let (scenario, output) = (folder.join("chain.scenario"), folder.join("out"));
+    fs::write(&scenario, "nodes 2\nexpert 0\nedge 0 1 1\n").unwrap();
+    let watch = Watch::start(&scenario, &output);
+    let line = watch.next_line();
+    assert_eq!(changes(&line).iter().map(|&(node, _)| node).collect::<Vec<_>>(), [0, 1], "{}", line);
+
+    // The saved version has a third node, which the re-run ranks
+    thread::sleep(Duration::from_millis(50));
+    fs::write(&scenario, "nodes 3\nexpert 0\nedge 0 1 1\nedge 1 2 2\n").unwrap();
+    let line = watch.next_line();
+    assert!(changes(&line).iter().any(|&(node, _)| node == 2), "{}", line);
+    assert!(fs::read_to_string(output.join("chain/ranks.csv")).unwrap().lines().any(|row| row.contains(",2,2,")));
+    drop(watch);
+    fs::remove_dir_all(&folder).unwrap();
+}